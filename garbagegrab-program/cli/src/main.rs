@@ -0,0 +1,1264 @@
+// Operator CLI for the score program. Connection and signer default to the
+// standard Solana CLI config (`solana config get`), like the `solana` tool itself.
+use std::{error::Error, str::FromStr};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use garbagegrab_client::{self as client, ScoreClient};
+use score_program::{
+    instruction::ConfigUpdate,
+    state::{
+        parse_region, xp_for_level, BadgeMilestone, GameMode, QuestMetric, StakeLock,
+        COSMETIC_COUNT, LEADERBOARD_SIZE, MAX_LEVEL, NO_ACHIEVEMENT, PRIZE_PLACES, STAKE_TIERS,
+    },
+};
+use solana_cli_config::{Config as CliConfig, CONFIG_FILE};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair},
+    signer::Signer,
+};
+
+// Address the score program is deployed at on the Gorbagana testnet
+const DEFAULT_PROGRAM_ID: &str = "6t7gLJEudrC9JNw8ZXSnnwyMgmofdGxtQVQErA67nxhN";
+
+#[derive(Parser)]
+#[command(
+    name = "garbagegrab-cli",
+    about = "Administer the Garbage Grab score program"
+)]
+struct Cli {
+    /// Solana CLI config file [default: ~/.config/solana/cli/config.yml]
+    #[arg(long, global = true)]
+    config: Option<String>,
+
+    /// RPC URL, overriding the config file
+    #[arg(long, short = 'u', global = true)]
+    url: Option<String>,
+
+    /// Signer keypair file, overriding the config file
+    #[arg(long, short = 'k', global = true)]
+    keypair: Option<String>,
+
+    /// Score program address
+    #[arg(long, global = true, default_value = DEFAULT_PROGRAM_ID)]
+    program_id: Pubkey,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create the config account; the signer must be the program's upgrade authority
+    InitConfig {
+        #[arg(long)]
+        max_score: u64,
+        #[arg(long)]
+        max_increment: u64,
+        #[arg(long, default_value_t = 0)]
+        cooldown_secs: u32,
+    },
+    /// Pause all score-mutating instructions
+    Pause,
+    /// Resume score-mutating instructions
+    Unpause,
+    /// Nominate the next admin; the signer must be the admin
+    NominateAdmin { new_admin: Pubkey },
+    /// Take over as admin; the signer must be the nominated admin
+    AcceptAdmin,
+    /// Bar a player from updating their score or submitting to the leaderboard
+    Ban { player: Pubkey },
+    /// Lift a player's ban
+    Unban { player: Pubkey },
+    /// Change the maximum score a player can hold
+    SetMaxScore { max_score: u64 },
+    /// Change the lamport fee players pay to start a ranked run
+    SetEntryFee { lamports: u64 },
+    /// Price the ranked run fee in US cents, converted at a Pyth SOL/USD feed
+    SetUsdEntryFee {
+        cents: u64,
+        price_feed: Pubkey,
+        /// Oldest price accepted, in seconds
+        #[arg(long, default_value_t = 60)]
+        max_age_secs: u32,
+        /// Widest confidence interval accepted, in basis points of the price
+        #[arg(long, default_value_t = 100)]
+        max_conf_bps: u16,
+    },
+    /// Create the reward vault for the reward mint set in the config
+    InitVault,
+    /// Deposit reward tokens from the signer's associated token account into the vault
+    FundVault { amount: u64 },
+    /// Print a player's score account
+    ShowScore {
+        player: Pubkey,
+        #[arg(long, value_enum, default_value_t = Mode::Arcade)]
+        mode: Mode,
+    },
+    /// Print the global leaderboard, or one of the pages below it
+    Leaderboard {
+        /// Page to print; page 0 is the global leaderboard
+        #[arg(long, default_value_t = 0)]
+        page: u8,
+    },
+    /// Follow a player; they need a profile
+    Follow { player: Pubkey },
+    /// Stop following a player
+    Unfollow { player: Pubkey },
+    /// Rank the signer and the players they follow by best score
+    Friends {
+        #[arg(long, value_enum, default_value_t = Mode::Arcade)]
+        mode: Mode,
+    },
+    /// Set the region, a two-letter country code, the signer ranks under; only possible once
+    SetRegion { region: String },
+    /// Correct a player's region; the signer must be the admin
+    CorrectRegion { player: Pubkey, region: String },
+    /// Print the leaderboard of one region
+    RegionalLeaderboard { region: String },
+    /// Print this week's leaderboard
+    WeeklyLeaderboard,
+    /// Publish the base58 level seed of a UTC day's daily challenge; the signer must be the admin
+    PublishDailyChallenge {
+        /// UTC day, unix time divided by 86400
+        date: i64,
+        seed: Hash,
+    },
+    /// Print a UTC day's daily challenge seed and leaderboard
+    DailyChallenge { date: i64 },
+    /// Promote entries from a leaderboard page into the page above it
+    RebalanceLeaderboard {
+        /// Page to promote from
+        page: u8,
+    },
+    /// Pay the prize pool out to the leaderboard's top places once the season has ended
+    DistributePrizes,
+    /// Crank the ended season's prize payout, collecting the treasury's tip
+    CrankFinalize,
+    /// Crank the start of the next season, collecting the treasury's tip
+    CrankRollover,
+    /// Stream season prizes over a vesting period, none unlocking before the cliff; the signer must be the admin
+    SetPrizeVesting { cliff_secs: u64, vesting_secs: u64 },
+    /// Claim what has unlocked of the signer's vesting prize from a season
+    ClaimVested { season_id: u64 },
+    /// Set the staking tiers: the least tokens staked for each and its score multiplier in basis points
+    SetStakeTiers {
+        #[arg(long, value_delimiter = ',')]
+        amounts: Vec<u64>,
+        #[arg(long, value_delimiter = ',')]
+        multipliers_bps: Vec<u16>,
+        /// Seconds staked tokens stay locked after the last stake
+        #[arg(long)]
+        cooldown_secs: u32,
+    },
+    /// Set the lock multipliers for 30, 90 and 180 day stakes in basis points and the early unstake penalty
+    SetStakeLocks {
+        #[arg(long, value_delimiter = ',')]
+        multipliers_bps: Vec<u16>,
+        #[arg(long)]
+        penalty_bps: u16,
+    },
+    /// Stake reward tokens from the signer's associated token account
+    Stake {
+        amount: u64,
+        /// Lock everything staked for a period in exchange for a higher multiplier
+        #[arg(long, value_enum, default_value_t = Lock::None)]
+        lock: Lock,
+    },
+    /// Return staked reward tokens to the signer once the unstake cooldown has passed
+    Unstake { amount: u64 },
+    /// Print a player's stake
+    ShowStake { player: Pubkey },
+    /// Require a minimum player level for bets and match stakes of at least `lamports`; zero lamports removes the limit
+    SetHighStakes {
+        lamports: u64,
+        #[arg(long)]
+        min_level: u16,
+    },
+    /// Limit new score accounts to the players listed one pubkey per line in `allowlist`; the signer must be the admin
+    CloseBeta { allowlist: String },
+    /// Let anyone create a score account again; the signer must be the admin
+    OpenBeta,
+    /// Print a player's XP, level and unlocked cosmetics
+    ShowLevel { player: Pubkey },
+    /// Set what unlocks a cosmetic: a minimum level and optionally an achievement; the signer must be the admin
+    SetCosmetic {
+        cosmetic_id: u8,
+        #[arg(long, default_value_t = 0)]
+        min_level: u16,
+        /// Achievement id the player must hold in the game mode
+        #[arg(long)]
+        achievement: Option<u8>,
+        #[arg(long, value_enum, default_value_t = Mode::Arcade)]
+        mode: Mode,
+    },
+    /// Record a cosmetic a player has earned on their profile; defaults to the signer
+    UnlockCosmetic {
+        cosmetic_id: u8,
+        #[arg(long)]
+        player: Option<Pubkey>,
+    },
+    /// Add a soulbound badge for reaching a best score or streak; the signer must be the admin
+    AddBadge {
+        #[arg(long, value_enum)]
+        milestone: Milestone,
+        #[arg(long)]
+        threshold: u64,
+    },
+    /// Mint a badge the signer has earned in a game mode to their wallet
+    ClaimBadge {
+        badge_id: u8,
+        #[arg(long, value_enum, default_value_t = Mode::Arcade)]
+        mode: Mode,
+    },
+    /// List the badges in the catalog
+    Badges,
+    /// Start counting players, runs, score and house fees program-wide; the signer must be the admin
+    InitGlobalStats,
+    /// Print the program-wide player, run, score and house fee totals
+    Stats,
+    /// Create a quest paying reward tokens once a player reaches the target; the signer must be the admin
+    CreateQuest {
+        quest_id: u64,
+        #[arg(long, value_enum)]
+        metric: Metric,
+        #[arg(long)]
+        target: u64,
+        #[arg(long)]
+        reward: u64,
+    },
+    /// Start tracking the signer's progress towards a quest
+    AcceptQuest { quest_id: u64 },
+    /// Claim a completed quest's reward into the signer's associated token account
+    ClaimQuest { quest_id: u64 },
+    /// Print a quest, and a player's progress towards it if given
+    ShowQuest {
+        quest_id: u64,
+        #[arg(long)]
+        player: Option<Pubkey>,
+    },
+    /// Withdraw lamports from the treasury; the signer must be the admin
+    WithdrawTreasury {
+        lamports: u64,
+        /// Wallet to pay; defaults to the signer
+        #[arg(long)]
+        destination: Option<Pubkey>,
+    },
+    /// Cap treasury withdrawals per epoch, in lamports; zero removes the cap
+    SetTreasuryCap { lamports: u64 },
+    /// Set the house fee on match bet pools, in basis points; the signer must be the admin
+    SetBetFee { bps: u16 },
+    /// Set the house fee on entry fees, wagers and SOL shop purchases, in basis points
+    SetHouseFee { bps: u16 },
+    /// Withdraw collected house fees; the signer must be the admin
+    WithdrawFees {
+        lamports: u64,
+        /// Wallet to pay; defaults to the signer
+        #[arg(long)]
+        destination: Option<Pubkey>,
+    },
+    /// Limit game sessions: score per slot they ran and slots before they expire; zero disables either
+    SetSessionLimits {
+        max_score_per_slot: u64,
+        max_slots: u64,
+        /// Also cap score per second between a session's start and end; zero disables it
+        #[arg(long)]
+        max_score_per_second: Option<u64>,
+    },
+    /// Create a tournament; the signer must be the admin
+    CreateTournament {
+        tournament_id: u64,
+        #[arg(long, default_value_t = 0)]
+        entry_fee: u64,
+        #[arg(long)]
+        max_participants: u8,
+        /// Unix timestamp after which registrations and scores close
+        #[arg(long)]
+        end_ts: i64,
+        /// Verified collection registrants must hold an NFT from
+        #[arg(long)]
+        collection: Option<Pubkey>,
+    },
+    /// Pay out a tournament's collected fees once it has ended
+    FinalizeTournament { tournament_id: u64 },
+    /// Close a score account and refund its rent; the signer must be the player
+    Close {
+        player: Pubkey,
+        #[arg(long, value_enum, default_value_t = Mode::Arcade)]
+        mode: Mode,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Mode {
+    Arcade,
+    Endless,
+    DailyChallenge,
+}
+
+impl From<Mode> for GameMode {
+    fn from(mode: Mode) -> Self {
+        match mode {
+            Mode::Arcade => GameMode::Arcade,
+            Mode::Endless => GameMode::Endless,
+            Mode::DailyChallenge => GameMode::DailyChallenge,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Lock {
+    None,
+    #[value(name = "30d")]
+    Days30,
+    #[value(name = "90d")]
+    Days90,
+    #[value(name = "180d")]
+    Days180,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Milestone {
+    Score,
+    Streak,
+}
+
+impl From<Milestone> for BadgeMilestone {
+    fn from(milestone: Milestone) -> Self {
+        match milestone {
+            Milestone::Score => BadgeMilestone::BestScore,
+            Milestone::Streak => BadgeMilestone::Streak,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Metric {
+    Score,
+    Achievements,
+}
+
+impl From<Metric> for QuestMetric {
+    fn from(metric: Metric) -> Self {
+        match metric {
+            Metric::Score => QuestMetric::ScoreEarned,
+            Metric::Achievements => QuestMetric::AchievementsUnlocked,
+        }
+    }
+}
+
+impl From<Lock> for StakeLock {
+    fn from(lock: Lock) -> Self {
+        match lock {
+            Lock::None => StakeLock::None,
+            Lock::Days30 => StakeLock::Days30,
+            Lock::Days90 => StakeLock::Days90,
+            Lock::Days180 => StakeLock::Days180,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+
+    let cli_config = match cli.config.as_ref().or(CONFIG_FILE.as_ref()) {
+        Some(path) => CliConfig::load(path).unwrap_or_default(),
+        None => CliConfig::default(),
+    };
+    let url = cli.url.unwrap_or(cli_config.json_rpc_url);
+    let keypair_path = cli.keypair.unwrap_or(cli_config.keypair_path);
+    let commitment = CommitmentConfig::from_str(&cli_config.commitment).unwrap_or_default();
+    let rpc = ScoreClient::new(
+        RpcClient::new_with_commitment(url, commitment),
+        cli.program_id,
+    );
+    let program_id = cli.program_id;
+
+    // Only load the signer for commands that send a transaction
+    let signer = || {
+        read_keypair_file(&keypair_path)
+            .map_err(|e| format!("Failed to read keypair {}: {}", keypair_path, e))
+    };
+    let send = |instruction, signer: Keypair| {
+        let rpc = &rpc;
+        async move {
+            let signature = rpc.send(&[instruction], &signer, &[]).await?;
+            println!("Signature: {}", signature);
+            Ok::<_, Box<dyn Error>>(())
+        }
+    };
+
+    match cli.command {
+        Command::InitConfig {
+            max_score,
+            max_increment,
+            cooldown_secs,
+        } => {
+            let admin = signer()?;
+            let ix = client::initialize_config(
+                &program_id,
+                &admin.pubkey(),
+                max_score,
+                max_increment,
+                cooldown_secs,
+            );
+            send(ix, admin).await?;
+        }
+        Command::Pause => {
+            let admin = signer()?;
+            send(client::pause(&program_id, &admin.pubkey()), admin).await?;
+        }
+        Command::Unpause => {
+            let admin = signer()?;
+            send(client::unpause(&program_id, &admin.pubkey()), admin).await?;
+        }
+        Command::NominateAdmin { new_admin } => {
+            let admin = signer()?;
+            send(
+                client::nominate_admin(&program_id, &admin.pubkey(), &new_admin),
+                admin,
+            )
+            .await?;
+        }
+        Command::AcceptAdmin => {
+            let new_admin = signer()?;
+            send(
+                client::accept_admin(&program_id, &new_admin.pubkey()),
+                new_admin,
+            )
+            .await?;
+        }
+        Command::Ban { player } => {
+            let admin = signer()?;
+            send(client::ban(&program_id, &admin.pubkey(), &player), admin).await?;
+        }
+        Command::Unban { player } => {
+            let admin = signer()?;
+            send(client::unban(&program_id, &admin.pubkey(), &player), admin).await?;
+        }
+        Command::SetMaxScore { max_score } => {
+            let admin = signer()?;
+            let update = ConfigUpdate {
+                max_score: Some(max_score),
+                ..ConfigUpdate::default()
+            };
+            send(
+                client::update_config(&program_id, &admin.pubkey(), update),
+                admin,
+            )
+            .await?;
+        }
+        Command::SetEntryFee { lamports } => {
+            let admin = signer()?;
+            let update = ConfigUpdate {
+                ranked_entry_fee: Some(lamports),
+                ..ConfigUpdate::default()
+            };
+            send(
+                client::update_config(&program_id, &admin.pubkey(), update),
+                admin,
+            )
+            .await?;
+        }
+        Command::SetUsdEntryFee {
+            cents,
+            price_feed,
+            max_age_secs,
+            max_conf_bps,
+        } => {
+            let admin = signer()?;
+            let update = ConfigUpdate {
+                ranked_entry_fee_usd_cents: Some(cents),
+                sol_usd_price_feed: Some(price_feed),
+                max_price_age_secs: Some(max_age_secs),
+                max_price_conf_bps: Some(max_conf_bps),
+                ..ConfigUpdate::default()
+            };
+            send(
+                client::update_config(&program_id, &admin.pubkey(), update),
+                admin,
+            )
+            .await?;
+        }
+        Command::InitVault => {
+            let admin = signer()?;
+            let config = rpc
+                .get_config()
+                .await?
+                .ok_or("Config has not been initialized")?;
+            let token_program = rpc.get_mint_token_program(&config.reward_mint).await?;
+            send(
+                client::initialize_vault(
+                    &program_id,
+                    &admin.pubkey(),
+                    &config.reward_mint,
+                    &token_program,
+                ),
+                admin,
+            )
+            .await?;
+        }
+        Command::FundVault { amount } => {
+            let admin = signer()?;
+            let config = rpc
+                .get_config()
+                .await?
+                .ok_or("Config has not been initialized")?;
+            let token_program = rpc.get_mint_token_program(&config.reward_mint).await?;
+            send(
+                client::fund_vault(
+                    &program_id,
+                    &admin.pubkey(),
+                    &config.reward_mint,
+                    &token_program,
+                    amount,
+                ),
+                admin,
+            )
+            .await?;
+        }
+        Command::ShowScore { player, mode } => match rpc.get_score(&player, mode.into()).await? {
+            Some(score) => {
+                println!("Player:      {}", score.player);
+                println!("Game mode:   {:?}", score.game_mode);
+                println!("Score:       {}", score.score);
+                println!("Best score:  {}", score.best_score);
+                println!("Season:      {}", score.season_id);
+                println!("Last update: {}", score.last_update_ts);
+                println!("Nonce:       {}", score.nonce);
+                println!("Version:     {}", score.version);
+                println!("Rewards:     {} claimed", score.rewards_claimed);
+                println!("Achievements: {:#066b}", score.achievements);
+                println!(
+                    "Streak:      {} (last day {})",
+                    score.streak, score.last_play_day
+                );
+                println!("Trophies:    {}", score.trophies);
+                if let Some(top_bps) = rpc
+                    .get_global_stats()
+                    .await?
+                    .and_then(|stats| stats.top_bps(score.best_score))
+                {
+                    println!("Percentile:  top {:.1}%", top_bps as f64 / 100.0);
+                }
+            }
+            None => println!("No {:?} score account for {}", GameMode::from(mode), player),
+        },
+        Command::Leaderboard { page } => match rpc.get_leaderboard_page(page).await? {
+            Some(board) => {
+                let offset = page as usize * LEADERBOARD_SIZE;
+                for (rank, entry) in board.entries().iter().enumerate() {
+                    println!(
+                        "{:>4}. {:<44} {}",
+                        offset + rank + 1,
+                        entry.player,
+                        entry.score()
+                    );
+                }
+            }
+            None => println!("Leaderboard page {} has not been initialized", page),
+        },
+        Command::Follow { player } => {
+            let follower = signer()?;
+            send(
+                client::follow(&program_id, &follower.pubkey(), &player),
+                follower,
+            )
+            .await?;
+        }
+        Command::Unfollow { player } => {
+            let follower = signer()?;
+            send(
+                client::unfollow(&program_id, &follower.pubkey(), &player),
+                follower,
+            )
+            .await?;
+        }
+        Command::Friends { mode } => {
+            let player = signer()?.pubkey();
+            let board = rpc.get_friends_leaderboard(&player, mode.into()).await?;
+            for (rank, (friend, best)) in board.iter().enumerate() {
+                println!("{:>3}. {:<44} {}", rank + 1, friend, best);
+            }
+        }
+        Command::SetRegion { region } => {
+            let player = signer()?;
+            let region = parse_region(&region).ok_or("Region must be a two-letter code")?;
+            send(
+                client::set_region(&program_id, &player.pubkey(), region),
+                player,
+            )
+            .await?;
+        }
+        Command::CorrectRegion { player, region } => {
+            let admin = signer()?;
+            let region = parse_region(&region).ok_or("Region must be a two-letter code")?;
+            send(
+                client::admin_set_region(&program_id, &admin.pubkey(), &player, region),
+                admin,
+            )
+            .await?;
+        }
+        Command::RegionalLeaderboard { region } => {
+            let region = parse_region(&region).ok_or("Region must be a two-letter code")?;
+            match rpc.get_regional_leaderboard(&region).await? {
+                Some(board) => {
+                    for (rank, entry) in board.entries().iter().enumerate() {
+                        println!("{:>3}. {:<44} {}", rank + 1, entry.player, entry.score());
+                    }
+                }
+                None => println!("Leaderboard of this region has not been initialized"),
+            }
+        }
+        Command::WeeklyLeaderboard => match rpc.get_weekly_leaderboard().await? {
+            Some(weekly) => {
+                println!("Week {}", weekly.week());
+                for (rank, entry) in weekly.board.entries().iter().enumerate() {
+                    println!("{:>3}. {:<44} {}", rank + 1, entry.player, entry.score());
+                }
+            }
+            None => println!("Weekly leaderboard has not been initialized"),
+        },
+        Command::PublishDailyChallenge { date, seed } => {
+            let admin = signer()?;
+            send(
+                client::publish_daily_challenge(
+                    &program_id,
+                    &admin.pubkey(),
+                    seed.to_bytes(),
+                    date,
+                ),
+                admin,
+            )
+            .await?;
+        }
+        Command::DailyChallenge { date } => match rpc.get_daily_challenge(date).await? {
+            Some(daily) => {
+                println!(
+                    "Day {} seed {}",
+                    daily.day(),
+                    Hash::new_from_array(daily.seed)
+                );
+                for (rank, entry) in daily.board.entries().iter().enumerate() {
+                    println!("{:>3}. {:<44} {}", rank + 1, entry.player, entry.score());
+                }
+            }
+            None => println!("No daily challenge was published for day {}", date),
+        },
+        Command::RebalanceLeaderboard { page } => {
+            let payer = signer()?;
+            send(
+                client::rebalance_leaderboard_pages(&program_id, page),
+                payer,
+            )
+            .await?;
+        }
+        Command::DistributePrizes => {
+            let payer = signer()?;
+            let board = rpc
+                .get_leaderboard()
+                .await?
+                .ok_or("Leaderboard has not been initialized")?;
+            let winners: Vec<Pubkey> = board
+                .entries()
+                .iter()
+                .take(PRIZE_PLACES)
+                .map(|entry| entry.player)
+                .collect();
+            let vesting_season = vesting_season(&rpc).await?;
+            send(
+                client::distribute_prizes(&program_id, &winners, vesting_season),
+                payer,
+            )
+            .await?;
+        }
+        Command::CrankFinalize => {
+            let cranker = signer()?;
+            let board = rpc
+                .get_leaderboard()
+                .await?
+                .ok_or("Leaderboard has not been initialized")?;
+            let winners: Vec<Pubkey> = board
+                .entries()
+                .iter()
+                .take(PRIZE_PLACES)
+                .map(|entry| entry.player)
+                .collect();
+            let vesting_season = vesting_season(&rpc).await?;
+            send(
+                client::crank_leaderboard_finalize(
+                    &program_id,
+                    &cranker.pubkey(),
+                    &winners,
+                    vesting_season,
+                ),
+                cranker,
+            )
+            .await?;
+        }
+        Command::CrankRollover => {
+            let cranker = signer()?;
+            send(
+                client::crank_season_rollover(&program_id, &cranker.pubkey()),
+                cranker,
+            )
+            .await?;
+        }
+        Command::WithdrawTreasury {
+            lamports,
+            destination,
+        } => {
+            let admin = signer()?;
+            let destination = destination.unwrap_or(admin.pubkey());
+            send(
+                client::withdraw_treasury(&program_id, &admin.pubkey(), &destination, lamports),
+                admin,
+            )
+            .await?;
+        }
+        Command::SetTreasuryCap { lamports } => {
+            let admin = signer()?;
+            let update = ConfigUpdate {
+                treasury_withdraw_cap_lamports: Some(lamports),
+                ..ConfigUpdate::default()
+            };
+            send(
+                client::update_config(&program_id, &admin.pubkey(), update),
+                admin,
+            )
+            .await?;
+        }
+        Command::SetPrizeVesting {
+            cliff_secs,
+            vesting_secs,
+        } => {
+            let admin = signer()?;
+            let update = ConfigUpdate {
+                prize_vesting_cliff_secs: Some(cliff_secs),
+                prize_vesting_secs: Some(vesting_secs),
+                ..ConfigUpdate::default()
+            };
+            send(
+                client::update_config(&program_id, &admin.pubkey(), update),
+                admin,
+            )
+            .await?;
+        }
+        Command::ClaimVested { season_id } => {
+            let beneficiary = signer()?;
+            send(
+                client::claim_vested(&program_id, &beneficiary.pubkey(), season_id),
+                beneficiary,
+            )
+            .await?;
+        }
+        Command::SetStakeTiers {
+            amounts,
+            multipliers_bps,
+            cooldown_secs,
+        } => {
+            let admin = signer()?;
+            let amounts: [u64; STAKE_TIERS] = amounts
+                .try_into()
+                .map_err(|_| format!("Expected {} tier amounts", STAKE_TIERS))?;
+            let multipliers_bps: [u16; STAKE_TIERS] = multipliers_bps
+                .try_into()
+                .map_err(|_| format!("Expected {} tier multipliers", STAKE_TIERS))?;
+            let update = ConfigUpdate {
+                stake_tier_amounts: Some(amounts),
+                stake_tier_bps: Some(multipliers_bps),
+                unstake_cooldown_secs: Some(cooldown_secs),
+                ..ConfigUpdate::default()
+            };
+            send(
+                client::update_config(&program_id, &admin.pubkey(), update),
+                admin,
+            )
+            .await?;
+        }
+        Command::SetStakeLocks {
+            multipliers_bps,
+            penalty_bps,
+        } => {
+            let admin = signer()?;
+            let multipliers_bps: [u16; 3] = multipliers_bps
+                .try_into()
+                .map_err(|_| "Expected 30, 90 and 180 day lock multipliers")?;
+            let update = ConfigUpdate {
+                stake_lock_bps: Some(multipliers_bps),
+                early_unstake_penalty_bps: Some(penalty_bps),
+                ..ConfigUpdate::default()
+            };
+            send(
+                client::update_config(&program_id, &admin.pubkey(), update),
+                admin,
+            )
+            .await?;
+        }
+        Command::Stake { amount, lock } => {
+            let player = signer()?;
+            let config = rpc
+                .get_config()
+                .await?
+                .ok_or("Config has not been initialized")?;
+            let token_program = rpc.get_mint_token_program(&config.reward_mint).await?;
+            send(
+                client::stake(
+                    &program_id,
+                    &player.pubkey(),
+                    &config.reward_mint,
+                    &token_program,
+                    amount,
+                    lock.into(),
+                ),
+                player,
+            )
+            .await?;
+        }
+        Command::Unstake { amount } => {
+            let player = signer()?;
+            let config = rpc
+                .get_config()
+                .await?
+                .ok_or("Config has not been initialized")?;
+            let token_program = rpc.get_mint_token_program(&config.reward_mint).await?;
+            send(
+                client::unstake(
+                    &program_id,
+                    &player.pubkey(),
+                    &config.reward_mint,
+                    &token_program,
+                    amount,
+                ),
+                player,
+            )
+            .await?;
+        }
+        Command::ShowStake { player } => match rpc.get_stake(&player).await? {
+            Some(stake) => {
+                println!("Player:     {}", stake.player);
+                println!("Staked:     {}", stake.amount);
+                println!("Last stake: {}", stake.last_stake_ts);
+                if stake.lock != StakeLock::None {
+                    println!("Lock:       {:?} until {}", stake.lock, stake.lock_end_ts);
+                }
+            }
+            None => println!("{} has not staked", player),
+        },
+        Command::SetHighStakes {
+            lamports,
+            min_level,
+        } => {
+            let admin = signer()?;
+            let update = ConfigUpdate {
+                high_stakes_wager: Some(lamports),
+                high_stakes_min_level: Some(min_level),
+                ..ConfigUpdate::default()
+            };
+            send(
+                client::update_config(&program_id, &admin.pubkey(), update),
+                admin,
+            )
+            .await?;
+        }
+        Command::CloseBeta { allowlist } => {
+            let admin = signer()?;
+            let contents = std::fs::read_to_string(&allowlist)
+                .map_err(|e| format!("Failed to read allowlist {}: {}", allowlist, e))?;
+            let players = contents
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(Pubkey::from_str)
+                .collect::<Result<Vec<_>, _>>()?;
+            let (root, _) = client::allowlist_tree(&players);
+            let update = ConfigUpdate {
+                closed_beta: Some(true),
+                allowlist_root: Some(root),
+                ..ConfigUpdate::default()
+            };
+            send(
+                client::update_config(&program_id, &admin.pubkey(), update),
+                admin,
+            )
+            .await?;
+            println!("Allowlist root: {}", Hash::new_from_array(root));
+        }
+        Command::OpenBeta => {
+            let admin = signer()?;
+            let update = ConfigUpdate {
+                closed_beta: Some(false),
+                ..ConfigUpdate::default()
+            };
+            send(
+                client::update_config(&program_id, &admin.pubkey(), update),
+                admin,
+            )
+            .await?;
+        }
+        Command::ShowLevel { player } => match rpc.get_profile(&player).await? {
+            Some(profile) => {
+                println!("Level: {}", profile.level);
+                if profile.level < MAX_LEVEL {
+                    println!(
+                        "XP:    {} / {}",
+                        profile.xp,
+                        xp_for_level(profile.level + 1)
+                    );
+                } else {
+                    println!("XP:    {}", profile.xp);
+                }
+                let unlocks: Vec<String> = (0..COSMETIC_COUNT)
+                    .filter(|&id| profile.has_unlock(id))
+                    .map(|id| id.to_string())
+                    .collect();
+                println!("Cosmetics: {}", unlocks.join(", "));
+            }
+            None => println!("{} has no profile", player),
+        },
+        Command::SetCosmetic {
+            cosmetic_id,
+            min_level,
+            achievement,
+            mode,
+        } => {
+            let admin = signer()?;
+            send(
+                client::set_cosmetic(
+                    &program_id,
+                    &admin.pubkey(),
+                    cosmetic_id,
+                    min_level,
+                    mode.into(),
+                    achievement.unwrap_or(NO_ACHIEVEMENT),
+                ),
+                admin,
+            )
+            .await?;
+        }
+        Command::UnlockCosmetic {
+            cosmetic_id,
+            player,
+        } => {
+            let payer = signer()?;
+            let player = player.unwrap_or_else(|| payer.pubkey());
+            let cosmetic = rpc
+                .get_cosmetic(cosmetic_id)
+                .await?
+                .ok_or_else(|| format!("Cosmetic {} does not exist", cosmetic_id))?;
+            let achievement_mode = cosmetic.needs_achievement().then_some(cosmetic.game_mode);
+            send(
+                client::unlock_cosmetic(&program_id, &player, cosmetic_id, achievement_mode),
+                payer,
+            )
+            .await?;
+        }
+        Command::AddBadge {
+            milestone,
+            threshold,
+        } => {
+            let admin = signer()?;
+            let mint = Keypair::new();
+            let instruction = client::add_badge(
+                &program_id,
+                &admin.pubkey(),
+                &mint.pubkey(),
+                milestone.into(),
+                threshold,
+            );
+            let signature = rpc.send(&[instruction], &admin, &[&mint]).await?;
+            println!("Badge mint: {}", mint.pubkey());
+            println!("Signature: {}", signature);
+        }
+        Command::ClaimBadge { badge_id, mode } => {
+            let player = signer()?;
+            let catalog = rpc
+                .get_badge_catalog()
+                .await?
+                .ok_or("No badges have been added")?;
+            let badge = catalog
+                .get(badge_id)
+                .ok_or_else(|| format!("Badge {} does not exist", badge_id))?;
+            send(
+                client::claim_badge(
+                    &program_id,
+                    &player.pubkey(),
+                    &badge.mint,
+                    badge_id,
+                    mode.into(),
+                ),
+                player,
+            )
+            .await?;
+        }
+        Command::Badges => match rpc.get_badge_catalog().await? {
+            Some(catalog) => {
+                for (id, badge) in catalog.badges.iter().enumerate() {
+                    println!(
+                        "{:>3}  {:?} {}  mint {}",
+                        id, badge.milestone, badge.threshold, badge.mint
+                    );
+                }
+            }
+            None => println!("No badges have been added"),
+        },
+        Command::InitGlobalStats => {
+            let admin = signer()?;
+            send(
+                client::initialize_global_stats(&program_id, &admin.pubkey()),
+                admin,
+            )
+            .await?;
+        }
+        Command::Stats => match rpc.get_global_stats().await? {
+            Some(stats) => {
+                println!("Players: {}", stats.total_players);
+                println!("Runs:    {}", stats.total_runs);
+                println!("Score:   {}", stats.total_score);
+                println!("Fees:    {} lamports", stats.total_fees);
+                // Bucket b > 0 holds best scores from 2^(b-1) up
+                for (bucket, count) in stats.histogram.iter().enumerate() {
+                    if *count > 0 {
+                        let floor = if bucket == 0 { 0 } else { 1u64 << (bucket - 1) };
+                        println!("  best >= {:>10}: {}", floor, count);
+                    }
+                }
+            }
+            None => println!("Global stats have not been initialized"),
+        },
+        Command::CreateQuest {
+            quest_id,
+            metric,
+            target,
+            reward,
+        } => {
+            let admin = signer()?;
+            send(
+                client::create_quest(
+                    &program_id,
+                    &admin.pubkey(),
+                    quest_id,
+                    metric.into(),
+                    target,
+                    reward,
+                ),
+                admin,
+            )
+            .await?;
+        }
+        Command::AcceptQuest { quest_id } => {
+            let player = signer()?;
+            send(
+                client::accept_quest(&program_id, &player.pubkey(), quest_id),
+                player,
+            )
+            .await?;
+        }
+        Command::ClaimQuest { quest_id } => {
+            let player = signer()?;
+            let config = rpc
+                .get_config()
+                .await?
+                .ok_or("Config has not been initialized")?;
+            let token_program = rpc.get_mint_token_program(&config.reward_mint).await?;
+            send(
+                client::claim_quest_reward(
+                    &program_id,
+                    &player.pubkey(),
+                    &config.reward_mint,
+                    &token_program,
+                    quest_id,
+                ),
+                player,
+            )
+            .await?;
+        }
+        Command::ShowQuest { quest_id, player } => {
+            let quest = rpc
+                .get_quest(quest_id)
+                .await?
+                .ok_or_else(|| format!("Quest {} does not exist", quest_id))?;
+            println!("Quest:    {}", quest.quest_id);
+            println!("Metric:   {:?}", quest.metric);
+            println!("Target:   {}", quest.target);
+            println!("Reward:   {}", quest.reward);
+            if let Some(player) = player {
+                match rpc.get_quest_progress(quest_id, &player).await? {
+                    Some(progress) => {
+                        println!("Progress: {}", progress.progress);
+                        println!("Claimed:  {}", progress.claimed);
+                    }
+                    None => println!("{} has not accepted the quest", player),
+                }
+            }
+        }
+        Command::SetBetFee { bps } => {
+            let admin = signer()?;
+            let update = ConfigUpdate {
+                bet_fee_bps: Some(bps),
+                ..ConfigUpdate::default()
+            };
+            send(
+                client::update_config(&program_id, &admin.pubkey(), update),
+                admin,
+            )
+            .await?;
+        }
+        Command::SetHouseFee { bps } => {
+            let admin = signer()?;
+            let update = ConfigUpdate {
+                fee_bps: Some(bps),
+                ..ConfigUpdate::default()
+            };
+            send(
+                client::update_config(&program_id, &admin.pubkey(), update),
+                admin,
+            )
+            .await?;
+        }
+        Command::WithdrawFees {
+            lamports,
+            destination,
+        } => {
+            let admin = signer()?;
+            let destination = destination.unwrap_or(admin.pubkey());
+            send(
+                client::withdraw_fees(&program_id, &admin.pubkey(), &destination, lamports),
+                admin,
+            )
+            .await?;
+        }
+        Command::SetSessionLimits {
+            max_score_per_slot,
+            max_slots,
+            max_score_per_second,
+        } => {
+            let admin = signer()?;
+            let update = ConfigUpdate {
+                max_score_per_slot: Some(max_score_per_slot),
+                session_max_slots: Some(max_slots),
+                max_score_per_second,
+                ..ConfigUpdate::default()
+            };
+            send(
+                client::update_config(&program_id, &admin.pubkey(), update),
+                admin,
+            )
+            .await?;
+        }
+        Command::CreateTournament {
+            tournament_id,
+            entry_fee,
+            max_participants,
+            end_ts,
+            collection,
+        } => {
+            let admin = signer()?;
+            let ix = client::create_tournament(
+                &program_id,
+                &admin.pubkey(),
+                tournament_id,
+                entry_fee,
+                max_participants,
+                end_ts,
+                &collection.unwrap_or_default(),
+            );
+            send(ix, admin).await?;
+        }
+        Command::FinalizeTournament { tournament_id } => {
+            let payer = signer()?;
+            let tournament = rpc
+                .get_tournament(tournament_id)
+                .await?
+                .ok_or_else(|| format!("Tournament {} does not exist", tournament_id))?;
+            let winners: Vec<Pubkey> = tournament
+                .standings()
+                .iter()
+                .take(PRIZE_PLACES)
+                .map(|entry| entry.player)
+                .collect();
+            let ix = client::finalize_tournament(
+                &program_id,
+                &tournament.creator,
+                tournament_id,
+                &winners,
+            );
+            send(ix, payer).await?;
+        }
+        Command::Close { player, mode } => {
+            let owner = signer()?;
+            if owner.pubkey() != player {
+                return Err(format!(
+                    "Closing {}'s score account needs their keypair, but the signer is {}",
+                    player,
+                    owner.pubkey()
+                )
+                .into());
+            }
+            send(
+                client::close_score_account(&program_id, &player, mode.into()),
+                owner,
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+// Id of the season whose prizes are about to be paid when the config has them
+// vest, and `None` when they are paid out at once
+async fn vesting_season(rpc: &ScoreClient) -> Result<Option<u64>, Box<dyn Error>> {
+    let config = rpc
+        .get_config()
+        .await?
+        .ok_or("Config has not been initialized")?;
+    if config.prize_vesting_secs == 0 {
+        return Ok(None);
+    }
+    let season = rpc
+        .get_season()
+        .await?
+        .ok_or("No season has been started")?;
+    Ok(Some(season.season_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn cli_definition_is_consistent() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn parses_operator_subcommands() {
+        let cli = Cli::try_parse_from(["garbagegrab-cli", "set-max-score", "500"]).unwrap();
+        assert!(matches!(cli.command, Command::SetMaxScore { max_score: 500 }));
+        assert_eq!(cli.program_id, Pubkey::from_str(DEFAULT_PROGRAM_ID).unwrap());
+        assert!(cli.config.is_none() && cli.keypair.is_none());
+
+        let player = Pubkey::new_unique();
+        let cli = Cli::try_parse_from([
+            "garbagegrab-cli",
+            "show-score",
+            &player.to_string(),
+            "--url",
+            "http://localhost:8899",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::ShowScore { player: p, mode: Mode::Arcade } if p == player
+        ));
+        assert_eq!(cli.url.as_deref(), Some("http://localhost:8899"));
+
+        assert!(Cli::try_parse_from(["garbagegrab-cli", "set-max-score"]).is_err());
+        assert!(Cli::try_parse_from(["garbagegrab-cli", "show-score", "not-a-key"]).is_err());
+    }
+}
@@ -0,0 +1,2755 @@
+// Instruction builders for the score program. Each builder derives the PDAs the
+// instruction touches and returns an `Instruction` ready to add to a transaction.
+use mpl_token_metadata::accounts::{MasterEdition, Metadata};
+use score_program::{
+    instruction::{ConfigUpdate, GarbageGrabInstruction},
+    merkle,
+    pda::{
+        AdminSetPda, AirdropClaimsPda, AirdropPda, BadgeAuthorityPda, BadgeCatalogPda, BanListPda,
+        BetPda, BetPoolPda, CatalogPda, ConfigPda, CosmeticPda, DailyChallengePda,
+        DailySubmissionPda, FeeVaultPda, FollowPda, GameSessionPda, GlobalStatsPda,
+        GuildLeaderboardPda, GuildMemberPda, GuildPda, HistoryPda, InventoryPda, LeaderboardPda,
+        LobbyPda, LootRequestPda, MatchPda, NameRecordPda, PrizePoolPda, ProfilePda, ProposalPda,
+        QuestPda, QuestProgressPda, RegionalLeaderboardPda, ReportPda, ScorePda, ScoreTreePda,
+        SeasonPda, SessionPda, StakePda, StakeVaultPda, TeamPda, TournamentPda, TreasuryPda,
+        TrophyAuthorityPda, VaultPda, VestingPda, WeeklyLeaderboardPda,
+    },
+    state::{
+        normalize_profile_name, sort_team_members, BadgeMilestone, CompressedScore, GameMode,
+        ItemDefinition, ProposalAccount, QuestMetric, ShopCurrency, StakeLock, LEADERBOARD_PAGES,
+        PROFILE_NAME_LEN,
+    },
+};
+use solana_program::{
+    bpf_loader_upgradeable,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+use spl_account_compression::{state::CONCURRENT_MERKLE_TREE_HEADER_SIZE_V1, ConcurrentMerkleTree};
+use spl_associated_token_account::{
+    get_associated_token_address, get_associated_token_address_with_program_id,
+};
+
+pub mod rpc;
+
+pub use rpc::{ScoreClient, ScoreClientError};
+pub use score_program::{attestation::score_attestation_message, state::ScoreAccount};
+
+// Address of a player's score account for one game mode
+pub fn find_score_address(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    game_mode: GameMode,
+) -> (Pubkey, u8) {
+    ScorePda::find(program_id, player, game_mode)
+}
+
+// Address of the global leaderboard
+pub fn find_leaderboard_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    LeaderboardPda::find(program_id)
+}
+
+// Address of leaderboard page `page`; page 0 is the global leaderboard
+pub fn find_leaderboard_page_address(program_id: &Pubkey, page: u8) -> (Pubkey, u8) {
+    if page == 0 {
+        return LeaderboardPda::find(program_id);
+    }
+    LeaderboardPda::find_page(program_id, page)
+}
+
+// Address of the current season account
+pub fn find_season_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    SeasonPda::find(program_id)
+}
+
+// Address of the program config
+pub fn find_config_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    ConfigPda::find(program_id)
+}
+
+// Address of the list of banned players
+pub fn find_ban_list_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    BanListPda::find(program_id)
+}
+
+// Address of `reporter`'s report on a player's score in one game mode
+pub fn find_report_address(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    reporter: &Pubkey,
+    game_mode: GameMode,
+) -> (Pubkey, u8) {
+    ReportPda::find(program_id, player, reporter, game_mode)
+}
+
+// Address of the m-of-n admin set
+pub fn find_admin_set_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    AdminSetPda::find(program_id)
+}
+
+// Address of an admin set proposal
+pub fn find_proposal_address(program_id: &Pubkey, proposal_id: u64) -> (Pubkey, u8) {
+    ProposalPda::find(program_id, proposal_id)
+}
+
+// Address of a player's pending loot drop
+pub fn find_loot_request_address(program_id: &Pubkey, player: &Pubkey) -> (Pubkey, u8) {
+    LootRequestPda::find(program_id, player)
+}
+
+// Address of a player's loot inventory
+pub fn find_inventory_address(program_id: &Pubkey, player: &Pubkey) -> (Pubkey, u8) {
+    InventoryPda::find(program_id, player)
+}
+
+// Address of the item catalog
+pub fn find_catalog_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    CatalogPda::find(program_id)
+}
+
+// Address of the treasury, a system account that collects shop payments
+pub fn find_treasury_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    TreasuryPda::find(program_id)
+}
+
+// Address of the fee vault that collects the house fee
+pub fn find_fee_vault_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    FeeVaultPda::find(program_id)
+}
+
+// Address of a player's profile
+pub fn find_profile_address(program_id: &Pubkey, player: &Pubkey) -> (Pubkey, u8) {
+    ProfilePda::find(program_id, player)
+}
+
+// Address of the edge recording that `follower` follows `followed`
+pub fn find_follow_address(
+    program_id: &Pubkey,
+    follower: &Pubkey,
+    followed: &Pubkey,
+) -> (Pubkey, u8) {
+    FollowPda::find(program_id, follower, followed)
+}
+
+// Address of the record claiming a display name. Valid names are keyed by their
+// lowercase form; anything else maps to an address the program will reject.
+pub fn find_name_record_address(
+    program_id: &Pubkey,
+    name: &[u8; PROFILE_NAME_LEN],
+) -> (Pubkey, u8) {
+    let normalized = normalize_profile_name(name).unwrap_or(*name);
+    NameRecordPda::find(program_id, &normalized)
+}
+
+// Address of the PDA that is the mint and update authority of every trophy NFT
+pub fn find_trophy_authority_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    TrophyAuthorityPda::find(program_id)
+}
+
+// Address of the reward vault token account, which is its own transfer authority
+pub fn find_vault_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    VaultPda::find(program_id)
+}
+
+// Address of the prize pool that collects ranked run entry fees
+pub fn find_prize_pool_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    PrizePoolPda::find(program_id)
+}
+
+// Address of the account recording a player's stake
+pub fn find_stake_address(program_id: &Pubkey, player: &Pubkey) -> (Pubkey, u8) {
+    StakePda::find(program_id, player)
+}
+
+// Address of the token account holding a player's stake, which is its own transfer authority
+pub fn find_stake_vault_address(program_id: &Pubkey, player: &Pubkey) -> (Pubkey, u8) {
+    StakeVaultPda::find(program_id, player)
+}
+
+// Address of `winner`'s vesting prize from season `season_id`
+pub fn find_vesting_address(program_id: &Pubkey, season_id: u64, winner: &Pubkey) -> (Pubkey, u8) {
+    VestingPda::find(program_id, season_id, winner)
+}
+
+// Address of the wager match `match_id` between two players; the order of the players matters
+pub fn find_match_address(
+    program_id: &Pubkey,
+    player_a: &Pubkey,
+    player_b: &Pubkey,
+    match_id: u64,
+) -> (Pubkey, u8) {
+    MatchPda::find(program_id, player_a, player_b, match_id)
+}
+
+// Address of the pool holding the spectator bets on a match
+pub fn find_bet_pool_address(program_id: &Pubkey, match_account: &Pubkey) -> (Pubkey, u8) {
+    BetPoolPda::find(program_id, match_account)
+}
+
+// Address of `bettor`'s bet on a match
+pub fn find_bet_address(
+    program_id: &Pubkey,
+    match_account: &Pubkey,
+    bettor: &Pubkey,
+) -> (Pubkey, u8) {
+    BetPda::find(program_id, match_account, bettor)
+}
+
+// Address of the matchmaking lobby `host` opened
+pub fn find_lobby_address(program_id: &Pubkey, host: &Pubkey) -> (Pubkey, u8) {
+    LobbyPda::find(program_id, host)
+}
+
+pub fn find_tournament_address(program_id: &Pubkey, tournament_id: u64) -> (Pubkey, u8) {
+    TournamentPda::find(program_id, tournament_id)
+}
+
+// Address of a guild
+pub fn find_guild_address(program_id: &Pubkey, guild_id: u64) -> (Pubkey, u8) {
+    GuildPda::find(program_id, guild_id)
+}
+
+// Address of a player's guild membership
+pub fn find_guild_member_address(program_id: &Pubkey, player: &Pubkey) -> (Pubkey, u8) {
+    GuildMemberPda::find(program_id, player)
+}
+
+// Address of the guild leaderboard
+pub fn find_guild_leaderboard_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    GuildLeaderboardPda::find(program_id)
+}
+
+// Address of the leaderboard of one region
+pub fn find_regional_leaderboard_address(program_id: &Pubkey, region: &[u8; 2]) -> (Pubkey, u8) {
+    RegionalLeaderboardPda::find(program_id, region)
+}
+
+// Address of the weekly leaderboard
+pub fn find_weekly_leaderboard_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    WeeklyLeaderboardPda::find(program_id)
+}
+
+// Address of an airdrop
+pub fn find_airdrop_address(program_id: &Pubkey, airdrop_id: u64) -> (Pubkey, u8) {
+    AirdropPda::find(program_id, airdrop_id)
+}
+
+// Address of an airdrop's claimed bitmap
+pub fn find_airdrop_claims_address(program_id: &Pubkey, airdrop_id: u64) -> (Pubkey, u8) {
+    AirdropClaimsPda::find(program_id, airdrop_id)
+}
+
+// Address of the score tree that is the authority of a compressed score Merkle tree
+pub fn find_score_tree_address(program_id: &Pubkey, merkle_tree: &Pubkey) -> (Pubkey, u8) {
+    ScoreTreePda::find(program_id, merkle_tree)
+}
+
+// Address of quest `quest_id`
+pub fn find_quest_address(program_id: &Pubkey, quest_id: u64) -> (Pubkey, u8) {
+    QuestPda::find(program_id, quest_id)
+}
+
+// Address of a player's progress towards quest `quest_id`
+pub fn find_quest_progress_address(
+    program_id: &Pubkey,
+    quest_id: u64,
+    player: &Pubkey,
+) -> (Pubkey, u8) {
+    QuestProgressPda::find(program_id, quest_id, player)
+}
+
+// Address of the daily challenge for UTC day `day`
+pub fn find_daily_challenge_address(program_id: &Pubkey, day: i64) -> (Pubkey, u8) {
+    DailyChallengePda::find(program_id, day)
+}
+
+// Address of a player's submission to the daily challenge of UTC day `day`
+pub fn find_daily_submission_address(
+    program_id: &Pubkey,
+    day: i64,
+    player: &Pubkey,
+) -> (Pubkey, u8) {
+    DailySubmissionPda::find(program_id, day, player)
+}
+
+// Address of cosmetic `cosmetic_id`
+pub fn find_cosmetic_address(program_id: &Pubkey, cosmetic_id: u8) -> (Pubkey, u8) {
+    CosmeticPda::find(program_id, cosmetic_id)
+}
+
+// Address of the admin-managed list of badge mints
+pub fn find_badge_catalog_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    BadgeCatalogPda::find(program_id)
+}
+
+// Address of the PDA holding mint authority over every badge mint
+pub fn find_badge_authority_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    BadgeAuthorityPda::find(program_id)
+}
+
+// Address of the program-wide player, run, score and fee totals
+pub fn find_global_stats_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    GlobalStatsPda::find(program_id)
+}
+
+// Bytes to allocate for a compressed score Merkle tree without a canopy. The
+// depth and buffer size must be a pair the compression program supports.
+pub fn score_tree_space<const MAX_DEPTH: usize, const MAX_BUFFER_SIZE: usize>() -> usize {
+    CONCURRENT_MERKLE_TREE_HEADER_SIZE_V1
+        + std::mem::size_of::<ConcurrentMerkleTree<MAX_DEPTH, MAX_BUFFER_SIZE>>()
+}
+
+// Address of a player's run history for a game mode
+pub fn find_history_address(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    game_mode: GameMode,
+) -> (Pubkey, u8) {
+    HistoryPda::find(program_id, player, game_mode)
+}
+
+// Address of the token authorizing `session_key` for the player
+pub fn find_session_address(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    session_key: &Pubkey,
+) -> (Pubkey, u8) {
+    SessionPda::find(program_id, player, session_key)
+}
+
+// Address of a co-op team's shared score account. Members may be given in any
+// order; panics unless there are 2-4 distinct members.
+pub fn find_team_address(program_id: &Pubkey, members: &[Pubkey]) -> (Pubkey, u8) {
+    let members = sort_team_members(members).expect("teams have 2-4 distinct members");
+    TeamPda::find(program_id, &members)
+}
+
+// Address of the program data account holding the program's upgrade authority
+pub fn find_program_data_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id()).0
+}
+
+fn instruction(
+    program_id: &Pubkey,
+    data: GarbageGrabInstruction,
+    accounts: Vec<AccountMeta>,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: data.pack(),
+    }
+}
+
+// During a closed beta `allowlist_proof` comes from `allowlist_tree`; pass an
+// empty proof once the beta is open
+pub fn initialize_score(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    game_mode: GameMode,
+    referrer: Option<Pubkey>,
+    allowlist_proof: Vec<[u8; 32]>,
+) -> Instruction {
+    let (score, _) = find_score_address(program_id, player, game_mode);
+    instruction(
+        program_id,
+        GarbageGrabInstruction::Initialize {
+            game_mode,
+            referrer,
+            allowlist_proof,
+        },
+        vec![
+            AccountMeta::new(score, false),
+            AccountMeta::new(*player, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        ],
+    )
+}
+
+// Merkle root over a closed beta allowlist and each listed player's proof, in
+// the order given
+pub fn allowlist_tree(players: &[Pubkey]) -> ([u8; 32], Vec<Vec<[u8; 32]>>) {
+    let leaves: Vec<[u8; 32]> = players.iter().map(merkle::allowlist_leaf).collect();
+    merkle::build_tree(&leaves)
+}
+
+pub fn update_score(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    game_mode: GameMode,
+    increment: u64,
+) -> Instruction {
+    let (score, _) = find_score_address(program_id, player, game_mode);
+    instruction(
+        program_id,
+        GarbageGrabInstruction::UpdateScore {
+            game_mode,
+            increment,
+        },
+        vec![
+            AccountMeta::new(score, false),
+            AccountMeta::new_readonly(*player, true),
+            AccountMeta::new_readonly(find_season_address(program_id).0, false),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(find_ban_list_address(program_id).0, false),
+        ],
+    )
+}
+
+// UpdateScore signed by the player's approved delegate rather than the player
+pub fn update_score_as_delegate(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    delegate: &Pubkey,
+    game_mode: GameMode,
+    increment: u64,
+) -> Instruction {
+    let (score, _) = find_score_address(program_id, player, game_mode);
+    instruction(
+        program_id,
+        GarbageGrabInstruction::UpdateScoreAsDelegate {
+            game_mode,
+            increment,
+        },
+        vec![
+            AccountMeta::new(score, false),
+            AccountMeta::new_readonly(*player, false),
+            AccountMeta::new_readonly(*delegate, true),
+            AccountMeta::new_readonly(find_season_address(program_id).0, false),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(find_ban_list_address(program_id).0, false),
+        ],
+    )
+}
+
+// UpdateScore signed by a session key the player authorized with create_session
+pub fn update_score_with_session(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    session_key: &Pubkey,
+    game_mode: GameMode,
+    increment: u64,
+) -> Instruction {
+    let (score, _) = find_score_address(program_id, player, game_mode);
+    instruction(
+        program_id,
+        GarbageGrabInstruction::UpdateScoreWithSession {
+            game_mode,
+            increment,
+        },
+        vec![
+            AccountMeta::new(score, false),
+            AccountMeta::new_readonly(*player, false),
+            AccountMeta::new_readonly(*session_key, true),
+            AccountMeta::new_readonly(
+                find_session_address(program_id, player, session_key).0,
+                false,
+            ),
+            AccountMeta::new_readonly(find_season_address(program_id).0, false),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(find_ban_list_address(program_id).0, false),
+        ],
+    )
+}
+
+pub fn create_session(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    session_key: &Pubkey,
+    expiry_slot: u64,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::CreateSession {
+            session_key: *session_key,
+            expiry_slot,
+        },
+        vec![
+            AccountMeta::new(
+                find_session_address(program_id, player, session_key).0,
+                false,
+            ),
+            AccountMeta::new(*player, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn revoke_session(program_id: &Pubkey, player: &Pubkey, session_key: &Pubkey) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::RevokeSession,
+        vec![
+            AccountMeta::new(
+                find_session_address(program_id, player, session_key).0,
+                false,
+            ),
+            AccountMeta::new(*player, true),
+        ],
+    )
+}
+
+pub fn approve_delegate(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    game_mode: GameMode,
+    delegate: &Pubkey,
+) -> Instruction {
+    let (score, _) = find_score_address(program_id, player, game_mode);
+    instruction(
+        program_id,
+        GarbageGrabInstruction::ApproveDelegate {
+            game_mode,
+            delegate: *delegate,
+        },
+        vec![
+            AccountMeta::new(score, false),
+            AccountMeta::new_readonly(*player, true),
+        ],
+    )
+}
+
+pub fn revoke_delegate(program_id: &Pubkey, player: &Pubkey, game_mode: GameMode) -> Instruction {
+    let (score, _) = find_score_address(program_id, player, game_mode);
+    instruction(
+        program_id,
+        GarbageGrabInstruction::RevokeDelegate { game_mode },
+        vec![
+            AccountMeta::new(score, false),
+            AccountMeta::new_readonly(*player, true),
+        ],
+    )
+}
+
+pub fn reset_score(program_id: &Pubkey, player: &Pubkey, game_mode: GameMode) -> Instruction {
+    let (score, _) = find_score_address(program_id, player, game_mode);
+    instruction(
+        program_id,
+        GarbageGrabInstruction::ResetScore { game_mode },
+        vec![
+            AccountMeta::new(score, false),
+            AccountMeta::new_readonly(*player, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        ],
+    )
+}
+
+pub fn close_score_account(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    game_mode: GameMode,
+) -> Instruction {
+    let (score, _) = find_score_address(program_id, player, game_mode);
+    instruction(
+        program_id,
+        GarbageGrabInstruction::CloseScoreAccount { game_mode },
+        vec![
+            AccountMeta::new(score, false),
+            AccountMeta::new(*player, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn submit_run(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    game_mode: GameMode,
+    run_score: u64,
+    run_hash: [u8; 32],
+) -> Instruction {
+    let (score, _) = find_score_address(program_id, player, game_mode);
+    instruction(
+        program_id,
+        GarbageGrabInstruction::SubmitRun {
+            game_mode,
+            run_score,
+            run_hash,
+        },
+        vec![
+            AccountMeta::new(score, false),
+            AccountMeta::new_readonly(*player, true),
+            AccountMeta::new_readonly(find_season_address(program_id).0, false),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(find_ban_list_address(program_id).0, false),
+        ],
+    )
+}
+
+// Address of the game session the game server keyed with `seed`
+pub fn find_game_session_address(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    seed: &[u8; 32],
+) -> (Pubkey, u8) {
+    GameSessionPda::find(program_id, player, seed)
+}
+
+// Signed by the player and the game server, which picks `seed`
+pub fn start_session(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    game_server: &Pubkey,
+    game_mode: GameMode,
+    seed: [u8; 32],
+) -> Instruction {
+    let (score, _) = find_score_address(program_id, player, game_mode);
+    instruction(
+        program_id,
+        GarbageGrabInstruction::StartSession { game_mode, seed },
+        vec![
+            AccountMeta::new(
+                find_game_session_address(program_id, player, &seed).0,
+                false,
+            ),
+            AccountMeta::new_readonly(score, false),
+            AccountMeta::new(*player, true),
+            AccountMeta::new_readonly(*game_server, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Signed by the player and the game server, which vouches for `score`
+pub fn end_session(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    game_server: &Pubkey,
+    game_mode: GameMode,
+    seed: [u8; 32],
+    score: u64,
+) -> Instruction {
+    let (score_account, _) = find_score_address(program_id, player, game_mode);
+    instruction(
+        program_id,
+        GarbageGrabInstruction::EndSession { score },
+        vec![
+            AccountMeta::new(
+                find_game_session_address(program_id, player, &seed).0,
+                false,
+            ),
+            AccountMeta::new(score_account, false),
+            AccountMeta::new(*player, true),
+            AccountMeta::new_readonly(*game_server, true),
+            AccountMeta::new_readonly(find_season_address(program_id).0, false),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(find_ban_list_address(program_id).0, false),
+        ],
+    )
+}
+
+// Optional account to append to SubmitRun so the run is kept in the player's history
+pub fn history_account(program_id: &Pubkey, player: &Pubkey, game_mode: GameMode) -> AccountMeta {
+    AccountMeta::new(find_history_address(program_id, player, game_mode).0, false)
+}
+
+pub fn initialize_history(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    game_mode: GameMode,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::InitializeHistory { game_mode },
+        vec![
+            history_account(program_id, player, game_mode),
+            AccountMeta::new(*player, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Grow one of the player's score, profile or history accounts to `new_len` bytes
+pub fn realloc(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    player: &Pubkey,
+    new_len: u32,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::Realloc { new_len },
+        vec![
+            AccountMeta::new(*account, false),
+            AccountMeta::new(*player, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Bar a player from updating their score or submitting to the leaderboard
+pub fn ban(program_id: &Pubkey, admin: &Pubkey, player: &Pubkey) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::Ban { player: *player },
+        vec![
+            AccountMeta::new(find_ban_list_address(program_id).0, false),
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn unban(program_id: &Pubkey, admin: &Pubkey, player: &Pubkey) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::Unban { player: *player },
+        vec![
+            AccountMeta::new(find_ban_list_address(program_id).0, false),
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        ],
+    )
+}
+
+// Report a player's score as cheated, putting up the report bond
+pub fn report_player(
+    program_id: &Pubkey,
+    reporter: &Pubkey,
+    player: &Pubkey,
+    game_mode: GameMode,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::ReportPlayer { game_mode },
+        vec![
+            AccountMeta::new(
+                find_report_address(program_id, player, reporter, game_mode).0,
+                false,
+            ),
+            AccountMeta::new(*reporter, true),
+            AccountMeta::new_readonly(*player, false),
+            AccountMeta::new_readonly(find_score_address(program_id, player, game_mode).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Uphold a report, slashing the player's score by `slash`, or reject it
+pub fn resolve_report(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    reporter: &Pubkey,
+    player: &Pubkey,
+    game_mode: GameMode,
+    upheld: bool,
+    slash: u64,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::ResolveReport { upheld, slash },
+        vec![
+            AccountMeta::new(
+                find_report_address(program_id, player, reporter, game_mode).0,
+                false,
+            ),
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new(find_score_address(program_id, player, game_mode).0, false),
+            AccountMeta::new(*reporter, false),
+            AccountMeta::new(find_prize_pool_address(program_id).0, false),
+        ],
+    )
+}
+
+// Pay for a loot drop decided by `randomness`, a Switchboard randomness account
+// committed in the previous slot
+pub fn request_loot(program_id: &Pubkey, player: &Pubkey, randomness: &Pubkey) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::RequestLoot,
+        vec![
+            AccountMeta::new(find_loot_request_address(program_id, player).0, false),
+            AccountMeta::new(find_inventory_address(program_id, player).0, false),
+            AccountMeta::new(*player, true),
+            AccountMeta::new_readonly(*randomness, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Settle a player's loot drop. Send it in the same transaction as the
+// Switchboard reveal of `randomness`, since the value only counts in that slot.
+pub fn fulfill_loot(program_id: &Pubkey, player: &Pubkey, randomness: &Pubkey) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::FulfillLoot,
+        vec![
+            AccountMeta::new(find_loot_request_address(program_id, player).0, false),
+            AccountMeta::new(find_inventory_address(program_id, player).0, false),
+            AccountMeta::new(*player, false),
+            AccountMeta::new_readonly(*randomness, false),
+            AccountMeta::new(find_prize_pool_address(program_id).0, false),
+        ],
+    )
+}
+
+pub fn set_catalog_item(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    definition: ItemDefinition,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::SetCatalogItem { definition },
+        vec![
+            AccountMeta::new(find_catalog_address(program_id).0, false),
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn grant_item(
+    program_id: &Pubkey,
+    game_server: &Pubkey,
+    player: &Pubkey,
+    item_id: u32,
+    quantity: u32,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::GrantItem { item_id, quantity },
+        vec![
+            AccountMeta::new(find_inventory_address(program_id, player).0, false),
+            AccountMeta::new_readonly(*player, false),
+            AccountMeta::new(*game_server, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(find_catalog_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Use up one item, applying its effect to the player's score account in `game_mode`
+pub fn consume_item(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    game_mode: GameMode,
+    item_id: u32,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::ConsumeItem { game_mode, item_id },
+        vec![
+            AccountMeta::new(find_inventory_address(program_id, player).0, false),
+            AccountMeta::new_readonly(*player, true),
+            AccountMeta::new_readonly(find_catalog_address(program_id).0, false),
+            AccountMeta::new(find_score_address(program_id, player, game_mode).0, false),
+        ],
+    )
+}
+
+// Buy `quantity` of a catalog item with SOL
+pub fn purchase_item_with_sol(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    item_id: u32,
+    quantity: u32,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::PurchaseItem {
+            item_id,
+            quantity,
+            currency: ShopCurrency::Sol,
+        },
+        purchase_accounts(program_id, player),
+    )
+}
+
+// Buy `quantity` of a catalog item with the config's `shop_mint`, paid from the
+// player's associated token account into the treasury's, which must exist.
+// `token_program` is the SPL Token or Token-2022 program owning the mint; a mint
+// with a transfer hook needs the hook's accounts appended.
+pub fn purchase_item_with_tokens(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    shop_mint: &Pubkey,
+    token_program: &Pubkey,
+    item_id: u32,
+    quantity: u32,
+) -> Instruction {
+    let mut accounts = purchase_accounts(program_id, player);
+    let treasury = find_treasury_address(program_id).0;
+    accounts.extend([
+        AccountMeta::new(
+            get_associated_token_address_with_program_id(player, shop_mint, token_program),
+            false,
+        ),
+        AccountMeta::new(
+            get_associated_token_address_with_program_id(&treasury, shop_mint, token_program),
+            false,
+        ),
+        AccountMeta::new_readonly(*shop_mint, false),
+        AccountMeta::new_readonly(*token_program, false),
+    ]);
+    instruction(
+        program_id,
+        GarbageGrabInstruction::PurchaseItem {
+            item_id,
+            quantity,
+            currency: ShopCurrency::Token,
+        },
+        accounts,
+    )
+}
+
+// Accounts every purchase takes, before the token payment accounts
+fn purchase_accounts(program_id: &Pubkey, player: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(find_inventory_address(program_id, player).0, false),
+        AccountMeta::new(*player, true),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        AccountMeta::new_readonly(find_catalog_address(program_id).0, false),
+        AccountMeta::new(find_treasury_address(program_id).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(find_fee_vault_address(program_id).0, false),
+    ]
+}
+
+// Burn the config's continue price in `reward_mint` from the player's
+// associated token account to resume the current run
+pub fn buy_continue(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    reward_mint: &Pubkey,
+    token_program: &Pubkey,
+    game_mode: GameMode,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::Continue { game_mode },
+        vec![
+            AccountMeta::new(find_score_address(program_id, player, game_mode).0, false),
+            AccountMeta::new_readonly(*player, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new(
+                get_associated_token_address_with_program_id(player, reward_mint, token_program),
+                false,
+            ),
+            AccountMeta::new(*reward_mint, false),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+    )
+}
+
+// Make a `threshold`-of-`members` admin set the admin, or replace its members
+pub fn set_admin_set(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    threshold: u8,
+    members: Vec<Pubkey>,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::SetAdminSet { threshold, members },
+        vec![
+            AccountMeta::new(find_admin_set_address(program_id).0, false),
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Propose running `proposed`, an admin instruction built with the admin set
+// address as its admin, as proposal `proposal_id` (the set's proposal count)
+pub fn propose(
+    program_id: &Pubkey,
+    proposer: &Pubkey,
+    proposal_id: u64,
+    proposed: &Instruction,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::Propose {
+            accounts: proposed
+                .accounts
+                .iter()
+                .map(|meta| ProposalAccount {
+                    pubkey: meta.pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: proposed.data.clone(),
+        },
+        vec![
+            AccountMeta::new(find_proposal_address(program_id, proposal_id).0, false),
+            AccountMeta::new(find_admin_set_address(program_id).0, false),
+            AccountMeta::new(*proposer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn approve(program_id: &Pubkey, member: &Pubkey, proposal_id: u64) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::Approve,
+        vec![
+            AccountMeta::new(find_proposal_address(program_id, proposal_id).0, false),
+            AccountMeta::new_readonly(find_admin_set_address(program_id).0, false),
+            AccountMeta::new_readonly(*member, true),
+        ],
+    )
+}
+
+// Execute proposal `proposal_id`, passing on the accounts of the instruction it
+// was proposed with. The admin set signs inside the program, not the transaction.
+pub fn execute_proposal(
+    program_id: &Pubkey,
+    proposer: &Pubkey,
+    proposal_id: u64,
+    proposed: &Instruction,
+) -> Instruction {
+    let admin_set = find_admin_set_address(program_id).0;
+    let mut accounts = vec![
+        AccountMeta::new(find_proposal_address(program_id, proposal_id).0, false),
+        AccountMeta::new_readonly(admin_set, false),
+        AccountMeta::new(*proposer, false),
+        AccountMeta::new_readonly(*program_id, false),
+    ];
+    accounts.extend(proposed.accounts.iter().map(|meta| AccountMeta {
+        is_signer: meta.is_signer && meta.pubkey != admin_set,
+        ..meta.clone()
+    }));
+    instruction(
+        program_id,
+        GarbageGrabInstruction::ExecuteProposal,
+        accounts,
+    )
+}
+
+// Pay whatever a program-owned account is short of being rent exempt
+pub fn top_up_rent(program_id: &Pubkey, account: &Pubkey, payer: &Pubkey) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::TopUpRent,
+        vec![
+            AccountMeta::new(*account, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn initialize_leaderboard(program_id: &Pubkey, payer: &Pubkey) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::InitializeLeaderboard,
+        vec![
+            AccountMeta::new(find_leaderboard_address(program_id).0, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn submit_to_leaderboard(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    game_mode: GameMode,
+) -> Instruction {
+    let (score, _) = find_score_address(program_id, player, game_mode);
+    instruction(
+        program_id,
+        GarbageGrabInstruction::SubmitToLeaderboard { game_mode },
+        vec![
+            AccountMeta::new(find_leaderboard_address(program_id).0, false),
+            AccountMeta::new(score, false),
+            AccountMeta::new_readonly(*player, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(find_ban_list_address(program_id).0, false),
+            AccountMeta::new(find_leaderboard_page_address(program_id, 1).0, false),
+        ],
+    )
+}
+
+pub fn initialize_weekly_leaderboard(program_id: &Pubkey, payer: &Pubkey) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::InitializeWeeklyLeaderboard,
+        vec![
+            AccountMeta::new(find_weekly_leaderboard_address(program_id).0, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn submit_to_weekly_leaderboard(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    game_mode: GameMode,
+) -> Instruction {
+    let (score, _) = find_score_address(program_id, player, game_mode);
+    instruction(
+        program_id,
+        GarbageGrabInstruction::SubmitToWeeklyLeaderboard { game_mode },
+        vec![
+            AccountMeta::new(find_weekly_leaderboard_address(program_id).0, false),
+            AccountMeta::new_readonly(score, false),
+            AccountMeta::new_readonly(*player, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(find_ban_list_address(program_id).0, false),
+        ],
+    )
+}
+
+pub fn publish_daily_challenge(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    seed: [u8; 32],
+    date: i64,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::PublishDailyChallenge { seed, date },
+        vec![
+            AccountMeta::new(find_daily_challenge_address(program_id, date).0, false),
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Must be co-signed by the configured game server, which vouches for `score`
+pub fn submit_daily_challenge(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    game_server: &Pubkey,
+    date: i64,
+    score: u64,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::SubmitDailyChallenge { date, score },
+        vec![
+            AccountMeta::new(find_daily_challenge_address(program_id, date).0, false),
+            AccountMeta::new(find_daily_submission_address(program_id, date, player).0, false),
+            AccountMeta::new(*player, true),
+            AccountMeta::new_readonly(*game_server, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(find_ban_list_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn initialize_leaderboard_page(program_id: &Pubkey, payer: &Pubkey, page: u8) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::InitializeLeaderboardPage { page },
+        vec![
+            AccountMeta::new(find_leaderboard_page_address(program_id, page).0, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// `page` must be below the global leaderboard, on the highest page whose page
+// above is full of better scores
+pub fn submit_to_leaderboard_page(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    game_mode: GameMode,
+    page: u8,
+) -> Instruction {
+    let (score, _) = find_score_address(program_id, player, game_mode);
+    let mut accounts = vec![
+        AccountMeta::new(find_leaderboard_page_address(program_id, page).0, false),
+        AccountMeta::new_readonly(
+            find_leaderboard_page_address(program_id, page.saturating_sub(1)).0,
+            false,
+        ),
+        AccountMeta::new(score, false),
+        AccountMeta::new_readonly(*player, true),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        AccountMeta::new_readonly(find_ban_list_address(program_id).0, false),
+    ];
+    if page + 1 < LEADERBOARD_PAGES {
+        accounts.push(AccountMeta::new(
+            find_leaderboard_page_address(program_id, page + 1).0,
+            false,
+        ));
+    }
+    instruction(
+        program_id,
+        GarbageGrabInstruction::SubmitToLeaderboardPage { game_mode, page },
+        accounts,
+    )
+}
+
+// Anyone may send it
+pub fn rebalance_leaderboard_pages(program_id: &Pubkey, page: u8) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::RebalanceLeaderboardPages { page },
+        vec![
+            AccountMeta::new(
+                find_leaderboard_page_address(program_id, page.saturating_sub(1)).0,
+                false,
+            ),
+            AccountMeta::new(find_leaderboard_page_address(program_id, page).0, false),
+        ],
+    )
+}
+
+pub fn start_season(program_id: &Pubkey, admin: &Pubkey, end_ts: i64) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::StartSeason { end_ts },
+        vec![
+            AccountMeta::new(find_season_address(program_id).0, false),
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(find_prize_pool_address(program_id).0, false),
+        ],
+    )
+}
+
+pub fn initialize_config(
+    program_id: &Pubkey,
+    upgrade_authority: &Pubkey,
+    max_score: u64,
+    max_increment: u64,
+    update_cooldown_secs: u32,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::InitializeConfig {
+            max_score,
+            max_increment,
+            update_cooldown_secs,
+        },
+        vec![
+            AccountMeta::new(find_config_address(program_id).0, false),
+            AccountMeta::new(*upgrade_authority, true),
+            AccountMeta::new_readonly(find_program_data_address(program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn update_config(program_id: &Pubkey, admin: &Pubkey, update: ConfigUpdate) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::UpdateConfig(update),
+        admin_accounts(program_id, admin),
+    )
+}
+
+pub fn pause(program_id: &Pubkey, admin: &Pubkey) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::Pause,
+        admin_accounts(program_id, admin),
+    )
+}
+
+// Nominate the next admin; they take over once they sign AcceptAdmin
+pub fn nominate_admin(program_id: &Pubkey, admin: &Pubkey, new_admin: &Pubkey) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::NominateAdmin {
+            new_admin: *new_admin,
+        },
+        admin_accounts(program_id, admin),
+    )
+}
+
+pub fn accept_admin(program_id: &Pubkey, new_admin: &Pubkey) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::AcceptAdmin,
+        admin_accounts(program_id, new_admin),
+    )
+}
+
+pub fn unpause(program_id: &Pubkey, admin: &Pubkey) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::Unpause,
+        admin_accounts(program_id, admin),
+    )
+}
+
+// Config and admin, shared by every instruction that only edits the config
+fn admin_accounts(program_id: &Pubkey, admin: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(find_config_address(program_id).0, false),
+        AccountMeta::new_readonly(*admin, true),
+    ]
+}
+
+// The transaction must place an ed25519 verification of the game server's signature
+// over `score_attestation_message` directly before this instruction
+pub fn submit_attested_score(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    game_mode: GameMode,
+    increment: u64,
+    nonce: u64,
+) -> Instruction {
+    let (score, _) = find_score_address(program_id, player, game_mode);
+    instruction(
+        program_id,
+        GarbageGrabInstruction::SubmitAttestedScore {
+            game_mode,
+            increment,
+            nonce,
+        },
+        vec![
+            AccountMeta::new(score, false),
+            AccountMeta::new_readonly(*player, true),
+            AccountMeta::new_readonly(find_season_address(program_id).0, false),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(find_ban_list_address(program_id).0, false),
+            AccountMeta::new_readonly(sysvar::instructions::id(), false),
+        ],
+    )
+}
+
+// `authority` is the admin or the configured game server
+pub fn penalize_score(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    player: &Pubkey,
+    game_mode: GameMode,
+    amount: u64,
+) -> Instruction {
+    let (score, _) = find_score_address(program_id, player, game_mode);
+    instruction(
+        program_id,
+        GarbageGrabInstruction::PenalizeScore { game_mode, amount },
+        vec![
+            AccountMeta::new(score, false),
+            AccountMeta::new_readonly(*player, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        ],
+    )
+}
+
+pub fn set_score(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    player: &Pubkey,
+    game_mode: GameMode,
+    value: u64,
+) -> Instruction {
+    let (score, _) = find_score_address(program_id, player, game_mode);
+    instruction(
+        program_id,
+        GarbageGrabInstruction::SetScore { game_mode, value },
+        vec![
+            AccountMeta::new(score, false),
+            AccountMeta::new_readonly(*player, false),
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        ],
+    )
+}
+
+pub fn migrate_account(program_id: &Pubkey, player: &Pubkey, game_mode: GameMode) -> Instruction {
+    let (score, _) = find_score_address(program_id, player, game_mode);
+    instruction(
+        program_id,
+        GarbageGrabInstruction::MigrateAccount { game_mode },
+        vec![
+            AccountMeta::new(score, false),
+            AccountMeta::new(*player, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Rewards go to the player's associated token account for `reward_mint`. Set
+// `create_token_account` when that account does not exist yet so the claim creates
+// it at the player's expense. `token_program` is the SPL Token or Token-2022
+// program owning the mint; a mint with a transfer hook needs the hook's accounts
+// appended.
+pub fn claim_rewards(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    game_mode: GameMode,
+    reward_mint: &Pubkey,
+    token_program: &Pubkey,
+    create_token_account: bool,
+) -> Instruction {
+    let (score, _) = find_score_address(program_id, player, game_mode);
+    let mut accounts = vec![
+        AccountMeta::new(score, false),
+        AccountMeta::new(*player, true),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        AccountMeta::new(find_vault_address(program_id).0, false),
+        AccountMeta::new(
+            get_associated_token_address_with_program_id(player, reward_mint, token_program),
+            false,
+        ),
+        AccountMeta::new_readonly(*reward_mint, false),
+        AccountMeta::new_readonly(*token_program, false),
+    ];
+    if create_token_account {
+        accounts.extend([
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ]);
+    }
+    instruction(
+        program_id,
+        GarbageGrabInstruction::ClaimRewards { game_mode },
+        accounts,
+    )
+}
+
+pub fn initialize_vault(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    reward_mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::InitializeVault,
+        vec![
+            AccountMeta::new(find_vault_address(program_id).0, false),
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(*reward_mint, false),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Deposits come from the admin's associated token account for `reward_mint`
+pub fn fund_vault(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    reward_mint: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::FundVault { amount },
+        vec![
+            AccountMeta::new(find_vault_address(program_id).0, false),
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new(
+                get_associated_token_address_with_program_id(admin, reward_mint, token_program),
+                false,
+            ),
+            AccountMeta::new_readonly(*reward_mint, false),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+    )
+}
+
+// Stakes from the player's associated token account. `token_program` is the SPL
+// Token or Token-2022 program owning the reward mint. `lock` commits everything
+// staked for its duration, StakeLock::None keeping any running lock.
+pub fn stake(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    reward_mint: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+    lock: StakeLock,
+) -> Instruction {
+    let mut accounts = stake_accounts(program_id, player, reward_mint, token_program);
+    accounts[2].is_writable = true;
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    instruction(program_id, GarbageGrabInstruction::Stake { amount, lock }, accounts)
+}
+
+// Returns the tokens to the player's associated token account, less the early
+// unstake penalty paid to the treasury while a lock runs
+pub fn unstake(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    reward_mint: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let treasury = find_treasury_address(program_id).0;
+    let mut accounts = stake_accounts(program_id, player, reward_mint, token_program);
+    accounts.push(AccountMeta::new_readonly(treasury, false));
+    accounts.push(AccountMeta::new(
+        get_associated_token_address_with_program_id(&treasury, reward_mint, token_program),
+        false,
+    ));
+    instruction(program_id, GarbageGrabInstruction::Unstake { amount }, accounts)
+}
+
+// Accounts Stake and Unstake share, the player read-only
+fn stake_accounts(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    reward_mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(find_stake_address(program_id, player).0, false),
+        AccountMeta::new(find_stake_vault_address(program_id, player).0, false),
+        AccountMeta::new_readonly(*player, true),
+        AccountMeta::new(
+            get_associated_token_address_with_program_id(player, reward_mint, token_program),
+            false,
+        ),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        AccountMeta::new_readonly(*reward_mint, false),
+        AccountMeta::new_readonly(*token_program, false),
+    ]
+}
+
+// `price_feed` is the config's Pyth SOL/USD account, needed only while the
+// entry fee is set in US cents
+pub fn start_ranked_run(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    game_mode: GameMode,
+    price_feed: Option<Pubkey>,
+) -> Instruction {
+    let (score, _) = find_score_address(program_id, player, game_mode);
+    let mut accounts = vec![
+        AccountMeta::new(score, false),
+        AccountMeta::new(*player, true),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        AccountMeta::new(find_prize_pool_address(program_id).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(find_fee_vault_address(program_id).0, false),
+    ];
+    accounts.extend(price_feed.map(|feed| AccountMeta::new_readonly(feed, false)));
+    instruction(
+        program_id,
+        GarbageGrabInstruction::StartRankedRun { game_mode },
+        accounts,
+    )
+}
+
+// Each winner's wallet, followed by their vesting account for season
+// `vesting_season` when prizes vest
+fn winner_accounts(
+    program_id: &Pubkey,
+    winners: &[Pubkey],
+    vesting_season: Option<u64>,
+) -> Vec<AccountMeta> {
+    winners
+        .iter()
+        .flat_map(|winner| {
+            let vesting = vesting_season.map(|season_id| {
+                AccountMeta::new(find_vesting_address(program_id, season_id, winner).0, false)
+            });
+            std::iter::once(AccountMeta::new(*winner, false)).chain(vesting)
+        })
+        .collect()
+}
+
+// `winners` are the leaderboard's top players in rank order, one per paid place
+// that has an entry. Pass the season's id as `vesting_season` when the config
+// sets prize_vesting_secs.
+pub fn distribute_prizes(
+    program_id: &Pubkey,
+    winners: &[Pubkey],
+    vesting_season: Option<u64>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(find_leaderboard_address(program_id).0, false),
+        AccountMeta::new(find_season_address(program_id).0, false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        AccountMeta::new(find_prize_pool_address(program_id).0, false),
+    ];
+    if vesting_season.is_some() {
+        accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    }
+    accounts.extend(winner_accounts(program_id, winners, vesting_season));
+    instruction(
+        program_id,
+        GarbageGrabInstruction::DistributePrizes,
+        accounts,
+    )
+}
+
+// Fails with ScoreBelowThreshold unless the player's score is at least `threshold`
+pub fn verify_score_at_least(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    game_mode: GameMode,
+    threshold: u64,
+) -> Instruction {
+    let (score, _) = find_score_address(program_id, player, game_mode);
+    instruction(
+        program_id,
+        GarbageGrabInstruction::VerifyScoreAtLeast { threshold },
+        vec![
+            AccountMeta::new_readonly(score, false),
+            AccountMeta::new_readonly(*player, false),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(find_season_address(program_id).0, false),
+        ],
+    )
+}
+
+pub fn crank_season_rollover(program_id: &Pubkey, cranker: &Pubkey) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::CrankSeasonRollover,
+        vec![
+            AccountMeta::new(find_season_address(program_id).0, false),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new(*cranker, true),
+            AccountMeta::new(find_treasury_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// `winners` are the leaderboard's top players in rank order, one per paid place
+// that has an entry. Pass the season's id as `vesting_season` when the config
+// sets prize_vesting_secs.
+pub fn crank_leaderboard_finalize(
+    program_id: &Pubkey,
+    cranker: &Pubkey,
+    winners: &[Pubkey],
+    vesting_season: Option<u64>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(find_leaderboard_address(program_id).0, false),
+        AccountMeta::new(find_season_address(program_id).0, false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        AccountMeta::new(find_prize_pool_address(program_id).0, false),
+        AccountMeta::new(*cranker, true),
+        AccountMeta::new(find_treasury_address(program_id).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.extend(winner_accounts(program_id, winners, vesting_season));
+    instruction(
+        program_id,
+        GarbageGrabInstruction::CrankLeaderboardFinalize,
+        accounts,
+    )
+}
+
+// The admin must sign; `amount` counts against the per-epoch cap in the config
+pub fn withdraw_treasury(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    destination: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::WithdrawTreasury { amount },
+        vec![
+            AccountMeta::new(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(find_treasury_address(program_id).0, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Both players must sign
+pub fn create_match(
+    program_id: &Pubkey,
+    player_a: &Pubkey,
+    player_b: &Pubkey,
+    match_id: u64,
+    stake: u64,
+) -> Instruction {
+    let (match_account, _) = find_match_address(program_id, player_a, player_b, match_id);
+    instruction(
+        program_id,
+        GarbageGrabInstruction::CreateMatch { match_id, stake },
+        vec![
+            AccountMeta::new(match_account, false),
+            AccountMeta::new(*player_a, true),
+            AccountMeta::new(*player_b, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn submit_match_result(
+    program_id: &Pubkey,
+    game_server: &Pubkey,
+    match_account: &Pubkey,
+    winner: &Pubkey,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::SubmitMatchResult { winner: *winner },
+        vec![
+            AccountMeta::new(*match_account, false),
+            AccountMeta::new_readonly(*game_server, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        ],
+    )
+}
+
+pub fn settle_match(
+    program_id: &Pubkey,
+    match_account: &Pubkey,
+    winner: &Pubkey,
+    player_a: &Pubkey,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::SettleMatch,
+        vec![
+            AccountMeta::new(*match_account, false),
+            AccountMeta::new(*winner, false),
+            AccountMeta::new(*player_a, false),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new(find_fee_vault_address(program_id).0, false),
+            AccountMeta::new_readonly(find_bet_pool_address(program_id, match_account).0, false),
+        ],
+    )
+}
+
+pub fn place_bet(
+    program_id: &Pubkey,
+    match_account: &Pubkey,
+    bettor: &Pubkey,
+    on: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::PlaceBet { on: *on, amount },
+        vec![
+            AccountMeta::new(find_bet_pool_address(program_id, match_account).0, false),
+            AccountMeta::new(find_bet_address(program_id, match_account, bettor).0, false),
+            AccountMeta::new_readonly(*match_account, false),
+            AccountMeta::new(*bettor, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Settles the bets of `bettors`; any number may be left for later calls
+pub fn settle_bets(program_id: &Pubkey, match_account: &Pubkey, bettors: &[Pubkey]) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(find_bet_pool_address(program_id, match_account).0, false),
+        AccountMeta::new_readonly(*match_account, false),
+        AccountMeta::new(find_treasury_address(program_id).0, false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+    ];
+    for bettor in bettors {
+        accounts.push(AccountMeta::new(
+            find_bet_address(program_id, match_account, bettor).0,
+            false,
+        ));
+        accounts.push(AccountMeta::new(*bettor, false));
+    }
+    instruction(program_id, GarbageGrabInstruction::SettleBets, accounts)
+}
+
+pub fn create_lobby(program_id: &Pubkey, host: &Pubkey, capacity: u8, stake: u64) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::CreateLobby { capacity, stake },
+        vec![
+            AccountMeta::new(find_lobby_address(program_id, host).0, false),
+            AccountMeta::new(*host, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn join_lobby(program_id: &Pubkey, host: &Pubkey, player: &Pubkey) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::JoinLobby,
+        vec![
+            AccountMeta::new(find_lobby_address(program_id, host).0, false),
+            AccountMeta::new(*player, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn leave_lobby(program_id: &Pubkey, host: &Pubkey, player: &Pubkey) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::LeaveLobby,
+        vec![
+            AccountMeta::new(find_lobby_address(program_id, host).0, false),
+            AccountMeta::new(*player, true),
+        ],
+    )
+}
+
+// Signed by `player_a`, the longest-waiting player; `player_b` is next in line and
+// `match_id` the number of matches the lobby has started so far
+pub fn start_match(
+    program_id: &Pubkey,
+    host: &Pubkey,
+    player_a: &Pubkey,
+    player_b: &Pubkey,
+    match_id: u64,
+) -> Instruction {
+    let (match_account, _) = find_match_address(program_id, player_a, player_b, match_id);
+    instruction(
+        program_id,
+        GarbageGrabInstruction::StartMatch,
+        vec![
+            AccountMeta::new(find_lobby_address(program_id, host).0, false),
+            AccountMeta::new(match_account, false),
+            AccountMeta::new(*player_a, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Signed by the admin
+pub fn withdraw_fees(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    destination: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::WithdrawFees { amount },
+        vec![
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(find_fee_vault_address(program_id).0, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Signed by the prize winner
+pub fn claim_vested(program_id: &Pubkey, beneficiary: &Pubkey, season_id: u64) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::ClaimVested,
+        vec![
+            AccountMeta::new(find_vesting_address(program_id, season_id, beneficiary).0, false),
+            AccountMeta::new(*beneficiary, true),
+        ],
+    )
+}
+
+// Pass `Pubkey::default()` as `collection` to let anyone register
+pub fn create_tournament(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    tournament_id: u64,
+    entry_fee: u64,
+    max_participants: u8,
+    end_ts: i64,
+    collection: &Pubkey,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::CreateTournament {
+            tournament_id,
+            entry_fee,
+            max_participants,
+            end_ts,
+            collection: *collection,
+        },
+        vec![
+            AccountMeta::new(find_tournament_address(program_id, tournament_id).0, false),
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Collection-gated tournaments need `nft_mint`, an NFT from the collection held in
+// the player's associated token account
+pub fn register(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    tournament_id: u64,
+    nft_mint: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(find_tournament_address(program_id, tournament_id).0, false),
+        AccountMeta::new(*player, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        AccountMeta::new(find_fee_vault_address(program_id).0, false),
+    ];
+    if let Some(nft_mint) = nft_mint {
+        accounts.push(AccountMeta::new_readonly(
+            get_associated_token_address(player, nft_mint),
+            false,
+        ));
+        accounts.push(AccountMeta::new_readonly(
+            Metadata::find_pda(nft_mint).0,
+            false,
+        ));
+    }
+    instruction(program_id, GarbageGrabInstruction::Register, accounts)
+}
+
+pub fn submit_tournament_score(
+    program_id: &Pubkey,
+    game_server: &Pubkey,
+    player: &Pubkey,
+    tournament_id: u64,
+    score: u64,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::SubmitTournamentScore { score },
+        vec![
+            AccountMeta::new(find_tournament_address(program_id, tournament_id).0, false),
+            AccountMeta::new_readonly(*player, false),
+            AccountMeta::new_readonly(*game_server, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        ],
+    )
+}
+
+// `winners` are the tournament's top participants in standings order, one per
+// paid place that has a participant
+pub fn finalize_tournament(
+    program_id: &Pubkey,
+    creator: &Pubkey,
+    tournament_id: u64,
+    winners: &[Pubkey],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(find_tournament_address(program_id, tournament_id).0, false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        AccountMeta::new(*creator, false),
+    ];
+    accounts.extend(
+        winners
+            .iter()
+            .map(|winner| AccountMeta::new(*winner, false)),
+    );
+    instruction(
+        program_id,
+        GarbageGrabInstruction::FinalizeTournament,
+        accounts,
+    )
+}
+
+pub fn unlock_achievement(
+    program_id: &Pubkey,
+    game_server: &Pubkey,
+    player: &Pubkey,
+    game_mode: GameMode,
+    id: u8,
+) -> Instruction {
+    let (score, _) = find_score_address(program_id, player, game_mode);
+    instruction(
+        program_id,
+        GarbageGrabInstruction::UnlockAchievement { game_mode, id },
+        vec![
+            AccountMeta::new(score, false),
+            AccountMeta::new_readonly(*player, false),
+            AccountMeta::new_readonly(*game_server, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        ],
+    )
+}
+
+pub fn check_in(program_id: &Pubkey, player: &Pubkey, game_mode: GameMode) -> Instruction {
+    let (score, _) = find_score_address(program_id, player, game_mode);
+    instruction(
+        program_id,
+        GarbageGrabInstruction::CheckIn { game_mode },
+        vec![
+            AccountMeta::new(score, false),
+            AccountMeta::new_readonly(*player, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        ],
+    )
+}
+
+// Add to a player's score with the config's boost multiplier applied, proving
+// ownership of `nft_mint` through the player's associated token account for it
+pub fn apply_boost(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    game_mode: GameMode,
+    increment: u64,
+    nft_mint: &Pubkey,
+) -> Instruction {
+    let (score, _) = find_score_address(program_id, player, game_mode);
+    instruction(
+        program_id,
+        GarbageGrabInstruction::ApplyBoost {
+            game_mode,
+            increment,
+        },
+        vec![
+            AccountMeta::new(score, false),
+            AccountMeta::new_readonly(*player, true),
+            AccountMeta::new_readonly(find_season_address(program_id).0, false),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(find_ban_list_address(program_id).0, false),
+            AccountMeta::new_readonly(get_associated_token_address(player, nft_mint), false),
+            AccountMeta::new_readonly(Metadata::find_pda(nft_mint).0, false),
+        ],
+    )
+}
+
+// Mint a trophy NFT for the player's best score. `trophy_mint` is a fresh keypair
+// that must also sign the transaction.
+pub fn mint_trophy(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    game_mode: GameMode,
+    trophy_mint: &Pubkey,
+) -> Instruction {
+    let (score, _) = find_score_address(program_id, player, game_mode);
+    instruction(
+        program_id,
+        GarbageGrabInstruction::MintTrophy { game_mode },
+        vec![
+            AccountMeta::new(score, false),
+            AccountMeta::new(*player, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new(*trophy_mint, true),
+            AccountMeta::new(get_associated_token_address(player, trophy_mint), false),
+            AccountMeta::new(Metadata::find_pda(trophy_mint).0, false),
+            AccountMeta::new(MasterEdition::find_pda(trophy_mint).0, false),
+            AccountMeta::new_readonly(find_trophy_authority_address(program_id).0, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+            AccountMeta::new_readonly(mpl_token_metadata::ID, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Create the player's profile, claiming a zero-padded display name
+// (see `score_program::state::pad_profile_name`)
+pub fn create_profile(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    name: &[u8; PROFILE_NAME_LEN],
+    avatar_mint: &Pubkey,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::CreateProfile {
+            name: *name,
+            avatar_mint: *avatar_mint,
+        },
+        vec![
+            AccountMeta::new(find_profile_address(program_id, player).0, false),
+            AccountMeta::new(find_name_record_address(program_id, name).0, false),
+            AccountMeta::new(*player, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Change the player's display name and/or avatar. `current_name` is the name the
+// profile holds now, whose record a rename releases.
+pub fn update_profile(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    current_name: &[u8; PROFILE_NAME_LEN],
+    name: Option<[u8; PROFILE_NAME_LEN]>,
+    avatar_mint: Option<Pubkey>,
+) -> Instruction {
+    let new_name = name.as_ref().unwrap_or(current_name);
+    instruction(
+        program_id,
+        GarbageGrabInstruction::UpdateProfile { name, avatar_mint },
+        vec![
+            AccountMeta::new(find_profile_address(program_id, player).0, false),
+            AccountMeta::new(*player, true),
+            AccountMeta::new(find_name_record_address(program_id, current_name).0, false),
+            AccountMeta::new(find_name_record_address(program_id, new_name).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Credit the referrer of `player` once the player has reached the referral threshold
+pub fn credit_referral(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    referrer: &Pubkey,
+    game_mode: GameMode,
+) -> Instruction {
+    let (score, _) = find_score_address(program_id, player, game_mode);
+    instruction(
+        program_id,
+        GarbageGrabInstruction::CreditReferral { game_mode },
+        vec![
+            AccountMeta::new(score, false),
+            AccountMeta::new_readonly(*player, false),
+            AccountMeta::new(find_profile_address(program_id, referrer).0, false),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        ],
+    )
+}
+
+// `followed` must have a profile, which counts its followers
+pub fn follow(program_id: &Pubkey, follower: &Pubkey, followed: &Pubkey) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::Follow,
+        vec![
+            AccountMeta::new(find_follow_address(program_id, follower, followed).0, false),
+            AccountMeta::new(*follower, true),
+            AccountMeta::new_readonly(*followed, false),
+            AccountMeta::new(find_profile_address(program_id, followed).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn unfollow(program_id: &Pubkey, follower: &Pubkey, followed: &Pubkey) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::Unfollow,
+        vec![
+            AccountMeta::new(find_follow_address(program_id, follower, followed).0, false),
+            AccountMeta::new(*follower, true),
+            AccountMeta::new_readonly(*followed, false),
+            AccountMeta::new(find_profile_address(program_id, followed).0, false),
+        ],
+    )
+}
+
+// Set the region on the player's profile; only possible once
+pub fn set_region(program_id: &Pubkey, player: &Pubkey, region: [u8; 2]) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::SetRegion { region },
+        vec![
+            AccountMeta::new(find_profile_address(program_id, player).0, false),
+            AccountMeta::new_readonly(*player, true),
+        ],
+    )
+}
+
+pub fn admin_set_region(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    player: &Pubkey,
+    region: [u8; 2],
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::AdminSetRegion { region },
+        vec![
+            AccountMeta::new(find_profile_address(program_id, player).0, false),
+            AccountMeta::new_readonly(*player, false),
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        ],
+    )
+}
+
+pub fn initialize_regional_leaderboard(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    region: [u8; 2],
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::InitializeRegionalLeaderboard { region },
+        vec![
+            AccountMeta::new(
+                find_regional_leaderboard_address(program_id, &region).0,
+                false,
+            ),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// `region` must be the one on the player's profile
+pub fn submit_to_regional_leaderboard(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    region: [u8; 2],
+    game_mode: GameMode,
+) -> Instruction {
+    let (score, _) = find_score_address(program_id, player, game_mode);
+    instruction(
+        program_id,
+        GarbageGrabInstruction::SubmitToRegionalLeaderboard { game_mode },
+        vec![
+            AccountMeta::new(
+                find_regional_leaderboard_address(program_id, &region).0,
+                false,
+            ),
+            AccountMeta::new_readonly(score, false),
+            AccountMeta::new_readonly(*player, true),
+            AccountMeta::new_readonly(find_profile_address(program_id, player).0, false),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(find_ban_list_address(program_id).0, false),
+        ],
+    )
+}
+
+// Optional account to append to UpdateScore, ahead of any guild accounts, so the
+// player's staked tokens multiply the increment
+pub fn stake_account(program_id: &Pubkey, player: &Pubkey) -> AccountMeta {
+    AccountMeta::new_readonly(find_stake_address(program_id, player).0, false)
+}
+
+// Optional account to append to UpdateScore after any stake account, or to
+// UnlockAchievement, so the update counts towards the player's quest `quest_id`.
+// Several may be appended, one per quest.
+pub fn quest_progress_account(program_id: &Pubkey, player: &Pubkey, quest_id: u64) -> AccountMeta {
+    AccountMeta::new(find_quest_progress_address(program_id, quest_id, player).0, false)
+}
+
+// Optional account to append to UpdateScore after any quest progress accounts,
+// to SubmitRun ahead of any history account, or to EndSession, so the run earns
+// the player XP. PlaceBet and CreateMatch need it for wagers of at least the
+// config's high_stakes_wager, one per player.
+pub fn profile_account(program_id: &Pubkey, player: &Pubkey) -> AccountMeta {
+    AccountMeta::new(find_profile_address(program_id, player).0, false)
+}
+
+// Optional accounts to append to UpdateScore, SubmitAttestedScore or ApplyBoost
+// so the credited points also count towards the player's guild
+pub fn guild_accounts(program_id: &Pubkey, player: &Pubkey, guild_id: u64) -> [AccountMeta; 2] {
+    [
+        AccountMeta::new(find_guild_member_address(program_id, player).0, false),
+        AccountMeta::new(find_guild_address(program_id, guild_id).0, false),
+    ]
+}
+
+pub fn create_guild(
+    program_id: &Pubkey,
+    leader: &Pubkey,
+    guild_id: u64,
+    name: &[u8; PROFILE_NAME_LEN],
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::CreateGuild {
+            guild_id,
+            name: *name,
+        },
+        vec![
+            AccountMeta::new(find_guild_address(program_id, guild_id).0, false),
+            AccountMeta::new(find_guild_member_address(program_id, leader).0, false),
+            AccountMeta::new(*leader, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn join_guild(program_id: &Pubkey, player: &Pubkey, guild_id: u64) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::JoinGuild,
+        vec![
+            AccountMeta::new(find_guild_address(program_id, guild_id).0, false),
+            AccountMeta::new(find_guild_member_address(program_id, player).0, false),
+            AccountMeta::new(*player, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn leave_guild(program_id: &Pubkey, player: &Pubkey, guild_id: u64) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::LeaveGuild,
+        vec![
+            AccountMeta::new(find_guild_address(program_id, guild_id).0, false),
+            AccountMeta::new(find_guild_member_address(program_id, player).0, false),
+            AccountMeta::new(*player, true),
+        ],
+    )
+}
+
+pub fn initialize_guild_leaderboard(program_id: &Pubkey, payer: &Pubkey) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::InitializeGuildLeaderboard,
+        vec![
+            AccountMeta::new(find_guild_leaderboard_address(program_id).0, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn submit_guild_to_leaderboard(program_id: &Pubkey, guild_id: u64) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::SubmitGuildToLeaderboard,
+        vec![
+            AccountMeta::new(find_guild_leaderboard_address(program_id).0, false),
+            AccountMeta::new_readonly(find_guild_address(program_id, guild_id).0, false),
+        ],
+    )
+}
+
+// Every member must also sign the transaction
+pub fn create_team(program_id: &Pubkey, payer: &Pubkey, members: &[Pubkey]) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(find_team_address(program_id, members).0, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.extend(members.iter().map(|m| AccountMeta::new_readonly(*m, true)));
+    instruction(program_id, GarbageGrabInstruction::CreateTeam, accounts)
+}
+
+pub fn update_team_score(
+    program_id: &Pubkey,
+    member: &Pubkey,
+    members: &[Pubkey],
+    increment: u64,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::UpdateTeamScore { increment },
+        vec![
+            AccountMeta::new(find_team_address(program_id, members).0, false),
+            AccountMeta::new_readonly(*member, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        ],
+    )
+}
+
+pub fn set_reward_root(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    airdrop_id: u64,
+    merkle_root: [u8; 32],
+    max_claims: u32,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::SetRewardRoot {
+            airdrop_id,
+            merkle_root,
+            max_claims,
+        },
+        vec![
+            AccountMeta::new(find_airdrop_address(program_id, airdrop_id).0, false),
+            AccountMeta::new(find_airdrop_claims_address(program_id, airdrop_id).0, false),
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// `proof` comes from `score_program::merkle::build_tree` over the airdrop's leaves
+#[allow(clippy::too_many_arguments)]
+pub fn claim_airdrop(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    reward_mint: &Pubkey,
+    token_program: &Pubkey,
+    airdrop_id: u64,
+    index: u32,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::ClaimAirdrop {
+            airdrop_id,
+            index,
+            amount,
+            proof,
+        },
+        vec![
+            AccountMeta::new(find_airdrop_address(program_id, airdrop_id).0, false),
+            AccountMeta::new(find_airdrop_claims_address(program_id, airdrop_id).0, false),
+            AccountMeta::new_readonly(*player, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new(find_vault_address(program_id).0, false),
+            AccountMeta::new(
+                get_associated_token_address_with_program_id(player, reward_mint, token_program),
+                false,
+            ),
+            AccountMeta::new_readonly(*reward_mint, false),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+    )
+}
+
+pub fn create_quest(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    quest_id: u64,
+    metric: QuestMetric,
+    target: u64,
+    reward: u64,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::CreateQuest {
+            quest_id,
+            metric,
+            target,
+            reward,
+        },
+        vec![
+            AccountMeta::new(find_quest_address(program_id, quest_id).0, false),
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn accept_quest(program_id: &Pubkey, player: &Pubkey, quest_id: u64) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::AcceptQuest,
+        vec![
+            AccountMeta::new(find_quest_progress_address(program_id, quest_id, player).0, false),
+            AccountMeta::new_readonly(find_quest_address(program_id, quest_id).0, false),
+            AccountMeta::new(*player, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Pays into the player's associated token account, which must already exist
+pub fn claim_quest_reward(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    reward_mint: &Pubkey,
+    token_program: &Pubkey,
+    quest_id: u64,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::ClaimQuestReward,
+        vec![
+            AccountMeta::new(find_quest_progress_address(program_id, quest_id, player).0, false),
+            AccountMeta::new_readonly(find_quest_address(program_id, quest_id).0, false),
+            AccountMeta::new_readonly(*player, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new(find_vault_address(program_id).0, false),
+            AccountMeta::new(
+                get_associated_token_address_with_program_id(player, reward_mint, token_program),
+                false,
+            ),
+            AccountMeta::new_readonly(*reward_mint, false),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+    )
+}
+
+// `achievement` must be `score_program::state::NO_ACHIEVEMENT` for a cosmetic
+// unlocked by level alone
+pub fn set_cosmetic(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    cosmetic_id: u8,
+    min_level: u16,
+    game_mode: GameMode,
+    achievement: u8,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::SetCosmetic {
+            cosmetic_id,
+            min_level,
+            game_mode,
+            achievement,
+        },
+        vec![
+            AccountMeta::new(find_cosmetic_address(program_id, cosmetic_id).0, false),
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Pass the cosmetic's game mode when it needs an achievement, so the player's
+// score account in that mode is read
+pub fn unlock_cosmetic(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    cosmetic_id: u8,
+    achievement_mode: Option<GameMode>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(find_cosmetic_address(program_id, cosmetic_id).0, false),
+        AccountMeta::new(find_profile_address(program_id, player).0, false),
+        AccountMeta::new_readonly(*player, false),
+    ];
+    if let Some(game_mode) = achievement_mode {
+        accounts.push(AccountMeta::new_readonly(
+            find_score_address(program_id, player, game_mode).0,
+            false,
+        ));
+    }
+    instruction(program_id, GarbageGrabInstruction::UnlockCosmetic, accounts)
+}
+
+// `badge_mint` is a fresh keypair that must also sign; the program creates it as
+// a non-transferable Token-2022 mint
+pub fn add_badge(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    badge_mint: &Pubkey,
+    milestone: BadgeMilestone,
+    threshold: u64,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::AddBadge {
+            milestone,
+            threshold,
+        },
+        vec![
+            AccountMeta::new(find_badge_catalog_address(program_id).0, false),
+            AccountMeta::new(*badge_mint, true),
+            AccountMeta::new_readonly(find_badge_authority_address(program_id).0, false),
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(spl_token_2022::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Mints the badge to the player's Token-2022 associated token account, creating
+// it at the player's expense
+pub fn claim_badge(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    badge_mint: &Pubkey,
+    badge_id: u8,
+    game_mode: GameMode,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::ClaimBadge {
+            badge_id,
+            game_mode,
+        },
+        vec![
+            AccountMeta::new_readonly(find_badge_catalog_address(program_id).0, false),
+            AccountMeta::new_readonly(find_score_address(program_id, player, game_mode).0, false),
+            AccountMeta::new(*player, true),
+            AccountMeta::new(*badge_mint, false),
+            AccountMeta::new(
+                get_associated_token_address_with_program_id(
+                    player,
+                    badge_mint,
+                    &spl_token_2022::id(),
+                ),
+                false,
+            ),
+            AccountMeta::new_readonly(find_badge_authority_address(program_id).0, false),
+            AccountMeta::new_readonly(spl_token_2022::id(), false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Once this exists, append `find_global_stats_address` to the instructions that
+// count towards it, right after their fixed accounts
+pub fn initialize_global_stats(program_id: &Pubkey, admin: &Pubkey) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::InitializeGlobalStats,
+        vec![
+            AccountMeta::new(find_global_stats_address(program_id).0, false),
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// `merkle_tree` must already be allocated with `score_tree_space` bytes and owned
+// by the account compression program
+pub fn initialize_score_tree(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    merkle_tree: &Pubkey,
+    max_depth: u32,
+    max_buffer_size: u32,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::InitializeScoreTree {
+            max_depth,
+            max_buffer_size,
+        },
+        vec![
+            AccountMeta::new(find_score_tree_address(program_id, merkle_tree).0, false),
+            AccountMeta::new(*merkle_tree, false),
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(spl_account_compression::id(), false),
+            AccountMeta::new_readonly(spl_noop::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn append_compressed_score(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    game_server: &Pubkey,
+    merkle_tree: &Pubkey,
+    game_mode: GameMode,
+    score: u64,
+) -> Instruction {
+    instruction(
+        program_id,
+        GarbageGrabInstruction::AppendCompressedScore { game_mode, score },
+        vec![
+            AccountMeta::new(find_score_tree_address(program_id, merkle_tree).0, false),
+            AccountMeta::new(*merkle_tree, false),
+            AccountMeta::new_readonly(*player, false),
+            AccountMeta::new_readonly(*game_server, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(spl_account_compression::id(), false),
+            AccountMeta::new_readonly(spl_noop::id(), false),
+        ],
+    )
+}
+
+// `proof` lists the sibling nodes from the leaf up, as served by an indexer
+pub fn verify_compressed_score(
+    program_id: &Pubkey,
+    merkle_tree: &Pubkey,
+    root: [u8; 32],
+    score: CompressedScore,
+    proof: &[[u8; 32]],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(find_score_tree_address(program_id, merkle_tree).0, false),
+        AccountMeta::new_readonly(*merkle_tree, false),
+        AccountMeta::new_readonly(spl_account_compression::id(), false),
+    ];
+    accounts.extend(
+        proof
+            .iter()
+            .map(|node| AccountMeta::new_readonly(Pubkey::new_from_array(*node), false)),
+    );
+    instruction(
+        program_id,
+        GarbageGrabInstruction::VerifyCompressedScore { root, score },
+        accounts,
+    )
+}
+
+// Each entry is a player and the increment to add to their score in `game_mode`
+pub fn batch_update(
+    program_id: &Pubkey,
+    game_server: &Pubkey,
+    game_mode: GameMode,
+    entries: &[(Pubkey, u64)],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*game_server, true),
+        AccountMeta::new_readonly(find_season_address(program_id).0, false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        AccountMeta::new_readonly(find_ban_list_address(program_id).0, false),
+    ];
+    accounts.extend(entries.iter().map(|(player, _)| {
+        AccountMeta::new(find_score_address(program_id, player, game_mode).0, false)
+    }));
+    instruction(
+        program_id,
+        GarbageGrabInstruction::BatchUpdate {
+            game_mode,
+            increments: entries.iter().map(|&(_, increment)| increment).collect(),
+        },
+        accounts,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn score_address_depends_on_game_mode() {
+        let program_id = Pubkey::new_unique();
+        let player = Pubkey::new_unique();
+        let (arcade, _) = find_score_address(&program_id, &player, GameMode::Arcade);
+        let (endless, _) = find_score_address(&program_id, &player, GameMode::Endless);
+        assert_ne!(arcade, endless);
+    }
+
+    #[test]
+    fn team_address_ignores_member_order() {
+        let program_id = Pubkey::new_unique();
+        let [a, b, c] = [0; 3].map(|_| Pubkey::new_unique());
+        assert_eq!(
+            find_team_address(&program_id, &[a, b, c]),
+            find_team_address(&program_id, &[c, a, b])
+        );
+        assert_ne!(
+            find_team_address(&program_id, &[a, b]).0,
+            find_team_address(&program_id, &[a, c]).0
+        );
+    }
+
+    #[test]
+    fn update_score_accounts_and_data() {
+        let program_id = Pubkey::new_unique();
+        let player = Pubkey::new_unique();
+        let ix = update_score(&program_id, &player, GameMode::Arcade, 25);
+
+        assert_eq!(ix.program_id, program_id);
+        assert_eq!(
+            GarbageGrabInstruction::unpack(&ix.data).unwrap(),
+            GarbageGrabInstruction::UpdateScore {
+                game_mode: GameMode::Arcade,
+                increment: 25,
+            }
+        );
+        let keys: Vec<_> = ix
+            .accounts
+            .iter()
+            .map(|meta| (meta.pubkey, meta.is_signer, meta.is_writable))
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                (
+                    find_score_address(&program_id, &player, GameMode::Arcade).0,
+                    false,
+                    true
+                ),
+                (player, true, false),
+                (find_season_address(&program_id).0, false, false),
+                (find_config_address(&program_id).0, false, false),
+                (find_ban_list_address(&program_id).0, false, false),
+            ]
+        );
+    }
+}
@@ -0,0 +1,384 @@
+use score_program::state::{
+    BadgeCatalog, Config, Cosmetic, DailyChallenge, Follow, GameMode, GlobalStats, Leaderboard,
+    PlayerProfile, Quest, QuestProgress, ScoreAccount, ScoreHistory, Season, Stake, Tournament,
+    WeeklyLeaderboard,
+};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    client_error::ClientError,
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{
+    account::Account,
+    instruction::Instruction,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    transaction::Transaction,
+};
+use thiserror::Error;
+
+use crate::{
+    find_badge_catalog_address, find_config_address, find_cosmetic_address,
+    find_daily_challenge_address, find_global_stats_address, find_history_address,
+    find_leaderboard_address, find_leaderboard_page_address, find_profile_address,
+    find_quest_address, find_quest_progress_address, find_regional_leaderboard_address,
+    find_score_address, find_season_address, find_stake_address, find_tournament_address,
+    find_weekly_leaderboard_address,
+};
+
+// Errors from querying or transacting with the score program over RPC
+#[derive(Debug, Error)]
+pub enum ScoreClientError {
+    #[error(transparent)]
+    Rpc(Box<ClientError>),
+    #[error("Account {0} does not hold the expected program state")]
+    InvalidAccountData(Pubkey),
+}
+
+impl From<ClientError> for ScoreClientError {
+    fn from(e: ClientError) -> Self {
+        ScoreClientError::Rpc(Box::new(e))
+    }
+}
+
+// Async wrapper around an RPC connection for reading scores and submitting
+// score program instructions
+pub struct ScoreClient {
+    rpc: RpcClient,
+    program_id: Pubkey,
+}
+
+impl ScoreClient {
+    pub fn new(rpc: RpcClient, program_id: Pubkey) -> Self {
+        Self { rpc, program_id }
+    }
+
+    pub fn rpc(&self) -> &RpcClient {
+        &self.rpc
+    }
+
+    pub fn program_id(&self) -> &Pubkey {
+        &self.program_id
+    }
+
+    // Fetch a player's score account, or `None` if it has not been created.
+    // Accounts still on an older layout are decoded with their newer fields zeroed.
+    pub async fn get_score(
+        &self,
+        player: &Pubkey,
+        game_mode: GameMode,
+    ) -> Result<Option<ScoreAccount>, ScoreClientError> {
+        let (address, _) = find_score_address(&self.program_id, player, game_mode);
+        self.get_decoded(&address, ScoreAccount::unpack_any_version)
+            .await
+    }
+
+    // Fetch the program config, or `None` before InitializeConfig has run
+    pub async fn get_config(&self) -> Result<Option<Config>, ScoreClientError> {
+        let (address, _) = find_config_address(&self.program_id);
+        self.get_decoded(&address, Config::unpack).await
+    }
+
+    // Fetch the current season, or `None` before the first one started
+    pub async fn get_season(&self) -> Result<Option<Season>, ScoreClientError> {
+        let (address, _) = find_season_address(&self.program_id);
+        self.get_decoded(&address, Season::unpack).await
+    }
+
+    // Fetch the global leaderboard, or `None` before InitializeLeaderboard has run
+    pub async fn get_leaderboard(&self) -> Result<Option<Leaderboard>, ScoreClientError> {
+        let (address, _) = find_leaderboard_address(&self.program_id);
+        self.get_decoded(&address, |data| Leaderboard::load(data).copied())
+            .await
+    }
+
+    // Fetch leaderboard page `page`, or `None` before it was initialized. Page 0
+    // is the global leaderboard.
+    pub async fn get_leaderboard_page(
+        &self,
+        page: u8,
+    ) -> Result<Option<Leaderboard>, ScoreClientError> {
+        let (address, _) = find_leaderboard_page_address(&self.program_id, page);
+        self.get_decoded(&address, |data| Leaderboard::load(data).copied())
+            .await
+    }
+
+    // Fetch the leaderboard of one region, or `None` before it was initialized
+    pub async fn get_regional_leaderboard(
+        &self,
+        region: &[u8; 2],
+    ) -> Result<Option<Leaderboard>, ScoreClientError> {
+        let (address, _) = find_regional_leaderboard_address(&self.program_id, region);
+        self.get_decoded(&address, |data| Leaderboard::load(data).copied())
+            .await
+    }
+
+    // Fetch the daily challenge of UTC day `day`, or `None` if it was not published
+    pub async fn get_daily_challenge(
+        &self,
+        day: i64,
+    ) -> Result<Option<DailyChallenge>, ScoreClientError> {
+        let (address, _) = find_daily_challenge_address(&self.program_id, day);
+        self.get_decoded(&address, |data| DailyChallenge::load(data).copied())
+            .await
+    }
+
+    // Fetch the weekly leaderboard, or `None` before InitializeWeeklyLeaderboard
+    // has run. Its entries may be from a past week until the next submission
+    // rolls it over; compare its week with `week_of` the current time.
+    pub async fn get_weekly_leaderboard(
+        &self,
+    ) -> Result<Option<WeeklyLeaderboard>, ScoreClientError> {
+        let (address, _) = find_weekly_leaderboard_address(&self.program_id);
+        self.get_decoded(&address, |data| WeeklyLeaderboard::load(data).copied())
+            .await
+    }
+
+    // Fetch a tournament, or `None` if it was never created
+    pub async fn get_tournament(
+        &self,
+        tournament_id: u64,
+    ) -> Result<Option<Tournament>, ScoreClientError> {
+        let (address, _) = find_tournament_address(&self.program_id, tournament_id);
+        self.get_decoded(&address, Tournament::unpack).await
+    }
+
+    // Fetch a player's profile, or `None` if they have not created one
+    pub async fn get_profile(
+        &self,
+        player: &Pubkey,
+    ) -> Result<Option<PlayerProfile>, ScoreClientError> {
+        let (address, _) = find_profile_address(&self.program_id, player);
+        self.get_decoded(&address, PlayerProfile::unpack).await
+    }
+
+    // Fetch a player's recent runs in a mode, or `None` if they have no history
+    pub async fn get_history(
+        &self,
+        player: &Pubkey,
+        game_mode: GameMode,
+    ) -> Result<Option<ScoreHistory>, ScoreClientError> {
+        let (address, _) = find_history_address(&self.program_id, player, game_mode);
+        self.get_decoded(&address, ScoreHistory::unpack).await
+    }
+
+    // Fetch a player's stake, or `None` if they have never staked
+    pub async fn get_stake(&self, player: &Pubkey) -> Result<Option<Stake>, ScoreClientError> {
+        let (address, _) = find_stake_address(&self.program_id, player);
+        self.get_decoded(&address, Stake::unpack).await
+    }
+
+    // Fetch quest `quest_id`, or `None` if it has not been created
+    pub async fn get_quest(&self, quest_id: u64) -> Result<Option<Quest>, ScoreClientError> {
+        let (address, _) = find_quest_address(&self.program_id, quest_id);
+        self.get_decoded(&address, Quest::unpack).await
+    }
+
+    // Fetch cosmetic `cosmetic_id`, or `None` if the admin has not set it up
+    pub async fn get_cosmetic(
+        &self,
+        cosmetic_id: u8,
+    ) -> Result<Option<Cosmetic>, ScoreClientError> {
+        let (address, _) = find_cosmetic_address(&self.program_id, cosmetic_id);
+        self.get_decoded(&address, Cosmetic::unpack).await
+    }
+
+    // Fetch the badge catalog, or `None` if no badge has been added yet
+    pub async fn get_badge_catalog(&self) -> Result<Option<BadgeCatalog>, ScoreClientError> {
+        let (address, _) = find_badge_catalog_address(&self.program_id);
+        self.get_decoded(&address, BadgeCatalog::unpack).await
+    }
+
+    // Fetch the global stats, or `None` if the admin has not created them
+    pub async fn get_global_stats(&self) -> Result<Option<GlobalStats>, ScoreClientError> {
+        let (address, _) = find_global_stats_address(&self.program_id);
+        self.get_decoded(&address, GlobalStats::unpack).await
+    }
+
+    // Fetch a player's progress towards quest `quest_id`, or `None` if they have
+    // not accepted it
+    pub async fn get_quest_progress(
+        &self,
+        quest_id: u64,
+        player: &Pubkey,
+    ) -> Result<Option<QuestProgress>, ScoreClientError> {
+        let (address, _) = find_quest_progress_address(&self.program_id, quest_id, player);
+        self.get_decoded(&address, QuestProgress::unpack).await
+    }
+
+    async fn get_decoded<T>(
+        &self,
+        address: &Pubkey,
+        unpack: fn(&[u8]) -> Result<T, ProgramError>,
+    ) -> Result<Option<T>, ScoreClientError> {
+        let account = self
+            .rpc
+            .get_account_with_commitment(address, self.rpc.commitment())
+            .await?
+            .value;
+        account
+            .map(|account| decode(address, &account, unpack))
+            .transpose()
+    }
+
+    // Fetch every score account owned by the program. Only accounts that carry the
+    // score discriminator are matched, so unmigrated untagged accounts are skipped.
+    pub async fn get_all_scores(&self) -> Result<Vec<(Pubkey, ScoreAccount)>, ScoreClientError> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(score_account_filters()),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                commitment: Some(self.rpc.commitment()),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+        let accounts = self
+            .rpc
+            .get_program_accounts_with_config(&self.program_id, config)
+            .await?;
+        accounts
+            .into_iter()
+            .map(|(address, account)| {
+                let score = decode(&address, &account, ScoreAccount::unpack_any_version)?;
+                Ok((address, score))
+            })
+            .collect()
+    }
+
+    // Fetch every follow edge whose follower is `player`
+    pub async fn get_follows(&self, player: &Pubkey) -> Result<Vec<Follow>, ScoreClientError> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(follow_filters(player)),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                commitment: Some(self.rpc.commitment()),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+        let accounts = self
+            .rpc
+            .get_program_accounts_with_config(&self.program_id, config)
+            .await?;
+        accounts
+            .into_iter()
+            .map(|(address, account)| decode(&address, &account, Follow::unpack))
+            .collect()
+    }
+
+    // Best scores of `player` and everyone they follow in one game mode, best
+    // first. Players without a score account in that mode are left out.
+    pub async fn get_friends_leaderboard(
+        &self,
+        player: &Pubkey,
+        game_mode: GameMode,
+    ) -> Result<Vec<(Pubkey, u64)>, ScoreClientError> {
+        let mut players = vec![*player];
+        players.extend(self.get_follows(player).await?.iter().map(|f| f.followed));
+        let addresses: Vec<Pubkey> = players
+            .iter()
+            .map(|p| find_score_address(&self.program_id, p, game_mode).0)
+            .collect();
+        let mut board = Vec::new();
+        // getMultipleAccounts takes at most 100 addresses per call
+        for (players, addresses) in players.chunks(100).zip(addresses.chunks(100)) {
+            let accounts = self.rpc.get_multiple_accounts(addresses).await?;
+            for ((player, address), account) in players.iter().zip(addresses).zip(accounts) {
+                if let Some(account) = account {
+                    let score = decode(address, &account, ScoreAccount::unpack_any_version)?;
+                    board.push((*player, score.best_score));
+                }
+            }
+        }
+        board.sort_by_key(|&(_, best)| std::cmp::Reverse(best));
+        Ok(board)
+    }
+
+    // The token program owning `mint`, SPL Token or Token-2022, which token
+    // instructions for that mint must name
+    pub async fn get_mint_token_program(&self, mint: &Pubkey) -> Result<Pubkey, ScoreClientError> {
+        Ok(self.rpc.get_account(mint).await?.owner)
+    }
+
+    // Sign the instructions with the payer and any extra signers, send them in one
+    // transaction and wait until it is confirmed
+    pub async fn send(
+        &self,
+        instructions: &[Instruction],
+        payer: &Keypair,
+        signers: &[&Keypair],
+    ) -> Result<Signature, ScoreClientError> {
+        let blockhash = self.rpc.get_latest_blockhash().await?;
+        let mut all_signers: Vec<&Keypair> = vec![payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+        Ok(self.rpc.send_and_confirm_transaction(&transaction).await?)
+    }
+}
+
+// getProgramAccounts filter matching the score account discriminator
+fn score_account_filters() -> Vec<RpcFilterType> {
+    vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+        0,
+        ScoreAccount::DISCRIMINATOR.to_vec(),
+    ))]
+}
+
+// getProgramAccounts filters matching the follow edges of one follower
+fn follow_filters(follower: &Pubkey) -> Vec<RpcFilterType> {
+    vec![
+        RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, Follow::DISCRIMINATOR.to_vec())),
+        RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+            Follow::FOLLOWER_OFFSET,
+            follower.to_bytes().to_vec(),
+        )),
+    ]
+}
+
+fn decode<T>(
+    address: &Pubkey,
+    account: &Account,
+    unpack: fn(&[u8]) -> Result<T, ProgramError>,
+) -> Result<T, ScoreClientError> {
+    unpack(&account.data).map_err(|_| ScoreClientError::InvalidAccountData(*address))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_score_accounts() {
+        let state = ScoreAccount {
+            is_initialized: true,
+            player: Pubkey::new_unique(),
+            score: 42,
+            version: ScoreAccount::VERSION,
+            ..ScoreAccount::default()
+        };
+        let mut account = Account {
+            data: vec![0; ScoreAccount::LEN],
+            ..Account::default()
+        };
+        state.pack(&mut account.data).unwrap();
+        let address = Pubkey::new_unique();
+        let unpack = ScoreAccount::unpack_any_version;
+        assert_eq!(decode(&address, &account, unpack).unwrap(), state);
+
+        account.data.truncate(10);
+        assert!(matches!(
+            decode(&address, &account, unpack),
+            Err(ScoreClientError::InvalidAccountData(key)) if key == address
+        ));
+    }
+}
@@ -0,0 +1,96 @@
+use std::error::Error;
+
+use solana_client::{
+    nonblocking::rpc_client::RpcClient, rpc_client::GetConfirmedSignaturesForAddress2Config,
+    rpc_config::RpcTransactionConfig,
+};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::UiTransactionEncoding;
+
+use crate::{
+    logs::parse_score_events,
+    store::{Store, BACKFILL_BEFORE, BACKFILL_NEWEST, BACKFILL_UNTIL},
+};
+
+// Signatures requested per `getSignaturesForAddress` call, the RPC maximum
+const PAGE_LIMIT: usize = 1_000;
+
+// Queued transactions fetched between checks of the queue
+const REPLAY_BATCH: usize = 100;
+
+// Rebuild score history from the program's finalized transactions. Signatures are
+// listed newest to oldest back to genesis, or to the newest one a previous backfill
+// saw, then replayed oldest first. Progress is checkpointed in the store after every
+// page and transaction, so an interrupted backfill picks up where it stopped.
+// Returns the number of transactions replayed.
+pub async fn backfill(
+    rpc: &RpcClient,
+    store: &mut Store,
+    program_id: &Pubkey,
+) -> Result<usize, Box<dyn Error>> {
+    let commitment = CommitmentConfig::finalized();
+    let until = parse_signature(store.checkpoint(BACKFILL_UNTIL)?)?;
+    loop {
+        let before = parse_signature(store.checkpoint(BACKFILL_BEFORE)?)?;
+        let config = GetConfirmedSignaturesForAddress2Config {
+            before,
+            until,
+            limit: Some(PAGE_LIMIT),
+            commitment: Some(commitment),
+        };
+        let page = rpc
+            .get_signatures_for_address_with_config(program_id, config)
+            .await?;
+        let (Some(newest), Some(oldest)) = (page.first(), page.last()) else {
+            break;
+        };
+        if before.is_none() {
+            store.set_checkpoint(BACKFILL_NEWEST, Some(&newest.signature))?;
+        }
+        // Failed transactions changed nothing, so they are never fetched
+        let succeeded: Vec<_> = page
+            .iter()
+            .filter(|status| status.err.is_none())
+            .map(|status| (status.signature.clone(), status.slot))
+            .collect();
+        store.queue_signatures(&succeeded, &oldest.signature)?;
+        println!(
+            "Listed {} signatures back to slot {}",
+            page.len(),
+            oldest.slot
+        );
+    }
+    store.finish_listing()?;
+
+    let config = RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Json),
+        commitment: Some(commitment),
+        max_supported_transaction_version: Some(0),
+    };
+    let mut replayed = 0;
+    loop {
+        let batch = store.oldest_queued(REPLAY_BATCH)?;
+        if batch.is_empty() {
+            break;
+        }
+        for queued in batch {
+            let signature: Signature = queued.signature.parse()?;
+            let transaction = rpc.get_transaction_with_config(&signature, config).await?;
+            let events = match transaction.transaction.meta {
+                Some(meta) if meta.err.is_none() => {
+                    let logs: Option<Vec<String>> = meta.log_messages.into();
+                    parse_score_events(program_id, &logs.unwrap_or_default())
+                }
+                _ => Vec::new(),
+            };
+            store.apply_backfilled(&queued, &events)?;
+            replayed += 1;
+        }
+        println!("Replayed {} transactions", replayed);
+    }
+    Ok(replayed)
+}
+
+fn parse_signature(signature: Option<String>) -> Result<Option<Signature>, Box<dyn Error>> {
+    Ok(signature.map(|s| s.parse()).transpose()?)
+}
@@ -0,0 +1,5 @@
+// Off-chain indexer for the score program: decodes the ScoreUpdated events it
+// logs and keeps a SQLite leaderboard table for the game website
+pub mod backfill;
+pub mod logs;
+pub mod store;
@@ -0,0 +1,75 @@
+use base64::{prelude::BASE64_STANDARD, Engine};
+use score_program::events::ScoreUpdated;
+use solana_sdk::pubkey::Pubkey;
+
+// Pull the ScoreUpdated events out of a transaction's log messages. Only
+// "Program data:" lines written while the score program itself is executing are
+// read, so another program cannot forge events by logging the same bytes.
+pub fn parse_score_events(program_id: &Pubkey, logs: &[String]) -> Vec<ScoreUpdated> {
+    let program = program_id.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+    for line in logs {
+        let Some(rest) = line.strip_prefix("Program ") else {
+            continue;
+        };
+        if let Some(data) = rest.strip_prefix("data: ") {
+            if stack.last() == Some(&program.as_str()) {
+                events.extend(
+                    BASE64_STANDARD
+                        .decode(data)
+                        .ok()
+                        .and_then(|data| ScoreUpdated::decode(&data)),
+                );
+            }
+        } else if let Some((id, status)) = rest.split_once(' ') {
+            if status.starts_with("invoke [") {
+                stack.push(id);
+            } else if status == "success" || status.starts_with("failed") {
+                stack.pop();
+            }
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use score_program::{events::ScoreChange, state::GameMode};
+
+    fn data_line(event: &ScoreUpdated) -> String {
+        let mut data = ScoreUpdated::DISCRIMINATOR.to_vec();
+        data.extend(borsh::to_vec(event).unwrap());
+        format!("Program data: {}", BASE64_STANDARD.encode(data))
+    }
+
+    #[test]
+    fn reads_only_events_logged_by_the_program() {
+        let program_id = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let event = ScoreUpdated {
+            player: Pubkey::new_unique(),
+            game_mode: GameMode::Arcade,
+            change: ScoreChange::Updated,
+            old: 10,
+            new: 25,
+            ts: 1_700_000_000,
+        };
+        let logs = vec![
+            format!("Program {} invoke [1]", other),
+            data_line(&event),
+            format!("Program {} success", other),
+            format!("Program {} invoke [1]", program_id),
+            "Program log: Score updated".to_string(),
+            data_line(&event),
+            "Program data: bm90IGFuIGV2ZW50".to_string(),
+            format!(
+                "Program {} consumed 4000 of 200000 compute units",
+                program_id
+            ),
+            format!("Program {} success", program_id),
+        ];
+        assert_eq!(parse_score_events(&program_id, &logs), vec![event]);
+    }
+}
@@ -0,0 +1,139 @@
+// Follows the score program over websocket RPC and keeps a SQLite leaderboard for
+// the game website. Events are queued as soon as they are processed and only
+// applied once their slot is rooted, so forks that get abandoned never show up.
+use std::error::Error;
+
+use clap::{Parser, Subcommand};
+use futures_util::StreamExt;
+use garbagegrab_indexer::{backfill::backfill, logs::parse_score_events, store::Store};
+use solana_cli_config::{Config as CliConfig, CONFIG_FILE};
+use solana_client::{
+    nonblocking::{pubsub_client::PubsubClient, rpc_client::RpcClient},
+    rpc_config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter},
+};
+use solana_sdk::{clock::Slot, commitment_config::CommitmentConfig, pubkey::Pubkey};
+
+// Address the score program is deployed at on the Gorbagana testnet
+const DEFAULT_PROGRAM_ID: &str = "6t7gLJEudrC9JNw8ZXSnnwyMgmofdGxtQVQErA67nxhN";
+
+#[derive(Parser)]
+#[command(
+    name = "garbagegrab-indexer",
+    about = "Index Garbage Grab score events into a SQLite leaderboard"
+)]
+struct Cli {
+    /// Solana CLI config file [default: ~/.config/solana/cli/config.yml]
+    #[arg(long, global = true)]
+    config: Option<String>,
+
+    /// RPC URL, overriding the config file
+    #[arg(long, short = 'u', global = true)]
+    url: Option<String>,
+
+    /// Websocket URL, overriding the config file
+    #[arg(long, global = true)]
+    ws_url: Option<String>,
+
+    /// Score program address
+    #[arg(long, global = true, default_value = DEFAULT_PROGRAM_ID)]
+    program_id: Pubkey,
+
+    /// SQLite database file, created if missing
+    #[arg(long, global = true, default_value = "garbagegrab-index.sqlite")]
+    db: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Follow new score events as they land
+    Watch,
+    /// Replay the program's transaction history from genesis, resuming an
+    /// interrupted backfill; run it before `watch` on a fresh database
+    Backfill,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+
+    let cli_config = match cli.config.as_ref().or(CONFIG_FILE.as_ref()) {
+        Some(path) => CliConfig::load(path).unwrap_or_default(),
+        None => CliConfig::default(),
+    };
+    let url = cli.url.unwrap_or(cli_config.json_rpc_url);
+    let ws_url = cli.ws_url.unwrap_or_else(|| {
+        if cli_config.websocket_url.is_empty() {
+            CliConfig::compute_websocket_url(&url)
+        } else {
+            cli_config.websocket_url
+        }
+    });
+
+    let mut store = Store::open(&cli.db)?;
+    let rpc = RpcClient::new_with_commitment(url, CommitmentConfig::finalized());
+    match cli.command {
+        Command::Watch => watch(&rpc, &ws_url, &mut store, &cli.program_id, &cli.db).await,
+        Command::Backfill => {
+            let replayed = backfill(&rpc, &mut store, &cli.program_id).await?;
+            println!("Backfill complete: {} transactions replayed", replayed);
+            Ok(())
+        }
+    }
+}
+
+async fn watch(
+    rpc: &RpcClient,
+    ws_url: &str,
+    store: &mut Store,
+    program_id: &Pubkey,
+    db: &str,
+) -> Result<(), Box<dyn Error>> {
+    let pubsub = PubsubClient::new(ws_url).await?;
+    let (mut logs, _logs_unsubscribe) = pubsub
+        .logs_subscribe(
+            RpcTransactionLogsFilter::Mentions(vec![program_id.to_string()]),
+            RpcTransactionLogsConfig {
+                commitment: Some(CommitmentConfig::processed()),
+            },
+        )
+        .await?;
+    let (mut roots, _roots_unsubscribe) = pubsub.root_subscribe().await?;
+    println!("Indexing {} into {}", program_id, db);
+
+    loop {
+        tokio::select! {
+            Some(response) = logs.next() => {
+                // Failed transactions still log, but none of their changes landed
+                if response.value.err.is_some() {
+                    continue;
+                }
+                let events = parse_score_events(program_id, &response.value.logs);
+                if !events.is_empty() {
+                    store.record_pending(response.context.slot, &response.value.signature, &events)?;
+                }
+            }
+            Some(root) = roots.next() => {
+                settle(rpc, store, root).await?;
+            }
+            else => return Err("Websocket subscription closed".into()),
+        }
+    }
+}
+
+// Apply the pending events of every slot the cluster has rooted up to `root`
+async fn settle(rpc: &RpcClient, store: &mut Store, root: Slot) -> Result<(), Box<dyn Error>> {
+    let start = match store.last_root()? {
+        Some(last) if last >= root => return Ok(()),
+        Some(last) => last + 1,
+        None => store.first_pending_slot()?.unwrap_or(root).min(root),
+    };
+    let finalized = rpc.get_blocks(start, Some(root)).await?;
+    let applied = store.finalize(root, &finalized)?;
+    if applied > 0 {
+        println!("Root {}: applied {} score events", root, applied);
+    }
+    Ok(())
+}
@@ -0,0 +1,446 @@
+use std::{collections::HashSet, path::Path};
+
+use rusqlite::{params, Connection, OptionalExtension};
+use score_program::{
+    events::{ScoreChange, ScoreUpdated},
+    state::GameMode,
+};
+use solana_sdk::{clock::Slot, pubkey::Pubkey};
+
+pub use rusqlite::Error as StoreError;
+
+// Events seen at `processed` commitment wait in `pending_events` until their slot
+// is rooted. Those from slots on the finalized chain are then applied to
+// `leaderboard`; those from abandoned forks are dropped.
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS pending_events (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        slot INTEGER NOT NULL,
+        signature TEXT NOT NULL,
+        player TEXT NOT NULL,
+        game_mode INTEGER NOT NULL,
+        change INTEGER NOT NULL,
+        old_score INTEGER NOT NULL,
+        new_score INTEGER NOT NULL,
+        ts INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS pending_events_slot ON pending_events (slot);
+    CREATE TABLE IF NOT EXISTS leaderboard (
+        player TEXT NOT NULL,
+        game_mode INTEGER NOT NULL,
+        score INTEGER NOT NULL,
+        best_score INTEGER NOT NULL,
+        updated_ts INTEGER NOT NULL,
+        slot INTEGER NOT NULL,
+        PRIMARY KEY (player, game_mode)
+    );
+    CREATE INDEX IF NOT EXISTS leaderboard_best ON leaderboard (game_mode, best_score DESC);
+    CREATE TABLE IF NOT EXISTS cursor (
+        name TEXT PRIMARY KEY,
+        slot INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS backfill_queue (
+        seq INTEGER PRIMARY KEY AUTOINCREMENT,
+        signature TEXT NOT NULL UNIQUE,
+        slot INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS checkpoint (
+        name TEXT PRIMARY KEY,
+        signature TEXT NOT NULL
+    );
+";
+
+// Backfill progress, kept so an interrupted backfill resumes where it stopped:
+// the oldest signature listed so far, the newest one of the listing in progress,
+// and the newest one of the last listing that completed
+pub const BACKFILL_BEFORE: &str = "backfill_before";
+pub const BACKFILL_NEWEST: &str = "backfill_newest";
+pub const BACKFILL_UNTIL: &str = "backfill_until";
+
+// A transaction signature waiting to be fetched and replayed by the backfill
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedSignature {
+    pub seq: i64,
+    pub signature: String,
+    pub slot: Slot,
+}
+
+// One row of the finalized leaderboard
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeaderboardRow {
+    pub player: Pubkey,
+    pub score: u64,
+    pub best_score: u64,
+    pub updated_ts: i64,
+}
+
+// SQLite database the indexer keeps the website's leaderboard in
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, StoreError> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self, StoreError> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    // Last slot whose events have been finalized, or `None` on a fresh database
+    pub fn last_root(&self) -> Result<Option<Slot>, StoreError> {
+        self.conn
+            .query_row("SELECT slot FROM cursor WHERE name = 'root'", [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .optional()
+            .map(|slot| slot.map(|slot| slot as Slot))
+    }
+
+    // Lowest slot with events still waiting to be finalized
+    pub fn first_pending_slot(&self) -> Result<Option<Slot>, StoreError> {
+        self.conn
+            .query_row("SELECT MIN(slot) FROM pending_events", [], |row| {
+                row.get::<_, Option<i64>>(0)
+            })
+            .map(|slot| slot.map(|slot| slot as Slot))
+    }
+
+    // Queue the events of one transaction seen at `slot` until that slot is rooted
+    pub fn record_pending(
+        &mut self,
+        slot: Slot,
+        signature: &str,
+        events: &[ScoreUpdated],
+    ) -> Result<(), StoreError> {
+        let tx = self.conn.transaction()?;
+        for event in events {
+            tx.execute(
+                "INSERT INTO pending_events
+                    (slot, signature, player, game_mode, change, old_score, new_score, ts)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    slot as i64,
+                    signature,
+                    event.player.to_string(),
+                    event.game_mode as u8,
+                    event.change as u8,
+                    event.old as i64,
+                    event.new as i64,
+                    event.ts,
+                ],
+            )?;
+        }
+        tx.commit()
+    }
+
+    // Settle every pending event up to `root`. `finalized_slots` lists the slots
+    // of the finalized chain since the previous root; events from any other slot
+    // were on an abandoned fork and are discarded. Returns how many were applied.
+    pub fn finalize(&mut self, root: Slot, finalized_slots: &[Slot]) -> Result<usize, StoreError> {
+        let finalized: HashSet<Slot> = finalized_slots.iter().copied().collect();
+        let tx = self.conn.transaction()?;
+        let events: Vec<(Slot, ScoreUpdated)> = {
+            let mut stmt = tx.prepare(
+                "SELECT slot, player, game_mode, change, old_score, new_score, ts
+                 FROM pending_events WHERE slot <= ?1 ORDER BY slot, id",
+            )?;
+            let rows = stmt.query_map([root as i64], |row| {
+                Ok((row.get::<_, i64>(0)? as Slot, event_from_row(row)?))
+            })?;
+            rows.collect::<Result<_, _>>()?
+        };
+
+        let mut applied = 0;
+        for (slot, event) in events.iter().filter(|(slot, _)| finalized.contains(slot)) {
+            apply(&tx, *slot, event)?;
+            applied += 1;
+        }
+        tx.execute("DELETE FROM pending_events WHERE slot <= ?1", [root as i64])?;
+        tx.execute(
+            "INSERT INTO cursor (name, slot) VALUES ('root', ?1)
+             ON CONFLICT (name) DO UPDATE SET slot = excluded.slot",
+            [root as i64],
+        )?;
+        tx.commit()?;
+        Ok(applied)
+    }
+
+    pub fn checkpoint(&self, name: &str) -> Result<Option<String>, StoreError> {
+        self.conn
+            .query_row(
+                "SELECT signature FROM checkpoint WHERE name = ?1",
+                [name],
+                |row| row.get(0),
+            )
+            .optional()
+    }
+
+    pub fn set_checkpoint(
+        &mut self,
+        name: &str,
+        signature: Option<&str>,
+    ) -> Result<(), StoreError> {
+        set_checkpoint(&self.conn, name, signature)
+    }
+
+    // Queue the signatures of one `getSignaturesForAddress` page, newest first, and
+    // remember the page's oldest signature so listing resumes after it
+    pub fn queue_signatures(
+        &mut self,
+        signatures: &[(String, Slot)],
+        oldest: &str,
+    ) -> Result<(), StoreError> {
+        let tx = self.conn.transaction()?;
+        for (signature, slot) in signatures {
+            tx.execute(
+                "INSERT OR IGNORE INTO backfill_queue (signature, slot) VALUES (?1, ?2)",
+                params![signature, *slot as i64],
+            )?;
+        }
+        set_checkpoint(&tx, BACKFILL_BEFORE, Some(oldest))?;
+        tx.commit()
+    }
+
+    // Mark the listing complete: the next backfill only lists signatures newer than
+    // the newest one seen by this one
+    pub fn finish_listing(&mut self) -> Result<(), StoreError> {
+        let tx = self.conn.transaction()?;
+        let newest: Option<String> = tx
+            .query_row(
+                "SELECT signature FROM checkpoint WHERE name = ?1",
+                [BACKFILL_NEWEST],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if let Some(newest) = newest {
+            set_checkpoint(&tx, BACKFILL_UNTIL, Some(&newest))?;
+        }
+        set_checkpoint(&tx, BACKFILL_NEWEST, None)?;
+        set_checkpoint(&tx, BACKFILL_BEFORE, None)?;
+        tx.commit()
+    }
+
+    // The oldest `limit` queued signatures. Within a slot, pages list the newest
+    // first, so later inserted rows are older.
+    pub fn oldest_queued(&self, limit: usize) -> Result<Vec<QueuedSignature>, StoreError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT seq, signature, slot FROM backfill_queue
+             ORDER BY slot ASC, seq DESC LIMIT ?1",
+        )?;
+        let rows = stmt.query_map([limit as i64], |row| {
+            Ok(QueuedSignature {
+                seq: row.get(0)?,
+                signature: row.get(1)?,
+                slot: row.get::<_, i64>(2)? as Slot,
+            })
+        })?;
+        rows.collect()
+    }
+
+    // Apply the events of one finalized historical transaction and dequeue it
+    pub fn apply_backfilled(
+        &mut self,
+        queued: &QueuedSignature,
+        events: &[ScoreUpdated],
+    ) -> Result<(), StoreError> {
+        let tx = self.conn.transaction()?;
+        for event in events {
+            apply(&tx, queued.slot, event)?;
+        }
+        tx.execute("DELETE FROM backfill_queue WHERE seq = ?1", [queued.seq])?;
+        tx.commit()
+    }
+
+    // Top `limit` players of a mode by best score
+    pub fn leaderboard(
+        &self,
+        game_mode: GameMode,
+        limit: usize,
+    ) -> Result<Vec<LeaderboardRow>, StoreError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT player, score, best_score, updated_ts FROM leaderboard
+             WHERE game_mode = ?1 ORDER BY best_score DESC, slot ASC LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![game_mode as u8, limit as i64], |row| {
+            Ok(LeaderboardRow {
+                player: parse_pubkey(row.get::<_, String>(0)?)?,
+                score: row.get::<_, i64>(1)? as u64,
+                best_score: row.get::<_, i64>(2)? as u64,
+                updated_ts: row.get(3)?,
+            })
+        })?;
+        rows.collect()
+    }
+}
+
+// Fold one finalized event into the player's leaderboard row
+fn apply(conn: &Connection, slot: Slot, event: &ScoreUpdated) -> Result<(), StoreError> {
+    let player = event.player.to_string();
+    let game_mode = event.game_mode as u8;
+    match event.change {
+        ScoreChange::Closed => {
+            conn.execute(
+                "DELETE FROM leaderboard WHERE player = ?1 AND game_mode = ?2",
+                params![player, game_mode],
+            )?;
+        }
+        ScoreChange::Initialized | ScoreChange::Updated | ScoreChange::Reset => {
+            conn.execute(
+                "INSERT INTO leaderboard (player, game_mode, score, best_score, updated_ts, slot)
+                 VALUES (?1, ?2, ?3, ?3, ?4, ?5)
+                 ON CONFLICT (player, game_mode) DO UPDATE SET
+                    score = excluded.score,
+                    best_score = MAX(best_score, excluded.score),
+                    updated_ts = excluded.updated_ts,
+                    slot = excluded.slot",
+                params![player, game_mode, event.new as i64, event.ts, slot as i64],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn set_checkpoint(
+    conn: &Connection,
+    name: &str,
+    signature: Option<&str>,
+) -> Result<(), StoreError> {
+    match signature {
+        Some(signature) => conn.execute(
+            "INSERT INTO checkpoint (name, signature) VALUES (?1, ?2)
+             ON CONFLICT (name) DO UPDATE SET signature = excluded.signature",
+            [name, signature],
+        )?,
+        None => conn.execute("DELETE FROM checkpoint WHERE name = ?1", [name])?,
+    };
+    Ok(())
+}
+
+fn event_from_row(row: &rusqlite::Row) -> Result<ScoreUpdated, StoreError> {
+    let game_mode = match row.get::<_, u8>(2)? {
+        0 => GameMode::Arcade,
+        1 => GameMode::Endless,
+        _ => GameMode::DailyChallenge,
+    };
+    let change = match row.get::<_, u8>(3)? {
+        0 => ScoreChange::Initialized,
+        1 => ScoreChange::Updated,
+        2 => ScoreChange::Reset,
+        _ => ScoreChange::Closed,
+    };
+    Ok(ScoreUpdated {
+        player: parse_pubkey(row.get::<_, String>(1)?)?,
+        game_mode,
+        change,
+        old: row.get::<_, i64>(4)? as u64,
+        new: row.get::<_, i64>(5)? as u64,
+        ts: row.get(6)?,
+    })
+}
+
+fn parse_pubkey(text: String) -> Result<Pubkey, StoreError> {
+    text.parse().map_err(|e| {
+        StoreError::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(player: Pubkey, change: ScoreChange, new: u64) -> ScoreUpdated {
+        ScoreUpdated {
+            player,
+            game_mode: GameMode::Arcade,
+            change,
+            old: 0,
+            new,
+            ts: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn backfill_queue_replays_oldest_first() {
+        let mut store = Store::open_in_memory().unwrap();
+        let page = |sigs: &[(&str, Slot)]| -> Vec<(String, Slot)> {
+            sigs.iter()
+                .map(|(s, slot)| (s.to_string(), *slot))
+                .collect()
+        };
+        store.set_checkpoint(BACKFILL_NEWEST, Some("sig4")).unwrap();
+        store
+            .queue_signatures(&page(&[("sig4", 40), ("sig3", 30)]), "sig3")
+            .unwrap();
+        store
+            .queue_signatures(&page(&[("sig2", 20), ("sig1", 10)]), "sig1")
+            .unwrap();
+        assert_eq!(
+            store.checkpoint(BACKFILL_BEFORE).unwrap().as_deref(),
+            Some("sig1")
+        );
+
+        let player = Pubkey::new_unique();
+        let queued = store.oldest_queued(3).unwrap();
+        let sigs: Vec<&str> = queued.iter().map(|q| q.signature.as_str()).collect();
+        assert_eq!(sigs, vec!["sig1", "sig2", "sig3"]);
+        store
+            .apply_backfilled(&queued[0], &[event(player, ScoreChange::Updated, 70)])
+            .unwrap();
+        assert_eq!(store.oldest_queued(1).unwrap()[0].signature, "sig2");
+        assert_eq!(
+            store.leaderboard(GameMode::Arcade, 1).unwrap()[0].best_score,
+            70
+        );
+
+        store.finish_listing().unwrap();
+        assert_eq!(store.checkpoint(BACKFILL_BEFORE).unwrap(), None);
+        assert_eq!(
+            store.checkpoint(BACKFILL_UNTIL).unwrap().as_deref(),
+            Some("sig4")
+        );
+    }
+
+    #[test]
+    fn only_finalized_slots_reach_the_leaderboard() {
+        let mut store = Store::open_in_memory().unwrap();
+        let [a, b] = [Pubkey::new_unique(), Pubkey::new_unique()];
+        store
+            .record_pending(10, "sig1", &[event(a, ScoreChange::Updated, 50)])
+            .unwrap();
+        // Slot 11 is on a fork that never gets rooted
+        store
+            .record_pending(11, "sig2", &[event(b, ScoreChange::Updated, 90)])
+            .unwrap();
+        store
+            .record_pending(12, "sig3", &[event(a, ScoreChange::Reset, 0)])
+            .unwrap();
+        store
+            .record_pending(14, "sig4", &[event(b, ScoreChange::Updated, 30)])
+            .unwrap();
+
+        assert_eq!(store.finalize(13, &[10, 12, 13]).unwrap(), 2);
+        assert_eq!(store.last_root().unwrap(), Some(13));
+        assert_eq!(store.first_pending_slot().unwrap(), Some(14));
+        let board = store.leaderboard(GameMode::Arcade, 10).unwrap();
+        assert_eq!(board.len(), 1);
+        assert_eq!(
+            (board[0].player, board[0].score, board[0].best_score),
+            (a, 0, 50)
+        );
+
+        store
+            .record_pending(15, "sig5", &[event(a, ScoreChange::Closed, 0)])
+            .unwrap();
+        assert_eq!(store.finalize(15, &[14, 15]).unwrap(), 2);
+        let board = store.leaderboard(GameMode::Arcade, 10).unwrap();
+        assert_eq!(board.len(), 1);
+        assert_eq!((board[0].player, board[0].best_score), (b, 30));
+    }
+}
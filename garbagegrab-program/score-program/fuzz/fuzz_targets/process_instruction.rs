@@ -0,0 +1,54 @@
+// Coverage-guided counterpart to tests/fuzz.rs. Run with
+// `cargo +nightly fuzz run process_instruction` from score-program.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use score_program::process_instruction;
+use solana_program::{account_info::AccountInfo, pubkey::Pubkey, system_program};
+
+const PROGRAM_ID: Pubkey = Pubkey::new_from_array([7; 32]);
+
+// Input layout: account count, then per account a flags byte, a data length
+// byte and that many data bytes; whatever is left is the instruction data
+fuzz_target!(|input: &[u8]| {
+    let Some((&count, mut rest)) = input.split_first() else {
+        return;
+    };
+
+    let mut accounts = Vec::new();
+    for index in 0..count % 8 {
+        let Some((&flags, tail)) = rest.split_first() else {
+            break;
+        };
+        let Some((&len, tail)) = tail.split_first() else {
+            break;
+        };
+        let len = (len as usize).min(tail.len());
+        let key = Pubkey::new_from_array([index; 32]);
+        accounts.push((key, flags, 1_000_000u64, tail[..len].to_vec()));
+        rest = &tail[len..];
+    }
+
+    let system = system_program::id();
+    let infos: Vec<AccountInfo> = accounts
+        .iter_mut()
+        .map(|(key, flags, lamports, data)| {
+            let owner = if *flags & 4 != 0 {
+                &PROGRAM_ID
+            } else {
+                &system
+            };
+            AccountInfo::new(
+                key,
+                *flags & 1 != 0,
+                *flags & 2 != 0,
+                lamports,
+                data,
+                owner,
+                false,
+                0,
+            )
+        })
+        .collect();
+    let _ = process_instruction(&PROGRAM_ID, &infos, rest);
+});
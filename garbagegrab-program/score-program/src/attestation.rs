@@ -0,0 +1,173 @@
+use solana_program::{
+    account_info::AccountInfo,
+    ed25519_program,
+    entrypoint::ProgramResult,
+    instruction::Instruction,
+    msg,
+    pubkey::Pubkey,
+    sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+};
+
+use crate::{error::GarbageGrabError, state::GameMode};
+
+// Layout of the ed25519 precompile instruction data: a signature count, one byte
+// of padding, then seven little-endian u16 offsets per signature
+const OFFSETS_START: usize = 2;
+const OFFSETS_LEN: usize = 14;
+const PUBKEY_LEN: usize = 32;
+
+// Instruction index the precompile uses to mean "data lives in this instruction"
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+// Domain prefix so score attestations can't be confused with other signed payloads
+const SCORE_ATTESTATION_PREFIX: &[u8] = b"garbagegrab:score";
+
+// Message the game server signs to vouch for a score increment. The nonce ties
+// the signature to a single submission so it cannot be replayed.
+pub fn score_attestation_message(
+    player: &Pubkey,
+    game_mode: GameMode,
+    increment: u64,
+    nonce: u64,
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(SCORE_ATTESTATION_PREFIX.len() + 32 + 1 + 8 + 8);
+    message.extend_from_slice(SCORE_ATTESTATION_PREFIX);
+    message.extend_from_slice(player.as_ref());
+    message.extend_from_slice(&game_mode.seed());
+    message.extend_from_slice(&increment.to_le_bytes());
+    message.extend_from_slice(&nonce.to_le_bytes());
+    message
+}
+
+// Check that the instruction right before the current one verifies `message`
+// signed by `signer` through the ed25519 precompile
+pub fn verify_preceding_ed25519(
+    instructions_sysvar: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> ProgramResult {
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    if current_index == 0 {
+        msg!("Missing ed25519 verification instruction");
+        return Err(GarbageGrabError::InvalidAttestation.into());
+    }
+    let ed25519_ix = load_instruction_at_checked(current_index as usize - 1, instructions_sysvar)?;
+    verify_ed25519_instruction(&ed25519_ix, signer, message)
+}
+
+// Check an ed25519 precompile instruction covers exactly one signature by `signer` over `message`
+pub fn verify_ed25519_instruction(
+    ix: &Instruction,
+    signer: &Pubkey,
+    message: &[u8],
+) -> ProgramResult {
+    if ix.program_id != ed25519_program::id() {
+        msg!("Attestation instruction is not an ed25519 verification");
+        return Err(GarbageGrabError::InvalidAttestation.into());
+    }
+
+    let data = &ix.data;
+    if data.len() < OFFSETS_START + OFFSETS_LEN || data[0] != 1 {
+        msg!("Attestation must carry exactly one signature");
+        return Err(GarbageGrabError::InvalidAttestation.into());
+    }
+    let offset = |field: usize| {
+        let at = OFFSETS_START + field * 2;
+        u16::from_le_bytes([data[at], data[at + 1]])
+    };
+    let signature_ix = offset(1);
+    let pubkey_offset = offset(2) as usize;
+    let pubkey_ix = offset(3);
+    let message_offset = offset(4) as usize;
+    let message_size = offset(5) as usize;
+    let message_ix = offset(6);
+
+    // Signature, key and message must all be read from the precompile instruction itself
+    if signature_ix != CURRENT_INSTRUCTION
+        || pubkey_ix != CURRENT_INSTRUCTION
+        || message_ix != CURRENT_INSTRUCTION
+    {
+        msg!("Attestation references data outside the ed25519 instruction");
+        return Err(GarbageGrabError::InvalidAttestation.into());
+    }
+
+    let attested_signer = data.get(pubkey_offset..pubkey_offset + PUBKEY_LEN);
+    if attested_signer != Some(signer.as_ref()) {
+        msg!("Attestation was not signed by the game server {}", signer);
+        return Err(GarbageGrabError::InvalidAttestation.into());
+    }
+
+    let attested_message = data.get(message_offset..message_offset + message_size);
+    if attested_message != Some(message) {
+        msg!("Attested message does not match the submitted score");
+        return Err(GarbageGrabError::InvalidAttestation.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::program_error::ProgramError;
+
+    // Lay out an ed25519 precompile instruction the same way the SDK helper does
+    fn ed25519_ix(signer: &Pubkey, message: &[u8], data_ix: u16) -> Instruction {
+        let pubkey_offset = (OFFSETS_START + OFFSETS_LEN) as u16;
+        let signature_offset = pubkey_offset + PUBKEY_LEN as u16;
+        let message_offset = signature_offset + 64;
+
+        let mut data = vec![1, 0];
+        for field in [
+            signature_offset,
+            data_ix,
+            pubkey_offset,
+            data_ix,
+            message_offset,
+            message.len() as u16,
+            data_ix,
+        ] {
+            data.extend_from_slice(&field.to_le_bytes());
+        }
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(&[7; 64]);
+        data.extend_from_slice(message);
+        Instruction::new_with_bytes(ed25519_program::id(), &data, vec![])
+    }
+
+    #[test]
+    fn accepts_matching_attestation() {
+        let server = Pubkey::new_unique();
+        let message = score_attestation_message(&Pubkey::new_unique(), GameMode::Arcade, 25, 0);
+        let ix = ed25519_ix(&server, &message, CURRENT_INSTRUCTION);
+        assert_eq!(verify_ed25519_instruction(&ix, &server, &message), Ok(()));
+    }
+
+    #[test]
+    fn rejects_wrong_signer_or_message() {
+        let server = Pubkey::new_unique();
+        let player = Pubkey::new_unique();
+        let message = score_attestation_message(&player, GameMode::Arcade, 25, 0);
+        let ix = ed25519_ix(&server, &message, CURRENT_INSTRUCTION);
+        let invalid = Err(ProgramError::from(GarbageGrabError::InvalidAttestation));
+
+        assert_eq!(
+            verify_ed25519_instruction(&ix, &Pubkey::new_unique(), &message),
+            invalid
+        );
+        let inflated = score_attestation_message(&player, GameMode::Arcade, 2_500, 0);
+        assert_eq!(verify_ed25519_instruction(&ix, &server, &inflated), invalid);
+        let replayed = score_attestation_message(&player, GameMode::Arcade, 25, 1);
+        assert_eq!(verify_ed25519_instruction(&ix, &server, &replayed), invalid);
+    }
+
+    #[test]
+    fn rejects_data_from_other_instructions() {
+        let server = Pubkey::new_unique();
+        let message = score_attestation_message(&Pubkey::new_unique(), GameMode::Endless, 10, 3);
+        let ix = ed25519_ix(&server, &message, 0);
+        assert_eq!(
+            verify_ed25519_instruction(&ix, &server, &message),
+            Err(GarbageGrabError::InvalidAttestation.into())
+        );
+    }
+}
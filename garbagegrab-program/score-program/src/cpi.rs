@@ -0,0 +1,98 @@
+// Helpers for other programs to invoke the score program. Depend on the crate
+// with the `cpi` feature, which also leaves out its entrypoint, and pass the
+// score program's account along with the instruction's own.
+
+use solana_program::{
+    account_info::AccountInfo,
+    instruction::{AccountMeta, Instruction},
+    msg,
+    program::{get_return_data, invoke},
+    program_error::ProgramError,
+};
+
+use crate::{instruction::GarbageGrabInstruction, state::CompressedScore};
+
+// Read the score the last call returned, making sure the score program set it
+fn returned_score(score_program: &AccountInfo) -> Result<u64, ProgramError> {
+    match get_return_data() {
+        Some((program_id, data)) if program_id == *score_program.key => {
+            let bytes = data
+                .try_into()
+                .map_err(|_| ProgramError::InvalidAccountData)?;
+            Ok(u64::from_le_bytes(bytes))
+        }
+        _ => {
+            msg!("Score program {} returned no score", score_program.key);
+            Err(ProgramError::InvalidAccountData)
+        }
+    }
+}
+
+// Fail with ScoreBelowThreshold unless the player's score is at least
+// `threshold`, returning the score otherwise
+pub fn verify_score_at_least<'a>(
+    score_program: &AccountInfo<'a>,
+    score_account: &AccountInfo<'a>,
+    player: &AccountInfo<'a>,
+    config: &AccountInfo<'a>,
+    season: &AccountInfo<'a>,
+    threshold: u64,
+) -> Result<u64, ProgramError> {
+    invoke(
+        &Instruction {
+            program_id: *score_program.key,
+            accounts: vec![
+                AccountMeta::new_readonly(*score_account.key, false),
+                AccountMeta::new_readonly(*player.key, false),
+                AccountMeta::new_readonly(*config.key, false),
+                AccountMeta::new_readonly(*season.key, false),
+            ],
+            data: GarbageGrabInstruction::VerifyScoreAtLeast { threshold }.pack(),
+        },
+        &[
+            score_account.clone(),
+            player.clone(),
+            config.clone(),
+            season.clone(),
+        ],
+    )?;
+    returned_score(score_program)
+}
+
+// Fail unless `score` is a leaf of the tree under `root`, returning its score.
+// `proof` holds the proof nodes, lowest level first.
+pub fn verify_compressed_score<'a>(
+    score_program: &AccountInfo<'a>,
+    score_tree: &AccountInfo<'a>,
+    merkle_tree: &AccountInfo<'a>,
+    compression_program: &AccountInfo<'a>,
+    proof: &[AccountInfo<'a>],
+    root: [u8; 32],
+    score: CompressedScore,
+) -> Result<u64, ProgramError> {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*score_tree.key, false),
+        AccountMeta::new_readonly(*merkle_tree.key, false),
+        AccountMeta::new_readonly(*compression_program.key, false),
+    ];
+    accounts.extend(
+        proof
+            .iter()
+            .map(|node| AccountMeta::new_readonly(*node.key, false)),
+    );
+    let mut infos = vec![
+        score_tree.clone(),
+        merkle_tree.clone(),
+        compression_program.clone(),
+    ];
+    infos.extend(proof.iter().cloned());
+    invoke(
+        &Instruction {
+            program_id: *score_program.key,
+            accounts,
+            data: GarbageGrabInstruction::VerifyCompressedScore { root, score }.pack(),
+        },
+        &infos,
+    )?;
+    returned_score(score_program)
+}
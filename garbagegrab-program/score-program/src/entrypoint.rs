@@ -0,0 +1,6 @@
+use solana_program::entrypoint;
+
+use crate::processor::process_instruction;
+
+// Define the entrypoint, unless another program is linking the crate
+entrypoint!(process_instruction);
@@ -0,0 +1,220 @@
+use solana_program::program_error::ProgramError;
+use thiserror::Error;
+
+// Errors returned by the score program, surfaced to clients as `ProgramError::Custom(code)`
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum GarbageGrabError {
+    #[error("Adding the increment would overflow the score")]
+    ScoreOverflow = 0,
+    #[error("Score exceeds the maximum allowed value")]
+    ScoreTooLarge = 1,
+    #[error("Account does not match the expected program-derived address")]
+    InvalidPda = 2,
+    #[error("Score account is not initialized")]
+    NotInitialized = 3,
+    #[error("Score account is already initialized")]
+    AlreadyInitialized = 4,
+    #[error("Signer is not authorized for this account")]
+    Unauthorized = 5,
+    #[error("Season must end after it starts")]
+    InvalidSeasonWindow = 6,
+    #[error("Config values are out of range")]
+    InvalidConfig = 7,
+    #[error("Program is paused")]
+    ProgramPaused = 8,
+    #[error("Increment exceeds the maximum allowed per update")]
+    IncrementTooLarge = 9,
+    #[error("Score updated again before the cooldown elapsed")]
+    UpdateTooSoon = 10,
+    #[error("Score submission lacks a valid game server attestation")]
+    InvalidAttestation = 11,
+    #[error("Submission nonce does not match the score account")]
+    InvalidNonce = 12,
+    #[error("Penalty exceeds the current score")]
+    ScoreUnderflow = 13,
+    #[error("Score account uses an old layout and must be migrated")]
+    AccountNeedsMigration = 14,
+    #[error("No unclaimed rewards for this score")]
+    NothingToClaim = 15,
+    #[error("Token account does not match the expected account")]
+    InvalidTokenAccount = 16,
+    #[error("Mint does not match the configured reward mint")]
+    InvalidMint = 17,
+    #[error("A ranked run is already in progress")]
+    RankedRunInProgress = 18,
+    #[error("No ranked run has been started")]
+    NoRankedRun = 19,
+    #[error("Season has not ended yet")]
+    SeasonNotOver = 20,
+    #[error("Prizes for this season were already distributed")]
+    PrizesAlreadyDistributed = 21,
+    #[error("Match is not in the right state for this instruction")]
+    InvalidMatchState = 22,
+    #[error("Winner must be one of the match's players")]
+    InvalidWinner = 23,
+    #[error("Tournament has no free places")]
+    TournamentFull = 24,
+    #[error("Tournament is closed to registrations and scores")]
+    TournamentClosed = 25,
+    #[error("Player is already registered for this tournament")]
+    AlreadyRegistered = 26,
+    #[error("Player is not registered for this tournament")]
+    NotRegistered = 27,
+    #[error("Tournament has not ended yet")]
+    TournamentInProgress = 28,
+    #[error("Achievement is already unlocked")]
+    AchievementAlreadyUnlocked = 29,
+    #[error("Player already checked in today")]
+    AlreadyCheckedIn = 30,
+    #[error("Token account does not hold an NFT from the boost collection")]
+    InvalidBoostNft = 31,
+    #[error("Best score has not improved enough since the last trophy")]
+    TrophyNotEarned = 32,
+    #[error("Profile names are 1-32 ASCII letters, digits, '_' or '-'")]
+    InvalidProfileName = 33,
+    #[error("Profile name is already taken")]
+    NameTaken = 34,
+    #[error("Player has not earned their referrer any credit")]
+    ReferralNotEarned = 35,
+    #[error("Player already belongs to a guild")]
+    AlreadyInGuild = 36,
+    #[error("Guild has no room for another member")]
+    GuildFull = 37,
+    #[error("Player is not a member of this guild")]
+    NotGuildMember = 38,
+    #[error("Teams need 2-4 distinct members who all sign")]
+    InvalidTeam = 39,
+    #[error("Signer is not a member of this team")]
+    NotTeamMember = 40,
+    #[error("Signer is not the score account's approved delegate")]
+    NotDelegate = 41,
+    #[error("Session has expired")]
+    SessionExpired = 42,
+    #[error("Merkle proof does not match the airdrop root")]
+    InvalidMerkleProof = 43,
+    #[error("Airdrop index has already been claimed")]
+    AirdropAlreadyClaimed = 44,
+    #[error("Player is banned")]
+    PlayerBanned = 45,
+    #[error("Signer is not a member of the admin set")]
+    NotAdminSetMember = 46,
+    #[error("Proposal has fewer approvals than the admin set threshold")]
+    NotEnoughApprovals = 47,
+    #[error("Admin set members changed since the proposal was made")]
+    StaleProposal = 48,
+    #[error("Too many score updates in this throttle window")]
+    TooManyUpdates = 49,
+    #[error("Randomness is not freshly committed, or not revealed in this slot")]
+    RandomnessNotReady = 50,
+    #[error("Item is not in the catalog")]
+    UnknownItem = 51,
+    #[error("Player holds none of this item")]
+    ItemNotHeld = 52,
+    #[error("An item effect of this kind is already active")]
+    ItemEffectActive = 53,
+    #[error("Item is not for sale in this currency")]
+    ItemNotForSale = 54,
+    #[error("No continues left for this run")]
+    ContinueLimitReached = 55,
+    #[error("Price feed has no price recent enough to use")]
+    StalePrice = 56,
+    #[error("Price feed confidence interval is too wide")]
+    PriceTooUncertain = 57,
+    #[error("Season prizes have not been distributed yet")]
+    PrizesNotDistributed = 58,
+    #[error("Score is below the required threshold")]
+    ScoreBelowThreshold = 59,
+    #[error("Withdrawal would exceed the treasury's per-epoch cap")]
+    TreasuryCapExceeded = 60,
+    #[error("Leaderboard page is out of range")]
+    InvalidLeaderboardPage = 61,
+    #[error("Score ranks on a different leaderboard page")]
+    WrongLeaderboardPage = 62,
+    #[error("Player has recorded no score this week")]
+    NoWeeklyScore = 63,
+    #[error("Players cannot follow themselves")]
+    CannotFollowSelf = 64,
+    #[error("Region code must be two uppercase ASCII letters")]
+    InvalidRegion = 65,
+    #[error("Region has already been set; ask the admin to correct it")]
+    RegionAlreadySet = 66,
+    #[error("Score is more than the session could have earned in its time")]
+    ImplausibleScore = 67,
+    #[error("Run was already submitted")]
+    DuplicateRun = 68,
+    #[error("Lobby is full")]
+    LobbyFull = 69,
+    #[error("Player is not waiting in the lobby")]
+    NotInLobby = 70,
+    #[error("Player is already waiting in the lobby")]
+    AlreadyInLobby = 71,
+    #[error("Match is no longer taking bets")]
+    BettingClosed = 72,
+    #[error("Bet must back a player in the match and stay on their side")]
+    InvalidBet = 73,
+    #[error("Nothing has vested since the last claim")]
+    NothingVested = 74,
+    #[error("Staked tokens are still in their unstake cooldown")]
+    StakeLocked = 75,
+    #[error("Quest progress has not reached its target")]
+    QuestIncomplete = 76,
+    #[error("Quest reward was already claimed")]
+    QuestAlreadyClaimed = 77,
+    #[error("Player already submitted to this daily challenge")]
+    DailyChallengeAlreadySubmitted = 78,
+    #[error("Daily challenge is not for today")]
+    NotTodaysChallenge = 79,
+    #[error("Player level is too low")]
+    LevelTooLow = 80,
+    #[error("Player has not earned this cosmetic")]
+    CosmeticLocked = 81,
+    #[error("Cosmetic already unlocked")]
+    CosmeticAlreadyUnlocked = 82,
+    #[error("Player has not reached the badge's milestone")]
+    BadgeNotEarned = 83,
+    #[error("Player already holds this badge")]
+    BadgeAlreadyClaimed = 84,
+    #[error("Registrant does not hold an NFT from the tournament's collection")]
+    CollectionNftRequired = 85,
+    #[error("Player is not on the closed beta allowlist")]
+    NotAllowlisted = 86,
+    #[error("Game mode is not ranked on the leaderboards")]
+    UnrankedGameMode = 87,
+    #[error("Season has ended")]
+    SeasonEnded = 88,
+}
+
+impl From<GarbageGrabError> for ProgramError {
+    fn from(e: GarbageGrabError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_surface_as_their_own_custom_codes() {
+        assert_eq!(
+            ProgramError::from(GarbageGrabError::ScoreOverflow),
+            ProgramError::Custom(0)
+        );
+        assert_eq!(
+            ProgramError::from(GarbageGrabError::InvalidPda),
+            ProgramError::Custom(2)
+        );
+        assert_eq!(
+            ProgramError::from(GarbageGrabError::SeasonEnded),
+            ProgramError::Custom(88)
+        );
+        assert_ne!(
+            ProgramError::from(GarbageGrabError::NotInitialized),
+            ProgramError::InvalidAccountData
+        );
+        assert_eq!(
+            GarbageGrabError::ScoreTooLarge.to_string(),
+            "Score exceeds the maximum allowed value"
+        );
+    }
+}
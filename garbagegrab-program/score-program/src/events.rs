@@ -0,0 +1,120 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{log::sol_log_data, pubkey::Pubkey};
+
+use crate::state::GameMode;
+
+// Events are logged with `sol_log_data` as "Program data:" lines, the way Anchor's
+// `emit!` writes them: an 8-byte tag, sha256("event:<Name>")[..8], then the Borsh body
+pub const EVENT_DISCRIMINATOR_LEN: usize = 8;
+
+// What happened to the score account
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[borsh(use_discriminant = true)]
+#[repr(u8)]
+pub enum ScoreChange {
+    Initialized = 0,
+    Updated = 1,
+    Reset = 2,
+    Closed = 3,
+}
+
+// Logged whenever a score account is created, changes score, is reset or is closed,
+// so indexers can follow scores without parsing `msg!` text
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScoreUpdated {
+    pub player: Pubkey,
+    pub game_mode: GameMode,
+    pub change: ScoreChange,
+    pub old: u64,
+    pub new: u64,
+    pub ts: i64,
+}
+
+impl ScoreUpdated {
+    pub const DISCRIMINATOR: [u8; EVENT_DISCRIMINATOR_LEN] =
+        [175, 144, 206, 62, 108, 213, 230, 183];
+
+    // Log the event as program data
+    pub fn emit(&self) {
+        let mut data = Self::DISCRIMINATOR.to_vec();
+        self.serialize(&mut data)
+            .expect("event serialization cannot fail");
+        sol_log_data(&[&data]);
+    }
+
+    // Decode one base64-decoded "Program data:" payload, or `None` if it holds
+    // some other event
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let body = data.strip_prefix(&Self::DISCRIMINATOR[..])?;
+        Self::try_from_slice(body).ok()
+    }
+}
+
+// Logged when the admin withdraws from the treasury, with the running total for
+// the epoch so the cap can be audited from logs alone
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TreasuryWithdrawn {
+    pub admin: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub epoch: u64,
+    pub withdrawn_in_epoch: u64,
+    pub ts: i64,
+}
+
+impl TreasuryWithdrawn {
+    pub const DISCRIMINATOR: [u8; EVENT_DISCRIMINATOR_LEN] = [143, 181, 157, 169, 87, 155, 170, 46];
+
+    // Log the event as program data
+    pub fn emit(&self) {
+        let mut data = Self::DISCRIMINATOR.to_vec();
+        self.serialize(&mut data)
+            .expect("event serialization cannot fail");
+        sol_log_data(&[&data]);
+    }
+
+    // Decode one base64-decoded "Program data:" payload, or `None` if it holds
+    // some other event
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let body = data.strip_prefix(&Self::DISCRIMINATOR[..])?;
+        Self::try_from_slice(body).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::hash::hash;
+
+    #[test]
+    fn discriminator_matches_anchor_derivation() {
+        let digest = hash(b"event:ScoreUpdated");
+        assert_eq!(
+            digest.to_bytes()[..EVENT_DISCRIMINATOR_LEN],
+            ScoreUpdated::DISCRIMINATOR
+        );
+        let digest = hash(b"event:TreasuryWithdrawn");
+        assert_eq!(
+            digest.to_bytes()[..EVENT_DISCRIMINATOR_LEN],
+            TreasuryWithdrawn::DISCRIMINATOR
+        );
+    }
+
+    #[test]
+    fn decodes_only_score_updates() {
+        let event = ScoreUpdated {
+            player: Pubkey::new_unique(),
+            game_mode: GameMode::Endless,
+            change: ScoreChange::Reset,
+            old: 40,
+            new: 0,
+            ts: 1_700_000_000,
+        };
+        let mut data = ScoreUpdated::DISCRIMINATOR.to_vec();
+        data.extend(borsh::to_vec(&event).unwrap());
+        assert_eq!(ScoreUpdated::decode(&data), Some(event));
+
+        data[0] ^= 1;
+        assert_eq!(ScoreUpdated::decode(&data), None);
+    }
+}
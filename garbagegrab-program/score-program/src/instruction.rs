@@ -0,0 +1,3645 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use shank::{ShankInstruction, ShankType};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+use crate::state::{
+    BadgeMilestone, CompressedScore, GameMode, ItemDefinition, ProposalAccount, QuestMetric,
+    ShopCurrency, StakeLock,
+};
+
+// Optional config changes; fields left as `None` keep their current value
+#[derive(BorshSerialize, BorshDeserialize, ShankType, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConfigUpdate {
+    pub max_score: Option<u64>,
+    pub max_increment: Option<u64>,
+    pub paused: Option<bool>,
+    pub update_cooldown_secs: Option<u32>,
+    pub game_server: Option<Pubkey>,
+    pub reward_mint: Option<Pubkey>,
+    pub reward_per_tier: Option<u64>,
+    pub ranked_entry_fee: Option<u64>,
+    pub prize_split_bps: Option<[u16; 3]>,
+    pub boost_collection: Option<Pubkey>,
+    pub boost_multiplier_bps: Option<u16>,
+    pub trophy_margin: Option<u64>,
+    pub referral_threshold: Option<u64>,
+    pub points_per_referral: Option<u64>,
+    pub decay_window_secs: Option<u32>,
+    pub decay_bps: Option<u16>,
+    pub throttle_window_slots: Option<u64>,
+    pub max_updates_per_window: Option<u16>,
+    pub shop_mint: Option<Pubkey>,
+    pub continue_burn_amount: Option<u64>,
+    pub max_continues_per_run: Option<u8>,
+    pub ranked_entry_fee_usd_cents: Option<u64>,
+    pub sol_usd_price_feed: Option<Pubkey>,
+    pub max_price_age_secs: Option<u32>,
+    pub max_price_conf_bps: Option<u16>,
+    pub season_length_secs: Option<u64>,
+    pub crank_tip_lamports: Option<u64>,
+    pub treasury_withdraw_cap_lamports: Option<u64>,
+    pub max_score_per_slot: Option<u64>,
+    pub session_max_slots: Option<u64>,
+    pub max_score_per_second: Option<u64>,
+    pub bet_fee_bps: Option<u16>,
+    pub fee_bps: Option<u16>,
+    pub prize_vesting_cliff_secs: Option<u64>,
+    pub prize_vesting_secs: Option<u64>,
+    pub stake_tier_amounts: Option<[u64; 3]>,
+    pub stake_tier_bps: Option<[u16; 3]>,
+    pub unstake_cooldown_secs: Option<u32>,
+    pub stake_lock_bps: Option<[u16; 3]>,
+    pub early_unstake_penalty_bps: Option<u16>,
+    pub high_stakes_wager: Option<u64>,
+    pub high_stakes_min_level: Option<u16>,
+    pub closed_beta: Option<bool>,
+    pub allowlist_root: Option<[u8; 32]>,
+    pub require_attestation: Option<bool>,
+}
+
+// Instructions supported by the score program, Borsh-encoded as a one-byte
+// variant tag followed by the variant's fields in little-endian order. Each
+// variant's `#[account]` attributes list the accounts it expects, in order, and
+// feed the IDL generated by Shank.
+// UpdateConfig dwarfs the other variants, but an instruction is decoded once
+// per call, so it stays unboxed to keep its IDL type plain.
+#[derive(BorshSerialize, BorshDeserialize, ShankInstruction, Clone, Debug, PartialEq, Eq)]
+#[allow(clippy::large_enum_variant)]
+pub enum GarbageGrabInstruction {
+    /// Create and initialize the player's score account, recording who referred
+    /// the player if anyone did. During a closed beta `allowlist_proof` must link
+    /// the player to config.allowlist_root; otherwise it is ignored.
+    #[account(
+        0,
+        writable,
+        name = "score_account",
+        desc = "Score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "player",
+        desc = "Player, pays for the account"
+    )]
+    #[account(2, name = "system_program", desc = "System program")]
+    #[account(3, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        4,
+        optional,
+        writable,
+        name = "global_stats",
+        desc = "Global stats PDA (seeds: 'global_stats'), counts the new player"
+    )]
+    Initialize {
+        game_mode: GameMode,
+        referrer: Option<Pubkey>,
+        allowlist_proof: Vec<[u8; 32]>,
+    },
+
+    /// Add `increment` to the player's score, starting from zero if the score
+    /// was earned in an earlier season. Updates closer together than the
+    /// configured cooldown are rejected, as are banned players. Passing the
+    /// player's stake account ahead of any guild accounts multiplies the
+    /// increment by the staking tier it reaches. Any of the player's quest
+    /// progress accounts passed after the stake, still ahead of the guild
+    /// accounts, count the credited score towards their quests. The player's
+    /// profile passed after those earns them XP for the credited score.
+    /// Refused while the config requires attestation, and once the active season
+    /// has ended until the next one starts.
+    #[account(
+        0,
+        writable,
+        name = "score_account",
+        desc = "Score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(1, signer, name = "player", desc = "Player")]
+    #[account(
+        2,
+        name = "season",
+        desc = "Season PDA (seeds: 'season'), may be uninitialized"
+    )]
+    #[account(3, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        4,
+        name = "ban_list",
+        desc = "Ban list PDA (seeds: 'ban_list'), may be uninitialized"
+    )]
+    #[account(
+        5,
+        optional,
+        writable,
+        name = "global_stats",
+        desc = "Global stats PDA (seeds: 'global_stats'), counts the credited score and any new best"
+    )]
+    #[account(
+        6,
+        optional,
+        name = "stake",
+        desc = "Player's stake PDA (seeds: 'stake', player), for their staking multiplier"
+    )]
+    #[account(
+        7,
+        optional,
+        writable,
+        name = "guild_member",
+        desc = "Player's guild membership PDA (seeds: 'guild_member', player), to credit their guild"
+    )]
+    #[account(8, optional, writable, name = "guild", desc = "The member's guild PDA")]
+    UpdateScore { game_mode: GameMode, increment: u64 },
+
+    /// Reset the player's score to zero.
+    #[account(
+        0,
+        writable,
+        name = "score_account",
+        desc = "Score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(1, signer, name = "player", desc = "Player")]
+    #[account(2, name = "config", desc = "Config PDA (seeds: 'config')")]
+    ResetScore { game_mode: GameMode },
+
+    /// Close the score account, returning its rent lamports to the player.
+    #[account(
+        0,
+        writable,
+        name = "score_account",
+        desc = "Score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "player",
+        desc = "Player, receives the reclaimed lamports"
+    )]
+    #[account(2, name = "system_program", desc = "System program")]
+    CloseScoreAccount { game_mode: GameMode },
+
+    /// Record the final score of a completed run, raising the best score if it was beaten.
+    /// `run_hash` identifies the run (a hash of its seed and inputs); a hash among the
+    /// player's recent runs is rejected so a replayed result is not credited twice.
+    /// Runs share the update cooldown and throttle, and score at most max_increment.
+    /// Refused while the config requires attestation.
+    #[account(
+        0,
+        writable,
+        name = "score_account",
+        desc = "Score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(1, signer, name = "player", desc = "Player")]
+    #[account(
+        2,
+        name = "season",
+        desc = "Season PDA (seeds: 'season'), may be uninitialized"
+    )]
+    #[account(3, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        4,
+        name = "ban_list",
+        desc = "Ban list PDA (seeds: 'ban_list'), may be uninitialized"
+    )]
+    #[account(
+        5,
+        optional,
+        writable,
+        name = "global_stats",
+        desc = "Global stats PDA (seeds: 'global_stats'), counts the run and any new best"
+    )]
+    #[account(
+        6,
+        optional,
+        writable,
+        name = "profile",
+        desc = "Player's profile PDA (seeds: 'profile', player), to earn XP for the run"
+    )]
+    #[account(
+        7,
+        optional,
+        writable,
+        name = "history",
+        desc = "Score history PDA (seeds: 'history', player, game_mode), to record the run"
+    )]
+    SubmitRun {
+        game_mode: GameMode,
+        run_score: u64,
+        run_hash: [u8; 32],
+    },
+
+    /// Create the global leaderboard account.
+    #[account(
+        0,
+        writable,
+        name = "leaderboard",
+        desc = "Leaderboard PDA (seeds: 'leaderboard')"
+    )]
+    #[account(1, writable, signer, name = "payer", desc = "Payer")]
+    #[account(2, name = "system_program", desc = "System program")]
+    InitializeLeaderboard,
+
+    /// Submit the player's best score to the leaderboard, displacing lower entries.
+    /// Banned players are rejected, as are modes other than RANKED_GAME_MODE.
+    #[account(
+        0,
+        writable,
+        name = "leaderboard",
+        desc = "Leaderboard PDA (seeds: 'leaderboard')"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "score_account",
+        desc = "Score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(2, signer, name = "player", desc = "Player")]
+    #[account(3, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        4,
+        name = "ban_list",
+        desc = "Ban list PDA (seeds: 'ban_list'), may be uninitialized"
+    )]
+    #[account(
+        5,
+        optional,
+        writable,
+        name = "next_page",
+        desc = "Leaderboard page 1 (seeds: 'leaderboard', [1]), may be uninitialized, to keep the entry a full board displaces"
+    )]
+    SubmitToLeaderboard { game_mode: GameMode },
+
+    /// Start a new season running from now until `end_ts`, creating the season
+    /// account on first use. The previous season's prizes must have been
+    /// distributed, unless its prize pool is empty.
+    #[account(0, writable, name = "season", desc = "Season PDA (seeds: 'season')")]
+    #[account(1, writable, signer, name = "admin", desc = "Admin")]
+    #[account(2, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(3, name = "system_program", desc = "System program")]
+    #[account(
+        4,
+        name = "prize_pool",
+        desc = "Prize pool PDA (seeds: 'prize_pool'), may be uninitialized"
+    )]
+    StartSeason { end_ts: i64 },
+
+    /// Create the config account. Only the program's upgrade authority may do this,
+    /// and it becomes the admin.
+    #[account(0, writable, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "upgrade_authority",
+        desc = "Upgrade authority"
+    )]
+    #[account(
+        2,
+        name = "program_data",
+        desc = "Program data account of this program"
+    )]
+    #[account(3, name = "system_program", desc = "System program")]
+    InitializeConfig {
+        max_score: u64,
+        max_increment: u64,
+        update_cooldown_secs: u32,
+    },
+
+    /// Change config values; fields left as `None` keep their current value.
+    #[account(0, writable, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(1, signer, name = "admin", desc = "Admin")]
+    UpdateConfig(ConfigUpdate),
+
+    /// Pause all score-mutating instructions. Players can still close their score
+    /// accounts to reclaim rent and migrate them to the current layout, as
+    /// neither changes a score.
+    #[account(0, writable, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(1, signer, name = "admin", desc = "Admin")]
+    Pause,
+
+    /// Resume score-mutating instructions.
+    #[account(0, writable, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(1, signer, name = "admin", desc = "Admin")]
+    Unpause,
+
+    /// Add `increment` to the player's score on the strength of a game server
+    /// attestation. The preceding instruction must be an ed25519 verification of
+    /// the configured game server's signature over the score attestation message.
+    /// `nonce` must equal the score account's current nonce, which is then bumped.
+    /// While the config requires attestation, player-signed updates are refused
+    /// and scores move through this instead.
+    #[account(
+        0,
+        writable,
+        name = "score_account",
+        desc = "Score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(1, signer, name = "player", desc = "Player")]
+    #[account(
+        2,
+        name = "season",
+        desc = "Season PDA (seeds: 'season'), may be uninitialized"
+    )]
+    #[account(3, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        4,
+        name = "ban_list",
+        desc = "Ban list PDA (seeds: 'ban_list'), may be uninitialized"
+    )]
+    #[account(5, name = "instructions_sysvar", desc = "Instructions sysvar")]
+    #[account(
+        6,
+        optional,
+        writable,
+        name = "guild_member",
+        desc = "Player's guild membership PDA (seeds: 'guild_member', player), to credit their guild"
+    )]
+    #[account(7, optional, writable, name = "guild", desc = "The member's guild PDA")]
+    SubmitAttestedScore {
+        game_mode: GameMode,
+        increment: u64,
+        nonce: u64,
+    },
+
+    /// Subtract `amount` from a player's score to revert fraudulent submissions.
+    /// Must be signed by the admin or the configured game server.
+    #[account(
+        0,
+        writable,
+        name = "score_account",
+        desc = "Score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(1, name = "player", desc = "Player")]
+    #[account(2, signer, name = "authority", desc = "Admin or game server")]
+    #[account(3, name = "config", desc = "Config PDA (seeds: 'config')")]
+    PenalizeScore { game_mode: GameMode, amount: u64 },
+
+    /// Overwrite a player's score for support cases such as migrations or disputes.
+    /// Admin only; the old and new values are logged for auditing.
+    #[account(
+        0,
+        writable,
+        name = "score_account",
+        desc = "Score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(1, name = "player", desc = "Player")]
+    #[account(2, signer, name = "admin", desc = "Admin")]
+    #[account(3, name = "config", desc = "Config PDA (seeds: 'config')")]
+    SetScore { game_mode: GameMode, value: u64 },
+
+    /// Upgrade a score account written in an older layout to the current one,
+    /// reallocating it and topping up rent from the player.
+    #[account(
+        0,
+        writable,
+        name = "score_account",
+        desc = "Score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "player",
+        desc = "Player, pays any additional rent"
+    )]
+    #[account(2, name = "system_program", desc = "System program")]
+    MigrateAccount { game_mode: GameMode },
+
+    /// Transfer the reward tokens the player's best score has earned but not yet
+    /// claimed from the reward vault to the player's associated token account.
+    /// If that account does not exist yet it is created first, paid for by the
+    /// player, which takes the optional associated token and system programs.
+    /// Accounts after those are passed on to the mint's transfer hook, if it has
+    /// one.
+    #[account(
+        0,
+        writable,
+        name = "score_account",
+        desc = "Score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "player",
+        desc = "Player, pays for the token account if it has to be created"
+    )]
+    #[account(2, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        3,
+        writable,
+        name = "vault",
+        desc = "Reward vault token account PDA (seeds: 'vault')"
+    )]
+    #[account(
+        4,
+        writable,
+        name = "player_token_account",
+        desc = "Player's associated token account for the reward mint"
+    )]
+    #[account(5, name = "reward_mint", desc = "Reward mint set in the config")]
+    #[account(
+        6,
+        name = "token_program",
+        desc = "SPL Token or Token-2022 program owning the reward mint"
+    )]
+    #[account(
+        7,
+        optional,
+        name = "associated_token_program",
+        desc = "Associated Token Account program, to create the player's token account"
+    )]
+    #[account(
+        8,
+        optional,
+        name = "system_program",
+        desc = "System program, to create the player's token account"
+    )]
+    ClaimRewards { game_mode: GameMode },
+
+    /// Create the reward vault, a token account for the configured reward mint
+    /// that is its own transfer authority. Admin only.
+    #[account(
+        0,
+        writable,
+        name = "vault",
+        desc = "Reward vault PDA (seeds: 'vault')"
+    )]
+    #[account(1, writable, signer, name = "admin", desc = "Admin, pays rent")]
+    #[account(2, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(3, name = "reward_mint", desc = "Reward mint set in the config")]
+    #[account(
+        4,
+        name = "token_program",
+        desc = "SPL Token or Token-2022 program owning the reward mint"
+    )]
+    #[account(5, name = "system_program", desc = "System program")]
+    InitializeVault,
+
+    /// Deposit reward tokens from the admin's token account into the reward vault.
+    /// Admin only. Accounts after the token program are passed on to the mint's
+    /// transfer hook, if it has one.
+    #[account(
+        0,
+        writable,
+        name = "vault",
+        desc = "Reward vault PDA (seeds: 'vault')"
+    )]
+    #[account(1, signer, name = "admin", desc = "Admin")]
+    #[account(2, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        3,
+        writable,
+        name = "admin_token_account",
+        desc = "Admin's token account for the reward mint"
+    )]
+    #[account(4, name = "reward_mint", desc = "Reward mint set in the config")]
+    #[account(
+        5,
+        name = "token_program",
+        desc = "SPL Token or Token-2022 program owning the reward mint"
+    )]
+    FundVault { amount: u64 },
+
+    /// Pay the configured entry fee into the prize pool and open a ranked run,
+    /// which the next leaderboard submission consumes. The first run creates the
+    /// prize pool account. A fee set in US cents is converted to lamports at the
+    /// price in the config's Pyth SOL/USD feed, which must then be passed. The
+    /// config.fee_bps house cut of the fee goes to the fee vault instead. Only
+    /// RANKED_GAME_MODE has ranked runs.
+    #[account(
+        0,
+        writable,
+        name = "score_account",
+        desc = "Score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "player",
+        desc = "Player, pays the entry fee"
+    )]
+    #[account(2, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        3,
+        writable,
+        name = "prize_pool",
+        desc = "Prize pool PDA (seeds: 'prize_pool')"
+    )]
+    #[account(4, name = "system_program", desc = "System program")]
+    #[account(
+        5,
+        writable,
+        name = "fee_vault",
+        desc = "Fee vault PDA (seeds: 'fee_vault'), receives the house fee"
+    )]
+    #[account(
+        6,
+        optional,
+        writable,
+        name = "global_stats",
+        desc = "Global stats PDA (seeds: 'global_stats'), counts the house fee"
+    )]
+    #[account(
+        7,
+        optional,
+        name = "price_feed",
+        desc = "Pyth SOL/USD price account named in the config, for a fee in US cents"
+    )]
+    StartRankedRun { game_mode: GameMode },
+
+    /// Once the season has ended, pay the prize pool out to the top leaderboard
+    /// places by the configured split and clear the leaderboard. Anyone may send
+    /// it; the winners' wallets follow in rank order, one per paid place that has
+    /// an entry. When config.prize_vesting_secs is set the prizes go into vesting
+    /// accounts instead, their rent taken from the prize: the system program
+    /// follows the prize pool and each winner's vesting PDA follows their wallet.
+    #[account(
+        0,
+        writable,
+        name = "leaderboard",
+        desc = "Leaderboard PDA (seeds: 'leaderboard')"
+    )]
+    #[account(1, writable, name = "season", desc = "Season PDA (seeds: 'season')")]
+    #[account(2, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        3,
+        writable,
+        name = "prize_pool",
+        desc = "Prize pool PDA (seeds: 'prize_pool')"
+    )]
+    #[account(
+        4,
+        optional,
+        name = "system_program",
+        desc = "System program, when prizes vest"
+    )]
+    DistributePrizes,
+
+    /// Open a wager match between two players, moving `stake` lamports from
+    /// each of them into the match account. A stake of config.high_stakes_wager
+    /// or more needs both players' profiles at config.high_stakes_min_level.
+    #[account(
+        0,
+        writable,
+        name = "match_account",
+        desc = "Match PDA (seeds: 'match', player_a, player_b, match_id)"
+    )]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "player_a",
+        desc = "First player, pays their stake and the account rent"
+    )]
+    #[account(
+        2,
+        writable,
+        signer,
+        name = "player_b",
+        desc = "Second player, pays their stake"
+    )]
+    #[account(3, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(4, name = "system_program", desc = "System program")]
+    #[account(
+        5,
+        optional,
+        name = "player_a_profile",
+        desc = "First player's profile PDA (seeds: 'profile', player_a), for high stakes"
+    )]
+    #[account(
+        6,
+        optional,
+        name = "player_b_profile",
+        desc = "Second player's profile PDA (seeds: 'profile', player_b), for high stakes"
+    )]
+    CreateMatch { match_id: u64, stake: u64 },
+
+    /// Record the winner of an active match. Game server only.
+    #[account(
+        0,
+        writable,
+        name = "match_account",
+        desc = "Match PDA (seeds: 'match', player_a, player_b, match_id)"
+    )]
+    #[account(1, signer, name = "game_server", desc = "Configured game server")]
+    #[account(2, name = "config", desc = "Config PDA (seeds: 'config')")]
+    SubmitMatchResult { winner: Pubkey },
+
+    /// Pay the pot of a decided match to its winner and close the match account,
+    /// refunding its rent to the first player. The config.fee_bps house cut of
+    /// the pot goes to the fee vault; the rent refund tops a new vault up to its
+    /// rent-exempt minimum. Anyone may send it, once any bets on the match are
+    /// settled.
+    #[account(
+        0,
+        writable,
+        name = "match_account",
+        desc = "Match PDA (seeds: 'match', player_a, player_b, match_id)"
+    )]
+    #[account(1, writable, name = "winner", desc = "Recorded winner")]
+    #[account(
+        2,
+        writable,
+        name = "player_a",
+        desc = "First player, receives the rent"
+    )]
+    #[account(3, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        4,
+        writable,
+        name = "fee_vault",
+        desc = "Fee vault PDA (seeds: 'fee_vault'), receives the house fee"
+    )]
+    #[account(
+        5,
+        name = "bet_pool",
+        desc = "Bet pool PDA (seeds: 'bet_pool', match_account), may be uninitialized"
+    )]
+    #[account(
+        6,
+        optional,
+        writable,
+        name = "global_stats",
+        desc = "Global stats PDA (seeds: 'global_stats'), counts the house fee"
+    )]
+    SettleMatch,
+
+    /// Create a tournament open to at most `max_participants` players until
+    /// `end_ts`, each paying `entry_fee` lamports to register. Unless `collection`
+    /// is the default pubkey, only holders of an NFT verified as a member of that
+    /// Metaplex collection may register. Admin only.
+    #[account(
+        0,
+        writable,
+        name = "tournament",
+        desc = "Tournament PDA (seeds: 'tournament', tournament_id)"
+    )]
+    #[account(1, writable, signer, name = "admin", desc = "Admin, pays rent")]
+    #[account(2, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(3, name = "system_program", desc = "System program")]
+    CreateTournament {
+        tournament_id: u64,
+        entry_fee: u64,
+        max_participants: u8,
+        end_ts: i64,
+        collection: Pubkey,
+    },
+
+    /// Pay the entry fee and take a place in an open tournament. The
+    /// config.fee_bps house cut of the fee goes to the fee vault, the rest to the
+    /// tournament's prizes. A collection-gated tournament also needs the player's
+    /// token account holding an NFT from the collection and that NFT's metadata.
+    #[account(
+        0,
+        writable,
+        name = "tournament",
+        desc = "Tournament PDA (seeds: 'tournament', tournament_id)"
+    )]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "player",
+        desc = "Player, pays the entry fee"
+    )]
+    #[account(2, name = "system_program", desc = "System program")]
+    #[account(3, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        4,
+        writable,
+        name = "fee_vault",
+        desc = "Fee vault PDA (seeds: 'fee_vault'), receives the house fee"
+    )]
+    #[account(
+        5,
+        optional,
+        writable,
+        name = "global_stats",
+        desc = "Global stats PDA (seeds: 'global_stats'), counts the house fee"
+    )]
+    #[account(
+        6,
+        optional,
+        name = "nft_token_account",
+        desc = "Player's token account holding an NFT from the tournament's collection"
+    )]
+    #[account(
+        7,
+        optional,
+        name = "nft_metadata",
+        desc = "Token Metadata account of that NFT"
+    )]
+    Register,
+
+    /// Record a registered player's tournament score, keeping their best.
+    /// Game server only.
+    #[account(
+        0,
+        writable,
+        name = "tournament",
+        desc = "Tournament PDA (seeds: 'tournament', tournament_id)"
+    )]
+    #[account(1, name = "player", desc = "Player")]
+    #[account(2, signer, name = "game_server", desc = "Configured game server")]
+    #[account(3, name = "config", desc = "Config PDA (seeds: 'config')")]
+    SubmitTournamentScore { score: u64 },
+
+    /// Once the tournament has ended, pay the collected fees to the top places by
+    /// the configured prize split. Anyone may send it; the winners' wallets follow
+    /// in standings order, one per paid place that has a participant, and any
+    /// unpaid remainder goes to the creator.
+    #[account(
+        0,
+        writable,
+        name = "tournament",
+        desc = "Tournament PDA (seeds: 'tournament', tournament_id)"
+    )]
+    #[account(1, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(2, writable, name = "creator", desc = "Tournament creator")]
+    FinalizeTournament,
+
+    /// Unlock achievement `id` (0-63) on the player's score account. Game server only.
+    /// Any of the player's quest progress accounts passed after the config count
+    /// the unlock towards their quests.
+    #[account(
+        0,
+        writable,
+        name = "score_account",
+        desc = "Score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(1, name = "player", desc = "Player")]
+    #[account(2, signer, name = "game_server", desc = "Configured game server")]
+    #[account(3, name = "config", desc = "Config PDA (seeds: 'config')")]
+    UnlockAchievement { game_mode: GameMode, id: u8 },
+
+    /// Record today's check-in, extending the player's daily streak if they also
+    /// checked in yesterday. Score increments credited on a checked-in day are
+    /// boosted by the streak.
+    #[account(
+        0,
+        writable,
+        name = "score_account",
+        desc = "Score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(1, signer, name = "player", desc = "Player")]
+    #[account(2, name = "config", desc = "Config PDA (seeds: 'config')")]
+    CheckIn { game_mode: GameMode },
+
+    /// Add `increment` to the player's score like UpdateScore, multiplied by the
+    /// configured boost because the player holds an NFT from the boost collection.
+    /// Refused while the config requires attestation.
+    #[account(
+        0,
+        writable,
+        name = "score_account",
+        desc = "Score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(1, signer, name = "player", desc = "Player")]
+    #[account(2, name = "season", desc = "Season PDA (seeds: 'season')")]
+    #[account(3, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        4,
+        name = "ban_list",
+        desc = "Ban list PDA (seeds: 'ban_list'), may be uninitialized"
+    )]
+    #[account(
+        5,
+        name = "nft_token_account",
+        desc = "Player's token account holding the NFT"
+    )]
+    #[account(
+        6,
+        name = "nft_metadata",
+        desc = "Token Metadata account of the NFT's mint"
+    )]
+    #[account(
+        7,
+        optional,
+        writable,
+        name = "guild_member",
+        desc = "Player's guild membership PDA (seeds: 'guild_member', player), to credit their guild"
+    )]
+    #[account(8, optional, writable, name = "guild", desc = "The member's guild PDA")]
+    ApplyBoost { game_mode: GameMode, increment: u64 },
+
+    /// Mint a one-of-one trophy NFT to the player once their best score has beaten
+    /// the best score of their last trophy by the configured margin. The program's
+    /// trophy authority PDA is the mint and update authority of every trophy.
+    #[account(
+        0,
+        writable,
+        name = "score_account",
+        desc = "Score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "player",
+        desc = "Player, paying for the trophy accounts"
+    )]
+    #[account(2, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        3,
+        writable,
+        signer,
+        name = "trophy_mint",
+        desc = "New keypair for the trophy mint"
+    )]
+    #[account(
+        4,
+        writable,
+        name = "trophy_token_account",
+        desc = "Player's associated token account for the trophy mint"
+    )]
+    #[account(
+        5,
+        writable,
+        name = "trophy_metadata",
+        desc = "Token Metadata account of the trophy mint"
+    )]
+    #[account(
+        6,
+        writable,
+        name = "trophy_edition",
+        desc = "Master edition account of the trophy mint"
+    )]
+    #[account(
+        7,
+        name = "trophy_authority",
+        desc = "Trophy authority PDA (seeds: 'trophy')"
+    )]
+    #[account(8, name = "token_program", desc = "SPL Token program")]
+    #[account(
+        9,
+        name = "associated_token_program",
+        desc = "Associated Token Account program"
+    )]
+    #[account(10, name = "token_metadata_program", desc = "Token Metadata program")]
+    #[account(11, name = "system_program", desc = "System program")]
+    MintTrophy { game_mode: GameMode },
+
+    /// Create the player's profile and claim its display name, which must not be
+    /// held by another profile in any letter case.
+    #[account(
+        0,
+        writable,
+        name = "profile",
+        desc = "Profile PDA (seeds: 'profile', player)"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "name_record",
+        desc = "Name record PDA (seeds: 'name', lowercased name)"
+    )]
+    #[account(
+        2,
+        writable,
+        signer,
+        name = "player",
+        desc = "Player, paying for both accounts"
+    )]
+    #[account(3, name = "system_program", desc = "System program")]
+    CreateProfile {
+        // Zero-padded display name; Shank only reads literal array lengths
+        name: [u8; 32],
+        avatar_mint: Pubkey,
+    },
+
+    /// Change the player's display name and/or avatar mint. Renaming claims the new
+    /// name and releases the old one, refunding its rent to the player; the name
+    /// record accounts are only read when renaming.
+    #[account(
+        0,
+        writable,
+        name = "profile",
+        desc = "Profile PDA (seeds: 'profile', player)"
+    )]
+    #[account(1, writable, signer, name = "player", desc = "Player")]
+    #[account(
+        2,
+        writable,
+        name = "current_name_record",
+        desc = "Name record PDA of the current name"
+    )]
+    #[account(
+        3,
+        writable,
+        name = "new_name_record",
+        desc = "Name record PDA of the new name"
+    )]
+    #[account(4, name = "system_program", desc = "System program")]
+    UpdateProfile {
+        name: Option<[u8; 32]>,
+        avatar_mint: Option<Pubkey>,
+    },
+
+    /// Credit a referrer's profile once the player they referred has reached the
+    /// configured score threshold. Anyone may send it; each player counts once.
+    #[account(
+        0,
+        writable,
+        name = "score_account",
+        desc = "Referred player's score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(1, name = "player", desc = "Referred player")]
+    #[account(
+        2,
+        writable,
+        name = "referrer_profile",
+        desc = "Referrer's profile PDA (seeds: 'profile', referrer)"
+    )]
+    #[account(3, name = "config", desc = "Config PDA (seeds: 'config')")]
+    CreditReferral { game_mode: GameMode },
+
+    /// Create a guild led by the signer, who becomes its first member.
+    #[account(
+        0,
+        writable,
+        name = "guild",
+        desc = "Guild PDA (seeds: 'guild', guild_id)"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "guild_member",
+        desc = "Leader's guild membership PDA (seeds: 'guild_member', leader)"
+    )]
+    #[account(
+        2,
+        writable,
+        signer,
+        name = "leader",
+        desc = "Guild leader, paying for both accounts"
+    )]
+    #[account(3, name = "system_program", desc = "System program")]
+    CreateGuild {
+        guild_id: u64,
+        // Zero-padded display name; Shank only reads literal array lengths
+        name: [u8; 32],
+    },
+
+    /// Join a guild. A player belongs to at most one guild at a time.
+    #[account(
+        0,
+        writable,
+        name = "guild",
+        desc = "Guild PDA (seeds: 'guild', guild_id)"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "guild_member",
+        desc = "Player's guild membership PDA (seeds: 'guild_member', player)"
+    )]
+    #[account(
+        2,
+        writable,
+        signer,
+        name = "player",
+        desc = "Player, paying for the membership"
+    )]
+    #[account(3, name = "system_program", desc = "System program")]
+    JoinGuild,
+
+    /// Leave the player's guild, taking their contributions out of its total and
+    /// refunding the membership rent. The leader can only leave as the last member.
+    #[account(
+        0,
+        writable,
+        name = "guild",
+        desc = "Guild PDA (seeds: 'guild', guild_id)"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "guild_member",
+        desc = "Player's guild membership PDA (seeds: 'guild_member', player)"
+    )]
+    #[account(2, writable, signer, name = "player", desc = "Player")]
+    LeaveGuild,
+
+    /// Create the guild leaderboard account, ranking guilds by total score.
+    #[account(
+        0,
+        writable,
+        name = "guild_leaderboard",
+        desc = "Guild leaderboard PDA (seeds: 'guild_leaderboard')"
+    )]
+    #[account(1, writable, signer, name = "payer", desc = "Payer")]
+    #[account(2, name = "system_program", desc = "System program")]
+    InitializeGuildLeaderboard,
+
+    /// Submit a guild's total score to the guild leaderboard. Anyone may send it.
+    #[account(
+        0,
+        writable,
+        name = "guild_leaderboard",
+        desc = "Guild leaderboard PDA (seeds: 'guild_leaderboard')"
+    )]
+    #[account(1, name = "guild", desc = "Guild PDA (seeds: 'guild', guild_id)")]
+    SubmitGuildToLeaderboard,
+
+    /// Create the shared score account of a co-op team. Every member signs; their
+    /// wallets follow the listed accounts, in any order.
+    #[account(
+        0,
+        writable,
+        name = "team",
+        desc = "Team PDA (seeds: 'team', then each member in sorted order)"
+    )]
+    #[account(1, writable, signer, name = "payer", desc = "Payer")]
+    #[account(2, name = "system_program", desc = "System program")]
+    CreateTeam,
+
+    /// Add `increment` to a team's shared score. Any member may send it, subject
+    /// to the same pause, cooldown and caps as UpdateScore.
+    #[account(0, writable, name = "team", desc = "Team PDA")]
+    #[account(1, signer, name = "member", desc = "Team member")]
+    #[account(2, name = "config", desc = "Config PDA (seeds: 'config')")]
+    UpdateTeamScore { increment: u64 },
+
+    /// Let `delegate`, such as a session key or relayer, submit score updates for
+    /// the player with UpdateScoreAsDelegate. Replaces any earlier delegate.
+    #[account(
+        0,
+        writable,
+        name = "score_account",
+        desc = "Score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(1, signer, name = "player", desc = "Player")]
+    ApproveDelegate {
+        game_mode: GameMode,
+        delegate: Pubkey,
+    },
+
+    /// Remove the player's delegate, if any.
+    #[account(
+        0,
+        writable,
+        name = "score_account",
+        desc = "Score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(1, signer, name = "player", desc = "Player")]
+    RevokeDelegate { game_mode: GameMode },
+
+    /// UpdateScore signed by the player's approved delegate instead of the player.
+    /// Refused while the config requires attestation.
+    #[account(
+        0,
+        writable,
+        name = "score_account",
+        desc = "Score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(1, name = "player", desc = "Player the delegate acts for")]
+    #[account(2, signer, name = "delegate", desc = "Approved delegate")]
+    #[account(
+        3,
+        name = "season",
+        desc = "Season PDA (seeds: 'season'), may be uninitialized"
+    )]
+    #[account(4, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        5,
+        name = "ban_list",
+        desc = "Ban list PDA (seeds: 'ban_list'), may be uninitialized"
+    )]
+    #[account(
+        6,
+        optional,
+        writable,
+        name = "global_stats",
+        desc = "Global stats PDA (seeds: 'global_stats'), counts the credited score and any new best"
+    )]
+    #[account(
+        7,
+        optional,
+        writable,
+        name = "guild_member",
+        desc = "Player's guild membership PDA (seeds: 'guild_member', player), to credit their guild"
+    )]
+    #[account(8, optional, writable, name = "guild", desc = "The member's guild PDA")]
+    UpdateScoreAsDelegate { game_mode: GameMode, increment: u64 },
+
+    /// Authorize `session_key` to submit the player's score updates with
+    /// UpdateScoreWithSession until `expiry_slot`.
+    #[account(
+        0,
+        writable,
+        name = "session",
+        desc = "Session token PDA (seeds: 'session', player, session_key)"
+    )]
+    #[account(1, writable, signer, name = "player", desc = "Player")]
+    #[account(2, name = "system_program", desc = "System program")]
+    CreateSession {
+        session_key: Pubkey,
+        expiry_slot: u64,
+    },
+
+    /// Close a session token before it expires, refunding its rent to the player.
+    #[account(0, writable, name = "session", desc = "Session token PDA")]
+    #[account(1, writable, signer, name = "player", desc = "Player")]
+    RevokeSession,
+
+    /// UpdateScore signed by a session key whose token has not expired.
+    /// Refused while the config requires attestation.
+    #[account(
+        0,
+        writable,
+        name = "score_account",
+        desc = "Score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(1, name = "player", desc = "Player who created the session")]
+    #[account(2, signer, name = "session_key", desc = "Session keypair")]
+    #[account(
+        3,
+        name = "session",
+        desc = "Session token PDA (seeds: 'session', player, session_key)"
+    )]
+    #[account(
+        4,
+        name = "season",
+        desc = "Season PDA (seeds: 'season'), may be uninitialized"
+    )]
+    #[account(5, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        6,
+        name = "ban_list",
+        desc = "Ban list PDA (seeds: 'ban_list'), may be uninitialized"
+    )]
+    #[account(
+        7,
+        optional,
+        writable,
+        name = "guild_member",
+        desc = "Player's guild membership PDA (seeds: 'guild_member', player), to credit their guild"
+    )]
+    #[account(8, optional, writable, name = "guild", desc = "The member's guild PDA")]
+    UpdateScoreWithSession { game_mode: GameMode, increment: u64 },
+
+    /// Create the player's score history for a mode. SubmitRun records each run
+    /// in it when the history account is passed.
+    #[account(
+        0,
+        writable,
+        name = "history",
+        desc = "Score history PDA (seeds: 'history', player, game_mode)"
+    )]
+    #[account(1, writable, signer, name = "player", desc = "Player")]
+    #[account(2, name = "system_program", desc = "System program")]
+    InitializeHistory { game_mode: GameMode },
+
+    /// Grow one of the player's score, profile or history accounts to `new_len`
+    /// bytes, paying the extra rent. Score and profile accounts keep the spare bytes
+    /// zeroed for fields appended later; a history keeps more runs.
+    #[account(
+        0,
+        writable,
+        name = "account",
+        desc = "The player's score account, profile or score history"
+    )]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "player",
+        desc = "Player, pays the extra rent"
+    )]
+    #[account(2, name = "system_program", desc = "System program")]
+    Realloc { new_len: u32 },
+
+    /// Publish the Merkle root of an airdrop of reward tokens, creating its
+    /// account and claimed bitmap on first use. The root can be replaced until the
+    /// first claim. Admin only.
+    #[account(
+        0,
+        writable,
+        name = "airdrop",
+        desc = "Airdrop PDA (seeds: 'airdrop', airdrop_id)"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "claims",
+        desc = "Claimed bitmap PDA (seeds: 'airdrop_claims', airdrop_id)"
+    )]
+    #[account(2, writable, signer, name = "admin", desc = "Admin, pays rent")]
+    #[account(3, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(4, name = "system_program", desc = "System program")]
+    SetRewardRoot {
+        airdrop_id: u64,
+        merkle_root: [u8; 32],
+        max_claims: u32,
+    },
+
+    /// Claim the reward tokens owed to the player by an airdrop, proving their
+    /// (index, player, amount) leaf is under the published root. Each index pays
+    /// out once. Accounts after the token program are passed on to the mint's
+    /// transfer hook, if it has one.
+    #[account(
+        0,
+        writable,
+        name = "airdrop",
+        desc = "Airdrop PDA (seeds: 'airdrop', airdrop_id)"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "claims",
+        desc = "Claimed bitmap PDA (seeds: 'airdrop_claims', airdrop_id)"
+    )]
+    #[account(2, signer, name = "player", desc = "Player named in the leaf")]
+    #[account(3, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        4,
+        writable,
+        name = "vault",
+        desc = "Reward vault token account PDA (seeds: 'vault')"
+    )]
+    #[account(
+        5,
+        writable,
+        name = "player_token_account",
+        desc = "Player's associated token account for the reward mint"
+    )]
+    #[account(6, name = "reward_mint", desc = "Reward mint set in the config")]
+    #[account(
+        7,
+        name = "token_program",
+        desc = "SPL Token or Token-2022 program owning the reward mint"
+    )]
+    ClaimAirdrop {
+        airdrop_id: u64,
+        index: u32,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    },
+
+    /// Start a compressed score tree. The Merkle tree account must already be
+    /// allocated, zeroed and owned by the account compression program; the score
+    /// tree PDA becomes its authority. Admin only.
+    #[account(
+        0,
+        writable,
+        name = "score_tree",
+        desc = "Score tree PDA (seeds: 'score_tree', merkle_tree)"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "merkle_tree",
+        desc = "Concurrent Merkle tree account"
+    )]
+    #[account(2, writable, signer, name = "admin", desc = "Admin, pays rent")]
+    #[account(3, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        4,
+        name = "compression_program",
+        desc = "SPL Account Compression program"
+    )]
+    #[account(5, name = "noop_program", desc = "SPL Noop program")]
+    #[account(6, name = "system_program", desc = "System program")]
+    InitializeScoreTree {
+        max_depth: u32,
+        max_buffer_size: u32,
+    },
+
+    /// Record a casual player's score as a leaf of a compressed score tree rather
+    /// than in a rent-paying score account. Game server only.
+    #[account(
+        0,
+        writable,
+        name = "score_tree",
+        desc = "Score tree PDA (seeds: 'score_tree', merkle_tree)"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "merkle_tree",
+        desc = "Concurrent Merkle tree account"
+    )]
+    #[account(2, name = "player", desc = "Player")]
+    #[account(3, signer, name = "game_server", desc = "Configured game server")]
+    #[account(4, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        5,
+        name = "compression_program",
+        desc = "SPL Account Compression program"
+    )]
+    #[account(6, name = "noop_program", desc = "SPL Noop program")]
+    AppendCompressedScore { game_mode: GameMode, score: u64 },
+
+    /// Succeed only if `score` is a leaf of the tree under `root`, returning its
+    /// score as a Borsh u64. The proof nodes follow the listed accounts, lowest
+    /// level first.
+    #[account(
+        0,
+        name = "score_tree",
+        desc = "Score tree PDA (seeds: 'score_tree', merkle_tree)"
+    )]
+    #[account(1, name = "merkle_tree", desc = "Concurrent Merkle tree account")]
+    #[account(
+        2,
+        name = "compression_program",
+        desc = "SPL Account Compression program"
+    )]
+    VerifyCompressedScore {
+        root: [u8; 32],
+        score: CompressedScore,
+    },
+
+    /// Add `increments[i]` to the i-th score account following the listed
+    /// accounts, settling many players' runs at once. Each entry goes through the
+    /// same checks as UpdateScore; an entry that fails them is logged and skipped
+    /// while the rest still apply. The whole batch fails only if the signer is not
+    /// the game server, the program is paused, or the accounts don't line up with
+    /// the increments. Guilds are not credited. Game server only.
+    #[account(0, signer, name = "game_server", desc = "Game server")]
+    #[account(
+        1,
+        name = "season",
+        desc = "Season PDA (seeds: 'season'), may be uninitialized"
+    )]
+    #[account(2, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        3,
+        name = "ban_list",
+        desc = "Ban list PDA (seeds: 'ban_list'), may be uninitialized"
+    )]
+    BatchUpdate {
+        game_mode: GameMode,
+        increments: Vec<u64>,
+    },
+
+    /// Send a program-owned account whatever lamports it is short of being rent
+    /// exempt at its current size, so an account that grew in a migration is not
+    /// garbage-collected. Anyone can pay; an account that is already exempt is
+    /// left untouched.
+    #[account(
+        0,
+        writable,
+        name = "account",
+        desc = "Any account owned by the program"
+    )]
+    #[account(1, writable, signer, name = "payer", desc = "Pays the shortfall")]
+    #[account(2, name = "system_program", desc = "System program")]
+    TopUpRent,
+
+    /// Bar `player` from updating their score or submitting to the leaderboard.
+    /// The first ban creates the list and each ban grows it by one key, paid by
+    /// the admin. Admin only.
+    #[account(
+        0,
+        writable,
+        name = "ban_list",
+        desc = "Ban list PDA (seeds: 'ban_list')"
+    )]
+    #[account(1, writable, signer, name = "admin", desc = "Admin, pays the rent")]
+    #[account(2, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(3, name = "system_program", desc = "System program")]
+    Ban { player: Pubkey },
+
+    /// Lift a ban, shrinking the list by one key. Admin only.
+    #[account(
+        0,
+        writable,
+        name = "ban_list",
+        desc = "Ban list PDA (seeds: 'ban_list')"
+    )]
+    #[account(1, signer, name = "admin", desc = "Admin")]
+    #[account(2, name = "config", desc = "Config PDA (seeds: 'config')")]
+    Unban { player: Pubkey },
+
+    /// Report `player`'s score in `game_mode` as cheated, putting up a bond of
+    /// REPORT_BOND_LAMPORTS. Each reporter can have one open report per player
+    /// and mode.
+    #[account(
+        0,
+        writable,
+        name = "report",
+        desc = "Report PDA (seeds: 'report', player, reporter, game_mode)"
+    )]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "reporter",
+        desc = "Reporter, pays the bond and rent"
+    )]
+    #[account(2, name = "player", desc = "Reported player")]
+    #[account(
+        3,
+        name = "score_account",
+        desc = "Reported score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(4, name = "system_program", desc = "System program")]
+    ReportPlayer { game_mode: GameMode },
+
+    /// Close a report. An upheld report takes `slash` off the player's score
+    /// (down to zero at most) and refunds the bond; a rejected one forfeits the
+    /// bond to the prize pool. The rent goes back to the reporter either way.
+    /// Admin only.
+    #[account(
+        0,
+        writable,
+        name = "report",
+        desc = "Report PDA (seeds: 'report', player, reporter, game_mode)"
+    )]
+    #[account(1, signer, name = "admin", desc = "Admin")]
+    #[account(2, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        3,
+        writable,
+        name = "score_account",
+        desc = "Reported score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(4, writable, name = "reporter", desc = "Reporter")]
+    #[account(
+        5,
+        writable,
+        name = "prize_pool",
+        desc = "Prize pool PDA (seeds: 'prize_pool')"
+    )]
+    ResolveReport { upheld: bool, slash: u64 },
+
+    /// Hand admin rights to an m-of-n admin set: `threshold` of `members` must
+    /// approve a proposal before any admin instruction runs. The first call
+    /// creates the set and makes it the config admin; later calls replace the
+    /// members and can only come from an executed proposal. Admin only.
+    #[account(
+        0,
+        writable,
+        name = "admin_set",
+        desc = "Admin set PDA (seeds: 'admin_set')"
+    )]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "admin",
+        desc = "Admin, pays the rent when creating the set"
+    )]
+    #[account(2, writable, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(3, name = "system_program", desc = "System program")]
+    SetAdminSet { threshold: u8, members: Vec<Pubkey> },
+
+    /// Propose running one score program instruction with the admin set as its
+    /// admin. Counts as the proposer's approval. Admin set members only.
+    #[account(
+        0,
+        writable,
+        name = "proposal",
+        desc = "Proposal PDA (seeds: 'proposal', admin set proposal count)"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "admin_set",
+        desc = "Admin set PDA (seeds: 'admin_set')"
+    )]
+    #[account(2, writable, signer, name = "proposer", desc = "Member, pays the rent")]
+    #[account(3, name = "system_program", desc = "System program")]
+    Propose {
+        accounts: Vec<ProposalAccount>,
+        data: Vec<u8>,
+    },
+
+    /// Approve a pending proposal. Admin set members only.
+    #[account(
+        0,
+        writable,
+        name = "proposal",
+        desc = "Proposal PDA (seeds: 'proposal', proposal_id)"
+    )]
+    #[account(1, name = "admin_set", desc = "Admin set PDA (seeds: 'admin_set')")]
+    #[account(2, signer, name = "member", desc = "Admin set member")]
+    Approve,
+
+    /// Run a proposal that has reached the threshold, signing for the admin set,
+    /// then close it and refund the proposer. The proposed instruction's
+    /// accounts follow the listed ones, in order. Anyone can execute.
+    #[account(
+        0,
+        writable,
+        name = "proposal",
+        desc = "Proposal PDA (seeds: 'proposal', proposal_id)"
+    )]
+    #[account(1, name = "admin_set", desc = "Admin set PDA (seeds: 'admin_set')")]
+    #[account(2, writable, name = "proposer", desc = "Proposer, receives the rent")]
+    #[account(
+        3,
+        name = "score_program",
+        desc = "This program, which the proposal calls back into"
+    )]
+    ExecuteProposal,
+
+    /// Nominate `new_admin` to take over as admin once they accept. Nominating
+    /// the default pubkey withdraws a nomination. Admin only.
+    #[account(0, writable, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(1, signer, name = "admin", desc = "Admin")]
+    NominateAdmin { new_admin: Pubkey },
+
+    /// Take over as admin. Only the nominated admin can accept.
+    #[account(0, writable, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(1, signer, name = "new_admin", desc = "Nominated admin")]
+    AcceptAdmin,
+
+    /// Pay LOOT_PRICE_LAMPORTS for a loot drop decided by a Switchboard
+    /// randomness account committed in the previous slot, creating the
+    /// player's inventory on first use. One drop can be pending per player.
+    #[account(
+        0,
+        writable,
+        name = "loot_request",
+        desc = "Loot request PDA (seeds: 'loot_request', player)"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "inventory",
+        desc = "Inventory PDA (seeds: 'inventory', player)"
+    )]
+    #[account(
+        2,
+        writable,
+        signer,
+        name = "player",
+        desc = "Player, pays the price and rent"
+    )]
+    #[account(
+        3,
+        name = "randomness",
+        desc = "Switchboard randomness account committed in the previous slot"
+    )]
+    #[account(4, name = "system_program", desc = "System program")]
+    RequestLoot,
+
+    /// Settle a pending loot drop in the slot its randomness is revealed:
+    /// credit the rolled tier to the inventory, move the price into the prize
+    /// pool and refund the request's rent to the player. Anyone can call it.
+    #[account(
+        0,
+        writable,
+        name = "loot_request",
+        desc = "Loot request PDA (seeds: 'loot_request', player)"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "inventory",
+        desc = "Inventory PDA (seeds: 'inventory', player)"
+    )]
+    #[account(
+        2,
+        writable,
+        name = "player",
+        desc = "Player who requested the drop, receives the rent"
+    )]
+    #[account(
+        3,
+        name = "randomness",
+        desc = "Switchboard randomness account named in the request"
+    )]
+    #[account(
+        4,
+        writable,
+        name = "prize_pool",
+        desc = "Prize pool PDA (seeds: 'prize_pool')"
+    )]
+    FulfillLoot,
+
+    /// Add `definition` to the item catalog, or replace the item with its id,
+    /// creating the catalog on first use. Admin only.
+    #[account(0, writable, name = "catalog", desc = "Catalog PDA (seeds: 'catalog')")]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "admin",
+        desc = "Admin, pays for the catalog to grow"
+    )]
+    #[account(2, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(3, name = "system_program", desc = "System program")]
+    SetCatalogItem { definition: ItemDefinition },
+
+    /// Give a player `quantity` of a catalog item, creating their inventory on
+    /// first use. Game server only.
+    #[account(
+        0,
+        writable,
+        name = "inventory",
+        desc = "Inventory PDA (seeds: 'inventory', player)"
+    )]
+    #[account(1, name = "player", desc = "Player receiving the item")]
+    #[account(
+        2,
+        writable,
+        signer,
+        name = "game_server",
+        desc = "Game server key from the config, pays for the inventory to grow"
+    )]
+    #[account(3, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(4, name = "catalog", desc = "Catalog PDA (seeds: 'catalog')")]
+    #[account(5, name = "system_program", desc = "System program")]
+    GrantItem { item_id: u32, quantity: u32 },
+
+    /// Use up one of the player's `item_id`, applying its effect to their score
+    /// account in `game_mode`.
+    #[account(
+        0,
+        writable,
+        name = "inventory",
+        desc = "Inventory PDA (seeds: 'inventory', player)"
+    )]
+    #[account(1, signer, name = "player", desc = "Player")]
+    #[account(2, name = "catalog", desc = "Catalog PDA (seeds: 'catalog')")]
+    #[account(
+        3,
+        writable,
+        name = "score_account",
+        desc = "Score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    ConsumeItem { game_mode: GameMode, item_id: u32 },
+
+    /// Buy `quantity` of a catalog item at its price in `currency`, paid into
+    /// the treasury, and add it to the player's inventory. The config.fee_bps
+    /// house cut of a SOL price goes to the fee vault instead; token payments go
+    /// to the treasury whole, less any Token-2022 transfer fee. Accounts after the
+    /// token program are passed on to the shop mint's transfer hook, if it has one.
+    #[account(
+        0,
+        writable,
+        name = "inventory",
+        desc = "Inventory PDA (seeds: 'inventory', player)"
+    )]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "player",
+        desc = "Player, pays for the item and any inventory growth"
+    )]
+    #[account(2, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(3, name = "catalog", desc = "Catalog PDA (seeds: 'catalog')")]
+    #[account(
+        4,
+        writable,
+        name = "treasury",
+        desc = "Treasury PDA (seeds: 'treasury'), receives SOL payments"
+    )]
+    #[account(5, name = "system_program", desc = "System program")]
+    #[account(
+        6,
+        writable,
+        name = "fee_vault",
+        desc = "Fee vault PDA (seeds: 'fee_vault'), receives the house fee"
+    )]
+    #[account(
+        7,
+        optional,
+        writable,
+        name = "global_stats",
+        desc = "Global stats PDA (seeds: 'global_stats'), counts the house fee"
+    )]
+    #[account(
+        8,
+        optional,
+        writable,
+        name = "player_token_account",
+        desc = "Player's token account for the shop mint, for token payments"
+    )]
+    #[account(
+        9,
+        optional,
+        writable,
+        name = "treasury_token_account",
+        desc = "Treasury's associated token account for the shop mint, for token payments"
+    )]
+    #[account(
+        10,
+        optional,
+        name = "shop_mint",
+        desc = "Shop mint set in the config, for token payments"
+    )]
+    #[account(
+        11,
+        optional,
+        name = "token_program",
+        desc = "SPL Token or Token-2022 program owning the shop mint, for token payments"
+    )]
+    PurchaseItem {
+        item_id: u32,
+        quantity: u32,
+        currency: ShopCurrency,
+    },
+
+    /// Burn the config's continue_burn_amount of reward tokens from the
+    /// player's associated token account so the current run may be resumed,
+    /// up to max_continues_per_run times per run.
+    #[account(
+        0,
+        writable,
+        name = "score_account",
+        desc = "Score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(1, signer, name = "player", desc = "Player")]
+    #[account(2, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        3,
+        writable,
+        name = "player_token_account",
+        desc = "Player's associated token account for the reward mint"
+    )]
+    #[account(4, writable, name = "reward_mint", desc = "Reward token mint")]
+    #[account(
+        5,
+        name = "token_program",
+        desc = "SPL Token or Token-2022 program owning the reward mint"
+    )]
+    Continue { game_mode: GameMode },
+
+    /// Once the current season has ended and its prizes are paid, start the
+    /// next one, config.season_length_secs long. Anyone may send it and is
+    /// tipped config.crank_tip_lamports from the treasury.
+    #[account(0, writable, name = "season", desc = "Season PDA (seeds: 'season')")]
+    #[account(1, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        2,
+        writable,
+        signer,
+        name = "cranker",
+        desc = "Crank sender, receives the tip"
+    )]
+    #[account(
+        3,
+        writable,
+        name = "treasury",
+        desc = "Treasury PDA (seeds: 'treasury')"
+    )]
+    #[account(4, name = "system_program", desc = "System program")]
+    CrankSeasonRollover,
+
+    /// DistributePrizes for cranks: once the season has ended, pay the prize
+    /// pool out and clear the leaderboard, tipping the sender
+    /// config.crank_tip_lamports from the treasury. The winners' wallets
+    /// follow in rank order, one per paid place that has an entry, each
+    /// followed by the winner's vesting PDA when prizes vest.
+    #[account(
+        0,
+        writable,
+        name = "leaderboard",
+        desc = "Leaderboard PDA (seeds: 'leaderboard')"
+    )]
+    #[account(1, writable, name = "season", desc = "Season PDA (seeds: 'season')")]
+    #[account(2, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        3,
+        writable,
+        name = "prize_pool",
+        desc = "Prize pool PDA (seeds: 'prize_pool')"
+    )]
+    #[account(
+        4,
+        writable,
+        signer,
+        name = "cranker",
+        desc = "Crank sender, receives the tip"
+    )]
+    #[account(
+        5,
+        writable,
+        name = "treasury",
+        desc = "Treasury PDA (seeds: 'treasury')"
+    )]
+    #[account(6, name = "system_program", desc = "System program")]
+    CrankLeaderboardFinalize,
+
+    /// Succeed if the player's score is at least `threshold`, and fail with
+    /// ScoreBelowThreshold otherwise, so other programs can gate on a score by
+    /// CPI instead of decoding the account. The score is set as return data, a
+    /// Borsh u64. The score is checked after the season rollover and decay the
+    /// next update would apply. No account signs or is written, and older
+    /// account layouts are read as they are. The tag and error code are stable.
+    #[account(
+        0,
+        name = "score_account",
+        desc = "Score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(1, name = "player", desc = "Player whose score is checked")]
+    #[account(2, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        3,
+        name = "season",
+        desc = "Season PDA (seeds: 'season'), may be uninitialized"
+    )]
+    VerifyScoreAtLeast { threshold: u64 },
+
+    /// Move `amount` lamports out of the treasury, leaving it rent exempt. Only
+    /// the admin may, which can be the admin set through a proposal, and at most
+    /// config.treasury_withdraw_cap_lamports per epoch when that is set. Logs a
+    /// TreasuryWithdrawn event.
+    #[account(0, writable, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(1, signer, name = "admin", desc = "Config admin")]
+    #[account(
+        2,
+        writable,
+        name = "treasury",
+        desc = "Treasury PDA (seeds: 'treasury')"
+    )]
+    #[account(3, writable, name = "destination", desc = "Receives the lamports")]
+    #[account(4, name = "system_program", desc = "System program")]
+    WithdrawTreasury { amount: u64 },
+
+    /// Create one of the leaderboard pages ranking players below the global
+    /// board, numbered from 1 to LEADERBOARD_PAGES - 1.
+    #[account(
+        0,
+        writable,
+        name = "page",
+        desc = "Leaderboard page PDA (seeds: 'leaderboard', [page])"
+    )]
+    #[account(1, writable, signer, name = "payer", desc = "Payer")]
+    #[account(2, name = "system_program", desc = "System program")]
+    InitializeLeaderboardPage { page: u8 },
+
+    /// Submit the player's best score to a leaderboard page, using up their
+    /// ranked run like SubmitToLeaderboard. The page above must be full and rank
+    /// the score no higher than its lowest entry, and must not list the player.
+    #[account(
+        0,
+        writable,
+        name = "page",
+        desc = "Leaderboard page PDA (seeds: 'leaderboard', [page])"
+    )]
+    #[account(
+        1,
+        name = "page_above",
+        desc = "The page above: the leaderboard PDA for page 1, otherwise (seeds: 'leaderboard', [page - 1])"
+    )]
+    #[account(
+        2,
+        writable,
+        name = "score_account",
+        desc = "Score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(3, signer, name = "player", desc = "Player")]
+    #[account(4, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        5,
+        name = "ban_list",
+        desc = "Ban list PDA (seeds: 'ban_list'), may be uninitialized"
+    )]
+    #[account(
+        6,
+        optional,
+        writable,
+        name = "next_page",
+        desc = "Leaderboard page PDA (seeds: 'leaderboard', [page + 1]), may be uninitialized, to keep the entry a full page displaces"
+    )]
+    SubmitToLeaderboardPage { game_mode: GameMode, page: u8 },
+
+    /// Move entries between `page` and the page above it until every entry
+    /// above ranks at least as high as every entry below, promoting the best of
+    /// `page` and demoting what they displace. Anyone may send it.
+    #[account(
+        0,
+        writable,
+        name = "page_above",
+        desc = "The page above: the leaderboard PDA for page 1, otherwise (seeds: 'leaderboard', [page - 1])"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "page",
+        desc = "Leaderboard page PDA (seeds: 'leaderboard', [page])"
+    )]
+    RebalanceLeaderboardPages { page: u8 },
+
+    /// Create the weekly leaderboard, starting at the current week.
+    #[account(
+        0,
+        writable,
+        name = "weekly_leaderboard",
+        desc = "Weekly leaderboard PDA (seeds: 'weekly_leaderboard')"
+    )]
+    #[account(1, writable, signer, name = "payer", desc = "Payer")]
+    #[account(2, name = "system_program", desc = "System program")]
+    InitializeWeeklyLeaderboard,
+
+    /// Submit the best score the player recorded this week to the weekly
+    /// leaderboard. The first submission in a new week clears last week's
+    /// entries. Unlike the all-time board it needs no ranked run, but it too
+    /// rejects modes other than RANKED_GAME_MODE.
+    #[account(
+        0,
+        writable,
+        name = "weekly_leaderboard",
+        desc = "Weekly leaderboard PDA (seeds: 'weekly_leaderboard')"
+    )]
+    #[account(
+        1,
+        name = "score_account",
+        desc = "Score PDA (seeds: 'score', player, [game_mode])"
+    )]
+    #[account(2, signer, name = "player", desc = "Player")]
+    #[account(3, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        4,
+        name = "ban_list",
+        desc = "Ban list PDA (seeds: 'ban_list'), may be uninitialized"
+    )]
+    SubmitToWeeklyLeaderboard { game_mode: GameMode },
+
+    /// Follow another player, creating the follow edge and counting the
+    /// follower on their profile.
+    #[account(
+        0,
+        writable,
+        name = "follow",
+        desc = "Follow PDA (seeds: 'follow', follower, followed)"
+    )]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "follower",
+        desc = "Following player, paying for the edge"
+    )]
+    #[account(2, name = "followed", desc = "Player to follow")]
+    #[account(
+        3,
+        writable,
+        name = "followed_profile",
+        desc = "Followed player's profile PDA (seeds: 'profile', followed)"
+    )]
+    #[account(4, name = "system_program", desc = "System program")]
+    Follow,
+
+    /// Stop following a player, closing the follow edge and refunding its rent.
+    #[account(
+        0,
+        writable,
+        name = "follow",
+        desc = "Follow PDA (seeds: 'follow', follower, followed)"
+    )]
+    #[account(1, writable, signer, name = "follower", desc = "Following player")]
+    #[account(2, name = "followed", desc = "Followed player")]
+    #[account(
+        3,
+        writable,
+        name = "followed_profile",
+        desc = "Followed player's profile PDA (seeds: 'profile', followed)"
+    )]
+    Unfollow,
+
+    /// Set the region the player ranks under on regional leaderboards. A player
+    /// can set it once; after that only the admin can correct it.
+    #[account(
+        0,
+        writable,
+        name = "profile",
+        desc = "Player's profile PDA (seeds: 'profile', player)"
+    )]
+    #[account(1, signer, name = "player", desc = "Player")]
+    SetRegion { region: [u8; 2] },
+
+    /// Correct a player's region. Entries already on the old region's leaderboard
+    /// stay there until better scores push them off.
+    #[account(
+        0,
+        writable,
+        name = "profile",
+        desc = "Player's profile PDA (seeds: 'profile', player)"
+    )]
+    #[account(1, name = "player", desc = "Player whose region is corrected")]
+    #[account(2, signer, name = "admin", desc = "Admin")]
+    #[account(3, name = "config", desc = "Config PDA (seeds: 'config')")]
+    AdminSetRegion { region: [u8; 2] },
+
+    /// Create the leaderboard of one region.
+    #[account(
+        0,
+        writable,
+        name = "regional_leaderboard",
+        desc = "Regional leaderboard PDA (seeds: 'regional_leaderboard', region)"
+    )]
+    #[account(1, writable, signer, name = "payer", desc = "Payer")]
+    #[account(2, name = "system_program", desc = "System program")]
+    InitializeRegionalLeaderboard { region: [u8; 2] },
+
+    /// Submit the player's best score to the leaderboard of the region on their
+    /// profile. Unlike the global board it needs no ranked run.
+    #[account(
+        0,
+        writable,
+        name = "regional_leaderboard",
+        desc = "Regional leaderboard PDA (seeds: 'regional_leaderboard', region)"
+    )]
+    #[account(
+        1,
+        name = "score_account",
+        desc = "Score PDA (seeds: 'score', player, [game_mode])"
+    )]
+    #[account(2, signer, name = "player", desc = "Player")]
+    #[account(
+        3,
+        name = "profile",
+        desc = "Player's profile PDA (seeds: 'profile', player)"
+    )]
+    #[account(4, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        5,
+        name = "ban_list",
+        desc = "Ban list PDA (seeds: 'ban_list'), may be uninitialized"
+    )]
+    SubmitToRegionalLeaderboard { game_mode: GameMode },
+
+    /// Open a game session for the player, recording the slot it starts in. The
+    /// game server co-signs and provides `seed`, which keys the session account.
+    #[account(
+        0,
+        writable,
+        name = "game_session",
+        desc = "Game session PDA (seeds: 'game_session', player, seed)"
+    )]
+    #[account(
+        1,
+        name = "score_account",
+        desc = "Score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(
+        2,
+        writable,
+        signer,
+        name = "player",
+        desc = "Player, paying for the session"
+    )]
+    #[account(3, signer, name = "game_server", desc = "Configured game server")]
+    #[account(4, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(5, name = "system_program", desc = "System program")]
+    StartSession { game_mode: GameMode, seed: [u8; 32] },
+
+    /// Close a game session and credit `score` as a run, like SubmitRun. The game
+    /// server co-signs the result, and the score must be plausible for the slots
+    /// the session ran at config.max_score_per_slot. A session older than
+    /// config.session_max_slots is closed without crediting anything. The score
+    /// must also stay within config.max_score_per_second over the seconds between
+    /// the session's start and end.
+    #[account(
+        0,
+        writable,
+        name = "game_session",
+        desc = "Game session PDA (seeds: 'game_session', player, seed)"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "score_account",
+        desc = "Score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(
+        2,
+        writable,
+        signer,
+        name = "player",
+        desc = "Player, refunded the session rent"
+    )]
+    #[account(3, signer, name = "game_server", desc = "Configured game server")]
+    #[account(
+        4,
+        name = "season",
+        desc = "Season PDA (seeds: 'season'), may be uninitialized"
+    )]
+    #[account(5, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        6,
+        name = "ban_list",
+        desc = "Ban list PDA (seeds: 'ban_list'), may be uninitialized"
+    )]
+    #[account(
+        7,
+        optional,
+        writable,
+        name = "global_stats",
+        desc = "Global stats PDA (seeds: 'global_stats'), counts the run and any new best"
+    )]
+    #[account(
+        8,
+        optional,
+        writable,
+        name = "profile",
+        desc = "Player's profile PDA (seeds: 'profile', player), to earn XP for the run"
+    )]
+    EndSession { score: u64 },
+
+    /// Open the signer's matchmaking lobby for 2 to LOBBY_CAPACITY players, each
+    /// staking `stake` lamports to join. A stake of config.high_stakes_wager or
+    /// more needs the host's profile at config.high_stakes_min_level.
+    #[account(0, writable, name = "lobby", desc = "Lobby PDA (seeds: 'lobby', host)")]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "host",
+        desc = "Lobby host, pays the account rent"
+    )]
+    #[account(2, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(3, name = "system_program", desc = "System program")]
+    #[account(
+        4,
+        optional,
+        name = "host_profile",
+        desc = "Host's profile PDA (seeds: 'profile', host), for high stakes"
+    )]
+    CreateLobby { capacity: u8, stake: u64 },
+
+    /// Wait in a lobby for a match, moving the lobby's stake from the player into
+    /// the lobby account. A high-stakes lobby needs the player's profile at
+    /// config.high_stakes_min_level, as in CreateMatch.
+    #[account(0, writable, name = "lobby", desc = "Lobby PDA (seeds: 'lobby', host)")]
+    #[account(1, writable, signer, name = "player", desc = "Player, pays the stake")]
+    #[account(2, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(3, name = "system_program", desc = "System program")]
+    #[account(
+        4,
+        optional,
+        name = "profile",
+        desc = "Player's profile PDA (seeds: 'profile', player), for high stakes"
+    )]
+    JoinLobby,
+
+    /// Stop waiting in a lobby and take the stake back.
+    #[account(0, writable, name = "lobby", desc = "Lobby PDA (seeds: 'lobby', host)")]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "player",
+        desc = "Waiting player, refunded the stake"
+    )]
+    LeaveLobby,
+
+    /// Pair the two players who have waited longest in a lobby into a wager match,
+    /// moving both stakes from the lobby into the new match account. The match is
+    /// numbered by the matches the lobby has started and is decided and settled
+    /// like one from CreateMatch. Sent by the longest-waiting player.
+    #[account(0, writable, name = "lobby", desc = "Lobby PDA (seeds: 'lobby', host)")]
+    #[account(
+        1,
+        writable,
+        name = "match_account",
+        desc = "Match PDA (seeds: 'match', player_a, player_b, match_id)"
+    )]
+    #[account(
+        2,
+        writable,
+        signer,
+        name = "player_a",
+        desc = "Longest-waiting player, pays the match account rent"
+    )]
+    #[account(3, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(4, name = "system_program", desc = "System program")]
+    StartMatch,
+
+    /// Bet `amount` lamports from a spectator on player `on` winning an active
+    /// match. The stake is held in the match's bet pool, which the first bet
+    /// creates. Further bets from the same bettor add to their bet and must back
+    /// the same player. The match's own players cannot bet. Once the bet comes to
+    /// config.high_stakes_wager or more, the bettor's profile must have reached
+    /// config.high_stakes_min_level.
+    #[account(
+        0,
+        writable,
+        name = "bet_pool",
+        desc = "Bet pool PDA (seeds: 'bet_pool', match)"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "bet",
+        desc = "Bet PDA (seeds: 'bet', match, bettor)"
+    )]
+    #[account(
+        2,
+        name = "match_account",
+        desc = "Match PDA (seeds: 'match', player_a, player_b, match_id)"
+    )]
+    #[account(
+        3,
+        writable,
+        signer,
+        name = "bettor",
+        desc = "Bettor, pays the stake and the rent of the accounts it creates"
+    )]
+    #[account(4, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(5, name = "system_program", desc = "System program")]
+    #[account(
+        6,
+        optional,
+        name = "bettor_profile",
+        desc = "Bettor's profile PDA (seeds: 'profile', bettor), for high stakes"
+    )]
+    PlaceBet { on: Pubkey, amount: u64 },
+
+    /// Pay out bets on a decided match and close them. The first call records the
+    /// result in the pool and sends config.bet_fee_bps of everything staked to the
+    /// treasury; the winning bets then share the rest pro-rata to their stakes.
+    /// When nobody backed the winner, or the match was settled before its result
+    /// reached the pool, every bet is refunded instead. Each bet account and its
+    /// bettor's wallet follow the fixed accounts in pairs. The pool closes into the
+    /// treasury, rounding dust included, with its last bet. Anyone may send it.
+    #[account(
+        0,
+        writable,
+        name = "bet_pool",
+        desc = "Bet pool PDA (seeds: 'bet_pool', match)"
+    )]
+    #[account(
+        1,
+        name = "match_account",
+        desc = "Match PDA (seeds: 'match', player_a, player_b, match_id), may be closed"
+    )]
+    #[account(
+        2,
+        writable,
+        name = "treasury",
+        desc = "Treasury PDA (seeds: 'treasury'), receives the house fee"
+    )]
+    #[account(3, name = "config", desc = "Config PDA (seeds: 'config')")]
+    SettleBets,
+
+    /// Move `amount` lamports of collected house fees out of the fee vault,
+    /// leaving it rent exempt. Admin only.
+    #[account(0, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(1, signer, name = "admin", desc = "Config admin")]
+    #[account(
+        2,
+        writable,
+        name = "fee_vault",
+        desc = "Fee vault PDA (seeds: 'fee_vault')"
+    )]
+    #[account(3, writable, name = "destination", desc = "Receives the lamports")]
+    #[account(4, name = "system_program", desc = "System program")]
+    WithdrawFees { amount: u64 },
+
+    /// Pay the beneficiary of a vesting season prize whatever has unlocked
+    /// since their last claim. The account closes to them with the final claim.
+    #[account(
+        0,
+        writable,
+        name = "vesting",
+        desc = "Vesting PDA (seeds: 'vesting', season_id, beneficiary)"
+    )]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "beneficiary",
+        desc = "Prize winner, receives the unlocked lamports"
+    )]
+    ClaimVested,
+
+    /// Lock `amount` of the player's reward tokens in their stake vault, creating
+    /// the stake and vault on the first stake. Staking restarts the unstake cooldown
+    /// on everything staked. Token-2022 transfer fees are withheld from the stake.
+    /// A `lock` commits everything staked for its duration from now, unless a lock
+    /// ending later already runs, and boosts the multiplier by the config's lock bonus.
+    #[account(0, writable, name = "stake", desc = "Stake PDA (seeds: 'stake', player)")]
+    #[account(
+        1,
+        writable,
+        name = "stake_vault",
+        desc = "Stake vault PDA (seeds: 'stake_vault', player)"
+    )]
+    #[account(
+        2,
+        writable,
+        signer,
+        name = "player",
+        desc = "Player, pays the rent of the accounts it creates"
+    )]
+    #[account(
+        3,
+        writable,
+        name = "player_token_account",
+        desc = "Player's reward token account"
+    )]
+    #[account(4, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(5, name = "reward_mint", desc = "Reward mint set in the config")]
+    #[account(
+        6,
+        name = "token_program",
+        desc = "SPL Token or Token-2022 program owning the reward mint"
+    )]
+    #[account(7, name = "system_program", desc = "System program")]
+    Stake { amount: u64, lock: StakeLock },
+
+    /// Return `amount` of the player's staked reward tokens once the unstake
+    /// cooldown since their last stake has passed. Before the stake's lock ends,
+    /// config.early_unstake_penalty_bps of the amount goes to the treasury.
+    #[account(0, writable, name = "stake", desc = "Stake PDA (seeds: 'stake', player)")]
+    #[account(
+        1,
+        writable,
+        name = "stake_vault",
+        desc = "Stake vault PDA (seeds: 'stake_vault', player)"
+    )]
+    #[account(2, signer, name = "player", desc = "Player")]
+    #[account(
+        3,
+        writable,
+        name = "player_token_account",
+        desc = "Player's reward token account, receives the tokens"
+    )]
+    #[account(4, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(5, name = "reward_mint", desc = "Reward mint set in the config")]
+    #[account(
+        6,
+        name = "token_program",
+        desc = "SPL Token or Token-2022 program owning the reward mint"
+    )]
+    #[account(7, name = "treasury", desc = "Treasury PDA (seeds: 'treasury')")]
+    #[account(
+        8,
+        writable,
+        name = "treasury_token_account",
+        desc = "Treasury's associated reward token account, receives any early unstake penalty"
+    )]
+    Unstake { amount: u64 },
+
+    /// Create quest `quest_id`, paying `reward` reward tokens to each player whose
+    /// count of `metric` since accepting the quest reaches `target`. Admin only.
+    #[account(
+        0,
+        writable,
+        name = "quest",
+        desc = "Quest PDA (seeds: 'quest', quest_id)"
+    )]
+    #[account(1, writable, signer, name = "admin", desc = "Admin, pays rent")]
+    #[account(2, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(3, name = "system_program", desc = "System program")]
+    CreateQuest {
+        quest_id: u64,
+        metric: QuestMetric,
+        target: u64,
+        reward: u64,
+    },
+
+    /// Start tracking the player's progress towards a quest. Only what happens
+    /// after accepting counts.
+    #[account(
+        0,
+        writable,
+        name = "quest_progress",
+        desc = "Quest progress PDA (seeds: 'quest_progress', quest_id, player)"
+    )]
+    #[account(1, name = "quest", desc = "Quest PDA (seeds: 'quest', quest_id)")]
+    #[account(
+        2,
+        writable,
+        signer,
+        name = "player",
+        desc = "Player, pays rent"
+    )]
+    #[account(3, name = "system_program", desc = "System program")]
+    AcceptQuest,
+
+    /// Pay a quest's reward from the reward vault once the player's progress has
+    /// reached its target. Each player claims a quest once. Accounts after the
+    /// token program are passed on to the mint's transfer hook, if it has one.
+    #[account(
+        0,
+        writable,
+        name = "quest_progress",
+        desc = "Quest progress PDA (seeds: 'quest_progress', quest_id, player)"
+    )]
+    #[account(1, name = "quest", desc = "Quest PDA (seeds: 'quest', quest_id)")]
+    #[account(2, signer, name = "player", desc = "Player")]
+    #[account(3, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        4,
+        writable,
+        name = "vault",
+        desc = "Reward vault token account PDA (seeds: 'vault')"
+    )]
+    #[account(
+        5,
+        writable,
+        name = "player_token_account",
+        desc = "Player's associated token account for the reward mint"
+    )]
+    #[account(6, name = "reward_mint", desc = "Reward mint set in the config")]
+    #[account(
+        7,
+        name = "token_program",
+        desc = "SPL Token or Token-2022 program owning the reward mint"
+    )]
+    ClaimQuestReward,
+
+    /// Publish the daily challenge for UTC day `date` (unix time divided by
+    /// SECONDS_PER_DAY): the `seed` every player's game generates that day's level
+    /// from, and an empty leaderboard for it. Days can be published ahead but not
+    /// after they have started, and only once. Admin only.
+    #[account(
+        0,
+        writable,
+        name = "daily_challenge",
+        desc = "Daily challenge PDA (seeds: 'daily_challenge', date)"
+    )]
+    #[account(1, writable, signer, name = "admin", desc = "Admin, pays rent")]
+    #[account(2, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(3, name = "system_program", desc = "System program")]
+    PublishDailyChallenge { seed: [u8; 32], date: i64 },
+
+    /// Submit the player's `score` on today's daily challenge to its leaderboard.
+    /// The game server co-signs the result, and each player submits once per day.
+    #[account(
+        0,
+        writable,
+        name = "daily_challenge",
+        desc = "Daily challenge PDA (seeds: 'daily_challenge', date)"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "daily_submission",
+        desc = "Daily submission PDA (seeds: 'daily_submission', date, player)"
+    )]
+    #[account(
+        2,
+        writable,
+        signer,
+        name = "player",
+        desc = "Player, pays for the submission"
+    )]
+    #[account(3, signer, name = "game_server", desc = "Configured game server")]
+    #[account(4, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(
+        5,
+        name = "ban_list",
+        desc = "Ban list PDA (seeds: 'ban_list'), may be uninitialized"
+    )]
+    #[account(6, name = "system_program", desc = "System program")]
+    SubmitDailyChallenge { date: i64, score: u64 },
+
+    /// Create cosmetic `cosmetic_id` (0-63), or change what unlocks it: reaching
+    /// `min_level`, plus holding `achievement` in `game_mode` unless it is
+    /// NO_ACHIEVEMENT. Cosmetics already unlocked stay unlocked. Admin only.
+    #[account(
+        0,
+        writable,
+        name = "cosmetic",
+        desc = "Cosmetic PDA (seeds: 'cosmetic', cosmetic_id)"
+    )]
+    #[account(1, writable, signer, name = "admin", desc = "Admin, pays rent")]
+    #[account(2, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(3, name = "system_program", desc = "System program")]
+    SetCosmetic {
+        cosmetic_id: u8,
+        min_level: u16,
+        game_mode: GameMode,
+        achievement: u8,
+    },
+
+    /// Set the cosmetic's bit in the player's profile unlocks once the player
+    /// meets its requirements, so anyone can verify which cosmetics they own from
+    /// the profile alone. Anyone may send it.
+    #[account(
+        0,
+        name = "cosmetic",
+        desc = "Cosmetic PDA (seeds: 'cosmetic', cosmetic_id)"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "profile",
+        desc = "Player's profile PDA (seeds: 'profile', player)"
+    )]
+    #[account(2, name = "player", desc = "Player")]
+    #[account(
+        3,
+        optional,
+        name = "score_account",
+        desc = "Score account PDA (seeds: 'score', player, game_mode), if the cosmetic needs an achievement"
+    )]
+    UnlockCosmetic,
+
+    /// Add a badge for players reaching `threshold` in `milestone`, creating the
+    /// badge catalog with the first one. The badge's mint is a new Token-2022 mint
+    /// with the non-transferable extension, so badges stay with the player they
+    /// were minted to. Its id is its position in the catalog. Admin only.
+    #[account(
+        0,
+        writable,
+        name = "badge_catalog",
+        desc = "Badge catalog PDA (seeds: 'badge_catalog')"
+    )]
+    #[account(
+        1,
+        writable,
+        signer,
+        name = "badge_mint",
+        desc = "New keypair for the badge mint"
+    )]
+    #[account(
+        2,
+        name = "badge_authority",
+        desc = "Badge authority PDA (seeds: 'badge_authority')"
+    )]
+    #[account(3, writable, signer, name = "admin", desc = "Admin, pays rent")]
+    #[account(4, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(5, name = "token_program", desc = "Token-2022 program")]
+    #[account(6, name = "system_program", desc = "System program")]
+    AddBadge {
+        milestone: BadgeMilestone,
+        threshold: u64,
+    },
+
+    /// Mint one token of badge `badge_id` to the player once their score account in
+    /// `game_mode` reaches its milestone. A player holds at most one of each badge.
+    #[account(
+        0,
+        name = "badge_catalog",
+        desc = "Badge catalog PDA (seeds: 'badge_catalog')"
+    )]
+    #[account(
+        1,
+        name = "score_account",
+        desc = "Score account PDA (seeds: 'score', player, game_mode)"
+    )]
+    #[account(
+        2,
+        writable,
+        signer,
+        name = "player",
+        desc = "Player, pays for their badge token account"
+    )]
+    #[account(3, writable, name = "badge_mint", desc = "The badge's mint")]
+    #[account(
+        4,
+        writable,
+        name = "badge_token_account",
+        desc = "Player's associated token account for the badge mint"
+    )]
+    #[account(
+        5,
+        name = "badge_authority",
+        desc = "Badge authority PDA (seeds: 'badge_authority')"
+    )]
+    #[account(6, name = "token_program", desc = "Token-2022 program")]
+    #[account(
+        7,
+        name = "associated_token_program",
+        desc = "Associated Token Account program"
+    )]
+    #[account(8, name = "system_program", desc = "System program")]
+    ClaimBadge { badge_id: u8, game_mode: GameMode },
+
+    /// Create the global stats account. Instructions that are passed it from then
+    /// on add their new players, runs, score and house fees to its totals, and
+    /// keep its histogram of best scores current.
+    #[account(
+        0,
+        writable,
+        name = "global_stats",
+        desc = "Global stats PDA (seeds: 'global_stats')"
+    )]
+    #[account(1, writable, signer, name = "admin", desc = "Admin, pays rent")]
+    #[account(2, name = "config", desc = "Config PDA (seeds: 'config')")]
+    #[account(3, name = "system_program", desc = "System program")]
+    InitializeGlobalStats,
+}
+
+impl GarbageGrabInstruction {
+    // Decode instruction data, rejecting unknown tags and malformed payloads
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        Self::try_from_slice(input).map_err(|_| ProgramError::InvalidInstructionData)
+    }
+
+    // Encode the instruction into its wire format
+    pub fn pack(&self) -> Vec<u8> {
+        borsh::to_vec(self).expect("instruction serialization cannot fail")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::ItemEffect;
+
+    #[test]
+    fn initialize_round_trip() {
+        let ix = GarbageGrabInstruction::Initialize {
+            game_mode: GameMode::Endless,
+            referrer: None,
+            allowlist_proof: Vec::new(),
+        };
+        let data = ix.pack();
+        assert_eq!(data, vec![0, 1, 0, 0, 0, 0, 0]);
+        assert_eq!(GarbageGrabInstruction::unpack(&data).unwrap(), ix);
+
+        let referrer = Pubkey::new_unique();
+        let ix = GarbageGrabInstruction::Initialize {
+            game_mode: GameMode::Arcade,
+            referrer: Some(referrer),
+            allowlist_proof: vec![[9; 32]],
+        };
+        let mut expected = vec![0, 0, 1];
+        expected.extend_from_slice(referrer.as_ref());
+        expected.extend_from_slice(&1u32.to_le_bytes());
+        expected.extend_from_slice(&[9; 32]);
+        assert_eq!(ix.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&ix.pack()).unwrap(), ix);
+    }
+
+    #[test]
+    fn update_score_round_trip() {
+        let ix = GarbageGrabInstruction::UpdateScore {
+            game_mode: GameMode::DailyChallenge,
+            increment: 50,
+        };
+        let data = ix.pack();
+        let mut expected = vec![1, 2];
+        expected.extend_from_slice(&50u64.to_le_bytes());
+        assert_eq!(data, expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&data).unwrap(), ix);
+    }
+
+    #[test]
+    fn reset_score_round_trip() {
+        let ix = GarbageGrabInstruction::ResetScore {
+            game_mode: GameMode::Arcade,
+        };
+        let data = ix.pack();
+        assert_eq!(data, vec![2, 0]);
+        assert_eq!(GarbageGrabInstruction::unpack(&data).unwrap(), ix);
+    }
+
+    #[test]
+    fn close_score_account_round_trip() {
+        let ix = GarbageGrabInstruction::CloseScoreAccount {
+            game_mode: GameMode::Endless,
+        };
+        let data = ix.pack();
+        assert_eq!(data, vec![3, 1]);
+        assert_eq!(GarbageGrabInstruction::unpack(&data).unwrap(), ix);
+    }
+
+    #[test]
+    fn submit_run_round_trip() {
+        let ix = GarbageGrabInstruction::SubmitRun {
+            game_mode: GameMode::Arcade,
+            run_score: 1_250,
+            run_hash: [7; 32],
+        };
+        let data = ix.pack();
+        let mut expected = vec![4, 0];
+        expected.extend_from_slice(&1_250u64.to_le_bytes());
+        expected.extend_from_slice(&[7; 32]);
+        assert_eq!(data, expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&data).unwrap(), ix);
+    }
+
+    #[test]
+    fn leaderboard_round_trip() {
+        let init = GarbageGrabInstruction::InitializeLeaderboard;
+        assert_eq!(init.pack(), vec![5]);
+        assert_eq!(GarbageGrabInstruction::unpack(&init.pack()).unwrap(), init);
+
+        let submit = GarbageGrabInstruction::SubmitToLeaderboard {
+            game_mode: GameMode::Endless,
+        };
+        assert_eq!(submit.pack(), vec![6, 1]);
+        assert_eq!(
+            GarbageGrabInstruction::unpack(&submit.pack()).unwrap(),
+            submit
+        );
+    }
+
+    #[test]
+    fn start_season_round_trip() {
+        let ix = GarbageGrabInstruction::StartSeason {
+            end_ts: 1_700_000_000,
+        };
+        let data = ix.pack();
+        let mut expected = vec![7];
+        expected.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+        assert_eq!(data, expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&data).unwrap(), ix);
+    }
+
+    #[test]
+    fn config_round_trip() {
+        let init = GarbageGrabInstruction::InitializeConfig {
+            max_score: 1_000_000,
+            max_increment: 500,
+            update_cooldown_secs: 2,
+        };
+        let mut expected = vec![8];
+        expected.extend_from_slice(&1_000_000u64.to_le_bytes());
+        expected.extend_from_slice(&500u64.to_le_bytes());
+        expected.extend_from_slice(&2u32.to_le_bytes());
+        assert_eq!(init.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&init.pack()).unwrap(), init);
+
+        let update = GarbageGrabInstruction::UpdateConfig(ConfigUpdate {
+            max_increment: Some(250),
+            paused: Some(true),
+            ..ConfigUpdate::default()
+        });
+        let mut expected = vec![9, 0, 1];
+        expected.extend_from_slice(&250u64.to_le_bytes());
+        expected.extend_from_slice(&[
+            1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]);
+        assert_eq!(update.pack(), expected);
+        assert_eq!(
+            GarbageGrabInstruction::unpack(&update.pack()).unwrap(),
+            update
+        );
+    }
+
+    #[test]
+    fn pause_round_trip() {
+        assert_eq!(GarbageGrabInstruction::Pause.pack(), vec![10]);
+        assert_eq!(GarbageGrabInstruction::Unpause.pack(), vec![11]);
+        assert_eq!(
+            GarbageGrabInstruction::unpack(&[10]).unwrap(),
+            GarbageGrabInstruction::Pause
+        );
+    }
+
+    #[test]
+    fn penalize_score_round_trip() {
+        let ix = GarbageGrabInstruction::PenalizeScore {
+            game_mode: GameMode::Arcade,
+            amount: 75,
+        };
+        let mut expected = vec![13, 0];
+        expected.extend_from_slice(&75u64.to_le_bytes());
+        assert_eq!(ix.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&ix.pack()).unwrap(), ix);
+    }
+
+    #[test]
+    fn set_score_round_trip() {
+        let ix = GarbageGrabInstruction::SetScore {
+            game_mode: GameMode::DailyChallenge,
+            value: 9_000,
+        };
+        let mut expected = vec![14, 2];
+        expected.extend_from_slice(&9_000u64.to_le_bytes());
+        assert_eq!(ix.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&ix.pack()).unwrap(), ix);
+    }
+
+    #[test]
+    fn migrate_account_round_trip() {
+        let ix = GarbageGrabInstruction::MigrateAccount {
+            game_mode: GameMode::Endless,
+        };
+        assert_eq!(ix.pack(), vec![15, 1]);
+        assert_eq!(GarbageGrabInstruction::unpack(&ix.pack()).unwrap(), ix);
+    }
+
+    #[test]
+    fn claim_rewards_round_trip() {
+        let ix = GarbageGrabInstruction::ClaimRewards {
+            game_mode: GameMode::DailyChallenge,
+        };
+        assert_eq!(ix.pack(), vec![16, 2]);
+        assert_eq!(GarbageGrabInstruction::unpack(&ix.pack()).unwrap(), ix);
+    }
+
+    #[test]
+    fn initialize_vault_round_trip() {
+        let ix = GarbageGrabInstruction::InitializeVault;
+        assert_eq!(ix.pack(), vec![17]);
+        assert_eq!(GarbageGrabInstruction::unpack(&ix.pack()).unwrap(), ix);
+    }
+
+    #[test]
+    fn fund_vault_round_trip() {
+        let ix = GarbageGrabInstruction::FundVault { amount: 500 };
+        let mut expected = vec![18];
+        expected.extend_from_slice(&500u64.to_le_bytes());
+        assert_eq!(ix.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&ix.pack()).unwrap(), ix);
+    }
+
+    #[test]
+    fn start_ranked_run_round_trip() {
+        let ix = GarbageGrabInstruction::StartRankedRun {
+            game_mode: GameMode::Arcade,
+        };
+        assert_eq!(ix.pack(), vec![19, 0]);
+        assert_eq!(GarbageGrabInstruction::unpack(&ix.pack()).unwrap(), ix);
+    }
+
+    #[test]
+    fn distribute_prizes_round_trip() {
+        let ix = GarbageGrabInstruction::DistributePrizes;
+        assert_eq!(ix.pack(), vec![20]);
+        assert_eq!(GarbageGrabInstruction::unpack(&ix.pack()).unwrap(), ix);
+    }
+
+    #[test]
+    fn match_round_trip() {
+        let create = GarbageGrabInstruction::CreateMatch {
+            match_id: 7,
+            stake: 1_000,
+        };
+        let mut expected = vec![21];
+        expected.extend_from_slice(&7u64.to_le_bytes());
+        expected.extend_from_slice(&1_000u64.to_le_bytes());
+        assert_eq!(create.pack(), expected);
+        assert_eq!(
+            GarbageGrabInstruction::unpack(&create.pack()).unwrap(),
+            create
+        );
+
+        let winner = Pubkey::new_unique();
+        let result = GarbageGrabInstruction::SubmitMatchResult { winner };
+        let mut expected = vec![22];
+        expected.extend_from_slice(winner.as_ref());
+        assert_eq!(result.pack(), expected);
+        assert_eq!(
+            GarbageGrabInstruction::unpack(&result.pack()).unwrap(),
+            result
+        );
+
+        let settle = GarbageGrabInstruction::SettleMatch;
+        assert_eq!(settle.pack(), vec![23]);
+        assert_eq!(
+            GarbageGrabInstruction::unpack(&settle.pack()).unwrap(),
+            settle
+        );
+    }
+
+    #[test]
+    fn tournament_round_trip() {
+        let create = GarbageGrabInstruction::CreateTournament {
+            tournament_id: 3,
+            entry_fee: 10_000,
+            max_participants: 16,
+            end_ts: 1_700_000_000,
+            collection: Pubkey::new_from_array([7; 32]),
+        };
+        let mut expected = vec![24];
+        expected.extend_from_slice(&3u64.to_le_bytes());
+        expected.extend_from_slice(&10_000u64.to_le_bytes());
+        expected.push(16);
+        expected.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+        expected.extend_from_slice(&[7; 32]);
+        assert_eq!(create.pack(), expected);
+        assert_eq!(
+            GarbageGrabInstruction::unpack(&create.pack()).unwrap(),
+            create
+        );
+
+        assert_eq!(GarbageGrabInstruction::Register.pack(), vec![25]);
+
+        let submit = GarbageGrabInstruction::SubmitTournamentScore { score: 42 };
+        let mut expected = vec![26];
+        expected.extend_from_slice(&42u64.to_le_bytes());
+        assert_eq!(submit.pack(), expected);
+        assert_eq!(
+            GarbageGrabInstruction::unpack(&submit.pack()).unwrap(),
+            submit
+        );
+
+        assert_eq!(GarbageGrabInstruction::FinalizeTournament.pack(), vec![27]);
+    }
+
+    #[test]
+    fn unlock_achievement_round_trip() {
+        let ix = GarbageGrabInstruction::UnlockAchievement {
+            game_mode: GameMode::Endless,
+            id: 12,
+        };
+        assert_eq!(ix.pack(), vec![28, 1, 12]);
+        assert_eq!(GarbageGrabInstruction::unpack(&ix.pack()).unwrap(), ix);
+    }
+
+    #[test]
+    fn check_in_round_trip() {
+        let ix = GarbageGrabInstruction::CheckIn {
+            game_mode: GameMode::Arcade,
+        };
+        assert_eq!(ix.pack(), vec![29, 0]);
+        assert_eq!(GarbageGrabInstruction::unpack(&ix.pack()).unwrap(), ix);
+    }
+
+    #[test]
+    fn mint_trophy_round_trip() {
+        let ix = GarbageGrabInstruction::MintTrophy {
+            game_mode: GameMode::DailyChallenge,
+        };
+        assert_eq!(ix.pack(), vec![31, 2]);
+        assert_eq!(GarbageGrabInstruction::unpack(&ix.pack()).unwrap(), ix);
+    }
+
+    #[test]
+    fn profile_round_trips() {
+        let avatar_mint = Pubkey::new_unique();
+        let create = GarbageGrabInstruction::CreateProfile {
+            name: [b'a'; 32],
+            avatar_mint,
+        };
+        let mut expected = vec![32];
+        expected.extend_from_slice(&[b'a'; 32]);
+        expected.extend_from_slice(avatar_mint.as_ref());
+        assert_eq!(create.pack(), expected);
+        assert_eq!(
+            GarbageGrabInstruction::unpack(&create.pack()).unwrap(),
+            create
+        );
+
+        let update = GarbageGrabInstruction::UpdateProfile {
+            name: None,
+            avatar_mint: Some(avatar_mint),
+        };
+        let mut expected = vec![33, 0, 1];
+        expected.extend_from_slice(avatar_mint.as_ref());
+        assert_eq!(update.pack(), expected);
+        assert_eq!(
+            GarbageGrabInstruction::unpack(&update.pack()).unwrap(),
+            update
+        );
+    }
+
+    #[test]
+    fn credit_referral_round_trip() {
+        let ix = GarbageGrabInstruction::CreditReferral {
+            game_mode: GameMode::Endless,
+        };
+        assert_eq!(ix.pack(), vec![34, 1]);
+        assert_eq!(GarbageGrabInstruction::unpack(&ix.pack()).unwrap(), ix);
+    }
+
+    #[test]
+    fn guild_round_trips() {
+        let create = GarbageGrabInstruction::CreateGuild {
+            guild_id: 5,
+            name: [b'g'; 32],
+        };
+        let mut expected = vec![35];
+        expected.extend_from_slice(&5u64.to_le_bytes());
+        expected.extend_from_slice(&[b'g'; 32]);
+        assert_eq!(create.pack(), expected);
+        assert_eq!(
+            GarbageGrabInstruction::unpack(&create.pack()).unwrap(),
+            create
+        );
+
+        for (ix, tag) in [
+            (GarbageGrabInstruction::JoinGuild, 36),
+            (GarbageGrabInstruction::LeaveGuild, 37),
+            (GarbageGrabInstruction::InitializeGuildLeaderboard, 38),
+            (GarbageGrabInstruction::SubmitGuildToLeaderboard, 39),
+        ] {
+            assert_eq!(ix.pack(), vec![tag]);
+            assert_eq!(GarbageGrabInstruction::unpack(&[tag]).unwrap(), ix);
+        }
+    }
+
+    #[test]
+    fn realloc_round_trip() {
+        let ix = GarbageGrabInstruction::Realloc { new_len: 1_024 };
+        let mut expected = vec![49];
+        expected.extend_from_slice(&1_024u32.to_le_bytes());
+        assert_eq!(ix.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&ix.pack()).unwrap(), ix);
+    }
+
+    #[test]
+    fn airdrop_round_trips() {
+        let ix = GarbageGrabInstruction::SetRewardRoot {
+            airdrop_id: 2,
+            merkle_root: [7; 32],
+            max_claims: 100,
+        };
+        let mut expected = vec![50];
+        expected.extend_from_slice(&2u64.to_le_bytes());
+        expected.extend_from_slice(&[7; 32]);
+        expected.extend_from_slice(&100u32.to_le_bytes());
+        assert_eq!(ix.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&ix.pack()).unwrap(), ix);
+
+        let ix = GarbageGrabInstruction::ClaimAirdrop {
+            airdrop_id: 2,
+            index: 9,
+            amount: 500,
+            proof: vec![[1; 32], [2; 32]],
+        };
+        let mut expected = vec![51];
+        expected.extend_from_slice(&2u64.to_le_bytes());
+        expected.extend_from_slice(&9u32.to_le_bytes());
+        expected.extend_from_slice(&500u64.to_le_bytes());
+        expected.extend_from_slice(&2u32.to_le_bytes());
+        expected.extend_from_slice(&[1; 32]);
+        expected.extend_from_slice(&[2; 32]);
+        assert_eq!(ix.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&ix.pack()).unwrap(), ix);
+    }
+
+    #[test]
+    fn compressed_score_round_trips() {
+        let ix = GarbageGrabInstruction::AppendCompressedScore {
+            game_mode: GameMode::Endless,
+            score: 900,
+        };
+        let mut expected = vec![53, 1];
+        expected.extend_from_slice(&900u64.to_le_bytes());
+        assert_eq!(ix.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&ix.pack()).unwrap(), ix);
+
+        let ix = GarbageGrabInstruction::VerifyCompressedScore {
+            root: [3; 32],
+            score: CompressedScore {
+                player: Pubkey::new_unique(),
+                game_mode: GameMode::Arcade,
+                score: 900,
+                timestamp: 1_700_000_000,
+                leaf_index: 4,
+            },
+        };
+        assert_eq!(ix.pack()[0], 54);
+        assert_eq!(GarbageGrabInstruction::unpack(&ix.pack()).unwrap(), ix);
+    }
+
+    #[test]
+    fn top_up_rent_round_trip() {
+        let ix = GarbageGrabInstruction::TopUpRent;
+        assert_eq!(ix.pack(), vec![56]);
+        assert_eq!(GarbageGrabInstruction::unpack(&[56]).unwrap(), ix);
+    }
+
+    #[test]
+    fn ban_round_trip() {
+        let player = Pubkey::new_unique();
+        for (ix, tag) in [
+            (GarbageGrabInstruction::Ban { player }, 57),
+            (GarbageGrabInstruction::Unban { player }, 58),
+        ] {
+            let mut expected = vec![tag];
+            expected.extend_from_slice(player.as_ref());
+            assert_eq!(ix.pack(), expected);
+            assert_eq!(GarbageGrabInstruction::unpack(&expected).unwrap(), ix);
+        }
+    }
+
+    #[test]
+    fn report_round_trip() {
+        let report = GarbageGrabInstruction::ReportPlayer {
+            game_mode: GameMode::Endless,
+        };
+        assert_eq!(report.pack(), vec![59, 1]);
+        assert_eq!(
+            GarbageGrabInstruction::unpack(&report.pack()).unwrap(),
+            report
+        );
+
+        let resolve = GarbageGrabInstruction::ResolveReport {
+            upheld: true,
+            slash: 500,
+        };
+        let mut expected = vec![60, 1];
+        expected.extend_from_slice(&500u64.to_le_bytes());
+        assert_eq!(resolve.pack(), expected);
+        assert_eq!(
+            GarbageGrabInstruction::unpack(&resolve.pack()).unwrap(),
+            resolve
+        );
+    }
+
+    #[test]
+    fn admin_set_round_trip() {
+        let member = Pubkey::new_unique();
+        let set = GarbageGrabInstruction::SetAdminSet {
+            threshold: 1,
+            members: vec![member],
+        };
+        let mut expected = vec![61, 1];
+        expected.extend_from_slice(&1u32.to_le_bytes());
+        expected.extend_from_slice(member.as_ref());
+        assert_eq!(set.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&expected).unwrap(), set);
+
+        let propose = GarbageGrabInstruction::Propose {
+            accounts: vec![ProposalAccount {
+                pubkey: member,
+                is_signer: true,
+                is_writable: false,
+            }],
+            data: vec![10],
+        };
+        let mut expected = vec![62];
+        expected.extend_from_slice(&1u32.to_le_bytes());
+        expected.extend_from_slice(member.as_ref());
+        expected.extend_from_slice(&[1, 0]);
+        expected.extend_from_slice(&1u32.to_le_bytes());
+        expected.push(10);
+        assert_eq!(propose.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&expected).unwrap(), propose);
+
+        for (ix, tag) in [
+            (GarbageGrabInstruction::Approve, 63),
+            (GarbageGrabInstruction::ExecuteProposal, 64),
+        ] {
+            assert_eq!(ix.pack(), vec![tag]);
+            assert_eq!(GarbageGrabInstruction::unpack(&[tag]).unwrap(), ix);
+        }
+    }
+
+    #[test]
+    fn admin_transfer_round_trip() {
+        let new_admin = Pubkey::new_unique();
+        let nominate = GarbageGrabInstruction::NominateAdmin { new_admin };
+        let mut expected = vec![65];
+        expected.extend_from_slice(new_admin.as_ref());
+        assert_eq!(nominate.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&expected).unwrap(), nominate);
+        assert_eq!(GarbageGrabInstruction::AcceptAdmin.pack(), vec![66]);
+    }
+
+    #[test]
+    fn item_round_trip() {
+        let set = GarbageGrabInstruction::SetCatalogItem {
+            definition: ItemDefinition {
+                item_id: 3,
+                effect: ItemEffect::ScoreMultiplier,
+                effect_bps: 15_000,
+                ..ItemDefinition::default()
+            },
+        };
+        let mut expected = vec![69];
+        expected.extend_from_slice(&3u32.to_le_bytes());
+        expected.push(1);
+        expected.extend_from_slice(&15_000u16.to_le_bytes());
+        expected.extend_from_slice(&[0; 16]);
+        assert_eq!(set.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&expected).unwrap(), set);
+
+        let grant = GarbageGrabInstruction::GrantItem {
+            item_id: 3,
+            quantity: 2,
+        };
+        let mut expected = vec![70];
+        expected.extend_from_slice(&3u32.to_le_bytes());
+        expected.extend_from_slice(&2u32.to_le_bytes());
+        assert_eq!(grant.pack(), expected);
+
+        let consume = GarbageGrabInstruction::ConsumeItem {
+            game_mode: GameMode::Endless,
+            item_id: 3,
+        };
+        assert_eq!(consume.pack(), vec![71, 1, 3, 0, 0, 0]);
+        assert_eq!(
+            GarbageGrabInstruction::unpack(&consume.pack()).unwrap(),
+            consume
+        );
+
+        let purchase = GarbageGrabInstruction::PurchaseItem {
+            item_id: 3,
+            quantity: 2,
+            currency: ShopCurrency::Token,
+        };
+        assert_eq!(purchase.pack(), vec![72, 3, 0, 0, 0, 2, 0, 0, 0, 1]);
+        assert_eq!(
+            GarbageGrabInstruction::unpack(&purchase.pack()).unwrap(),
+            purchase
+        );
+    }
+
+    #[test]
+    fn continue_round_trip() {
+        let ix = GarbageGrabInstruction::Continue {
+            game_mode: GameMode::Arcade,
+        };
+        assert_eq!(ix.pack(), vec![73, 0]);
+        assert_eq!(GarbageGrabInstruction::unpack(&[73, 0]).unwrap(), ix);
+    }
+
+    #[test]
+    fn verify_score_at_least_round_trip() {
+        let ix = GarbageGrabInstruction::VerifyScoreAtLeast { threshold: 500 };
+        let mut expected = vec![76];
+        expected.extend_from_slice(&500u64.to_le_bytes());
+        assert_eq!(ix.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&expected).unwrap(), ix);
+    }
+
+    #[test]
+    fn withdraw_fees_round_trip() {
+        let ix = GarbageGrabInstruction::WithdrawFees { amount: 2_500 };
+        let mut expected = vec![97];
+        expected.extend_from_slice(&2_500u64.to_le_bytes());
+        assert_eq!(ix.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&expected).unwrap(), ix);
+    }
+
+    #[test]
+    fn claim_vested_round_trip() {
+        let ix = GarbageGrabInstruction::ClaimVested;
+        assert_eq!(ix.pack(), vec![98]);
+        assert_eq!(GarbageGrabInstruction::unpack(&[98]).unwrap(), ix);
+    }
+
+    #[test]
+    fn stake_round_trip() {
+        let stake = GarbageGrabInstruction::Stake {
+            amount: 1_500,
+            lock: StakeLock::Days90,
+        };
+        let mut expected = vec![99];
+        expected.extend_from_slice(&1_500u64.to_le_bytes());
+        expected.push(2);
+        assert_eq!(stake.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&expected).unwrap(), stake);
+
+        let unstake = GarbageGrabInstruction::Unstake { amount: 500 };
+        let mut expected = vec![100];
+        expected.extend_from_slice(&500u64.to_le_bytes());
+        assert_eq!(unstake.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&expected).unwrap(), unstake);
+    }
+
+    #[test]
+    fn quest_round_trips() {
+        let create = GarbageGrabInstruction::CreateQuest {
+            quest_id: 4,
+            metric: QuestMetric::AchievementsUnlocked,
+            target: 3,
+            reward: 250,
+        };
+        let mut expected = vec![101];
+        expected.extend_from_slice(&4u64.to_le_bytes());
+        expected.push(1);
+        expected.extend_from_slice(&3u64.to_le_bytes());
+        expected.extend_from_slice(&250u64.to_le_bytes());
+        assert_eq!(create.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&expected).unwrap(), create);
+
+        for (ix, tag) in [
+            (GarbageGrabInstruction::AcceptQuest, 102),
+            (GarbageGrabInstruction::ClaimQuestReward, 103),
+        ] {
+            assert_eq!(ix.pack(), vec![tag]);
+            assert_eq!(GarbageGrabInstruction::unpack(&[tag]).unwrap(), ix);
+        }
+    }
+
+    #[test]
+    fn cosmetic_round_trips() {
+        let set = GarbageGrabInstruction::SetCosmetic {
+            cosmetic_id: 5,
+            min_level: 3,
+            game_mode: GameMode::Arcade,
+            achievement: 7,
+        };
+        let mut expected = vec![106, 5];
+        expected.extend_from_slice(&3u16.to_le_bytes());
+        expected.extend_from_slice(&[0, 7]);
+        assert_eq!(set.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&expected).unwrap(), set);
+
+        let unlock = GarbageGrabInstruction::UnlockCosmetic;
+        assert_eq!(unlock.pack(), vec![107]);
+        assert_eq!(GarbageGrabInstruction::unpack(&[107]).unwrap(), unlock);
+    }
+
+    #[test]
+    fn badge_round_trips() {
+        let add = GarbageGrabInstruction::AddBadge {
+            milestone: BadgeMilestone::Streak,
+            threshold: 30,
+        };
+        let mut expected = vec![108, 1];
+        expected.extend_from_slice(&30u64.to_le_bytes());
+        assert_eq!(add.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&expected).unwrap(), add);
+
+        let claim = GarbageGrabInstruction::ClaimBadge {
+            badge_id: 2,
+            game_mode: GameMode::Endless,
+        };
+        assert_eq!(claim.pack(), vec![109, 2, 1]);
+        assert_eq!(GarbageGrabInstruction::unpack(&[109, 2, 1]).unwrap(), claim);
+    }
+
+    #[test]
+    fn initialize_global_stats_round_trips() {
+        let init = GarbageGrabInstruction::InitializeGlobalStats;
+        assert_eq!(init.pack(), vec![110]);
+        assert_eq!(GarbageGrabInstruction::unpack(&[110]).unwrap(), init);
+    }
+
+    #[test]
+    fn daily_challenge_round_trips() {
+        let publish = GarbageGrabInstruction::PublishDailyChallenge {
+            seed: [9; 32],
+            date: 19_000,
+        };
+        let mut expected = vec![104];
+        expected.extend_from_slice(&[9; 32]);
+        expected.extend_from_slice(&19_000i64.to_le_bytes());
+        assert_eq!(publish.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&expected).unwrap(), publish);
+
+        let submit = GarbageGrabInstruction::SubmitDailyChallenge {
+            date: 19_000,
+            score: 750,
+        };
+        let mut expected = vec![105];
+        expected.extend_from_slice(&19_000i64.to_le_bytes());
+        expected.extend_from_slice(&750u64.to_le_bytes());
+        assert_eq!(submit.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&expected).unwrap(), submit);
+    }
+
+    #[test]
+    fn withdraw_treasury_round_trip() {
+        let ix = GarbageGrabInstruction::WithdrawTreasury { amount: 1_000 };
+        let mut expected = vec![77];
+        expected.extend_from_slice(&1_000u64.to_le_bytes());
+        assert_eq!(ix.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&expected).unwrap(), ix);
+    }
+
+    #[test]
+    fn leaderboard_page_round_trip() {
+        for (ix, expected) in [
+            (
+                GarbageGrabInstruction::InitializeLeaderboardPage { page: 3 },
+                vec![78, 3],
+            ),
+            (
+                GarbageGrabInstruction::SubmitToLeaderboardPage {
+                    game_mode: GameMode::Endless,
+                    page: 3,
+                },
+                vec![79, 1, 3],
+            ),
+            (
+                GarbageGrabInstruction::RebalanceLeaderboardPages { page: 3 },
+                vec![80, 3],
+            ),
+        ] {
+            assert_eq!(ix.pack(), expected);
+            assert_eq!(GarbageGrabInstruction::unpack(&expected).unwrap(), ix);
+        }
+    }
+
+    #[test]
+    fn weekly_leaderboard_round_trip() {
+        for (ix, expected) in [
+            (
+                GarbageGrabInstruction::InitializeWeeklyLeaderboard,
+                vec![81],
+            ),
+            (
+                GarbageGrabInstruction::SubmitToWeeklyLeaderboard {
+                    game_mode: GameMode::Endless,
+                },
+                vec![82, 1],
+            ),
+        ] {
+            assert_eq!(ix.pack(), expected);
+            assert_eq!(GarbageGrabInstruction::unpack(&expected).unwrap(), ix);
+        }
+    }
+
+    #[test]
+    fn follow_round_trip() {
+        for (ix, tag) in [
+            (GarbageGrabInstruction::Follow, 83),
+            (GarbageGrabInstruction::Unfollow, 84),
+        ] {
+            assert_eq!(ix.pack(), vec![tag]);
+            assert_eq!(GarbageGrabInstruction::unpack(&[tag]).unwrap(), ix);
+        }
+    }
+
+    #[test]
+    fn region_round_trip() {
+        for (ix, expected) in [
+            (
+                GarbageGrabInstruction::SetRegion { region: *b"DE" },
+                vec![85, b'D', b'E'],
+            ),
+            (
+                GarbageGrabInstruction::AdminSetRegion { region: *b"DE" },
+                vec![86, b'D', b'E'],
+            ),
+            (
+                GarbageGrabInstruction::InitializeRegionalLeaderboard { region: *b"DE" },
+                vec![87, b'D', b'E'],
+            ),
+            (
+                GarbageGrabInstruction::SubmitToRegionalLeaderboard {
+                    game_mode: GameMode::Endless,
+                },
+                vec![88, 1],
+            ),
+        ] {
+            assert_eq!(ix.pack(), expected);
+            assert_eq!(GarbageGrabInstruction::unpack(&expected).unwrap(), ix);
+        }
+    }
+
+    #[test]
+    fn game_session_round_trip() {
+        let start = GarbageGrabInstruction::StartSession {
+            game_mode: GameMode::Endless,
+            seed: [7; 32],
+        };
+        let mut expected = vec![89, 1];
+        expected.extend_from_slice(&[7; 32]);
+        assert_eq!(start.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&expected).unwrap(), start);
+
+        let end = GarbageGrabInstruction::EndSession { score: 1_500 };
+        let mut expected = vec![90];
+        expected.extend_from_slice(&1_500u64.to_le_bytes());
+        assert_eq!(end.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&expected).unwrap(), end);
+    }
+
+    #[test]
+    fn lobby_round_trip() {
+        let create = GarbageGrabInstruction::CreateLobby {
+            capacity: 4,
+            stake: 25_000,
+        };
+        let mut expected = vec![91, 4];
+        expected.extend_from_slice(&25_000u64.to_le_bytes());
+        assert_eq!(create.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&expected).unwrap(), create);
+
+        for (ix, tag) in [
+            (GarbageGrabInstruction::JoinLobby, 92),
+            (GarbageGrabInstruction::LeaveLobby, 93),
+            (GarbageGrabInstruction::StartMatch, 94),
+        ] {
+            assert_eq!(ix.pack(), vec![tag]);
+            assert_eq!(GarbageGrabInstruction::unpack(&[tag]).unwrap(), ix);
+        }
+    }
+
+    #[test]
+    fn bet_round_trip() {
+        let on = Pubkey::new_unique();
+        let place = GarbageGrabInstruction::PlaceBet { on, amount: 5_000 };
+        let mut expected = vec![95];
+        expected.extend_from_slice(on.as_ref());
+        expected.extend_from_slice(&5_000u64.to_le_bytes());
+        assert_eq!(place.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&expected).unwrap(), place);
+
+        let settle = GarbageGrabInstruction::SettleBets;
+        assert_eq!(settle.pack(), vec![96]);
+        assert_eq!(GarbageGrabInstruction::unpack(&[96]).unwrap(), settle);
+    }
+
+    #[test]
+    fn crank_round_trip() {
+        for (ix, tag) in [
+            (GarbageGrabInstruction::CrankSeasonRollover, 74),
+            (GarbageGrabInstruction::CrankLeaderboardFinalize, 75),
+        ] {
+            assert_eq!(ix.pack(), vec![tag]);
+            assert_eq!(GarbageGrabInstruction::unpack(&[tag]).unwrap(), ix);
+        }
+    }
+
+    #[test]
+    fn loot_round_trip() {
+        for (ix, tag) in [
+            (GarbageGrabInstruction::RequestLoot, 67),
+            (GarbageGrabInstruction::FulfillLoot, 68),
+        ] {
+            assert_eq!(ix.pack(), vec![tag]);
+            assert_eq!(GarbageGrabInstruction::unpack(&[tag]).unwrap(), ix);
+        }
+    }
+
+    #[test]
+    fn batch_update_round_trip() {
+        let ix = GarbageGrabInstruction::BatchUpdate {
+            game_mode: GameMode::Arcade,
+            increments: vec![5, 7],
+        };
+        let mut expected = vec![55, 0];
+        expected.extend_from_slice(&2u32.to_le_bytes());
+        expected.extend_from_slice(&5u64.to_le_bytes());
+        expected.extend_from_slice(&7u64.to_le_bytes());
+        assert_eq!(ix.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&ix.pack()).unwrap(), ix);
+    }
+
+    #[test]
+    fn initialize_history_round_trip() {
+        let ix = GarbageGrabInstruction::InitializeHistory {
+            game_mode: GameMode::DailyChallenge,
+        };
+        assert_eq!(ix.pack(), vec![48, 2]);
+        assert_eq!(GarbageGrabInstruction::unpack(&ix.pack()).unwrap(), ix);
+    }
+
+    #[test]
+    fn session_round_trips() {
+        let session_key = Pubkey::new_unique();
+        let ix = GarbageGrabInstruction::CreateSession {
+            session_key,
+            expiry_slot: 500,
+        };
+        let mut expected = vec![45];
+        expected.extend_from_slice(session_key.as_ref());
+        expected.extend_from_slice(&500u64.to_le_bytes());
+        assert_eq!(ix.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&ix.pack()).unwrap(), ix);
+
+        assert_eq!(GarbageGrabInstruction::RevokeSession.pack(), vec![46]);
+
+        let ix = GarbageGrabInstruction::UpdateScoreWithSession {
+            game_mode: GameMode::Endless,
+            increment: 3,
+        };
+        let mut expected = vec![47, 1];
+        expected.extend_from_slice(&3u64.to_le_bytes());
+        assert_eq!(ix.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&ix.pack()).unwrap(), ix);
+    }
+
+    #[test]
+    fn delegate_round_trips() {
+        let delegate = Pubkey::new_unique();
+        let ix = GarbageGrabInstruction::ApproveDelegate {
+            game_mode: GameMode::Endless,
+            delegate,
+        };
+        let mut expected = vec![42, 1];
+        expected.extend_from_slice(delegate.as_ref());
+        assert_eq!(ix.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&ix.pack()).unwrap(), ix);
+
+        let ix = GarbageGrabInstruction::RevokeDelegate {
+            game_mode: GameMode::Arcade,
+        };
+        assert_eq!(ix.pack(), vec![43, 0]);
+
+        let ix = GarbageGrabInstruction::UpdateScoreAsDelegate {
+            game_mode: GameMode::Arcade,
+            increment: 9,
+        };
+        let mut expected = vec![44, 0];
+        expected.extend_from_slice(&9u64.to_le_bytes());
+        assert_eq!(ix.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&ix.pack()).unwrap(), ix);
+    }
+
+    #[test]
+    fn team_round_trips() {
+        assert_eq!(GarbageGrabInstruction::CreateTeam.pack(), vec![40]);
+        let ix = GarbageGrabInstruction::UpdateTeamScore { increment: 15 };
+        let mut expected = vec![41];
+        expected.extend_from_slice(&15u64.to_le_bytes());
+        assert_eq!(ix.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&ix.pack()).unwrap(), ix);
+    }
+
+    #[test]
+    fn apply_boost_round_trip() {
+        let ix = GarbageGrabInstruction::ApplyBoost {
+            game_mode: GameMode::Arcade,
+            increment: 40,
+        };
+        let mut expected = vec![30, 0];
+        expected.extend_from_slice(&40u64.to_le_bytes());
+        assert_eq!(ix.pack(), expected);
+        assert_eq!(GarbageGrabInstruction::unpack(&ix.pack()).unwrap(), ix);
+    }
+
+    #[test]
+    fn rejects_malformed_data() {
+        assert_eq!(
+            GarbageGrabInstruction::unpack(&[]),
+            Err(ProgramError::InvalidInstructionData)
+        );
+        assert_eq!(
+            GarbageGrabInstruction::unpack(&[1, 0, 0]),
+            Err(ProgramError::InvalidInstructionData)
+        );
+        assert_eq!(
+            GarbageGrabInstruction::unpack(&[0, 7]),
+            Err(ProgramError::InvalidInstructionData)
+        );
+        assert_eq!(
+            GarbageGrabInstruction::unpack(&[0xff]),
+            Err(ProgramError::InvalidInstructionData)
+        );
+    }
+}
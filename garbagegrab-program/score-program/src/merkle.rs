@@ -0,0 +1,102 @@
+use solana_program::{hash::hashv, pubkey::Pubkey};
+
+// Domain bytes keeping leaves and internal nodes from being confused with each other
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+// Leaf of an airdrop tree: claim slot `index` entitles `player` to `amount` tokens
+pub fn airdrop_leaf(index: u32, player: &Pubkey, amount: u64) -> [u8; 32] {
+    hashv(&[
+        LEAF_PREFIX,
+        &index.to_le_bytes(),
+        player.as_ref(),
+        &amount.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+// Leaf of a closed beta allowlist tree
+pub fn allowlist_leaf(player: &Pubkey) -> [u8; 32] {
+    hashv(&[LEAF_PREFIX, player.as_ref()]).to_bytes()
+}
+
+// Parent of two nodes. Children are hashed in sorted order, so proofs need no
+// left/right flags.
+fn parent(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[NODE_PREFIX, first, second]).to_bytes()
+}
+
+// Whether `proof` links `leaf` up to `root`
+pub fn verify_proof(leaf: [u8; 32], proof: &[[u8; 32]], root: &[u8; 32]) -> bool {
+    proof
+        .iter()
+        .fold(leaf, |node, sibling| parent(&node, sibling))
+        == *root
+}
+
+// Root of the tree over `leaves` and the proof of every leaf, for publishing an
+// airdrop off-chain. A node without a sibling is carried up unchanged.
+pub fn build_tree(leaves: &[[u8; 32]]) -> ([u8; 32], Vec<Vec<[u8; 32]>>) {
+    let mut proofs = vec![Vec::new(); leaves.len()];
+    // Which node of the current level each leaf sits under
+    let mut positions: Vec<usize> = (0..leaves.len()).collect();
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        for (proof, position) in proofs.iter_mut().zip(positions.iter_mut()) {
+            if let Some(sibling) = level.get(*position ^ 1) {
+                proof.push(*sibling);
+            }
+            *position /= 2;
+        }
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => parent(a, b),
+                [a] => *a,
+                _ => unreachable!("chunks of two"),
+            })
+            .collect();
+    }
+    (level.first().copied().unwrap_or_default(), proofs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_proof_verifies_against_the_root() {
+        let players: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        let leaves: Vec<[u8; 32]> = players
+            .iter()
+            .enumerate()
+            .map(|(i, player)| airdrop_leaf(i as u32, player, 100 * i as u64))
+            .collect();
+        let (root, proofs) = build_tree(&leaves);
+        for (leaf, proof) in leaves.iter().zip(&proofs) {
+            assert!(verify_proof(*leaf, proof, &root));
+        }
+
+        // Claiming a different amount or someone else's slot fails
+        assert!(!verify_proof(
+            airdrop_leaf(1, &players[1], 999),
+            &proofs[1],
+            &root
+        ));
+        assert!(!verify_proof(leaves[2], &proofs[1], &root));
+    }
+
+    #[test]
+    fn allowlist_proofs_only_admit_listed_players() {
+        let players: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let leaves: Vec<[u8; 32]> = players.iter().map(allowlist_leaf).collect();
+        let (root, proofs) = build_tree(&leaves);
+        assert!(verify_proof(allowlist_leaf(&players[2]), &proofs[2], &root));
+        assert!(!verify_proof(
+            allowlist_leaf(&Pubkey::new_unique()),
+            &proofs[2],
+            &root
+        ));
+    }
+}
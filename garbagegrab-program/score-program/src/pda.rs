@@ -0,0 +1,717 @@
+use solana_program::pubkey::{Pubkey, PubkeyError};
+
+use crate::state::GameMode;
+
+// Every address the program derives, one type per account kind. `SEED` is the
+// prefix each derivation starts with, and `find` lays the rest of the seeds out
+// in their canonical order, so the program and its clients cannot drift apart.
+// Signing with `invoke_signed` takes the same seeds followed by the bump.
+// Accounts read on every score update store their bump, and `create` rebuilds
+// the address from it for a fraction of what `find` costs.
+
+// A player's score account for one game mode
+pub struct ScorePda;
+
+impl ScorePda {
+    pub const SEED: &'static [u8] = b"score";
+
+    pub fn find(program_id: &Pubkey, player: &Pubkey, game_mode: GameMode) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED, player.as_ref(), &game_mode.seed()],
+            program_id,
+        )
+    }
+
+    pub fn create(
+        program_id: &Pubkey,
+        player: &Pubkey,
+        game_mode: GameMode,
+        bump: u8,
+    ) -> Result<Pubkey, PubkeyError> {
+        Pubkey::create_program_address(
+            &[Self::SEED, player.as_ref(), &game_mode.seed(), &[bump]],
+            program_id,
+        )
+    }
+}
+
+// The global leaderboard
+pub struct LeaderboardPda;
+
+impl LeaderboardPda {
+    pub const SEED: &'static [u8] = b"leaderboard";
+
+    pub fn find(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+
+    // One of the pages ranking players below the global board, numbered from 1
+    pub fn find_page(program_id: &Pubkey, page: u8) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, &[page]], program_id)
+    }
+}
+
+// The current season
+pub struct SeasonPda;
+
+impl SeasonPda {
+    pub const SEED: &'static [u8] = b"season";
+
+    pub fn find(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+}
+
+// The program config
+pub struct ConfigPda;
+
+impl ConfigPda {
+    pub const SEED: &'static [u8] = b"config";
+
+    pub fn find(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+
+    pub fn create(program_id: &Pubkey, bump: u8) -> Result<Pubkey, PubkeyError> {
+        Pubkey::create_program_address(&[Self::SEED, &[bump]], program_id)
+    }
+}
+
+// The list of banned players
+pub struct BanListPda;
+
+impl BanListPda {
+    pub const SEED: &'static [u8] = b"ban_list";
+
+    pub fn find(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+}
+
+// `reporter`'s report on a player's score in one game mode
+pub struct ReportPda;
+
+impl ReportPda {
+    pub const SEED: &'static [u8] = b"report";
+
+    pub fn find(
+        program_id: &Pubkey,
+        player: &Pubkey,
+        reporter: &Pubkey,
+        game_mode: GameMode,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                Self::SEED,
+                player.as_ref(),
+                reporter.as_ref(),
+                &game_mode.seed(),
+            ],
+            program_id,
+        )
+    }
+}
+
+// The m-of-n admin set
+pub struct AdminSetPda;
+
+impl AdminSetPda {
+    pub const SEED: &'static [u8] = b"admin_set";
+
+    pub fn find(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+}
+
+// An admin set proposal
+pub struct ProposalPda;
+
+impl ProposalPda {
+    pub const SEED: &'static [u8] = b"proposal";
+
+    pub fn find(program_id: &Pubkey, proposal_id: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, &proposal_id.to_le_bytes()], program_id)
+    }
+}
+
+// A player's pending loot drop
+pub struct LootRequestPda;
+
+impl LootRequestPda {
+    pub const SEED: &'static [u8] = b"loot_request";
+
+    pub fn find(program_id: &Pubkey, player: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, player.as_ref()], program_id)
+    }
+}
+
+// A player's inventory
+pub struct InventoryPda;
+
+impl InventoryPda {
+    pub const SEED: &'static [u8] = b"inventory";
+
+    pub fn find(program_id: &Pubkey, player: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, player.as_ref()], program_id)
+    }
+}
+
+// The item catalog
+pub struct CatalogPda;
+
+impl CatalogPda {
+    pub const SEED: &'static [u8] = b"catalog";
+
+    pub fn find(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+}
+
+// The treasury, a system account that collects shop payments
+pub struct TreasuryPda;
+
+impl TreasuryPda {
+    pub const SEED: &'static [u8] = b"treasury";
+
+    pub fn find(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+}
+
+// The fee vault, a system account that collects the house fee
+pub struct FeeVaultPda;
+
+impl FeeVaultPda {
+    pub const SEED: &'static [u8] = b"fee_vault";
+
+    pub fn find(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+}
+
+// A player's profile
+pub struct ProfilePda;
+
+impl ProfilePda {
+    pub const SEED: &'static [u8] = b"profile";
+
+    pub fn find(program_id: &Pubkey, player: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, player.as_ref()], program_id)
+    }
+}
+
+// One player following another
+pub struct FollowPda;
+
+impl FollowPda {
+    pub const SEED: &'static [u8] = b"follow";
+
+    pub fn find(program_id: &Pubkey, follower: &Pubkey, followed: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED, follower.as_ref(), followed.as_ref()],
+            program_id,
+        )
+    }
+}
+
+// The record claiming a display name, keyed by the name's normalized form
+pub struct NameRecordPda;
+
+impl NameRecordPda {
+    pub const SEED: &'static [u8] = b"name";
+
+    pub fn find(program_id: &Pubkey, normalized_name: &[u8]) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, normalized_name], program_id)
+    }
+}
+
+// The mint and update authority of every trophy NFT
+pub struct TrophyAuthorityPda;
+
+impl TrophyAuthorityPda {
+    pub const SEED: &'static [u8] = b"trophy";
+
+    pub fn find(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+}
+
+// The reward vault token account, which is its own transfer authority
+pub struct VaultPda;
+
+impl VaultPda {
+    pub const SEED: &'static [u8] = b"vault";
+
+    pub fn find(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+}
+
+// The prize pool that collects ranked run entry fees
+pub struct PrizePoolPda;
+
+impl PrizePoolPda {
+    pub const SEED: &'static [u8] = b"prize_pool";
+
+    pub fn find(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+}
+
+// Reward tokens a player has staked
+pub struct StakePda;
+
+impl StakePda {
+    pub const SEED: &'static [u8] = b"stake";
+
+    pub fn find(program_id: &Pubkey, player: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, player.as_ref()], program_id)
+    }
+}
+
+// The token account holding a player's stake, which is its own transfer authority
+pub struct StakeVaultPda;
+
+impl StakeVaultPda {
+    pub const SEED: &'static [u8] = b"stake_vault";
+
+    pub fn find(program_id: &Pubkey, player: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, player.as_ref()], program_id)
+    }
+}
+
+// A winner's vesting prize from season `season_id`
+pub struct VestingPda;
+
+impl VestingPda {
+    pub const SEED: &'static [u8] = b"vesting";
+
+    pub fn find(program_id: &Pubkey, season_id: u64, winner: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED, &season_id.to_le_bytes(), winner.as_ref()],
+            program_id,
+        )
+    }
+}
+
+// Wager match `match_id` between two players; the order of the players matters
+pub struct MatchPda;
+
+impl MatchPda {
+    pub const SEED: &'static [u8] = b"match";
+
+    pub fn find(
+        program_id: &Pubkey,
+        player_a: &Pubkey,
+        player_b: &Pubkey,
+        match_id: u64,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                Self::SEED,
+                player_a.as_ref(),
+                player_b.as_ref(),
+                &match_id.to_le_bytes(),
+            ],
+            program_id,
+        )
+    }
+}
+
+// A tournament
+pub struct TournamentPda;
+
+impl TournamentPda {
+    pub const SEED: &'static [u8] = b"tournament";
+
+    pub fn find(program_id: &Pubkey, tournament_id: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, &tournament_id.to_le_bytes()], program_id)
+    }
+}
+
+// A guild
+pub struct GuildPda;
+
+impl GuildPda {
+    pub const SEED: &'static [u8] = b"guild";
+
+    pub fn find(program_id: &Pubkey, guild_id: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, &guild_id.to_le_bytes()], program_id)
+    }
+}
+
+// A player's guild membership
+pub struct GuildMemberPda;
+
+impl GuildMemberPda {
+    pub const SEED: &'static [u8] = b"guild_member";
+
+    pub fn find(program_id: &Pubkey, player: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, player.as_ref()], program_id)
+    }
+}
+
+// The guild leaderboard
+pub struct GuildLeaderboardPda;
+
+impl GuildLeaderboardPda {
+    pub const SEED: &'static [u8] = b"guild_leaderboard";
+
+    pub fn find(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+}
+
+// The weekly leaderboard, kept alongside the all-time one
+pub struct WeeklyLeaderboardPda;
+
+impl WeeklyLeaderboardPda {
+    pub const SEED: &'static [u8] = b"weekly_leaderboard";
+
+    pub fn find(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+}
+
+// The leaderboard of one region, keyed by its two-letter code
+pub struct RegionalLeaderboardPda;
+
+impl RegionalLeaderboardPda {
+    pub const SEED: &'static [u8] = b"regional_leaderboard";
+
+    pub fn find(program_id: &Pubkey, region: &[u8; 2]) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, region], program_id)
+    }
+}
+
+// A game session a player started with the game server, keyed by the server's seed
+pub struct GameSessionPda;
+
+impl GameSessionPda {
+    pub const SEED: &'static [u8] = b"game_session";
+
+    pub fn find(program_id: &Pubkey, player: &Pubkey, seed: &[u8; 32]) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, player.as_ref(), seed], program_id)
+    }
+}
+
+// Spectator bets on a match
+pub struct BetPoolPda;
+
+impl BetPoolPda {
+    pub const SEED: &'static [u8] = b"bet_pool";
+
+    pub fn find(program_id: &Pubkey, match_account: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, match_account.as_ref()], program_id)
+    }
+}
+
+// One bettor's bet on a match
+pub struct BetPda;
+
+impl BetPda {
+    pub const SEED: &'static [u8] = b"bet";
+
+    pub fn find(program_id: &Pubkey, match_account: &Pubkey, bettor: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED, match_account.as_ref(), bettor.as_ref()],
+            program_id,
+        )
+    }
+}
+
+// A host's matchmaking lobby
+pub struct LobbyPda;
+
+impl LobbyPda {
+    pub const SEED: &'static [u8] = b"lobby";
+
+    pub fn find(program_id: &Pubkey, host: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, host.as_ref()], program_id)
+    }
+}
+
+// A co-op team's shared score account, keyed by its sorted members
+pub struct TeamPda;
+
+impl TeamPda {
+    pub const SEED: &'static [u8] = b"team";
+
+    pub fn find(program_id: &Pubkey, sorted_members: &[Pubkey]) -> (Pubkey, u8) {
+        let mut seeds: Vec<&[u8]> = vec![Self::SEED];
+        seeds.extend(sorted_members.iter().map(|m| m.as_ref()));
+        Pubkey::find_program_address(&seeds, program_id)
+    }
+}
+
+// The token authorizing `session_key` for a player
+pub struct SessionPda;
+
+impl SessionPda {
+    pub const SEED: &'static [u8] = b"session";
+
+    pub fn find(program_id: &Pubkey, player: &Pubkey, session_key: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED, player.as_ref(), session_key.as_ref()],
+            program_id,
+        )
+    }
+}
+
+// A player's run history for one game mode
+pub struct HistoryPda;
+
+impl HistoryPda {
+    pub const SEED: &'static [u8] = b"history";
+
+    pub fn find(program_id: &Pubkey, player: &Pubkey, game_mode: GameMode) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED, player.as_ref(), &game_mode.seed()],
+            program_id,
+        )
+    }
+}
+
+// An airdrop
+pub struct AirdropPda;
+
+impl AirdropPda {
+    pub const SEED: &'static [u8] = b"airdrop";
+
+    pub fn find(program_id: &Pubkey, airdrop_id: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, &airdrop_id.to_le_bytes()], program_id)
+    }
+}
+
+// An airdrop's claimed bitmap
+pub struct AirdropClaimsPda;
+
+impl AirdropClaimsPda {
+    pub const SEED: &'static [u8] = b"airdrop_claims";
+
+    pub fn find(program_id: &Pubkey, airdrop_id: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, &airdrop_id.to_le_bytes()], program_id)
+    }
+}
+
+// The score tree that is the authority of a compressed score Merkle tree
+pub struct ScoreTreePda;
+
+impl ScoreTreePda {
+    pub const SEED: &'static [u8] = b"score_tree";
+
+    pub fn find(program_id: &Pubkey, merkle_tree: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, merkle_tree.as_ref()], program_id)
+    }
+}
+
+// Quest `quest_id`
+pub struct QuestPda;
+
+impl QuestPda {
+    pub const SEED: &'static [u8] = b"quest";
+
+    pub fn find(program_id: &Pubkey, quest_id: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, &quest_id.to_le_bytes()], program_id)
+    }
+}
+
+// A player's progress towards quest `quest_id`
+pub struct QuestProgressPda;
+
+impl QuestProgressPda {
+    pub const SEED: &'static [u8] = b"quest_progress";
+
+    pub fn find(program_id: &Pubkey, quest_id: u64, player: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED, &quest_id.to_le_bytes(), player.as_ref()],
+            program_id,
+        )
+    }
+}
+
+// The daily challenge for UTC day `day`
+pub struct DailyChallengePda;
+
+impl DailyChallengePda {
+    pub const SEED: &'static [u8] = b"daily_challenge";
+
+    pub fn find(program_id: &Pubkey, day: i64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, &day.to_le_bytes()], program_id)
+    }
+}
+
+// A player's submission to the daily challenge of UTC day `day`
+pub struct DailySubmissionPda;
+
+impl DailySubmissionPda {
+    pub const SEED: &'static [u8] = b"daily_submission";
+
+    pub fn find(program_id: &Pubkey, day: i64, player: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED, &day.to_le_bytes(), player.as_ref()],
+            program_id,
+        )
+    }
+}
+
+// Cosmetic `cosmetic_id` and what unlocks it
+pub struct CosmeticPda;
+
+impl CosmeticPda {
+    pub const SEED: &'static [u8] = b"cosmetic";
+
+    pub fn find(program_id: &Pubkey, cosmetic_id: u8) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, &[cosmetic_id]], program_id)
+    }
+}
+
+// The catalog of badges and their mints
+pub struct BadgeCatalogPda;
+
+impl BadgeCatalogPda {
+    pub const SEED: &'static [u8] = b"badge_catalog";
+
+    pub fn find(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+}
+
+// The mint authority of every badge mint
+pub struct BadgeAuthorityPda;
+
+impl BadgeAuthorityPda {
+    pub const SEED: &'static [u8] = b"badge_authority";
+
+    pub fn find(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+}
+
+// Program-wide statistics
+pub struct GlobalStatsPda;
+
+impl GlobalStatsPda {
+    pub const SEED: &'static [u8] = b"global_stats";
+
+    pub fn find(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+
+    pub fn create(program_id: &Pubkey, bump: u8) -> Result<Pubkey, PubkeyError> {
+        Pubkey::create_program_address(&[Self::SEED, &[bump]], program_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEEDS: [&[u8]; 47] = [
+        ScorePda::SEED,
+        LeaderboardPda::SEED,
+        SeasonPda::SEED,
+        ConfigPda::SEED,
+        BanListPda::SEED,
+        ReportPda::SEED,
+        AdminSetPda::SEED,
+        ProposalPda::SEED,
+        LootRequestPda::SEED,
+        InventoryPda::SEED,
+        CatalogPda::SEED,
+        TreasuryPda::SEED,
+        FeeVaultPda::SEED,
+        ProfilePda::SEED,
+        FollowPda::SEED,
+        NameRecordPda::SEED,
+        TrophyAuthorityPda::SEED,
+        VaultPda::SEED,
+        PrizePoolPda::SEED,
+        VestingPda::SEED,
+        StakePda::SEED,
+        StakeVaultPda::SEED,
+        MatchPda::SEED,
+        LobbyPda::SEED,
+        BetPoolPda::SEED,
+        BetPda::SEED,
+        TournamentPda::SEED,
+        GuildPda::SEED,
+        GuildMemberPda::SEED,
+        GuildLeaderboardPda::SEED,
+        WeeklyLeaderboardPda::SEED,
+        RegionalLeaderboardPda::SEED,
+        TeamPda::SEED,
+        SessionPda::SEED,
+        GameSessionPda::SEED,
+        HistoryPda::SEED,
+        AirdropPda::SEED,
+        AirdropClaimsPda::SEED,
+        ScoreTreePda::SEED,
+        QuestPda::SEED,
+        QuestProgressPda::SEED,
+        DailyChallengePda::SEED,
+        DailySubmissionPda::SEED,
+        CosmeticPda::SEED,
+        BadgeCatalogPda::SEED,
+        BadgeAuthorityPda::SEED,
+        GlobalStatsPda::SEED,
+    ];
+
+    #[test]
+    fn seeds_are_distinct() {
+        for (i, a) in SEEDS.iter().enumerate() {
+            for b in &SEEDS[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn singletons_differ_by_kind() {
+        let program_id = Pubkey::new_unique();
+        assert_ne!(ConfigPda::find(&program_id), SeasonPda::find(&program_id));
+        assert_ne!(
+            LeaderboardPda::find(&program_id),
+            GuildLeaderboardPda::find(&program_id)
+        );
+    }
+
+    #[test]
+    fn create_rebuilds_found_addresses() {
+        let program_id = Pubkey::new_unique();
+        let player = Pubkey::new_unique();
+        let (score, bump) = ScorePda::find(&program_id, &player, GameMode::Arcade);
+        assert_eq!(
+            ScorePda::create(&program_id, &player, GameMode::Arcade, bump),
+            Ok(score)
+        );
+        let (config, bump) = ConfigPda::find(&program_id);
+        assert_eq!(ConfigPda::create(&program_id, bump), Ok(config));
+    }
+
+    #[test]
+    fn leaderboard_pages_differ_from_the_global_board() {
+        let program_id = Pubkey::new_unique();
+        assert_ne!(
+            LeaderboardPda::find(&program_id),
+            LeaderboardPda::find_page(&program_id, 1)
+        );
+        assert_ne!(
+            LeaderboardPda::find_page(&program_id, 1),
+            LeaderboardPda::find_page(&program_id, 2)
+        );
+    }
+
+    #[test]
+    fn ids_are_keyed_little_endian() {
+        let program_id = Pubkey::new_unique();
+        assert_eq!(
+            GuildPda::find(&program_id, 7),
+            Pubkey::find_program_address(&[b"guild", &[7, 0, 0, 0, 0, 0, 0, 0]], &program_id)
+        );
+        assert_ne!(
+            AirdropPda::find(&program_id, 7),
+            AirdropClaimsPda::find(&program_id, 7)
+        );
+    }
+}
@@ -0,0 +1,6991 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    entrypoint::ProgramResult,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    program::{invoke, invoke_signed, set_return_data},
+    system_instruction,
+    sysvar::{self, clock::Clock, rent::Rent, Sysvar},
+    program_pack::{IsInitialized, Pack},
+};
+
+use mpl_token_metadata::{
+    accounts::Metadata,
+    instructions::{
+        CreateMasterEditionV3Cpi, CreateMasterEditionV3CpiAccounts, CreateMasterEditionV3InstructionArgs,
+        CreateMetadataAccountV3Cpi, CreateMetadataAccountV3CpiAccounts, CreateMetadataAccountV3InstructionArgs,
+    },
+    types::DataV2,
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::{
+    extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions},
+    onchain::invoke_transfer_checked,
+};
+use pyth_sdk_solana::state::SolanaPriceAccount;
+use switchboard_on_demand::{RandomnessAccountData, SWITCHBOARD_ON_DEMAND_PROGRAM_ID};
+
+use crate::{
+    attestation,
+    error::GarbageGrabError,
+    events::{ScoreChange, ScoreUpdated, TreasuryWithdrawn},
+    instruction::{ConfigUpdate, GarbageGrabInstruction},
+    merkle,
+    pda::{
+        AdminSetPda, AirdropClaimsPda, AirdropPda, BadgeAuthorityPda, BadgeCatalogPda, BanListPda, BetPda, BetPoolPda, CatalogPda, ConfigPda, CosmeticPda, DailyChallengePda, DailySubmissionPda, FeeVaultPda, FollowPda, GameSessionPda, GlobalStatsPda, GuildLeaderboardPda, GuildMemberPda, GuildPda, HistoryPda, InventoryPda, LeaderboardPda, LobbyPda, LootRequestPda,
+        MatchPda, NameRecordPda, PrizePoolPda, ProfilePda, ProposalPda, QuestPda, QuestProgressPda, RegionalLeaderboardPda, ReportPda, ScorePda, ScoreTreePda, SeasonPda, SessionPda, StakePda, StakeVaultPda, TeamPda, TournamentPda, TreasuryPda, TrophyAuthorityPda, VaultPda,
+        VestingPda, WeeklyLeaderboardPda,
+    },
+    state::{
+        self, normalize_profile_name, AdminSet, Airdrop, AirdropClaims, BadgeCatalog, BadgeDefinition, BadgeMilestone, BanList, Bet, BetPool, BetPoolStatus, CompressedScore, Config, Catalog, Cosmetic, DailyChallenge, DailySubmission, Follow, GameMode, GameSession, GlobalStats, Guild, GuildMember, Inventory, ItemDefinition, ItemEffect, Leaderboard, LeaderboardEntry, Lobby, ShopCurrency, LootRequest, LootTier, Match, MatchStatus, NameRecord,
+        PlayerProfile, Proposal, ProposalAccount, Quest, QuestMetric, QuestProgress, Report, RunRecord, ScoreAccount, ScoreHistory, ScoreTree, Season, SessionToken, Stake, StakeLock, Team, Tournament, TournamentEntry, Vesting, WeeklyLeaderboard, run_xp, week_of, MAX_ADMIN_SET_MEMBERS, MAX_BPS, MAX_LEVEL, PROFILE_NAME_LEN, COSMETIC_COUNT, NO_ACHIEVEMENT,
+        LEADERBOARD_PAGES, RANKED_GAME_MODE, LOBBY_CAPACITY, LOOT_PRICE_LAMPORTS, REPORT_BOND_LAMPORTS, TEAM_MAX_MEMBERS, TOURNAMENT_CAPACITY, NO_REGION, RECENT_RUN_HASHES, SECONDS_PER_DAY, STAKE_TIERS, is_valid_region,
+    },
+};
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    // Every instruction starts with a tag byte, so an empty payload can never be valid
+    if instruction_data.is_empty() {
+        msg!("Instruction data is empty");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // Decode the Borsh-encoded instruction and dispatch on its variant
+    let instruction = GarbageGrabInstruction::unpack(instruction_data)?;
+
+    match instruction {
+        GarbageGrabInstruction::Initialize { game_mode, referrer, allowlist_proof } => {
+            process_initialize(program_id, accounts, game_mode, referrer, &allowlist_proof)
+        }
+        GarbageGrabInstruction::UpdateScore { game_mode, increment } => {
+            process_update_score(program_id, accounts, game_mode, increment)
+        }
+        GarbageGrabInstruction::ResetScore { game_mode } => {
+            process_reset_score(program_id, accounts, game_mode)
+        }
+        GarbageGrabInstruction::CloseScoreAccount { game_mode } => {
+            process_close_score_account(program_id, accounts, game_mode)
+        }
+        GarbageGrabInstruction::SubmitRun { game_mode, run_score, run_hash } => {
+            process_submit_run(program_id, accounts, game_mode, run_score, run_hash)
+        }
+        GarbageGrabInstruction::InitializeLeaderboard => {
+            process_initialize_leaderboard(program_id, accounts)
+        }
+        GarbageGrabInstruction::SubmitToLeaderboard { game_mode } => {
+            process_submit_to_leaderboard(program_id, accounts, game_mode)
+        }
+        GarbageGrabInstruction::StartSeason { end_ts } => {
+            process_start_season(program_id, accounts, end_ts)
+        }
+        GarbageGrabInstruction::InitializeConfig { max_score, max_increment, update_cooldown_secs } => {
+            process_initialize_config(program_id, accounts, max_score, max_increment, update_cooldown_secs)
+        }
+        GarbageGrabInstruction::UpdateConfig(update) => process_update_config(program_id, accounts, update),
+        GarbageGrabInstruction::Pause => process_set_paused(program_id, accounts, true),
+        GarbageGrabInstruction::Unpause => process_set_paused(program_id, accounts, false),
+        GarbageGrabInstruction::SubmitAttestedScore { game_mode, increment, nonce } => {
+            process_submit_attested_score(program_id, accounts, game_mode, increment, nonce)
+        }
+        GarbageGrabInstruction::PenalizeScore { game_mode, amount } => {
+            process_penalize_score(program_id, accounts, game_mode, amount)
+        }
+        GarbageGrabInstruction::SetScore { game_mode, value } => {
+            process_set_score(program_id, accounts, game_mode, value)
+        }
+        GarbageGrabInstruction::MigrateAccount { game_mode } => {
+            process_migrate_account(program_id, accounts, game_mode)
+        }
+        GarbageGrabInstruction::ClaimRewards { game_mode } => {
+            process_claim_rewards(program_id, accounts, game_mode)
+        }
+        GarbageGrabInstruction::InitializeVault => process_initialize_vault(program_id, accounts),
+        GarbageGrabInstruction::FundVault { amount } => process_fund_vault(program_id, accounts, amount),
+        GarbageGrabInstruction::StartRankedRun { game_mode } => {
+            process_start_ranked_run(program_id, accounts, game_mode)
+        }
+        GarbageGrabInstruction::DistributePrizes => process_distribute_prizes(program_id, accounts),
+        GarbageGrabInstruction::CreateMatch { match_id, stake } => {
+            process_create_match(program_id, accounts, match_id, stake)
+        }
+        GarbageGrabInstruction::SubmitMatchResult { winner } => {
+            process_submit_match_result(program_id, accounts, winner)
+        }
+        GarbageGrabInstruction::SettleMatch => process_settle_match(program_id, accounts),
+        GarbageGrabInstruction::CreateTournament { tournament_id, entry_fee, max_participants, end_ts, collection } => {
+            process_create_tournament(program_id, accounts, tournament_id, entry_fee, max_participants, end_ts, collection)
+        }
+        GarbageGrabInstruction::Register => process_register(program_id, accounts),
+        GarbageGrabInstruction::SubmitTournamentScore { score } => {
+            process_submit_tournament_score(program_id, accounts, score)
+        }
+        GarbageGrabInstruction::FinalizeTournament => process_finalize_tournament(program_id, accounts),
+        GarbageGrabInstruction::UnlockAchievement { game_mode, id } => {
+            process_unlock_achievement(program_id, accounts, game_mode, id)
+        }
+        GarbageGrabInstruction::CheckIn { game_mode } => process_check_in(program_id, accounts, game_mode),
+        GarbageGrabInstruction::ApplyBoost { game_mode, increment } => {
+            process_apply_boost(program_id, accounts, game_mode, increment)
+        }
+        GarbageGrabInstruction::MintTrophy { game_mode } => process_mint_trophy(program_id, accounts, game_mode),
+        GarbageGrabInstruction::CreateProfile { name, avatar_mint } => {
+            process_create_profile(program_id, accounts, name, avatar_mint)
+        }
+        GarbageGrabInstruction::UpdateProfile { name, avatar_mint } => {
+            process_update_profile(program_id, accounts, name, avatar_mint)
+        }
+        GarbageGrabInstruction::CreditReferral { game_mode } => process_credit_referral(program_id, accounts, game_mode),
+        GarbageGrabInstruction::CreateGuild { guild_id, name } => process_create_guild(program_id, accounts, guild_id, name),
+        GarbageGrabInstruction::JoinGuild => process_join_guild(program_id, accounts),
+        GarbageGrabInstruction::LeaveGuild => process_leave_guild(program_id, accounts),
+        GarbageGrabInstruction::InitializeGuildLeaderboard => process_initialize_guild_leaderboard(program_id, accounts),
+        GarbageGrabInstruction::SubmitGuildToLeaderboard => process_submit_guild_to_leaderboard(program_id, accounts),
+        GarbageGrabInstruction::CreateTeam => process_create_team(program_id, accounts),
+        GarbageGrabInstruction::UpdateTeamScore { increment } => process_update_team_score(program_id, accounts, increment),
+        GarbageGrabInstruction::ApproveDelegate { game_mode, delegate } => {
+            process_set_delegate(program_id, accounts, game_mode, delegate)
+        }
+        GarbageGrabInstruction::RevokeDelegate { game_mode } => {
+            process_set_delegate(program_id, accounts, game_mode, Pubkey::default())
+        }
+        GarbageGrabInstruction::UpdateScoreAsDelegate { game_mode, increment } => {
+            process_update_score_as_delegate(program_id, accounts, game_mode, increment)
+        }
+        GarbageGrabInstruction::CreateSession { session_key, expiry_slot } => {
+            process_create_session(program_id, accounts, session_key, expiry_slot)
+        }
+        GarbageGrabInstruction::RevokeSession => process_revoke_session(program_id, accounts),
+        GarbageGrabInstruction::UpdateScoreWithSession { game_mode, increment } => {
+            process_update_score_with_session(program_id, accounts, game_mode, increment)
+        }
+        GarbageGrabInstruction::InitializeHistory { game_mode } => process_initialize_history(program_id, accounts, game_mode),
+        GarbageGrabInstruction::Realloc { new_len } => process_realloc(program_id, accounts, new_len),
+        GarbageGrabInstruction::SetRewardRoot { airdrop_id, merkle_root, max_claims } => {
+            process_set_reward_root(program_id, accounts, airdrop_id, merkle_root, max_claims)
+        }
+        GarbageGrabInstruction::ClaimAirdrop { airdrop_id, index, amount, proof } => {
+            process_claim_airdrop(program_id, accounts, airdrop_id, index, amount, proof)
+        }
+        GarbageGrabInstruction::InitializeScoreTree { max_depth, max_buffer_size } => {
+            process_initialize_score_tree(program_id, accounts, max_depth, max_buffer_size)
+        }
+        GarbageGrabInstruction::AppendCompressedScore { game_mode, score } => {
+            process_append_compressed_score(program_id, accounts, game_mode, score)
+        }
+        GarbageGrabInstruction::VerifyCompressedScore { root, score } => {
+            process_verify_compressed_score(program_id, accounts, root, score)
+        }
+        GarbageGrabInstruction::BatchUpdate { game_mode, increments } => {
+            process_batch_update(program_id, accounts, game_mode, increments)
+        }
+        GarbageGrabInstruction::TopUpRent => process_top_up_rent(program_id, accounts),
+        GarbageGrabInstruction::Ban { player } => process_ban(program_id, accounts, player),
+        GarbageGrabInstruction::Unban { player } => process_unban(program_id, accounts, player),
+        GarbageGrabInstruction::ReportPlayer { game_mode } => process_report_player(program_id, accounts, game_mode),
+        GarbageGrabInstruction::ResolveReport { upheld, slash } => {
+            process_resolve_report(program_id, accounts, upheld, slash)
+        }
+        GarbageGrabInstruction::SetAdminSet { threshold, members } => {
+            process_set_admin_set(program_id, accounts, threshold, members)
+        }
+        GarbageGrabInstruction::Propose { accounts: proposed_accounts, data } => {
+            process_propose(program_id, accounts, proposed_accounts, data)
+        }
+        GarbageGrabInstruction::Approve => process_approve(program_id, accounts),
+        GarbageGrabInstruction::ExecuteProposal => process_execute_proposal(program_id, accounts),
+        GarbageGrabInstruction::NominateAdmin { new_admin } => process_nominate_admin(program_id, accounts, new_admin),
+        GarbageGrabInstruction::AcceptAdmin => process_accept_admin(program_id, accounts),
+        GarbageGrabInstruction::RequestLoot => process_request_loot(program_id, accounts),
+        GarbageGrabInstruction::FulfillLoot => process_fulfill_loot(program_id, accounts),
+        GarbageGrabInstruction::SetCatalogItem { definition } => process_set_catalog_item(program_id, accounts, definition),
+        GarbageGrabInstruction::GrantItem { item_id, quantity } => process_grant_item(program_id, accounts, item_id, quantity),
+        GarbageGrabInstruction::ConsumeItem { game_mode, item_id } => process_consume_item(program_id, accounts, game_mode, item_id),
+        GarbageGrabInstruction::PurchaseItem { item_id, quantity, currency } => {
+            process_purchase_item(program_id, accounts, item_id, quantity, currency)
+        }
+        GarbageGrabInstruction::Continue { game_mode } => process_continue(program_id, accounts, game_mode),
+        GarbageGrabInstruction::CrankSeasonRollover => process_crank_season_rollover(program_id, accounts),
+        GarbageGrabInstruction::CrankLeaderboardFinalize => process_crank_leaderboard_finalize(program_id, accounts),
+        GarbageGrabInstruction::VerifyScoreAtLeast { threshold } => process_verify_score_at_least(program_id, accounts, threshold),
+        GarbageGrabInstruction::WithdrawTreasury { amount } => process_withdraw_treasury(program_id, accounts, amount),
+        GarbageGrabInstruction::InitializeLeaderboardPage { page } => process_initialize_leaderboard_page(program_id, accounts, page),
+        GarbageGrabInstruction::SubmitToLeaderboardPage { game_mode, page } => {
+            process_submit_to_leaderboard_page(program_id, accounts, game_mode, page)
+        }
+        GarbageGrabInstruction::RebalanceLeaderboardPages { page } => process_rebalance_leaderboard_pages(program_id, accounts, page),
+        GarbageGrabInstruction::InitializeWeeklyLeaderboard => process_initialize_weekly_leaderboard(program_id, accounts),
+        GarbageGrabInstruction::SubmitToWeeklyLeaderboard { game_mode } => process_submit_to_weekly_leaderboard(program_id, accounts, game_mode),
+        GarbageGrabInstruction::Follow => process_follow(program_id, accounts),
+        GarbageGrabInstruction::Unfollow => process_unfollow(program_id, accounts),
+        GarbageGrabInstruction::SetRegion { region } => process_set_region(program_id, accounts, region),
+        GarbageGrabInstruction::AdminSetRegion { region } => process_admin_set_region(program_id, accounts, region),
+        GarbageGrabInstruction::InitializeRegionalLeaderboard { region } => process_initialize_regional_leaderboard(program_id, accounts, region),
+        GarbageGrabInstruction::SubmitToRegionalLeaderboard { game_mode } => process_submit_to_regional_leaderboard(program_id, accounts, game_mode),
+        GarbageGrabInstruction::StartSession { game_mode, seed } => process_start_session(program_id, accounts, game_mode, seed),
+        GarbageGrabInstruction::EndSession { score } => process_end_session(program_id, accounts, score),
+        GarbageGrabInstruction::CreateLobby { capacity, stake } => process_create_lobby(program_id, accounts, capacity, stake),
+        GarbageGrabInstruction::JoinLobby => process_join_lobby(program_id, accounts),
+        GarbageGrabInstruction::LeaveLobby => process_leave_lobby(program_id, accounts),
+        GarbageGrabInstruction::StartMatch => process_start_match(program_id, accounts),
+        GarbageGrabInstruction::PlaceBet { on, amount } => process_place_bet(program_id, accounts, on, amount),
+        GarbageGrabInstruction::SettleBets => process_settle_bets(program_id, accounts),
+        GarbageGrabInstruction::WithdrawFees { amount } => process_withdraw_fees(program_id, accounts, amount),
+        GarbageGrabInstruction::ClaimVested => process_claim_vested(program_id, accounts),
+        GarbageGrabInstruction::Stake { amount, lock } => process_stake(program_id, accounts, amount, lock),
+        GarbageGrabInstruction::Unstake { amount } => process_unstake(program_id, accounts, amount),
+        GarbageGrabInstruction::CreateQuest {
+            quest_id,
+            metric,
+            target,
+            reward,
+        } => process_create_quest(program_id, accounts, quest_id, metric, target, reward),
+        GarbageGrabInstruction::AcceptQuest => process_accept_quest(program_id, accounts),
+        GarbageGrabInstruction::ClaimQuestReward => process_claim_quest_reward(program_id, accounts),
+        GarbageGrabInstruction::PublishDailyChallenge { seed, date } => process_publish_daily_challenge(program_id, accounts, seed, date),
+        GarbageGrabInstruction::SubmitDailyChallenge { date, score } => process_submit_daily_challenge(program_id, accounts, date, score),
+        GarbageGrabInstruction::SetCosmetic {
+            cosmetic_id,
+            min_level,
+            game_mode,
+            achievement,
+        } => process_set_cosmetic(program_id, accounts, cosmetic_id, min_level, game_mode, achievement),
+        GarbageGrabInstruction::UnlockCosmetic => process_unlock_cosmetic(program_id, accounts),
+        GarbageGrabInstruction::AddBadge { milestone, threshold } => process_add_badge(program_id, accounts, milestone, threshold),
+        GarbageGrabInstruction::ClaimBadge { badge_id, game_mode } => process_claim_badge(program_id, accounts, badge_id, game_mode),
+        GarbageGrabInstruction::InitializeGlobalStats => process_initialize_global_stats(program_id, accounts),
+    }
+}
+
+// Ensure the given account signed the transaction
+fn check_signer(account: &AccountInfo) -> ProgramResult {
+    if !account.is_signer {
+        msg!("Account {} did not sign the transaction", account.key);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    Ok(())
+}
+
+// Ensure the account's data was written by this program and can be trusted
+fn check_program_owned(program_id: &Pubkey, account: &AccountInfo) -> ProgramResult {
+    if account.owner != program_id {
+        msg!("Account {} is owned by {}, not this program", account.key, account.owner);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+// Ensure the account passed for CPI account creation is the real system program
+fn check_system_program(account: &AccountInfo) -> ProgramResult {
+    if !solana_program::system_program::check_id(account.key) {
+        msg!("Expected the system program, found {}", account.key);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+// Derive the score PDA using "score" seed, player's public key and game mode, and
+// check it matches the passed account. Accounts in the current layout carry their
+// bump, so only uninitialized and older accounts pay for the search. Returns the
+// bump seed.
+fn check_score_pda(
+    program_id: &Pubkey,
+    score_account: &AccountInfo,
+    player: &Pubkey,
+    game_mode: GameMode,
+) -> Result<u8, ProgramError> {
+    let stored_bump = ScoreAccount::stored_bump(&score_account.try_borrow_data()?);
+    let (pda, bump) = match stored_bump {
+        Some(bump) => (ScorePda::create(program_id, player, game_mode, bump).map_err(|_| GarbageGrabError::InvalidPda)?, bump),
+        None => ScorePda::find(program_id, player, game_mode),
+    };
+    if pda != *score_account.key {
+        msg!("Invalid score account PDA: expected {}, found {}", pda, score_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Read an initialized score account and make sure it belongs to the player
+fn load_score(score_data: &[u8], player: &Pubkey) -> Result<ScoreAccount, ProgramError> {
+    if score_data.is_empty() {
+        msg!("Account not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+    let state = ScoreAccount::unpack_any_version(score_data)?;
+    if !state.is_initialized() {
+        msg!("Account not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+    // Older layouts must be migrated before the program writes to them again
+    if score_data.len() < ScoreAccount::LEN || state.version != ScoreAccount::VERSION {
+        msg!("Score account is at layout version {}, run MigrateAccount first", state.version);
+        return Err(GarbageGrabError::AccountNeedsMigration.into());
+    }
+    if state.player != *player {
+        msg!("Score account belongs to {}, not {}", state.player, player);
+        return Err(GarbageGrabError::Unauthorized.into());
+    }
+    Ok(state)
+}
+
+// Only the player may modify their own score account, and it must be their program-owned
+// PDA for the mode
+fn check_player_score(
+    program_id: &Pubkey,
+    score_account: &AccountInfo,
+    player: &AccountInfo,
+    game_mode: GameMode,
+) -> ProgramResult {
+    check_signer(player)?;
+    check_score_pda(program_id, score_account, player.key, game_mode)?;
+    check_program_owned(program_id, score_account)
+}
+
+// Check the passed account is the season PDA. Returns the bump seed.
+fn check_season_pda(program_id: &Pubkey, season_account: &AccountInfo) -> Result<u8, ProgramError> {
+    let (pda, bump) = SeasonPda::find(program_id);
+    if pda != *season_account.key {
+        msg!("Invalid season PDA: expected {}, found {}", pda, season_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Read the current season id; before the first season starts this is season 0
+fn current_season_id(program_id: &Pubkey, season_account: &AccountInfo) -> Result<u64, ProgramError> {
+    check_season_pda(program_id, season_account)?;
+    if season_account.data_is_empty() {
+        return Ok(0);
+    }
+    if season_account.owner != program_id {
+        msg!("Season account must be owned by the program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(Season::unpack(&season_account.try_borrow_data()?)?.season_id)
+}
+
+// The id of the season scores are credited to. Once the active season has ended
+// nothing more is credited until StartSeason opens the next one.
+fn open_season_id(program_id: &Pubkey, season_account: &AccountInfo) -> Result<u64, ProgramError> {
+    let season_id = current_season_id(program_id, season_account)?;
+    if !season_account.data_is_empty() {
+        let season = Season::unpack(&season_account.try_borrow_data()?)?;
+        if Clock::get()?.unix_timestamp >= season.end_ts {
+            msg!("Season {} ended at {}", season_id, season.end_ts);
+            return Err(GarbageGrabError::SeasonEnded.into());
+        }
+    }
+    Ok(season_id)
+}
+
+// Check the passed account is the ban list PDA. Returns the bump seed.
+fn check_ban_list_pda(program_id: &Pubkey, ban_list_account: &AccountInfo) -> Result<u8, ProgramError> {
+    let (pda, bump) = BanListPda::find(program_id);
+    if pda != *ban_list_account.key {
+        msg!("Invalid ban list PDA: expected {}, found {}", pda, ban_list_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Reject players on the ban list. Until the first ban the list does not exist
+// and nobody is banned.
+fn check_not_banned(program_id: &Pubkey, ban_list_account: &AccountInfo, player: &Pubkey) -> ProgramResult {
+    check_ban_list_pda(program_id, ban_list_account)?;
+    if ban_list_account.data_is_empty() {
+        return Ok(());
+    }
+    check_program_owned(program_id, ban_list_account)?;
+    if BanList::unpack(&ban_list_account.try_borrow_data()?)?.is_banned(player) {
+        msg!("Player {} is banned", player);
+        return Err(GarbageGrabError::PlayerBanned.into());
+    }
+    Ok(())
+}
+
+// Check the passed account is the config PDA. Returns the bump seed.
+fn check_config_pda(program_id: &Pubkey, config_account: &AccountInfo) -> Result<u8, ProgramError> {
+    let (pda, bump) = ConfigPda::find(program_id);
+    if pda != *config_account.key {
+        msg!("Invalid config PDA: expected {}, found {}", pda, config_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Read the initialized config account. Only the program writes to accounts it
+// owns, so the address is checked against the bump stored in the config.
+fn load_config(program_id: &Pubkey, config_account: &AccountInfo) -> Result<Config, ProgramError> {
+    if config_account.owner != program_id || config_account.data_is_empty() {
+        check_config_pda(program_id, config_account)?;
+        msg!("Config not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+    let config = Config::unpack(&config_account.try_borrow_data()?)?;
+    if ConfigPda::create(program_id, config.bump).ok() != Some(*config_account.key) {
+        msg!("Invalid config PDA: {} does not derive from the stored bump", config_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    if !config.is_initialized() {
+        msg!("Config not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+    Ok(config)
+}
+
+// Reject score-mutating instructions while the admin has paused the program
+fn check_not_paused(config: &Config) -> ProgramResult {
+    if config.paused {
+        msg!("Program is paused");
+        return Err(GarbageGrabError::ProgramPaused.into());
+    }
+    Ok(())
+}
+
+// Reject score changes the game server has not vouched for while the config
+// requires attestation
+fn check_attestation_not_required(config: &Config) -> ProgramResult {
+    if config.require_attestation {
+        msg!("Scores must be attested by the game server");
+        return Err(GarbageGrabError::InvalidAttestation.into());
+    }
+    Ok(())
+}
+
+// Read the config and make sure `admin` is its signing admin
+fn load_config_as_admin(
+    program_id: &Pubkey,
+    config_account: &AccountInfo,
+    admin: &AccountInfo,
+) -> Result<Config, ProgramError> {
+    check_signer(admin)?;
+    let config = load_config(program_id, config_account)?;
+    if config.admin != *admin.key {
+        msg!("Signer {} is not the admin", admin.key);
+        return Err(GarbageGrabError::Unauthorized.into());
+    }
+    Ok(config)
+}
+
+// Read the config and make sure `game_server` is the signing game server
+fn load_config_as_game_server(
+    program_id: &Pubkey,
+    config_account: &AccountInfo,
+    game_server: &AccountInfo,
+) -> Result<Config, ProgramError> {
+    check_signer(game_server)?;
+    let config = load_config(program_id, config_account)?;
+    if config.game_server == Pubkey::default() || config.game_server != *game_server.key {
+        msg!("Signer {} is not the game server", game_server.key);
+        return Err(GarbageGrabError::Unauthorized.into());
+    }
+    Ok(config)
+}
+
+// Read the config and make sure `authority` is a signing admin or game server
+fn load_config_as_moderator(
+    program_id: &Pubkey,
+    config_account: &AccountInfo,
+    authority: &AccountInfo,
+) -> Result<Config, ProgramError> {
+    check_signer(authority)?;
+    let config = load_config(program_id, config_account)?;
+    let is_server = config.game_server != Pubkey::default() && config.game_server == *authority.key;
+    if config.admin != *authority.key && !is_server {
+        msg!("Signer {} is neither the admin nor the game server", authority.key);
+        return Err(GarbageGrabError::Unauthorized.into());
+    }
+    Ok(config)
+}
+
+fn process_initialize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    game_mode: GameMode,
+    referrer: Option<Pubkey>,
+    allowlist_proof: &[[u8; 32]],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let score_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+
+    // Only the player may initialize their own score account
+    check_signer(player)?;
+    let bump = check_score_pda(program_id, score_account, player.key, game_mode)?;
+    check_system_program(system_program)?;
+    let config = load_config(program_id, config_account)?;
+    let stats_account = next_global_stats(program_id, accounts_iter)?;
+    if config.closed_beta && !merkle::verify_proof(merkle::allowlist_leaf(player.key), allowlist_proof, &config.allowlist_root) {
+        msg!("Player {} is not on the closed beta allowlist", player.key);
+        return Err(GarbageGrabError::NotAllowlisted.into());
+    }
+    if referrer == Some(*player.key) {
+        msg!("Players cannot refer themselves");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Check if the account is already initialized, in any layout, with an immutable borrow
+    let is_initialized = {
+        let data = score_account.try_borrow_data()?;
+        !data.is_empty() && ScoreAccount::unpack_any_version(&data)?.is_initialized()
+    };
+    if is_initialized {
+        msg!("Account already initialized");
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+
+    // Create the account, signing for the PDA with its seeds
+    let space = ScoreAccount::LEN;
+    create_pda_account(player, score_account, system_program, space, program_id, &[ScorePda::SEED, player.key.as_ref(), &game_mode.seed(), &[bump]])?;
+
+    // Now, write the initial state into the account data
+    let state = ScoreAccount {
+        is_initialized: true,
+        player: *player.key,
+        score: 0,
+        game_mode,
+        best_score: 0,
+        season_id: 0,
+        last_update_ts: 0,
+        nonce: 0,
+        version: ScoreAccount::VERSION,
+        rewards_claimed: 0,
+        ranked_run_open: false,
+        achievements: 0,
+        streak: 0,
+        last_play_day: 0,
+        trophies: 0,
+        trophy_best: 0,
+        referrer: referrer.unwrap_or_default(),
+        referral_credited: false,
+        delegate: Pubkey::default(),
+        last_update_slot: 0,
+        window_updates: 0,
+        item_boost_bps: 0,
+        continue_pending: false,
+        run_continues: 0,
+        bump,
+        week: 0,
+        week_best_score: 0,
+        recent_run_hashes: [Hash::default(); RECENT_RUN_HASHES],
+        run_hash_cursor: 0,
+    };
+    state.pack(&mut score_account.try_borrow_mut_data()?)?;
+    emit_score_change(&state, ScoreChange::Initialized, 0, 0, Clock::get()?.unix_timestamp);
+    msg!("Score account initialized for player {} in mode {:?}", player.key, game_mode);
+    record_global_stats(program_id, stats_account, GlobalStats::record_player)
+}
+
+fn process_update_score(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    game_mode: GameMode,
+    increment: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let score_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let season_account = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let ban_list_account = next_account_info(accounts_iter)?;
+
+    check_player_score(program_id, score_account, player, game_mode)?;
+    let config = load_config(program_id, config_account)?;
+    check_attestation_not_required(&config)?;
+    let stats_account = next_global_stats(program_id, accounts_iter)?;
+    let stake_bps = stake_boost_bps(program_id, accounts_iter, player.key, &config)?;
+    let previous_best = best_score(score_account, player.key)?;
+    let credited = credit_increment(program_id, score_account, player.key, season_account, ban_list_account, &config, increment, None, stake_bps)?;
+    let best = best_score(score_account, player.key)?;
+    record_quest_progress(program_id, accounts_iter, player.key, QuestMetric::ScoreEarned, credited)?;
+    grant_run_xp(program_id, accounts_iter, player.key, credited)?;
+    credit_guild(program_id, accounts_iter, player.key, credited)?;
+    record_global_stats(program_id, stats_account, |stats| {
+        stats.record_score(credited);
+        stats.record_best(previous_best, best);
+    })
+}
+
+// Read the best score of a validated score account
+fn best_score(score_account: &AccountInfo, player: &Pubkey) -> Result<u64, ProgramError> {
+    Ok(load_score(&score_account.try_borrow_data()?, player)?.best_score)
+}
+
+fn process_update_score_as_delegate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    game_mode: GameMode,
+    increment: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let score_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let delegate = next_account_info(accounts_iter)?;
+    let season_account = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let ban_list_account = next_account_info(accounts_iter)?;
+
+    check_signer(delegate)?;
+    check_score_pda(program_id, score_account, player.key, game_mode)?;
+    check_program_owned(program_id, score_account)?;
+    let state = load_score(&score_account.try_borrow_data()?, player.key)?;
+    if !state.is_delegate(delegate.key) {
+        msg!("{} is not the approved delegate of {}", delegate.key, player.key);
+        return Err(GarbageGrabError::NotDelegate.into());
+    }
+
+    let config = load_config(program_id, config_account)?;
+    check_attestation_not_required(&config)?;
+    let stats_account = next_global_stats(program_id, accounts_iter)?;
+    let credited = credit_increment(program_id, score_account, player.key, season_account, ban_list_account, &config, increment, None, MAX_BPS)?;
+    let best = best_score(score_account, player.key)?;
+    credit_guild(program_id, accounts_iter, player.key, credited)?;
+    record_global_stats(program_id, stats_account, |stats| {
+        stats.record_score(credited);
+        stats.record_best(state.best_score, best);
+    })
+}
+
+// Add an increment to a validated score account, enforcing the ban list, pause,
+// cooldown and score caps.
+// Signed submissions pass their nonce, which must match and is consumed on success.
+// `boost_bps` multiplies the increment on top of the streak bonus; MAX_BPS means none.
+// Returns the boosted amount actually added to the score.
+#[allow(clippy::too_many_arguments)]
+fn credit_increment(
+    program_id: &Pubkey,
+    score_account: &AccountInfo,
+    player: &Pubkey,
+    season_account: &AccountInfo,
+    ban_list_account: &AccountInfo,
+    config: &Config,
+    increment: u64,
+    nonce: Option<u64>,
+    boost_bps: u64,
+) -> Result<u64, ProgramError> {
+    check_not_banned(program_id, ban_list_account, player)?;
+    check_not_paused(config)?;
+    let season_id = open_season_id(program_id, season_account)?;
+
+    // Borrow the account data mutably for updates
+    let mut score_data = score_account.try_borrow_mut_data()?;
+    let mut state = load_score(&score_data, player)?;
+
+    // Reject replayed or out-of-order signed submissions
+    if let Some(nonce) = nonce {
+        if nonce != state.nonce {
+            msg!("Invalid nonce: expected {}, found {}", state.nonce, nonce);
+            return Err(GarbageGrabError::InvalidNonce.into());
+        }
+        state.nonce = state.nonce.checked_add(1).ok_or(ProgramError::ArithmeticOverflow)?;
+    }
+
+    // Scores from a previous season start over from zero
+    let stored_score = state.score;
+    state.roll_season(season_id);
+
+    let now = check_update_rate(&mut state, config)?;
+
+    // Cap how much a single update may add
+    if increment > config.max_increment {
+        msg!("Increment too large: {} (max {})", increment, config.max_increment);
+        return Err(GarbageGrabError::IncrementTooLarge.into());
+    }
+
+    // A consumed multiplier item stacks on any NFT boost and is spent by this update
+    let boost_bps = match std::mem::take(&mut state.item_boost_bps) {
+        0 => boost_bps,
+        item_bps => boost_bps.saturating_mul(u64::from(item_bps)) / MAX_BPS,
+    };
+
+    // Boost the increment by the player's daily streak and any NFT or item boost
+    let multiplier_bps = state.streak_multiplier_bps(now);
+    let boosted = u64::try_from(
+        increment as u128 * multiplier_bps as u128 * boost_bps as u128 / (MAX_BPS as u128 * MAX_BPS as u128),
+    )
+    .map_err(|_| GarbageGrabError::ScoreOverflow)?;
+    if boosted != increment {
+        msg!(
+            "Increment boosted: {} -> {} ({} day streak, {} bps boost)",
+            increment,
+            boosted,
+            state.streak,
+            boost_bps
+        );
+    }
+
+    // Read the current score from the account data
+    let current_score = state.score;
+
+    // Safely add increment to current score, checking for overflow
+    let new_score = current_score
+        .checked_add(boosted)
+        .ok_or(GarbageGrabError::ScoreOverflow)?;
+
+    // Check the resulting score stays within the maximum allowed value
+    if new_score > config.max_score {
+        msg!("Score too large: {} (max {})", new_score, config.max_score);
+        return Err(GarbageGrabError::ScoreTooLarge.into());
+    }
+
+    // Update the score in the account data, tracking a new best along the way
+    state.record_score(new_score, now);
+    state.last_update_ts = now;
+    state.pack(&mut score_data)?;
+    emit_score_change(&state, ScoreChange::Updated, stored_score, new_score, now);
+    msg!("Score updated for player {}: {} -> {}", player, current_score, new_score);
+    Ok(boosted)
+}
+
+// Hold a score change to the configured cooldown and throttle, and decay the
+// score of an inactive player before it lands. Returns the current timestamp.
+fn check_update_rate(state: &mut ScoreAccount, config: &Config) -> Result<i64, ProgramError> {
+    // Rate-limit updates to the configured cooldown
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    let elapsed = now.saturating_sub(state.last_update_ts);
+    if elapsed < i64::from(config.update_cooldown_secs) {
+        msg!("Update too soon: {}s since the last one (cooldown {}s)", elapsed, config.update_cooldown_secs);
+        return Err(GarbageGrabError::UpdateTooSoon.into());
+    }
+
+    // Cap how many updates a player may land per throttle window of slots
+    if !state.throttle(clock.slot, config.throttle_window_slots, config.max_updates_per_window) {
+        msg!(
+            "Too many updates: {} already in this {}-slot window",
+            state.window_updates,
+            config.throttle_window_slots
+        );
+        return Err(GarbageGrabError::TooManyUpdates.into());
+    }
+
+    // Inactive players lose part of their score before the change lands
+    let undecayed = state.decay(now, config.decay_window_secs, config.decay_bps);
+    if state.score != undecayed {
+        msg!("Score decayed after {}s without updates: {} -> {}", elapsed, undecayed, state.score);
+    }
+    Ok(now)
+}
+
+// Log a ScoreUpdated event for a change to this score account
+fn emit_score_change(state: &ScoreAccount, change: ScoreChange, old: u64, new: u64, ts: i64) {
+    ScoreUpdated {
+        player: state.player,
+        game_mode: state.game_mode,
+        change,
+        old,
+        new,
+        ts,
+    }
+    .emit();
+}
+
+fn process_reset_score(program_id: &Pubkey, accounts: &[AccountInfo], game_mode: GameMode) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let score_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+
+    check_player_score(program_id, score_account, player, game_mode)?;
+    check_not_paused(&load_config(program_id, config_account)?)?;
+
+    let mut score_data = score_account.try_borrow_mut_data()?;
+    let mut state = load_score(&score_data, player.key)?;
+
+    let old_score = state.score;
+    state.score = 0;
+    state.pack(&mut score_data)?;
+    emit_score_change(&state, ScoreChange::Reset, old_score, 0, Clock::get()?.unix_timestamp);
+    msg!("Score reset for player {}: {} -> 0", player.key, old_score);
+    Ok(())
+}
+
+fn process_close_score_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    game_mode: GameMode,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let score_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+
+    check_player_score(program_id, score_account, player, game_mode)?;
+    let state = load_score(&score_account.try_borrow_data()?, player.key)?;
+
+    // Move every lamport back to the player; the runtime reclaims the empty account
+    let reclaimed = score_account.lamports();
+    **player.try_borrow_mut_lamports()? = player
+        .lamports()
+        .checked_add(reclaimed)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **score_account.try_borrow_mut_lamports()? = 0;
+
+    // Zero the data so the account reads as uninitialized for the rest of the transaction
+    score_account.try_borrow_mut_data()?.fill(0);
+    emit_score_change(&state, ScoreChange::Closed, state.score, 0, Clock::get()?.unix_timestamp);
+    msg!("Score account closed for player {}: {} lamports reclaimed", player.key, reclaimed);
+    Ok(())
+}
+
+fn process_submit_run(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    game_mode: GameMode,
+    run_score: u64,
+    run_hash: [u8; 32],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let score_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let season_account = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let ban_list_account = next_account_info(accounts_iter)?;
+
+    check_player_score(program_id, score_account, player, game_mode)?;
+    let season_id = open_season_id(program_id, season_account)?;
+    let config = load_config(program_id, config_account)?;
+    check_not_paused(&config)?;
+    check_attestation_not_required(&config)?;
+    let stats_account = next_global_stats(program_id, accounts_iter)?;
+
+    let (previous_best, best) = credit_run(program_id, score_account, player.key, ban_list_account, season_id, &config, run_score, Some(&run_hash))?;
+    grant_run_xp(program_id, accounts_iter, player.key, run_score)?;
+    if let Some(history_account) = accounts_iter.next() {
+        record_run(program_id, history_account, player.key, game_mode, run_score)?;
+    }
+    msg!("Run submitted for player {}: {} (best {} -> {})", player.key, run_score, previous_best, best);
+    record_global_stats(program_id, stats_account, |stats| {
+        stats.record_run(run_score);
+        stats.record_best(previous_best, best);
+    })
+}
+
+// Grant the XP a run scoring `score` earns to `player`'s profile, if it is the
+// next of the trailing accounts
+fn grant_run_xp(
+    program_id: &Pubkey,
+    accounts_iter: &mut std::slice::Iter<AccountInfo>,
+    player: &Pubkey,
+    score: u64,
+) -> ProgramResult {
+    let Some(profile_account) = accounts_iter.as_slice().first() else {
+        return Ok(());
+    };
+    if profile_account.owner != program_id || !profile_account.try_borrow_data()?.starts_with(&PlayerProfile::DISCRIMINATOR) {
+        return Ok(());
+    }
+    accounts_iter.next();
+    let mut profile = load_profile(program_id, profile_account, player)?;
+    let xp = profile.cap_daily_xp(run_xp(score), Clock::get()?.unix_timestamp);
+    if profile.add_xp(xp) {
+        msg!("Player {} reached level {}", player, profile.level);
+    }
+    profile.pack(&mut profile_account.try_borrow_mut_data()?)?;
+    msg!("Player {} earned {} XP ({} total)", player, xp, profile.xp);
+    Ok(())
+}
+
+// The global stats account, if it is the next of the trailing accounts. It only
+// exists once the admin has created it, so callers leave it out until then.
+fn next_global_stats<'a, 'b>(
+    program_id: &Pubkey,
+    accounts_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+) -> Result<Option<&'a AccountInfo<'b>>, ProgramError> {
+    let Some(stats_account) = accounts_iter.as_slice().first() else {
+        return Ok(None);
+    };
+    if stats_account.owner != program_id || !stats_account.try_borrow_data()?.starts_with(&GlobalStats::DISCRIMINATOR) {
+        return Ok(None);
+    }
+    Ok(accounts_iter.next())
+}
+
+// Add to the global stats, if they were passed
+fn record_global_stats(program_id: &Pubkey, stats_account: Option<&AccountInfo>, record: impl FnOnce(&mut GlobalStats)) -> ProgramResult {
+    let Some(stats_account) = stats_account else {
+        return Ok(());
+    };
+    let mut stats = GlobalStats::unpack(&stats_account.try_borrow_data()?)?;
+    if GlobalStatsPda::create(program_id, stats.bump).ok() != Some(*stats_account.key) {
+        msg!("Invalid global stats PDA: {}", stats_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    record(&mut stats);
+    stats.pack(&mut stats_account.try_borrow_mut_data()?)
+}
+
+// Record `run_score` as the result of a finished run, in the current season,
+// turning it away if the player is banned or `run_hash` matches a recent run.
+// Returns the best score before and after.
+#[allow(clippy::too_many_arguments)]
+fn credit_run(program_id: &Pubkey, score_account: &AccountInfo, player: &Pubkey, ban_list_account: &AccountInfo, season_id: u64, config: &Config, run_score: u64, run_hash: Option<&[u8; 32]>) -> Result<(u64, u64), ProgramError> {
+    check_not_banned(program_id, ban_list_account, player)?;
+    let mut score_data = score_account.try_borrow_mut_data()?;
+    let mut state = load_score(&score_data, player)?;
+    let stored_score = state.score;
+    state.roll_season(season_id);
+    let now = check_update_rate(&mut state, config)?;
+
+    // A run earns at most what a single update may add
+    if run_score > config.max_increment {
+        msg!("Run score too large: {} (max {})", run_score, config.max_increment);
+        return Err(GarbageGrabError::IncrementTooLarge.into());
+    }
+    if run_score > config.max_score {
+        msg!("Score too large: {}", run_score);
+        return Err(GarbageGrabError::ScoreTooLarge.into());
+    }
+    if let Some(run_hash) = run_hash {
+        if !state.record_run_hash(&Hash::new_from_array(*run_hash)) {
+            msg!("Run {:?} was already submitted", run_hash);
+            return Err(GarbageGrabError::DuplicateRun.into());
+        }
+    }
+
+    let previous_best = state.best_score;
+    state.record_score(run_score, now);
+    state.last_update_ts = now;
+    state.end_run();
+    state.pack(&mut score_data)?;
+    emit_score_change(&state, ScoreChange::Updated, stored_score, run_score, now);
+    Ok((previous_best, state.best_score))
+}
+
+// Check the passed account is the game session PDA for `player` and `seed`.
+// Returns the bump seed.
+fn check_game_session_pda(program_id: &Pubkey, session_account: &AccountInfo, player: &Pubkey, seed: &[u8; 32]) -> Result<u8, ProgramError> {
+    let (pda, bump) = GameSessionPda::find(program_id, player, seed);
+    if pda != *session_account.key {
+        msg!("Invalid game session PDA: expected {}, found {}", pda, session_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+fn process_start_session(program_id: &Pubkey, accounts: &[AccountInfo], game_mode: GameMode, seed: [u8; 32]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let session_account = next_account_info(accounts_iter)?;
+    let score_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let game_server = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_player_score(program_id, score_account, player, game_mode)?;
+    load_score(&score_account.try_borrow_data()?, player.key)?;
+    let config = load_config_as_game_server(program_id, config_account, game_server)?;
+    check_not_paused(&config)?;
+    let bump = check_game_session_pda(program_id, session_account, player.key, &seed)?;
+    check_system_program(system_program)?;
+    if !session_account.data_is_empty() {
+        msg!("Game session already started with this seed");
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+
+    let space = GameSession::LEN;
+    create_pda_account(player, session_account, system_program, space, program_id, &[GameSessionPda::SEED, player.key.as_ref(), &seed, &[bump]])?;
+    let clock = Clock::get()?;
+    let session = GameSession {
+        is_initialized: true,
+        player: *player.key,
+        game_mode,
+        seed,
+        start_slot: clock.slot,
+        start_ts: clock.unix_timestamp,
+    };
+    session.pack(&mut session_account.try_borrow_mut_data()?)?;
+    msg!("Game session for player {} started at slot {}", player.key, clock.slot);
+    Ok(())
+}
+
+fn process_end_session(program_id: &Pubkey, accounts: &[AccountInfo], score: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let session_account = next_account_info(accounts_iter)?;
+    let score_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let game_server = next_account_info(accounts_iter)?;
+    let season_account = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let ban_list_account = next_account_info(accounts_iter)?;
+
+    check_signer(player)?;
+    check_program_owned(program_id, session_account)?;
+    let session = GameSession::unpack(&session_account.try_borrow_data()?)?;
+    if !session.is_initialized() || session.player != *player.key {
+        msg!("Game session does not belong to {}", player.key);
+        return Err(GarbageGrabError::Unauthorized.into());
+    }
+    check_game_session_pda(program_id, session_account, player.key, &session.seed)?;
+    check_player_score(program_id, score_account, player, session.game_mode)?;
+    let config = load_config_as_game_server(program_id, config_account, game_server)?;
+    check_not_paused(&config)?;
+    let season_id = open_season_id(program_id, season_account)?;
+    let stats_account = next_global_stats(program_id, accounts_iter)?;
+
+    let clock = Clock::get()?;
+    let slot = clock.slot;
+    let expired = session.is_expired(slot, config.session_max_slots);
+    if expired {
+        msg!("Game session started at slot {} expired; nothing credited", session.start_slot);
+    } else {
+        if !session.is_plausible(score, slot, config.max_score_per_slot) {
+            msg!(
+                "Score {} in {} slots exceeds {} per slot",
+                score,
+                slot.saturating_sub(session.start_slot),
+                config.max_score_per_slot
+            );
+            return Err(GarbageGrabError::ImplausibleScore.into());
+        }
+        if !session.is_plausible_rate(score, clock.unix_timestamp, config.max_score_per_second) {
+            msg!(
+                "Score {} in {} seconds exceeds {} per second",
+                score,
+                clock.unix_timestamp.saturating_sub(session.start_ts).max(0),
+                config.max_score_per_second
+            );
+            return Err(GarbageGrabError::ImplausibleScore.into());
+        }
+        let (previous_best, best) = credit_run(program_id, score_account, player.key, ban_list_account, season_id, &config, score, None)?;
+        msg!("Game session for player {} ended with {} (best {} -> {})", player.key, score, previous_best, best);
+        grant_run_xp(program_id, accounts_iter, player.key, score)?;
+        record_global_stats(program_id, stats_account, |stats| {
+            stats.record_run(score);
+            stats.record_best(previous_best, best);
+        })?;
+    }
+
+    // Move every lamport back to the player; the runtime reclaims the empty account
+    let reclaimed = session_account.lamports();
+    **player.try_borrow_mut_lamports()? = player
+        .lamports()
+        .checked_add(reclaimed)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **session_account.try_borrow_mut_lamports()? = 0;
+    session_account.try_borrow_mut_data()?.fill(0);
+    Ok(())
+}
+
+// Check the passed account is the global leaderboard PDA. Returns the bump seed.
+fn check_leaderboard_pda(program_id: &Pubkey, leaderboard_account: &AccountInfo) -> Result<u8, ProgramError> {
+    let (pda, bump) = LeaderboardPda::find(program_id);
+    if pda != *leaderboard_account.key {
+        msg!("Invalid leaderboard PDA: expected {}, found {}", pda, leaderboard_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+fn process_initialize_leaderboard(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let leaderboard_account = next_account_info(accounts_iter)?;
+    let payer = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_signer(payer)?;
+    let bump = check_leaderboard_pda(program_id, leaderboard_account)?;
+    check_system_program(system_program)?;
+    if !leaderboard_account.data_is_empty() {
+        msg!("Leaderboard already initialized");
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+
+    let space = Leaderboard::LEN;
+    create_pda_account(payer, leaderboard_account, system_program, space, program_id, &[LeaderboardPda::SEED, &[bump]])?;
+
+    Leaderboard::init(&mut leaderboard_account.try_borrow_mut_data()?)?;
+    msg!("Leaderboard initialized");
+    Ok(())
+}
+
+fn process_submit_to_leaderboard(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    game_mode: GameMode,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let leaderboard_account = next_account_info(accounts_iter)?;
+    let score_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let ban_list_account = next_account_info(accounts_iter)?;
+    let next_page = accounts_iter.next();
+
+    check_signer(player)?;
+    check_not_paused(&load_config(program_id, config_account)?)?;
+    check_not_banned(program_id, ban_list_account, player.key)?;
+    check_ranked_mode(game_mode)?;
+    check_leaderboard_pda(program_id, leaderboard_account)?;
+    check_score_pda(program_id, score_account, player.key, game_mode)?;
+    check_program_owned(program_id, leaderboard_account)?;
+    check_program_owned(program_id, score_account)?;
+
+    let best_score = use_ranked_run(score_account, player.key)?;
+    let mut board_data = leaderboard_account.try_borrow_mut_data()?;
+    let board = load_leaderboard_mut(&mut board_data)?;
+    let (rank, displaced) = board.submit_displacing(*player.key, best_score);
+    match rank {
+        Some(rank) => msg!("Player {} ranked #{} with {}", player.key, rank + 1, best_score),
+        None => msg!("Score {} did not place on the leaderboard", best_score),
+    }
+    demote_entry(program_id, next_page, 1, displaced)
+}
+
+// Only ranked mode scores compete on the global and weekly leaderboards
+fn check_ranked_mode(game_mode: GameMode) -> ProgramResult {
+    if game_mode != RANKED_GAME_MODE {
+        msg!("{:?} is not ranked on the leaderboards", game_mode);
+        return Err(GarbageGrabError::UnrankedGameMode.into());
+    }
+    Ok(())
+}
+
+// The submitted value always comes from the player's own best score, and each
+// submission uses up the ranked run the player paid for. Returns the best score.
+fn use_ranked_run(score_account: &AccountInfo, player: &Pubkey) -> Result<u64, ProgramError> {
+    let mut score_data = score_account.try_borrow_mut_data()?;
+    let mut state = load_score(&score_data, player)?;
+    if !state.ranked_run_open {
+        msg!("Start a ranked run before submitting to the leaderboard");
+        return Err(GarbageGrabError::NoRankedRun.into());
+    }
+    state.ranked_run_open = false;
+    state.pack(&mut score_data)?;
+    Ok(state.best_score)
+}
+
+// View an initialized leaderboard or leaderboard page in place
+fn load_leaderboard_mut(data: &mut [u8]) -> Result<&mut Leaderboard, ProgramError> {
+    let board = Leaderboard::load_mut(data)?;
+    if !board.is_initialized() {
+        msg!("Leaderboard not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+    Ok(board)
+}
+
+// Check the passed account is leaderboard page `page`: the global leaderboard for
+// page 0 and one of the pages below it otherwise. Returns the bump seed.
+fn check_leaderboard_page_pda(program_id: &Pubkey, page_account: &AccountInfo, page: u8) -> Result<u8, ProgramError> {
+    if page >= LEADERBOARD_PAGES {
+        msg!("Leaderboard page {} is out of range, the last is {}", page, LEADERBOARD_PAGES - 1);
+        return Err(GarbageGrabError::InvalidLeaderboardPage.into());
+    }
+    if page == 0 {
+        return check_leaderboard_pda(program_id, page_account);
+    }
+    let (pda, bump) = LeaderboardPda::find_page(program_id, page);
+    if pda != *page_account.key {
+        msg!("Invalid leaderboard page {} PDA: expected {}, found {}", page, pda, page_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Move the entry a full page dropped onto `page`, the next one down, when the
+// caller passed it and it exists. Whatever that in turn displaces falls off the
+// rankings.
+fn demote_entry(program_id: &Pubkey, next_page: Option<&AccountInfo>, page: u8, displaced: Option<LeaderboardEntry>) -> ProgramResult {
+    let Some(next_page) = next_page else {
+        return Ok(());
+    };
+    check_leaderboard_page_pda(program_id, next_page, page)?;
+    if next_page.data_is_empty() {
+        return Ok(());
+    }
+    check_program_owned(program_id, next_page)?;
+    if let Some(entry) = displaced {
+        let mut page_data = next_page.try_borrow_mut_data()?;
+        if load_leaderboard_mut(&mut page_data)?.submit(entry.player, entry.score()).is_some() {
+            msg!("Player {} moved down to leaderboard page {}", entry.player, page);
+        }
+    }
+    Ok(())
+}
+
+fn process_initialize_leaderboard_page(program_id: &Pubkey, accounts: &[AccountInfo], page: u8) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let page_account = next_account_info(accounts_iter)?;
+    let payer = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_signer(payer)?;
+    if page == 0 {
+        msg!("Page 0 is the global leaderboard, created by InitializeLeaderboard");
+        return Err(GarbageGrabError::InvalidLeaderboardPage.into());
+    }
+    let bump = check_leaderboard_page_pda(program_id, page_account, page)?;
+    check_system_program(system_program)?;
+    if !page_account.data_is_empty() {
+        msg!("Leaderboard page {} already initialized", page);
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+
+    let space = Leaderboard::LEN;
+    create_pda_account(payer, page_account, system_program, space, program_id, &[LeaderboardPda::SEED, &[page], &[bump]])?;
+
+    Leaderboard::init(&mut page_account.try_borrow_mut_data()?)?;
+    msg!("Leaderboard page {} initialized", page);
+    Ok(())
+}
+
+fn process_submit_to_leaderboard_page(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    game_mode: GameMode,
+    page: u8,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let page_account = next_account_info(accounts_iter)?;
+    let page_above = next_account_info(accounts_iter)?;
+    let score_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let ban_list_account = next_account_info(accounts_iter)?;
+    let next_page = accounts_iter.next();
+
+    check_signer(player)?;
+    check_not_paused(&load_config(program_id, config_account)?)?;
+    check_not_banned(program_id, ban_list_account, player.key)?;
+    if page == 0 {
+        msg!("Submit to the global leaderboard with SubmitToLeaderboard");
+        return Err(GarbageGrabError::InvalidLeaderboardPage.into());
+    }
+    check_ranked_mode(game_mode)?;
+    check_leaderboard_page_pda(program_id, page_account, page)?;
+    check_leaderboard_page_pda(program_id, page_above, page - 1)?;
+    check_score_pda(program_id, score_account, player.key, game_mode)?;
+    check_program_owned(program_id, page_account)?;
+    check_program_owned(program_id, page_above)?;
+    check_program_owned(program_id, score_account)?;
+
+    let best_score = use_ranked_run(score_account, player.key)?;
+
+    // A score belongs on the highest page that would take it
+    {
+        let above_data = page_above.try_borrow_data()?;
+        let above = Leaderboard::load(&above_data)?;
+        let ranks_above = !above.is_full() || above.last().is_some_and(|last| last.score() < best_score);
+        if !above.is_initialized() || ranks_above || above.contains(player.key) {
+            msg!("Score {} ranks on leaderboard page {} or higher", best_score, page - 1);
+            return Err(GarbageGrabError::WrongLeaderboardPage.into());
+        }
+    }
+
+    let mut page_data = page_account.try_borrow_mut_data()?;
+    let board = load_leaderboard_mut(&mut page_data)?;
+    let (rank, displaced) = board.submit_displacing(*player.key, best_score);
+    match rank {
+        Some(rank) => msg!("Player {} ranked #{} on page {} with {}", player.key, rank + 1, page, best_score),
+        None => msg!("Score {} did not place on leaderboard page {}", best_score, page),
+    }
+    demote_entry(program_id, next_page, page + 1, displaced)
+}
+
+// Restore the order between two adjacent leaderboard pages
+fn process_rebalance_leaderboard_pages(program_id: &Pubkey, accounts: &[AccountInfo], page: u8) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let page_above = next_account_info(accounts_iter)?;
+    let page_account = next_account_info(accounts_iter)?;
+
+    if page == 0 {
+        msg!("The global leaderboard has no page above it");
+        return Err(GarbageGrabError::InvalidLeaderboardPage.into());
+    }
+    check_leaderboard_page_pda(program_id, page_above, page - 1)?;
+    check_leaderboard_page_pda(program_id, page_account, page)?;
+    check_program_owned(program_id, page_above)?;
+    check_program_owned(program_id, page_account)?;
+
+    let mut above_data = page_above.try_borrow_mut_data()?;
+    let mut page_data = page_account.try_borrow_mut_data()?;
+    let taken = load_leaderboard_mut(&mut above_data)?.rebalance(load_leaderboard_mut(&mut page_data)?);
+    msg!("Rebalanced leaderboard pages {} and {}, taking {} entries up", page - 1, page, taken);
+    Ok(())
+}
+
+// Check the passed account is the weekly leaderboard PDA. Returns the bump seed.
+fn check_weekly_leaderboard_pda(program_id: &Pubkey, weekly_account: &AccountInfo) -> Result<u8, ProgramError> {
+    let (pda, bump) = WeeklyLeaderboardPda::find(program_id);
+    if pda != *weekly_account.key {
+        msg!("Invalid weekly leaderboard PDA: expected {}, found {}", pda, weekly_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+fn process_initialize_weekly_leaderboard(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let weekly_account = next_account_info(accounts_iter)?;
+    let payer = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_signer(payer)?;
+    let bump = check_weekly_leaderboard_pda(program_id, weekly_account)?;
+    check_system_program(system_program)?;
+    if !weekly_account.data_is_empty() {
+        msg!("Weekly leaderboard already initialized");
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+
+    let space = WeeklyLeaderboard::LEN;
+    create_pda_account(payer, weekly_account, system_program, space, program_id, &[WeeklyLeaderboardPda::SEED, &[bump]])?;
+
+    let week = week_of(Clock::get()?.unix_timestamp);
+    WeeklyLeaderboard::init(&mut weekly_account.try_borrow_mut_data()?, week)?;
+    msg!("Weekly leaderboard initialized for week {}", week);
+    Ok(())
+}
+
+fn process_submit_to_weekly_leaderboard(program_id: &Pubkey, accounts: &[AccountInfo], game_mode: GameMode) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let weekly_account = next_account_info(accounts_iter)?;
+    let score_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let ban_list_account = next_account_info(accounts_iter)?;
+
+    check_signer(player)?;
+    check_not_paused(&load_config(program_id, config_account)?)?;
+    check_not_banned(program_id, ban_list_account, player.key)?;
+    check_ranked_mode(game_mode)?;
+    check_weekly_leaderboard_pda(program_id, weekly_account)?;
+    check_score_pda(program_id, score_account, player.key, game_mode)?;
+    check_program_owned(program_id, weekly_account)?;
+    check_program_owned(program_id, score_account)?;
+
+    let state = load_score(&score_account.try_borrow_data()?, player.key)?;
+    let mut weekly_data = weekly_account.try_borrow_mut_data()?;
+    let weekly = WeeklyLeaderboard::load_mut(&mut weekly_data)?;
+    if !weekly.is_initialized() {
+        msg!("Weekly leaderboard not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+
+    // Last week's entries go the first time anyone submits in a new week
+    let week = week_of(Clock::get()?.unix_timestamp);
+    let previous_week = weekly.week();
+    if weekly.roll_week(week) {
+        msg!("Weekly leaderboard rolled over from week {} to {}", previous_week, week);
+    }
+    if state.week != week || state.week_best_score == 0 {
+        msg!("Player {} has recorded no score in week {}", player.key, week);
+        return Err(GarbageGrabError::NoWeeklyScore.into());
+    }
+
+    match weekly.board.submit(*player.key, state.week_best_score) {
+        Some(rank) => msg!("Player {} ranked #{} in week {} with {}", player.key, rank + 1, week, state.week_best_score),
+        None => msg!("Score {} did not place on the weekly leaderboard", state.week_best_score),
+    }
+    Ok(())
+}
+
+fn process_start_season(program_id: &Pubkey, accounts: &[AccountInfo], end_ts: i64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let season_account = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let prize_pool = next_account_info(accounts_iter)?;
+
+    load_config_as_admin(program_id, config_account, admin)?;
+    let bump = check_season_pda(program_id, season_account)?;
+    check_system_program(system_program)?;
+    check_prize_pool_pda(program_id, prize_pool)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    if end_ts <= now {
+        msg!("Season end {} is not after the current time {}", end_ts, now);
+        return Err(GarbageGrabError::InvalidSeasonWindow.into());
+    }
+
+    // The first season creates the account
+    let mut season = if season_account.data_is_empty() {
+        let space = Season::LEN;
+        create_pda_account(admin, season_account, system_program, space, program_id, &[SeasonPda::SEED, &[bump]])?;
+        Season {
+            is_initialized: true,
+            ..Season::default()
+        }
+    } else {
+        Season::unpack(&season_account.try_borrow_data()?)?
+    };
+    // Starting over first would carry the old season's pool into the new one. A
+    // pool the program never took over holds no prize money.
+    let pool = if prize_pool.owner == program_id {
+        prize_pool.lamports().saturating_sub(Rent::get()?.minimum_balance(prize_pool.data_len()))
+    } else {
+        0
+    };
+    if season.is_initialized() && !season.prizes_distributed && pool > 0 {
+        msg!("Season {} prizes must be distributed before the next season starts", season.season_id);
+        return Err(GarbageGrabError::PrizesNotDistributed.into());
+    }
+
+    season.season_id = season
+        .season_id
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    season.start_ts = now;
+    season.end_ts = end_ts;
+    season.prizes_distributed = false;
+    season.pack(&mut season_account.try_borrow_mut_data()?)?;
+    msg!("Season {} started: {} -> {}", season.season_id, now, end_ts);
+    Ok(())
+}
+
+// Config values must allow at least one point per update and never let a single update exceed the cap.
+// The prize split may not pay out more than the whole pool.
+fn validate_config(config: &Config) -> ProgramResult {
+    if config.max_score == 0 || config.max_increment == 0 || config.max_increment > config.max_score {
+        msg!("Invalid config: max_score {}, max_increment {}", config.max_score, config.max_increment);
+        return Err(GarbageGrabError::InvalidConfig.into());
+    }
+    let split_total: u64 = config.prize_split_bps.iter().map(|&bps| bps as u64).sum();
+    if split_total > MAX_BPS {
+        msg!("Invalid config: prize split {:?} exceeds {} bps", config.prize_split_bps, MAX_BPS);
+        return Err(GarbageGrabError::InvalidConfig.into());
+    }
+    if config.boost_collection != Pubkey::default() && u64::from(config.boost_multiplier_bps) < MAX_BPS {
+        msg!("Invalid config: boost multiplier {} bps is below 1x", config.boost_multiplier_bps);
+        return Err(GarbageGrabError::InvalidConfig.into());
+    }
+    if u64::from(config.decay_bps) > MAX_BPS {
+        msg!("Invalid config: decay of {} bps exceeds {} bps", config.decay_bps, MAX_BPS);
+        return Err(GarbageGrabError::InvalidConfig.into());
+    }
+    if config.max_updates_per_window > 0 && config.throttle_window_slots == 0 {
+        msg!("Invalid config: update throttle needs a window of at least one slot");
+        return Err(GarbageGrabError::InvalidConfig.into());
+    }
+    if config.ranked_entry_fee_usd_cents > 0 && (config.sol_usd_price_feed == Pubkey::default() || config.max_price_age_secs == 0) {
+        msg!("Invalid config: a USD entry fee needs a price feed and a maximum price age");
+        return Err(GarbageGrabError::InvalidConfig.into());
+    }
+    if u64::from(config.max_price_conf_bps) > MAX_BPS {
+        msg!("Invalid config: price confidence of {} bps exceeds {} bps", config.max_price_conf_bps, MAX_BPS);
+        return Err(GarbageGrabError::InvalidConfig.into());
+    }
+    if u64::from(config.bet_fee_bps) > MAX_BPS {
+        msg!("Invalid config: bet fee of {} bps exceeds {} bps", config.bet_fee_bps, MAX_BPS);
+        return Err(GarbageGrabError::InvalidConfig.into());
+    }
+    if u64::from(config.fee_bps) > MAX_BPS {
+        msg!("Invalid config: house fee of {} bps exceeds {} bps", config.fee_bps, MAX_BPS);
+        return Err(GarbageGrabError::InvalidConfig.into());
+    }
+    if i64::try_from(config.season_length_secs).is_err() {
+        msg!("Invalid config: season length of {}s is too long", config.season_length_secs);
+        return Err(GarbageGrabError::InvalidConfig.into());
+    }
+    for (&amount, &bps) in config.stake_tier_amounts.iter().zip(&config.stake_tier_bps) {
+        if amount > 0 && u64::from(bps) < MAX_BPS {
+            msg!("Invalid config: staking tier of {} tokens has a multiplier of {} bps, below 1x", amount, bps);
+            return Err(GarbageGrabError::InvalidConfig.into());
+        }
+    }
+    for &bps in &config.stake_lock_bps {
+        if bps > 0 && u64::from(bps) < MAX_BPS {
+            msg!("Invalid config: stake lock multiplier of {} bps is below 1x", bps);
+            return Err(GarbageGrabError::InvalidConfig.into());
+        }
+    }
+    if u64::from(config.early_unstake_penalty_bps) > MAX_BPS {
+        msg!("Invalid config: early unstake penalty of {} bps exceeds {} bps", config.early_unstake_penalty_bps, MAX_BPS);
+        return Err(GarbageGrabError::InvalidConfig.into());
+    }
+    if config.high_stakes_min_level > MAX_LEVEL {
+        msg!("Invalid config: high-stakes level {} exceeds the maximum level {}", config.high_stakes_min_level, MAX_LEVEL);
+        return Err(GarbageGrabError::InvalidConfig.into());
+    }
+    if config.closed_beta && config.allowlist_root == [0; 32] {
+        msg!("Invalid config: a closed beta needs an allowlist root");
+        return Err(GarbageGrabError::InvalidConfig.into());
+    }
+    if config.require_attestation && config.game_server == Pubkey::default() {
+        msg!("Invalid config: requiring attestation needs a game server key");
+        return Err(GarbageGrabError::InvalidConfig.into());
+    }
+    if i64::try_from(config.prize_vesting_secs).is_err() || config.prize_vesting_cliff_secs > config.prize_vesting_secs {
+        msg!("Invalid config: a {}s prize vesting cliff does not fit a {}s vesting period", config.prize_vesting_cliff_secs, config.prize_vesting_secs);
+        return Err(GarbageGrabError::InvalidConfig.into());
+    }
+    Ok(())
+}
+
+fn process_initialize_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    max_score: u64,
+    max_increment: u64,
+    update_cooldown_secs: u32,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let config_account = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    let program_data = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_signer(authority)?;
+    let bump = check_config_pda(program_id, config_account)?;
+    check_system_program(system_program)?;
+    if !config_account.data_is_empty() {
+        msg!("Config already initialized");
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+
+    // Only the upgrade authority of this program may create the config
+    let (program_data_key, _) = Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+    if program_data_key != *program_data.key || *program_data.owner != bpf_loader_upgradeable::id() {
+        msg!("Invalid program data account: {}", program_data.key);
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let upgrade_authority = match bincode::deserialize(&program_data.try_borrow_data()?) {
+        Ok(UpgradeableLoaderState::ProgramData { upgrade_authority_address, .. }) => upgrade_authority_address,
+        _ => return Err(ProgramError::InvalidAccountData),
+    };
+    if upgrade_authority != Some(*authority.key) {
+        msg!("Signer {} is not the program upgrade authority", authority.key);
+        return Err(GarbageGrabError::Unauthorized.into());
+    }
+
+    let config = Config {
+        is_initialized: true,
+        admin: *authority.key,
+        max_score,
+        max_increment,
+        paused: false,
+        update_cooldown_secs,
+        game_server: Pubkey::default(),
+        reward_mint: Pubkey::default(),
+        reward_per_tier: 0,
+        ranked_entry_fee: 0,
+        prize_split_bps: Config::DEFAULT_PRIZE_SPLIT_BPS,
+        boost_collection: Pubkey::default(),
+        boost_multiplier_bps: MAX_BPS as u16,
+        trophy_margin: 0,
+        referral_threshold: 0,
+        points_per_referral: 0,
+        pending_admin: Pubkey::default(),
+        decay_window_secs: 0,
+        decay_bps: 0,
+        throttle_window_slots: 0,
+        max_updates_per_window: 0,
+        shop_mint: Pubkey::default(),
+        continue_burn_amount: 0,
+        max_continues_per_run: 0,
+        ranked_entry_fee_usd_cents: 0,
+        sol_usd_price_feed: Pubkey::default(),
+        max_price_age_secs: 0,
+        max_price_conf_bps: 0,
+        season_length_secs: 0,
+        crank_tip_lamports: 0,
+        bump,
+        treasury_withdraw_cap_lamports: 0,
+        treasury_withdraw_epoch: 0,
+        treasury_withdrawn_lamports: 0,
+        max_score_per_slot: 0,
+        session_max_slots: 0,
+        max_score_per_second: 0,
+        bet_fee_bps: 0,
+        fee_bps: 0,
+        prize_vesting_cliff_secs: 0,
+        prize_vesting_secs: 0,
+        stake_tier_amounts: [0; STAKE_TIERS],
+        stake_tier_bps: [0; STAKE_TIERS],
+        unstake_cooldown_secs: 0,
+        stake_lock_bps: [0; 3],
+        early_unstake_penalty_bps: 0,
+        high_stakes_wager: 0,
+        high_stakes_min_level: 0,
+        closed_beta: false,
+        allowlist_root: [0; 32],
+        require_attestation: false,
+    };
+    validate_config(&config)?;
+
+    let space = Config::LEN;
+    create_pda_account(authority, config_account, system_program, space, program_id, &[ConfigPda::SEED, &[bump]])?;
+
+    config.pack(&mut config_account.try_borrow_mut_data()?)?;
+    msg!("Config initialized with admin {}", authority.key);
+    Ok(())
+}
+
+fn process_update_config(program_id: &Pubkey, accounts: &[AccountInfo], update: ConfigUpdate) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let config_account = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+
+    let mut config = load_config_as_admin(program_id, config_account, admin)?;
+    if let Some(max_score) = update.max_score {
+        config.max_score = max_score;
+    }
+    if let Some(max_increment) = update.max_increment {
+        config.max_increment = max_increment;
+    }
+    if let Some(paused) = update.paused {
+        config.paused = paused;
+    }
+    if let Some(update_cooldown_secs) = update.update_cooldown_secs {
+        config.update_cooldown_secs = update_cooldown_secs;
+    }
+    if let Some(game_server) = update.game_server {
+        config.game_server = game_server;
+    }
+    if let Some(reward_mint) = update.reward_mint {
+        config.reward_mint = reward_mint;
+    }
+    if let Some(reward_per_tier) = update.reward_per_tier {
+        config.reward_per_tier = reward_per_tier;
+    }
+    if let Some(ranked_entry_fee) = update.ranked_entry_fee {
+        config.ranked_entry_fee = ranked_entry_fee;
+    }
+    if let Some(prize_split_bps) = update.prize_split_bps {
+        config.prize_split_bps = prize_split_bps;
+    }
+    if let Some(boost_collection) = update.boost_collection {
+        config.boost_collection = boost_collection;
+    }
+    if let Some(boost_multiplier_bps) = update.boost_multiplier_bps {
+        config.boost_multiplier_bps = boost_multiplier_bps;
+    }
+    if let Some(trophy_margin) = update.trophy_margin {
+        config.trophy_margin = trophy_margin;
+    }
+    if let Some(referral_threshold) = update.referral_threshold {
+        config.referral_threshold = referral_threshold;
+    }
+    if let Some(points_per_referral) = update.points_per_referral {
+        config.points_per_referral = points_per_referral;
+    }
+    if let Some(decay_window_secs) = update.decay_window_secs {
+        config.decay_window_secs = decay_window_secs;
+    }
+    if let Some(decay_bps) = update.decay_bps {
+        config.decay_bps = decay_bps;
+    }
+    if let Some(throttle_window_slots) = update.throttle_window_slots {
+        config.throttle_window_slots = throttle_window_slots;
+    }
+    if let Some(max_updates_per_window) = update.max_updates_per_window {
+        config.max_updates_per_window = max_updates_per_window;
+    }
+    if let Some(shop_mint) = update.shop_mint {
+        config.shop_mint = shop_mint;
+    }
+    if let Some(continue_burn_amount) = update.continue_burn_amount {
+        config.continue_burn_amount = continue_burn_amount;
+    }
+    if let Some(max_continues_per_run) = update.max_continues_per_run {
+        config.max_continues_per_run = max_continues_per_run;
+    }
+    if let Some(ranked_entry_fee_usd_cents) = update.ranked_entry_fee_usd_cents {
+        config.ranked_entry_fee_usd_cents = ranked_entry_fee_usd_cents;
+    }
+    if let Some(sol_usd_price_feed) = update.sol_usd_price_feed {
+        config.sol_usd_price_feed = sol_usd_price_feed;
+    }
+    if let Some(max_price_age_secs) = update.max_price_age_secs {
+        config.max_price_age_secs = max_price_age_secs;
+    }
+    if let Some(max_price_conf_bps) = update.max_price_conf_bps {
+        config.max_price_conf_bps = max_price_conf_bps;
+    }
+    if let Some(season_length_secs) = update.season_length_secs {
+        config.season_length_secs = season_length_secs;
+    }
+    if let Some(crank_tip_lamports) = update.crank_tip_lamports {
+        config.crank_tip_lamports = crank_tip_lamports;
+    }
+    if let Some(treasury_withdraw_cap_lamports) = update.treasury_withdraw_cap_lamports {
+        config.treasury_withdraw_cap_lamports = treasury_withdraw_cap_lamports;
+    }
+    if let Some(max_score_per_slot) = update.max_score_per_slot {
+        config.max_score_per_slot = max_score_per_slot;
+    }
+    if let Some(session_max_slots) = update.session_max_slots {
+        config.session_max_slots = session_max_slots;
+    }
+    if let Some(max_score_per_second) = update.max_score_per_second {
+        config.max_score_per_second = max_score_per_second;
+    }
+    if let Some(bet_fee_bps) = update.bet_fee_bps {
+        config.bet_fee_bps = bet_fee_bps;
+    }
+    if let Some(fee_bps) = update.fee_bps {
+        config.fee_bps = fee_bps;
+    }
+    if let Some(prize_vesting_cliff_secs) = update.prize_vesting_cliff_secs {
+        config.prize_vesting_cliff_secs = prize_vesting_cliff_secs;
+    }
+    if let Some(prize_vesting_secs) = update.prize_vesting_secs {
+        config.prize_vesting_secs = prize_vesting_secs;
+    }
+    if let Some(stake_tier_amounts) = update.stake_tier_amounts {
+        config.stake_tier_amounts = stake_tier_amounts;
+    }
+    if let Some(stake_tier_bps) = update.stake_tier_bps {
+        config.stake_tier_bps = stake_tier_bps;
+    }
+    if let Some(unstake_cooldown_secs) = update.unstake_cooldown_secs {
+        config.unstake_cooldown_secs = unstake_cooldown_secs;
+    }
+    if let Some(stake_lock_bps) = update.stake_lock_bps {
+        config.stake_lock_bps = stake_lock_bps;
+    }
+    if let Some(early_unstake_penalty_bps) = update.early_unstake_penalty_bps {
+        config.early_unstake_penalty_bps = early_unstake_penalty_bps;
+    }
+    if let Some(high_stakes_wager) = update.high_stakes_wager {
+        config.high_stakes_wager = high_stakes_wager;
+    }
+    if let Some(high_stakes_min_level) = update.high_stakes_min_level {
+        config.high_stakes_min_level = high_stakes_min_level;
+    }
+    if let Some(closed_beta) = update.closed_beta {
+        config.closed_beta = closed_beta;
+    }
+    if let Some(allowlist_root) = update.allowlist_root {
+        config.allowlist_root = allowlist_root;
+    }
+    if let Some(require_attestation) = update.require_attestation {
+        config.require_attestation = require_attestation;
+    }
+    validate_config(&config)?;
+
+    config.pack(&mut config_account.try_borrow_mut_data()?)?;
+    msg!("Config updated: {:?}", config);
+    Ok(())
+}
+
+fn process_set_paused(program_id: &Pubkey, accounts: &[AccountInfo], paused: bool) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let config_account = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+
+    let mut config = load_config_as_admin(program_id, config_account, admin)?;
+    config.paused = paused;
+    config.pack(&mut config_account.try_borrow_mut_data()?)?;
+    msg!("Program {}", if paused { "paused" } else { "unpaused" });
+    Ok(())
+}
+
+fn process_submit_attested_score(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    game_mode: GameMode,
+    increment: u64,
+    nonce: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let score_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let season_account = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let ban_list_account = next_account_info(accounts_iter)?;
+    let instructions_sysvar = next_account_info(accounts_iter)?;
+
+    check_player_score(program_id, score_account, player, game_mode)?;
+    let config = load_config(program_id, config_account)?;
+    if config.game_server == Pubkey::default() {
+        msg!("No game server key configured");
+        return Err(GarbageGrabError::InvalidAttestation.into());
+    }
+    if *instructions_sysvar.key != sysvar::instructions::id() {
+        msg!("Invalid instructions sysvar: {}", instructions_sysvar.key);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // The game server must have signed exactly this player, mode, increment and nonce
+    let message = attestation::score_attestation_message(player.key, game_mode, increment, nonce);
+    attestation::verify_preceding_ed25519(instructions_sysvar, &config.game_server, &message)?;
+
+    let credited =
+        credit_increment(program_id, score_account, player.key, season_account, ban_list_account, &config, increment, Some(nonce), MAX_BPS)?;
+    credit_guild(program_id, accounts_iter, player.key, credited)
+}
+
+fn process_penalize_score(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    game_mode: GameMode,
+    amount: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let score_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+
+    load_config_as_moderator(program_id, config_account, authority)?;
+    check_score_pda(program_id, score_account, player.key, game_mode)?;
+    check_program_owned(program_id, score_account)?;
+
+    let mut score_data = score_account.try_borrow_mut_data()?;
+    let mut state = load_score(&score_data, player.key)?;
+
+    let old_score = state.score;
+    state.score = old_score
+        .checked_sub(amount)
+        .ok_or(GarbageGrabError::ScoreUnderflow)?;
+    state.pack(&mut score_data)?;
+    emit_score_change(&state, ScoreChange::Updated, old_score, state.score, Clock::get()?.unix_timestamp);
+    msg!(
+        "Score penalized for player {} by {}: {} -> {} (signed by {})",
+        player.key,
+        amount,
+        old_score,
+        state.score,
+        authority.key
+    );
+    Ok(())
+}
+
+fn process_set_score(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    game_mode: GameMode,
+    value: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let score_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+
+    let config = load_config_as_admin(program_id, config_account, admin)?;
+    check_score_pda(program_id, score_account, player.key, game_mode)?;
+    check_program_owned(program_id, score_account)?;
+    if value > config.max_score {
+        msg!("Score too large: {} (max {})", value, config.max_score);
+        return Err(GarbageGrabError::ScoreTooLarge.into());
+    }
+
+    let mut score_data = score_account.try_borrow_mut_data()?;
+    let mut state = load_score(&score_data, player.key)?;
+
+    let old_score = state.score;
+    let now = Clock::get()?.unix_timestamp;
+    state.record_score(value, now);
+    state.pack(&mut score_data)?;
+    emit_score_change(&state, ScoreChange::Updated, old_score, value, now);
+    msg!(
+        "Score overridden for player {} by admin {}: {} -> {}",
+        player.key,
+        admin.key,
+        old_score,
+        value
+    );
+    Ok(())
+}
+
+// Grow the account to `new_len` bytes, with the new bytes zeroed, and have the
+// player top it up so it stays rent exempt at its new size
+fn grow_account<'a>(
+    account: &AccountInfo<'a>,
+    player: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    new_len: usize,
+) -> ProgramResult {
+    account.realloc(new_len, true)?;
+    top_up_rent(account, player, system_program)?;
+    Ok(())
+}
+
+// Create a PDA with `space` bytes owned by `owner`, paid for by `payer` and signed
+// for with `seeds`. Anyone can send lamports to the address beforehand, which would
+// make create_account fail, so allocate and assign it in place and top up whatever
+// it is short of rent exemption.
+fn create_pda_account<'a>(
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    space: usize,
+    owner: &Pubkey,
+    seeds: &[&[u8]],
+) -> ProgramResult {
+    allocate_pda_account(account, system_program, space, owner, seeds)?;
+    top_up_rent(account, payer, system_program)?;
+    Ok(())
+}
+
+// Allocate `space` bytes for a PDA and assign it to `owner`, leaving its funding
+// to the caller
+fn allocate_pda_account<'a>(
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    space: usize,
+    owner: &Pubkey,
+    seeds: &[&[u8]],
+) -> ProgramResult {
+    invoke_signed(
+        &system_instruction::allocate(account.key, space as u64),
+        &[account.clone(), system_program.clone()],
+        &[seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(account.key, owner),
+        &[account.clone(), system_program.clone()],
+        &[seeds],
+    )?;
+    Ok(())
+}
+
+// Have `payer` send the account whatever it is short of being rent exempt at its
+// current size. Returns the lamports sent.
+fn top_up_rent<'a>(
+    account: &AccountInfo<'a>,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> Result<u64, ProgramError> {
+    let required = Rent::get()?.minimum_balance(account.data_len());
+    let shortfall = required.saturating_sub(account.lamports());
+    if shortfall > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, account.key, shortfall),
+            &[payer.clone(), account.clone(), system_program.clone()],
+        )?;
+    }
+    Ok(shortfall)
+}
+
+fn process_migrate_account(program_id: &Pubkey, accounts: &[AccountInfo], game_mode: GameMode) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let score_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_signer(player)?;
+    let bump = check_score_pda(program_id, score_account, player.key, game_mode)?;
+    check_program_owned(program_id, score_account)?;
+    check_system_program(system_program)?;
+
+    // Decode whatever layout is stored; accounts already on the current one are left alone
+    let mut state = {
+        let data = score_account.try_borrow_data()?;
+        if data.is_empty() {
+            msg!("Account not initialized");
+            return Err(GarbageGrabError::NotInitialized.into());
+        }
+        ScoreAccount::unpack_any_version(&data)?
+    };
+    if !state.is_initialized() {
+        msg!("Account not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+    if state.player != *player.key {
+        msg!("Score account belongs to {}, not {}", state.player, player.key);
+        return Err(GarbageGrabError::Unauthorized.into());
+    }
+    let old_version = state.version;
+    if old_version == ScoreAccount::VERSION && score_account.data_len() >= ScoreAccount::LEN {
+        msg!("Score account already at layout version {}", old_version);
+        return Ok(());
+    }
+
+    if score_account.data_len() < ScoreAccount::LEN {
+        grow_account(score_account, player, system_program, ScoreAccount::LEN)?;
+    }
+
+    state.version = ScoreAccount::VERSION;
+    state.bump = bump;
+    state.pack(&mut score_account.try_borrow_mut_data()?)?;
+    msg!(
+        "Score account for player {} migrated from layout version {} to {}",
+        player.key,
+        old_version,
+        ScoreAccount::VERSION
+    );
+    Ok(())
+}
+
+// Derive the reward vault PDA, a token account that is also its own transfer authority
+fn check_vault_pda(program_id: &Pubkey, vault_account: &AccountInfo) -> Result<u8, ProgramError> {
+    let (pda, bump) = VaultPda::find(program_id);
+    if pda != *vault_account.key {
+        msg!("Invalid vault PDA: expected {}, found {}", pda, vault_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Ensure the passed account is the SPL Token program
+fn check_token_program(token_program: &AccountInfo) -> ProgramResult {
+    if *token_program.key != spl_token::id() {
+        msg!("Expected the SPL Token program, found {}", token_program.key);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+// Ensure the passed account is the SPL Token or the Token-2022 program. Reward and
+// shop tokens may live under either.
+fn check_token_interface_program(token_program: &AccountInfo) -> ProgramResult {
+    if spl_token_2022::check_spl_token_program_account(token_program.key).is_err() {
+        msg!("Expected the SPL Token or Token-2022 program, found {}", token_program.key);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+// Ensure the passed account is the Associated Token Account program
+fn check_associated_token_program(associated_token_program: &AccountInfo) -> ProgramResult {
+    if !spl_associated_token_account::check_id(associated_token_program.key) {
+        msg!("Expected the Associated Token Account program, found {}", associated_token_program.key);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+// Check `mint` is the configured `expected` mint and is owned by `token_program`.
+// Returns its decimals, which checked transfers must name.
+fn check_token_mint(mint: &AccountInfo, expected: &Pubkey, token_program: &AccountInfo) -> Result<u8, ProgramError> {
+    if *expected == Pubkey::default() || *mint.key != *expected {
+        msg!("Mint {} does not match the configured {}", mint.key, expected);
+        return Err(GarbageGrabError::InvalidMint.into());
+    }
+    if mint.owner != token_program.key {
+        msg!("Mint {} is not owned by token program {}", mint.key, token_program.key);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let data = mint.try_borrow_data()?;
+    Ok(StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?.base.decimals)
+}
+
+fn process_initialize_vault(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let vault = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let reward_mint = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    let config = load_config_as_admin(program_id, config_account, admin)?;
+    let bump = check_vault_pda(program_id, vault)?;
+    check_token_interface_program(token_program)?;
+    check_system_program(system_program)?;
+    check_token_mint(reward_mint, &config.reward_mint, token_program)?;
+    if !vault.data_is_empty() {
+        msg!("Reward vault already initialized");
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+
+    // Token-2022 mints may need room for account extensions, such as the withheld
+    // amount of a transfer fee mint
+    let space = {
+        let mint_data = reward_mint.try_borrow_data()?;
+        let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+        let extensions = ExtensionType::get_required_init_account_extensions(&mint.get_extension_types()?);
+        ExtensionType::try_calculate_account_len::<spl_token_2022::state::Account>(&extensions)?
+    };
+    create_pda_account(admin, vault, system_program, space, token_program.key, &[VaultPda::SEED, &[bump]])?;
+    // The vault is its own authority so only this program can sign transfers out of it
+    invoke(
+        &spl_token_2022::instruction::initialize_account3(token_program.key, vault.key, reward_mint.key, vault.key)?,
+        &[vault.clone(), reward_mint.clone(), token_program.clone()],
+    )?;
+    msg!("Reward vault {} initialized for mint {}", vault.key, reward_mint.key);
+    Ok(())
+}
+
+fn process_fund_vault(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let vault = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let admin_token_account = next_account_info(accounts_iter)?;
+    let reward_mint = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+
+    let config = load_config_as_admin(program_id, config_account, admin)?;
+    check_vault_pda(program_id, vault)?;
+    check_token_interface_program(token_program)?;
+    let decimals = check_token_mint(reward_mint, &config.reward_mint, token_program)?;
+    if amount == 0 {
+        msg!("Funding amount must be greater than zero");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // The token program rejects the transfer if the admin's account holds a different
+    // mint. Any transfer fee is withheld in the vault, which is credited the rest.
+    invoke_transfer_checked(
+        token_program.key,
+        admin_token_account.clone(),
+        reward_mint.clone(),
+        vault.clone(),
+        admin.clone(),
+        accounts_iter.as_slice(),
+        amount,
+        decimals,
+        &[],
+    )?;
+    msg!("Admin {} deposited {} reward tokens into the vault", admin.key, amount);
+    Ok(())
+}
+
+fn process_claim_rewards(program_id: &Pubkey, accounts: &[AccountInfo], game_mode: GameMode) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let score_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let vault = next_account_info(accounts_iter)?;
+    let player_token_account = next_account_info(accounts_iter)?;
+    let reward_mint = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+
+    check_player_score(program_id, score_account, player, game_mode)?;
+    let config = load_config(program_id, config_account)?;
+    check_not_paused(&config)?;
+    let vault_bump = check_vault_pda(program_id, vault)?;
+    check_token_interface_program(token_program)?;
+    let decimals = check_token_mint(reward_mint, &config.reward_mint, token_program)?;
+    if vault.owner != token_program.key {
+        msg!("Reward vault has not been initialized");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let expected_destination = get_associated_token_address_with_program_id(player.key, &config.reward_mint, token_program.key);
+    if *player_token_account.key != expected_destination {
+        msg!("Rewards are paid to the player's associated token account {}", expected_destination);
+        return Err(GarbageGrabError::InvalidTokenAccount.into());
+    }
+
+    // Record the claim before paying out; the score borrow must end before the CPI
+    let amount = {
+        let mut score_data = score_account.try_borrow_mut_data()?;
+        let mut state = load_score(&score_data, player.key)?;
+        let amount = state.claimable_reward(config.reward_per_tier);
+        if amount == 0 {
+            msg!("Nothing to claim: best score {}, already claimed {}", state.best_score, state.rewards_claimed);
+            return Err(GarbageGrabError::NothingToClaim.into());
+        }
+        state.rewards_claimed = state.rewards_claimed.checked_add(amount).ok_or(ProgramError::ArithmeticOverflow)?;
+        state.pack(&mut score_data)?;
+        amount
+    };
+
+    // A player without a token account for the reward mint gets one in the same
+    // transaction, paying its rent
+    if player_token_account.data_is_empty() {
+        let associated_token_program = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+        check_associated_token_program(associated_token_program)?;
+        check_system_program(system_program)?;
+        invoke(
+            &spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                player.key,
+                player.key,
+                reward_mint.key,
+                token_program.key,
+            ),
+            &[
+                player.clone(),
+                player_token_account.clone(),
+                player.clone(),
+                reward_mint.clone(),
+                system_program.clone(),
+                token_program.clone(),
+                associated_token_program.clone(),
+            ],
+        )?;
+    }
+
+    invoke_transfer_checked(
+        token_program.key,
+        vault.clone(),
+        reward_mint.clone(),
+        player_token_account.clone(),
+        vault.clone(),
+        accounts_iter.as_slice(),
+        amount,
+        decimals,
+        &[&[VaultPda::SEED, &[vault_bump]]],
+    )?;
+    msg!("Player {} claimed {} reward tokens", player.key, amount);
+    Ok(())
+}
+
+// Check the passed account is the stake PDA of `player`. Returns the bump seed.
+fn check_stake_pda(program_id: &Pubkey, stake_account: &AccountInfo, player: &Pubkey) -> Result<u8, ProgramError> {
+    let (pda, bump) = StakePda::find(program_id, player);
+    if pda != *stake_account.key {
+        msg!("Invalid stake PDA: expected {}, found {}", pda, stake_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Check the passed account is the stake vault PDA of `player`. Returns the bump seed.
+fn check_stake_vault_pda(program_id: &Pubkey, stake_vault: &AccountInfo, player: &Pubkey) -> Result<u8, ProgramError> {
+    let (pda, bump) = StakeVaultPda::find(program_id, player);
+    if pda != *stake_vault.key {
+        msg!("Invalid stake vault PDA: expected {}, found {}", pda, stake_vault.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Read `player`'s existing stake
+fn load_stake(program_id: &Pubkey, stake_account: &AccountInfo, player: &Pubkey) -> Result<Stake, ProgramError> {
+    check_stake_pda(program_id, stake_account, player)?;
+    check_program_owned(program_id, stake_account)?;
+    let stake = Stake::unpack(&stake_account.try_borrow_data()?)?;
+    if !stake.is_initialized() {
+        msg!("Stake not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+    Ok(stake)
+}
+
+// Tokens held by a token account of either token program
+fn token_balance(token_account: &AccountInfo) -> Result<u64, ProgramError> {
+    let data = token_account.try_borrow_data()?;
+    Ok(StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)?.base.amount)
+}
+
+// UpdateScore takes the player's stake account ahead of any guild accounts. Returns
+// the multiplier its staked tokens and any running lock earn, consuming the account,
+// or MAX_BPS when the next account is not a stake.
+fn stake_boost_bps(
+    program_id: &Pubkey,
+    accounts_iter: &mut std::slice::Iter<AccountInfo>,
+    player: &Pubkey,
+    config: &Config,
+) -> Result<u64, ProgramError> {
+    let Some(stake_account) = accounts_iter.as_slice().first() else {
+        return Ok(MAX_BPS);
+    };
+    if stake_account.owner != program_id || !stake_account.try_borrow_data()?.starts_with(&Stake::DISCRIMINATOR) {
+        return Ok(MAX_BPS);
+    }
+    accounts_iter.next();
+    let stake = load_stake(program_id, stake_account, player)?;
+    Ok(config.staked_multiplier_bps(&stake, Clock::get()?.unix_timestamp))
+}
+
+fn process_stake(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64, lock: StakeLock) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let stake_account = next_account_info(accounts_iter)?;
+    let stake_vault = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let player_token_account = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let reward_mint = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_signer(player)?;
+    let config = load_config(program_id, config_account)?;
+    check_not_paused(&config)?;
+    check_token_interface_program(token_program)?;
+    check_system_program(system_program)?;
+    let decimals = check_token_mint(reward_mint, &config.reward_mint, token_program)?;
+    if amount == 0 {
+        msg!("Stake must be greater than zero");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let stake_bump = check_stake_pda(program_id, stake_account, player.key)?;
+    let mut stake = if stake_account.data_is_empty() {
+        create_pda_account(player, stake_account, system_program, Stake::LEN, program_id, &[StakePda::SEED, player.key.as_ref(), &[stake_bump]])?;
+        Stake {
+            is_initialized: true,
+            player: *player.key,
+            ..Stake::default()
+        }
+    } else {
+        load_stake(program_id, stake_account, player.key)?
+    };
+    let vault_bump = check_stake_vault_pda(program_id, stake_vault, player.key)?;
+    if stake_vault.data_is_empty() {
+        // Room for any account extensions a Token-2022 mint requires, as for the reward vault
+        let space = {
+            let mint_data = reward_mint.try_borrow_data()?;
+            let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+            let extensions = ExtensionType::get_required_init_account_extensions(&mint.get_extension_types()?);
+            ExtensionType::try_calculate_account_len::<spl_token_2022::state::Account>(&extensions)?
+        };
+        create_pda_account(player, stake_vault, system_program, space, token_program.key, &[StakeVaultPda::SEED, player.key.as_ref(), &[vault_bump]])?;
+        invoke(
+            &spl_token_2022::instruction::initialize_account3(token_program.key, stake_vault.key, reward_mint.key, stake_vault.key)?,
+            &[stake_vault.clone(), reward_mint.clone(), token_program.clone()],
+        )?;
+    } else if stake_vault.owner != token_program.key {
+        msg!("Stake vault {} is not owned by token program {}", stake_vault.key, token_program.key);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Only what reaches the vault counts, net of any Token-2022 transfer fee
+    let before = token_balance(stake_vault)?;
+    invoke_transfer_checked(
+        token_program.key,
+        player_token_account.clone(),
+        reward_mint.clone(),
+        stake_vault.clone(),
+        player.clone(),
+        accounts_iter.as_slice(),
+        amount,
+        decimals,
+        &[],
+    )?;
+    let received = token_balance(stake_vault)?.saturating_sub(before);
+    let now = Clock::get()?.unix_timestamp;
+    stake.amount = stake.amount.checked_add(received).ok_or(ProgramError::ArithmeticOverflow)?;
+    stake.last_stake_ts = now;
+    stake.apply_lock(lock, now);
+    stake.pack(&mut stake_account.try_borrow_mut_data()?)?;
+    msg!("Player {} staked {} tokens, {} in total", player.key, received, stake.amount);
+    if stake.is_lock_active(now) {
+        msg!("Stake locked ({:?}) until {}", stake.lock, stake.lock_end_ts);
+    }
+    Ok(())
+}
+
+fn process_unstake(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let stake_account = next_account_info(accounts_iter)?;
+    let stake_vault = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let player_token_account = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let reward_mint = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+    let treasury = next_account_info(accounts_iter)?;
+    let treasury_token_account = next_account_info(accounts_iter)?;
+
+    check_signer(player)?;
+    let config = load_config(program_id, config_account)?;
+    check_token_interface_program(token_program)?;
+    let decimals = check_token_mint(reward_mint, &config.reward_mint, token_program)?;
+    check_treasury_pda(program_id, treasury)?;
+    let expected_treasury_account = get_associated_token_address_with_program_id(treasury.key, &config.reward_mint, token_program.key);
+    if *treasury_token_account.key != expected_treasury_account {
+        msg!("Early unstake penalties go to the treasury's associated token account {}", expected_treasury_account);
+        return Err(GarbageGrabError::InvalidTokenAccount.into());
+    }
+    let mut stake = load_stake(program_id, stake_account, player.key)?;
+    let vault_bump = check_stake_vault_pda(program_id, stake_vault, player.key)?;
+    if amount == 0 || amount > stake.amount {
+        msg!("Cannot unstake {} of {} staked tokens", amount, stake.amount);
+        return Err(ProgramError::InsufficientFunds);
+    }
+    let now = Clock::get()?.unix_timestamp;
+    if stake.is_locked(now, config.unstake_cooldown_secs) {
+        msg!("Stake is locked until {}, it is now {}", stake.last_stake_ts.saturating_add(i64::from(config.unstake_cooldown_secs)), now);
+        return Err(GarbageGrabError::StakeLocked.into());
+    }
+
+    let penalty = if stake.is_lock_active(now) {
+        config.early_unstake_penalty(amount)
+    } else {
+        0
+    };
+    stake.amount -= amount;
+    stake.pack(&mut stake_account.try_borrow_mut_data()?)?;
+    let vault_seeds: &[&[u8]] = &[StakeVaultPda::SEED, player.key.as_ref(), &[vault_bump]];
+    if penalty > 0 {
+        invoke_transfer_checked(
+            token_program.key,
+            stake_vault.clone(),
+            reward_mint.clone(),
+            treasury_token_account.clone(),
+            stake_vault.clone(),
+            accounts_iter.as_slice(),
+            penalty,
+            decimals,
+            &[vault_seeds],
+        )?;
+        msg!("Unstaked before the lock ends at {}: {} tokens to the treasury", stake.lock_end_ts, penalty);
+    }
+    invoke_transfer_checked(
+        token_program.key,
+        stake_vault.clone(),
+        reward_mint.clone(),
+        player_token_account.clone(),
+        stake_vault.clone(),
+        accounts_iter.as_slice(),
+        amount - penalty,
+        decimals,
+        &[vault_seeds],
+    )?;
+    msg!("Player {} unstaked {} tokens, {} still staked", player.key, amount - penalty, stake.amount);
+    Ok(())
+}
+
+// Check the passed account is the PDA of quest `quest_id`. Returns the bump seed.
+fn check_quest_pda(program_id: &Pubkey, quest_account: &AccountInfo, quest_id: u64) -> Result<u8, ProgramError> {
+    let (pda, bump) = QuestPda::find(program_id, quest_id);
+    if pda != *quest_account.key {
+        msg!("Invalid quest PDA: expected {}, found {}", pda, quest_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Check the passed account is `player`'s progress PDA for quest `quest_id`. Returns the bump seed.
+fn check_quest_progress_pda(
+    program_id: &Pubkey,
+    progress_account: &AccountInfo,
+    quest_id: u64,
+    player: &Pubkey,
+) -> Result<u8, ProgramError> {
+    let (pda, bump) = QuestProgressPda::find(program_id, quest_id, player);
+    if pda != *progress_account.key {
+        msg!("Invalid quest progress PDA: expected {}, found {}", pda, progress_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Read an existing quest
+fn load_quest(program_id: &Pubkey, quest_account: &AccountInfo) -> Result<Quest, ProgramError> {
+    check_program_owned(program_id, quest_account)?;
+    let quest = Quest::unpack(&quest_account.try_borrow_data()?)?;
+    if !quest.is_initialized() {
+        msg!("Quest not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+    check_quest_pda(program_id, quest_account, quest.quest_id)?;
+    Ok(quest)
+}
+
+// Read `player`'s existing progress towards a quest
+fn load_quest_progress(
+    program_id: &Pubkey,
+    progress_account: &AccountInfo,
+    player: &Pubkey,
+) -> Result<QuestProgress, ProgramError> {
+    check_program_owned(program_id, progress_account)?;
+    let progress = QuestProgress::unpack(&progress_account.try_borrow_data()?)?;
+    if !progress.is_initialized() {
+        msg!("Quest progress not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+    check_quest_progress_pda(program_id, progress_account, progress.quest_id, player)?;
+    Ok(progress)
+}
+
+// Score and achievement updates take the player's quest progress accounts next.
+// Counts `amount` of `metric` towards each, consuming accounts for as long as
+// they are quest progress.
+fn record_quest_progress(
+    program_id: &Pubkey,
+    accounts_iter: &mut std::slice::Iter<AccountInfo>,
+    player: &Pubkey,
+    metric: QuestMetric,
+    amount: u64,
+) -> ProgramResult {
+    while let Some(progress_account) = accounts_iter.as_slice().first() {
+        if progress_account.owner != program_id || !progress_account.try_borrow_data()?.starts_with(&QuestProgress::DISCRIMINATOR) {
+            break;
+        }
+        accounts_iter.next();
+        let mut progress = load_quest_progress(program_id, progress_account, player)?;
+        if progress.record(metric, amount) {
+            progress.pack(&mut progress_account.try_borrow_mut_data()?)?;
+            msg!("Quest {} progress: {}", progress.quest_id, progress.progress);
+        }
+    }
+    Ok(())
+}
+
+fn process_create_quest(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    quest_id: u64,
+    metric: QuestMetric,
+    target: u64,
+    reward: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let quest_account = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    load_config_as_admin(program_id, config_account, admin)?;
+    check_system_program(system_program)?;
+    let bump = check_quest_pda(program_id, quest_account, quest_id)?;
+    if target == 0 {
+        msg!("A quest needs a target above zero");
+        return Err(ProgramError::InvalidArgument);
+    }
+    if !quest_account.data_is_empty() {
+        msg!("Quest {} already exists", quest_id);
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+
+    create_pda_account(admin, quest_account, system_program, Quest::LEN, program_id, &[QuestPda::SEED, &quest_id.to_le_bytes(), &[bump]])?;
+    let quest = Quest {
+        is_initialized: true,
+        quest_id,
+        metric,
+        target,
+        reward,
+    };
+    quest.pack(&mut quest_account.try_borrow_mut_data()?)?;
+    msg!("Quest {} created: {} {:?} for {} reward tokens", quest_id, target, metric, reward);
+    Ok(())
+}
+
+fn process_accept_quest(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let progress_account = next_account_info(accounts_iter)?;
+    let quest_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_signer(player)?;
+    check_system_program(system_program)?;
+    let quest = load_quest(program_id, quest_account)?;
+    let bump = check_quest_progress_pda(program_id, progress_account, quest.quest_id, player.key)?;
+    if !progress_account.data_is_empty() {
+        msg!("Player {} already accepted quest {}", player.key, quest.quest_id);
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+
+    create_pda_account(
+        player,
+        progress_account,
+        system_program,
+        QuestProgress::LEN,
+        program_id,
+        &[QuestProgressPda::SEED, &quest.quest_id.to_le_bytes(), player.key.as_ref(), &[bump]],
+    )?;
+    let progress = QuestProgress {
+        is_initialized: true,
+        player: *player.key,
+        quest_id: quest.quest_id,
+        metric: quest.metric,
+        progress: 0,
+        claimed: false,
+    };
+    progress.pack(&mut progress_account.try_borrow_mut_data()?)?;
+    msg!("Player {} accepted quest {}", player.key, quest.quest_id);
+    Ok(())
+}
+
+fn process_claim_quest_reward(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let progress_account = next_account_info(accounts_iter)?;
+    let quest_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let vault = next_account_info(accounts_iter)?;
+    let player_token_account = next_account_info(accounts_iter)?;
+    let reward_mint = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+
+    check_signer(player)?;
+    let config = load_config(program_id, config_account)?;
+    check_not_paused(&config)?;
+    let vault_bump = check_vault_pda(program_id, vault)?;
+    check_token_interface_program(token_program)?;
+    let decimals = check_token_mint(reward_mint, &config.reward_mint, token_program)?;
+    if vault.owner != token_program.key {
+        msg!("Reward vault has not been initialized");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let expected_destination = get_associated_token_address_with_program_id(player.key, &config.reward_mint, token_program.key);
+    if *player_token_account.key != expected_destination {
+        msg!("Rewards are paid to the player's associated token account {}", expected_destination);
+        return Err(GarbageGrabError::InvalidTokenAccount.into());
+    }
+
+    let quest = load_quest(program_id, quest_account)?;
+    let mut progress = load_quest_progress(program_id, progress_account, player.key)?;
+    if progress.quest_id != quest.quest_id {
+        msg!("Progress is for quest {}, not quest {}", progress.quest_id, quest.quest_id);
+        return Err(ProgramError::InvalidArgument);
+    }
+    if progress.claimed {
+        msg!("Player {} already claimed quest {}", player.key, quest.quest_id);
+        return Err(GarbageGrabError::QuestAlreadyClaimed.into());
+    }
+    if progress.progress < quest.target {
+        msg!("Quest {} progress {} has not reached {}", quest.quest_id, progress.progress, quest.target);
+        return Err(GarbageGrabError::QuestIncomplete.into());
+    }
+    // Record the claim before paying out
+    progress.claimed = true;
+    progress.pack(&mut progress_account.try_borrow_mut_data()?)?;
+
+    if quest.reward > 0 {
+        invoke_transfer_checked(
+            token_program.key,
+            vault.clone(),
+            reward_mint.clone(),
+            player_token_account.clone(),
+            vault.clone(),
+            accounts_iter.as_slice(),
+            quest.reward,
+            decimals,
+            &[&[VaultPda::SEED, &[vault_bump]]],
+        )?;
+    }
+    msg!("Player {} completed quest {} for {} reward tokens", player.key, quest.quest_id, quest.reward);
+    Ok(())
+}
+
+// Check the passed account is the daily challenge PDA of UTC day `day`. Returns the bump seed.
+fn check_daily_challenge_pda(program_id: &Pubkey, daily_account: &AccountInfo, day: i64) -> Result<u8, ProgramError> {
+    let (pda, bump) = DailyChallengePda::find(program_id, day);
+    if pda != *daily_account.key {
+        msg!("Invalid daily challenge PDA: expected {}, found {}", pda, daily_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Check the passed account is `player`'s submission PDA for UTC day `day`. Returns the bump seed.
+fn check_daily_submission_pda(
+    program_id: &Pubkey,
+    submission_account: &AccountInfo,
+    day: i64,
+    player: &Pubkey,
+) -> Result<u8, ProgramError> {
+    let (pda, bump) = DailySubmissionPda::find(program_id, day, player);
+    if pda != *submission_account.key {
+        msg!("Invalid daily submission PDA: expected {}, found {}", pda, submission_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+fn process_publish_daily_challenge(program_id: &Pubkey, accounts: &[AccountInfo], seed: [u8; 32], date: i64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let daily_account = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    load_config_as_admin(program_id, config_account, admin)?;
+    check_system_program(system_program)?;
+    let bump = check_daily_challenge_pda(program_id, daily_account, date)?;
+    let today = Clock::get()?.unix_timestamp.div_euclid(SECONDS_PER_DAY);
+    if date < today {
+        msg!("Day {} has already passed; today is day {}", date, today);
+        return Err(GarbageGrabError::NotTodaysChallenge.into());
+    }
+    if !daily_account.data_is_empty() {
+        msg!("Daily challenge for day {} already published", date);
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+
+    create_pda_account(admin, daily_account, system_program, DailyChallenge::LEN, program_id, &[DailyChallengePda::SEED, &date.to_le_bytes(), &[bump]])?;
+    DailyChallenge::init(&mut daily_account.try_borrow_mut_data()?, date, seed)?;
+    msg!("Daily challenge for day {} published", date);
+    Ok(())
+}
+
+fn process_submit_daily_challenge(program_id: &Pubkey, accounts: &[AccountInfo], date: i64, score: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let daily_account = next_account_info(accounts_iter)?;
+    let submission_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let game_server = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let ban_list_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_signer(player)?;
+    let config = load_config_as_game_server(program_id, config_account, game_server)?;
+    check_not_paused(&config)?;
+    check_not_banned(program_id, ban_list_account, player.key)?;
+    check_system_program(system_program)?;
+    check_daily_challenge_pda(program_id, daily_account, date)?;
+    check_program_owned(program_id, daily_account)?;
+    let bump = check_daily_submission_pda(program_id, submission_account, date, player.key)?;
+    let today = Clock::get()?.unix_timestamp.div_euclid(SECONDS_PER_DAY);
+    if date != today {
+        msg!("Daily challenge is for day {}, today is day {}", date, today);
+        return Err(GarbageGrabError::NotTodaysChallenge.into());
+    }
+    if score > config.max_score {
+        msg!("Score too large: {} (max {})", score, config.max_score);
+        return Err(GarbageGrabError::ScoreTooLarge.into());
+    }
+    // The submission account is the once-per-day guard
+    if !submission_account.data_is_empty() {
+        msg!("Player {} already submitted to the challenge of day {}", player.key, date);
+        return Err(GarbageGrabError::DailyChallengeAlreadySubmitted.into());
+    }
+
+    create_pda_account(
+        player,
+        submission_account,
+        system_program,
+        DailySubmission::LEN,
+        program_id,
+        &[DailySubmissionPda::SEED, &date.to_le_bytes(), player.key.as_ref(), &[bump]],
+    )?;
+    let submission = DailySubmission {
+        is_initialized: true,
+        player: *player.key,
+        day: date,
+        score,
+    };
+    submission.pack(&mut submission_account.try_borrow_mut_data()?)?;
+
+    let mut daily_data = daily_account.try_borrow_mut_data()?;
+    let daily = DailyChallenge::load_mut(&mut daily_data)?;
+    match daily.board.submit(*player.key, score) {
+        Some(rank) => msg!("Player {} ranked #{} in the challenge of day {} with {}", player.key, rank + 1, date, score),
+        None => msg!("Score {} did not place on the daily leaderboard", score),
+    }
+    Ok(())
+}
+
+// Check the passed account is the PDA of cosmetic `cosmetic_id`. Returns the bump seed.
+fn check_cosmetic_pda(program_id: &Pubkey, cosmetic_account: &AccountInfo, cosmetic_id: u8) -> Result<u8, ProgramError> {
+    let (pda, bump) = CosmeticPda::find(program_id, cosmetic_id);
+    if pda != *cosmetic_account.key {
+        msg!("Invalid cosmetic PDA: expected {}, found {}", pda, cosmetic_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+fn process_set_cosmetic(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    cosmetic_id: u8,
+    min_level: u16,
+    game_mode: GameMode,
+    achievement: u8,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let cosmetic_account = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    load_config_as_admin(program_id, config_account, admin)?;
+    check_system_program(system_program)?;
+    if cosmetic_id >= COSMETIC_COUNT {
+        msg!("Cosmetic id {} is out of range", cosmetic_id);
+        return Err(ProgramError::InvalidArgument);
+    }
+    if min_level > MAX_LEVEL || (achievement != NO_ACHIEVEMENT && achievement >= ScoreAccount::ACHIEVEMENT_COUNT) {
+        msg!("Cosmetic {} cannot require level {} and achievement {}", cosmetic_id, min_level, achievement);
+        return Err(ProgramError::InvalidArgument);
+    }
+    let bump = check_cosmetic_pda(program_id, cosmetic_account, cosmetic_id)?;
+
+    // The first call creates the cosmetic; later ones change what unlocks it
+    if cosmetic_account.data_is_empty() {
+        create_pda_account(admin, cosmetic_account, system_program, Cosmetic::LEN, program_id, &[CosmeticPda::SEED, &[cosmetic_id], &[bump]])?;
+    } else {
+        check_program_owned(program_id, cosmetic_account)?;
+        Cosmetic::unpack(&cosmetic_account.try_borrow_data()?)?;
+    }
+    let cosmetic = Cosmetic {
+        is_initialized: true,
+        cosmetic_id,
+        min_level,
+        game_mode,
+        achievement,
+    };
+    cosmetic.pack(&mut cosmetic_account.try_borrow_mut_data()?)?;
+    msg!("Cosmetic {} needs level {} and achievement {} ({:?})", cosmetic_id, min_level, achievement, game_mode);
+    Ok(())
+}
+
+fn process_unlock_cosmetic(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let cosmetic_account = next_account_info(accounts_iter)?;
+    let profile_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+
+    check_program_owned(program_id, cosmetic_account)?;
+    let cosmetic = Cosmetic::unpack(&cosmetic_account.try_borrow_data()?)?;
+    if !cosmetic.is_initialized() {
+        msg!("Cosmetic not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+    check_cosmetic_pda(program_id, cosmetic_account, cosmetic.cosmetic_id)?;
+    let mut profile = load_profile(program_id, profile_account, player.key)?;
+    if profile.has_unlock(cosmetic.cosmetic_id) {
+        msg!("Player {} already unlocked cosmetic {}", player.key, cosmetic.cosmetic_id);
+        return Err(GarbageGrabError::CosmeticAlreadyUnlocked.into());
+    }
+
+    // Achievements are read from the player's score account in the cosmetic's game mode
+    let achievements = if cosmetic.needs_achievement() {
+        let score_account = next_account_info(accounts_iter)?;
+        check_score_pda(program_id, score_account, player.key, cosmetic.game_mode)?;
+        check_program_owned(program_id, score_account)?;
+        load_score(&score_account.try_borrow_data()?, player.key)?.achievements
+    } else {
+        0
+    };
+    if !cosmetic.is_earned(profile.level, achievements) {
+        msg!("Player {} at level {} has not earned cosmetic {}", player.key, profile.level, cosmetic.cosmetic_id);
+        return Err(GarbageGrabError::CosmeticLocked.into());
+    }
+
+    profile.unlock(cosmetic.cosmetic_id);
+    profile.pack(&mut profile_account.try_borrow_mut_data()?)?;
+    msg!("Player {} unlocked cosmetic {}", player.key, cosmetic.cosmetic_id);
+    Ok(())
+}
+
+// Check the passed account is the prize pool PDA. Returns the bump seed.
+fn check_prize_pool_pda(program_id: &Pubkey, prize_pool: &AccountInfo) -> Result<u8, ProgramError> {
+    let (pda, bump) = PrizePoolPda::find(program_id);
+    if pda != *prize_pool.key {
+        msg!("Invalid prize pool PDA: expected {}, found {}", pda, prize_pool.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Lamports owed for a ranked run: the fixed lamport fee, or the USD fee converted
+// at the configured Pyth feed's SOL/USD price when one is set
+fn ranked_entry_fee(config: &Config, price_feed: Option<&AccountInfo>) -> Result<u64, ProgramError> {
+    if config.ranked_entry_fee_usd_cents == 0 {
+        return Ok(config.ranked_entry_fee);
+    }
+    let price_feed = price_feed.ok_or(ProgramError::NotEnoughAccountKeys)?;
+    if *price_feed.key != config.sol_usd_price_feed {
+        msg!("Expected price feed {}, found {}", config.sol_usd_price_feed, price_feed.key);
+        return Err(ProgramError::InvalidArgument);
+    }
+    let feed = SolanaPriceAccount::account_info_to_feed(price_feed).map_err(|_| ProgramError::InvalidAccountData)?;
+    let now = Clock::get()?.unix_timestamp;
+    let Some(price) = feed.get_price_no_older_than(now, u64::from(config.max_price_age_secs)) else {
+        msg!("Price feed {} has no price from the last {}s", price_feed.key, config.max_price_age_secs);
+        return Err(GarbageGrabError::StalePrice.into());
+    };
+    // conf / price > max bps, cross-multiplied to stay in integers
+    if price.price <= 0 || price.conf as u128 * MAX_BPS as u128 > price.price as u128 * config.max_price_conf_bps as u128 {
+        msg!("Price {} ± {} is outside the {} bps confidence limit", price.price, price.conf, config.max_price_conf_bps);
+        return Err(GarbageGrabError::PriceTooUncertain.into());
+    }
+    config.usd_fee_lamports(price.price, price.expo).ok_or(ProgramError::ArithmeticOverflow)
+}
+
+fn process_start_ranked_run(program_id: &Pubkey, accounts: &[AccountInfo], game_mode: GameMode) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let score_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let prize_pool = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let fee_vault = next_account_info(accounts_iter)?;
+
+    check_ranked_mode(game_mode)?;
+    check_player_score(program_id, score_account, player, game_mode)?;
+    let config = load_config(program_id, config_account)?;
+    check_not_paused(&config)?;
+    let bump = check_prize_pool_pda(program_id, prize_pool)?;
+    check_system_program(system_program)?;
+    check_fee_vault_pda(program_id, fee_vault)?;
+    let stats_account = next_global_stats(program_id, accounts_iter)?;
+    let entry_fee = ranked_entry_fee(&config, accounts_iter.next())?;
+
+    {
+        let mut score_data = score_account.try_borrow_mut_data()?;
+        let mut state = load_score(&score_data, player.key)?;
+        if state.ranked_run_open {
+            msg!("Player {} already has a ranked run in progress", player.key);
+            return Err(GarbageGrabError::RankedRunInProgress.into());
+        }
+        state.ranked_run_open = true;
+        state.pack(&mut score_data)?;
+    }
+
+    // The pool holds no data, just the lamports paid in above its rent-exempt minimum.
+    // Owning it lets the program pay prizes out by debiting it directly. Anyone can send
+    // lamports to the address beforehand, so the first run tops it up and assigns it
+    // rather than calling create_account.
+    if *prize_pool.owner != *program_id {
+        let shortfall = Rent::get()?.minimum_balance(0).saturating_sub(prize_pool.lamports());
+        if shortfall > 0 {
+            invoke(
+                &system_instruction::transfer(player.key, prize_pool.key, shortfall),
+                &[player.clone(), prize_pool.clone(), system_program.clone()],
+            )?;
+        }
+        invoke_signed(
+            &system_instruction::assign(prize_pool.key, program_id),
+            &[prize_pool.clone(), system_program.clone()],
+            &[&[PrizePoolPda::SEED, &[bump]]],
+        )?;
+    }
+    let fee = config.house_fee(entry_fee);
+    if entry_fee > fee {
+        invoke(
+            &system_instruction::transfer(player.key, prize_pool.key, entry_fee - fee),
+            &[player.clone(), prize_pool.clone(), system_program.clone()],
+        )?;
+    }
+    pay_house_fee(player, fee_vault, system_program, fee)?;
+    msg!("Player {} started a ranked run for {} lamports", player.key, entry_fee);
+    record_global_stats(program_id, stats_account, |stats| stats.record_fee(fee))
+}
+
+fn process_distribute_prizes(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let leaderboard_account = next_account_info(accounts_iter)?;
+    let season_account = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let prize_pool = next_account_info(accounts_iter)?;
+    // Only needed to create the vesting accounts
+    let system_program = if load_config(program_id, config_account)?.prize_vesting_secs > 0 {
+        Some(next_account_info(accounts_iter)?)
+    } else {
+        None
+    };
+
+    distribute_season_prizes(program_id, leaderboard_account, season_account, config_account, prize_pool, system_program, accounts_iter)
+}
+
+// Pay an ended season's prize pool to the top leaderboard places, whose wallets
+// `winners` yields in rank order, and clear the leaderboard. When prizes vest,
+// each wallet is followed by the winner's vesting PDA.
+fn distribute_season_prizes<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    leaderboard_account: &AccountInfo<'b>,
+    season_account: &AccountInfo<'b>,
+    config_account: &AccountInfo<'b>,
+    prize_pool: &AccountInfo<'b>,
+    system_program: Option<&AccountInfo<'b>>,
+    winners: &mut impl Iterator<Item = &'a AccountInfo<'b>>,
+) -> ProgramResult {
+    let config = load_config(program_id, config_account)?;
+    check_leaderboard_pda(program_id, leaderboard_account)?;
+    check_season_pda(program_id, season_account)?;
+    check_prize_pool_pda(program_id, prize_pool)?;
+    check_program_owned(program_id, leaderboard_account)?;
+    check_program_owned(program_id, season_account)?;
+    check_program_owned(program_id, prize_pool)?;
+    if config.prize_vesting_secs > 0 {
+        check_system_program(system_program.ok_or(ProgramError::NotEnoughAccountKeys)?)?;
+    }
+
+    let mut season_data = season_account.try_borrow_mut_data()?;
+    let mut season = Season::unpack(&season_data)?;
+    let now = Clock::get()?.unix_timestamp;
+    if !season.is_initialized() || now < season.end_ts {
+        msg!("Season {} ends at {}, it is now {}", season.season_id, season.end_ts, now);
+        return Err(GarbageGrabError::SeasonNotOver.into());
+    }
+    if season.prizes_distributed {
+        msg!("Season {} prizes already distributed", season.season_id);
+        return Err(GarbageGrabError::PrizesAlreadyDistributed.into());
+    }
+
+    let mut board_data = leaderboard_account.try_borrow_mut_data()?;
+    let board = Leaderboard::load_mut(&mut board_data)?;
+    if !board.is_initialized() {
+        msg!("Leaderboard not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+
+    // Everything above the rent-exempt minimum is prize money
+    let pool = prize_pool.lamports().saturating_sub(Rent::get()?.minimum_balance(prize_pool.data_len()));
+    let shares = config.prize_shares(pool, board.entries().len());
+    for (place, (entry, &share)) in board.entries().iter().zip(&shares).enumerate() {
+        let winner = next_account_info(winners)?;
+        if *winner.key != entry.player {
+            msg!("Place {} belongs to {}, found {}", place + 1, entry.player, winner.key);
+            return Err(ProgramError::InvalidArgument);
+        }
+        if let Some(system_program) = system_program.filter(|_| config.prize_vesting_secs > 0) {
+            let vesting_account = next_account_info(winners)?;
+            vest_prize(program_id, &config, season.season_id, now, prize_pool, winner, vesting_account, system_program, share)?;
+        } else {
+            move_lamports(prize_pool, winner, share)?;
+        }
+        msg!("Place {}: {} won {} lamports", place + 1, winner.key, share);
+    }
+
+    board.clear();
+    season.prizes_distributed = true;
+    season.pack(&mut season_data)?;
+    msg!("Season {} prizes distributed from a pool of {} lamports", season.season_id, pool);
+    Ok(())
+}
+
+// Check the passed account is the PDA of `winner`'s prize from season `season_id`.
+// Returns the bump seed.
+fn check_vesting_pda(program_id: &Pubkey, vesting_account: &AccountInfo, season_id: u64, winner: &Pubkey) -> Result<u8, ProgramError> {
+    let (pda, bump) = VestingPda::find(program_id, season_id, winner);
+    if pda != *vesting_account.key {
+        msg!("Invalid vesting PDA: expected {}, found {}", pda, vesting_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Move a `share` lamport prize out of the prize pool into a new vesting account
+// for `winner`, which pays its rent out of the prize and hands the rest out over
+// the configured schedule. A prize too small to cover the rent is paid outright.
+#[allow(clippy::too_many_arguments)]
+fn vest_prize<'a>(
+    program_id: &Pubkey,
+    config: &Config,
+    season_id: u64,
+    now: i64,
+    prize_pool: &AccountInfo<'a>,
+    winner: &AccountInfo<'a>,
+    vesting_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    share: u64,
+) -> ProgramResult {
+    let bump = check_vesting_pda(program_id, vesting_account, season_id, winner.key)?;
+    if !vesting_account.data_is_empty() {
+        msg!("{} already has a vesting prize from season {}", winner.key, season_id);
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+    let rent = Rent::get()?.minimum_balance(Vesting::LEN);
+    if share <= rent {
+        return move_lamports(prize_pool, winner, share);
+    }
+
+    // The prize pool is program owned and cannot pay through the system program,
+    // so take the account over first and fund it directly afterwards
+    allocate_pda_account(vesting_account, system_program, Vesting::LEN, program_id, &[VestingPda::SEED, &season_id.to_le_bytes(), winner.key.as_ref(), &[bump]])?;
+    move_lamports(prize_pool, vesting_account, share)?;
+    // validate_config keeps both durations within i64
+    let vesting = Vesting {
+        is_initialized: true,
+        beneficiary: *winner.key,
+        season_id,
+        total: share - rent,
+        claimed: 0,
+        start_ts: now,
+        cliff_ts: now.checked_add(config.prize_vesting_cliff_secs as i64).ok_or(ProgramError::ArithmeticOverflow)?,
+        end_ts: now.checked_add(config.prize_vesting_secs as i64).ok_or(ProgramError::ArithmeticOverflow)?,
+    };
+    vesting.pack(&mut vesting_account.try_borrow_mut_data()?)?;
+    msg!("{} lamports vest to {} until {}", vesting.total, winner.key, vesting.end_ts);
+    Ok(())
+}
+
+fn process_claim_vested(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let vesting_account = next_account_info(accounts_iter)?;
+    let beneficiary = next_account_info(accounts_iter)?;
+
+    check_signer(beneficiary)?;
+    check_program_owned(program_id, vesting_account)?;
+    let mut vesting = Vesting::unpack(&vesting_account.try_borrow_data()?)?;
+    if !vesting.is_initialized() {
+        msg!("Vesting account not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+    check_vesting_pda(program_id, vesting_account, vesting.season_id, &vesting.beneficiary)?;
+    if vesting.beneficiary != *beneficiary.key {
+        msg!("Vesting prize belongs to {}, not {}", vesting.beneficiary, beneficiary.key);
+        return Err(GarbageGrabError::Unauthorized.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    let amount = vesting.claimable(now);
+    if amount == 0 {
+        msg!("Nothing more of {} lamports has vested at {}", vesting.total, now);
+        return Err(GarbageGrabError::NothingVested.into());
+    }
+    vesting.claimed += amount;
+    if vesting.claimed == vesting.total {
+        // Close the account; the runtime reclaims it once empty
+        move_lamports(vesting_account, beneficiary, vesting_account.lamports())?;
+        vesting_account.try_borrow_mut_data()?.fill(0);
+    } else {
+        move_lamports(vesting_account, beneficiary, amount)?;
+        vesting.pack(&mut vesting_account.try_borrow_mut_data()?)?;
+    }
+    msg!("{} claimed {} vested lamports, {} of {} in total", beneficiary.key, amount, vesting.claimed, vesting.total);
+    Ok(())
+}
+
+// Tip a crank's sender config.crank_tip_lamports from the treasury, or whatever
+// the treasury holds above its rent-exempt minimum when that is less
+fn pay_crank_tip<'a>(
+    config: &Config,
+    cranker: &AccountInfo<'a>,
+    treasury: &AccountInfo<'a>,
+    treasury_bump: u8,
+    system_program: &AccountInfo<'a>,
+) -> ProgramResult {
+    let available = treasury.lamports().saturating_sub(Rent::get()?.minimum_balance(0));
+    let tip = config.crank_tip_lamports.min(available);
+    if tip > 0 {
+        invoke_signed(
+            &system_instruction::transfer(treasury.key, cranker.key, tip),
+            &[treasury.clone(), cranker.clone(), system_program.clone()],
+            &[&[TreasuryPda::SEED, &[treasury_bump]]],
+        )?;
+    }
+    msg!("Cranker {} tipped {} lamports", cranker.key, tip);
+    Ok(())
+}
+
+fn process_crank_season_rollover(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let season_account = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let cranker = next_account_info(accounts_iter)?;
+    let treasury = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_signer(cranker)?;
+    let config = load_config(program_id, config_account)?;
+    check_season_pda(program_id, season_account)?;
+    check_program_owned(program_id, season_account)?;
+    let treasury_bump = check_treasury_pda(program_id, treasury)?;
+    check_system_program(system_program)?;
+    if config.season_length_secs == 0 {
+        msg!("Seasons are not rolled over by crank");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut season = Season::unpack(&season_account.try_borrow_data()?)?;
+    let now = Clock::get()?.unix_timestamp;
+    if !season.is_initialized() || now < season.end_ts {
+        msg!("Season {} ends at {}, it is now {}", season.season_id, season.end_ts, now);
+        return Err(GarbageGrabError::SeasonNotOver.into());
+    }
+    // Rolling over first would carry the old season's pool and leaderboard into the new one
+    if !season.prizes_distributed {
+        msg!("Season {} prizes must be distributed before it rolls over", season.season_id);
+        return Err(GarbageGrabError::PrizesNotDistributed.into());
+    }
+
+    season.season_id = season
+        .season_id
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    season.start_ts = now;
+    season.end_ts = now
+        .checked_add(config.season_length_secs as i64)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    season.prizes_distributed = false;
+    season.pack(&mut season_account.try_borrow_mut_data()?)?;
+    msg!("Season {} rolled over: {} -> {}", season.season_id, season.start_ts, season.end_ts);
+
+    pay_crank_tip(&config, cranker, treasury, treasury_bump, system_program)
+}
+
+fn process_crank_leaderboard_finalize(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let leaderboard_account = next_account_info(accounts_iter)?;
+    let season_account = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let prize_pool = next_account_info(accounts_iter)?;
+    let cranker = next_account_info(accounts_iter)?;
+    let treasury = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_signer(cranker)?;
+    let treasury_bump = check_treasury_pda(program_id, treasury)?;
+    check_system_program(system_program)?;
+
+    distribute_season_prizes(program_id, leaderboard_account, season_account, config_account, prize_pool, Some(system_program), accounts_iter)?;
+    let config = load_config(program_id, config_account)?;
+    pay_crank_tip(&config, cranker, treasury, treasury_bump, system_program)
+}
+
+// Pay lamports out of the treasury to a destination of the admin's choosing,
+// counting them against the per-epoch cap
+fn process_withdraw_treasury(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let config_account = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+    let treasury = next_account_info(accounts_iter)?;
+    let destination = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    let mut config = load_config_as_admin(program_id, config_account, admin)?;
+    let bump = check_treasury_pda(program_id, treasury)?;
+    check_system_program(system_program)?;
+
+    // The treasury holds no data and must stay rent exempt to keep collecting
+    let available = treasury.lamports().saturating_sub(Rent::get()?.minimum_balance(0));
+    if amount > available {
+        msg!("Treasury holds {} lamports above rent, {} requested", available, amount);
+        return Err(ProgramError::InsufficientFunds);
+    }
+    let clock = Clock::get()?;
+    if !config.record_treasury_withdrawal(clock.epoch, amount) {
+        msg!(
+            "Withdrawing {} lamports would exceed the cap of {} per epoch; {} already withdrawn in epoch {}",
+            amount,
+            config.treasury_withdraw_cap_lamports,
+            config.treasury_withdrawn_lamports,
+            clock.epoch
+        );
+        return Err(GarbageGrabError::TreasuryCapExceeded.into());
+    }
+    config.pack(&mut config_account.try_borrow_mut_data()?)?;
+
+    invoke_signed(
+        &system_instruction::transfer(treasury.key, destination.key, amount),
+        &[treasury.clone(), destination.clone(), system_program.clone()],
+        &[&[TreasuryPda::SEED, &[bump]]],
+    )?;
+    TreasuryWithdrawn {
+        admin: *admin.key,
+        destination: *destination.key,
+        amount,
+        epoch: clock.epoch,
+        withdrawn_in_epoch: config.treasury_withdrawn_lamports,
+        ts: clock.unix_timestamp,
+    }
+    .emit();
+    msg!("Withdrew {} lamports from the treasury to {}", amount, destination.key);
+    Ok(())
+}
+
+// Hand a read score back as return data, Borsh-encoded as a little-endian u64, so
+// CPI callers and simulations need not fetch and decode the account
+fn return_score(score: u64) {
+    set_return_data(&score.to_le_bytes());
+}
+
+fn process_verify_score_at_least(program_id: &Pubkey, accounts: &[AccountInfo], threshold: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let score_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let season_account = next_account_info(accounts_iter)?;
+
+    check_program_owned(program_id, score_account)?;
+    let mut state = ScoreAccount::unpack_any_version(&score_account.try_borrow_data()?)?;
+    if !state.is_initialized() {
+        msg!("Account not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+    // The mode comes from the account itself, so only the player needs passing in
+    check_score_pda(program_id, score_account, player.key, state.game_mode)?;
+
+    // Check the score as the next update would see it, without writing it back
+    let config = load_config(program_id, config_account)?;
+    state.roll_season(current_season_id(program_id, season_account)?);
+    state.decay(Clock::get()?.unix_timestamp, config.decay_window_secs, config.decay_bps);
+    // Returned before the check so a simulation shows the score either way
+    return_score(state.score);
+    if state.score < threshold {
+        msg!("Player {} has {} points, below {}", player.key, state.score, threshold);
+        return Err(GarbageGrabError::ScoreBelowThreshold.into());
+    }
+    msg!("Player {} has {} points, at least {}", player.key, state.score, threshold);
+    Ok(())
+}
+
+// Check the passed account is the match PDA for these players and id. Returns the bump seed.
+fn check_match_pda(
+    program_id: &Pubkey,
+    match_account: &AccountInfo,
+    player_a: &Pubkey,
+    player_b: &Pubkey,
+    match_id: u64,
+) -> Result<u8, ProgramError> {
+    let (pda, bump) = MatchPda::find(program_id, player_a, player_b, match_id);
+    if pda != *match_account.key {
+        msg!("Invalid match PDA: expected {}, found {}", pda, match_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Read an existing match account
+fn load_match(program_id: &Pubkey, match_account: &AccountInfo) -> Result<Match, ProgramError> {
+    check_program_owned(program_id, match_account)?;
+    let state = Match::unpack(&match_account.try_borrow_data()?)?;
+    if !state.is_initialized() {
+        msg!("Match not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+    // Re-derive the address from the stored fields so a forged account cannot stand in
+    check_match_pda(program_id, match_account, &state.player_a, &state.player_b, state.match_id)?;
+    Ok(state)
+}
+
+// Require `player` to have reached config.high_stakes_min_level when `wager`
+// lamports reach config.high_stakes_wager. Their profile is read from the next
+// trailing account.
+fn check_high_stakes_level(
+    program_id: &Pubkey,
+    accounts_iter: &mut std::slice::Iter<AccountInfo>,
+    player: &Pubkey,
+    config: &Config,
+    wager: u64,
+) -> ProgramResult {
+    if config.high_stakes_wager == 0 || wager < config.high_stakes_wager {
+        return Ok(());
+    }
+    let level = match accounts_iter.next() {
+        Some(profile_account) => load_profile(program_id, profile_account, player)?.level,
+        None => 0,
+    };
+    if level < config.high_stakes_min_level {
+        msg!("Wagers of {} lamports need level {}; {} is level {}", config.high_stakes_wager, config.high_stakes_min_level, player, level);
+        return Err(GarbageGrabError::LevelTooLow.into());
+    }
+    Ok(())
+}
+
+fn process_create_match(program_id: &Pubkey, accounts: &[AccountInfo], match_id: u64, stake: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let match_account = next_account_info(accounts_iter)?;
+    let player_a = next_account_info(accounts_iter)?;
+    let player_b = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_signer(player_a)?;
+    check_signer(player_b)?;
+    let config = load_config(program_id, config_account)?;
+    check_not_paused(&config)?;
+    let bump = check_match_pda(program_id, match_account, player_a.key, player_b.key, match_id)?;
+    check_system_program(system_program)?;
+    if player_a.key == player_b.key {
+        msg!("A match needs two different players");
+        return Err(ProgramError::InvalidArgument);
+    }
+    if stake == 0 {
+        msg!("Stake must be greater than zero");
+        return Err(ProgramError::InvalidArgument);
+    }
+    for player in [player_a, player_b] {
+        check_high_stakes_level(program_id, accounts_iter, player.key, &config, stake)?;
+    }
+    if !match_account.data_is_empty() {
+        msg!("Match {} already exists", match_id);
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+
+    let space = Match::LEN;
+    create_pda_account(player_a, match_account, system_program, space, program_id, &[MatchPda::SEED, player_a.key.as_ref(), player_b.key.as_ref(), &match_id.to_le_bytes(), &[bump]])?;
+    for staker in [player_a, player_b] {
+        invoke(
+            &system_instruction::transfer(staker.key, match_account.key, stake),
+            &[staker.clone(), match_account.clone(), system_program.clone()],
+        )?;
+    }
+
+    let state = Match {
+        is_initialized: true,
+        match_id,
+        player_a: *player_a.key,
+        player_b: *player_b.key,
+        stake,
+        status: MatchStatus::Active,
+        winner: Pubkey::default(),
+    };
+    state.pack(&mut match_account.try_borrow_mut_data()?)?;
+    msg!("Match {} created: {} vs {} for {} lamports each", match_id, player_a.key, player_b.key, stake);
+    Ok(())
+}
+
+fn process_submit_match_result(program_id: &Pubkey, accounts: &[AccountInfo], winner: Pubkey) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let match_account = next_account_info(accounts_iter)?;
+    let game_server = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+
+    load_config_as_game_server(program_id, config_account, game_server)?;
+
+    let mut state = load_match(program_id, match_account)?;
+    if state.status != MatchStatus::Active {
+        msg!("Match {} already has a result", state.match_id);
+        return Err(GarbageGrabError::InvalidMatchState.into());
+    }
+    if !state.is_player(&winner) {
+        msg!("{} did not play in match {}", winner, state.match_id);
+        return Err(GarbageGrabError::InvalidWinner.into());
+    }
+    state.status = MatchStatus::Decided;
+    state.winner = winner;
+    state.pack(&mut match_account.try_borrow_mut_data()?)?;
+    msg!("Match {} won by {}", state.match_id, winner);
+    Ok(())
+}
+
+fn process_settle_match(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let match_account = next_account_info(accounts_iter)?;
+    let winner = next_account_info(accounts_iter)?;
+    let player_a = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let fee_vault = next_account_info(accounts_iter)?;
+    let pool_account = next_account_info(accounts_iter)?;
+
+    let config = load_config(program_id, config_account)?;
+    check_fee_vault_pda(program_id, fee_vault)?;
+    let stats_account = next_global_stats(program_id, accounts_iter)?;
+    let state = load_match(program_id, match_account)?;
+    if state.status != MatchStatus::Decided {
+        msg!("Match {} has no result yet", state.match_id);
+        return Err(GarbageGrabError::InvalidMatchState.into());
+    }
+    // Bets read the result from the match, so it has to outlive an open bet pool
+    check_bet_pool_pda(program_id, pool_account, match_account.key)?;
+    if !pool_account.data_is_empty() && load_bet_pool(program_id, pool_account)?.status == BetPoolStatus::Open {
+        msg!("Bets on match {} must be settled before the match", state.match_id);
+        return Err(GarbageGrabError::InvalidMatchState.into());
+    }
+    if *winner.key != state.winner || *player_a.key != state.player_a {
+        msg!("Expected winner {} and first player {}", state.winner, state.player_a);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Pay the pot less the house fee, hand whatever is left (the rent) back to the
+    // first player and close. A vault still short of rent exemption is topped up
+    // out of the refund, which is more than a data-less account's minimum.
+    let pot = state.pot().ok_or(ProgramError::ArithmeticOverflow)?;
+    let fee = config.house_fee(pot);
+    let total = match_account.lamports();
+    let mut rent_refund = total.checked_sub(pot).ok_or(ProgramError::InsufficientFunds)?;
+    let vault_credit = if fee > 0 {
+        let shortfall = Rent::get()?.minimum_balance(0).saturating_sub(fee_vault.lamports().saturating_add(fee));
+        rent_refund = rent_refund.checked_sub(shortfall).ok_or(ProgramError::InsufficientFunds)?;
+        fee + shortfall
+    } else {
+        0
+    };
+    **match_account.try_borrow_mut_lamports()? = 0;
+    **winner.try_borrow_mut_lamports()? = winner
+        .lamports()
+        .checked_add(pot - fee)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **fee_vault.try_borrow_mut_lamports()? = fee_vault
+        .lamports()
+        .checked_add(vault_credit)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **player_a.try_borrow_mut_lamports()? = player_a
+        .lamports()
+        .checked_add(rent_refund)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    match_account.try_borrow_mut_data()?.fill(0);
+    msg!("Match {} settled: {} lamports to {}, {} in fees", state.match_id, pot - fee, winner.key, fee);
+    record_global_stats(program_id, stats_account, |stats| stats.record_fee(fee))
+}
+
+// Check the passed account is the bet pool PDA of `match_account`. Returns the bump seed.
+fn check_bet_pool_pda(program_id: &Pubkey, pool_account: &AccountInfo, match_account: &Pubkey) -> Result<u8, ProgramError> {
+    let (pda, bump) = BetPoolPda::find(program_id, match_account);
+    if pda != *pool_account.key {
+        msg!("Invalid bet pool PDA: expected {}, found {}", pda, pool_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Check the passed account is the PDA of `bettor`'s bet on `match_account`. Returns the bump seed.
+fn check_bet_pda(program_id: &Pubkey, bet_account: &AccountInfo, match_account: &Pubkey, bettor: &Pubkey) -> Result<u8, ProgramError> {
+    let (pda, bump) = BetPda::find(program_id, match_account, bettor);
+    if pda != *bet_account.key {
+        msg!("Invalid bet PDA: expected {}, found {}", pda, bet_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Read an existing bet pool, re-deriving its address from the stored match
+fn load_bet_pool(program_id: &Pubkey, pool_account: &AccountInfo) -> Result<BetPool, ProgramError> {
+    check_program_owned(program_id, pool_account)?;
+    let pool = BetPool::unpack(&pool_account.try_borrow_data()?)?;
+    if !pool.is_initialized() {
+        msg!("Bet pool not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+    check_bet_pool_pda(program_id, pool_account, &pool.match_account)?;
+    Ok(pool)
+}
+
+// Move `amount` lamports out of a program-owned account into `to`
+fn move_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> ProgramResult {
+    **from.try_borrow_mut_lamports()? = from
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    **to.try_borrow_mut_lamports()? = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    Ok(())
+}
+
+fn process_place_bet(program_id: &Pubkey, accounts: &[AccountInfo], on: Pubkey, amount: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let pool_account = next_account_info(accounts_iter)?;
+    let bet_account = next_account_info(accounts_iter)?;
+    let match_account = next_account_info(accounts_iter)?;
+    let bettor = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_signer(bettor)?;
+    let config = load_config(program_id, config_account)?;
+    check_not_paused(&config)?;
+    check_system_program(system_program)?;
+    let match_state = load_match(program_id, match_account)?;
+    if match_state.status != MatchStatus::Active {
+        msg!("Match {} already has a result", match_state.match_id);
+        return Err(GarbageGrabError::BettingClosed.into());
+    }
+    if match_state.is_player(bettor.key) || !match_state.is_player(&on) {
+        msg!("{} cannot bet on {} in match {}", bettor.key, on, match_state.match_id);
+        return Err(GarbageGrabError::InvalidBet.into());
+    }
+    if amount == 0 {
+        msg!("Bet must be greater than zero");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let pool_bump = check_bet_pool_pda(program_id, pool_account, match_account.key)?;
+    let mut pool = if pool_account.data_is_empty() {
+        create_pda_account(bettor, pool_account, system_program, BetPool::LEN, program_id, &[BetPoolPda::SEED, match_account.key.as_ref(), &[pool_bump]])?;
+        BetPool {
+            is_initialized: true,
+            match_account: *match_account.key,
+            player_a: match_state.player_a,
+            player_b: match_state.player_b,
+            ..BetPool::default()
+        }
+    } else {
+        load_bet_pool(program_id, pool_account)?
+    };
+    let bet_bump = check_bet_pda(program_id, bet_account, match_account.key, bettor.key)?;
+    let mut bet = if bet_account.data_is_empty() {
+        create_pda_account(bettor, bet_account, system_program, Bet::LEN, program_id, &[BetPda::SEED, match_account.key.as_ref(), bettor.key.as_ref(), &[bet_bump]])?;
+        pool.open_bets = pool.open_bets.checked_add(1).ok_or(ProgramError::ArithmeticOverflow)?;
+        Bet {
+            is_initialized: true,
+            match_account: *match_account.key,
+            bettor: *bettor.key,
+            on,
+            amount: 0,
+        }
+    } else {
+        check_program_owned(program_id, bet_account)?;
+        Bet::unpack(&bet_account.try_borrow_data()?)?
+    };
+    if bet.on != on {
+        msg!("{} already backs {} in match {}", bettor.key, bet.on, match_state.match_id);
+        return Err(GarbageGrabError::InvalidBet.into());
+    }
+    bet.amount = bet.amount.checked_add(amount).ok_or(ProgramError::ArithmeticOverflow)?;
+    check_high_stakes_level(program_id, accounts_iter, bettor.key, &config, bet.amount)?;
+    pool.add_stake(&on, amount).ok_or(ProgramError::ArithmeticOverflow)?;
+
+    invoke(
+        &system_instruction::transfer(bettor.key, pool_account.key, amount),
+        &[bettor.clone(), pool_account.clone(), system_program.clone()],
+    )?;
+    pool.pack(&mut pool_account.try_borrow_mut_data()?)?;
+    bet.pack(&mut bet_account.try_borrow_mut_data()?)?;
+    msg!("{} bet {} lamports on {} in match {}", bettor.key, amount, on, match_state.match_id);
+    Ok(())
+}
+
+fn process_settle_bets(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let pool_account = next_account_info(accounts_iter)?;
+    let match_account = next_account_info(accounts_iter)?;
+    let treasury = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+
+    let mut pool = load_bet_pool(program_id, pool_account)?;
+    if *match_account.key != pool.match_account {
+        msg!("Bet pool is for match {}, not {}", pool.match_account, match_account.key);
+        return Err(ProgramError::InvalidArgument);
+    }
+    check_treasury_pda(program_id, treasury)?;
+    let config = load_config(program_id, config_account)?;
+
+    if pool.status == BetPoolStatus::Open {
+        if match_account.data_is_empty() {
+            // Only a match settled before SettleMatch checked for open bets can be
+            // gone while its pool is still open
+            pool.status = BetPoolStatus::Void;
+            msg!("Match {} closed without a result; refunding bets", match_account.key);
+        } else {
+            let match_state = load_match(program_id, match_account)?;
+            if match_state.status != MatchStatus::Decided {
+                msg!("Match {} has no result yet", match_state.match_id);
+                return Err(GarbageGrabError::InvalidMatchState.into());
+            }
+            let fee = pool.decide(match_state.winner, config.bet_fee_bps);
+            move_lamports(pool_account, treasury, fee)?;
+            msg!("Bets on match {} decided for {}; {} lamports to the treasury", match_state.match_id, match_state.winner, fee);
+        }
+    }
+
+    while let Some(bet_account) = accounts_iter.next() {
+        let bettor = next_account_info(accounts_iter)?;
+        check_program_owned(program_id, bet_account)?;
+        let bet = Bet::unpack(&bet_account.try_borrow_data()?)?;
+        if !bet.is_initialized() || bet.match_account != pool.match_account || bet.bettor != *bettor.key {
+            msg!("{} is not a bet of {} on this match", bet_account.key, bettor.key);
+            return Err(ProgramError::InvalidArgument);
+        }
+        check_bet_pda(program_id, bet_account, &pool.match_account, &bet.bettor)?;
+
+        let payout = pool.payout(&bet);
+        move_lamports(pool_account, bettor, payout)?;
+        // Close the bet; the runtime reclaims the empty account
+        move_lamports(bet_account, bettor, bet_account.lamports())?;
+        bet_account.try_borrow_mut_data()?.fill(0);
+        pool.open_bets = pool.open_bets.saturating_sub(1);
+        msg!("Bet of {} settled: {} lamports", bettor.key, payout);
+    }
+
+    if pool.open_bets == 0 {
+        move_lamports(pool_account, treasury, pool_account.lamports())?;
+        pool_account.try_borrow_mut_data()?.fill(0);
+        msg!("Bet pool for match {} closed", pool.match_account);
+    } else {
+        pool.pack(&mut pool_account.try_borrow_mut_data()?)?;
+    }
+    Ok(())
+}
+
+// Check the passed account is the lobby PDA of `host`. Returns the bump seed.
+fn check_lobby_pda(program_id: &Pubkey, lobby_account: &AccountInfo, host: &Pubkey) -> Result<u8, ProgramError> {
+    let (pda, bump) = LobbyPda::find(program_id, host);
+    if pda != *lobby_account.key {
+        msg!("Invalid lobby PDA: expected {}, found {}", pda, lobby_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Read an existing lobby, re-deriving its address from the stored host
+fn load_lobby(program_id: &Pubkey, lobby_account: &AccountInfo) -> Result<Lobby, ProgramError> {
+    check_program_owned(program_id, lobby_account)?;
+    let lobby = Lobby::unpack(&lobby_account.try_borrow_data()?)?;
+    if !lobby.is_initialized() {
+        msg!("Lobby not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+    check_lobby_pda(program_id, lobby_account, &lobby.host)?;
+    Ok(lobby)
+}
+
+fn process_create_lobby(program_id: &Pubkey, accounts: &[AccountInfo], capacity: u8, stake: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let lobby_account = next_account_info(accounts_iter)?;
+    let host = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_signer(host)?;
+    let config = load_config(program_id, config_account)?;
+    check_not_paused(&config)?;
+    let bump = check_lobby_pda(program_id, lobby_account, host.key)?;
+    check_system_program(system_program)?;
+    if !(2..=LOBBY_CAPACITY).contains(&(capacity as usize)) {
+        msg!("Lobby capacity must be 2 to {}", LOBBY_CAPACITY);
+        return Err(ProgramError::InvalidArgument);
+    }
+    if stake == 0 {
+        msg!("Stake must be greater than zero");
+        return Err(ProgramError::InvalidArgument);
+    }
+    check_high_stakes_level(program_id, accounts_iter, host.key, &config, stake)?;
+    if !lobby_account.data_is_empty() {
+        msg!("Lobby of {} already exists", host.key);
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+
+    create_pda_account(host, lobby_account, system_program, Lobby::LEN, program_id, &[LobbyPda::SEED, host.key.as_ref(), &[bump]])?;
+    let lobby = Lobby {
+        is_initialized: true,
+        host: *host.key,
+        capacity,
+        stake,
+        ..Lobby::default()
+    };
+    lobby.pack(&mut lobby_account.try_borrow_mut_data()?)?;
+    msg!("Lobby of {} opened for {} players at {} lamports each", host.key, capacity, stake);
+    Ok(())
+}
+
+fn process_join_lobby(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let lobby_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_signer(player)?;
+    let config = load_config(program_id, config_account)?;
+    check_not_paused(&config)?;
+    check_system_program(system_program)?;
+    let mut lobby = load_lobby(program_id, lobby_account)?;
+    check_high_stakes_level(program_id, accounts_iter, player.key, &config, lobby.stake)?;
+    if lobby.is_waiting(player.key) {
+        msg!("{} is already waiting in the lobby", player.key);
+        return Err(GarbageGrabError::AlreadyInLobby.into());
+    }
+    if !lobby.join(*player.key) {
+        msg!("Lobby of {} is full", lobby.host);
+        return Err(GarbageGrabError::LobbyFull.into());
+    }
+
+    invoke(
+        &system_instruction::transfer(player.key, lobby_account.key, lobby.stake),
+        &[player.clone(), lobby_account.clone(), system_program.clone()],
+    )?;
+    lobby.pack(&mut lobby_account.try_borrow_mut_data()?)?;
+    msg!("{} joined the lobby of {} ({}/{})", player.key, lobby.host, lobby.player_count, lobby.capacity);
+    Ok(())
+}
+
+fn process_leave_lobby(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let lobby_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+
+    check_signer(player)?;
+    let mut lobby = load_lobby(program_id, lobby_account)?;
+    if !lobby.leave(player.key) {
+        msg!("{} is not waiting in the lobby", player.key);
+        return Err(GarbageGrabError::NotInLobby.into());
+    }
+
+    // Hand the stake back out of the lobby's own lamports
+    **lobby_account.try_borrow_mut_lamports()? = lobby_account
+        .lamports()
+        .checked_sub(lobby.stake)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    **player.try_borrow_mut_lamports()? = player
+        .lamports()
+        .checked_add(lobby.stake)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    lobby.pack(&mut lobby_account.try_borrow_mut_data()?)?;
+    msg!("{} left the lobby of {}", player.key, lobby.host);
+    Ok(())
+}
+
+fn process_start_match(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let lobby_account = next_account_info(accounts_iter)?;
+    let match_account = next_account_info(accounts_iter)?;
+    let player_a = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_signer(player_a)?;
+    check_not_paused(&load_config(program_id, config_account)?)?;
+    check_system_program(system_program)?;
+    let mut lobby = load_lobby(program_id, lobby_account)?;
+    let Some((first, second)) = lobby.take_pair() else {
+        msg!("A match needs two players waiting in the lobby");
+        return Err(ProgramError::InvalidArgument);
+    };
+    if first != *player_a.key {
+        msg!("Only {}, who has waited longest, can start the match", first);
+        return Err(GarbageGrabError::Unauthorized.into());
+    }
+    let match_id = lobby.matches_started;
+    let bump = check_match_pda(program_id, match_account, &first, &second, match_id)?;
+    if !match_account.data_is_empty() {
+        msg!("Match {} already exists", match_id);
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+    lobby.matches_started = match_id.checked_add(1).ok_or(ProgramError::ArithmeticOverflow)?;
+
+    create_pda_account(player_a, match_account, system_program, Match::LEN, program_id, &[MatchPda::SEED, first.as_ref(), second.as_ref(), &match_id.to_le_bytes(), &[bump]])?;
+    // Both stakes move from the lobby, which owns them, into the match
+    let state = Match {
+        is_initialized: true,
+        match_id,
+        player_a: first,
+        player_b: second,
+        stake: lobby.stake,
+        status: MatchStatus::Active,
+        winner: Pubkey::default(),
+    };
+    let pot = state.pot().ok_or(ProgramError::ArithmeticOverflow)?;
+    **lobby_account.try_borrow_mut_lamports()? = lobby_account
+        .lamports()
+        .checked_sub(pot)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    **match_account.try_borrow_mut_lamports()? = match_account
+        .lamports()
+        .checked_add(pot)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    state.pack(&mut match_account.try_borrow_mut_data()?)?;
+    lobby.pack(&mut lobby_account.try_borrow_mut_data()?)?;
+    msg!("Match {} started from the lobby of {}: {} vs {} for {} lamports each", match_id, lobby.host, first, second, lobby.stake);
+    Ok(())
+}
+
+// Check the passed account is the PDA of tournament `tournament_id`. Returns the bump seed.
+fn check_tournament_pda(
+    program_id: &Pubkey,
+    tournament_account: &AccountInfo,
+    tournament_id: u64,
+) -> Result<u8, ProgramError> {
+    let (pda, bump) = TournamentPda::find(program_id, tournament_id);
+    if pda != *tournament_account.key {
+        msg!("Invalid tournament PDA: expected {}, found {}", pda, tournament_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Read an existing tournament, re-deriving its address from the stored id
+fn load_tournament(program_id: &Pubkey, tournament_account: &AccountInfo) -> Result<Tournament, ProgramError> {
+    check_program_owned(program_id, tournament_account)?;
+    let tournament = Tournament::unpack(&tournament_account.try_borrow_data()?)?;
+    if !tournament.is_initialized() {
+        msg!("Tournament not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+    check_tournament_pda(program_id, tournament_account, tournament.tournament_id)?;
+    Ok(tournament)
+}
+
+// Registrations and scores are only accepted before the end and while not finalized
+fn check_tournament_open(tournament: &Tournament) -> ProgramResult {
+    let now = Clock::get()?.unix_timestamp;
+    if tournament.finalized || now >= tournament.end_ts {
+        msg!("Tournament {} closed at {}", tournament.tournament_id, tournament.end_ts);
+        return Err(GarbageGrabError::TournamentClosed.into());
+    }
+    Ok(())
+}
+
+fn process_create_tournament(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    tournament_id: u64,
+    entry_fee: u64,
+    max_participants: u8,
+    end_ts: i64,
+    collection: Pubkey,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let tournament_account = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    load_config_as_admin(program_id, config_account, admin)?;
+    let bump = check_tournament_pda(program_id, tournament_account, tournament_id)?;
+    check_system_program(system_program)?;
+    if max_participants == 0 || max_participants as usize > TOURNAMENT_CAPACITY {
+        msg!("Participant cap must be between 1 and {}", TOURNAMENT_CAPACITY);
+        return Err(ProgramError::InvalidArgument);
+    }
+    let now = Clock::get()?.unix_timestamp;
+    if end_ts <= now {
+        msg!("Tournament end {} is not after the current time {}", end_ts, now);
+        return Err(GarbageGrabError::InvalidSeasonWindow.into());
+    }
+    if !tournament_account.data_is_empty() {
+        msg!("Tournament {} already exists", tournament_id);
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+
+    let space = Tournament::LEN;
+    create_pda_account(admin, tournament_account, system_program, space, program_id, &[TournamentPda::SEED, &tournament_id.to_le_bytes(), &[bump]])?;
+
+    let tournament = Tournament {
+        is_initialized: true,
+        tournament_id,
+        creator: *admin.key,
+        entry_fee,
+        max_participants,
+        end_ts,
+        finalized: false,
+        collection,
+        participants: Vec::new(),
+    };
+    tournament.pack(&mut tournament_account.try_borrow_mut_data()?)?;
+    msg!(
+        "Tournament {} created: {} places, {} lamport entry, ends {}",
+        tournament_id,
+        max_participants,
+        entry_fee,
+        end_ts
+    );
+    if tournament.is_gated() {
+        msg!("Registration limited to holders of collection {}", collection);
+    }
+    Ok(())
+}
+
+fn process_register(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let tournament_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let fee_vault = next_account_info(accounts_iter)?;
+
+    check_signer(player)?;
+    check_system_program(system_program)?;
+    let config = load_config(program_id, config_account)?;
+    check_not_paused(&config)?;
+    check_fee_vault_pda(program_id, fee_vault)?;
+    let stats_account = next_global_stats(program_id, accounts_iter)?;
+    let mut tournament = load_tournament(program_id, tournament_account)?;
+    check_tournament_open(&tournament)?;
+    if tournament.entry_mut(player.key).is_some() {
+        msg!("Player {} is already registered", player.key);
+        return Err(GarbageGrabError::AlreadyRegistered.into());
+    }
+    if tournament.is_full() {
+        msg!("Tournament {} is full", tournament.tournament_id);
+        return Err(GarbageGrabError::TournamentFull.into());
+    }
+    if tournament.is_gated() {
+        let nft_token_account = next_account_info(accounts_iter)?;
+        let nft_metadata = next_account_info(accounts_iter)?;
+        check_collection_nft(&tournament.collection, player.key, nft_token_account, nft_metadata, GarbageGrabError::CollectionNftRequired)?;
+    }
+
+    let fee = config.house_fee(tournament.entry_fee);
+    if tournament.entry_fee > fee {
+        invoke(
+            &system_instruction::transfer(player.key, tournament_account.key, tournament.entry_fee - fee),
+            &[player.clone(), tournament_account.clone(), system_program.clone()],
+        )?;
+    }
+    pay_house_fee(player, fee_vault, system_program, fee)?;
+    tournament.participants.push(TournamentEntry {
+        player: *player.key,
+        score: 0,
+    });
+    tournament.pack(&mut tournament_account.try_borrow_mut_data()?)?;
+    msg!(
+        "Player {} registered for tournament {} ({}/{})",
+        player.key,
+        tournament.tournament_id,
+        tournament.participants.len(),
+        tournament.max_participants
+    );
+    record_global_stats(program_id, stats_account, |stats| stats.record_fee(fee))
+}
+
+fn process_submit_tournament_score(program_id: &Pubkey, accounts: &[AccountInfo], score: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let tournament_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let game_server = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+
+    let config = load_config_as_game_server(program_id, config_account, game_server)?;
+    check_not_paused(&config)?;
+    let mut tournament = load_tournament(program_id, tournament_account)?;
+    check_tournament_open(&tournament)?;
+    let entry = tournament.entry_mut(player.key).ok_or_else(|| {
+        msg!("Player {} is not registered", player.key);
+        GarbageGrabError::NotRegistered
+    })?;
+    entry.score = entry.score.max(score);
+    let best = entry.score;
+    tournament.pack(&mut tournament_account.try_borrow_mut_data()?)?;
+    msg!("Tournament {} score for {}: {} (best {})", tournament.tournament_id, player.key, score, best);
+    Ok(())
+}
+
+fn process_finalize_tournament(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let tournament_account = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let creator = next_account_info(accounts_iter)?;
+
+    let config = load_config(program_id, config_account)?;
+    let mut tournament = load_tournament(program_id, tournament_account)?;
+    if tournament.finalized {
+        msg!("Tournament {} already finalized", tournament.tournament_id);
+        return Err(GarbageGrabError::TournamentClosed.into());
+    }
+    let now = Clock::get()?.unix_timestamp;
+    if now < tournament.end_ts {
+        msg!("Tournament {} ends at {}, it is now {}", tournament.tournament_id, tournament.end_ts, now);
+        return Err(GarbageGrabError::TournamentInProgress.into());
+    }
+    if *creator.key != tournament.creator {
+        msg!("Tournament creator is {}, found {}", tournament.creator, creator.key);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Everything above the rent-exempt minimum is collected entry fees
+    let rent = Rent::get()?.minimum_balance(tournament_account.data_len());
+    let pool = tournament_account.lamports().saturating_sub(rent);
+    let standings = tournament.standings();
+    let shares = config.prize_shares(pool, standings.len());
+    let mut paid = 0u64;
+    for (place, (entry, &share)) in standings.iter().zip(&shares).enumerate() {
+        let winner = next_account_info(accounts_iter)?;
+        if *winner.key != entry.player {
+            msg!("Place {} belongs to {}, found {}", place + 1, entry.player, winner.key);
+            return Err(ProgramError::InvalidArgument);
+        }
+        **winner.try_borrow_mut_lamports()? = winner
+            .lamports()
+            .checked_add(share)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        paid += share;
+        msg!("Place {}: {} won {} lamports", place + 1, winner.key, share);
+    }
+    let remainder = pool - paid;
+    **creator.try_borrow_mut_lamports()? = creator
+        .lamports()
+        .checked_add(remainder)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **tournament_account.try_borrow_mut_lamports()? = rent;
+
+    tournament.finalized = true;
+    tournament.pack(&mut tournament_account.try_borrow_mut_data()?)?;
+    msg!(
+        "Tournament {} finalized: {} lamports paid, {} returned to the creator",
+        tournament.tournament_id,
+        paid,
+        remainder
+    );
+    Ok(())
+}
+
+fn process_unlock_achievement(program_id: &Pubkey, accounts: &[AccountInfo], game_mode: GameMode, id: u8) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let score_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let game_server = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+
+    load_config_as_game_server(program_id, config_account, game_server)?;
+    check_score_pda(program_id, score_account, player.key, game_mode)?;
+    check_program_owned(program_id, score_account)?;
+    if id >= ScoreAccount::ACHIEVEMENT_COUNT {
+        msg!("Achievement id {} is out of range", id);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut score_data = score_account.try_borrow_mut_data()?;
+    let mut state = load_score(&score_data, player.key)?;
+    if !state.unlock_achievement(id) {
+        msg!("Achievement {} already unlocked for player {}", id, player.key);
+        return Err(GarbageGrabError::AchievementAlreadyUnlocked.into());
+    }
+    state.pack(&mut score_data)?;
+    msg!("Achievement unlocked: player {} achievement {} ({:?})", player.key, id, game_mode);
+    record_quest_progress(program_id, accounts_iter, player.key, QuestMetric::AchievementsUnlocked, 1)
+}
+
+fn process_check_in(program_id: &Pubkey, accounts: &[AccountInfo], game_mode: GameMode) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let score_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+
+    check_player_score(program_id, score_account, player, game_mode)?;
+    check_not_paused(&load_config(program_id, config_account)?)?;
+
+    let mut score_data = score_account.try_borrow_mut_data()?;
+    let mut state = load_score(&score_data, player.key)?;
+    let now = Clock::get()?.unix_timestamp;
+    if !state.check_in(now) {
+        msg!("Player {} already checked in today", player.key);
+        return Err(GarbageGrabError::AlreadyCheckedIn.into());
+    }
+    state.pack(&mut score_data)?;
+    msg!(
+        "Player {} checked in: {} day streak, {} bps multiplier",
+        player.key,
+        state.streak,
+        state.streak_multiplier_bps(now)
+    );
+    Ok(())
+}
+
+// Check `token_account` holds an NFT whose Token Metadata lists it in the config's
+// boost collection, with the collection verified by the collection authority
+fn check_boost_nft(
+    config: &Config,
+    player: &Pubkey,
+    token_account: &AccountInfo,
+    metadata_account: &AccountInfo,
+) -> ProgramResult {
+    if config.boost_collection == Pubkey::default() {
+        msg!("No boost collection configured");
+        return Err(GarbageGrabError::InvalidBoostNft.into());
+    }
+    check_collection_nft(&config.boost_collection, player, token_account, metadata_account, GarbageGrabError::InvalidBoostNft)
+}
+
+// Check `token_account` is the player's and holds an NFT whose Token Metadata lists
+// it in `collection`, verified by the collection authority. Fails with `error`.
+fn check_collection_nft(
+    collection: &Pubkey,
+    player: &Pubkey,
+    token_account: &AccountInfo,
+    metadata_account: &AccountInfo,
+    error: GarbageGrabError,
+) -> ProgramResult {
+    if *token_account.owner != spl_token::id() || *metadata_account.owner != mpl_token_metadata::ID {
+        msg!("NFT accounts must be owned by the Token and Token Metadata programs");
+        return Err(error.into());
+    }
+    let token = spl_token::state::Account::unpack(&token_account.try_borrow_data()?)?;
+    if token.owner != *player || token.amount == 0 {
+        msg!("Token account {} does not hold the NFT for {}", token_account.key, player);
+        return Err(error.into());
+    }
+    if Metadata::find_pda(&token.mint).0 != *metadata_account.key {
+        msg!("Metadata account {} is not the metadata of mint {}", metadata_account.key, token.mint);
+        return Err(error.into());
+    }
+    let metadata = Metadata::safe_deserialize(&metadata_account.try_borrow_data()?)
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    match metadata.collection {
+        Some(member_of) if member_of.verified && member_of.key == *collection => Ok(()),
+        _ => {
+            msg!("Mint {} is not a verified member of collection {}", token.mint, collection);
+            Err(error.into())
+        }
+    }
+}
+
+fn process_apply_boost(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    game_mode: GameMode,
+    increment: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let score_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let season_account = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let ban_list_account = next_account_info(accounts_iter)?;
+    let nft_token_account = next_account_info(accounts_iter)?;
+    let nft_metadata = next_account_info(accounts_iter)?;
+
+    check_player_score(program_id, score_account, player, game_mode)?;
+    let config = load_config(program_id, config_account)?;
+    check_attestation_not_required(&config)?;
+    check_boost_nft(&config, player.key, nft_token_account, nft_metadata)?;
+    let boost_bps = u64::from(config.boost_multiplier_bps);
+    let credited = credit_increment(program_id, score_account, player.key, season_account, ban_list_account, &config, increment, None, boost_bps)?;
+    credit_guild(program_id, accounts_iter, player.key, credited)
+}
+
+// Symbol and metadata JSON shared by every trophy NFT
+const TROPHY_SYMBOL: &str = "GGTROPHY";
+const TROPHY_URI: &str = "https://garbagegrab-game.vercel.app/trophy.json";
+
+// Derive the trophy authority PDA using "trophy" seed and check it matches the
+// passed account. Returns the bump seed.
+fn check_trophy_authority_pda(program_id: &Pubkey, authority: &AccountInfo) -> Result<u8, ProgramError> {
+    let (pda, bump) = TrophyAuthorityPda::find(program_id);
+    if pda != *authority.key {
+        msg!("Invalid trophy authority PDA: expected {}, found {}", pda, authority.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+fn process_mint_trophy(program_id: &Pubkey, accounts: &[AccountInfo], game_mode: GameMode) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let score_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let trophy_mint = next_account_info(accounts_iter)?;
+    let trophy_token_account = next_account_info(accounts_iter)?;
+    let trophy_metadata = next_account_info(accounts_iter)?;
+    let trophy_edition = next_account_info(accounts_iter)?;
+    let trophy_authority = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+    let associated_token_program = next_account_info(accounts_iter)?;
+    let token_metadata_program = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_player_score(program_id, score_account, player, game_mode)?;
+    check_signer(trophy_mint)?;
+    let config = load_config(program_id, config_account)?;
+    check_not_paused(&config)?;
+    let bump = check_trophy_authority_pda(program_id, trophy_authority)?;
+    check_token_program(token_program)?;
+    check_system_program(system_program)?;
+    check_associated_token_program(associated_token_program)?;
+    if *token_metadata_program.key != mpl_token_metadata::ID {
+        msg!("Expected the Token Metadata program, found {}", token_metadata_program.key);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut state = load_score(&score_account.try_borrow_data()?, player.key)?;
+    if !state.trophy_earned(config.trophy_margin) {
+        msg!(
+            "Best score {} has not beaten the last trophy's {} by {}",
+            state.best_score,
+            state.trophy_best,
+            config.trophy_margin
+        );
+        return Err(GarbageGrabError::TrophyNotEarned.into());
+    }
+    let signer_seeds: &[&[u8]] = &[TrophyAuthorityPda::SEED, &[bump]];
+
+    // A zero-decimal mint held by the trophy authority; the master edition below
+    // takes over its authority and caps the supply at the single minted token
+    let space = spl_token::state::Mint::LEN;
+    invoke(
+        &system_instruction::create_account(
+            player.key,
+            trophy_mint.key,
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            token_program.key,
+        ),
+        &[player.clone(), trophy_mint.clone(), system_program.clone()],
+    )?;
+    invoke(
+        &spl_token::instruction::initialize_mint2(token_program.key, trophy_mint.key, trophy_authority.key, None, 0)?,
+        &[trophy_mint.clone(), token_program.clone()],
+    )?;
+    invoke(
+        &spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+            player.key,
+            player.key,
+            trophy_mint.key,
+            token_program.key,
+        ),
+        &[
+            player.clone(),
+            trophy_token_account.clone(),
+            player.clone(),
+            trophy_mint.clone(),
+            system_program.clone(),
+            token_program.clone(),
+            associated_token_program.clone(),
+        ],
+    )?;
+    invoke_signed(
+        &spl_token::instruction::mint_to(
+            token_program.key,
+            trophy_mint.key,
+            trophy_token_account.key,
+            trophy_authority.key,
+            &[],
+            1,
+        )?,
+        &[trophy_mint.clone(), trophy_token_account.clone(), trophy_authority.clone()],
+        &[signer_seeds],
+    )?;
+
+    let trophy_number = state.trophies.checked_add(1).ok_or(GarbageGrabError::ScoreOverflow)?;
+    CreateMetadataAccountV3Cpi::new(
+        token_metadata_program,
+        CreateMetadataAccountV3CpiAccounts {
+            metadata: trophy_metadata,
+            mint: trophy_mint,
+            mint_authority: trophy_authority,
+            payer: player,
+            update_authority: (trophy_authority, true),
+            system_program,
+            rent: None,
+        },
+        CreateMetadataAccountV3InstructionArgs {
+            data: DataV2 {
+                name: format!("Garbage Grab Trophy #{}", trophy_number),
+                symbol: TROPHY_SYMBOL.to_string(),
+                uri: TROPHY_URI.to_string(),
+                seller_fee_basis_points: 0,
+                creators: None,
+                collection: None,
+                uses: None,
+            },
+            is_mutable: false,
+            collection_details: None,
+        },
+    )
+    .invoke_signed(&[signer_seeds])?;
+    CreateMasterEditionV3Cpi::new(
+        token_metadata_program,
+        CreateMasterEditionV3CpiAccounts {
+            edition: trophy_edition,
+            mint: trophy_mint,
+            update_authority: trophy_authority,
+            mint_authority: trophy_authority,
+            payer: player,
+            metadata: trophy_metadata,
+            token_program,
+            system_program,
+            rent: None,
+        },
+        CreateMasterEditionV3InstructionArgs { max_supply: Some(0) },
+    )
+    .invoke_signed(&[signer_seeds])?;
+
+    state.trophies = trophy_number;
+    state.trophy_best = state.best_score;
+    state.pack(&mut score_account.try_borrow_mut_data()?)?;
+    msg!(
+        "Trophy #{} minted to {} for best score {} (mint {})",
+        trophy_number,
+        player.key,
+        state.best_score,
+        trophy_mint.key
+    );
+    Ok(())
+}
+
+// Ensure the passed account is the Token-2022 program, which badge mints live under
+fn check_token_2022_program(token_program: &AccountInfo) -> ProgramResult {
+    if *token_program.key != spl_token_2022::id() {
+        msg!("Expected the Token-2022 program, found {}", token_program.key);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+// Check the passed account is the badge catalog PDA. Returns the bump seed.
+fn check_badge_catalog_pda(program_id: &Pubkey, catalog_account: &AccountInfo) -> Result<u8, ProgramError> {
+    let (pda, bump) = BadgeCatalogPda::find(program_id);
+    if pda != *catalog_account.key {
+        msg!("Invalid badge catalog PDA: expected {}, found {}", pda, catalog_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Check the passed account is the badge authority PDA. Returns the bump seed.
+fn check_badge_authority_pda(program_id: &Pubkey, authority: &AccountInfo) -> Result<u8, ProgramError> {
+    let (pda, bump) = BadgeAuthorityPda::find(program_id);
+    if pda != *authority.key {
+        msg!("Invalid badge authority PDA: expected {}, found {}", pda, authority.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+fn process_add_badge(program_id: &Pubkey, accounts: &[AccountInfo], milestone: BadgeMilestone, threshold: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let catalog_account = next_account_info(accounts_iter)?;
+    let badge_mint = next_account_info(accounts_iter)?;
+    let badge_authority = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    load_config_as_admin(program_id, config_account, admin)?;
+    check_signer(badge_mint)?;
+    let bump = check_badge_catalog_pda(program_id, catalog_account)?;
+    check_badge_authority_pda(program_id, badge_authority)?;
+    check_token_2022_program(token_program)?;
+    check_system_program(system_program)?;
+    if threshold == 0 {
+        msg!("A badge needs a threshold above zero");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // The first badge creates the catalog
+    let mut catalog = if catalog_account.data_is_empty() {
+        let space = BadgeCatalog::space(0);
+        create_pda_account(admin, catalog_account, system_program, space, program_id, &[BadgeCatalogPda::SEED, &[bump]])?;
+        BadgeCatalog { is_initialized: true, ..BadgeCatalog::default() }
+    } else {
+        check_program_owned(program_id, catalog_account)?;
+        BadgeCatalog::unpack(&catalog_account.try_borrow_data()?)?
+    };
+    if catalog.badges.len() >= BadgeCatalog::CAPACITY {
+        msg!("Badge catalog is full");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // A zero-decimal mint held by the badge authority whose tokens can never leave
+    // the account they are minted to
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[ExtensionType::NonTransferable])?;
+    invoke(
+        &system_instruction::create_account(
+            admin.key,
+            badge_mint.key,
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            token_program.key,
+        ),
+        &[admin.clone(), badge_mint.clone(), system_program.clone()],
+    )?;
+    invoke(
+        &spl_token_2022::instruction::initialize_non_transferable_mint(token_program.key, badge_mint.key)?,
+        &[badge_mint.clone(), token_program.clone()],
+    )?;
+    invoke(
+        &spl_token_2022::instruction::initialize_mint2(token_program.key, badge_mint.key, badge_authority.key, None, 0)?,
+        &[badge_mint.clone(), token_program.clone()],
+    )?;
+
+    let badge_id = catalog.badges.len();
+    catalog.badges.push(BadgeDefinition {
+        mint: *badge_mint.key,
+        milestone,
+        threshold,
+    });
+    grow_account(catalog_account, admin, system_program, BadgeCatalog::space(catalog.badges.len()))?;
+    catalog.pack(&mut catalog_account.try_borrow_mut_data()?)?;
+    msg!("Badge {} added for {:?} of {} (mint {})", badge_id, milestone, threshold, badge_mint.key);
+    Ok(())
+}
+
+fn process_claim_badge(program_id: &Pubkey, accounts: &[AccountInfo], badge_id: u8, game_mode: GameMode) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let catalog_account = next_account_info(accounts_iter)?;
+    let score_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let badge_mint = next_account_info(accounts_iter)?;
+    let badge_token_account = next_account_info(accounts_iter)?;
+    let badge_authority = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+    let associated_token_program = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_player_score(program_id, score_account, player, game_mode)?;
+    check_badge_catalog_pda(program_id, catalog_account)?;
+    check_program_owned(program_id, catalog_account)?;
+    let bump = check_badge_authority_pda(program_id, badge_authority)?;
+    check_token_2022_program(token_program)?;
+    check_associated_token_program(associated_token_program)?;
+    check_system_program(system_program)?;
+
+    let catalog = BadgeCatalog::unpack(&catalog_account.try_borrow_data()?)?;
+    let badge = *catalog.get(badge_id).ok_or_else(|| {
+        msg!("Badge {} does not exist", badge_id);
+        ProgramError::InvalidArgument
+    })?;
+    if *badge_mint.key != badge.mint {
+        msg!("Mint {} is not the mint of badge {}", badge_mint.key, badge_id);
+        return Err(GarbageGrabError::InvalidMint.into());
+    }
+    let state = load_score(&score_account.try_borrow_data()?, player.key)?;
+    if !badge.is_reached(&state) {
+        msg!("Player {} has not reached {:?} of {} in {:?}", player.key, badge.milestone, badge.threshold, game_mode);
+        return Err(GarbageGrabError::BadgeNotEarned.into());
+    }
+
+    invoke(
+        &spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+            player.key,
+            player.key,
+            badge_mint.key,
+            token_program.key,
+        ),
+        &[
+            player.clone(),
+            badge_token_account.clone(),
+            player.clone(),
+            badge_mint.clone(),
+            system_program.clone(),
+            token_program.clone(),
+            associated_token_program.clone(),
+        ],
+    )?;
+    // Badges cannot be transferred away, so a held token means it was claimed
+    if token_balance(badge_token_account)? > 0 {
+        msg!("Player {} already holds badge {}", player.key, badge_id);
+        return Err(GarbageGrabError::BadgeAlreadyClaimed.into());
+    }
+    invoke_signed(
+        &spl_token_2022::instruction::mint_to(
+            token_program.key,
+            badge_mint.key,
+            badge_token_account.key,
+            badge_authority.key,
+            &[],
+            1,
+        )?,
+        &[badge_mint.clone(), badge_token_account.clone(), badge_authority.clone()],
+        &[&[BadgeAuthorityPda::SEED, &[bump]]],
+    )?;
+    msg!("Badge {} minted to {}", badge_id, player.key);
+    Ok(())
+}
+
+// Check the passed account is the global stats PDA. Returns the bump seed.
+fn check_global_stats_pda(program_id: &Pubkey, stats_account: &AccountInfo) -> Result<u8, ProgramError> {
+    let (pda, bump) = GlobalStatsPda::find(program_id);
+    if pda != *stats_account.key {
+        msg!("Invalid global stats PDA: expected {}, found {}", pda, stats_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+fn process_initialize_global_stats(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let stats_account = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    load_config_as_admin(program_id, config_account, admin)?;
+    let bump = check_global_stats_pda(program_id, stats_account)?;
+    check_system_program(system_program)?;
+    if !stats_account.data_is_empty() {
+        msg!("Global stats are already initialized");
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+
+    create_pda_account(admin, stats_account, system_program, GlobalStats::LEN, program_id, &[GlobalStatsPda::SEED, &[bump]])?;
+    let stats = GlobalStats { is_initialized: true, bump, ..GlobalStats::default() };
+    stats.pack(&mut stats_account.try_borrow_mut_data()?)?;
+    msg!("Global stats initialized");
+    Ok(())
+}
+
+// Derive the profile PDA using "profile" seed and the player's public key, and
+// check it matches the passed account. Returns the bump seed.
+fn check_profile_pda(program_id: &Pubkey, profile: &AccountInfo, player: &Pubkey) -> Result<u8, ProgramError> {
+    let (pda, bump) = ProfilePda::find(program_id, player);
+    if pda != *profile.key {
+        msg!("Invalid profile PDA: expected {}, found {}", pda, profile.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Derive the name record PDA using "name" seed and the normalized name, and check
+// it matches the passed account. Returns the bump seed.
+fn check_name_record_pda(
+    program_id: &Pubkey,
+    name_record: &AccountInfo,
+    normalized_name: &[u8; PROFILE_NAME_LEN],
+) -> Result<u8, ProgramError> {
+    let (pda, bump) = NameRecordPda::find(program_id, normalized_name);
+    if pda != *name_record.key {
+        msg!("Invalid name record PDA: expected {}, found {}", pda, name_record.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Validate a requested display name, returning the normalized form that keys its record
+fn normalize_name(name: &[u8; PROFILE_NAME_LEN]) -> Result<[u8; PROFILE_NAME_LEN], ProgramError> {
+    normalize_profile_name(name).ok_or_else(|| {
+        msg!("Invalid profile name {:?}", String::from_utf8_lossy(name).trim_end_matches('\0'));
+        GarbageGrabError::InvalidProfileName.into()
+    })
+}
+
+// Create the name record claiming `normalized_name` for `player`, failing if
+// another profile already holds it
+fn claim_name<'a>(
+    program_id: &Pubkey,
+    name_record: &AccountInfo<'a>,
+    player: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    normalized_name: &[u8; PROFILE_NAME_LEN],
+) -> ProgramResult {
+    let bump = check_name_record_pda(program_id, name_record, normalized_name)?;
+    if !name_record.data_is_empty() {
+        msg!("Name is already taken");
+        return Err(GarbageGrabError::NameTaken.into());
+    }
+    let space = NameRecord::LEN;
+    create_pda_account(player, name_record, system_program, space, program_id, &[NameRecordPda::SEED, normalized_name, &[bump]])?;
+    let record = NameRecord {
+        is_initialized: true,
+        owner: *player.key,
+    };
+    record.pack(&mut name_record.try_borrow_mut_data()?)
+}
+
+fn process_create_profile(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    name: [u8; PROFILE_NAME_LEN],
+    avatar_mint: Pubkey,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let profile = next_account_info(accounts_iter)?;
+    let name_record = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_signer(player)?;
+    let bump = check_profile_pda(program_id, profile, player.key)?;
+    check_system_program(system_program)?;
+    if !profile.data_is_empty() {
+        msg!("Profile already exists for {}", player.key);
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+    let normalized_name = normalize_name(&name)?;
+    claim_name(program_id, name_record, player, system_program, &normalized_name)?;
+
+    let space = PlayerProfile::LEN;
+    create_pda_account(player, profile, system_program, space, program_id, &[ProfilePda::SEED, player.key.as_ref(), &[bump]])?;
+    let state = PlayerProfile {
+        is_initialized: true,
+        player: *player.key,
+        name,
+        avatar_mint,
+        created_ts: Clock::get()?.unix_timestamp,
+        referral_count: 0,
+        referral_points: 0,
+        follower_count: 0,
+        region: NO_REGION,
+        xp: 0,
+        level: 0,
+        unlocks: 0,
+        xp_day: 0,
+        day_xp: 0,
+    };
+    state.pack(&mut profile.try_borrow_mut_data()?)?;
+    msg!("Profile {} created for {}", state.display_name(), player.key);
+    Ok(())
+}
+
+fn process_update_profile(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    name: Option<[u8; PROFILE_NAME_LEN]>,
+    avatar_mint: Option<Pubkey>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let profile = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let current_name_record = next_account_info(accounts_iter)?;
+    let new_name_record = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_signer(player)?;
+    check_profile_pda(program_id, profile, player.key)?;
+    check_program_owned(program_id, profile)?;
+    let mut state = PlayerProfile::unpack(&profile.try_borrow_data()?)?;
+    if !state.is_initialized() {
+        msg!("Profile not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+
+    if let Some(name) = name {
+        let normalized_name = normalize_name(&name)?;
+        let current_name = normalize_name(&state.name)?;
+        // A change of letter case keeps the same record
+        if normalized_name != current_name {
+            check_system_program(system_program)?;
+            claim_name(program_id, new_name_record, player, system_program, &normalized_name)?;
+
+            check_name_record_pda(program_id, current_name_record, &current_name)?;
+            check_program_owned(program_id, current_name_record)?;
+            let reclaimed = current_name_record.lamports();
+            **player.try_borrow_mut_lamports()? = player
+                .lamports()
+                .checked_add(reclaimed)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            **current_name_record.try_borrow_mut_lamports()? = 0;
+            current_name_record.try_borrow_mut_data()?.fill(0);
+        }
+        state.name = name;
+    }
+    if let Some(avatar_mint) = avatar_mint {
+        state.avatar_mint = avatar_mint;
+    }
+
+    state.pack(&mut profile.try_borrow_mut_data()?)?;
+    msg!("Profile {} updated for {}", state.display_name(), player.key);
+    Ok(())
+}
+
+// Derive the follow edge PDA using "follow" seed, the follower and the followed
+// player, and check it matches the passed account. Returns the bump seed.
+fn check_follow_pda(program_id: &Pubkey, follow: &AccountInfo, follower: &Pubkey, followed: &Pubkey) -> Result<u8, ProgramError> {
+    let (pda, bump) = FollowPda::find(program_id, follower, followed);
+    if pda != *follow.key {
+        msg!("Invalid follow PDA: expected {}, found {}", pda, follow.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Load the initialized profile of `player`
+fn load_profile(program_id: &Pubkey, profile: &AccountInfo, player: &Pubkey) -> Result<PlayerProfile, ProgramError> {
+    check_profile_pda(program_id, profile, player)?;
+    check_program_owned(program_id, profile)?;
+    let state = PlayerProfile::unpack(&profile.try_borrow_data()?)?;
+    if !state.is_initialized() {
+        msg!("Profile not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+    Ok(state)
+}
+
+fn process_follow(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let follow_account = next_account_info(accounts_iter)?;
+    let follower = next_account_info(accounts_iter)?;
+    let followed = next_account_info(accounts_iter)?;
+    let followed_profile = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_signer(follower)?;
+    if follower.key == followed.key {
+        msg!("Player {} cannot follow themselves", follower.key);
+        return Err(GarbageGrabError::CannotFollowSelf.into());
+    }
+    let bump = check_follow_pda(program_id, follow_account, follower.key, followed.key)?;
+    check_system_program(system_program)?;
+    let mut profile = load_profile(program_id, followed_profile, followed.key)?;
+    if !follow_account.data_is_empty() {
+        msg!("{} already follows {}", follower.key, followed.key);
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+
+    let space = Follow::LEN;
+    create_pda_account(follower, follow_account, system_program, space, program_id, &[FollowPda::SEED, follower.key.as_ref(), followed.key.as_ref(), &[bump]])?;
+    let edge = Follow {
+        is_initialized: true,
+        follower: *follower.key,
+        followed: *followed.key,
+        created_ts: Clock::get()?.unix_timestamp,
+    };
+    edge.pack(&mut follow_account.try_borrow_mut_data()?)?;
+
+    profile.follower_count = profile.follower_count.saturating_add(1);
+    profile.pack(&mut followed_profile.try_borrow_mut_data()?)?;
+    msg!("{} followed {} ({} followers)", follower.key, followed.key, profile.follower_count);
+    Ok(())
+}
+
+fn process_unfollow(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let follow_account = next_account_info(accounts_iter)?;
+    let follower = next_account_info(accounts_iter)?;
+    let followed = next_account_info(accounts_iter)?;
+    let followed_profile = next_account_info(accounts_iter)?;
+
+    check_signer(follower)?;
+    check_follow_pda(program_id, follow_account, follower.key, followed.key)?;
+    check_program_owned(program_id, follow_account)?;
+    let edge = Follow::unpack(&follow_account.try_borrow_data()?)?;
+    if !edge.is_initialized() {
+        msg!("{} does not follow {}", follower.key, followed.key);
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+    let mut profile = load_profile(program_id, followed_profile, followed.key)?;
+    profile.follower_count = profile.follower_count.saturating_sub(1);
+    profile.pack(&mut followed_profile.try_borrow_mut_data()?)?;
+
+    // Move every lamport back to the follower; the runtime reclaims the empty account
+    let reclaimed = follow_account.lamports();
+    **follower.try_borrow_mut_lamports()? = follower
+        .lamports()
+        .checked_add(reclaimed)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **follow_account.try_borrow_mut_lamports()? = 0;
+    follow_account.try_borrow_mut_data()?.fill(0);
+    msg!("{} unfollowed {} ({} followers)", follower.key, followed.key, profile.follower_count);
+    Ok(())
+}
+
+fn check_region(region: &[u8; 2]) -> ProgramResult {
+    if !is_valid_region(region) {
+        msg!("Invalid region code {:?}", region);
+        return Err(GarbageGrabError::InvalidRegion.into());
+    }
+    Ok(())
+}
+
+fn process_set_region(program_id: &Pubkey, accounts: &[AccountInfo], region: [u8; 2]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let profile_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+
+    check_signer(player)?;
+    check_region(&region)?;
+    let mut profile = load_profile(program_id, profile_account, player.key)?;
+    if profile.has_region() {
+        msg!("Player {} already set region {}", player.key, String::from_utf8_lossy(&profile.region));
+        return Err(GarbageGrabError::RegionAlreadySet.into());
+    }
+
+    profile.region = region;
+    profile.pack(&mut profile_account.try_borrow_mut_data()?)?;
+    msg!("Player {} set region {}", player.key, String::from_utf8_lossy(&region));
+    Ok(())
+}
+
+// NO_REGION clears the region, letting the player pick again
+fn process_admin_set_region(program_id: &Pubkey, accounts: &[AccountInfo], region: [u8; 2]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let profile_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+
+    load_config_as_admin(program_id, config_account, admin)?;
+    if region != NO_REGION {
+        check_region(&region)?;
+    }
+    let mut profile = load_profile(program_id, profile_account, player.key)?;
+    let previous = profile.region;
+    profile.region = region;
+    profile.pack(&mut profile_account.try_borrow_mut_data()?)?;
+    msg!("Admin {} corrected region of {}: {:?} -> {:?}", admin.key, player.key, String::from_utf8_lossy(&previous), String::from_utf8_lossy(&region));
+    Ok(())
+}
+
+// Check the passed account is the leaderboard PDA of `region`. Returns the bump seed.
+fn check_regional_leaderboard_pda(program_id: &Pubkey, board_account: &AccountInfo, region: &[u8; 2]) -> Result<u8, ProgramError> {
+    let (pda, bump) = RegionalLeaderboardPda::find(program_id, region);
+    if pda != *board_account.key {
+        msg!("Invalid regional leaderboard PDA: expected {}, found {}", pda, board_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+fn process_initialize_regional_leaderboard(program_id: &Pubkey, accounts: &[AccountInfo], region: [u8; 2]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let board_account = next_account_info(accounts_iter)?;
+    let payer = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_signer(payer)?;
+    check_region(&region)?;
+    let bump = check_regional_leaderboard_pda(program_id, board_account, &region)?;
+    check_system_program(system_program)?;
+    if !board_account.data_is_empty() {
+        msg!("Leaderboard of region {} already initialized", String::from_utf8_lossy(&region));
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+
+    let space = Leaderboard::LEN;
+    create_pda_account(payer, board_account, system_program, space, program_id, &[RegionalLeaderboardPda::SEED, &region, &[bump]])?;
+    Leaderboard::init(&mut board_account.try_borrow_mut_data()?)?;
+    msg!("Leaderboard of region {} initialized", String::from_utf8_lossy(&region));
+    Ok(())
+}
+
+fn process_submit_to_regional_leaderboard(program_id: &Pubkey, accounts: &[AccountInfo], game_mode: GameMode) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let board_account = next_account_info(accounts_iter)?;
+    let score_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let profile_account = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let ban_list_account = next_account_info(accounts_iter)?;
+
+    check_signer(player)?;
+    check_not_paused(&load_config(program_id, config_account)?)?;
+    check_not_banned(program_id, ban_list_account, player.key)?;
+    let profile = load_profile(program_id, profile_account, player.key)?;
+    if !profile.has_region() {
+        msg!("Player {} has not set a region", player.key);
+        return Err(GarbageGrabError::InvalidRegion.into());
+    }
+    check_regional_leaderboard_pda(program_id, board_account, &profile.region)?;
+    check_score_pda(program_id, score_account, player.key, game_mode)?;
+    check_program_owned(program_id, board_account)?;
+    check_program_owned(program_id, score_account)?;
+
+    let best_score = load_score(&score_account.try_borrow_data()?, player.key)?.best_score;
+    let mut board_data = board_account.try_borrow_mut_data()?;
+    let board = load_leaderboard_mut(&mut board_data)?;
+    match board.submit(*player.key, best_score) {
+        Some(rank) => msg!("Player {} ranked #{} in region {} with {}", player.key, rank + 1, String::from_utf8_lossy(&profile.region), best_score),
+        None => msg!("Score {} did not place in region {}", best_score, String::from_utf8_lossy(&profile.region)),
+    }
+    Ok(())
+}
+
+fn process_credit_referral(program_id: &Pubkey, accounts: &[AccountInfo], game_mode: GameMode) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let score_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let referrer_profile = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+
+    check_program_owned(program_id, score_account)?;
+    check_score_pda(program_id, score_account, player.key, game_mode)?;
+    let config = load_config(program_id, config_account)?;
+    check_not_paused(&config)?;
+
+    let mut score_data = score_account.try_borrow_mut_data()?;
+    let mut state = load_score(&score_data, player.key)?;
+    if !state.referral_earned(config.referral_threshold) {
+        msg!(
+            "Player {} has no uncredited referral at best score {} (threshold {})",
+            player.key,
+            state.best_score,
+            config.referral_threshold
+        );
+        return Err(GarbageGrabError::ReferralNotEarned.into());
+    }
+
+    check_profile_pda(program_id, referrer_profile, &state.referrer)?;
+    check_program_owned(program_id, referrer_profile)?;
+    let mut profile = PlayerProfile::unpack(&referrer_profile.try_borrow_data()?)?;
+    if !profile.is_initialized() {
+        msg!("Referrer profile not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+    profile.referral_count = profile.referral_count.saturating_add(1);
+    profile.referral_points = profile.referral_points.saturating_add(config.points_per_referral);
+    profile.pack(&mut referrer_profile.try_borrow_mut_data()?)?;
+
+    state.referral_credited = true;
+    state.pack(&mut score_data)?;
+    msg!(
+        "Referrer {} credited for {}: {} referrals, {} points",
+        state.referrer,
+        player.key,
+        profile.referral_count,
+        profile.referral_points
+    );
+    Ok(())
+}
+
+// Derive the guild PDA using "guild" seed and the guild id, and check it matches
+// the passed account. Returns the bump seed.
+fn check_guild_pda(program_id: &Pubkey, guild_account: &AccountInfo, guild_id: u64) -> Result<u8, ProgramError> {
+    let (pda, bump) = GuildPda::find(program_id, guild_id);
+    if pda != *guild_account.key {
+        msg!("Invalid guild PDA: expected {}, found {}", pda, guild_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+fn load_guild(program_id: &Pubkey, guild_account: &AccountInfo) -> Result<Guild, ProgramError> {
+    check_program_owned(program_id, guild_account)?;
+    let guild = Guild::unpack(&guild_account.try_borrow_data()?)?;
+    if !guild.is_initialized() {
+        msg!("Guild not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+    // Re-derive the address from the stored id so a forged account cannot stand in
+    check_guild_pda(program_id, guild_account, guild.guild_id)?;
+    Ok(guild)
+}
+
+// Derive the guild membership PDA using "guild_member" seed and the player's public
+// key, and check it matches the passed account. Returns the bump seed.
+fn check_guild_member_pda(program_id: &Pubkey, member_account: &AccountInfo, player: &Pubkey) -> Result<u8, ProgramError> {
+    let (pda, bump) = GuildMemberPda::find(program_id, player);
+    if pda != *member_account.key {
+        msg!("Invalid guild member PDA: expected {}, found {}", pda, member_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Load the player's membership and check it belongs to `guild_account`
+fn load_guild_member(
+    program_id: &Pubkey,
+    member_account: &AccountInfo,
+    player: &Pubkey,
+    guild_account: &AccountInfo,
+) -> Result<GuildMember, ProgramError> {
+    check_guild_member_pda(program_id, member_account, player)?;
+    check_program_owned(program_id, member_account)?;
+    let member = GuildMember::unpack(&member_account.try_borrow_data()?)?;
+    if !member.is_initialized() || member.guild != *guild_account.key {
+        msg!("Player {} is not a member of guild {}", player, guild_account.key);
+        return Err(GarbageGrabError::NotGuildMember.into());
+    }
+    Ok(member)
+}
+
+// Add points the player just earned to their guild's total when the optional
+// guild member and guild accounts follow the instruction's own accounts
+fn credit_guild(
+    program_id: &Pubkey,
+    accounts_iter: &mut std::slice::Iter<AccountInfo>,
+    player: &Pubkey,
+    credited: u64,
+) -> ProgramResult {
+    let Some(member_account) = accounts_iter.next() else {
+        return Ok(());
+    };
+    let guild_account = next_account_info(accounts_iter)?;
+
+    let mut member = load_guild_member(program_id, member_account, player, guild_account)?;
+    let mut guild = load_guild(program_id, guild_account)?;
+    member.contributed = member.contributed.saturating_add(credited);
+    guild.total_score = guild.total_score.saturating_add(credited);
+    member.pack(&mut member_account.try_borrow_mut_data()?)?;
+    guild.pack(&mut guild_account.try_borrow_mut_data()?)?;
+    msg!("Guild {} total score: {}", guild.guild_id, guild.total_score);
+    Ok(())
+}
+
+// Create the membership account tying `player` to `guild_account`
+fn create_guild_member<'a>(
+    program_id: &Pubkey,
+    member_account: &AccountInfo<'a>,
+    player: &AccountInfo<'a>,
+    guild_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> ProgramResult {
+    let bump = check_guild_member_pda(program_id, member_account, player.key)?;
+    if !member_account.data_is_empty() {
+        msg!("Player {} already belongs to a guild", player.key);
+        return Err(GarbageGrabError::AlreadyInGuild.into());
+    }
+    let space = GuildMember::LEN;
+    create_pda_account(player, member_account, system_program, space, program_id, &[GuildMemberPda::SEED, player.key.as_ref(), &[bump]])?;
+    let member = GuildMember {
+        is_initialized: true,
+        player: *player.key,
+        guild: *guild_account.key,
+        contributed: 0,
+    };
+    member.pack(&mut member_account.try_borrow_mut_data()?)
+}
+
+fn process_create_guild(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    guild_id: u64,
+    name: [u8; PROFILE_NAME_LEN],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let guild_account = next_account_info(accounts_iter)?;
+    let member_account = next_account_info(accounts_iter)?;
+    let leader = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_signer(leader)?;
+    let bump = check_guild_pda(program_id, guild_account, guild_id)?;
+    check_system_program(system_program)?;
+    if !guild_account.data_is_empty() {
+        msg!("Guild {} already exists", guild_id);
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+    create_guild_member(program_id, member_account, leader, guild_account, system_program)?;
+
+    let space = Guild::LEN;
+    create_pda_account(leader, guild_account, system_program, space, program_id, &[GuildPda::SEED, &guild_id.to_le_bytes(), &[bump]])?;
+    let guild = Guild {
+        is_initialized: true,
+        guild_id,
+        leader: *leader.key,
+        name,
+        member_count: 1,
+        total_score: 0,
+    };
+    guild.pack(&mut guild_account.try_borrow_mut_data()?)?;
+    msg!("Guild {} created by {}", guild_id, leader.key);
+    Ok(())
+}
+
+fn process_join_guild(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let guild_account = next_account_info(accounts_iter)?;
+    let member_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_signer(player)?;
+    check_system_program(system_program)?;
+    let mut guild = load_guild(program_id, guild_account)?;
+    if guild.is_full() {
+        msg!("Guild {} is full", guild.guild_id);
+        return Err(GarbageGrabError::GuildFull.into());
+    }
+    create_guild_member(program_id, member_account, player, guild_account, system_program)?;
+
+    guild.member_count += 1;
+    guild.pack(&mut guild_account.try_borrow_mut_data()?)?;
+    msg!("Player {} joined guild {} ({} members)", player.key, guild.guild_id, guild.member_count);
+    Ok(())
+}
+
+fn process_leave_guild(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let guild_account = next_account_info(accounts_iter)?;
+    let member_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+
+    check_signer(player)?;
+    let mut guild = load_guild(program_id, guild_account)?;
+    let member = load_guild_member(program_id, member_account, player.key, guild_account)?;
+    if guild.leader == *player.key && guild.member_count > 1 {
+        msg!("The guild leader can only leave once every other member has");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    guild.member_count = guild.member_count.saturating_sub(1);
+    guild.total_score = guild.total_score.saturating_sub(member.contributed);
+    guild.pack(&mut guild_account.try_borrow_mut_data()?)?;
+
+    // Move every lamport back to the player; the runtime reclaims the empty account
+    let reclaimed = member_account.lamports();
+    **player.try_borrow_mut_lamports()? = player
+        .lamports()
+        .checked_add(reclaimed)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **member_account.try_borrow_mut_lamports()? = 0;
+    member_account.try_borrow_mut_data()?.fill(0);
+    msg!("Player {} left guild {} ({} members)", player.key, guild.guild_id, guild.member_count);
+    Ok(())
+}
+
+fn check_guild_leaderboard_pda(program_id: &Pubkey, leaderboard_account: &AccountInfo) -> Result<u8, ProgramError> {
+    let (pda, bump) = GuildLeaderboardPda::find(program_id);
+    if pda != *leaderboard_account.key {
+        msg!("Invalid guild leaderboard PDA: expected {}, found {}", pda, leaderboard_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+fn process_initialize_guild_leaderboard(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let leaderboard_account = next_account_info(accounts_iter)?;
+    let payer = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_signer(payer)?;
+    let bump = check_guild_leaderboard_pda(program_id, leaderboard_account)?;
+    check_system_program(system_program)?;
+    if !leaderboard_account.data_is_empty() {
+        msg!("Guild leaderboard already initialized");
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+
+    let space = Leaderboard::LEN;
+    create_pda_account(payer, leaderboard_account, system_program, space, program_id, &[GuildLeaderboardPda::SEED, &[bump]])?;
+    Leaderboard::init(&mut leaderboard_account.try_borrow_mut_data()?)?;
+    msg!("Guild leaderboard initialized");
+    Ok(())
+}
+
+fn process_submit_guild_to_leaderboard(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let leaderboard_account = next_account_info(accounts_iter)?;
+    let guild_account = next_account_info(accounts_iter)?;
+
+    check_guild_leaderboard_pda(program_id, leaderboard_account)?;
+    check_program_owned(program_id, leaderboard_account)?;
+    let guild = load_guild(program_id, guild_account)?;
+
+    let mut board_data = leaderboard_account.try_borrow_mut_data()?;
+    let board = Leaderboard::load_mut(&mut board_data)?;
+    if !board.is_initialized() {
+        msg!("Guild leaderboard not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+    match board.submit(*guild_account.key, guild.total_score) {
+        Some(rank) => msg!("Guild {} ranked #{} with {}", guild.guild_id, rank + 1, guild.total_score),
+        None => msg!("Guild total {} did not place on the leaderboard", guild.total_score),
+    }
+    Ok(())
+}
+
+// Derive the team PDA from "team" seed and the sorted members, and check it matches
+// the passed account. Returns the bump seed.
+fn check_team_pda(program_id: &Pubkey, team_account: &AccountInfo, members: &[Pubkey]) -> Result<u8, ProgramError> {
+    let (pda, bump) = TeamPda::find(program_id, members);
+    if pda != *team_account.key {
+        msg!("Invalid team PDA: expected {}, found {}", pda, team_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+fn process_create_team(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let team_account = next_account_info(accounts_iter)?;
+    let payer = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let member_accounts = accounts_iter.as_slice();
+
+    check_signer(payer)?;
+    check_system_program(system_program)?;
+    if member_accounts.iter().any(|m| !m.is_signer) {
+        msg!("Every team member must sign");
+        return Err(GarbageGrabError::InvalidTeam.into());
+    }
+    let keys: Vec<Pubkey> = member_accounts.iter().map(|m| *m.key).collect();
+    let members = state::sort_team_members(&keys).ok_or_else(|| {
+        msg!("Teams need 2-{} distinct members, found {}", TEAM_MAX_MEMBERS, keys.len());
+        ProgramError::from(GarbageGrabError::InvalidTeam)
+    })?;
+    let bump = check_team_pda(program_id, team_account, &members)?;
+    if !team_account.data_is_empty() {
+        msg!("Team already exists");
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+
+    let space = Team::LEN;
+    let bump_seed = [bump];
+    let mut seeds: Vec<&[u8]> = vec![TeamPda::SEED];
+    seeds.extend(members.iter().map(|m| m.as_ref()));
+    seeds.push(&bump_seed);
+    create_pda_account(payer, team_account, system_program, space, program_id, &seeds)?;
+
+    let mut team = Team {
+        is_initialized: true,
+        member_count: members.len() as u8,
+        ..Team::default()
+    };
+    team.members[..members.len()].copy_from_slice(&members);
+    team.pack(&mut team_account.try_borrow_mut_data()?)?;
+    msg!("Team {} created with {} members", team_account.key, members.len());
+    Ok(())
+}
+
+fn process_update_team_score(program_id: &Pubkey, accounts: &[AccountInfo], increment: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let team_account = next_account_info(accounts_iter)?;
+    let member = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+
+    check_signer(member)?;
+    check_program_owned(program_id, team_account)?;
+    let config = load_config(program_id, config_account)?;
+    check_not_paused(&config)?;
+
+    let mut team_data = team_account.try_borrow_mut_data()?;
+    let mut team = Team::unpack(&team_data)?;
+    if !team.is_initialized() {
+        msg!("Team not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+    check_team_pda(program_id, team_account, team.members())?;
+    if !team.is_member(member.key) {
+        msg!("{} is not a member of team {}", member.key, team_account.key);
+        return Err(GarbageGrabError::NotTeamMember.into());
+    }
+
+    // The team shares one cooldown and the per-update and total caps of a player
+    let now = Clock::get()?.unix_timestamp;
+    let elapsed = now.saturating_sub(team.last_update_ts);
+    if elapsed < i64::from(config.update_cooldown_secs) {
+        msg!("Update too soon: {}s since the last one (cooldown {}s)", elapsed, config.update_cooldown_secs);
+        return Err(GarbageGrabError::UpdateTooSoon.into());
+    }
+    if increment > config.max_increment {
+        msg!("Increment too large: {} (max {})", increment, config.max_increment);
+        return Err(GarbageGrabError::IncrementTooLarge.into());
+    }
+    let new_score = team.score.checked_add(increment).ok_or(GarbageGrabError::ScoreOverflow)?;
+    if new_score > config.max_score {
+        msg!("Score too large: {} (max {})", new_score, config.max_score);
+        return Err(GarbageGrabError::ScoreTooLarge.into());
+    }
+
+    team.score = new_score;
+    team.best_score = team.best_score.max(new_score);
+    team.last_update_ts = now;
+    team.pack(&mut team_data)?;
+    msg!("Team score updated by {}: {}", member.key, new_score);
+    Ok(())
+}
+
+// Approve `delegate` to submit score updates for the player, or clear the delegate
+// when it is the default pubkey
+fn process_set_delegate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    game_mode: GameMode,
+    delegate: Pubkey,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let score_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+
+    check_player_score(program_id, score_account, player, game_mode)?;
+    let mut score_data = score_account.try_borrow_mut_data()?;
+    let mut state = load_score(&score_data, player.key)?;
+    state.delegate = delegate;
+    state.pack(&mut score_data)?;
+    if delegate == Pubkey::default() {
+        msg!("Player {} revoked their delegate", player.key);
+    } else {
+        msg!("Player {} approved delegate {}", player.key, delegate);
+    }
+    Ok(())
+}
+
+// Derive the session PDA from "session" seed, the player and the session key, and
+// check it matches the passed account. Returns the bump seed.
+fn check_session_pda(
+    program_id: &Pubkey,
+    session_account: &AccountInfo,
+    player: &Pubkey,
+    session_key: &Pubkey,
+) -> Result<u8, ProgramError> {
+    let (pda, bump) = SessionPda::find(program_id, player, session_key);
+    if pda != *session_account.key {
+        msg!("Invalid session PDA: expected {}, found {}", pda, session_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Load the player's session token for `session_key`
+fn load_session(
+    program_id: &Pubkey,
+    session_account: &AccountInfo,
+    player: &Pubkey,
+    session_key: &Pubkey,
+) -> Result<SessionToken, ProgramError> {
+    check_session_pda(program_id, session_account, player, session_key)?;
+    check_program_owned(program_id, session_account)?;
+    let session = SessionToken::unpack(&session_account.try_borrow_data()?)?;
+    if !session.is_initialized() {
+        msg!("Session not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+    Ok(session)
+}
+
+fn process_create_session(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    session_key: Pubkey,
+    expiry_slot: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let session_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_signer(player)?;
+    check_system_program(system_program)?;
+    let bump = check_session_pda(program_id, session_account, player.key, &session_key)?;
+    if !session_account.data_is_empty() {
+        msg!("Session already exists");
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+    let slot = Clock::get()?.slot;
+    if expiry_slot <= slot {
+        msg!("Session would expire at slot {}, which is not after the current slot {}", expiry_slot, slot);
+        return Err(GarbageGrabError::SessionExpired.into());
+    }
+
+    let space = SessionToken::LEN;
+    create_pda_account(player, session_account, system_program, space, program_id, &[SessionPda::SEED, player.key.as_ref(), session_key.as_ref(), &[bump]])?;
+
+    let session = SessionToken {
+        is_initialized: true,
+        player: *player.key,
+        session_key,
+        expiry_slot,
+    };
+    session.pack(&mut session_account.try_borrow_mut_data()?)?;
+    msg!("Player {} opened session {} until slot {}", player.key, session_key, expiry_slot);
+    Ok(())
+}
+
+fn process_revoke_session(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let session_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+
+    check_signer(player)?;
+    check_program_owned(program_id, session_account)?;
+    let session = SessionToken::unpack(&session_account.try_borrow_data()?)?;
+    if !session.is_initialized() || session.player != *player.key {
+        msg!("Session {} does not belong to {}", session_account.key, player.key);
+        return Err(GarbageGrabError::Unauthorized.into());
+    }
+    check_session_pda(program_id, session_account, player.key, &session.session_key)?;
+
+    // Move every lamport back to the player; the runtime reclaims the empty account
+    let reclaimed = session_account.lamports();
+    **player.try_borrow_mut_lamports()? = player
+        .lamports()
+        .checked_add(reclaimed)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **session_account.try_borrow_mut_lamports()? = 0;
+    session_account.try_borrow_mut_data()?.fill(0);
+    msg!("Player {} revoked session {}", player.key, session.session_key);
+    Ok(())
+}
+
+fn process_update_score_with_session(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    game_mode: GameMode,
+    increment: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let score_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let session_key = next_account_info(accounts_iter)?;
+    let session_account = next_account_info(accounts_iter)?;
+    let season_account = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let ban_list_account = next_account_info(accounts_iter)?;
+
+    check_signer(session_key)?;
+    check_score_pda(program_id, score_account, player.key, game_mode)?;
+    check_program_owned(program_id, score_account)?;
+    let session = load_session(program_id, session_account, player.key, session_key.key)?;
+    let slot = Clock::get()?.slot;
+    if !session.is_active(slot) {
+        msg!("Session {} expired at slot {} (now {})", session_key.key, session.expiry_slot, slot);
+        return Err(GarbageGrabError::SessionExpired.into());
+    }
+
+    let config = load_config(program_id, config_account)?;
+    check_attestation_not_required(&config)?;
+    let credited = credit_increment(program_id, score_account, player.key, season_account, ban_list_account, &config, increment, None, MAX_BPS)?;
+    credit_guild(program_id, accounts_iter, player.key, credited)
+}
+
+// Derive the history PDA using "history" seed, player's public key and game mode,
+// and check it matches the passed account. Returns the bump seed.
+fn check_history_pda(
+    program_id: &Pubkey,
+    history_account: &AccountInfo,
+    player: &Pubkey,
+    game_mode: GameMode,
+) -> Result<u8, ProgramError> {
+    let (pda, bump) = HistoryPda::find(program_id, player, game_mode);
+    if pda != *history_account.key {
+        msg!("Invalid history PDA: expected {}, found {}", pda, history_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Append a submitted run to the player's history, dropping the oldest runs once full
+fn record_run(
+    program_id: &Pubkey,
+    history_account: &AccountInfo,
+    player: &Pubkey,
+    game_mode: GameMode,
+    run_score: u64,
+) -> ProgramResult {
+    check_history_pda(program_id, history_account, player, game_mode)?;
+    check_program_owned(program_id, history_account)?;
+    let mut data = history_account.try_borrow_mut_data()?;
+    let mut history = ScoreHistory::unpack(&data)?;
+    if !history.is_initialized() {
+        msg!("History not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+    let record = RunRecord {
+        score: run_score,
+        timestamp: Clock::get()?.unix_timestamp,
+    };
+    history.push(record, ScoreHistory::capacity(data.len()));
+    history.pack(&mut data)
+}
+
+fn process_initialize_history(program_id: &Pubkey, accounts: &[AccountInfo], game_mode: GameMode) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let history_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_signer(player)?;
+    check_system_program(system_program)?;
+    let bump = check_history_pda(program_id, history_account, player.key, game_mode)?;
+    if !history_account.data_is_empty() {
+        msg!("History already initialized");
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+
+    let space = ScoreHistory::space(state::HISTORY_CAPACITY);
+    create_pda_account(player, history_account, system_program, space, program_id, &[HistoryPda::SEED, player.key.as_ref(), &game_mode.seed(), &[bump]])?;
+
+    let history = ScoreHistory {
+        is_initialized: true,
+        player: *player.key,
+        game_mode,
+        runs: Vec::new(),
+    };
+    history.pack(&mut history_account.try_borrow_mut_data()?)?;
+    msg!("Score history initialized for player {} in mode {:?}", player.key, game_mode);
+    Ok(())
+}
+
+// Grow one of the player's own score, profile or history accounts. The account
+// type is read from its discriminator and the address re-derived from its fields.
+fn process_realloc(program_id: &Pubkey, accounts: &[AccountInfo], new_len: u32) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_signer(player)?;
+    check_system_program(system_program)?;
+    check_program_owned(program_id, account)?;
+
+    let owner = {
+        let data = account.try_borrow_data()?;
+        if data.starts_with(&ScoreAccount::DISCRIMINATOR) {
+            let state = ScoreAccount::unpack(&data)?;
+            check_score_pda(program_id, account, &state.player, state.game_mode)?;
+            state.player
+        } else if data.starts_with(&PlayerProfile::DISCRIMINATOR) {
+            let profile = PlayerProfile::unpack(&data)?;
+            check_profile_pda(program_id, account, &profile.player)?;
+            profile.player
+        } else if data.starts_with(&ScoreHistory::DISCRIMINATOR) {
+            let history = ScoreHistory::unpack(&data)?;
+            check_history_pda(program_id, account, &history.player, history.game_mode)?;
+            history.player
+        } else {
+            msg!("Account {} cannot be reallocated", account.key);
+            return Err(ProgramError::InvalidAccountData);
+        }
+    };
+    if owner != *player.key {
+        msg!("Account {} belongs to {}, not {}", account.key, owner, player.key);
+        return Err(GarbageGrabError::Unauthorized.into());
+    }
+
+    let old_len = account.data_len();
+    let new_len = new_len as usize;
+    if new_len <= old_len {
+        msg!("Account is already {} bytes, which is not less than {}", old_len, new_len);
+        return Err(ProgramError::InvalidArgument);
+    }
+    grow_account(account, player, system_program, new_len)?;
+    msg!("Account {} grown from {} to {} bytes", account.key, old_len, new_len);
+    Ok(())
+}
+
+// Check the passed accounts are an airdrop's PDA and claimed bitmap PDA. Returns
+// both bump seeds.
+fn check_airdrop_pdas(
+    program_id: &Pubkey,
+    airdrop_account: &AccountInfo,
+    claims_account: &AccountInfo,
+    airdrop_id: u64,
+) -> Result<(u8, u8), ProgramError> {
+    let (pda, bump) = AirdropPda::find(program_id, airdrop_id);
+    if pda != *airdrop_account.key {
+        msg!("Invalid airdrop PDA: expected {}, found {}", pda, airdrop_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    let (claims_pda, claims_bump) = AirdropClaimsPda::find(program_id, airdrop_id);
+    if claims_pda != *claims_account.key {
+        msg!("Invalid airdrop claims PDA: expected {}, found {}", claims_pda, claims_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok((bump, claims_bump))
+}
+
+fn process_set_reward_root(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    airdrop_id: u64,
+    merkle_root: [u8; 32],
+    max_claims: u32,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let airdrop_account = next_account_info(accounts_iter)?;
+    let claims_account = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    load_config_as_admin(program_id, config_account, admin)?;
+    check_system_program(system_program)?;
+    let (bump, claims_bump) = check_airdrop_pdas(program_id, airdrop_account, claims_account, airdrop_id)?;
+    if max_claims == 0 {
+        msg!("An airdrop needs at least one claim");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if !airdrop_account.data_is_empty() {
+        // Replacing the root is only safe before anyone has claimed against it
+        check_program_owned(program_id, airdrop_account)?;
+        let mut data = airdrop_account.try_borrow_mut_data()?;
+        let mut airdrop = Airdrop::unpack(&data)?;
+        if airdrop.claimed_count > 0 || airdrop.max_claims != max_claims {
+            msg!(
+                "Airdrop {} has {} claims out of {}; its root can no longer change",
+                airdrop_id,
+                airdrop.claimed_count,
+                airdrop.max_claims
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        airdrop.merkle_root = merkle_root;
+        airdrop.pack(&mut data)?;
+        msg!("Airdrop {} root replaced", airdrop_id);
+        return Ok(());
+    }
+    if !claims_account.data_is_empty() {
+        msg!("Airdrop claims already initialized");
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+
+    let id = airdrop_id.to_le_bytes();
+    create_pda_account(admin, airdrop_account, system_program, Airdrop::LEN, program_id, &[AirdropPda::SEED, &id, &[bump]])?;
+    let space = AirdropClaims::space(max_claims);
+    create_pda_account(admin, claims_account, system_program, space, program_id, &[AirdropClaimsPda::SEED, &id, &[claims_bump]])?;
+
+    let airdrop = Airdrop {
+        is_initialized: true,
+        airdrop_id,
+        merkle_root,
+        max_claims,
+        claimed_count: 0,
+        total_claimed: 0,
+    };
+    airdrop.pack(&mut airdrop_account.try_borrow_mut_data()?)?;
+    let claims = AirdropClaims {
+        is_initialized: true,
+        airdrop_id,
+        bits: vec![0; AirdropClaims::bitmap_len(max_claims)],
+    };
+    claims.pack(&mut claims_account.try_borrow_mut_data()?)?;
+    msg!("Airdrop {} published for up to {} claims", airdrop_id, max_claims);
+    Ok(())
+}
+
+fn process_claim_airdrop(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    airdrop_id: u64,
+    index: u32,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let airdrop_account = next_account_info(accounts_iter)?;
+    let claims_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let vault = next_account_info(accounts_iter)?;
+    let player_token_account = next_account_info(accounts_iter)?;
+    let reward_mint = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+
+    check_signer(player)?;
+    let config = load_config(program_id, config_account)?;
+    check_not_paused(&config)?;
+    check_airdrop_pdas(program_id, airdrop_account, claims_account, airdrop_id)?;
+    check_program_owned(program_id, airdrop_account)?;
+    check_program_owned(program_id, claims_account)?;
+    let vault_bump = check_vault_pda(program_id, vault)?;
+    check_token_interface_program(token_program)?;
+    let decimals = check_token_mint(reward_mint, &config.reward_mint, token_program)?;
+    if vault.owner != token_program.key {
+        msg!("Reward vault has not been initialized");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let expected_destination = get_associated_token_address_with_program_id(player.key, &config.reward_mint, token_program.key);
+    if *player_token_account.key != expected_destination {
+        msg!("Rewards are paid to the player's associated token account {}", expected_destination);
+        return Err(GarbageGrabError::InvalidTokenAccount.into());
+    }
+
+    // Record the claim before paying out; the borrows must end before the CPI
+    {
+        let mut airdrop_data = airdrop_account.try_borrow_mut_data()?;
+        let mut airdrop = Airdrop::unpack(&airdrop_data)?;
+        if !airdrop.is_initialized() {
+            msg!("Airdrop not initialized");
+            return Err(GarbageGrabError::NotInitialized.into());
+        }
+        let leaf = merkle::airdrop_leaf(index, player.key, amount);
+        if index >= airdrop.max_claims || !merkle::verify_proof(leaf, &proof, &airdrop.merkle_root) {
+            msg!("Proof for index {} does not match airdrop {}", index, airdrop_id);
+            return Err(GarbageGrabError::InvalidMerkleProof.into());
+        }
+
+        let mut claims_data = claims_account.try_borrow_mut_data()?;
+        let mut claims = AirdropClaims::unpack(&claims_data)?;
+        if !claims.set_claimed(index) {
+            msg!("Airdrop {} index {} was already claimed", airdrop_id, index);
+            return Err(GarbageGrabError::AirdropAlreadyClaimed.into());
+        }
+        claims.pack(&mut claims_data)?;
+
+        airdrop.claimed_count = airdrop.claimed_count.checked_add(1).ok_or(ProgramError::ArithmeticOverflow)?;
+        airdrop.total_claimed = airdrop.total_claimed.checked_add(amount).ok_or(ProgramError::ArithmeticOverflow)?;
+        airdrop.pack(&mut airdrop_data)?;
+    }
+
+    invoke_transfer_checked(
+        token_program.key,
+        vault.clone(),
+        reward_mint.clone(),
+        player_token_account.clone(),
+        vault.clone(),
+        accounts_iter.as_slice(),
+        amount,
+        decimals,
+        &[&[VaultPda::SEED, &[vault_bump]]],
+    )?;
+    msg!("Player {} claimed {} tokens from airdrop {}", player.key, amount, airdrop_id);
+    Ok(())
+}
+
+// Check the passed account is the score tree PDA for `merkle_tree`. Returns the bump seed.
+fn check_score_tree_pda(
+    program_id: &Pubkey,
+    score_tree_account: &AccountInfo,
+    merkle_tree: &Pubkey,
+) -> Result<u8, ProgramError> {
+    let (pda, bump) = ScoreTreePda::find(program_id, merkle_tree);
+    if pda != *score_tree_account.key {
+        msg!("Invalid score tree PDA: expected {}, found {}", pda, score_tree_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Anchor instruction discriminators of the SPL Account Compression program,
+// the first 8 bytes of sha256("global:<instruction name>")
+const INIT_EMPTY_MERKLE_TREE_DISCRIMINATOR: [u8; 8] = [191, 11, 119, 7, 180, 107, 220, 110];
+const APPEND_DISCRIMINATOR: [u8; 8] = [149, 120, 18, 222, 236, 225, 88, 203];
+const VERIFY_LEAF_DISCRIMINATOR: [u8; 8] = [124, 220, 22, 223, 104, 10, 250, 224];
+
+// Build an SPL Account Compression instruction: the discriminator followed by
+// the Borsh-encoded arguments
+fn compression_instruction<T: borsh::BorshSerialize>(
+    discriminator: [u8; 8],
+    args: &T,
+    accounts: Vec<AccountMeta>,
+) -> Result<Instruction, ProgramError> {
+    let mut data = discriminator.to_vec();
+    borsh::to_writer(&mut data, args).map_err(|_| ProgramError::InvalidInstructionData)?;
+    Ok(Instruction {
+        program_id: spl_account_compression::id(),
+        accounts,
+        data,
+    })
+}
+
+// Accounts of the compression program's Initialize and Modify contexts, with
+// the score tree PDA signing as tree authority
+fn compression_modify_accounts(merkle_tree: &Pubkey, authority: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(*merkle_tree, false),
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(spl_noop::id(), false),
+    ]
+}
+
+// Ensure the passed accounts are the SPL Account Compression and Noop programs
+fn check_compression_programs(compression_program: &AccountInfo, noop_program: Option<&AccountInfo>) -> ProgramResult {
+    if *compression_program.key != spl_account_compression::id() {
+        msg!("Expected the account compression program, found {}", compression_program.key);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if let Some(noop_program) = noop_program {
+        if *noop_program.key != spl_noop::id() {
+            msg!("Expected the noop program, found {}", noop_program.key);
+            return Err(ProgramError::IncorrectProgramId);
+        }
+    }
+    Ok(())
+}
+
+fn process_initialize_score_tree(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    max_depth: u32,
+    max_buffer_size: u32,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let score_tree_account = next_account_info(accounts_iter)?;
+    let merkle_tree = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let compression_program = next_account_info(accounts_iter)?;
+    let noop_program = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    load_config_as_admin(program_id, config_account, admin)?;
+    check_system_program(system_program)?;
+    check_compression_programs(compression_program, Some(noop_program))?;
+    let bump = check_score_tree_pda(program_id, score_tree_account, merkle_tree.key)?;
+    if !score_tree_account.data_is_empty() {
+        msg!("Score tree already initialized");
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+
+    create_pda_account(admin, score_tree_account, system_program, ScoreTree::LEN, program_id, &[ScoreTreePda::SEED, merkle_tree.key.as_ref(), &[bump]])?;
+    let score_tree = ScoreTree {
+        is_initialized: true,
+        merkle_tree: *merkle_tree.key,
+        leaf_count: 0,
+    };
+    score_tree.pack(&mut score_tree_account.try_borrow_mut_data()?)?;
+
+    // The compression program checks the tree account is zeroed, owned by it and
+    // sized for the depth and buffer
+    let init = compression_instruction(
+        INIT_EMPTY_MERKLE_TREE_DISCRIMINATOR,
+        &(max_depth, max_buffer_size),
+        compression_modify_accounts(merkle_tree.key, score_tree_account.key),
+    )?;
+    invoke_signed(
+        &init,
+        &[merkle_tree.clone(), score_tree_account.clone(), noop_program.clone(), compression_program.clone()],
+        &[&[ScoreTreePda::SEED, merkle_tree.key.as_ref(), &[bump]]],
+    )?;
+    msg!("Score tree {} initialized with depth {}", merkle_tree.key, max_depth);
+    Ok(())
+}
+
+fn process_append_compressed_score(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    game_mode: GameMode,
+    score: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let score_tree_account = next_account_info(accounts_iter)?;
+    let merkle_tree = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let game_server = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let compression_program = next_account_info(accounts_iter)?;
+    let noop_program = next_account_info(accounts_iter)?;
+
+    let config = load_config_as_game_server(program_id, config_account, game_server)?;
+    check_not_paused(&config)?;
+    check_compression_programs(compression_program, Some(noop_program))?;
+    let bump = check_score_tree_pda(program_id, score_tree_account, merkle_tree.key)?;
+    check_program_owned(program_id, score_tree_account)?;
+    if score > config.max_score {
+        msg!("Score too large: {} (max {})", score, config.max_score);
+        return Err(GarbageGrabError::ScoreTooLarge.into());
+    }
+
+    let leaf = {
+        let mut data = score_tree_account.try_borrow_mut_data()?;
+        let mut score_tree = ScoreTree::unpack(&data)?;
+        if !score_tree.is_initialized() {
+            msg!("Score tree not initialized");
+            return Err(GarbageGrabError::NotInitialized.into());
+        }
+        let leaf = CompressedScore {
+            player: *player.key,
+            game_mode,
+            score,
+            timestamp: Clock::get()?.unix_timestamp,
+            leaf_index: score_tree.leaf_count,
+        };
+        score_tree.leaf_count = score_tree.leaf_count.checked_add(1).ok_or(ProgramError::ArithmeticOverflow)?;
+        score_tree.pack(&mut data)?;
+        leaf
+    };
+
+    // Write the full leaf to the noop program so indexers can rebuild the tree
+    invoke(
+        &spl_noop::instruction(borsh::to_vec(&leaf).map_err(|_| ProgramError::InvalidAccountData)?),
+        std::slice::from_ref(noop_program),
+    )?;
+    let append = compression_instruction(
+        APPEND_DISCRIMINATOR,
+        &leaf.leaf_hash(),
+        compression_modify_accounts(merkle_tree.key, score_tree_account.key),
+    )?;
+    invoke_signed(
+        &append,
+        &[merkle_tree.clone(), score_tree_account.clone(), noop_program.clone(), compression_program.clone()],
+        &[&[ScoreTreePda::SEED, merkle_tree.key.as_ref(), &[bump]]],
+    )?;
+    msg!("Compressed score {} for player {} appended at leaf {}", score, player.key, leaf.leaf_index);
+    Ok(())
+}
+
+fn process_verify_compressed_score(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    root: [u8; 32],
+    score: CompressedScore,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let score_tree_account = next_account_info(accounts_iter)?;
+    let merkle_tree = next_account_info(accounts_iter)?;
+    let compression_program = next_account_info(accounts_iter)?;
+    let proof = accounts_iter.as_slice();
+
+    check_compression_programs(compression_program, None)?;
+    check_score_tree_pda(program_id, score_tree_account, merkle_tree.key)?;
+    check_program_owned(program_id, score_tree_account)?;
+    let index = u32::try_from(score.leaf_index).map_err(|_| ProgramError::InvalidArgument)?;
+
+    // The proof nodes follow the tree as remaining accounts
+    let mut metas = vec![AccountMeta::new_readonly(*merkle_tree.key, false)];
+    metas.extend(proof.iter().map(|node| AccountMeta::new_readonly(*node.key, false)));
+    let verify = compression_instruction(VERIFY_LEAF_DISCRIMINATOR, &(root, score.leaf_hash(), index), metas)?;
+    let mut infos = vec![merkle_tree.clone()];
+    infos.extend(proof.iter().cloned());
+    infos.push(compression_program.clone());
+    invoke(&verify, &infos)?;
+    return_score(score.score);
+    msg!("Compressed score {} for player {} verified at leaf {}", score.score, score.player, index);
+    Ok(())
+}
+
+// Apply one entry of a batch: the account must be a migrated score PDA of the mode,
+// and the increment must pass the usual update checks
+fn credit_batch_entry(
+    program_id: &Pubkey,
+    score_account: &AccountInfo,
+    season_account: &AccountInfo,
+    ban_list_account: &AccountInfo,
+    config: &Config,
+    game_mode: GameMode,
+    increment: u64,
+) -> Result<u64, ProgramError> {
+    check_program_owned(program_id, score_account)?;
+    let player = ScoreAccount::unpack(&score_account.try_borrow_data()?)?.player;
+    check_score_pda(program_id, score_account, &player, game_mode)?;
+    credit_increment(program_id, score_account, &player, season_account, ban_list_account, config, increment, None, MAX_BPS)
+}
+
+fn process_batch_update(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    game_mode: GameMode,
+    increments: Vec<u64>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let game_server = next_account_info(accounts_iter)?;
+    let season_account = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let ban_list_account = next_account_info(accounts_iter)?;
+    let score_accounts = accounts_iter.as_slice();
+
+    let config = load_config_as_game_server(program_id, config_account, game_server)?;
+    check_not_paused(&config)?;
+    // A bad season or ban list account or an ended season would fail every
+    // entry, so reject the batch up front
+    open_season_id(program_id, season_account)?;
+    check_ban_list_pda(program_id, ban_list_account)?;
+    if score_accounts.len() != increments.len() {
+        msg!("Batch has {} increments but {} score accounts", increments.len(), score_accounts.len());
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let mut applied = 0;
+    for (entry, (score_account, &increment)) in score_accounts.iter().zip(&increments).enumerate() {
+        match credit_batch_entry(program_id, score_account, season_account, ban_list_account, &config, game_mode, increment) {
+            Ok(_) => applied += 1,
+            Err(err) => msg!("Batch entry {} ({}) skipped: {}", entry, score_account.key, err),
+        }
+    }
+    msg!("Batch applied {} of {} updates", applied, increments.len());
+    Ok(())
+}
+
+// Top a program-owned account back up to its rent-exempt minimum. Anyone can pay,
+// since the lamports only keep the account alive.
+fn process_top_up_rent(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let account = next_account_info(accounts_iter)?;
+    let payer = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_signer(payer)?;
+    check_system_program(system_program)?;
+    check_program_owned(program_id, account)?;
+
+    match top_up_rent(account, payer, system_program)? {
+        0 => msg!("Account {} is already rent exempt", account.key),
+        shortfall => msg!("Account {} topped up with {} lamports by {}", account.key, shortfall, payer.key),
+    }
+    Ok(())
+}
+
+fn process_ban(program_id: &Pubkey, accounts: &[AccountInfo], player: Pubkey) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let ban_list_account = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    load_config_as_admin(program_id, config_account, admin)?;
+    let bump = check_ban_list_pda(program_id, ban_list_account)?;
+    check_system_program(system_program)?;
+
+    // The first ban creates the list
+    let mut list = if ban_list_account.data_is_empty() {
+        let space = BanList::space(0);
+        create_pda_account(admin, ban_list_account, system_program, space, program_id, &[BanListPda::SEED, &[bump]])?;
+        BanList {
+            is_initialized: true,
+            ..BanList::default()
+        }
+    } else {
+        check_program_owned(program_id, ban_list_account)?;
+        BanList::unpack(&ban_list_account.try_borrow_data()?)?
+    };
+
+    if !list.ban(player) {
+        msg!("Player {} is already banned", player);
+        return Err(GarbageGrabError::PlayerBanned.into());
+    }
+    grow_account(ban_list_account, admin, system_program, BanList::space(list.players.len()))?;
+    list.pack(&mut ban_list_account.try_borrow_mut_data()?)?;
+    msg!("Player {} banned by admin {}", player, admin.key);
+    Ok(())
+}
+
+// Lift a ban. The lamports freed by shrinking stay in the list to pay for the
+// next ban.
+fn process_unban(program_id: &Pubkey, accounts: &[AccountInfo], player: Pubkey) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let ban_list_account = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+
+    load_config_as_admin(program_id, config_account, admin)?;
+    check_ban_list_pda(program_id, ban_list_account)?;
+    check_program_owned(program_id, ban_list_account)?;
+
+    let mut list = BanList::unpack(&ban_list_account.try_borrow_data()?)?;
+    if !list.unban(&player) {
+        msg!("Player {} is not banned", player);
+        return Err(ProgramError::InvalidArgument);
+    }
+    ban_list_account.realloc(BanList::space(list.players.len()), false)?;
+    list.pack(&mut ban_list_account.try_borrow_mut_data()?)?;
+    msg!("Player {} unbanned by admin {}", player, admin.key);
+    Ok(())
+}
+
+// Check the passed account is the PDA of `reporter`'s report on `player`. Returns the bump seed.
+fn check_report_pda(
+    program_id: &Pubkey,
+    report_account: &AccountInfo,
+    player: &Pubkey,
+    reporter: &Pubkey,
+    game_mode: GameMode,
+) -> Result<u8, ProgramError> {
+    let (pda, bump) = ReportPda::find(program_id, player, reporter, game_mode);
+    if pda != *report_account.key {
+        msg!("Invalid report PDA: expected {}, found {}", pda, report_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+fn process_report_player(program_id: &Pubkey, accounts: &[AccountInfo], game_mode: GameMode) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let report_account = next_account_info(accounts_iter)?;
+    let reporter = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let score_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_signer(reporter)?;
+    check_system_program(system_program)?;
+    if reporter.key == player.key {
+        msg!("Players cannot report themselves");
+        return Err(ProgramError::InvalidArgument);
+    }
+    check_score_pda(program_id, score_account, player.key, game_mode)?;
+    check_program_owned(program_id, score_account)?;
+    load_score(&score_account.try_borrow_data()?, player.key)?;
+    let bump = check_report_pda(program_id, report_account, player.key, reporter.key, game_mode)?;
+    if !report_account.data_is_empty() {
+        msg!("{} already has an open report on {}", reporter.key, player.key);
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+
+    // The bond sits in the report on top of its rent until the admin resolves it
+    let space = Report::LEN;
+    create_pda_account(reporter, report_account, system_program, space, program_id, &[ReportPda::SEED, player.key.as_ref(), reporter.key.as_ref(), &game_mode.seed(), &[bump]])?;
+    invoke(
+        &system_instruction::transfer(reporter.key, report_account.key, REPORT_BOND_LAMPORTS),
+        &[reporter.clone(), report_account.clone(), system_program.clone()],
+    )?;
+    Report {
+        is_initialized: true,
+        reporter: *reporter.key,
+        player: *player.key,
+        game_mode,
+        bond: REPORT_BOND_LAMPORTS,
+        created_ts: Clock::get()?.unix_timestamp,
+    }
+    .pack(&mut report_account.try_borrow_mut_data()?)?;
+    msg!("Player {} reported by {} in mode {:?}", player.key, reporter.key, game_mode);
+    Ok(())
+}
+
+fn process_resolve_report(program_id: &Pubkey, accounts: &[AccountInfo], upheld: bool, slash: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let report_account = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let score_account = next_account_info(accounts_iter)?;
+    let reporter = next_account_info(accounts_iter)?;
+    let prize_pool = next_account_info(accounts_iter)?;
+
+    load_config_as_admin(program_id, config_account, admin)?;
+    check_program_owned(program_id, report_account)?;
+    let report = Report::unpack(&report_account.try_borrow_data()?)?;
+    if !report.is_initialized() {
+        msg!("Report not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+    check_report_pda(program_id, report_account, &report.player, &report.reporter, report.game_mode)?;
+    if *reporter.key != report.reporter {
+        msg!("Expected reporter {}, found {}", report.reporter, reporter.key);
+        return Err(ProgramError::InvalidArgument);
+    }
+    check_prize_pool_pda(program_id, prize_pool)?;
+
+    if upheld {
+        check_score_pda(program_id, score_account, &report.player, report.game_mode)?;
+        check_program_owned(program_id, score_account)?;
+        let mut score_data = score_account.try_borrow_mut_data()?;
+        let mut state = load_score(&score_data, &report.player)?;
+        let old_score = state.score;
+        state.score = old_score.saturating_sub(slash);
+        state.pack(&mut score_data)?;
+        emit_score_change(&state, ScoreChange::Updated, old_score, state.score, Clock::get()?.unix_timestamp);
+        msg!("Report upheld: player {} slashed {} -> {}", report.player, old_score, state.score);
+    } else {
+        msg!("Report on player {} rejected, bond forfeited", report.player);
+    }
+
+    // Refund the bond or forfeit it to the prize pool, then hand the rest back and close
+    let total = report_account.lamports();
+    let forfeited = if upheld { 0 } else { report.bond.min(total) };
+    **report_account.try_borrow_mut_lamports()? = 0;
+    **prize_pool.try_borrow_mut_lamports()? = prize_pool
+        .lamports()
+        .checked_add(forfeited)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **reporter.try_borrow_mut_lamports()? = reporter
+        .lamports()
+        .checked_add(total - forfeited)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    report_account.try_borrow_mut_data()?.fill(0);
+    Ok(())
+}
+
+// Check the passed account is the admin set PDA. Returns the bump seed.
+fn check_admin_set_pda(program_id: &Pubkey, admin_set_account: &AccountInfo) -> Result<u8, ProgramError> {
+    let (pda, bump) = AdminSetPda::find(program_id);
+    if pda != *admin_set_account.key {
+        msg!("Invalid admin set PDA: expected {}, found {}", pda, admin_set_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Read the initialized admin set
+fn load_admin_set(program_id: &Pubkey, admin_set_account: &AccountInfo) -> Result<AdminSet, ProgramError> {
+    check_admin_set_pda(program_id, admin_set_account)?;
+    check_program_owned(program_id, admin_set_account)?;
+    let set = AdminSet::unpack(&admin_set_account.try_borrow_data()?)?;
+    if !set.is_initialized() {
+        msg!("Admin set not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+    Ok(set)
+}
+
+// Check the passed account is the PDA of proposal `proposal_id`. Returns the bump seed.
+fn check_proposal_pda(program_id: &Pubkey, proposal_account: &AccountInfo, proposal_id: u64) -> Result<u8, ProgramError> {
+    let (pda, bump) = ProposalPda::find(program_id, proposal_id);
+    if pda != *proposal_account.key {
+        msg!("Invalid proposal PDA: expected {}, found {}", pda, proposal_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Read a pending proposal, checking it was made for the set's current members
+fn load_proposal(program_id: &Pubkey, proposal_account: &AccountInfo, set: &AdminSet) -> Result<Proposal, ProgramError> {
+    check_program_owned(program_id, proposal_account)?;
+    let proposal = Proposal::unpack(&proposal_account.try_borrow_data()?)?;
+    if !proposal.is_initialized() {
+        msg!("Proposal not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+    check_proposal_pda(program_id, proposal_account, proposal.proposal_id)?;
+    if proposal.executed {
+        msg!("Proposal {} already executed", proposal.proposal_id);
+        return Err(ProgramError::InvalidArgument);
+    }
+    if proposal.members_version != set.members_version {
+        msg!("Proposal {} predates the current admin set", proposal.proposal_id);
+        return Err(GarbageGrabError::StaleProposal.into());
+    }
+    Ok(proposal)
+}
+
+// Approval bit of a signing admin set member
+fn member_bit(set: &AdminSet, member: &AccountInfo) -> Result<u16, ProgramError> {
+    check_signer(member)?;
+    match set.member_index(member.key) {
+        Some(index) => Ok(1 << index),
+        None => {
+            msg!("{} is not a member of the admin set", member.key);
+            Err(GarbageGrabError::NotAdminSetMember.into())
+        }
+    }
+}
+
+fn process_set_admin_set(program_id: &Pubkey, accounts: &[AccountInfo], threshold: u8, members: Vec<Pubkey>) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let admin_set_account = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    let mut config = load_config_as_admin(program_id, config_account, admin)?;
+    let bump = check_admin_set_pda(program_id, admin_set_account)?;
+    check_system_program(system_program)?;
+    if members.is_empty() || members.len() > MAX_ADMIN_SET_MEMBERS || threshold == 0 || threshold as usize > members.len() {
+        msg!("Need 1 to {} members and a threshold between 1 and the member count", MAX_ADMIN_SET_MEMBERS);
+        return Err(ProgramError::InvalidArgument);
+    }
+    if members.iter().enumerate().any(|(i, member)| members[..i].contains(member)) {
+        msg!("Admin set members must be distinct");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // The first call creates the set; replacing the members voids pending proposals
+    let mut set = if admin_set_account.data_is_empty() {
+        let space = AdminSet::LEN;
+        create_pda_account(admin, admin_set_account, system_program, space, program_id, &[AdminSetPda::SEED, &[bump]])?;
+        AdminSet {
+            is_initialized: true,
+            ..AdminSet::default()
+        }
+    } else {
+        let mut set = load_admin_set(program_id, admin_set_account)?;
+        set.members_version = set.members_version.checked_add(1).ok_or(ProgramError::ArithmeticOverflow)?;
+        set
+    };
+    set.threshold = threshold;
+    set.member_count = members.len() as u8;
+    set.members = [Pubkey::default(); MAX_ADMIN_SET_MEMBERS];
+    set.members[..members.len()].copy_from_slice(&members);
+    set.pack(&mut admin_set_account.try_borrow_mut_data()?)?;
+
+    config.admin = *admin_set_account.key;
+    config.pack(&mut config_account.try_borrow_mut_data()?)?;
+    msg!("Admin is now a {}-of-{} admin set", threshold, members.len());
+    Ok(())
+}
+
+fn process_propose(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    proposed_accounts: Vec<ProposalAccount>,
+    data: Vec<u8>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let proposal_account = next_account_info(accounts_iter)?;
+    let admin_set_account = next_account_info(accounts_iter)?;
+    let proposer = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_system_program(system_program)?;
+    let mut set = load_admin_set(program_id, admin_set_account)?;
+    let approvals = member_bit(&set, proposer)?;
+    let proposal_id = set.proposal_count;
+    let bump = check_proposal_pda(program_id, proposal_account, proposal_id)?;
+    if !proposal_account.data_is_empty() {
+        msg!("Proposal {} already exists", proposal_id);
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+
+    let space = Proposal::space(proposed_accounts.len(), data.len());
+    create_pda_account(proposer, proposal_account, system_program, space, program_id, &[ProposalPda::SEED, &proposal_id.to_le_bytes(), &[bump]])?;
+    Proposal {
+        is_initialized: true,
+        proposal_id,
+        proposer: *proposer.key,
+        members_version: set.members_version,
+        approvals,
+        executed: false,
+        accounts: proposed_accounts,
+        data,
+    }
+    .pack(&mut proposal_account.try_borrow_mut_data()?)?;
+
+    set.proposal_count = proposal_id.checked_add(1).ok_or(ProgramError::ArithmeticOverflow)?;
+    set.pack(&mut admin_set_account.try_borrow_mut_data()?)?;
+    msg!("Proposal {} created by {}", proposal_id, proposer.key);
+    Ok(())
+}
+
+fn process_approve(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let proposal_account = next_account_info(accounts_iter)?;
+    let admin_set_account = next_account_info(accounts_iter)?;
+    let member = next_account_info(accounts_iter)?;
+
+    let set = load_admin_set(program_id, admin_set_account)?;
+    let mut proposal = load_proposal(program_id, proposal_account, &set)?;
+    proposal.approvals |= member_bit(&set, member)?;
+    proposal.pack(&mut proposal_account.try_borrow_mut_data()?)?;
+    msg!(
+        "Proposal {} approved by {}: {} of {} approvals",
+        proposal.proposal_id,
+        member.key,
+        proposal.approval_count(),
+        set.threshold
+    );
+    Ok(())
+}
+
+// Run an approved proposal's instruction through a call back into this program,
+// signed by the admin set, which the config names as admin
+fn process_execute_proposal(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let proposal_account = next_account_info(accounts_iter)?;
+    let admin_set_account = next_account_info(accounts_iter)?;
+    let proposer = next_account_info(accounts_iter)?;
+    let score_program = next_account_info(accounts_iter)?;
+
+    if score_program.key != program_id {
+        msg!("Expected the score program, found {}", score_program.key);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let set = load_admin_set(program_id, admin_set_account)?;
+    let bump = check_admin_set_pda(program_id, admin_set_account)?;
+    let mut proposal = load_proposal(program_id, proposal_account, &set)?;
+    if proposal.approval_count() < u32::from(set.threshold) {
+        msg!("Proposal {} has {} of {} approvals", proposal.proposal_id, proposal.approval_count(), set.threshold);
+        return Err(GarbageGrabError::NotEnoughApprovals.into());
+    }
+    if *proposer.key != proposal.proposer {
+        msg!("Expected proposer {}, found {}", proposal.proposer, proposer.key);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Marked executed first so the proposed instruction cannot execute it again
+    proposal.executed = true;
+    proposal.pack(&mut proposal_account.try_borrow_mut_data()?)?;
+    let instruction = Instruction {
+        program_id: *program_id,
+        accounts: proposal
+            .accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.pubkey,
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+        data: proposal.data,
+    };
+    invoke_signed(&instruction, accounts, &[&[AdminSetPda::SEED, &[bump]]])?;
+
+    // Close the proposal and refund its rent
+    let reclaimed = proposal_account.lamports();
+    **proposal_account.try_borrow_mut_lamports()? = 0;
+    **proposer.try_borrow_mut_lamports()? = proposer
+        .lamports()
+        .checked_add(reclaimed)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    proposal_account.try_borrow_mut_data()?.fill(0);
+    msg!("Proposal {} executed", proposal.proposal_id);
+    Ok(())
+}
+
+// First half of an admin handover: record who may take over
+fn process_nominate_admin(program_id: &Pubkey, accounts: &[AccountInfo], new_admin: Pubkey) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let config_account = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+
+    let mut config = load_config_as_admin(program_id, config_account, admin)?;
+    config.pending_admin = new_admin;
+    config.pack(&mut config_account.try_borrow_mut_data()?)?;
+    if new_admin == Pubkey::default() {
+        msg!("Admin nomination withdrawn");
+    } else {
+        msg!("Admin {} nominated {} as admin", admin.key, new_admin);
+    }
+    Ok(())
+}
+
+// Second half: the nominee signs, proving the key is one they control
+fn process_accept_admin(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let config_account = next_account_info(accounts_iter)?;
+    let new_admin = next_account_info(accounts_iter)?;
+
+    check_signer(new_admin)?;
+    let mut config = load_config(program_id, config_account)?;
+    if config.pending_admin == Pubkey::default() || config.pending_admin != *new_admin.key {
+        msg!("{} has not been nominated as admin", new_admin.key);
+        return Err(GarbageGrabError::Unauthorized.into());
+    }
+    let old_admin = config.admin;
+    config.admin = config.pending_admin;
+    config.pending_admin = Pubkey::default();
+    config.pack(&mut config_account.try_borrow_mut_data()?)?;
+    msg!("Admin handed over from {} to {}", old_admin, new_admin.key);
+    Ok(())
+}
+
+// Check the passed account is the player's loot request PDA. Returns the bump seed.
+fn check_loot_request_pda(program_id: &Pubkey, loot_request: &AccountInfo, player: &Pubkey) -> Result<u8, ProgramError> {
+    let (pda, bump) = LootRequestPda::find(program_id, player);
+    if pda != *loot_request.key {
+        msg!("Invalid loot request PDA: expected {}, found {}", pda, loot_request.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Check the passed account is the player's inventory PDA. Returns the bump seed.
+fn check_inventory_pda(program_id: &Pubkey, inventory: &AccountInfo, player: &Pubkey) -> Result<u8, ProgramError> {
+    let (pda, bump) = InventoryPda::find(program_id, player);
+    if pda != *inventory.key {
+        msg!("Invalid inventory PDA: expected {}, found {}", pda, inventory.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Read the player's inventory, first creating an empty one paid for by `payer`
+fn load_or_create_inventory<'a>(
+    program_id: &Pubkey,
+    inventory: &AccountInfo<'a>,
+    player: &Pubkey,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> Result<Inventory, ProgramError> {
+    let bump = check_inventory_pda(program_id, inventory, player)?;
+    if !inventory.data_is_empty() {
+        check_program_owned(program_id, inventory)?;
+        return Inventory::unpack(&inventory.try_borrow_data()?);
+    }
+    let space = Inventory::space(0);
+    create_pda_account(payer, inventory, system_program, space, program_id, &[InventoryPda::SEED, player.as_ref(), &[bump]])?;
+    let state = Inventory { is_initialized: true, player: *player, ..Inventory::default() };
+    state.pack(&mut inventory.try_borrow_mut_data()?)?;
+    Ok(state)
+}
+
+// Read a Switchboard on-demand randomness account
+fn load_randomness(randomness: &AccountInfo) -> Result<RandomnessAccountData, ProgramError> {
+    if *randomness.owner != SWITCHBOARD_ON_DEMAND_PROGRAM_ID {
+        msg!("Randomness account {} is not owned by Switchboard", randomness.key);
+        return Err(ProgramError::IllegalOwner);
+    }
+    let data = randomness.try_borrow_data()?;
+    let parsed = RandomnessAccountData::parse(data).map_err(|_| ProgramError::InvalidAccountData)?;
+    Ok(*parsed)
+}
+
+fn process_request_loot(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let loot_request = next_account_info(accounts_iter)?;
+    let inventory = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let randomness = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    check_signer(player)?;
+    check_system_program(system_program)?;
+    let request_bump = check_loot_request_pda(program_id, loot_request, player.key)?;
+    if !loot_request.data_is_empty() {
+        msg!("Player {} already has a loot drop pending", player.key);
+        return Err(GarbageGrabError::AlreadyInitialized.into());
+    }
+
+    // Only randomness committed in the previous slot is still unknown to everyone
+    let clock = Clock::get()?;
+    let seed_slot = load_randomness(randomness)?.seed_slot;
+    if seed_slot != clock.slot.saturating_sub(1) {
+        msg!("Randomness was committed in slot {}, expected {}", seed_slot, clock.slot.saturating_sub(1));
+        return Err(GarbageGrabError::RandomnessNotReady.into());
+    }
+
+    load_or_create_inventory(program_id, inventory, player.key, player, system_program)?;
+
+    // The price sits in the request on top of its rent until the drop is settled
+    let space = LootRequest::LEN;
+    create_pda_account(player, loot_request, system_program, space, program_id, &[LootRequestPda::SEED, player.key.as_ref(), &[request_bump]])?;
+    invoke(
+        &system_instruction::transfer(player.key, loot_request.key, LOOT_PRICE_LAMPORTS),
+        &[player.clone(), loot_request.clone(), system_program.clone()],
+    )?;
+    LootRequest {
+        is_initialized: true,
+        player: *player.key,
+        randomness: *randomness.key,
+        seed_slot,
+        escrow: LOOT_PRICE_LAMPORTS,
+    }
+    .pack(&mut loot_request.try_borrow_mut_data()?)?;
+    msg!("Loot drop requested by {} against randomness {}", player.key, randomness.key);
+    Ok(())
+}
+
+fn process_fulfill_loot(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let loot_request = next_account_info(accounts_iter)?;
+    let inventory = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let randomness = next_account_info(accounts_iter)?;
+    let prize_pool = next_account_info(accounts_iter)?;
+
+    check_loot_request_pda(program_id, loot_request, player.key)?;
+    check_program_owned(program_id, loot_request)?;
+    let request = LootRequest::unpack(&loot_request.try_borrow_data()?)?;
+    if !request.is_initialized() {
+        msg!("Loot request not initialized");
+        return Err(GarbageGrabError::NotInitialized.into());
+    }
+    if *randomness.key != request.randomness {
+        msg!("Expected randomness account {}, found {}", request.randomness, randomness.key);
+        return Err(ProgramError::InvalidArgument);
+    }
+    check_inventory_pda(program_id, inventory, player.key)?;
+    check_program_owned(program_id, inventory)?;
+    check_prize_pool_pda(program_id, prize_pool)?;
+
+    // The value only counts in its reveal slot and only for the commit the request saw
+    let randomness_data = load_randomness(randomness)?;
+    if randomness_data.seed_slot != request.seed_slot {
+        msg!("Randomness was recommitted in slot {} after the request", randomness_data.seed_slot);
+        return Err(GarbageGrabError::RandomnessNotReady.into());
+    }
+    let value = randomness_data.get_value(&Clock::get()?).map_err(|_| {
+        msg!("Randomness was not revealed in this slot");
+        GarbageGrabError::RandomnessNotReady
+    })?;
+
+    let tier = LootTier::roll(&value);
+    let mut inventory_data = inventory.try_borrow_mut_data()?;
+    let mut items = Inventory::unpack(&inventory_data)?;
+    items.credit_loot(tier);
+    items.pack(&mut inventory_data)?;
+    msg!("Loot drop for {} rolled {:?}", player.key, tier);
+
+    // Pay the price into the prize pool, then refund the rest and close the request
+    let total = loot_request.lamports();
+    let price = request.escrow.min(total);
+    **loot_request.try_borrow_mut_lamports()? = 0;
+    **prize_pool.try_borrow_mut_lamports()? = prize_pool
+        .lamports()
+        .checked_add(price)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **player.try_borrow_mut_lamports()? = player
+        .lamports()
+        .checked_add(total - price)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    loot_request.try_borrow_mut_data()?.fill(0);
+    Ok(())
+}
+
+// Check the passed account is the catalog PDA. Returns the bump seed.
+fn check_catalog_pda(program_id: &Pubkey, catalog_account: &AccountInfo) -> Result<u8, ProgramError> {
+    let (pda, bump) = CatalogPda::find(program_id);
+    if pda != *catalog_account.key {
+        msg!("Invalid catalog PDA: expected {}, found {}", pda, catalog_account.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Look up `item_id` in the initialized catalog
+fn load_catalog_item(program_id: &Pubkey, catalog_account: &AccountInfo, item_id: u32) -> Result<ItemDefinition, ProgramError> {
+    check_catalog_pda(program_id, catalog_account)?;
+    check_program_owned(program_id, catalog_account)?;
+    let catalog = Catalog::unpack(&catalog_account.try_borrow_data()?)?;
+    catalog.find(item_id).copied().ok_or_else(|| {
+        msg!("Item {} is not in the catalog", item_id);
+        GarbageGrabError::UnknownItem.into()
+    })
+}
+
+fn process_set_catalog_item(program_id: &Pubkey, accounts: &[AccountInfo], definition: ItemDefinition) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let catalog_account = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    load_config_as_admin(program_id, config_account, admin)?;
+    let bump = check_catalog_pda(program_id, catalog_account)?;
+    check_system_program(system_program)?;
+    if definition.effect == ItemEffect::ScoreMultiplier && u64::from(definition.effect_bps) < MAX_BPS {
+        msg!("Item {} multiplier of {} bps is below 1x", definition.item_id, definition.effect_bps);
+        return Err(GarbageGrabError::InvalidConfig.into());
+    }
+
+    // The first item creates the catalog
+    let mut catalog = if catalog_account.data_is_empty() {
+        let space = Catalog::space(0);
+        create_pda_account(admin, catalog_account, system_program, space, program_id, &[CatalogPda::SEED, &[bump]])?;
+        Catalog { is_initialized: true, ..Catalog::default() }
+    } else {
+        check_program_owned(program_id, catalog_account)?;
+        Catalog::unpack(&catalog_account.try_borrow_data()?)?
+    };
+
+    catalog.upsert(definition);
+    grow_account(catalog_account, admin, system_program, Catalog::space(catalog.items.len()))?;
+    catalog.pack(&mut catalog_account.try_borrow_mut_data()?)?;
+    msg!("Catalog item {} set to {:?} ({} bps)", definition.item_id, definition.effect, definition.effect_bps);
+    Ok(())
+}
+
+fn process_grant_item(program_id: &Pubkey, accounts: &[AccountInfo], item_id: u32, quantity: u32) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let inventory = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let game_server = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let catalog_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    load_config_as_game_server(program_id, config_account, game_server)?;
+    check_system_program(system_program)?;
+    load_catalog_item(program_id, catalog_account, item_id)?;
+    if quantity == 0 {
+        msg!("Grant quantity must be positive");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut state = load_or_create_inventory(program_id, inventory, player.key, game_server, system_program)?;
+    state.grant(item_id, quantity);
+    grow_account(inventory, game_server, system_program, Inventory::space(state.items.len()))?;
+    state.pack(&mut inventory.try_borrow_mut_data()?)?;
+    msg!("Granted {} of item {} to {}", quantity, item_id, player.key);
+    Ok(())
+}
+
+// Spend one item. The lamports freed when a stack is used up stay in the
+// inventory to pay for the next new stack.
+fn process_consume_item(program_id: &Pubkey, accounts: &[AccountInfo], game_mode: GameMode, item_id: u32) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let inventory = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let catalog_account = next_account_info(accounts_iter)?;
+    let score_account = next_account_info(accounts_iter)?;
+
+    check_signer(player)?;
+    check_inventory_pda(program_id, inventory, player.key)?;
+    check_program_owned(program_id, inventory)?;
+    let definition = load_catalog_item(program_id, catalog_account, item_id)?;
+    check_player_score(program_id, score_account, player, game_mode)?;
+
+    let mut state = Inventory::unpack(&inventory.try_borrow_data()?)?;
+    if !state.consume(item_id) {
+        msg!("Player {} holds no item {}", player.key, item_id);
+        return Err(GarbageGrabError::ItemNotHeld.into());
+    }
+
+    let mut score_data = score_account.try_borrow_mut_data()?;
+    let mut score = load_score(&score_data, player.key)?;
+    match definition.effect {
+        ItemEffect::None => {}
+        ItemEffect::ScoreMultiplier => {
+            if score.item_boost_bps != 0 {
+                msg!("A {} bps item boost is already waiting for the next update", score.item_boost_bps);
+                return Err(GarbageGrabError::ItemEffectActive.into());
+            }
+            score.item_boost_bps = definition.effect_bps;
+        }
+        ItemEffect::Continue => {
+            if score.continue_pending {
+                msg!("A continue is already pending");
+                return Err(GarbageGrabError::ItemEffectActive.into());
+            }
+            score.continue_pending = true;
+        }
+    }
+    score.pack(&mut score_data)?;
+
+    inventory.realloc(Inventory::space(state.items.len()), false)?;
+    state.pack(&mut inventory.try_borrow_mut_data()?)?;
+    msg!("Player {} used item {} ({:?}) in mode {:?}", player.key, item_id, definition.effect, game_mode);
+    Ok(())
+}
+
+// Check the passed account is the treasury PDA, a system account that collects
+// shop payments. Returns the bump seed.
+fn check_treasury_pda(program_id: &Pubkey, treasury: &AccountInfo) -> Result<u8, ProgramError> {
+    let (pda, bump) = TreasuryPda::find(program_id);
+    if pda != *treasury.key {
+        msg!("Invalid treasury PDA: expected {}, found {}", pda, treasury.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Check the passed account is the fee vault PDA. Returns the bump seed.
+fn check_fee_vault_pda(program_id: &Pubkey, fee_vault: &AccountInfo) -> Result<u8, ProgramError> {
+    let (pda, bump) = FeeVaultPda::find(program_id);
+    if pda != *fee_vault.key {
+        msg!("Invalid fee vault PDA: expected {}, found {}", pda, fee_vault.key);
+        return Err(GarbageGrabError::InvalidPda.into());
+    }
+    Ok(bump)
+}
+
+// Pay `fee` lamports of house fee from `payer` into the fee vault. The vault holds
+// no data, so the first fee also tops it up to its rent-exempt minimum.
+fn pay_house_fee<'a>(payer: &AccountInfo<'a>, fee_vault: &AccountInfo<'a>, system_program: &AccountInfo<'a>, fee: u64) -> ProgramResult {
+    if fee == 0 {
+        return Ok(());
+    }
+    let shortfall = Rent::get()?.minimum_balance(0).saturating_sub(fee_vault.lamports().saturating_add(fee));
+    invoke(
+        &system_instruction::transfer(payer.key, fee_vault.key, fee + shortfall),
+        &[payer.clone(), fee_vault.clone(), system_program.clone()],
+    )?;
+    msg!("House fee of {} lamports collected", fee);
+    Ok(())
+}
+
+fn process_withdraw_fees(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let config_account = next_account_info(accounts_iter)?;
+    let admin = next_account_info(accounts_iter)?;
+    let fee_vault = next_account_info(accounts_iter)?;
+    let destination = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    load_config_as_admin(program_id, config_account, admin)?;
+    let bump = check_fee_vault_pda(program_id, fee_vault)?;
+    check_system_program(system_program)?;
+
+    // The vault holds no data and must stay rent exempt to keep collecting
+    let available = fee_vault.lamports().saturating_sub(Rent::get()?.minimum_balance(0));
+    if amount > available {
+        msg!("Fee vault holds {} lamports above rent, {} requested", available, amount);
+        return Err(ProgramError::InsufficientFunds);
+    }
+    invoke_signed(
+        &system_instruction::transfer(fee_vault.key, destination.key, amount),
+        &[fee_vault.clone(), destination.clone(), system_program.clone()],
+        &[&[FeeVaultPda::SEED, &[bump]]],
+    )?;
+    msg!("Withdrew {} lamports of fees to {}", amount, destination.key);
+    Ok(())
+}
+
+fn process_purchase_item(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    item_id: u32,
+    quantity: u32,
+    currency: ShopCurrency,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let inventory = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let catalog_account = next_account_info(accounts_iter)?;
+    let treasury = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let fee_vault = next_account_info(accounts_iter)?;
+
+    check_signer(player)?;
+    let config = load_config(program_id, config_account)?;
+    check_not_paused(&config)?;
+    check_treasury_pda(program_id, treasury)?;
+    check_system_program(system_program)?;
+    check_fee_vault_pda(program_id, fee_vault)?;
+    let stats_account = next_global_stats(program_id, accounts_iter)?;
+    let definition = load_catalog_item(program_id, catalog_account, item_id)?;
+    if quantity == 0 {
+        msg!("Purchase quantity must be positive");
+        return Err(ProgramError::InvalidArgument);
+    }
+    let price = definition.price(currency).ok_or_else(|| {
+        msg!("Item {} has no {:?} price", item_id, currency);
+        GarbageGrabError::ItemNotForSale
+    })?;
+    let cost = price.checked_mul(u64::from(quantity)).ok_or(ProgramError::ArithmeticOverflow)?;
+
+    match currency {
+        ShopCurrency::Sol => {
+            // The treasury holds no data, so the first payment also tops it up to its
+            // rent-exempt minimum
+            let fee = config.house_fee(cost);
+            let proceeds = cost - fee;
+            let shortfall = Rent::get()?.minimum_balance(0).saturating_sub(treasury.lamports().saturating_add(proceeds));
+            invoke(
+                &system_instruction::transfer(player.key, treasury.key, proceeds + shortfall),
+                &[player.clone(), treasury.clone(), system_program.clone()],
+            )?;
+            pay_house_fee(player, fee_vault, system_program, fee)?;
+            record_global_stats(program_id, stats_account, |stats| stats.record_fee(fee))?;
+        }
+        ShopCurrency::Token => {
+            let player_token_account = next_account_info(accounts_iter)?;
+            let treasury_token_account = next_account_info(accounts_iter)?;
+            let shop_mint = next_account_info(accounts_iter)?;
+            let token_program = next_account_info(accounts_iter)?;
+            check_token_interface_program(token_program)?;
+            if config.shop_mint == Pubkey::default() {
+                msg!("The shop does not take tokens");
+                return Err(GarbageGrabError::InvalidMint.into());
+            }
+            let decimals = check_token_mint(shop_mint, &config.shop_mint, token_program)?;
+            let expected_destination = get_associated_token_address_with_program_id(treasury.key, &config.shop_mint, token_program.key);
+            if *treasury_token_account.key != expected_destination {
+                msg!("Token payments go to the treasury's associated token account {}", expected_destination);
+                return Err(GarbageGrabError::InvalidTokenAccount.into());
+            }
+            // The token program rejects the transfer if the player's account holds a different mint
+            invoke_transfer_checked(
+                token_program.key,
+                player_token_account.clone(),
+                shop_mint.clone(),
+                treasury_token_account.clone(),
+                player.clone(),
+                accounts_iter.as_slice(),
+                cost,
+                decimals,
+                &[],
+            )?;
+        }
+    }
+
+    let mut state = load_or_create_inventory(program_id, inventory, player.key, player, system_program)?;
+    state.grant(item_id, quantity);
+    grow_account(inventory, player, system_program, Inventory::space(state.items.len()))?;
+    state.pack(&mut inventory.try_borrow_mut_data()?)?;
+    msg!("Player {} bought {} of item {} for {} ({:?})", player.key, quantity, item_id, cost, currency);
+    Ok(())
+}
+
+fn process_continue(program_id: &Pubkey, accounts: &[AccountInfo], game_mode: GameMode) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let score_account = next_account_info(accounts_iter)?;
+    let player = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let player_token_account = next_account_info(accounts_iter)?;
+    let reward_mint = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+
+    check_player_score(program_id, score_account, player, game_mode)?;
+    let config = load_config(program_id, config_account)?;
+    check_not_paused(&config)?;
+    check_token_interface_program(token_program)?;
+    if config.continue_burn_amount == 0 {
+        msg!("Continues are not for sale");
+        return Err(ProgramError::InvalidArgument);
+    }
+    check_token_mint(reward_mint, &config.reward_mint, token_program)?;
+    let expected_source = get_associated_token_address_with_program_id(player.key, &config.reward_mint, token_program.key);
+    if *player_token_account.key != expected_source {
+        msg!("Continues are burned from the player's associated token account {}", expected_source);
+        return Err(GarbageGrabError::InvalidTokenAccount.into());
+    }
+
+    // Count the continue before burning; the score borrow must end before the CPI
+    let run_continues = {
+        let mut score_data = score_account.try_borrow_mut_data()?;
+        let mut state = load_score(&score_data, player.key)?;
+        if !state.add_continue(config.max_continues_per_run) {
+            msg!("Player {} already used {} continues this run", player.key, state.run_continues);
+            return Err(GarbageGrabError::ContinueLimitReached.into());
+        }
+        state.pack(&mut score_data)?;
+        state.run_continues
+    };
+
+    invoke(
+        &spl_token_2022::instruction::burn(
+            token_program.key,
+            player_token_account.key,
+            reward_mint.key,
+            player.key,
+            &[],
+            config.continue_burn_amount,
+        )?,
+        &[player_token_account.clone(), reward_mint.clone(), player.clone(), token_program.clone()],
+    )?;
+    msg!(
+        "Player {} burned {} tokens for continue {} of this run",
+        player.key,
+        config.continue_burn_amount,
+        run_continues
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn valid_config() -> Config {
+        Config {
+            is_initialized: true,
+            max_score: 1_000,
+            max_increment: 100,
+            prize_split_bps: Config::DEFAULT_PRIZE_SPLIT_BPS,
+            boost_multiplier_bps: MAX_BPS as u16,
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn validate_config_accepts_defaults() {
+        assert_eq!(validate_config(&valid_config()), Ok(()));
+    }
+
+    #[test]
+    fn validate_config_rejects_inconsistent_settings() {
+        let invalid: ProgramResult = Err(GarbageGrabError::InvalidConfig.into());
+        for config in [
+            Config { max_increment: 1_001, ..valid_config() },
+            Config { prize_split_bps: [5_000, 5_000, 1], ..valid_config() },
+            Config { boost_collection: Pubkey::new_unique(), boost_multiplier_bps: 9_999, ..valid_config() },
+            Config { decay_bps: 10_001, ..valid_config() },
+            Config { max_updates_per_window: 1, ..valid_config() },
+            Config { ranked_entry_fee_usd_cents: 500, max_price_age_secs: 60, ..valid_config() },
+            Config { max_price_conf_bps: 10_001, ..valid_config() },
+            Config { season_length_secs: u64::MAX, ..valid_config() },
+            Config { bet_fee_bps: 10_001, ..valid_config() },
+            Config { fee_bps: 10_001, ..valid_config() },
+            Config { prize_vesting_cliff_secs: 11, prize_vesting_secs: 10, ..valid_config() },
+            Config { prize_vesting_secs: u64::MAX, ..valid_config() },
+            Config { stake_tier_amounts: [100, 0, 0], stake_tier_bps: [9_999, 0, 0], ..valid_config() },
+            Config { stake_lock_bps: [0, 5_000, 0], ..valid_config() },
+            Config { early_unstake_penalty_bps: 10_001, ..valid_config() },
+            Config { high_stakes_min_level: MAX_LEVEL + 1, ..valid_config() },
+            Config { closed_beta: true, ..valid_config() },
+            Config { require_attestation: true, ..valid_config() },
+        ] {
+            assert_eq!(validate_config(&config), invalid, "{:?}", config);
+        }
+    }
+
+    #[test]
+    fn empty_instruction_data_is_rejected() {
+        let program_id = Pubkey::new_unique();
+        assert_eq!(process_instruction(&program_id, &[], &[]), Err(ProgramError::InvalidInstructionData));
+    }
+
+    #[test]
+    fn compression_discriminators_match_anchor_derivation() {
+        for (name, discriminator) in [
+            ("init_empty_merkle_tree", INIT_EMPTY_MERKLE_TREE_DISCRIMINATOR),
+            ("append", APPEND_DISCRIMINATOR),
+            ("verify_leaf", VERIFY_LEAF_DISCRIMINATOR),
+        ] {
+            let digest = solana_program::hash::hash(format!("global:{}", name).as_bytes());
+            assert_eq!(digest.to_bytes()[..8], discriminator, "{}", name);
+        }
+    }
+
+    #[test]
+    fn compression_instruction_packs_borsh_args_after_the_discriminator() {
+        let (root, leaf) = ([1u8; 32], [2u8; 32]);
+        let ix = compression_instruction(VERIFY_LEAF_DISCRIMINATOR, &(root, leaf, 7u32), vec![]).unwrap();
+        assert_eq!(ix.program_id, spl_account_compression::id());
+        assert_eq!(ix.data[..8], VERIFY_LEAF_DISCRIMINATOR);
+        assert_eq!(ix.data[8..40], root);
+        assert_eq!(ix.data[40..72], leaf);
+        assert_eq!(ix.data[72..], 7u32.to_le_bytes());
+    }
+}
@@ -0,0 +1,4637 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use bytemuck::{Pod, Zeroable};
+use shank::{ShankAccount, ShankType};
+use solana_program::{
+    entrypoint::ProgramResult, hash::Hash, keccak, program_error::ProgramError,
+    program_pack::IsInitialized, pubkey::Pubkey,
+};
+
+// Every program account starts with an 8-byte type tag so indexers and `getProgramAccounts`
+// memcmp filters can tell account types apart. Tags follow Anchor's convention: the first
+// 8 bytes of sha256("account:<TypeName>").
+pub const DISCRIMINATOR_LEN: usize = 8;
+
+// Check the type tag at the front of an account and return the data behind it
+fn strip_discriminator<'a>(
+    data: &'a [u8],
+    discriminator: &[u8; DISCRIMINATOR_LEN],
+) -> Result<&'a [u8], ProgramError> {
+    if data.len() < DISCRIMINATOR_LEN || data[..DISCRIMINATOR_LEN] != discriminator[..] {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(&data[DISCRIMINATOR_LEN..])
+}
+
+// Write the type tag at the front of an account and return the buffer behind it
+fn write_discriminator<'a>(
+    dst: &'a mut [u8],
+    discriminator: &[u8; DISCRIMINATOR_LEN],
+) -> &'a mut [u8] {
+    dst[..DISCRIMINATOR_LEN].copy_from_slice(discriminator);
+    &mut dst[DISCRIMINATOR_LEN..]
+}
+
+// Game modes that keep separate score accounts, encoded as a single byte
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankType, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+#[borsh(use_discriminant = true)]
+#[repr(u8)]
+pub enum GameMode {
+    #[default]
+    Arcade = 0,
+    Endless = 1,
+    DailyChallenge = 2,
+}
+
+impl GameMode {
+    // Seed byte used when deriving the score PDA for this mode
+    pub fn seed(self) -> [u8; 1] {
+        [self as u8]
+    }
+}
+
+pub const SECONDS_PER_DAY: i64 = 86_400;
+pub const SECONDS_PER_WEEK: i64 = 7 * SECONDS_PER_DAY;
+
+// Number of the week containing unix timestamp `ts`, counted from the week of
+// the epoch. Weeks start on Monday 00:00 UTC like ISO weeks; the epoch fell on a
+// Thursday.
+pub fn week_of(ts: i64) -> i64 {
+    (ts + 3 * SECONDS_PER_DAY).div_euclid(SECONDS_PER_WEEK)
+}
+
+// Each consecutive day after the first adds 10% to score increments, up to double
+pub const STREAK_BONUS_BPS_PER_DAY: u64 = 1_000;
+pub const MAX_STREAK_BONUS_DAYS: u16 = 10;
+
+// Best-score thresholds of the reward tiers; every tier reached is worth
+// `Config::reward_per_tier` reward tokens
+pub const REWARD_TIER_THRESHOLDS: [u64; 5] = [1_000, 5_000, 25_000, 100_000, 500_000];
+
+// Define the score account structure
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct ScoreAccount {
+    pub is_initialized: bool,
+    pub player: Pubkey,
+    pub score: u64,
+    pub game_mode: GameMode,
+    pub best_score: u64,
+    pub season_id: u64,
+    pub last_update_ts: i64,
+    pub nonce: u64,
+    pub version: u8,
+    pub rewards_claimed: u64,
+    pub ranked_run_open: bool,
+    pub achievements: u64,
+    pub streak: u16,
+    pub last_play_day: i64,
+    pub trophies: u32,
+    pub trophy_best: u64,
+    // Player who referred this one, or the default pubkey if nobody did
+    pub referrer: Pubkey,
+    pub referral_credited: bool,
+    // Key the player approved to submit score updates for them, or the default
+    // pubkey if none
+    pub delegate: Pubkey,
+    // Slot of the last update and how many updates landed in its throttle window
+    pub last_update_slot: u64,
+    pub window_updates: u16,
+    // Multiplier in basis points a consumed item put on the next update, or 0
+    // for none
+    pub item_boost_bps: u16,
+    // Whether a consumed item or burned tokens let the player resume their
+    // current run
+    pub continue_pending: bool,
+    // Continues bought with tokens since the last submitted run
+    pub run_continues: u8,
+    // Bump of the account's address, so later instructions can check it without
+    // searching for it again
+    pub bump: u8,
+    // Week of the last recorded score, see `week_of`, and the best score recorded
+    // during it
+    pub week: i64,
+    pub week_best_score: u64,
+    // Hashes of the most recent submitted runs, oldest overwritten first at
+    // run_hash_cursor. Shank only reads literal array lengths; this is
+    // RECENT_RUN_HASHES.
+    pub recent_run_hashes: [Hash; 8],
+    pub run_hash_cursor: u8,
+}
+
+// Run hashes each score account remembers to turn away replayed runs
+pub const RECENT_RUN_HASHES: usize = 8;
+
+impl ScoreAccount {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [97, 121, 23, 147, 120, 45, 149, 84];
+
+    // Layout version written by this program; new fields are only ever appended
+    pub const VERSION: u8 = 15;
+
+    // Untagged layouts written before discriminators. The v0 fields form a prefix of
+    // every later layout: is_initialized, player pubkey, score, game mode, best score,
+    // season id, last update timestamp, nonce. v1 adds the version byte.
+    pub const LEN_V0: usize = 1 + 32 + 8 + 1 + 8 + 8 + 8 + 8;
+    pub const LEN_V1: usize = Self::LEN_V0 + 1;
+
+    // Current layout: the type tag, the v1 fields, then fields appended since:
+    // rewards claimed (v3), ranked run flag (v4), achievements bitmask (v5),
+    // daily streak and the UTC day of the last check-in (v6), trophy count and the
+    // best score the last trophy was minted for (v7), referrer and whether they have
+    // been credited for this player (v8), delegate allowed to submit updates (v9),
+    // last update slot and the updates counted in its throttle window (v10), item
+    // boost for the next update and whether an item continue is pending (v11),
+    // continues bought this run (v12), address bump (v13), week of the last score
+    // and the best score that week (v14), recent run hashes and the next one to
+    // overwrite (v15)
+    pub const LEN: usize = DISCRIMINATOR_LEN
+        + Self::LEN_V1
+        + 8
+        + 1
+        + 8
+        + 2
+        + 8
+        + 4
+        + 8
+        + 32
+        + 1
+        + 32
+        + 8
+        + 2
+        + 2
+        + 1
+        + 1
+        + 1
+        + 8
+        + 8
+        + 32 * RECENT_RUN_HASHES
+        + 1;
+
+    // Where the bump sits in the current layout, ahead of the v14 and v15 fields
+    const BUMP_OFFSET: usize = Self::LEN - 8 - 8 - 32 * RECENT_RUN_HASHES - 1 - 1;
+
+    // Number of achievements the bitmask can hold
+    pub const ACHIEVEMENT_COUNT: u8 = 64;
+
+    // Zero the running score if it was earned in an earlier season
+    pub fn roll_season(&mut self, season_id: u64) {
+        if self.season_id != season_id {
+            self.score = 0;
+            self.season_id = season_id;
+        }
+    }
+
+    // Take `bps` off the running score for every full `window_secs` since the
+    // last update, compounding. Returns the score before decay.
+    pub fn decay(&mut self, now: i64, window_secs: u32, bps: u16) -> u64 {
+        let before = self.score;
+        if window_secs == 0 || bps == 0 || self.last_update_ts == 0 {
+            return before;
+        }
+        let windows = now.saturating_sub(self.last_update_ts) / i64::from(window_secs);
+        if windows > 0 {
+            let kept =
+                (MAX_BPS - u64::from(bps).min(MAX_BPS)) as u128 * DECAY_SCALE / MAX_BPS as u128;
+            self.score = (before as u128 * pow_scaled(kept, windows as u64) / DECAY_SCALE) as u64;
+        }
+        before
+    }
+
+    // Count an update landing in `slot` against a limit of `max_updates` per
+    // `window_slots`-slot window. Returns false, counting nothing, once the limit
+    // is reached. A zero limit disables the throttle.
+    pub fn throttle(&mut self, slot: u64, window_slots: u64, max_updates: u16) -> bool {
+        if max_updates == 0 {
+            return true;
+        }
+        let window_slots = window_slots.max(1);
+        let same_window =
+            self.last_update_slot / window_slots == slot / window_slots && self.window_updates > 0;
+        let updates = if same_window { self.window_updates } else { 0 };
+        if updates >= max_updates {
+            return false;
+        }
+        self.last_update_slot = slot;
+        self.window_updates = updates + 1;
+        true
+    }
+
+    // Buy a continue for the current run, up to `max_per_run` of them (zero for
+    // no cap). Returns false once the cap is reached.
+    pub fn add_continue(&mut self, max_per_run: u8) -> bool {
+        if max_per_run > 0 && self.run_continues >= max_per_run {
+            return false;
+        }
+        self.run_continues = self.run_continues.saturating_add(1);
+        self.continue_pending = true;
+        true
+    }
+
+    // Close out the current run, dropping any unused continue
+    pub fn end_run(&mut self) {
+        self.run_continues = 0;
+        self.continue_pending = false;
+    }
+
+    // Set the running score at `now`, raising the best score and the best score
+    // of the current week if they were beaten
+    pub fn record_score(&mut self, score: u64, now: i64) {
+        self.score = score;
+        self.best_score = self.best_score.max(score);
+        let week = week_of(now);
+        if self.week != week {
+            self.week = week;
+            self.week_best_score = 0;
+        }
+        self.week_best_score = self.week_best_score.max(score);
+    }
+
+    // Remember `hash` as a submitted run, overwriting the oldest remembered one.
+    // Returns false, remembering nothing, if it is among the recent runs; the
+    // zero hash always is, as it fills the unused slots.
+    pub fn record_run_hash(&mut self, hash: &Hash) -> bool {
+        if self.recent_run_hashes.contains(hash) {
+            return false;
+        }
+        let cursor = self.run_hash_cursor as usize % RECENT_RUN_HASHES;
+        self.recent_run_hashes[cursor] = *hash;
+        self.run_hash_cursor = ((cursor + 1) % RECENT_RUN_HASHES) as u8;
+        true
+    }
+
+    // Record a check-in at `now`, extending the streak if the last one was the
+    // previous UTC day and restarting it otherwise. Returns false if the player
+    // already checked in today.
+    pub fn check_in(&mut self, now: i64) -> bool {
+        let today = now.div_euclid(SECONDS_PER_DAY);
+        if self.streak > 0 && self.last_play_day == today {
+            return false;
+        }
+        self.streak = if self.streak > 0 && self.last_play_day == today - 1 {
+            self.streak.saturating_add(1)
+        } else {
+            1
+        };
+        self.last_play_day = today;
+        true
+    }
+
+    // Score multiplier in basis points for increments credited at `now`. The
+    // streak bonus only applies on a day the player has checked in.
+    pub fn streak_multiplier_bps(&self, now: i64) -> u64 {
+        if self.streak == 0 || self.last_play_day != now.div_euclid(SECONDS_PER_DAY) {
+            return MAX_BPS;
+        }
+        let bonus_days = (self.streak - 1).min(MAX_STREAK_BONUS_DAYS);
+        MAX_BPS + bonus_days as u64 * STREAK_BONUS_BPS_PER_DAY
+    }
+
+    pub fn has_achievement(&self, id: u8) -> bool {
+        id < Self::ACHIEVEMENT_COUNT && self.achievements & (1 << id) != 0
+    }
+
+    // Set achievement `id`, returning false if it was already unlocked
+    pub fn unlock_achievement(&mut self, id: u8) -> bool {
+        let unlocked = !self.has_achievement(id);
+        self.achievements |= 1 << id;
+        unlocked
+    }
+
+    // Whether the best score has beaten the one the last trophy was minted for by
+    // at least `margin`. A zero margin means trophies are disabled.
+    pub fn trophy_earned(&self, margin: u64) -> bool {
+        margin > 0 && self.best_score >= self.trophy_best.saturating_add(margin)
+    }
+
+    // Whether this player's referrer is owed credit: the best score has reached
+    // `threshold` and they have not been credited yet. A zero threshold disables referrals.
+    pub fn referral_earned(&self, threshold: u64) -> bool {
+        threshold > 0
+            && self.referrer != Pubkey::default()
+            && !self.referral_credited
+            && self.best_score >= threshold
+    }
+
+    // Whether `key` is the approved delegate; nobody is while none is set
+    pub fn is_delegate(&self, key: &Pubkey) -> bool {
+        self.delegate != Pubkey::default() && self.delegate == *key
+    }
+
+    // Reward tokens earned by the tiers the best score has reached, minus what was already claimed
+    pub fn claimable_reward(&self, reward_per_tier: u64) -> u64 {
+        let tiers = REWARD_TIER_THRESHOLDS
+            .iter()
+            .filter(|&&threshold| self.best_score >= threshold)
+            .count() as u64;
+        tiers
+            .saturating_mul(reward_per_tier)
+            .saturating_sub(self.rewards_claimed)
+    }
+
+    // Deserialize a score account in the current layout from raw account data.
+    // Spare bytes after the fields, left by Realloc, are ignored.
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(&data[..Self::LEN], &Self::DISCRIMINATOR)?;
+        let state = Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)?;
+        if state.version != Self::VERSION {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(state)
+    }
+
+    // Deserialize a score account written in any earlier layout. Fields appended
+    // after that layout read as zero and the version reports what was stored.
+    pub fn unpack_any_version(data: &[u8]) -> Result<Self, ProgramError> {
+        let body = if data.len() == Self::LEN_V0 || data.len() == Self::LEN_V1 {
+            data
+        } else if data.len() >= DISCRIMINATOR_LEN + Self::LEN_V1 {
+            strip_discriminator(data, &Self::DISCRIMINATOR)?
+        } else {
+            return Err(ProgramError::InvalidAccountData);
+        };
+        let mut padded = [0u8; Self::LEN - DISCRIMINATOR_LEN];
+        let len = body.len().min(padded.len());
+        padded[..len].copy_from_slice(&body[..len]);
+        Self::try_from_slice(&padded).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Bump stored in score account data in the current layout, read without
+    // decoding the rest of the account. None for other layouts, which predate it.
+    pub fn stored_bump(data: &[u8]) -> Option<u8> {
+        if data.len() < Self::LEN
+            || data[..DISCRIMINATOR_LEN] != Self::DISCRIMINATOR
+            || data[DISCRIMINATOR_LEN + Self::LEN_V0] != Self::VERSION
+        {
+            return None;
+        }
+        Some(data[Self::BUMP_OFFSET])
+    }
+
+    // Serialize the score account into the front of the account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for ScoreAccount {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Number of leaderboard places paid from the prize pool at season end
+pub const PRIZE_PLACES: usize = 3;
+
+// Basis points in a whole prize pool
+pub const MAX_BPS: u64 = 10_000;
+
+// Staking tiers the config can set score multipliers for
+pub const STAKE_TIERS: usize = 3;
+
+// Fixed-point unit for compounding score decay
+const DECAY_SCALE: u128 = 1_000_000_000_000_000_000;
+
+// `base` (a fraction of DECAY_SCALE) raised to `exp`, by repeated squaring
+fn pow_scaled(mut base: u128, mut exp: u64) -> u128 {
+    let mut result = DECAY_SCALE;
+    while exp > 0 && result > 0 {
+        if exp & 1 == 1 {
+            result = result * base / DECAY_SCALE;
+        }
+        base = base * base / DECAY_SCALE;
+        exp >>= 1;
+    }
+    result
+}
+
+// Program-wide tunables, editable only by the admin
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct Config {
+    pub is_initialized: bool,
+    pub admin: Pubkey,
+    pub max_score: u64,
+    pub max_increment: u64,
+    pub paused: bool,
+    pub update_cooldown_secs: u32,
+    pub game_server: Pubkey,
+    pub reward_mint: Pubkey,
+    pub reward_per_tier: u64,
+    pub ranked_entry_fee: u64,
+    // Shank only reads literal array lengths; this is PRIZE_PLACES
+    pub prize_split_bps: [u16; 3],
+    pub boost_collection: Pubkey,
+    pub boost_multiplier_bps: u16,
+    pub trophy_margin: u64,
+    pub referral_threshold: u64,
+    pub points_per_referral: u64,
+    // Nominated successor, who becomes admin on accepting; default when none
+    pub pending_admin: Pubkey,
+    // Each full window a player goes without updating takes decay_bps off their
+    // score at the next update. A zero window disables decay.
+    pub decay_window_secs: u32,
+    pub decay_bps: u16,
+    // At most max_updates_per_window score updates per player in each window of
+    // throttle_window_slots slots (1 for per slot, an epoch's slots for per
+    // epoch). A zero limit disables the throttle.
+    pub throttle_window_slots: u64,
+    pub max_updates_per_window: u16,
+    // SPL token the shop accepts besides SOL; default when it only takes SOL
+    pub shop_mint: Pubkey,
+    // Reward tokens burned for each Continue (zero disables it) and how many
+    // continues one run may buy (zero for no cap)
+    pub continue_burn_amount: u64,
+    pub max_continues_per_run: u8,
+    // Ranked entry fee in US cents, charged in lamports at the SOL/USD price read
+    // from the Pyth account sol_usd_price_feed; zero charges ranked_entry_fee
+    // instead. Prices older than max_price_age_secs, or whose confidence interval
+    // exceeds max_price_conf_bps of the price, are refused.
+    pub ranked_entry_fee_usd_cents: u64,
+    pub sol_usd_price_feed: Pubkey,
+    pub max_price_age_secs: u32,
+    pub max_price_conf_bps: u16,
+    // Length of each season CrankSeasonRollover starts (zero leaves seasons to
+    // the admin) and the lamports the treasury tips whoever sends a crank
+    pub season_length_secs: u64,
+    pub crank_tip_lamports: u64,
+    // Bump of the config address, checked in place of searching for it
+    pub bump: u8,
+    // Lamports WithdrawTreasury may move out of the treasury each epoch (zero
+    // for no cap), and what was withdrawn in treasury_withdraw_epoch
+    pub treasury_withdraw_cap_lamports: u64,
+    pub treasury_withdraw_epoch: u64,
+    pub treasury_withdrawn_lamports: u64,
+    // Most score a game session may earn per slot it ran (zero for no limit), and
+    // slots after which an unfinished session expires (zero for never)
+    pub max_score_per_slot: u64,
+    pub session_max_slots: u64,
+    // Most score a game session may earn per second between its start and end
+    // timestamps (zero for no limit)
+    pub max_score_per_second: u64,
+    // House cut of every decided match's bet pool, paid to the treasury
+    pub bet_fee_bps: u16,
+    // House cut of ranked and tournament entry fees, settled wagers and SOL shop
+    // purchases, paid to the fee vault rather than the prize pools or treasury
+    pub fee_bps: u16,
+    // Season prizes unlock linearly over prize_vesting_secs from the distribution,
+    // none of them before prize_vesting_cliff_secs have passed; a zero duration
+    // pays them out at once
+    pub prize_vesting_cliff_secs: u64,
+    pub prize_vesting_secs: u64,
+    // Players with at least stake_tier_amounts[i] reward tokens staked have their
+    // score increments multiplied by stake_tier_bps[i]; a zero amount leaves the
+    // tier unused. Shank only reads literal array lengths; these are STAKE_TIERS.
+    pub stake_tier_amounts: [u64; 3],
+    pub stake_tier_bps: [u16; 3],
+    // Seconds staked tokens stay locked after the player last staked
+    pub unstake_cooldown_secs: u32,
+    // Extra multiplier on the staking tier while a 30, 90 or 180 day lock runs
+    // (zero for none), and the share of tokens unstaked before their lock ends
+    // that goes to the treasury instead
+    pub stake_lock_bps: [u16; 3],
+    pub early_unstake_penalty_bps: u16,
+    // Bets and match stakes of at least high_stakes_wager lamports (zero for no
+    // limit) need a player of at least high_stakes_min_level
+    pub high_stakes_wager: u64,
+    pub high_stakes_min_level: u16,
+    // While closed_beta is set, only players proving membership of the
+    // allowlist_root Merkle tree may create score accounts
+    pub closed_beta: bool,
+    pub allowlist_root: [u8; 32],
+    // While set, scores only move through the game server: the player-signed
+    // update paths are refused in favour of SubmitAttestedScore
+    pub require_attestation: bool,
+}
+
+impl Config {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [155, 12, 170, 224, 30, 250, 204, 130];
+
+    // type tag, is_initialized, admin, max score, max increment, paused, cooldown, game server key,
+    // reward mint, reward per tier, ranked entry fee in lamports, prize split,
+    // boost NFT collection, boost multiplier, trophy margin, referral score threshold,
+    // referral points, pending admin, decay window, decay rate, throttle window,
+    // updates allowed per throttle window, shop mint, continue price, continues per run,
+    // ranked entry fee in cents, SOL/USD price feed, max price age, max price confidence,
+    // cranked season length, crank tip, address bump, treasury withdrawal cap per
+    // epoch, epoch of the last withdrawal, lamports withdrawn in it, game session
+    // score per slot, game session lifetime, game session score per second,
+    // betting house fee, house fee, prize vesting cliff, prize vesting duration,
+    // staking tier amounts and multipliers, unstake cooldown, lock multipliers,
+    // early unstake penalty, high-stakes wager, high-stakes minimum level, closed
+    // beta flag, allowlist Merkle root, attestation requirement
+    pub const LEN: usize = DISCRIMINATOR_LEN
+        + 1
+        + 32
+        + 8
+        + 8
+        + 1
+        + 4
+        + 32
+        + 32
+        + 8
+        + 8
+        + 2 * PRIZE_PLACES
+        + 32
+        + 2
+        + 8
+        + 8
+        + 8
+        + 32
+        + 4
+        + 2
+        + 8
+        + 2
+        + 32
+        + 8
+        + 1
+        + 8
+        + 32
+        + 4
+        + 2
+        + 8
+        + 8
+        + 1
+        + 8
+        + 8
+        + 8
+        + 8
+        + 8
+        + 8
+        + 2
+        + 2
+        + 8
+        + 8
+        + 8 * STAKE_TIERS
+        + 2 * STAKE_TIERS
+        + 4
+        + 2 * 3
+        + 2
+        + 8
+        + 2
+        + 1
+        + 32
+        + 1;
+
+    // Split applied until the admin sets another: 50/30/20
+    pub const DEFAULT_PRIZE_SPLIT_BPS: [u16; PRIZE_PLACES] = [5_000, 3_000, 2_000];
+
+    // The fee_bps house cut of `amount` lamports
+    pub fn house_fee(&self, amount: u64) -> u64 {
+        (amount as u128 * u64::from(self.fee_bps).min(MAX_BPS) as u128 / MAX_BPS as u128) as u64
+    }
+
+    // Multiplier earned by `staked` reward tokens: the best tier they reach, or
+    // MAX_BPS (none) below every tier
+    pub fn stake_multiplier_bps(&self, staked: u64) -> u64 {
+        self.stake_tier_amounts
+            .iter()
+            .zip(&self.stake_tier_bps)
+            .filter(|(&amount, _)| amount > 0 && staked >= amount)
+            .map(|(_, &bps)| u64::from(bps))
+            .fold(MAX_BPS, u64::max)
+    }
+
+    // Multiplier `stake` earns at `now`: its staking tier, boosted by its lock
+    // while the lock runs
+    pub fn staked_multiplier_bps(&self, stake: &Stake, now: i64) -> u64 {
+        let tier_bps = self.stake_multiplier_bps(stake.amount);
+        let lock_bps = match stake.lock.index() {
+            Some(lock) if stake.is_lock_active(now) => u64::from(self.stake_lock_bps[lock]).max(MAX_BPS),
+            _ => MAX_BPS,
+        };
+        tier_bps * lock_bps / MAX_BPS
+    }
+
+    // The early_unstake_penalty_bps share of `amount` tokens
+    pub fn early_unstake_penalty(&self, amount: u64) -> u64 {
+        (amount as u128 * u64::from(self.early_unstake_penalty_bps).min(MAX_BPS) as u128 / MAX_BPS as u128) as u64
+    }
+
+    // Lamports owed to each paid place out of a pool of `pool` lamports. Places
+    // beyond the number of leaderboard entries get nothing and their share stays
+    // in the pool.
+    pub fn prize_shares(&self, pool: u64, entries: usize) -> [u64; PRIZE_PLACES] {
+        let mut shares = [0u64; PRIZE_PLACES];
+        for (share, &bps) in shares.iter_mut().zip(&self.prize_split_bps).take(entries) {
+            *share = (pool as u128 * bps as u128 / MAX_BPS as u128) as u64;
+        }
+        shares
+    }
+
+    // Lamports that ranked_entry_fee_usd_cents costs at a SOL/USD price of
+    // `price * 10^expo`, rounded up so the pool is never short. None for a
+    // non-positive price or a fee too large to represent.
+    pub fn usd_fee_lamports(&self, price: i64, expo: i32) -> Option<u64> {
+        let price = u128::try_from(price).ok().filter(|&price| price > 0)?;
+        // cents / 100 USD at price * 10^expo USD per SOL, times 10^9 lamports per SOL
+        let scale = 10u128.checked_pow(expo.unsigned_abs())?;
+        let (numerator, denominator) = if expo < 0 {
+            (
+                (self.ranked_entry_fee_usd_cents as u128)
+                    .checked_mul(10_000_000)?
+                    .checked_mul(scale)?,
+                price,
+            )
+        } else {
+            (
+                (self.ranked_entry_fee_usd_cents as u128).checked_mul(10_000_000)?,
+                price.checked_mul(scale)?,
+            )
+        };
+        u64::try_from(numerator.div_ceil(denominator)).ok()
+    }
+
+    // Count a treasury withdrawal of `amount` lamports in `epoch` against the
+    // per-epoch cap. Returns false, counting nothing, if it would exceed the cap.
+    pub fn record_treasury_withdrawal(&mut self, epoch: u64, amount: u64) -> bool {
+        let withdrawn = if self.treasury_withdraw_epoch == epoch {
+            self.treasury_withdrawn_lamports
+        } else {
+            0
+        };
+        let Some(total) = withdrawn.checked_add(amount) else {
+            return false;
+        };
+        if self.treasury_withdraw_cap_lamports > 0 && total > self.treasury_withdraw_cap_lamports {
+            return false;
+        }
+        self.treasury_withdraw_epoch = epoch;
+        self.treasury_withdrawn_lamports = total;
+        true
+    }
+
+    // Deserialize the config account from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the config account into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for Config {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Current season window; scores from earlier seasons are dropped on their next update
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct Season {
+    pub is_initialized: bool,
+    pub season_id: u64,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub prizes_distributed: bool,
+}
+
+impl Season {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [76, 67, 93, 156, 180, 157, 248, 47];
+
+    // type tag, is_initialized, season id, start and end timestamps, prizes distributed
+    pub const LEN: usize = DISCRIMINATOR_LEN + 1 + 8 + 8 + 8 + 1;
+
+    // Deserialize the season account from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the season account into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for Season {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// How long staked tokens are committed for a boosted multiplier
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankType, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+#[borsh(use_discriminant = true)]
+#[repr(u8)]
+pub enum StakeLock {
+    #[default]
+    None = 0,
+    Days30 = 1,
+    Days90 = 2,
+    Days180 = 3,
+}
+
+impl StakeLock {
+    // Position in Config::stake_lock_bps, or `None` for no lock
+    pub fn index(self) -> Option<usize> {
+        match self {
+            StakeLock::None => None,
+            lock => Some(lock as usize - 1),
+        }
+    }
+
+    pub fn duration_secs(self) -> i64 {
+        SECONDS_PER_DAY
+            * match self {
+                StakeLock::None => 0,
+                StakeLock::Days30 => 30,
+                StakeLock::Days90 => 90,
+                StakeLock::Days180 => 180,
+            }
+    }
+}
+
+// Reward tokens a player has staked for a score multiplier (seeds: "stake",
+// player). The tokens sit in the player's stake vault.
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct Stake {
+    pub is_initialized: bool,
+    pub player: Pubkey,
+    pub amount: u64,
+    // When the player last staked; unstaking waits out the cooldown from here
+    pub last_stake_ts: i64,
+    // Lock over everything staked and when it ends; unstaking earlier pays the
+    // config's early unstake penalty
+    pub lock: StakeLock,
+    pub lock_end_ts: i64,
+}
+
+impl Stake {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [150, 197, 176, 29, 55, 132, 112, 149];
+
+    // type tag, is_initialized, player, amount, last stake timestamp, lock, lock end
+    pub const LEN: usize = DISCRIMINATOR_LEN + 1 + 32 + 8 + 8 + 1 + 8;
+
+    // Whether the tokens are still locked at `now` with an unstake cooldown of `cooldown_secs`
+    pub fn is_locked(&self, now: i64, cooldown_secs: u32) -> bool {
+        now < self.last_stake_ts.saturating_add(i64::from(cooldown_secs))
+    }
+
+    // Whether the stake's lock still runs at `now`
+    pub fn is_lock_active(&self, now: i64) -> bool {
+        self.lock != StakeLock::None && now < self.lock_end_ts
+    }
+
+    // Apply `lock` from `now` to everything staked. A lock only replaces the
+    // current one if it ends no earlier, so staking more never shortens a lock.
+    pub fn apply_lock(&mut self, lock: StakeLock, now: i64) {
+        let end_ts = now.saturating_add(lock.duration_secs());
+        if lock != StakeLock::None && (!self.is_lock_active(now) || end_ts >= self.lock_end_ts) {
+            self.lock = lock;
+            self.lock_end_ts = end_ts;
+        }
+    }
+
+    // Deserialize a stake account from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the stake account into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for Stake {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// A season prize streaming to its winner (seeds: "vesting", season id, winner).
+// The prize lamports are held in the account on top of its rent-exempt minimum.
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct Vesting {
+    pub is_initialized: bool,
+    pub beneficiary: Pubkey,
+    pub season_id: u64,
+    pub total: u64,
+    pub claimed: u64,
+    // Vesting runs linearly from start_ts to end_ts, nothing unlocking before cliff_ts
+    pub start_ts: i64,
+    pub cliff_ts: i64,
+    pub end_ts: i64,
+}
+
+impl Vesting {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [100, 149, 66, 138, 95, 200, 128, 241];
+
+    // type tag, is_initialized, beneficiary, season id, total, claimed, start,
+    // cliff and end timestamps
+    pub const LEN: usize = DISCRIMINATOR_LEN + 1 + 32 + 8 + 8 + 8 + 8 + 8 + 8;
+
+    // Lamports of the total unlocked at `now`, claimed or not
+    pub fn unlocked(&self, now: i64) -> u64 {
+        if now < self.cliff_ts {
+            0
+        } else if now >= self.end_ts {
+            self.total
+        } else {
+            let elapsed = (now - self.start_ts) as u128;
+            let duration = (self.end_ts - self.start_ts) as u128;
+            (self.total as u128 * elapsed / duration) as u64
+        }
+    }
+
+    // Lamports unlocked at `now` and not yet claimed
+    pub fn claimable(&self, now: i64) -> u64 {
+        self.unlocked(now).saturating_sub(self.claimed)
+    }
+
+    // Deserialize a vesting account from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the vesting account into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for Vesting {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Number of ranked entries kept on the global leaderboard
+pub const LEADERBOARD_SIZE: usize = 100;
+
+// Pages ranking players past the global board, which is page 0. Each page holds
+// LEADERBOARD_SIZE entries, so the pages together rank the top 1000.
+pub const LEADERBOARD_PAGES: u8 = 10;
+
+// The one mode ranked runs are played in. Scores from different modes don't
+// compare, so the global leaderboard, its pages and the weekly leaderboard rank
+// this mode alone.
+pub const RANKED_GAME_MODE: GameMode = GameMode::Arcade;
+
+// A single ranked leaderboard entry. The score is kept as little-endian bytes so
+// the entry has no alignment requirement and can be viewed in place.
+#[repr(C)]
+#[derive(ShankType, Clone, Copy, Debug, Default, PartialEq, Eq, Pod, Zeroable)]
+pub struct LeaderboardEntry {
+    pub player: Pubkey,
+    score: [u8; 8],
+}
+
+impl LeaderboardEntry {
+    pub const LEN: usize = 32 + 8; // 32 bytes player pubkey, 8 bytes score
+
+    pub fn new(player: Pubkey, score: u64) -> Self {
+        Self {
+            player,
+            score: score.to_le_bytes(),
+        }
+    }
+
+    pub fn score(&self) -> u64 {
+        u64::from_le_bytes(self.score)
+    }
+}
+
+// Leaderboard holding the top entries sorted by descending score. It is the
+// largest account and every submission rewrites it, so it is viewed in place over
+// the account data instead of being deserialized. The bytes match the Borsh
+// encoding of a bool, a u32 entry count and the entries.
+#[repr(C)]
+#[derive(ShankAccount, Clone, Copy, Debug, PartialEq, Eq, Pod, Zeroable)]
+pub struct Leaderboard {
+    is_initialized: u8,
+    // Little-endian number of entries in use
+    entry_count: [u8; 4],
+    // Shank only reads literal array lengths; this is LEADERBOARD_SIZE
+    entries: [LeaderboardEntry; 100],
+}
+
+impl Default for Leaderboard {
+    fn default() -> Self {
+        Self::zeroed()
+    }
+}
+
+impl Leaderboard {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [247, 186, 238, 243, 194, 30, 9, 36];
+
+    // Type tag, 1 byte is_initialized, 4 bytes entry count, then room for every entry
+    pub const LEN: usize = DISCRIMINATOR_LEN + 1 + 4 + LEADERBOARD_SIZE * LeaderboardEntry::LEN;
+
+    // View leaderboard account data in place
+    pub fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        let board: &Self =
+            bytemuck::try_from_bytes(body).map_err(|_| ProgramError::InvalidAccountData)?;
+        board.check_entry_count()?;
+        Ok(board)
+    }
+
+    // Mutable view of leaderboard account data; changes land directly in the account
+    pub fn load_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        let board: &mut Self = bytemuck::try_from_bytes_mut(&mut data[DISCRIMINATOR_LEN..])
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        board.check_entry_count()?;
+        Ok(board)
+    }
+
+    // Write an empty, initialized leaderboard over freshly allocated account data
+    pub fn init(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = write_discriminator(data, &Self::DISCRIMINATOR);
+        body.fill(0);
+        let board: &mut Self =
+            bytemuck::try_from_bytes_mut(body).map_err(|_| ProgramError::InvalidAccountData)?;
+        board.is_initialized = 1;
+        Ok(board)
+    }
+
+    fn check_entry_count(&self) -> ProgramResult {
+        if u32::from_le_bytes(self.entry_count) as usize > LEADERBOARD_SIZE {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        (u32::from_le_bytes(self.entry_count) as usize).min(LEADERBOARD_SIZE)
+    }
+
+    fn set_len(&mut self, len: usize) {
+        self.entry_count = (len as u32).to_le_bytes();
+    }
+
+    // Ranked entries, best first
+    pub fn entries(&self) -> &[LeaderboardEntry] {
+        &self.entries[..self.len()]
+    }
+
+    // Remove every entry
+    pub fn clear(&mut self) {
+        self.entries = Zeroable::zeroed();
+        self.set_len(0);
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len() >= LEADERBOARD_SIZE
+    }
+
+    // Lowest ranked entry, if there is one
+    pub fn last(&self) -> Option<&LeaderboardEntry> {
+        self.entries().last()
+    }
+
+    pub fn contains(&self, player: &Pubkey) -> bool {
+        self.entries().iter().any(|e| e.player == *player)
+    }
+
+    // Insert or raise the player's entry, keeping the board sorted and capped.
+    // Returns the player's zero-based rank if they are on the board afterwards.
+    pub fn submit(&mut self, player: Pubkey, score: u64) -> Option<usize> {
+        self.submit_displacing(player, score).0
+    }
+
+    // Submit, also returning the entry a full board dropped to make room, which
+    // belongs at the top of the next page down
+    pub fn submit_displacing(
+        &mut self,
+        player: Pubkey,
+        score: u64,
+    ) -> (Option<usize>, Option<LeaderboardEntry>) {
+        let mut len = self.len();
+        let mut displaced = None;
+        if let Some(pos) = self.entries().iter().position(|e| e.player == player) {
+            if self.entries[pos].score() >= score {
+                return (Some(pos), None);
+            }
+            self.entries.copy_within(pos + 1..len, pos);
+            len -= 1;
+        } else if len >= LEADERBOARD_SIZE {
+            // A full board only admits scores strictly above the lowest entry
+            if self.entries[len - 1].score() >= score {
+                return (None, None);
+            }
+            len -= 1;
+            displaced = Some(self.entries[len]);
+        }
+
+        // Ties keep the earlier entry ahead of the new one
+        let rank = self.entries[..len].partition_point(|e| e.score() >= score);
+        self.entries.copy_within(rank..len, rank + 1);
+        self.entries[rank] = LeaderboardEntry::new(player, score);
+        self.set_len(len + 1);
+        (Some(rank), displaced)
+    }
+
+    // Take the top entry off the board
+    fn pop_first(&mut self) -> Option<LeaderboardEntry> {
+        let len = self.len();
+        let first = *self.entries().first()?;
+        self.entries.copy_within(1..len, 0);
+        self.entries[len - 1] = LeaderboardEntry::zeroed();
+        self.set_len(len - 1);
+        Some(first)
+    }
+
+    // Restore the order between this page and `lower`, the next one down: move
+    // the best of `lower` up while this page has room or they outrank its lowest
+    // entry, and demote what they displace. An entry for a player who already
+    // ranks higher here is dropped. Returns the number of entries taken from `lower`.
+    pub fn rebalance(&mut self, lower: &mut Leaderboard) -> usize {
+        let mut taken = 0;
+        while let Some(top) = lower.entries().first().copied() {
+            if self
+                .last()
+                .is_some_and(|last| self.is_full() && last.score() >= top.score())
+            {
+                break;
+            }
+            lower.pop_first();
+            let (_, displaced) = self.submit_displacing(top.player, top.score());
+            if let Some(displaced) = displaced {
+                lower.submit(displaced.player, displaced.score());
+            }
+            taken += 1;
+        }
+        taken
+    }
+}
+
+impl IsInitialized for Leaderboard {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized != 0
+    }
+}
+
+// Leaderboard of the best scores recorded during one week, kept alongside the
+// all-time board. It starts over the first time a score is submitted in a later
+// week, so every week's ranking begins empty.
+#[repr(C)]
+#[derive(ShankAccount, Clone, Copy, Debug, PartialEq, Eq, Pod, Zeroable)]
+pub struct WeeklyLeaderboard {
+    // Little-endian week the entries were recorded in, see `week_of`
+    week: [u8; 8],
+    pub board: Leaderboard,
+}
+
+impl Default for WeeklyLeaderboard {
+    fn default() -> Self {
+        Self::zeroed()
+    }
+}
+
+impl WeeklyLeaderboard {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [112, 136, 1, 92, 43, 158, 221, 13];
+
+    // Type tag, 8 bytes week, then the leaderboard without its type tag
+    pub const LEN: usize = DISCRIMINATOR_LEN + 8 + Leaderboard::LEN - DISCRIMINATOR_LEN;
+
+    // View weekly leaderboard account data in place
+    pub fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        let weekly: &Self =
+            bytemuck::try_from_bytes(body).map_err(|_| ProgramError::InvalidAccountData)?;
+        weekly.board.check_entry_count()?;
+        Ok(weekly)
+    }
+
+    // Mutable view of weekly leaderboard account data
+    pub fn load_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        let weekly: &mut Self = bytemuck::try_from_bytes_mut(&mut data[DISCRIMINATOR_LEN..])
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        weekly.board.check_entry_count()?;
+        Ok(weekly)
+    }
+
+    // Write an empty, initialized weekly leaderboard for `week` over freshly
+    // allocated account data
+    pub fn init(data: &mut [u8], week: i64) -> Result<&mut Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = write_discriminator(data, &Self::DISCRIMINATOR);
+        body.fill(0);
+        let weekly: &mut Self =
+            bytemuck::try_from_bytes_mut(body).map_err(|_| ProgramError::InvalidAccountData)?;
+        weekly.week = week.to_le_bytes();
+        weekly.board.is_initialized = 1;
+        Ok(weekly)
+    }
+
+    pub fn week(&self) -> i64 {
+        i64::from_le_bytes(self.week)
+    }
+
+    // Clear the entries once `week` is later than the one they were recorded in.
+    // Returns whether the board started over.
+    pub fn roll_week(&mut self, week: i64) -> bool {
+        if week <= self.week() {
+            return false;
+        }
+        self.board.clear();
+        self.week = week.to_le_bytes();
+        true
+    }
+}
+
+impl IsInitialized for WeeklyLeaderboard {
+    fn is_initialized(&self) -> bool {
+        self.board.is_initialized()
+    }
+}
+
+// The daily challenge for one UTC day (seeds: "daily_challenge", day): the seed
+// every player's game generates the day's level from, and the day's leaderboard.
+// Each player submits to it once, recorded by their DailySubmission.
+#[repr(C)]
+#[derive(ShankAccount, Clone, Copy, Debug, PartialEq, Eq, Pod, Zeroable)]
+pub struct DailyChallenge {
+    // Little-endian UTC day, unix time divided by SECONDS_PER_DAY
+    day: [u8; 8],
+    pub seed: [u8; 32],
+    pub board: Leaderboard,
+}
+
+impl Default for DailyChallenge {
+    fn default() -> Self {
+        Self::zeroed()
+    }
+}
+
+impl DailyChallenge {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [217, 74, 215, 176, 49, 63, 217, 226];
+
+    // Type tag, 8 bytes day, 32 bytes seed, then the leaderboard without its type tag
+    pub const LEN: usize = DISCRIMINATOR_LEN + 8 + 32 + Leaderboard::LEN - DISCRIMINATOR_LEN;
+
+    // View daily challenge account data in place
+    pub fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        let daily: &Self =
+            bytemuck::try_from_bytes(body).map_err(|_| ProgramError::InvalidAccountData)?;
+        daily.board.check_entry_count()?;
+        Ok(daily)
+    }
+
+    // Mutable view of daily challenge account data
+    pub fn load_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        let daily: &mut Self = bytemuck::try_from_bytes_mut(&mut data[DISCRIMINATOR_LEN..])
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        daily.board.check_entry_count()?;
+        Ok(daily)
+    }
+
+    // Write the challenge for `day` with an empty leaderboard over freshly
+    // allocated account data
+    pub fn init(data: &mut [u8], day: i64, seed: [u8; 32]) -> Result<&mut Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = write_discriminator(data, &Self::DISCRIMINATOR);
+        body.fill(0);
+        let daily: &mut Self =
+            bytemuck::try_from_bytes_mut(body).map_err(|_| ProgramError::InvalidAccountData)?;
+        daily.day = day.to_le_bytes();
+        daily.seed = seed;
+        daily.board.is_initialized = 1;
+        Ok(daily)
+    }
+
+    pub fn day(&self) -> i64 {
+        i64::from_le_bytes(self.day)
+    }
+}
+
+impl IsInitialized for DailyChallenge {
+    fn is_initialized(&self) -> bool {
+        self.board.is_initialized()
+    }
+}
+
+// A player's one submission to a day's challenge (seeds: "daily_submission",
+// day, player). Its existence is what turns away a second submission.
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct DailySubmission {
+    pub is_initialized: bool,
+    pub player: Pubkey,
+    pub day: i64,
+    pub score: u64,
+}
+
+impl DailySubmission {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [203, 113, 154, 13, 92, 93, 47, 57];
+
+    // type tag, is_initialized, player, day, score
+    pub const LEN: usize = DISCRIMINATOR_LEN + 1 + 32 + 8 + 8;
+
+    // Deserialize a daily submission from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the daily submission into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for DailySubmission {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Lifecycle of a wager match: stakes are locked on creation, the game server
+// records the winner, and settling pays the pot out and closes the account
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankType, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+#[borsh(use_discriminant = true)]
+#[repr(u8)]
+pub enum MatchStatus {
+    #[default]
+    Active = 0,
+    Decided = 1,
+}
+
+// Head-to-head wager between two players. Both stakes are held as lamports in
+// the account on top of its rent-exempt minimum.
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct Match {
+    pub is_initialized: bool,
+    pub match_id: u64,
+    pub player_a: Pubkey,
+    pub player_b: Pubkey,
+    pub stake: u64,
+    pub status: MatchStatus,
+    pub winner: Pubkey,
+}
+
+impl Match {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [236, 63, 169, 38, 15, 56, 196, 162];
+
+    // type tag, is_initialized, match id, both players, stake per player, status, winner
+    pub const LEN: usize = DISCRIMINATOR_LEN + 1 + 8 + 32 + 32 + 8 + 1 + 32;
+
+    // Lamports paid to the winner: both players' stakes
+    pub fn pot(&self) -> Option<u64> {
+        self.stake.checked_mul(2)
+    }
+
+    pub fn is_player(&self, key: &Pubkey) -> bool {
+        self.player_a == *key || self.player_b == *key
+    }
+
+    // Deserialize a match account from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the match account into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for Match {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Lifecycle of the spectator bets on a match: taken while the match is active,
+// paid out pro-rata once its result is recorded, or refunded when nobody backed
+// the winner or the match closed before its result reached the pool
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankType, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+#[borsh(use_discriminant = true)]
+#[repr(u8)]
+pub enum BetPoolStatus {
+    #[default]
+    Open = 0,
+    Decided = 1,
+    Void = 2,
+}
+
+// Spectator bets on one match (seeds: "bet_pool", match). The bettors' lamports
+// are held in the account on top of its rent-exempt minimum.
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct BetPool {
+    pub is_initialized: bool,
+    pub match_account: Pubkey,
+    pub player_a: Pubkey,
+    pub player_b: Pubkey,
+    pub staked_on_a: u64,
+    pub staked_on_b: u64,
+    // Bets placed and not yet settled; the pool closes when the last one is
+    pub open_bets: u32,
+    pub status: BetPoolStatus,
+    pub winner: Pubkey,
+    // Lamports shared among the winning bets, what is left after the house fee
+    pub payout_total: u64,
+}
+
+impl BetPool {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [18, 177, 201, 70, 154, 231, 26, 62];
+
+    // type tag, is_initialized, match, both players, lamports on each player,
+    // open bets, status, winner, payout total
+    pub const LEN: usize = DISCRIMINATOR_LEN + 1 + 32 + 32 + 32 + 8 + 8 + 4 + 1 + 32 + 8;
+
+    // Lamports bet on `player`, or `None` if they are not in the match
+    pub fn staked_on(&self, player: &Pubkey) -> Option<u64> {
+        if *player == self.player_a {
+            Some(self.staked_on_a)
+        } else if *player == self.player_b {
+            Some(self.staked_on_b)
+        } else {
+            None
+        }
+    }
+
+    // Add `amount` lamports to the side of `player`. Returns `None`, adding
+    // nothing, if they are not in the match or the total would overflow.
+    pub fn add_stake(&mut self, player: &Pubkey, amount: u64) -> Option<()> {
+        let staked = if *player == self.player_a {
+            &mut self.staked_on_a
+        } else if *player == self.player_b {
+            &mut self.staked_on_b
+        } else {
+            return None;
+        };
+        *staked = staked.checked_add(amount)?;
+        self.staked_on_a.checked_add(self.staked_on_b)?;
+        Some(())
+    }
+
+    // Record `winner` and take `fee_bps` of everything staked for the house.
+    // Returns the fee; nothing is taken when the pool is void because nobody
+    // backed the winner.
+    pub fn decide(&mut self, winner: Pubkey, fee_bps: u16) -> u64 {
+        let total = self.staked_on_a + self.staked_on_b;
+        if self.staked_on(&winner).unwrap_or(0) == 0 {
+            self.status = BetPoolStatus::Void;
+            return 0;
+        }
+        let fee =
+            (total as u128 * u64::from(fee_bps).min(MAX_BPS) as u128 / MAX_BPS as u128) as u64;
+        self.status = BetPoolStatus::Decided;
+        self.winner = winner;
+        self.payout_total = total - fee;
+        fee
+    }
+
+    // Lamports owed for `bet`: its share of the payout total if it backed the
+    // winner, its stake back if the pool is void, and nothing otherwise
+    pub fn payout(&self, bet: &Bet) -> u64 {
+        match self.status {
+            BetPoolStatus::Open => 0,
+            BetPoolStatus::Void => bet.amount,
+            BetPoolStatus::Decided if bet.on == self.winner => {
+                let winning = self.staked_on(&self.winner).unwrap_or(0).max(1);
+                (bet.amount as u128 * self.payout_total as u128 / winning as u128) as u64
+            }
+            BetPoolStatus::Decided => 0,
+        }
+    }
+
+    // Deserialize a bet pool from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the bet pool into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for BetPool {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// One spectator's bet on a match (seeds: "bet", match, bettor). The staked
+// lamports sit in the match's bet pool.
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct Bet {
+    pub is_initialized: bool,
+    pub match_account: Pubkey,
+    pub bettor: Pubkey,
+    // Player the bet backs
+    pub on: Pubkey,
+    pub amount: u64,
+}
+
+impl Bet {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [147, 23, 35, 59, 15, 75, 155, 32];
+
+    // type tag, is_initialized, match, bettor, backed player, amount
+    pub const LEN: usize = DISCRIMINATOR_LEN + 1 + 32 + 32 + 32 + 8;
+
+    // Deserialize a bet from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the bet into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for Bet {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Most players one matchmaking lobby can hold
+pub const LOBBY_CAPACITY: usize = 8;
+
+// Matchmaking queue opened by a host (seeds: "lobby", host). Players join by
+// paying `stake` lamports, which the account holds on top of its rent-exempt
+// minimum until they leave or are paired: StartMatch takes the two players who
+// have waited longest into a new wager match.
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct Lobby {
+    pub is_initialized: bool,
+    pub host: Pubkey,
+    pub capacity: u8,
+    pub stake: u64,
+    // Waiting players, longest-waiting first, padded with default pubkeys; Shank
+    // only reads literal array lengths, this is LOBBY_CAPACITY
+    pub players: [Pubkey; 8],
+    pub player_count: u8,
+    // Matches started from the lobby, which numbers the next one
+    pub matches_started: u64,
+}
+
+impl Lobby {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [167, 194, 217, 163, 92, 92, 103, 49];
+
+    // type tag, is_initialized, host, capacity, stake, waiting players, player count,
+    // matches started
+    pub const LEN: usize = DISCRIMINATOR_LEN + 1 + 32 + 1 + 8 + 32 * LOBBY_CAPACITY + 1 + 8;
+
+    pub fn players(&self) -> &[Pubkey] {
+        &self.players[..(self.player_count as usize).min(LOBBY_CAPACITY)]
+    }
+
+    pub fn is_waiting(&self, key: &Pubkey) -> bool {
+        self.players().contains(key)
+    }
+
+    // Queue `player` behind everyone already waiting. Returns false once the
+    // lobby is at capacity.
+    pub fn join(&mut self, player: Pubkey) -> bool {
+        let count = self.players().len();
+        if count >= (self.capacity as usize).min(LOBBY_CAPACITY) {
+            return false;
+        }
+        self.players[count] = player;
+        self.player_count += 1;
+        true
+    }
+
+    // Take `player` out of the queue, keeping everyone else's order. Returns
+    // false if they were not waiting.
+    pub fn leave(&mut self, player: &Pubkey) -> bool {
+        let count = self.players().len();
+        let Some(index) = self.players().iter().position(|p| p == player) else {
+            return false;
+        };
+        self.players.copy_within(index + 1..count, index);
+        self.players[count - 1] = Pubkey::default();
+        self.player_count -= 1;
+        true
+    }
+
+    // Take the two longest-waiting players out of the queue, or `None` while
+    // fewer than two are waiting
+    pub fn take_pair(&mut self) -> Option<(Pubkey, Pubkey)> {
+        let (first, second) = match self.players() {
+            [first, second, ..] => (*first, *second),
+            _ => return None,
+        };
+        self.leave(&first);
+        self.leave(&second);
+        Some((first, second))
+    }
+
+    // Deserialize a lobby from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the lobby into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for Lobby {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Most participants a single tournament can hold
+pub const TOURNAMENT_CAPACITY: usize = 32;
+
+// Registered tournament player and the best score the game server has recorded for them
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankType, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct TournamentEntry {
+    pub player: Pubkey,
+    pub score: u64,
+}
+
+impl TournamentEntry {
+    pub const LEN: usize = 32 + 8; // 32 bytes player pubkey, 8 bytes score
+}
+
+// Admin-run tournament. Entry fees are held as lamports in the account on top of
+// its rent-exempt minimum and paid to the top places when it is finalized.
+#[derive(BorshSerialize, BorshDeserialize, ShankAccount, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Tournament {
+    pub is_initialized: bool,
+    pub tournament_id: u64,
+    pub creator: Pubkey,
+    pub entry_fee: u64,
+    pub max_participants: u8,
+    pub end_ts: i64,
+    pub finalized: bool,
+    // Verified Metaplex collection registrants must hold an NFT from; the default
+    // pubkey leaves the tournament open to everyone
+    pub collection: Pubkey,
+    pub participants: Vec<TournamentEntry>,
+}
+
+impl Tournament {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [175, 139, 119, 242, 115, 194, 57, 92];
+
+    // Type tag, is_initialized, id, creator, entry fee, cap, end timestamp, finalized,
+    // collection, 4 bytes vector length, then room for a full tournament
+    pub const LEN: usize = DISCRIMINATOR_LEN
+        + 1
+        + 8
+        + 32
+        + 8
+        + 1
+        + 8
+        + 1
+        + 32
+        + 4
+        + TOURNAMENT_CAPACITY * TournamentEntry::LEN;
+
+    pub fn is_gated(&self) -> bool {
+        self.collection != Pubkey::default()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.participants.len() >= self.max_participants as usize
+    }
+
+    pub fn entry_mut(&mut self, player: &Pubkey) -> Option<&mut TournamentEntry> {
+        self.participants.iter_mut().find(|e| e.player == *player)
+    }
+
+    // Participants ordered by descending score; ties go to whoever registered first
+    pub fn standings(&self) -> Vec<TournamentEntry> {
+        let mut standings = self.participants.clone();
+        standings.sort_by_key(|e| std::cmp::Reverse(e.score));
+        standings
+    }
+
+    // Deserialize the tournament, ignoring the unused tail of the account
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        let tournament =
+            Self::deserialize(&mut body).map_err(|_| ProgramError::InvalidAccountData)?;
+        if tournament.participants.len() > TOURNAMENT_CAPACITY {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(tournament)
+    }
+
+    // Serialize the tournament into the front of the account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::LEN || self.participants.len() > TOURNAMENT_CAPACITY {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for Tournament {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Most members a guild can hold
+pub const GUILD_CAPACITY: u32 = 50;
+
+// Group of players whose score increments add up to a shared total
+// (seeds: "guild", guild id). The guild leaderboard reuses the `Leaderboard`
+// layout with guild addresses in place of players.
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct Guild {
+    pub is_initialized: bool,
+    pub guild_id: u64,
+    pub leader: Pubkey,
+    // Shank only reads literal array lengths; this is PROFILE_NAME_LEN
+    pub name: [u8; 32],
+    pub member_count: u32,
+    pub total_score: u64,
+}
+
+impl Guild {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [74, 176, 57, 164, 195, 188, 156, 237];
+
+    // type tag, is_initialized, guild id, leader, display name, member count, total score
+    pub const LEN: usize = DISCRIMINATOR_LEN + 1 + 8 + 32 + PROFILE_NAME_LEN + 4 + 8;
+
+    pub fn is_full(&self) -> bool {
+        self.member_count >= GUILD_CAPACITY
+    }
+
+    // Deserialize a guild from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the guild into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for Guild {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// A player's membership of one guild and the score they have added to it
+// (seeds: "guild_member", player)
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct GuildMember {
+    pub is_initialized: bool,
+    pub player: Pubkey,
+    pub guild: Pubkey,
+    pub contributed: u64,
+}
+
+impl GuildMember {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [255, 176, 230, 246, 44, 246, 114, 56];
+
+    // type tag, is_initialized, player, guild address, contributed score
+    pub const LEN: usize = DISCRIMINATOR_LEN + 1 + 32 + 32 + 8;
+
+    // Deserialize a guild membership from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the guild membership into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for GuildMember {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Runs a new score history keeps before the oldest is dropped
+pub const HISTORY_CAPACITY: usize = 16;
+
+// Final score of one submitted run and when it was recorded
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankType, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct RunRecord {
+    pub score: u64,
+    pub timestamp: i64,
+}
+
+impl RunRecord {
+    pub const LEN: usize = 8 + 8; // 8 bytes score, 8 bytes timestamp
+}
+
+// A player's most recent run scores in one mode, oldest first (seeds: "history",
+// player, game_mode). Capacity is set by the account size, so growing the account
+// keeps more runs.
+#[derive(BorshSerialize, BorshDeserialize, ShankAccount, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScoreHistory {
+    pub is_initialized: bool,
+    pub player: Pubkey,
+    pub game_mode: GameMode,
+    pub runs: Vec<RunRecord>,
+}
+
+impl ScoreHistory {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [151, 42, 88, 82, 13, 159, 2, 172];
+
+    // Type tag, is_initialized, player, game mode, 4 bytes vector length
+    pub const HEADER_LEN: usize = DISCRIMINATOR_LEN + 1 + 32 + 1 + 4;
+
+    // Account size that holds `capacity` runs
+    pub const fn space(capacity: usize) -> usize {
+        Self::HEADER_LEN + capacity * RunRecord::LEN
+    }
+
+    // Runs an account of `data_len` bytes can hold
+    pub fn capacity(data_len: usize) -> usize {
+        data_len.saturating_sub(Self::HEADER_LEN) / RunRecord::LEN
+    }
+
+    // Append a run, dropping the oldest ones once `capacity` runs are kept
+    pub fn push(&mut self, record: RunRecord, capacity: usize) {
+        self.runs.push(record);
+        if self.runs.len() > capacity {
+            let excess = self.runs.len() - capacity;
+            self.runs.drain(..excess);
+        }
+    }
+
+    // Deserialize a history, ignoring the unused tail of the account
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() < Self::HEADER_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        let history = Self::deserialize(&mut body).map_err(|_| ProgramError::InvalidAccountData)?;
+        if history.runs.len() > Self::capacity(data.len()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(history)
+    }
+
+    // Serialize the history into the front of the account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() < Self::space(self.runs.len()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for ScoreHistory {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Authorizes a temporary session keypair to submit a player's score updates until
+// an expiry slot (seeds: "session", player, session key)
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct SessionToken {
+    pub is_initialized: bool,
+    pub player: Pubkey,
+    pub session_key: Pubkey,
+    pub expiry_slot: u64,
+}
+
+impl SessionToken {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [233, 4, 115, 14, 46, 21, 1, 15];
+
+    // type tag, is_initialized, player, session key, expiry slot
+    pub const LEN: usize = DISCRIMINATOR_LEN + 1 + 32 + 32 + 8;
+
+    // Whether the session can still be used at `slot`; it expires at `expiry_slot`
+    pub fn is_active(&self, slot: u64) -> bool {
+        slot < self.expiry_slot
+    }
+
+    // Deserialize a session token from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the session token into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for SessionToken {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// A game session between StartSession and EndSession (seeds: "game_session",
+// player, seed). The game server picks the seed and signs both ends, and the
+// slots the session ran bound the score it can credit.
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct GameSession {
+    pub is_initialized: bool,
+    pub player: Pubkey,
+    pub game_mode: GameMode,
+    pub seed: [u8; 32],
+    pub start_slot: u64,
+    pub start_ts: i64,
+}
+
+impl GameSession {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [150, 116, 20, 197, 205, 121, 220, 240];
+
+    // type tag, is_initialized, player, game mode, server seed, start slot, start timestamp
+    pub const LEN: usize = DISCRIMINATOR_LEN + 1 + 32 + 1 + 32 + 8 + 8;
+
+    // Whether a session still open at `slot` has outlived `max_slots`; zero
+    // means sessions never expire
+    pub fn is_expired(&self, slot: u64, max_slots: u64) -> bool {
+        max_slots > 0 && slot.saturating_sub(self.start_slot) > max_slots
+    }
+
+    // Whether `score` could have been earned between the start slot and `slot`
+    // at `max_per_slot`; zero means any score is plausible
+    pub fn is_plausible(&self, score: u64, slot: u64, max_per_slot: u64) -> bool {
+        let elapsed = slot.saturating_sub(self.start_slot);
+        max_per_slot == 0 || score <= elapsed.saturating_mul(max_per_slot)
+    }
+
+    // Whether `score` could have been earned between the start timestamp and
+    // `now` at `max_per_second`; zero means any score is plausible
+    pub fn is_plausible_rate(&self, score: u64, now: i64, max_per_second: u64) -> bool {
+        let elapsed = now.saturating_sub(self.start_ts).max(0) as u64;
+        max_per_second == 0 || score <= elapsed.saturating_mul(max_per_second)
+    }
+
+    // Deserialize a game session from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the game session into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for GameSession {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Players a co-op team can hold
+pub const TEAM_MIN_MEMBERS: usize = 2;
+pub const TEAM_MAX_MEMBERS: usize = 4;
+
+// Members of a team in the canonical order that seeds its address, or `None`
+// unless there are 2-4 distinct players
+pub fn sort_team_members(members: &[Pubkey]) -> Option<Vec<Pubkey>> {
+    if !(TEAM_MIN_MEMBERS..=TEAM_MAX_MEMBERS).contains(&members.len()) {
+        return None;
+    }
+    let mut sorted = members.to_vec();
+    sorted.sort();
+    sorted.dedup();
+    (sorted.len() == members.len()).then_some(sorted)
+}
+
+// Shared score account for a co-op team (seeds: "team", then each member in
+// sorted order). Any member can add to the score.
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct Team {
+    pub is_initialized: bool,
+    // Sorted members, padded with default pubkeys; Shank only reads literal array
+    // lengths, this is TEAM_MAX_MEMBERS
+    pub members: [Pubkey; 4],
+    pub member_count: u8,
+    pub score: u64,
+    pub best_score: u64,
+    pub last_update_ts: i64,
+}
+
+impl Team {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [140, 218, 177, 140, 193, 241, 199, 106];
+
+    // type tag, is_initialized, members, member count, score, best score, last update timestamp
+    pub const LEN: usize = DISCRIMINATOR_LEN + 1 + 32 * TEAM_MAX_MEMBERS + 1 + 8 + 8 + 8;
+
+    pub fn members(&self) -> &[Pubkey] {
+        &self.members[..(self.member_count as usize).min(TEAM_MAX_MEMBERS)]
+    }
+
+    pub fn is_member(&self, key: &Pubkey) -> bool {
+        self.members().contains(key)
+    }
+
+    // Deserialize a team from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the team into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for Team {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Bytes in a profile display name, which is zero-padded on the right
+pub const PROFILE_NAME_LEN: usize = 32;
+
+// Pad a display name to its stored form, or `None` if it is too long
+pub fn pad_profile_name(name: &str) -> Option<[u8; PROFILE_NAME_LEN]> {
+    let bytes = name.as_bytes();
+    if bytes.len() > PROFILE_NAME_LEN {
+        return None;
+    }
+    let mut padded = [0u8; PROFILE_NAME_LEN];
+    padded[..bytes.len()].copy_from_slice(bytes);
+    Some(padded)
+}
+
+// Lowercased copy of a stored display name, which seeds its NameRecord address so
+// names differing only in case collide. `None` unless the name is one or more ASCII
+// letters, digits, '_' or '-' followed only by padding.
+pub fn normalize_profile_name(name: &[u8; PROFILE_NAME_LEN]) -> Option<[u8; PROFILE_NAME_LEN]> {
+    let len = name
+        .iter()
+        .position(|&b| b == 0)
+        .unwrap_or(PROFILE_NAME_LEN);
+    let (text, padding) = name.split_at(len);
+    let valid = |b: &u8| b.is_ascii_alphanumeric() || *b == b'_' || *b == b'-';
+    if text.is_empty() || !text.iter().all(valid) || padding.iter().any(|&b| b != 0) {
+        return None;
+    }
+    Some(name.to_ascii_lowercase().try_into().unwrap())
+}
+
+// Region codes are ISO 3166-1 alpha-2 country codes, two uppercase ASCII letters.
+// A profile without a region stores NO_REGION.
+pub const NO_REGION: [u8; 2] = [0, 0];
+
+pub fn is_valid_region(region: &[u8; 2]) -> bool {
+    region.iter().all(u8::is_ascii_uppercase)
+}
+
+// Stored form of a region code typed in either case, or `None` if it is not one
+pub fn parse_region(code: &str) -> Option<[u8; 2]> {
+    let region: [u8; 2] = code.to_ascii_uppercase().as_bytes().try_into().ok()?;
+    is_valid_region(&region).then_some(region)
+}
+
+// XP curve: reaching level `n` takes LEVEL_XP * n^2 experience in total, up to
+// MAX_LEVEL. Every run earns RUN_XP plus one XP per SCORE_PER_XP points scored,
+// up to DAILY_XP_CAP a UTC day so levels take time as well as runs.
+pub const LEVEL_XP: u64 = 100;
+pub const MAX_LEVEL: u16 = 100;
+pub const RUN_XP: u64 = 10;
+pub const SCORE_PER_XP: u64 = 100;
+pub const DAILY_XP_CAP: u64 = 1_000;
+
+// Total experience needed to reach `level`
+pub fn xp_for_level(level: u16) -> u64 {
+    LEVEL_XP * u64::from(level).pow(2)
+}
+
+// Level reached with `xp` experience
+pub fn level_for_xp(xp: u64) -> u16 {
+    (1..=MAX_LEVEL)
+        .take_while(|&level| xp_for_level(level) <= xp)
+        .last()
+        .unwrap_or(0)
+}
+
+// Experience a run scoring `score` earns
+pub fn run_xp(score: u64) -> u64 {
+    RUN_XP.saturating_add(score / SCORE_PER_XP)
+}
+
+// Player's public profile (seeds: "profile", player)
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct PlayerProfile {
+    pub is_initialized: bool,
+    pub player: Pubkey,
+    // Shank only reads literal array lengths; this is PROFILE_NAME_LEN
+    pub name: [u8; 32],
+    pub avatar_mint: Pubkey,
+    pub created_ts: i64,
+    pub referral_count: u32,
+    pub referral_points: u64,
+    // Players following this one
+    pub follower_count: u32,
+    // Region code the player ranks under, or NO_REGION before they pick one
+    pub region: [u8; 2],
+    // Experience earned from runs, and the level it reached on the XP curve
+    pub xp: u64,
+    pub level: u16,
+    // Cosmetics the player unlocked, one bit per cosmetic id
+    pub unlocks: u64,
+    // UTC day of the latest XP grant and the XP granted on it, for DAILY_XP_CAP
+    pub xp_day: i64,
+    pub day_xp: u64,
+}
+
+impl PlayerProfile {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [82, 226, 99, 87, 164, 130, 181, 80];
+
+    // type tag, is_initialized, player, display name, avatar mint, creation timestamp,
+    // referred players who reached the referral threshold, points earned from them,
+    // follower count, region code, experience, level, cosmetic unlocks, XP day, XP
+    // earned that day
+    pub const LEN: usize =
+        DISCRIMINATOR_LEN + 1 + 32 + PROFILE_NAME_LEN + 32 + 8 + 4 + 8 + 4 + 2 + 8 + 2 + 8 + 8 + 8;
+
+    // Display name without its padding
+    pub fn display_name(&self) -> &str {
+        let len = self
+            .name
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(PROFILE_NAME_LEN);
+        std::str::from_utf8(&self.name[..len]).unwrap_or_default()
+    }
+
+    pub fn has_region(&self) -> bool {
+        self.region != NO_REGION
+    }
+
+    // Trim `xp` earned at `now` to what is left of today's DAILY_XP_CAP and count
+    // it against the cap. Returns the XP to add.
+    pub fn cap_daily_xp(&mut self, xp: u64, now: i64) -> u64 {
+        let today = now.div_euclid(SECONDS_PER_DAY);
+        if self.xp_day != today {
+            self.xp_day = today;
+            self.day_xp = 0;
+        }
+        let xp = xp.min(DAILY_XP_CAP.saturating_sub(self.day_xp));
+        self.day_xp += xp;
+        xp
+    }
+
+    // Add `xp` experience, returning whether it raised the player's level
+    pub fn add_xp(&mut self, xp: u64) -> bool {
+        self.xp = self.xp.saturating_add(xp);
+        let level = level_for_xp(self.xp);
+        let leveled_up = level > self.level;
+        self.level = level;
+        leveled_up
+    }
+
+    pub fn has_unlock(&self, cosmetic_id: u8) -> bool {
+        cosmetic_id < COSMETIC_COUNT && self.unlocks & (1 << cosmetic_id) != 0
+    }
+
+    // Set cosmetic `cosmetic_id`, returning false if it was already unlocked
+    pub fn unlock(&mut self, cosmetic_id: u8) -> bool {
+        let unlocked = !self.has_unlock(cosmetic_id);
+        self.unlocks |= 1 << cosmetic_id;
+        unlocked
+    }
+
+    // Deserialize a profile from raw account data, ignoring spare bytes left by Realloc
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(&data[..Self::LEN], &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the profile into the front of the account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for PlayerProfile {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Number of cosmetics the profile's unlock bitmap can hold
+pub const COSMETIC_COUNT: u8 = 64;
+
+// Cosmetic requirement meaning no achievement is needed
+pub const NO_ACHIEVEMENT: u8 = u8::MAX;
+
+// What a player needs to unlock cosmetic `cosmetic_id` (seeds: "cosmetic",
+// cosmetic id): at least `min_level`, and achievement `achievement` in
+// `game_mode` unless it is NO_ACHIEVEMENT
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct Cosmetic {
+    pub is_initialized: bool,
+    pub cosmetic_id: u8,
+    pub min_level: u16,
+    pub game_mode: GameMode,
+    pub achievement: u8,
+}
+
+impl Cosmetic {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [191, 145, 3, 226, 133, 186, 64, 166];
+
+    // type tag, is_initialized, cosmetic id, minimum level, game mode, achievement
+    pub const LEN: usize = DISCRIMINATOR_LEN + 1 + 1 + 2 + 1 + 1;
+
+    pub fn needs_achievement(&self) -> bool {
+        self.achievement != NO_ACHIEVEMENT
+    }
+
+    // Whether a player at `level` holding `achievements` in the cosmetic's game
+    // mode meets its requirements
+    pub fn is_earned(&self, level: u16, achievements: u64) -> bool {
+        level >= self.min_level
+            && (!self.needs_achievement() || achievements & (1 << self.achievement) != 0)
+    }
+
+    // Deserialize a cosmetic from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the cosmetic into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for Cosmetic {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Milestone a badge marks
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankType, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+#[borsh(use_discriminant = true)]
+#[repr(u8)]
+pub enum BadgeMilestone {
+    // Best score of at least the threshold
+    #[default]
+    BestScore = 0,
+    // Daily check-in streak of at least the threshold in days
+    Streak = 1,
+}
+
+// One badge: a Token-2022 mint with the non-transferable extension, of which
+// every player who reached `threshold` in `milestone` may hold one token
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankType, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct BadgeDefinition {
+    pub mint: Pubkey,
+    pub milestone: BadgeMilestone,
+    pub threshold: u64,
+}
+
+impl BadgeDefinition {
+    // mint, milestone, threshold
+    pub const LEN: usize = 32 + 1 + 8;
+
+    // Whether the player owning `score` has reached the badge's milestone
+    pub fn is_reached(&self, score: &ScoreAccount) -> bool {
+        match self.milestone {
+            BadgeMilestone::BestScore => score.best_score >= self.threshold,
+            BadgeMilestone::Streak => u64::from(score.streak) >= self.threshold,
+        }
+    }
+}
+
+// Badges the admin added, in the order of their ids (seeds: "badge_catalog")
+#[derive(BorshSerialize, BorshDeserialize, ShankAccount, Clone, Debug, Default, PartialEq, Eq)]
+pub struct BadgeCatalog {
+    pub is_initialized: bool,
+    pub badges: Vec<BadgeDefinition>,
+}
+
+impl BadgeCatalog {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [52, 79, 66, 158, 116, 27, 189, 239];
+
+    // Type tag, is_initialized, 4 bytes vector length
+    pub const HEADER_LEN: usize = DISCRIMINATOR_LEN + 1 + 4;
+
+    // Badge ids are one byte
+    pub const CAPACITY: usize = u8::MAX as usize + 1;
+
+    // Account size holding `count` badges
+    pub const fn space(count: usize) -> usize {
+        Self::HEADER_LEN + count * BadgeDefinition::LEN
+    }
+
+    pub fn get(&self, badge_id: u8) -> Option<&BadgeDefinition> {
+        self.badges.get(usize::from(badge_id))
+    }
+
+    // Deserialize a badge catalog from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() < Self::HEADER_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the badge catalog into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::space(self.badges.len()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for BadgeCatalog {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Score distribution buckets in GlobalStats. Bucket 0 holds best scores of
+// zero and bucket `b` those in [2^(b-1), 2^b), with the last one open-ended.
+pub const HISTOGRAM_BUCKETS: usize = 32;
+
+// The histogram bucket a best score falls into
+pub fn score_bucket(score: u64) -> usize {
+    (u64::BITS - score.leading_zeros()).min(HISTOGRAM_BUCKETS as u32 - 1) as usize
+}
+
+// Program-wide running totals (seeds: "global_stats"), so dashboards read one
+// account instead of scanning every score account. Counting starts when the
+// admin creates it, and only instructions that are passed it add to it.
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct GlobalStats {
+    pub is_initialized: bool,
+    pub bump: u8,
+    // Score accounts created; a player counts once for each game mode they play
+    pub total_players: u64,
+    // Runs finished through SubmitRun or a game session
+    pub total_runs: u64,
+    // Score credited by updates and runs
+    pub total_score: u64,
+    // House fees paid into the fee vault, in lamports
+    pub total_fees: u64,
+    // Score accounts per best score bucket, see `score_bucket`. Moves that miss
+    // the account leave it approximate, so counts saturate at zero. Shank only
+    // reads literal array lengths; this is HISTOGRAM_BUCKETS
+    pub histogram: [u64; 32],
+}
+
+impl GlobalStats {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [119, 53, 78, 3, 254, 129, 78, 28];
+
+    // type tag, is_initialized, address bump, players, runs, score, fees, histogram
+    pub const LEN: usize = DISCRIMINATOR_LEN + 1 + 1 + 8 + 8 + 8 + 8 + 8 * HISTOGRAM_BUCKETS;
+
+    // A new score account starts with a best score of zero
+    pub fn record_player(&mut self) {
+        self.total_players = self.total_players.saturating_add(1);
+        self.histogram[0] = self.histogram[0].saturating_add(1);
+    }
+
+    // Move a score account whose best score went from `previous_best` to `best`
+    // into its new bucket
+    pub fn record_best(&mut self, previous_best: u64, best: u64) {
+        let (from, to) = (score_bucket(previous_best), score_bucket(best));
+        if from != to {
+            self.histogram[from] = self.histogram[from].saturating_sub(1);
+            self.histogram[to] = self.histogram[to].saturating_add(1);
+        }
+    }
+
+    // Share of counted score accounts whose best score is in the same bucket as
+    // `best_score` or above, in basis points, or `None` before any are counted
+    pub fn top_bps(&self, best_score: u64) -> Option<u64> {
+        let total: u64 = self.histogram.iter().sum();
+        if total == 0 {
+            return None;
+        }
+        let at_or_above: u64 = self.histogram[score_bucket(best_score)..].iter().sum();
+        Some(at_or_above.min(total) * MAX_BPS / total)
+    }
+
+    pub fn record_run(&mut self, score: u64) {
+        self.total_runs = self.total_runs.saturating_add(1);
+        self.record_score(score);
+    }
+
+    pub fn record_score(&mut self, score: u64) {
+        self.total_score = self.total_score.saturating_add(score);
+    }
+
+    pub fn record_fee(&mut self, fee: u64) {
+        self.total_fees = self.total_fees.saturating_add(fee);
+    }
+
+    // Deserialize the stats from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the stats into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for GlobalStats {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// One player following another (seeds: "follow", follower, followed). The
+// follower comes first after the flag so a client can list everyone a player
+// follows with a memcmp filter at FOLLOWER_OFFSET.
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct Follow {
+    pub is_initialized: bool,
+    pub follower: Pubkey,
+    pub followed: Pubkey,
+    pub created_ts: i64,
+}
+
+impl Follow {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [222, 247, 253, 60, 70, 4, 164, 51];
+
+    // type tag, is_initialized, follower, followed, creation timestamp
+    pub const LEN: usize = DISCRIMINATOR_LEN + 1 + 32 + 32 + 8;
+
+    // Where the follower pubkey starts in the account data
+    pub const FOLLOWER_OFFSET: usize = DISCRIMINATOR_LEN + 1;
+
+    // Deserialize a follow edge from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the follow edge into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for Follow {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Claim on a display name by the player whose profile uses it
+// (seeds: "name", normalized name)
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct NameRecord {
+    pub is_initialized: bool,
+    pub owner: Pubkey,
+}
+
+impl NameRecord {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [254, 22, 17, 161, 229, 49, 238, 105];
+
+    // type tag, is_initialized, owner
+    pub const LEN: usize = DISCRIMINATOR_LEN + 1 + 32;
+
+    // Deserialize a name record from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the name record into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for NameRecord {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Reward airdrop published as a Merkle root over (index, player, amount) leaves
+// (seeds: "airdrop", airdrop_id). Claims are tracked in its AirdropClaims bitmap.
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct Airdrop {
+    pub is_initialized: bool,
+    pub airdrop_id: u64,
+    pub merkle_root: [u8; 32],
+    pub max_claims: u32,
+    pub claimed_count: u32,
+    pub total_claimed: u64,
+}
+
+impl Airdrop {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [31, 112, 159, 158, 124, 237, 9, 241];
+
+    // type tag, is_initialized, airdrop id, merkle root, max claims, claimed count, total claimed
+    pub const LEN: usize = DISCRIMINATOR_LEN + 1 + 8 + 32 + 4 + 4 + 8;
+
+    // Deserialize an airdrop from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the airdrop into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for Airdrop {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// One bit per claim index of an airdrop, set once that index is paid out
+// (seeds: "airdrop_claims", airdrop_id)
+#[derive(BorshSerialize, BorshDeserialize, ShankAccount, Clone, Debug, Default, PartialEq, Eq)]
+pub struct AirdropClaims {
+    pub is_initialized: bool,
+    pub airdrop_id: u64,
+    pub bits: Vec<u8>,
+}
+
+impl AirdropClaims {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [255, 174, 194, 46, 254, 142, 178, 189];
+
+    // Type tag, is_initialized, airdrop id, 4 bytes vector length
+    pub const HEADER_LEN: usize = DISCRIMINATOR_LEN + 1 + 8 + 4;
+
+    // Bitmap bytes covering `max_claims` indices
+    pub const fn bitmap_len(max_claims: u32) -> usize {
+        (max_claims as usize).div_ceil(8)
+    }
+
+    // Account size that tracks `max_claims` indices
+    pub const fn space(max_claims: u32) -> usize {
+        Self::HEADER_LEN + Self::bitmap_len(max_claims)
+    }
+
+    pub fn is_claimed(&self, index: u32) -> bool {
+        self.bits
+            .get(index as usize / 8)
+            .is_some_and(|byte| byte & (1 << (index % 8)) != 0)
+    }
+
+    // Mark `index` claimed. Returns false if it already was or is out of range.
+    pub fn set_claimed(&mut self, index: u32) -> bool {
+        match self.bits.get_mut(index as usize / 8) {
+            Some(byte) if *byte & (1 << (index % 8)) == 0 => {
+                *byte |= 1 << (index % 8);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Deserialize a claims bitmap from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() < Self::HEADER_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the claims bitmap into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::HEADER_LEN + self.bits.len() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for AirdropClaims {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Leaf of the compressed score tree. Casual players append these instead of
+// paying rent for a ScoreAccount; indexers rebuild the leaves from the copy the
+// program writes to the noop program.
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankType, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct CompressedScore {
+    pub player: Pubkey,
+    pub game_mode: GameMode,
+    pub score: u64,
+    pub timestamp: i64,
+    // Position in the tree, which also keeps identical scores from sharing a leaf
+    pub leaf_index: u64,
+}
+
+impl CompressedScore {
+    // Hash stored in the tree for this leaf
+    pub fn leaf_hash(&self) -> [u8; 32] {
+        keccak::hashv(&[&borsh::to_vec(self).expect("leaf serialization cannot fail")]).to_bytes()
+    }
+}
+
+// Tracks a concurrent Merkle tree of compressed scores and signs for it as the
+// tree authority (seeds: "score_tree", merkle_tree)
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct ScoreTree {
+    pub is_initialized: bool,
+    pub merkle_tree: Pubkey,
+    // Leaves appended so far, which is also the index of the next one
+    pub leaf_count: u64,
+}
+
+impl ScoreTree {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [72, 161, 248, 168, 74, 13, 160, 253];
+
+    // type tag, is_initialized, merkle tree, leaf count
+    pub const LEN: usize = DISCRIMINATOR_LEN + 1 + 32 + 8;
+
+    // Deserialize a score tree from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the score tree into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for ScoreTree {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Players barred from updating their score or submitting to the leaderboard,
+// kept sorted so lookups are a binary search. The account grows and shrinks by
+// one key per ban. (seeds: "ban_list")
+#[derive(BorshSerialize, BorshDeserialize, ShankAccount, Clone, Debug, Default, PartialEq, Eq)]
+pub struct BanList {
+    pub is_initialized: bool,
+    pub players: Vec<Pubkey>,
+}
+
+impl BanList {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [11, 203, 65, 167, 86, 15, 195, 85];
+
+    // Type tag, is_initialized, 4 bytes vector length
+    pub const HEADER_LEN: usize = DISCRIMINATOR_LEN + 1 + 4;
+
+    // Account size holding `count` banned players
+    pub const fn space(count: usize) -> usize {
+        Self::HEADER_LEN + count * 32
+    }
+
+    pub fn is_banned(&self, player: &Pubkey) -> bool {
+        self.players.binary_search(player).is_ok()
+    }
+
+    // Add `player` in sorted position. Returns false if already banned.
+    pub fn ban(&mut self, player: Pubkey) -> bool {
+        match self.players.binary_search(&player) {
+            Ok(_) => false,
+            Err(position) => {
+                self.players.insert(position, player);
+                true
+            }
+        }
+    }
+
+    // Remove `player`. Returns false if they were not banned.
+    pub fn unban(&mut self, player: &Pubkey) -> bool {
+        match self.players.binary_search(player) {
+            Ok(position) => {
+                self.players.remove(position);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    // Deserialize a ban list from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() < Self::HEADER_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the ban list into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::space(self.players.len()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for BanList {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Lamports a reporter puts up with each report, refunded if the admin upholds it
+pub const REPORT_BOND_LAMPORTS: u64 = 10_000_000;
+
+// A pending cheating report against one player's score in one mode. The bond is
+// held as lamports in the account on top of its rent-exempt minimum.
+// (seeds: "report", player, reporter, game_mode)
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct Report {
+    pub is_initialized: bool,
+    pub reporter: Pubkey,
+    pub player: Pubkey,
+    pub game_mode: GameMode,
+    pub bond: u64,
+    pub created_ts: i64,
+}
+
+impl Report {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [232, 246, 229, 227, 242, 105, 190, 2];
+
+    // type tag, is_initialized, reporter, player, game mode, bond, creation time
+    pub const LEN: usize = DISCRIMINATOR_LEN + 1 + 32 + 32 + 1 + 8 + 8;
+
+    // Deserialize a report from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the report into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for Report {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Most keys an admin set can hold
+pub const MAX_ADMIN_SET_MEMBERS: usize = 10;
+
+// m-of-n admin authority. Once created, its address is the config admin, so
+// admin instructions only run when a proposal approved by `threshold` members
+// executes them. (seeds: "admin_set")
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct AdminSet {
+    pub is_initialized: bool,
+    pub threshold: u8,
+    pub member_count: u8,
+    // Shank only reads literal array lengths; this is MAX_ADMIN_SET_MEMBERS
+    pub members: [Pubkey; 10],
+    // Bumped whenever the members change, so older approvals no longer count
+    pub members_version: u32,
+    // Proposals created so far, which is also the id of the next one
+    pub proposal_count: u64,
+}
+
+impl AdminSet {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [149, 222, 12, 244, 38, 168, 60, 207];
+
+    // type tag, is_initialized, threshold, member count, members, members version,
+    // proposal count
+    pub const LEN: usize = DISCRIMINATOR_LEN + 1 + 1 + 1 + 32 * MAX_ADMIN_SET_MEMBERS + 4 + 8;
+
+    pub fn members(&self) -> &[Pubkey] {
+        &self.members[..self.member_count as usize]
+    }
+
+    // Position of `key` among the members, which is its approval bit
+    pub fn member_index(&self, key: &Pubkey) -> Option<usize> {
+        self.members().iter().position(|member| member == key)
+    }
+
+    // Deserialize an admin set from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the admin set into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for AdminSet {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// One account of a proposed instruction
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankType, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct ProposalAccount {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+// A score program instruction waiting for enough admin set approvals, executed
+// with the admin set signing as admin. (seeds: "proposal", proposal_id)
+#[derive(BorshSerialize, BorshDeserialize, ShankAccount, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Proposal {
+    pub is_initialized: bool,
+    pub proposal_id: u64,
+    // Gets the rent back once the proposal executes
+    pub proposer: Pubkey,
+    // The admin set's members version when proposed
+    pub members_version: u32,
+    // Bit i is set once member i approved
+    pub approvals: u16,
+    pub executed: bool,
+    pub accounts: Vec<ProposalAccount>,
+    pub data: Vec<u8>,
+}
+
+impl Proposal {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [26, 94, 189, 187, 116, 136, 53, 33];
+
+    // Type tag, is_initialized, proposal id, proposer, members version,
+    // approvals, executed, 4 bytes length for each vector
+    pub const HEADER_LEN: usize = DISCRIMINATOR_LEN + 1 + 8 + 32 + 4 + 2 + 1 + 4 + 4;
+
+    // Account size holding an instruction with `account_count` accounts and
+    // `data_len` bytes of data
+    pub const fn space(account_count: usize, data_len: usize) -> usize {
+        Self::HEADER_LEN + account_count * (32 + 1 + 1) + data_len
+    }
+
+    pub fn approval_count(&self) -> u32 {
+        self.approvals.count_ones()
+    }
+
+    // Deserialize a proposal from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() < Self::HEADER_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the proposal into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::space(self.accounts.len(), self.data.len()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for Proposal {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Lamports a player pays for each loot drop, escrowed until the roll lands and
+// then moved into the prize pool
+pub const LOOT_PRICE_LAMPORTS: u64 = 5_000_000;
+
+// Number of loot tiers an inventory counts
+pub const LOOT_TIERS: usize = 4;
+
+// Chance of each loot tier in basis points, from most to least common
+pub const LOOT_TIER_WEIGHTS_BPS: [u64; LOOT_TIERS] = [6_000, 3_000, 900, 100];
+
+// Loot tiers, encoded as a single byte
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankType, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+#[borsh(use_discriminant = true)]
+#[repr(u8)]
+pub enum LootTier {
+    #[default]
+    Common = 0,
+    Rare = 1,
+    Epic = 2,
+    Legendary = 3,
+}
+
+impl LootTier {
+    // Pick the tier a revealed random value lands in, weighted by LOOT_TIER_WEIGHTS_BPS
+    pub fn roll(value: &[u8; 32]) -> Self {
+        let mut draw = u64::from_le_bytes(value[..8].try_into().unwrap()) % MAX_BPS;
+        for (tier, weight) in [Self::Common, Self::Rare, Self::Epic]
+            .into_iter()
+            .zip(LOOT_TIER_WEIGHTS_BPS)
+        {
+            if draw < weight {
+                return tier;
+            }
+            draw -= weight;
+        }
+        Self::Legendary
+    }
+}
+
+// A player's pending loot drop, waiting on a Switchboard randomness account
+// committed in `seed_slot` to be revealed. The price is held as lamports in the
+// account on top of its rent-exempt minimum. (seeds: "loot_request", player)
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct LootRequest {
+    pub is_initialized: bool,
+    pub player: Pubkey,
+    pub randomness: Pubkey,
+    pub seed_slot: u64,
+    pub escrow: u64,
+}
+
+impl LootRequest {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [224, 193, 179, 100, 23, 24, 159, 205];
+
+    // type tag, is_initialized, player, randomness account, commit slot, escrowed price
+    pub const LEN: usize = DISCRIMINATOR_LEN + 1 + 32 + 32 + 8 + 8;
+
+    // Deserialize a loot request from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the loot request into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for LootRequest {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// What consuming an item does, encoded as a single byte
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankType, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+#[borsh(use_discriminant = true)]
+#[repr(u8)]
+pub enum ItemEffect {
+    // Nothing on chain, e.g. a cosmetic the game client reads from the inventory
+    #[default]
+    None = 0,
+    // Multiply the next credited score update by the item's effect_bps
+    ScoreMultiplier = 1,
+    // Let the player resume their current run once
+    Continue = 2,
+}
+
+// One catalog entry
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankType, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct ItemDefinition {
+    pub item_id: u32,
+    pub effect: ItemEffect,
+    // Strength of the effect in basis points; the multiplier for ScoreMultiplier
+    pub effect_bps: u16,
+    // Shop prices in lamports and in the config's shop mint; zero when the item
+    // is not sold for that currency
+    pub price_lamports: u64,
+    pub price_tokens: u64,
+}
+
+impl ItemDefinition {
+    // item id, effect, effect strength, SOL price, token price
+    pub const LEN: usize = 4 + 1 + 2 + 8 + 8;
+
+    // Unit price in `currency`, or None if the item is not sold for it
+    pub fn price(&self, currency: ShopCurrency) -> Option<u64> {
+        let price = match currency {
+            ShopCurrency::Sol => self.price_lamports,
+            ShopCurrency::Token => self.price_tokens,
+        };
+        (price > 0).then_some(price)
+    }
+}
+
+// What a shop purchase is paid in, encoded as a single byte
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankType, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+#[borsh(use_discriminant = true)]
+#[repr(u8)]
+pub enum ShopCurrency {
+    #[default]
+    Sol = 0,
+    // The config's shop mint
+    Token = 1,
+}
+
+// Items the game knows about, sorted by id (seeds: "catalog")
+#[derive(BorshSerialize, BorshDeserialize, ShankAccount, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Catalog {
+    pub is_initialized: bool,
+    pub items: Vec<ItemDefinition>,
+}
+
+impl Catalog {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [175, 32, 23, 106, 77, 71, 227, 200];
+
+    // Type tag, is_initialized, 4 bytes vector length
+    pub const HEADER_LEN: usize = DISCRIMINATOR_LEN + 1 + 4;
+
+    // Account size holding `count` item definitions
+    pub const fn space(count: usize) -> usize {
+        Self::HEADER_LEN + count * ItemDefinition::LEN
+    }
+
+    pub fn find(&self, item_id: u32) -> Option<&ItemDefinition> {
+        self.items
+            .binary_search_by_key(&item_id, |item| item.item_id)
+            .ok()
+            .map(|position| &self.items[position])
+    }
+
+    // Add `definition` in sorted position or replace the one with its id.
+    // Returns true if it was added.
+    pub fn upsert(&mut self, definition: ItemDefinition) -> bool {
+        match self
+            .items
+            .binary_search_by_key(&definition.item_id, |item| item.item_id)
+        {
+            Ok(position) => {
+                self.items[position] = definition;
+                false
+            }
+            Err(position) => {
+                self.items.insert(position, definition);
+                true
+            }
+        }
+    }
+
+    // Deserialize a catalog from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() < Self::HEADER_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the catalog into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::space(self.items.len()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for Catalog {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// How many of one catalog item a player holds
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankType, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct ItemStack {
+    pub item_id: u32,
+    pub quantity: u32,
+}
+
+impl ItemStack {
+    // item id, quantity
+    pub const LEN: usize = 4 + 4;
+}
+
+// A player's loot drops, counted per tier, and their catalog items sorted by
+// id. Stacks are removed once used up. (seeds: "inventory", player)
+#[derive(BorshSerialize, BorshDeserialize, ShankAccount, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Inventory {
+    pub is_initialized: bool,
+    pub player: Pubkey,
+    // Shank only reads literal array lengths; this is LOOT_TIERS
+    pub loot: [u32; 4],
+    pub items: Vec<ItemStack>,
+}
+
+impl Inventory {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [97, 218, 167, 233, 216, 170, 49, 27];
+
+    // Type tag, is_initialized, player, loot count per tier, 4 bytes vector length
+    pub const HEADER_LEN: usize = DISCRIMINATOR_LEN + 1 + 32 + 4 * LOOT_TIERS + 4;
+
+    // Account size holding `count` item stacks
+    pub const fn space(count: usize) -> usize {
+        Self::HEADER_LEN + count * ItemStack::LEN
+    }
+
+    // Add one loot drop of `tier`
+    pub fn credit_loot(&mut self, tier: LootTier) {
+        let count = &mut self.loot[tier as usize];
+        *count = count.saturating_add(1);
+    }
+
+    // Number of `item_id` held
+    pub fn quantity(&self, item_id: u32) -> u32 {
+        self.items
+            .binary_search_by_key(&item_id, |stack| stack.item_id)
+            .map_or(0, |position| self.items[position].quantity)
+    }
+
+    // Add `quantity` of `item_id`. Returns true if that started a new stack.
+    pub fn grant(&mut self, item_id: u32, quantity: u32) -> bool {
+        match self
+            .items
+            .binary_search_by_key(&item_id, |stack| stack.item_id)
+        {
+            Ok(position) => {
+                let stack = &mut self.items[position];
+                stack.quantity = stack.quantity.saturating_add(quantity);
+                false
+            }
+            Err(position) => {
+                self.items.insert(position, ItemStack { item_id, quantity });
+                true
+            }
+        }
+    }
+
+    // Take one `item_id`, returning false if none is held
+    pub fn consume(&mut self, item_id: u32) -> bool {
+        let Ok(position) = self
+            .items
+            .binary_search_by_key(&item_id, |stack| stack.item_id)
+        else {
+            return false;
+        };
+        let stack = &mut self.items[position];
+        stack.quantity -= 1;
+        if stack.quantity == 0 {
+            self.items.remove(position);
+        }
+        true
+    }
+
+    // Deserialize an inventory from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() < Self::HEADER_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the inventory into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::space(self.items.len()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for Inventory {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// What a quest counts towards its target, encoded as a single byte
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankType, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+#[borsh(use_discriminant = true)]
+#[repr(u8)]
+pub enum QuestMetric {
+    // Score credited by UpdateScore, after any boosts
+    #[default]
+    ScoreEarned = 0,
+    // Achievements unlocked
+    AchievementsUnlocked = 1,
+}
+
+// A quest the admin set up (seeds: "quest", quest id). Players who accepted it
+// earn `reward` tokens from the reward vault once their progress reaches `target`.
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct Quest {
+    pub is_initialized: bool,
+    pub quest_id: u64,
+    pub metric: QuestMetric,
+    pub target: u64,
+    pub reward: u64,
+}
+
+impl Quest {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [68, 78, 51, 23, 204, 27, 76, 132];
+
+    // type tag, is_initialized, quest id, metric, target, reward
+    pub const LEN: usize = DISCRIMINATOR_LEN + 1 + 8 + 1 + 8 + 8;
+
+    // Deserialize a quest from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize the quest into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for Quest {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// A player's progress towards a quest they accepted (seeds: "quest_progress",
+// quest id, player). The quest's metric is copied in so score and achievement
+// updates can advance it without the quest account.
+#[derive(
+    BorshSerialize, BorshDeserialize, ShankAccount, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct QuestProgress {
+    pub is_initialized: bool,
+    pub player: Pubkey,
+    pub quest_id: u64,
+    pub metric: QuestMetric,
+    pub progress: u64,
+    pub claimed: bool,
+}
+
+impl QuestProgress {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [77, 66, 99, 169, 234, 177, 58, 162];
+
+    // type tag, is_initialized, player, quest id, metric, progress, claimed flag
+    pub const LEN: usize = DISCRIMINATOR_LEN + 1 + 32 + 8 + 1 + 8 + 1;
+
+    // Count `amount` of `metric` towards the quest if it is what the quest tracks.
+    // Returns whether it counted.
+    pub fn record(&mut self, metric: QuestMetric, amount: u64) -> bool {
+        if self.metric != metric || self.claimed {
+            return false;
+        }
+        self.progress = self.progress.saturating_add(amount);
+        true
+    }
+
+    // Deserialize quest progress from raw account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = strip_discriminator(data, &Self::DISCRIMINATOR)?;
+        Self::try_from_slice(body).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Serialize quest progress into raw account data
+    pub fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        if dst.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.serialize(&mut write_discriminator(dst, &Self::DISCRIMINATOR))
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl IsInitialized for QuestProgress {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::hash::hash;
+
+    fn scores(board: &Leaderboard) -> Vec<u64> {
+        board.entries().iter().map(|e| e.score()).collect()
+    }
+
+    #[test]
+    fn roll_season_zeroes_stale_scores() {
+        let mut state = ScoreAccount {
+            score: 40,
+            best_score: 40,
+            season_id: 1,
+            ..ScoreAccount::default()
+        };
+        state.roll_season(1);
+        assert_eq!(state.score, 40);
+        state.roll_season(2);
+        assert_eq!((state.score, state.best_score, state.season_id), (0, 40, 2));
+    }
+
+    #[test]
+    fn decay_compounds_per_full_window() {
+        let mut state = ScoreAccount {
+            score: 10_000,
+            last_update_ts: 1_000,
+            ..ScoreAccount::default()
+        };
+        assert_eq!(state.decay(1_099, 100, 1_000), 10_000);
+        assert_eq!(state.score, 10_000);
+        state.decay(1_250, 100, 1_000);
+        assert_eq!(state.score, 8_100);
+        state.decay(i64::MAX, 1, 1);
+        assert_eq!(state.score, 0);
+
+        // Accounts that were never updated have nothing to measure from
+        let mut state = ScoreAccount {
+            score: 500,
+            ..ScoreAccount::default()
+        };
+        state.decay(1_000_000, 100, 5_000);
+        assert_eq!(state.score, 500);
+    }
+
+    #[test]
+    fn throttle_limits_updates_per_window() {
+        let mut state = ScoreAccount::default();
+        assert!(state.throttle(10, 1, 1));
+        assert!(!state.throttle(10, 1, 1));
+        assert!(state.throttle(11, 1, 1));
+
+        // Two per 100-slot window, counted afresh when the window rolls over
+        assert!(state.throttle(150, 100, 2));
+        assert!(state.throttle(199, 100, 2));
+        assert!(!state.throttle(199, 100, 2));
+        assert_eq!((state.last_update_slot, state.window_updates), (199, 2));
+        assert!(state.throttle(200, 100, 2));
+        assert_eq!(state.window_updates, 1);
+
+        // A zero limit never throttles
+        assert!(state.throttle(200, 100, 0));
+    }
+
+    #[test]
+    fn continues_are_capped_per_run() {
+        let mut state = ScoreAccount::default();
+        assert!(state.add_continue(2));
+        assert!(state.add_continue(2));
+        assert!(!state.add_continue(2));
+        assert_eq!((state.run_continues, state.continue_pending), (2, true));
+        state.end_run();
+        assert_eq!((state.run_continues, state.continue_pending), (0, false));
+        assert!((0..300).all(|_| state.add_continue(0)));
+    }
+
+    #[test]
+    fn treasury_withdrawals_are_capped_per_epoch() {
+        let mut config = Config {
+            treasury_withdraw_cap_lamports: 100,
+            ..Config::default()
+        };
+        assert!(config.record_treasury_withdrawal(5, 60));
+        assert!(!config.record_treasury_withdrawal(5, 41));
+        assert!(config.record_treasury_withdrawal(5, 40));
+        assert_eq!(config.treasury_withdrawn_lamports, 100);
+
+        // A new epoch starts from nothing
+        assert!(config.record_treasury_withdrawal(6, 100));
+        assert_eq!(config.treasury_withdraw_epoch, 6);
+
+        config.treasury_withdraw_cap_lamports = 0;
+        assert!(config.record_treasury_withdrawal(6, u64::MAX - 100));
+        assert!(!config.record_treasury_withdrawal(6, 1));
+    }
+
+    #[test]
+    fn loot_rolls_follow_tier_weights() {
+        let roll = |draw: u64| {
+            let mut value = [0xffu8; 32];
+            value[..8].copy_from_slice(&draw.to_le_bytes());
+            LootTier::roll(&value)
+        };
+        assert_eq!(roll(0), LootTier::Common);
+        assert_eq!(roll(5_999), LootTier::Common);
+        assert_eq!(roll(6_000), LootTier::Rare);
+        assert_eq!(roll(8_999), LootTier::Rare);
+        assert_eq!(roll(9_000), LootTier::Epic);
+        assert_eq!(roll(9_899), LootTier::Epic);
+        assert_eq!(roll(9_900), LootTier::Legendary);
+        assert_eq!(roll(29_999), LootTier::Legendary);
+
+        let mut inventory = Inventory::default();
+        inventory.credit_loot(LootTier::Epic);
+        inventory.credit_loot(LootTier::Epic);
+        assert_eq!(inventory.loot, [0, 0, 2, 0]);
+    }
+
+    #[test]
+    fn inventory_stacks_stay_sorted_and_drop_when_used_up() {
+        let mut inventory = Inventory::default();
+        assert!(inventory.grant(7, 1));
+        assert!(inventory.grant(3, 2));
+        assert!(!inventory.grant(7, 1));
+        assert_eq!(
+            inventory.items,
+            vec![
+                ItemStack {
+                    item_id: 3,
+                    quantity: 2
+                },
+                ItemStack {
+                    item_id: 7,
+                    quantity: 2
+                },
+            ]
+        );
+        assert!(inventory.consume(3));
+        assert!(inventory.consume(3));
+        assert!(!inventory.consume(3));
+        assert_eq!((inventory.quantity(3), inventory.quantity(7)), (0, 2));
+        assert_eq!(inventory.items.len(), 1);
+
+        let mut data = vec![0u8; Inventory::space(1)];
+        inventory.pack(&mut data).unwrap();
+        assert_eq!(Inventory::unpack(&data).unwrap(), inventory);
+
+        let mut catalog = Catalog::default();
+        let boost = ItemDefinition {
+            item_id: 4,
+            effect: ItemEffect::ScoreMultiplier,
+            effect_bps: 15_000,
+            price_lamports: 1_000,
+            price_tokens: 0,
+        };
+        assert!(catalog.upsert(boost));
+        assert!(!catalog.upsert(ItemDefinition {
+            effect_bps: 20_000,
+            ..boost
+        }));
+        assert_eq!(catalog.find(4).unwrap().effect_bps, 20_000);
+        assert_eq!(catalog.find(5), None);
+        assert_eq!(boost.price(ShopCurrency::Sol), Some(1_000));
+        assert_eq!(boost.price(ShopCurrency::Token), None);
+    }
+
+    #[test]
+    fn check_in_tracks_consecutive_days() {
+        let day = |n: i64| n * SECONDS_PER_DAY + 3_600;
+        let mut state = ScoreAccount::default();
+        assert!(state.check_in(day(100)));
+        assert!(!state.check_in(day(100) + 7_200));
+        assert!(state.check_in(day(101)));
+        assert_eq!((state.streak, state.last_play_day), (2, 101));
+        assert_eq!(state.streak_multiplier_bps(day(101)), 11_000);
+        assert_eq!(state.streak_multiplier_bps(day(102)), MAX_BPS);
+
+        // Missing a day starts over
+        assert!(state.check_in(day(103)));
+        assert_eq!(state.streak, 1);
+        assert_eq!(state.streak_multiplier_bps(day(103)), MAX_BPS);
+
+        state.streak = 40;
+        assert_eq!(state.streak_multiplier_bps(day(103)), 2 * MAX_BPS);
+    }
+
+    #[test]
+    fn unlock_achievement_sets_one_bit() {
+        let mut state = ScoreAccount::default();
+        assert!(state.unlock_achievement(0));
+        assert!(state.unlock_achievement(63));
+        assert!(!state.unlock_achievement(63));
+        assert_eq!(state.achievements, 1 | 1 << 63);
+        assert!(state.has_achievement(0) && !state.has_achievement(1));
+    }
+
+    #[test]
+    fn trophy_needs_margin_over_last_trophy() {
+        let mut state = ScoreAccount {
+            best_score: 5_000,
+            ..ScoreAccount::default()
+        };
+        assert!(state.trophy_earned(5_000));
+        assert!(!state.trophy_earned(5_001));
+        assert!(!state.trophy_earned(0));
+
+        state.trophy_best = 5_000;
+        assert!(!state.trophy_earned(1_000));
+        state.best_score = 6_000;
+        assert!(state.trophy_earned(1_000));
+    }
+
+    #[test]
+    fn referral_is_earned_once_past_threshold() {
+        let mut state = ScoreAccount {
+            best_score: 999,
+            referrer: Pubkey::new_unique(),
+            ..ScoreAccount::default()
+        };
+        assert!(!state.referral_earned(1_000));
+        state.best_score = 1_000;
+        assert!(state.referral_earned(1_000));
+        assert!(!state.referral_earned(0));
+        state.referral_credited = true;
+        assert!(!state.referral_earned(1_000));
+
+        let unreferred = ScoreAccount {
+            best_score: 5_000,
+            ..ScoreAccount::default()
+        };
+        assert!(!unreferred.referral_earned(1_000));
+    }
+
+    #[test]
+    fn claimable_reward_counts_unclaimed_tiers() {
+        let mut state = ScoreAccount {
+            best_score: 30_000,
+            ..ScoreAccount::default()
+        };
+        assert_eq!(state.claimable_reward(10), 30);
+        state.rewards_claimed = 30;
+        assert_eq!(state.claimable_reward(10), 0);
+        state.best_score = 100_000;
+        assert_eq!(state.claimable_reward(10), 10);
+        assert_eq!(state.claimable_reward(0), 0);
+    }
+
+    #[test]
+    fn vesting_unlocks_linearly_after_the_cliff() {
+        let vesting = Vesting {
+            total: 1_000,
+            start_ts: 100,
+            cliff_ts: 200,
+            end_ts: 500,
+            ..Vesting::default()
+        };
+        assert_eq!(vesting.unlocked(50), 0);
+        assert_eq!(vesting.unlocked(199), 0);
+        // The cliff releases everything accrued since the start at once
+        assert_eq!(vesting.unlocked(200), 250);
+        assert_eq!(vesting.unlocked(333), 582);
+        assert_eq!(vesting.unlocked(500), 1_000);
+        assert_eq!(vesting.unlocked(i64::MAX), 1_000);
+
+        let vesting = Vesting {
+            claimed: 250,
+            ..vesting
+        };
+        assert_eq!(vesting.claimable(199), 0);
+        assert_eq!(vesting.claimable(300), 250);
+        assert_eq!(vesting.claimable(600), 750);
+    }
+
+    #[test]
+    fn stake_multiplier_is_the_best_tier_reached() {
+        let config = Config {
+            stake_tier_amounts: [100, 1_000, 0],
+            stake_tier_bps: [11_000, 12_500, 20_000],
+            ..Config::default()
+        };
+        assert_eq!(config.stake_multiplier_bps(0), MAX_BPS);
+        assert_eq!(config.stake_multiplier_bps(99), MAX_BPS);
+        assert_eq!(config.stake_multiplier_bps(100), 11_000);
+        assert_eq!(config.stake_multiplier_bps(999), 11_000);
+        // The unused third tier never applies however much is staked
+        assert_eq!(config.stake_multiplier_bps(u64::MAX), 12_500);
+        assert_eq!(Config::default().stake_multiplier_bps(u64::MAX), MAX_BPS);
+    }
+
+    #[test]
+    fn stake_locks_boost_the_tier_until_they_end() {
+        let config = Config {
+            stake_tier_amounts: [100, 0, 0],
+            stake_tier_bps: [12_000, 0, 0],
+            stake_lock_bps: [11_000, 0, 15_000],
+            early_unstake_penalty_bps: 1_000,
+            ..Config::default()
+        };
+        let mut stake = Stake {
+            amount: 100,
+            ..Stake::default()
+        };
+        assert_eq!(config.staked_multiplier_bps(&stake, 0), 12_000);
+
+        stake.apply_lock(StakeLock::Days180, 0);
+        assert_eq!(stake.lock_end_ts, 180 * SECONDS_PER_DAY);
+        assert_eq!(config.staked_multiplier_bps(&stake, 0), 18_000);
+        // A shorter lock leaves the longer one in place
+        stake.apply_lock(StakeLock::Days30, SECONDS_PER_DAY);
+        assert_eq!(stake.lock, StakeLock::Days180);
+        assert_eq!(config.staked_multiplier_bps(&stake, 180 * SECONDS_PER_DAY), 12_000);
+
+        // A lock without a configured boost earns only the tier
+        stake.apply_lock(StakeLock::Days90, 180 * SECONDS_PER_DAY);
+        assert_eq!(stake.lock, StakeLock::Days90);
+        assert!(stake.is_lock_active(180 * SECONDS_PER_DAY));
+        assert_eq!(config.staked_multiplier_bps(&stake, 180 * SECONDS_PER_DAY), 12_000);
+
+        assert_eq!(config.early_unstake_penalty(1_005), 100);
+    }
+
+    #[test]
+    fn quest_progress_counts_only_its_metric() {
+        let mut progress = QuestProgress {
+            metric: QuestMetric::AchievementsUnlocked,
+            ..QuestProgress::default()
+        };
+        assert!(!progress.record(QuestMetric::ScoreEarned, 500));
+        assert!(progress.record(QuestMetric::AchievementsUnlocked, 1));
+        assert!(progress.record(QuestMetric::AchievementsUnlocked, u64::MAX));
+        assert_eq!(progress.progress, u64::MAX);
+
+        progress.claimed = true;
+        assert!(!progress.record(QuestMetric::AchievementsUnlocked, 1));
+    }
+
+    #[test]
+    fn house_fee_is_a_share_of_the_amount() {
+        let config = Config {
+            fee_bps: 250,
+            ..Config::default()
+        };
+        assert_eq!(config.house_fee(10_000), 250);
+        assert_eq!(config.house_fee(39), 0);
+        assert_eq!(config.house_fee(u64::MAX), u64::MAX / 40);
+        assert_eq!(Config::default().house_fee(10_000), 0);
+    }
+
+    #[test]
+    fn prize_shares_follow_split_and_entry_count() {
+        let config = Config {
+            prize_split_bps: Config::DEFAULT_PRIZE_SPLIT_BPS,
+            ..Config::default()
+        };
+        assert_eq!(config.prize_shares(1_000, 5), [500, 300, 200]);
+        assert_eq!(config.prize_shares(1_000, 2), [500, 300, 0]);
+        assert_eq!(config.prize_shares(999, 3), [499, 299, 199]);
+        assert_eq!(config.prize_shares(u64::MAX, 1)[0], u64::MAX / 2);
+    }
+
+    #[test]
+    fn usd_fee_converts_at_the_feed_price() {
+        let config = Config {
+            ranked_entry_fee_usd_cents: 500,
+            ..Config::default()
+        };
+        // $5 at $150 per SOL, whichever exponent the feed uses, rounded up
+        assert_eq!(
+            config.usd_fee_lamports(15_000_000_000, -8),
+            Some(33_333_334)
+        );
+        assert_eq!(config.usd_fee_lamports(150, 0), Some(33_333_334));
+        assert_eq!(config.usd_fee_lamports(15, 1), Some(33_333_334));
+        assert_eq!(config.usd_fee_lamports(0, -8), None);
+        assert_eq!(config.usd_fee_lamports(-1, -8), None);
+        assert_eq!(config.usd_fee_lamports(1, -30), None);
+    }
+
+    #[test]
+    fn tournament_standings_break_ties_by_registration() {
+        let [a, b, c] = [
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ];
+        let tournament = Tournament {
+            participants: vec![
+                TournamentEntry {
+                    player: a,
+                    score: 10,
+                },
+                TournamentEntry {
+                    player: b,
+                    score: 30,
+                },
+                TournamentEntry {
+                    player: c,
+                    score: 10,
+                },
+            ],
+            ..Tournament::default()
+        };
+        let order: Vec<_> = tournament.standings().iter().map(|e| e.player).collect();
+        assert_eq!(order, vec![b, a, c]);
+    }
+
+    #[test]
+    fn full_tournament_round_trips() {
+        let tournament = Tournament {
+            is_initialized: true,
+            tournament_id: 4,
+            max_participants: TOURNAMENT_CAPACITY as u8,
+            collection: Pubkey::new_unique(),
+            participants: vec![TournamentEntry::default(); TOURNAMENT_CAPACITY],
+            ..Tournament::default()
+        };
+        let mut data = vec![0u8; Tournament::LEN];
+        tournament.pack(&mut data).unwrap();
+        assert_eq!(Tournament::unpack(&data).unwrap(), tournament);
+    }
+
+    #[test]
+    fn profile_names_normalize_to_lowercase() {
+        let name = pad_profile_name("Trash_Panda-9").unwrap();
+        assert_eq!(
+            normalize_profile_name(&name),
+            pad_profile_name("trash_panda-9")
+        );
+        let profile = PlayerProfile {
+            name,
+            ..PlayerProfile::default()
+        };
+        assert_eq!(profile.display_name(), "Trash_Panda-9");
+
+        assert!(pad_profile_name(&"x".repeat(33)).is_none());
+        assert!(normalize_profile_name(&pad_profile_name(&"x".repeat(32)).unwrap()).is_some());
+        for invalid in ["", "two words", "émile"] {
+            assert!(normalize_profile_name(&pad_profile_name(invalid).unwrap()).is_none());
+        }
+        let mut gap = pad_profile_name("ab").unwrap();
+        gap[3] = b'c';
+        assert!(normalize_profile_name(&gap).is_none());
+    }
+
+    #[test]
+    fn team_members_sort_and_reject_duplicates() {
+        let [a, b, c] = [1, 2, 3].map(|n| Pubkey::new_from_array([n; 32]));
+        assert_eq!(sort_team_members(&[c, a, b]), Some(vec![a, b, c]));
+        assert_eq!(sort_team_members(&[a]), None);
+        assert_eq!(sort_team_members(&[a, b, a]), None);
+        assert_eq!(sort_team_members(&[a, b, c, a, b]), None);
+
+        let team = Team {
+            members: [a, b, Pubkey::default(), Pubkey::default()],
+            member_count: 2,
+            ..Team::default()
+        };
+        assert!(team.is_member(&b));
+        assert!(!team.is_member(&c) && !team.is_member(&Pubkey::default()));
+    }
+
+    #[test]
+    fn history_keeps_most_recent_runs() {
+        let mut history = ScoreHistory::default();
+        for score in 0..5 {
+            history.push(
+                RunRecord {
+                    score,
+                    timestamp: 0,
+                },
+                3,
+            );
+        }
+        let scores: Vec<u64> = history.runs.iter().map(|r| r.score).collect();
+        assert_eq!(scores, vec![2, 3, 4]);
+
+        let mut data = vec![0u8; ScoreHistory::space(3)];
+        history.pack(&mut data).unwrap();
+        assert_eq!(ScoreHistory::unpack(&data).unwrap(), history);
+        // A larger account reads the same runs back with room for more
+        data.resize(ScoreHistory::space(HISTORY_CAPACITY), 0);
+        assert_eq!(ScoreHistory::unpack(&data).unwrap(), history);
+        assert_eq!(ScoreHistory::capacity(data.len()), HISTORY_CAPACITY);
+    }
+
+    #[test]
+    fn airdrop_claims_set_each_bit_once() {
+        let mut claims = AirdropClaims {
+            is_initialized: true,
+            airdrop_id: 1,
+            bits: vec![0; AirdropClaims::bitmap_len(10)],
+        };
+        assert_eq!(claims.bits.len(), 2);
+        assert!(claims.set_claimed(9));
+        assert!(!claims.set_claimed(9));
+        assert!(!claims.set_claimed(16));
+        assert!(claims.is_claimed(9) && !claims.is_claimed(8));
+
+        let mut data = vec![0u8; AirdropClaims::space(10)];
+        claims.pack(&mut data).unwrap();
+        assert_eq!(AirdropClaims::unpack(&data).unwrap(), claims);
+    }
+
+    #[test]
+    fn ban_list_stays_sorted() {
+        let players: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let mut list = BanList {
+            is_initialized: true,
+            players: Vec::new(),
+        };
+        for player in players.iter().rev() {
+            assert!(list.ban(*player));
+        }
+        assert!(!list.ban(players[2]));
+        assert_eq!(list.players, players);
+        assert!(list.unban(&players[1]));
+        assert!(!list.unban(&players[1]));
+        assert!(list.is_banned(&players[0]) && !list.is_banned(&players[1]));
+
+        let mut data = vec![0u8; BanList::space(3)];
+        list.pack(&mut data).unwrap();
+        assert_eq!(BanList::unpack(&data).unwrap(), list);
+    }
+
+    #[test]
+    fn proposal_space_matches_packed_size() {
+        let proposal = Proposal {
+            is_initialized: true,
+            proposal_id: 3,
+            accounts: vec![ProposalAccount::default(); 2],
+            data: vec![10],
+            ..Proposal::default()
+        };
+        let mut data = vec![0u8; Proposal::space(2, 1)];
+        proposal.pack(&mut data).unwrap();
+        assert_eq!(Proposal::unpack(&data).unwrap(), proposal);
+    }
+
+    #[test]
+    fn discriminators_match_anchor_derivation() {
+        for (name, discriminator) in [
+            ("ScoreAccount", ScoreAccount::DISCRIMINATOR),
+            ("Config", Config::DISCRIMINATOR),
+            ("Season", Season::DISCRIMINATOR),
+            ("Leaderboard", Leaderboard::DISCRIMINATOR),
+            ("WeeklyLeaderboard", WeeklyLeaderboard::DISCRIMINATOR),
+            ("DailyChallenge", DailyChallenge::DISCRIMINATOR),
+            ("DailySubmission", DailySubmission::DISCRIMINATOR),
+            ("Cosmetic", Cosmetic::DISCRIMINATOR),
+            ("BadgeCatalog", BadgeCatalog::DISCRIMINATOR),
+            ("GlobalStats", GlobalStats::DISCRIMINATOR),
+            ("Match", Match::DISCRIMINATOR),
+            ("Lobby", Lobby::DISCRIMINATOR),
+            ("BetPool", BetPool::DISCRIMINATOR),
+            ("Bet", Bet::DISCRIMINATOR),
+            ("Tournament", Tournament::DISCRIMINATOR),
+            ("PlayerProfile", PlayerProfile::DISCRIMINATOR),
+            ("NameRecord", NameRecord::DISCRIMINATOR),
+            ("Follow", Follow::DISCRIMINATOR),
+            ("Guild", Guild::DISCRIMINATOR),
+            ("GuildMember", GuildMember::DISCRIMINATOR),
+            ("Team", Team::DISCRIMINATOR),
+            ("SessionToken", SessionToken::DISCRIMINATOR),
+            ("GameSession", GameSession::DISCRIMINATOR),
+            ("ScoreHistory", ScoreHistory::DISCRIMINATOR),
+            ("Airdrop", Airdrop::DISCRIMINATOR),
+            ("AirdropClaims", AirdropClaims::DISCRIMINATOR),
+            ("ScoreTree", ScoreTree::DISCRIMINATOR),
+            ("BanList", BanList::DISCRIMINATOR),
+            ("Report", Report::DISCRIMINATOR),
+            ("AdminSet", AdminSet::DISCRIMINATOR),
+            ("Proposal", Proposal::DISCRIMINATOR),
+            ("LootRequest", LootRequest::DISCRIMINATOR),
+            ("Inventory", Inventory::DISCRIMINATOR),
+            ("Catalog", Catalog::DISCRIMINATOR),
+            ("Vesting", Vesting::DISCRIMINATOR),
+            ("Stake", Stake::DISCRIMINATOR),
+            ("Quest", Quest::DISCRIMINATOR),
+            ("QuestProgress", QuestProgress::DISCRIMINATOR),
+        ] {
+            let digest = hash(format!("account:{}", name).as_bytes());
+            assert_eq!(
+                digest.to_bytes()[..DISCRIMINATOR_LEN],
+                discriminator,
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn unpack_rejects_wrong_discriminator() {
+        let season = Season {
+            is_initialized: true,
+            season_id: 3,
+            ..Season::default()
+        };
+        let mut data = vec![0u8; Season::LEN];
+        season.pack(&mut data).unwrap();
+        assert_eq!(Season::unpack(&data).unwrap(), season);
+
+        data[..DISCRIMINATOR_LEN].copy_from_slice(&Config::DISCRIMINATOR);
+        assert_eq!(Season::unpack(&data), Err(ProgramError::InvalidAccountData));
+    }
+
+    #[test]
+    fn score_account_round_trips_through_borsh() {
+        let state = ScoreAccount {
+            is_initialized: true,
+            player: Pubkey::new_unique(),
+            score: 1_234,
+            game_mode: GameMode::Endless,
+            best_score: 5_678,
+            season_id: 9,
+            last_update_ts: -1,
+            nonce: 3,
+            version: ScoreAccount::VERSION,
+            delegate: Pubkey::new_unique(),
+            week_best_score: 42,
+            run_hash_cursor: 7,
+            ..ScoreAccount::default()
+        };
+        let mut data = vec![0u8; ScoreAccount::LEN];
+        state.pack(&mut data).unwrap();
+        assert_eq!(ScoreAccount::unpack(&data).unwrap(), state);
+
+        // The score sits right after the discriminator, flag and player key
+        let score_at = DISCRIMINATOR_LEN + 1 + 32;
+        assert_eq!(data[score_at..score_at + 8], 1_234u64.to_le_bytes());
+        assert_eq!(
+            state.pack(&mut data[..ScoreAccount::LEN - 1]),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn unpacks_untagged_layouts() {
+        let state = ScoreAccount {
+            is_initialized: true,
+            player: Pubkey::new_unique(),
+            score: 120,
+            best_score: 300,
+            version: 1,
+            ..ScoreAccount::default()
+        };
+        // Before discriminators the fields were stored bare and ended at the version
+        // byte; v0 lacked the version byte too
+        let v1 = borsh::to_vec(&state).unwrap()[..ScoreAccount::LEN_V1].to_vec();
+        assert_eq!(ScoreAccount::unpack_any_version(&v1).unwrap(), state);
+
+        let v0 = ScoreAccount::unpack_any_version(&v1[..ScoreAccount::LEN_V0]).unwrap();
+        assert_eq!(
+            v0,
+            ScoreAccount {
+                version: 0,
+                ..state
+            }
+        );
+        assert!(ScoreAccount::unpack(&v1).is_err());
+    }
+
+    #[test]
+    fn grown_score_accounts_ignore_spare_bytes() {
+        let state = ScoreAccount {
+            is_initialized: true,
+            score: 7,
+            version: ScoreAccount::VERSION,
+            ..ScoreAccount::default()
+        };
+        let mut data = vec![0u8; ScoreAccount::LEN + 64];
+        state.pack(&mut data).unwrap();
+        assert_eq!(ScoreAccount::unpack(&data).unwrap(), state);
+        assert_eq!(ScoreAccount::unpack_any_version(&data).unwrap(), state);
+        assert!(ScoreAccount::unpack(&data[..ScoreAccount::LEN - 1]).is_err());
+    }
+
+    #[test]
+    fn stored_bump_is_read_from_current_layout_only() {
+        let mut state = ScoreAccount {
+            is_initialized: true,
+            version: ScoreAccount::VERSION,
+            bump: 253,
+            ..ScoreAccount::default()
+        };
+        let mut data = vec![0u8; ScoreAccount::LEN];
+        state.pack(&mut data).unwrap();
+        assert_eq!(ScoreAccount::stored_bump(&data), Some(253));
+        assert_eq!(
+            ScoreAccount::stored_bump(&data[..ScoreAccount::LEN - 1]),
+            None
+        );
+
+        state.version = ScoreAccount::VERSION - 1;
+        state.pack(&mut data).unwrap();
+        assert_eq!(ScoreAccount::stored_bump(&data), None);
+    }
+
+    #[test]
+    fn leaderboard_keeps_descending_order() {
+        let mut board = Leaderboard::default();
+        board.submit(Pubkey::new_unique(), 20);
+        board.submit(Pubkey::new_unique(), 50);
+        assert_eq!(board.submit(Pubkey::new_unique(), 30), Some(1));
+        assert_eq!(scores(&board), vec![50, 30, 20]);
+    }
+
+    #[test]
+    fn leaderboard_keeps_one_entry_per_player() {
+        let mut board = Leaderboard::default();
+        let player = Pubkey::new_unique();
+        board.submit(Pubkey::new_unique(), 40);
+        board.submit(player, 10);
+        assert_eq!(board.submit(player, 5), Some(1));
+        assert_eq!(board.submit(player, 60), Some(0));
+        assert_eq!(scores(&board), vec![60, 40]);
+    }
+
+    #[test]
+    fn full_leaderboard_displaces_lowest_entry() {
+        let mut board = Leaderboard::default();
+        for score in 1..=LEADERBOARD_SIZE as u64 {
+            board.submit(Pubkey::new_unique(), score);
+        }
+        assert_eq!(board.submit(Pubkey::new_unique(), 1), None);
+        assert_eq!(board.submit(Pubkey::new_unique(), 1_000), Some(0));
+        assert_eq!(board.entries().len(), LEADERBOARD_SIZE);
+        assert_eq!(board.entries().last().unwrap().score(), 2);
+    }
+
+    #[test]
+    fn full_leaderboard_reports_displaced_entry() {
+        let mut board = Leaderboard::default();
+        let lowest = Pubkey::new_unique();
+        board.submit(lowest, 1);
+        for score in 2..=LEADERBOARD_SIZE as u64 {
+            board.submit(Pubkey::new_unique(), score);
+        }
+        let (rank, displaced) = board.submit_displacing(Pubkey::new_unique(), 50);
+        assert_eq!(rank, Some(51));
+        assert_eq!(displaced, Some(LeaderboardEntry::new(lowest, 1)));
+        assert_eq!(
+            board.submit_displacing(Pubkey::new_unique(), 2),
+            (None, None)
+        );
+    }
+
+    #[test]
+    fn rebalance_promotes_across_pages() {
+        let mut upper = Leaderboard::default();
+        let mut lower = Leaderboard::default();
+        for score in 101..=200 {
+            upper.submit(Pubkey::new_unique(), score);
+        }
+        let repeat = upper.entries()[0].player;
+        lower.submit(repeat, 150);
+        lower.submit(Pubkey::new_unique(), 300);
+        lower.submit(Pubkey::new_unique(), 102);
+        lower.submit(Pubkey::new_unique(), 50);
+
+        // 300 moves up and knocks 101 down; the stale entry for a player already
+        // ranked higher is dropped; 102 stays, tied with the lowest above
+        assert_eq!(upper.rebalance(&mut lower), 2);
+        assert!(upper.is_full());
+        assert_eq!(upper.entries()[0].score(), 300);
+        assert_eq!(upper.last().unwrap().score(), 102);
+        assert_eq!(scores(&lower), vec![102, 101, 50]);
+
+        // A page with room takes entries until the page below is empty
+        let mut top = Leaderboard::default();
+        assert_eq!(top.rebalance(&mut lower), 3);
+        assert_eq!(scores(&top), vec![102, 101, 50]);
+        assert!(lower.entries().is_empty());
+    }
+
+    #[test]
+    fn game_sessions_bound_score_by_elapsed_slots() {
+        let session = GameSession {
+            start_slot: 100,
+            ..GameSession::default()
+        };
+        assert!(session.is_plausible(500, 110, 50));
+        assert!(!session.is_plausible(501, 110, 50));
+        assert!(!session.is_plausible(1, 100, 50));
+        assert!(session.is_plausible(u64::MAX, 100, 0));
+        assert!(!session.is_expired(150, 50));
+        assert!(session.is_expired(151, 50));
+        assert!(!session.is_expired(u64::MAX, 0));
+    }
+
+    #[test]
+    fn game_sessions_bound_score_by_elapsed_seconds() {
+        let session = GameSession {
+            start_ts: 1_000,
+            ..GameSession::default()
+        };
+        assert!(session.is_plausible_rate(300, 1_030, 10));
+        assert!(!session.is_plausible_rate(301, 1_030, 10));
+        assert!(!session.is_plausible_rate(1, 1_000, 10));
+        // A clock behind the start counts as no time elapsed
+        assert!(!session.is_plausible_rate(1, 900, 10));
+        assert!(session.is_plausible_rate(u64::MAX, 1_000, 0));
+    }
+
+    #[test]
+    fn xp_curve_levels_up_quadratically() {
+        assert_eq!(level_for_xp(0), 0);
+        assert_eq!(level_for_xp(99), 0);
+        assert_eq!(level_for_xp(100), 1);
+        assert_eq!(level_for_xp(399), 1);
+        assert_eq!(level_for_xp(400), 2);
+        assert_eq!(level_for_xp(u64::MAX), MAX_LEVEL);
+        assert_eq!(run_xp(250), 12);
+
+        let mut profile = PlayerProfile::default();
+        assert!(!profile.add_xp(50));
+        assert!(profile.add_xp(xp_for_level(3) - 50));
+        assert_eq!((profile.xp, profile.level), (900, 3));
+    }
+
+    #[test]
+    fn daily_xp_cap_resets_each_day() {
+        let mut profile = PlayerProfile::default();
+        let day = 20_000 * SECONDS_PER_DAY;
+        assert_eq!(profile.cap_daily_xp(600, day), 600);
+        assert_eq!(profile.cap_daily_xp(600, day + 3_600), DAILY_XP_CAP - 600);
+        assert_eq!(profile.cap_daily_xp(10, day + 7_200), 0);
+        assert_eq!(profile.cap_daily_xp(10, day + SECONDS_PER_DAY), 10);
+    }
+
+    #[test]
+    fn cosmetics_need_their_level_and_achievement() {
+        let cosmetic = Cosmetic {
+            is_initialized: true,
+            cosmetic_id: 5,
+            min_level: 3,
+            game_mode: GameMode::Arcade,
+            achievement: 7,
+        };
+        assert!(!cosmetic.is_earned(2, 1 << 7));
+        assert!(!cosmetic.is_earned(3, 1 << 6));
+        assert!(cosmetic.is_earned(3, 1 << 7));
+        let level_only = Cosmetic {
+            achievement: NO_ACHIEVEMENT,
+            ..cosmetic
+        };
+        assert!(level_only.is_earned(3, 0));
+
+        let mut profile = PlayerProfile::default();
+        assert!(profile.unlock(5));
+        assert!(!profile.unlock(5));
+        assert!(profile.has_unlock(5) && !profile.has_unlock(4));
+        assert!(!profile.has_unlock(COSMETIC_COUNT));
+    }
+
+    #[test]
+    fn badges_check_their_milestone() {
+        let score = ScoreAccount {
+            best_score: 100_000,
+            streak: 29,
+            ..ScoreAccount::default()
+        };
+        let badge = BadgeDefinition {
+            mint: Pubkey::new_unique(),
+            milestone: BadgeMilestone::BestScore,
+            threshold: 100_000,
+        };
+        assert!(badge.is_reached(&score));
+        let streak = BadgeDefinition {
+            milestone: BadgeMilestone::Streak,
+            threshold: 30,
+            ..badge
+        };
+        assert!(!streak.is_reached(&score));
+
+        let catalog = BadgeCatalog {
+            is_initialized: true,
+            badges: vec![badge, streak],
+        };
+        let mut data = vec![0; BadgeCatalog::space(2)];
+        catalog.pack(&mut data).unwrap();
+        let unpacked = BadgeCatalog::unpack(&data).unwrap();
+        assert_eq!(unpacked.get(1), Some(&streak));
+        assert_eq!(unpacked.get(2), None);
+    }
+
+    #[test]
+    fn global_stats_saturate_instead_of_wrapping() {
+        let mut stats = GlobalStats {
+            total_score: u64::MAX - 5,
+            ..GlobalStats::default()
+        };
+        stats.record_player();
+        stats.record_run(10);
+        stats.record_score(3);
+        stats.record_fee(250);
+        assert_eq!(
+            (
+                stats.total_players,
+                stats.total_runs,
+                stats.total_score,
+                stats.total_fees
+            ),
+            (1, 1, u64::MAX, 250)
+        );
+        let mut data = vec![0; GlobalStats::LEN];
+        stats.pack(&mut data).unwrap();
+        assert_eq!(GlobalStats::unpack(&data).unwrap(), stats);
+    }
+
+    #[test]
+    fn score_buckets_are_powers_of_two() {
+        assert_eq!(score_bucket(0), 0);
+        assert_eq!(score_bucket(1), 1);
+        assert_eq!((score_bucket(1023), score_bucket(1024)), (10, 11));
+        assert_eq!(score_bucket(u64::MAX), HISTOGRAM_BUCKETS - 1);
+    }
+
+    #[test]
+    fn histogram_ranks_best_scores() {
+        let mut stats = GlobalStats::default();
+        assert_eq!(stats.top_bps(0), None);
+        for best in [5, 100, 100, 2_000] {
+            stats.record_player();
+            stats.record_best(0, best);
+        }
+        for _ in 0..21 {
+            stats.record_player();
+        }
+        assert_eq!(stats.histogram[0], 21);
+        assert_eq!(stats.top_bps(2_000), Some(400));
+        assert_eq!(stats.top_bps(64), Some(1_200));
+        assert_eq!(stats.top_bps(0), Some(MAX_BPS));
+
+        // A better score in the same bucket leaves the counts alone
+        stats.record_best(100, 120);
+        assert_eq!(stats.histogram[score_bucket(100)], 2);
+        stats.record_best(2_000, 5_000);
+        assert_eq!(stats.histogram[score_bucket(2_000)], 0);
+        assert_eq!(stats.histogram[score_bucket(5_000)], 1);
+    }
+
+    #[test]
+    fn region_codes_are_two_letters() {
+        assert_eq!(parse_region("de"), Some(*b"DE"));
+        assert_eq!(parse_region("US"), Some(*b"US"));
+        assert_eq!(parse_region("USA"), None);
+        assert_eq!(parse_region("1A"), None);
+        assert_eq!(parse_region(""), None);
+        assert!(!is_valid_region(&NO_REGION));
+    }
+
+    #[test]
+    fn weeks_start_on_monday() {
+        // 1970-01-01 was a Thursday and 1970-01-05 a Monday
+        assert_eq!(week_of(0), 0);
+        assert_eq!(week_of(4 * SECONDS_PER_DAY - 1), 0);
+        assert_eq!(week_of(4 * SECONDS_PER_DAY), 1);
+        assert_eq!(week_of(-3 * SECONDS_PER_DAY), 0);
+        assert_eq!(week_of(-3 * SECONDS_PER_DAY - 1), -1);
+    }
+
+    #[test]
+    fn week_best_resets_in_a_new_week() {
+        let mut state = ScoreAccount::default();
+        state.record_score(40, 0);
+        state.record_score(25, 60);
+        assert_eq!((state.week, state.week_best_score), (0, 40));
+        state.record_score(10, SECONDS_PER_WEEK);
+        assert_eq!(
+            (state.best_score, state.week, state.week_best_score),
+            (40, 1, 10)
+        );
+    }
+
+    #[test]
+    fn bet_pools_pay_winners_pro_rata_after_the_fee() {
+        let [a, b] = [(); 2].map(|_| Pubkey::new_unique());
+        let mut pool = BetPool {
+            player_a: a,
+            player_b: b,
+            ..BetPool::default()
+        };
+        assert_eq!(pool.add_stake(&Pubkey::new_unique(), 1), None);
+        pool.add_stake(&a, 300).unwrap();
+        pool.add_stake(&a, 100).unwrap();
+        pool.add_stake(&b, 600).unwrap();
+        let bet = |on: Pubkey, amount: u64| Bet {
+            on,
+            amount,
+            ..Bet::default()
+        };
+
+        // 5% of 1000 to the house leaves 950 for the 400 on the winner
+        assert_eq!(pool.decide(a, 500), 50);
+        assert_eq!(pool.status, BetPoolStatus::Decided);
+        assert_eq!(pool.payout(&bet(a, 300)), 712);
+        assert_eq!(pool.payout(&bet(a, 100)), 237);
+        assert_eq!(pool.payout(&bet(b, 600)), 0);
+
+        // Nobody backed b, so everyone gets their stake back and no fee is taken
+        let mut pool = BetPool {
+            player_a: a,
+            player_b: b,
+            staked_on_a: 400,
+            ..BetPool::default()
+        };
+        assert_eq!(pool.decide(b, 500), 0);
+        assert_eq!(pool.status, BetPoolStatus::Void);
+        assert_eq!(pool.payout(&bet(a, 400)), 400);
+    }
+
+    #[test]
+    fn lobbies_pair_players_in_join_order() {
+        let mut lobby = Lobby {
+            capacity: 3,
+            ..Lobby::default()
+        };
+        let [a, b, c, d] = [(); 4].map(|_| Pubkey::new_unique());
+        assert!(lobby.join(a) && lobby.join(b) && lobby.join(c));
+        assert!(!lobby.join(d));
+        assert!(lobby.leave(&b));
+        assert!(!lobby.leave(&b));
+        assert!(lobby.join(d));
+        assert_eq!(lobby.players(), &[a, c, d]);
+
+        assert_eq!(lobby.take_pair(), Some((a, c)));
+        assert_eq!(lobby.players(), &[d]);
+        assert_eq!(lobby.take_pair(), None);
+        assert_eq!(lobby.players, {
+            let mut players = [Pubkey::default(); LOBBY_CAPACITY];
+            players[0] = d;
+            players
+        });
+    }
+
+    #[test]
+    fn run_hashes_are_remembered_until_overwritten() {
+        let mut state = ScoreAccount::default();
+        let run = |byte: u8| Hash::new_from_array([byte; 32]);
+        assert!(!state.record_run_hash(&Hash::default()));
+        assert!(state.record_run_hash(&run(1)));
+        assert!(!state.record_run_hash(&run(1)));
+        for i in 2..=RECENT_RUN_HASHES as u8 {
+            assert!(state.record_run_hash(&run(i)));
+        }
+        assert!(!state.record_run_hash(&run(1)));
+        // One more run pushes the oldest hash out of the ring
+        assert!(state.record_run_hash(&run(100)));
+        assert!(state.record_run_hash(&run(1)));
+        assert!(!state.record_run_hash(&run(100)));
+    }
+
+    #[test]
+    fn weekly_leaderboard_clears_when_the_week_rolls_over() {
+        let mut data = vec![0u8; WeeklyLeaderboard::LEN];
+        let weekly = WeeklyLeaderboard::init(&mut data, 5).unwrap();
+        weekly.board.submit(Pubkey::new_unique(), 7);
+        assert!(!weekly.roll_week(5));
+        assert!(!weekly.roll_week(4));
+        assert_eq!(weekly.board.entries().len(), 1);
+        assert!(weekly.roll_week(6));
+        assert_eq!(weekly.week(), 6);
+        assert!(weekly.board.entries().is_empty());
+        assert!(WeeklyLeaderboard::load(&data).unwrap().is_initialized());
+    }
+
+    #[test]
+    fn daily_challenge_keeps_its_day_and_seed_ahead_of_the_board() {
+        let mut data = vec![0u8; DailyChallenge::LEN];
+        let daily = DailyChallenge::init(&mut data, 19_000, [7; 32]).unwrap();
+        daily.board.submit(Pubkey::new_unique(), 42);
+
+        let mut expected = DailyChallenge::DISCRIMINATOR.to_vec();
+        expected.extend_from_slice(&19_000i64.to_le_bytes());
+        expected.extend_from_slice(&[7; 32]);
+        expected.push(1);
+        expected.extend_from_slice(&1u32.to_le_bytes());
+        assert_eq!(data[..expected.len()], expected[..]);
+
+        let daily = DailyChallenge::load(&data).unwrap();
+        assert_eq!(daily.day(), 19_000);
+        assert_eq!(daily.board.entries()[0].score(), 42);
+    }
+
+    #[test]
+    fn leaderboard_is_edited_in_place() {
+        let mut data = vec![0u8; Leaderboard::LEN];
+        let player = Pubkey::new_unique();
+        Leaderboard::init(&mut data).unwrap().submit(player, 7);
+        Leaderboard::load_mut(&mut data)
+            .unwrap()
+            .submit(Pubkey::new_unique(), 9);
+
+        // Same bytes as a Borsh-encoded bool, u32 count and entries
+        let mut expected = Leaderboard::DISCRIMINATOR.to_vec();
+        expected.push(1);
+        expected.extend_from_slice(&2u32.to_le_bytes());
+        assert_eq!(data[..expected.len()], expected[..]);
+        let board = Leaderboard::load(&data).unwrap();
+        assert!(board.is_initialized());
+        assert_eq!(scores(board), vec![9, 7]);
+        assert_eq!(board.entries()[1].player, player);
+
+        data[DISCRIMINATOR_LEN + 1] = LEADERBOARD_SIZE as u8 + 1;
+        assert!(Leaderboard::load(&data).is_err());
+    }
+    #[test]
+    fn zero_copy_views_need_no_alignment() {
+        assert_eq!(
+            std::mem::size_of::<Leaderboard>(),
+            Leaderboard::LEN - DISCRIMINATOR_LEN
+        );
+        assert_eq!(std::mem::align_of::<Leaderboard>(), 1);
+
+        // Account data carries no alignment guarantee, so view it one byte in
+        let mut buffer = vec![0xff; Leaderboard::LEN + 1];
+        let data = &mut buffer[1..];
+        let board = Leaderboard::init(data).unwrap();
+        assert!(board.entries().is_empty());
+        board.submit(Pubkey::new_unique(), 5);
+        assert_eq!(scores(Leaderboard::load(data).unwrap()), vec![5]);
+
+        assert!(Leaderboard::load(&data[..Leaderboard::LEN - 1]).is_err());
+        data[..DISCRIMINATOR_LEN].copy_from_slice(&WeeklyLeaderboard::DISCRIMINATOR);
+        assert!(Leaderboard::load(data).is_err());
+    }
+}
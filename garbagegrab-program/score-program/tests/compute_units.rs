@@ -0,0 +1,263 @@
+// Compute-unit budgets for the hot instructions. These run the SBF build of the
+// program, since natively executed processors are not metered, so they are
+// ignored by default. Run them after building with
+//
+//     cargo test-sbf --test compute_units -- --ignored --nocapture
+//
+// and lower a budget once an optimization lands so it cannot quietly regress.
+use score_program::{
+    instruction::GarbageGrabInstruction,
+    pda::{ConfigPda, ScorePda},
+    state::{Config, GameMode, Leaderboard, ScoreAccount, LEADERBOARD_SIZE},
+};
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program,
+    transaction::Transaction,
+};
+
+const INITIALIZE_BUDGET: u64 = 25_000;
+const UPDATE_SCORE_BUDGET: u64 = 12_000;
+// Placing on a full board shifts every entry below the new one
+const SUBMIT_TO_FULL_LEADERBOARD_BUDGET: u64 = 20_000;
+const BATCH_UPDATE_BUDGET_PER_ENTRY: u64 = 6_000;
+
+const BATCH_SIZE: usize = 16;
+
+fn score_address(program_id: &Pubkey, player: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"score", player.as_ref(), &GameMode::Arcade.seed()],
+        program_id,
+    )
+    .0
+}
+
+fn config_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"config"], program_id).0
+}
+
+fn season_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"season"], program_id).0
+}
+
+fn leaderboard_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"leaderboard"], program_id).0
+}
+
+fn ban_list_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"ban_list"], program_id).0
+}
+
+fn program_account(program_id: &Pubkey, data: Vec<u8>) -> Account {
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: *program_id,
+        ..Account::default()
+    }
+}
+
+fn score_account(program_id: &Pubkey, player: &Pubkey, score: u64) -> Account {
+    let mut data = vec![0u8; ScoreAccount::LEN];
+    ScoreAccount {
+        is_initialized: true,
+        player: *player,
+        score,
+        best_score: score,
+        ranked_run_open: true,
+        version: ScoreAccount::VERSION,
+        bump: ScorePda::find(program_id, player, GameMode::Arcade).1,
+        ..ScoreAccount::default()
+    }
+    .pack(&mut data)
+    .unwrap();
+    program_account(program_id, data)
+}
+
+// Start a validator running the SBF build, with a config naming `game_server`,
+// a full leaderboard of low scores and a score account for each of `players`
+async fn setup(game_server: &Pubkey, players: &[Pubkey]) -> (ProgramTestContext, Pubkey) {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new("score_program", program_id, None);
+    program_test.prefer_bpf(true);
+
+    let config = Config {
+        is_initialized: true,
+        game_server: *game_server,
+        max_score: u64::MAX,
+        max_increment: u64::MAX,
+        bump: ConfigPda::find(&program_id).1,
+        ..Config::default()
+    };
+    let mut data = vec![0u8; Config::LEN];
+    config.pack(&mut data).unwrap();
+    program_test.add_account(
+        config_address(&program_id),
+        program_account(&program_id, data),
+    );
+
+    let mut data = vec![0u8; Leaderboard::LEN];
+    let board = Leaderboard::init(&mut data).unwrap();
+    for score in 1..=LEADERBOARD_SIZE as u64 {
+        board.submit(Pubkey::new_unique(), score);
+    }
+    program_test.add_account(
+        leaderboard_address(&program_id),
+        program_account(&program_id, data),
+    );
+
+    for player in players {
+        program_test.add_account(
+            *player,
+            Account::new(1_000_000_000, 0, &system_program::id()),
+        );
+        program_test.add_account(
+            score_address(&program_id, player),
+            score_account(&program_id, player, 1_000),
+        );
+    }
+
+    (program_test.start_with_context().await, program_id)
+}
+
+// Process `instruction` on its own and return the compute units it consumed
+async fn compute_units(
+    context: &mut ProgramTestContext,
+    instruction: Instruction,
+    signers: &[&Keypair],
+) -> u64 {
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    let outcome = context
+        .banks_client
+        .process_transaction_with_metadata(transaction)
+        .await
+        .unwrap();
+    outcome.result.unwrap();
+    outcome.metadata.unwrap().compute_units_consumed
+}
+
+fn check_budget(name: &str, consumed: u64, budget: u64) {
+    println!("{:<28} {:>7} CU (budget {})", name, consumed, budget);
+    assert!(
+        consumed <= budget,
+        "{} used {} compute units, over its budget of {}",
+        name,
+        consumed,
+        budget
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF build of the program; run with cargo test-sbf"]
+async fn single_player_instructions_stay_within_budget() {
+    let player = Keypair::new();
+    let newcomer = Keypair::new();
+    let (mut context, program_id) = setup(&Pubkey::default(), &[player.pubkey()]).await;
+    let funded = Account::new(1_000_000_000, 0, &system_program::id());
+    context.set_account(&newcomer.pubkey(), &funded.into());
+
+    let initialize = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::Initialize {
+            game_mode: GameMode::Arcade,
+            referrer: None,
+            allowlist_proof: Vec::new(),
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(score_address(&program_id, &newcomer.pubkey()), false),
+            AccountMeta::new(newcomer.pubkey(), true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+        ],
+    );
+    let consumed = compute_units(&mut context, initialize, &[&newcomer]).await;
+    check_budget("Initialize", consumed, INITIALIZE_BUDGET);
+
+    let update = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::UpdateScore {
+            game_mode: GameMode::Arcade,
+            increment: 50,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(score_address(&program_id, &player.pubkey()), false),
+            AccountMeta::new_readonly(player.pubkey(), true),
+            AccountMeta::new_readonly(season_address(&program_id), false),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(ban_list_address(&program_id), false),
+        ],
+    );
+    let consumed = compute_units(&mut context, update, &[&player]).await;
+    check_budget("UpdateScore", consumed, UPDATE_SCORE_BUDGET);
+
+    // The player's score beats every seeded entry, so it lands at the top
+    let submit = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::SubmitToLeaderboard {
+            game_mode: GameMode::Arcade,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(leaderboard_address(&program_id), false),
+            AccountMeta::new(score_address(&program_id, &player.pubkey()), false),
+            AccountMeta::new_readonly(player.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(ban_list_address(&program_id), false),
+        ],
+    );
+    let consumed = compute_units(&mut context, submit, &[&player]).await;
+    check_budget(
+        "SubmitToLeaderboard (full)",
+        consumed,
+        SUBMIT_TO_FULL_LEADERBOARD_BUDGET,
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs the SBF build of the program; run with cargo test-sbf"]
+async fn batch_update_stays_within_budget_per_entry() {
+    let server = Keypair::new();
+    let players: Vec<Pubkey> = (0..BATCH_SIZE).map(|_| Pubkey::new_unique()).collect();
+    let (mut context, program_id) = setup(&server.pubkey(), &players).await;
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(server.pubkey(), true),
+        AccountMeta::new_readonly(season_address(&program_id), false),
+        AccountMeta::new_readonly(config_address(&program_id), false),
+        AccountMeta::new_readonly(ban_list_address(&program_id), false),
+    ];
+    accounts.extend(
+        players
+            .iter()
+            .map(|player| AccountMeta::new(score_address(&program_id, player), false)),
+    );
+    let batch = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::BatchUpdate {
+            game_mode: GameMode::Arcade,
+            increments: vec![25; BATCH_SIZE],
+        }
+        .pack(),
+        accounts,
+    );
+    let consumed = compute_units(&mut context, batch, &[&server]).await;
+    check_budget(
+        "BatchUpdate",
+        consumed,
+        BATCH_UPDATE_BUDGET_PER_ENTRY * BATCH_SIZE as u64,
+    );
+}
@@ -0,0 +1,176 @@
+use proptest::{collection::vec, prelude::*};
+use score_program::{
+    process_instruction,
+    state::{
+        AdminSet, Airdrop, AirdropClaims, BanList, Catalog, Config, Guild, GuildMember, Inventory,
+        Leaderboard, LootRequest, Match, NameRecord, PlayerProfile, Proposal, Report, ScoreAccount,
+        ScoreHistory, ScoreTree, Season, SessionToken, Team, Tournament, HISTORY_CAPACITY,
+    },
+};
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::SUCCESS,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    pubkey::Pubkey,
+    system_program,
+};
+
+// Highest instruction tag the program currently decodes
+const MAX_TAG: u8 = 110;
+
+const PROGRAM_ID: Pubkey = Pubkey::new_from_array([7; 32]);
+const PLAYER: Pubkey = Pubkey::new_from_array([9; 32]);
+
+// Serve a fixed Clock so fuzzed inputs get past the timestamp checks
+struct ClockStubs;
+
+impl SyscallStubs for ClockStubs {
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            unix_timestamp: 1_700_000_000,
+            ..Clock::default()
+        };
+        unsafe { *(var_addr as *mut Clock) = clock };
+        SUCCESS
+    }
+}
+
+// An owned account the fuzzer can hand to the program as an AccountInfo
+#[derive(Debug, Clone)]
+struct FuzzAccount {
+    key: Pubkey,
+    is_signer: bool,
+    is_writable: bool,
+    owned_by_program: bool,
+    lamports: u64,
+    data: Vec<u8>,
+}
+
+// Keys the program derives, so fuzzed accounts regularly pass the PDA checks
+fn known_keys() -> Vec<Pubkey> {
+    let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &PROGRAM_ID).0;
+    vec![
+        PLAYER,
+        pda(&[b"score", PLAYER.as_ref(), &[0]]),
+        pda(&[b"season"]),
+        pda(&[b"config"]),
+        pda(&[b"leaderboard"]),
+        pda(&[b"vault"]),
+        pda(&[b"prize_pool"]),
+        pda(&[b"profile", PLAYER.as_ref()]),
+        pda(&[b"guild_member", PLAYER.as_ref()]),
+        pda(&[b"ban_list"]),
+        pda(&[b"catalog"]),
+        pda(&[b"inventory", PLAYER.as_ref()]),
+        system_program::id(),
+    ]
+}
+
+// Random bytes either of arbitrary length or sized like one of the program's accounts
+fn account_data() -> impl Strategy<Value = Vec<u8>> {
+    let sized = |len: usize| vec(any::<u8>(), len..=len);
+    prop_oneof![
+        vec(any::<u8>(), 0..128),
+        sized(ScoreAccount::LEN_V0),
+        sized(ScoreAccount::LEN_V1),
+        sized(ScoreAccount::LEN),
+        sized(Config::LEN),
+        sized(Season::LEN),
+        sized(Leaderboard::LEN),
+        sized(Match::LEN),
+        sized(Tournament::LEN),
+        sized(PlayerProfile::LEN),
+        sized(NameRecord::LEN),
+        sized(Guild::LEN),
+        sized(GuildMember::LEN),
+        sized(Team::LEN),
+        sized(SessionToken::LEN),
+        sized(ScoreHistory::space(HISTORY_CAPACITY)),
+        sized(Airdrop::LEN),
+        sized(AirdropClaims::space(64)),
+        sized(ScoreTree::LEN),
+        sized(BanList::space(4)),
+        sized(Report::LEN),
+        sized(AdminSet::LEN),
+        sized(Proposal::space(2, 8)),
+        sized(LootRequest::LEN),
+        sized(Inventory::space(2)),
+        sized(Catalog::space(2)),
+    ]
+}
+
+fn fuzz_account() -> impl Strategy<Value = FuzzAccount> {
+    let keys = known_keys();
+    (
+        prop_oneof![
+            proptest::sample::select(keys).boxed(),
+            any::<[u8; 32]>().prop_map(Pubkey::new_from_array).boxed(),
+        ],
+        any::<bool>(),
+        any::<bool>(),
+        any::<bool>(),
+        0..u32::MAX as u64,
+        account_data(),
+    )
+        .prop_map(
+            |(key, is_signer, is_writable, owned_by_program, lamports, data)| FuzzAccount {
+                key,
+                is_signer,
+                is_writable,
+                owned_by_program,
+                lamports,
+                data,
+            },
+        )
+}
+
+// Run one instruction; any result is fine as long as the program does not panic
+fn run(accounts: &mut [FuzzAccount], instruction_data: &[u8]) {
+    set_syscall_stubs(Box::new(ClockStubs));
+    let system = system_program::id();
+    let infos: Vec<AccountInfo> = accounts
+        .iter_mut()
+        .map(|account| {
+            let owner = if account.owned_by_program {
+                &PROGRAM_ID
+            } else {
+                &system
+            };
+            AccountInfo::new(
+                &account.key,
+                account.is_signer,
+                account.is_writable,
+                &mut account.lamports,
+                &mut account.data,
+                owner,
+                false,
+                0,
+            )
+        })
+        .collect();
+    let _ = process_instruction(&PROGRAM_ID, &infos, instruction_data);
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(256))]
+
+    #[test]
+    fn random_instruction_data_never_panics(
+        data in vec(any::<u8>(), 0..64),
+        mut accounts in vec(fuzz_account(), 0..6),
+    ) {
+        run(&mut accounts, &data);
+    }
+
+    #[test]
+    fn known_tags_with_random_accounts_never_panic(
+        tag in 0..=MAX_TAG,
+        payload in vec(any::<u8>(), 0..48),
+        mut accounts in vec(fuzz_account(), 0..6),
+    ) {
+        let mut data = vec![tag];
+        data.extend_from_slice(&payload);
+        run(&mut accounts, &data);
+    }
+}
@@ -0,0 +1,38 @@
+use score_program::{error::GarbageGrabError, instruction::GarbageGrabInstruction};
+use serde_json::Value;
+
+// The IDL Shank generated from the crate, as shipped to clients
+fn idl() -> Value {
+    serde_json::from_str(include_str!("../idl/score_program.json")).unwrap()
+}
+
+#[test]
+fn idl_instructions_follow_the_enum() {
+    let idl = idl();
+    let instructions = idl["instructions"].as_array().unwrap();
+    for (index, instruction) in instructions.iter().enumerate() {
+        let name = instruction["name"].as_str().unwrap();
+        assert_eq!(instruction["discriminant"]["value"], index, "{}", name);
+
+        // Instructions without arguments decode from their discriminant alone
+        if instruction["args"].as_array().unwrap().is_empty() {
+            let decoded = GarbageGrabInstruction::unpack(&[index as u8]).unwrap();
+            assert_eq!(format!("{:?}", decoded), name);
+        }
+    }
+    // and no instruction is missing from the end
+    assert!(GarbageGrabInstruction::unpack(&[instructions.len() as u8]).is_err());
+}
+
+#[test]
+fn idl_errors_carry_the_program_codes() {
+    let idl = idl();
+    let errors = idl["errors"].as_array().unwrap();
+    for (code, error) in errors.iter().enumerate() {
+        assert_eq!(error["code"], code);
+    }
+    let last = errors.last().unwrap();
+    assert_eq!(last["code"], GarbageGrabError::SeasonEnded as u32);
+    assert_eq!(last["name"], "SeasonEnded");
+    assert_eq!(last["msg"], GarbageGrabError::SeasonEnded.to_string());
+}
@@ -0,0 +1,6769 @@
+use borsh::BorshDeserialize;
+use mpl_token_metadata::accounts::{MasterEdition, Metadata};
+use pyth_sdk_solana::state::{
+    AccountType, PriceInfo, PriceStatus, SolanaPriceAccount, MAGIC, VERSION_2,
+};
+use score_program::{
+    attestation::score_attestation_message,
+    cpi,
+    error::GarbageGrabError,
+    instruction::{ConfigUpdate, GarbageGrabInstruction},
+    merkle,
+    pda::{
+        BadgeAuthorityPda, BadgeCatalogPda, BetPda, BetPoolPda, ConfigPda, CosmeticPda,
+        DailyChallengePda, DailySubmissionPda, FollowPda, GameSessionPda, GlobalStatsPda,
+        LeaderboardPda, LobbyPda, MatchPda, QuestPda, QuestProgressPda, RegionalLeaderboardPda,
+        ScorePda, StakePda, StakeVaultPda, VestingPda, WeeklyLeaderboardPda,
+    },
+    process_instruction,
+    state::{
+        normalize_profile_name, pad_profile_name, score_bucket, week_of, Airdrop, BadgeCatalog,
+        BadgeMilestone, BanList, Bet, BetPool, CompressedScore, Config, Cosmetic, DailyChallenge,
+        Follow,
+        GameMode, GameSession, GlobalStats, Guild, Inventory, ItemDefinition, ItemEffect,
+        ItemStack, Leaderboard, LeaderboardEntry, Lobby, Match, PlayerProfile, ProposalAccount,
+        QuestMetric, QuestProgress, ScoreAccount, ScoreHistory, ScoreTree, Season, ShopCurrency,
+        Stake, StakeLock, Team, Tournament, Vesting, WeeklyLeaderboard, DAILY_XP_CAP,
+        COSMETIC_COUNT, HISTOGRAM_BUCKETS, HISTORY_CAPACITY, LEADERBOARD_SIZE, LOOT_PRICE_LAMPORTS, NO_ACHIEVEMENT,
+        REPORT_BOND_LAMPORTS, SECONDS_PER_DAY,
+    },
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    program_option::COption,
+    program_pack::Pack,
+    ed25519_program,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program, sysvar,
+    transaction::{Transaction, TransactionError},
+};
+use spl_account_compression::{state::CONCURRENT_MERKLE_TREE_HEADER_SIZE_V1, ConcurrentMerkleTree};
+use spl_associated_token_account::{
+    get_associated_token_address, get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account,
+};
+use spl_concurrent_merkle_tree::{hash::recompute, node::empty_node};
+use spl_token::state::{Account as TokenAccount, AccountState, Mint};
+use spl_token_2022::extension::{
+    transfer_fee::instruction::initialize_transfer_fee_config, ExtensionType, StateWithExtensions,
+};
+use std::mem::size_of;
+use switchboard_on_demand::{
+    Discriminator, RandomnessAccountData, SWITCHBOARD_ON_DEMAND_PROGRAM_ID,
+};
+
+const MAX_SCORE: u64 = 1_000_000;
+
+// Everything a test needs to drive the program through a BanksClient
+struct TestEnv {
+    context: ProgramTestContext,
+    program_id: Pubkey,
+    admin: Keypair,
+    player: Keypair,
+}
+
+fn score_address(program_id: &Pubkey, player: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"score", player.as_ref(), &GameMode::Arcade.seed()],
+        program_id,
+    )
+    .0
+}
+
+// Bump a score account in the current layout stores for its address
+fn score_bump(program_id: &Pubkey, player: &Pubkey) -> u8 {
+    ScorePda::find(program_id, player, GameMode::Arcade).1
+}
+
+fn config_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"config"], program_id).0
+}
+
+fn season_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"season"], program_id).0
+}
+
+fn leaderboard_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"leaderboard"], program_id).0
+}
+
+fn ban_list_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"ban_list"], program_id).0
+}
+
+fn prize_pool_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"prize_pool"], program_id).0
+}
+
+fn fee_vault_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"fee_vault"], program_id).0
+}
+
+fn vault_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"vault"], program_id).0
+}
+
+fn program_account(program_id: &Pubkey, data: Vec<u8>) -> Account {
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: *program_id,
+        ..Account::default()
+    }
+}
+
+fn config_account(program_id: &Pubkey, config: &Config) -> Account {
+    let mut data = vec![0u8; Config::LEN];
+    config.pack(&mut data).unwrap();
+    program_account(program_id, data)
+}
+
+fn leaderboard_account(program_id: &Pubkey, entries: Vec<LeaderboardEntry>) -> Account {
+    let mut data = vec![0u8; Leaderboard::LEN];
+    let board = Leaderboard::init(&mut data).unwrap();
+    for entry in entries {
+        board.submit(entry.player, entry.score());
+    }
+    program_account(program_id, data)
+}
+
+// Start a test validator with a funded player and a config account already in
+// place. InitializeConfig needs an upgradeable program, which builtins are not.
+// The compression program's Anchor entrypoint wants account infos that live as
+// long as their data, so hand it a leaked copy
+fn compression_entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    spl_account_compression::entry(program_id, accounts, data)
+}
+
+// A partner program that gates on a score by CPI. It takes the score program,
+// score account, player, config and season, with the threshold as little-endian
+// instruction data.
+const GATE_PROGRAM_ID: Pubkey = Pubkey::new_from_array([7; 32]);
+
+fn gate_entry(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let threshold = u64::from_le_bytes(data.try_into().unwrap());
+    let [score_program, score_account, player, config, season] = accounts else {
+        panic!("gate takes five accounts");
+    };
+    let score =
+        cpi::verify_score_at_least(score_program, score_account, player, config, season, threshold)?;
+    assert!(score >= threshold);
+    Ok(())
+}
+
+// A partner program that trusts a compressed score once the score program has
+// verified it. It takes the score program, score tree, merkle tree, compression
+// program and proof nodes, with the root and Borsh-encoded leaf as data.
+const COMPRESSED_GATE_PROGRAM_ID: Pubkey = Pubkey::new_from_array([8; 32]);
+
+fn compressed_gate_entry(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let (root, leaf) = data.split_at(32);
+    let leaf = CompressedScore::try_from_slice(leaf).unwrap();
+    let [score_program, score_tree, merkle_tree, compression_program, proof @ ..] = accounts else {
+        panic!("compressed gate takes four accounts and the proof");
+    };
+    let score = cpi::verify_compressed_score(
+        score_program,
+        score_tree,
+        merkle_tree,
+        compression_program,
+        proof,
+        root.try_into().unwrap(),
+        leaf,
+    )?;
+    assert_eq!(score, leaf.score);
+    Ok(())
+}
+
+async fn setup(existing_score: Option<u64>) -> TestEnv {
+    let program_id = Pubkey::new_unique();
+    let admin = Keypair::new();
+    let player = Keypair::new();
+    let mut program_test =
+        ProgramTest::new("score_program", program_id, processor!(process_instruction));
+
+    let config = Config {
+        is_initialized: true,
+        admin: admin.pubkey(),
+        max_score: MAX_SCORE,
+        max_increment: u64::MAX,
+        bump: ConfigPda::find(&program_id).1,
+        ..Config::default()
+    };
+    program_test.add_account(
+        config_address(&program_id),
+        config_account(&program_id, &config),
+    );
+
+    program_test.add_program(
+        "spl_account_compression",
+        spl_account_compression::id(),
+        processor!(compression_entry),
+    );
+    program_test.add_program("spl_noop", spl_noop::id(), processor!(spl_noop::noop));
+    program_test.add_program("score_gate", GATE_PROGRAM_ID, processor!(gate_entry));
+    program_test.add_program(
+        "compressed_score_gate",
+        COMPRESSED_GATE_PROGRAM_ID,
+        processor!(compressed_gate_entry),
+    );
+
+    for key in [admin.pubkey(), player.pubkey()] {
+        program_test.add_account(key, Account::new(1_000_000_000, 0, &system_program::id()));
+    }
+
+    if let Some(score) = existing_score {
+        let mut data = vec![0u8; ScoreAccount::LEN];
+        ScoreAccount {
+            is_initialized: true,
+            player: player.pubkey(),
+            score,
+            best_score: score,
+            version: ScoreAccount::VERSION,
+            bump: score_bump(&program_id, &player.pubkey()),
+            ..ScoreAccount::default()
+        }
+        .pack(&mut data)
+        .unwrap();
+        program_test.add_account(
+            score_address(&program_id, &player.pubkey()),
+            program_account(&program_id, data),
+        );
+    }
+
+    TestEnv {
+        context: program_test.start_with_context().await,
+        program_id,
+        admin,
+        player,
+    }
+}
+
+impl TestEnv {
+    async fn process(
+        &mut self,
+        instruction: Instruction,
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        // A fresh blockhash keeps repeated identical transactions from being deduplicated
+        let blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+        let payer = &self.context.payer;
+        let mut all_signers = vec![payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+        self.context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    fn initialize(&self) -> Instruction {
+        Instruction::new_with_bytes(
+            self.program_id,
+            &GarbageGrabInstruction::Initialize {
+                game_mode: GameMode::Arcade,
+                referrer: None,
+                allowlist_proof: Vec::new(),
+            }
+            .pack(),
+            vec![
+                AccountMeta::new(
+                    score_address(&self.program_id, &self.player.pubkey()),
+                    false,
+                ),
+                AccountMeta::new(self.player.pubkey(), true),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(config_address(&self.program_id), false),
+            ],
+        )
+    }
+
+    fn update_score(
+        &self,
+        score_account: Pubkey,
+        player_signs: bool,
+        increment: u64,
+    ) -> Instruction {
+        let player = self.player.pubkey();
+        Instruction::new_with_bytes(
+            self.program_id,
+            &GarbageGrabInstruction::UpdateScore {
+                game_mode: GameMode::Arcade,
+                increment,
+            }
+            .pack(),
+            vec![
+                AccountMeta::new(score_account, false),
+                AccountMeta::new_readonly(player, player_signs),
+                AccountMeta::new_readonly(season_address(&self.program_id), false),
+                AccountMeta::new_readonly(config_address(&self.program_id), false),
+                AccountMeta::new_readonly(ban_list_address(&self.program_id), false),
+            ],
+        )
+    }
+
+    // The config `setup` installs, for tests that only change a few fields
+    fn config(&self) -> Config {
+        Config {
+            is_initialized: true,
+            admin: self.admin.pubkey(),
+            max_score: MAX_SCORE,
+            max_increment: u64::MAX,
+            bump: ConfigPda::find(&self.program_id).1,
+            ..Config::default()
+        }
+    }
+
+    fn set_config(&mut self, config: Config) {
+        let account = config_account(&self.program_id, &config);
+        self.context
+            .set_account(&config_address(&self.program_id), &account.into());
+    }
+
+    async fn balance(&mut self, address: Pubkey) -> u64 {
+        self.context
+            .banks_client
+            .get_balance(address)
+            .await
+            .unwrap()
+    }
+
+    async fn score(&mut self) -> ScoreAccount {
+        let address = score_address(&self.program_id, &self.player.pubkey());
+        let account = self
+            .context
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+            .expect("score account exists");
+        ScoreAccount::unpack(&account.data).unwrap()
+    }
+}
+
+// An SPL token account holding `amount` of `mint`
+fn token_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Account {
+    let mut data = vec![0u8; TokenAccount::LEN];
+    TokenAccount {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        state: AccountState::Initialized,
+        ..TokenAccount::default()
+    }
+    .pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::id(),
+        ..Account::default()
+    }
+}
+
+// An SPL mint with `supply` tokens and no decimals
+fn mint_account(supply: u64) -> Account {
+    let mut data = vec![0u8; Mint::LEN];
+    Mint {
+        mint_authority: COption::None,
+        supply,
+        decimals: 0,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::id(),
+        ..Account::default()
+    }
+}
+
+// Balance of an SPL Token or Token-2022 account
+async fn token_balance(env: &mut TestEnv, address: Pubkey) -> u64 {
+    let account = env
+        .context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .expect("token account exists");
+    StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data)
+        .unwrap()
+        .base
+        .amount
+}
+
+fn instruction_error(result: Result<(), BanksClientError>) -> InstructionError {
+    match result.unwrap_err().unwrap() {
+        TransactionError::InstructionError(0, error) => error,
+        other => panic!("unexpected transaction error: {:?}", other),
+    }
+}
+
+fn custom(error: GarbageGrabError) -> InstructionError {
+    InstructionError::Custom(error as u32)
+}
+
+#[tokio::test]
+async fn initialize_creates_empty_score_account() {
+    let mut env = setup(None).await;
+    let player = env.player.insecure_clone();
+    env.process(env.initialize(), &[&player]).await.unwrap();
+
+    let score = env.score().await;
+    assert!(score.is_initialized);
+    assert_eq!(score.player, player.pubkey());
+    assert_eq!((score.score, score.version), (0, ScoreAccount::VERSION));
+    assert_eq!(score.bump, score_bump(&env.program_id, &player.pubkey()));
+
+    // A second initialize must not wipe the account
+    let result = env.process(env.initialize(), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::AlreadyInitialized)
+    );
+}
+
+#[tokio::test]
+async fn empty_instruction_data_is_rejected() {
+    let mut env = setup(None).await;
+    let instruction = Instruction::new_with_bytes(env.program_id, &[], vec![]);
+    let result = env.process(instruction, &[]).await;
+    assert_eq!(
+        instruction_error(result),
+        InstructionError::InvalidInstructionData
+    );
+}
+
+#[tokio::test]
+async fn update_adds_increment() {
+    let mut env = setup(Some(10)).await;
+    let player = env.player.insecure_clone();
+    let address = score_address(&env.program_id, &player.pubkey());
+    env.process(env.update_score(address, true, 15), &[&player])
+        .await
+        .unwrap();
+
+    let score = env.score().await;
+    assert_eq!((score.score, score.best_score), (25, 25));
+}
+
+#[tokio::test]
+async fn update_rejects_overflow_and_max_score() {
+    let mut env = setup(Some(MAX_SCORE)).await;
+    let player = env.player.insecure_clone();
+    let address = score_address(&env.program_id, &player.pubkey());
+
+    let result = env
+        .process(env.update_score(address, true, u64::MAX), &[&player])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::ScoreOverflow)
+    );
+
+    let result = env
+        .process(env.update_score(address, true, 1), &[&player])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::ScoreTooLarge)
+    );
+    assert_eq!(env.score().await.score, MAX_SCORE);
+}
+
+#[tokio::test]
+async fn update_rejects_increments_over_the_cap() {
+    let mut env = setup(Some(950)).await;
+    let player = env.player.insecure_clone();
+    let address = score_address(&env.program_id, &player.pubkey());
+    env.set_config(Config {
+        max_score: 1_000,
+        max_increment: 40,
+        ..env.config()
+    });
+
+    let result = env
+        .process(env.update_score(address, true, 41), &[&player])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::IncrementTooLarge)
+    );
+    env.process(env.update_score(address, true, 40), &[&player])
+        .await
+        .unwrap();
+
+    // Within the cap, the sum is still checked against the maximum score
+    let result = env
+        .process(env.update_score(address, true, 11), &[&player])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::ScoreTooLarge)
+    );
+    assert_eq!(env.score().await.score, 990);
+}
+
+#[tokio::test]
+async fn updates_wait_out_the_cooldown() {
+    let mut env = setup(Some(10)).await;
+    let player = env.player.insecure_clone();
+    let address = score_address(&env.program_id, &player.pubkey());
+    env.set_config(Config {
+        update_cooldown_secs: 60,
+        ..env.config()
+    });
+
+    env.process(env.update_score(address, true, 5), &[&player])
+        .await
+        .unwrap();
+    let result = env
+        .process(env.update_score(address, true, 5), &[&player])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::UpdateTooSoon)
+    );
+    assert_eq!(env.score().await.score, 15);
+
+    let mut clock: Clock = env.context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp = env.score().await.last_update_ts + 60;
+    env.context.set_sysvar(&clock);
+    env.process(env.update_score(address, true, 5), &[&player])
+        .await
+        .unwrap();
+    assert_eq!(env.score().await.score, 20);
+}
+
+#[tokio::test]
+async fn ended_season_takes_no_more_scores() {
+    let mut env = setup(Some(10)).await;
+    let player = env.player.insecure_clone();
+    let program_id = env.program_id;
+    let address = score_address(&program_id, &player.pubkey());
+    let season = |season_id: u64, end_ts: i64| {
+        let mut data = vec![0u8; Season::LEN];
+        Season {
+            is_initialized: true,
+            season_id,
+            start_ts: 0,
+            end_ts,
+            prizes_distributed: false,
+        }
+        .pack(&mut data)
+        .unwrap();
+        program_account(&program_id, data)
+    };
+
+    env.context
+        .set_account(&season_address(&program_id), &season(0, 1).into());
+    let result = env
+        .process(env.update_score(address, true, 15), &[&player])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::SeasonEnded)
+    );
+    assert_eq!(env.score().await.score, 10);
+
+    // The next season starts the score over
+    env.context.set_account(
+        &season_address(&program_id),
+        &season(1, i64::MAX).into(),
+    );
+    env.process(env.update_score(address, true, 15), &[&player])
+        .await
+        .unwrap();
+    assert_eq!(env.score().await.score, 15);
+}
+
+#[tokio::test]
+async fn required_attestation_refuses_player_updates() {
+    let mut env = setup(Some(10)).await;
+    let player = env.player.insecure_clone();
+    let address = score_address(&env.program_id, &player.pubkey());
+    let config = Config {
+        game_server: Keypair::new().pubkey(),
+        ..env.config()
+    };
+    env.set_config(Config {
+        require_attestation: true,
+        ..config
+    });
+    let result = env
+        .process(env.update_score(address, true, 15), &[&player])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::InvalidAttestation)
+    );
+    assert_eq!(env.score().await.score, 10);
+
+    env.set_config(config);
+    env.process(env.update_score(address, true, 15), &[&player])
+        .await
+        .unwrap();
+    assert_eq!(env.score().await.score, 25);
+}
+
+// An ed25519 precompile instruction verifying `signer`'s signature over
+// `message`, with every offset pointing into its own data
+fn ed25519_verify(signer: &Keypair, message: &[u8]) -> Instruction {
+    const HEADER_LEN: u16 = 16;
+    let signature = signer.sign_message(message);
+    let (pubkey_offset, signature_offset) = (HEADER_LEN, HEADER_LEN + 32);
+    let message_offset = signature_offset + 64;
+    let mut data = vec![1, 0];
+    for field in [
+        signature_offset,
+        u16::MAX,
+        pubkey_offset,
+        u16::MAX,
+        message_offset,
+        message.len() as u16,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&field.to_le_bytes());
+    }
+    data.extend_from_slice(signer.pubkey().as_ref());
+    data.extend_from_slice(signature.as_ref());
+    data.extend_from_slice(message);
+    Instruction::new_with_bytes(ed25519_program::id(), &data, vec![])
+}
+
+#[tokio::test]
+async fn attested_scores_consume_their_nonce() {
+    let mut env = setup(Some(10)).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let server = Keypair::new();
+    env.set_config(Config {
+        game_server: server.pubkey(),
+        ..env.config()
+    });
+
+    // The server's signature travels in the instruction right before the submission
+    let attested = |increment: u64, nonce: u64| {
+        let message =
+            score_attestation_message(&player.pubkey(), GameMode::Arcade, increment, nonce);
+        let submit = Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::SubmitAttestedScore {
+                game_mode: GameMode::Arcade,
+                increment,
+                nonce,
+            }
+            .pack(),
+            vec![
+                AccountMeta::new(score_address(&program_id, &player.pubkey()), false),
+                AccountMeta::new_readonly(player.pubkey(), true),
+                AccountMeta::new_readonly(season_address(&program_id), false),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(ban_list_address(&program_id), false),
+                AccountMeta::new_readonly(sysvar::instructions::id(), false),
+            ],
+        );
+        [ed25519_verify(&server, &message), submit]
+    };
+    async fn send(
+        env: &mut TestEnv,
+        instructions: &[Instruction],
+    ) -> Result<(), BanksClientError> {
+        let blockhash = env.context.get_new_latest_blockhash().await.unwrap();
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&env.context.payer.pubkey()),
+            &[&env.context.payer, &env.player],
+            blockhash,
+        );
+        env.context.banks_client.process_transaction(transaction).await
+    }
+    let invalid_nonce = TransactionError::InstructionError(
+        1,
+        custom(GarbageGrabError::InvalidNonce),
+    );
+
+    // A correctly signed payload still needs the account's next nonce
+    let result = send(&mut env, &attested(5, 1)).await;
+    assert_eq!(result.unwrap_err().unwrap(), invalid_nonce);
+
+    let first = attested(5, 0);
+    send(&mut env, &first).await.unwrap();
+    let state = env.score().await;
+    assert_eq!((state.score, state.nonce), (15, 1));
+
+    // Sending the same signed payload again finds its nonce already used
+    let result = send(&mut env, &first).await;
+    assert_eq!(result.unwrap_err().unwrap(), invalid_nonce);
+    assert_eq!(env.score().await.score, 15);
+
+    send(&mut env, &attested(7, 1)).await.unwrap();
+    let state = env.score().await;
+    assert_eq!((state.score, state.nonce), (22, 2));
+}
+
+#[tokio::test]
+async fn approved_delegate_can_update_score() {
+    let mut env = setup(Some(10)).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let delegate = Keypair::new();
+    let address = score_address(&program_id, &player.pubkey());
+    let set_delegate = |ix: GarbageGrabInstruction| {
+        Instruction::new_with_bytes(
+            program_id,
+            &ix.pack(),
+            vec![
+                AccountMeta::new(address, false),
+                AccountMeta::new_readonly(player.pubkey(), true),
+            ],
+        )
+    };
+    let delegated_update = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::UpdateScoreAsDelegate {
+            game_mode: GameMode::Arcade,
+            increment: 5,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(address, false),
+            AccountMeta::new_readonly(player.pubkey(), false),
+            AccountMeta::new_readonly(delegate.pubkey(), true),
+            AccountMeta::new_readonly(season_address(&program_id), false),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(ban_list_address(&program_id), false),
+        ],
+    );
+
+    let result = env.process(delegated_update.clone(), &[&delegate]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::NotDelegate)
+    );
+
+    let approve = GarbageGrabInstruction::ApproveDelegate {
+        game_mode: GameMode::Arcade,
+        delegate: delegate.pubkey(),
+    };
+    env.process(set_delegate(approve), &[&player])
+        .await
+        .unwrap();
+    env.process(delegated_update.clone(), &[&delegate])
+        .await
+        .unwrap();
+    assert_eq!(env.score().await.score, 15);
+
+    let revoke = GarbageGrabInstruction::RevokeDelegate {
+        game_mode: GameMode::Arcade,
+    };
+    env.process(set_delegate(revoke), &[&player]).await.unwrap();
+    let result = env.process(delegated_update, &[&delegate]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::NotDelegate)
+    );
+}
+
+#[tokio::test]
+async fn session_key_updates_score_until_expiry() {
+    let mut env = setup(Some(10)).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let session_key = Keypair::new();
+    let session = Pubkey::find_program_address(
+        &[
+            b"session",
+            player.pubkey().as_ref(),
+            session_key.pubkey().as_ref(),
+        ],
+        &program_id,
+    )
+    .0;
+    let slot = env.context.banks_client.get_root_slot().await.unwrap();
+    let expiry_slot = slot + 100;
+    let create = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::CreateSession {
+            session_key: session_key.pubkey(),
+            expiry_slot,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(session, false),
+            AccountMeta::new(player.pubkey(), true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    env.process(create, &[&player]).await.unwrap();
+
+    let update = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::UpdateScoreWithSession {
+            game_mode: GameMode::Arcade,
+            increment: 5,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(score_address(&program_id, &player.pubkey()), false),
+            AccountMeta::new_readonly(player.pubkey(), false),
+            AccountMeta::new_readonly(session_key.pubkey(), true),
+            AccountMeta::new_readonly(session, false),
+            AccountMeta::new_readonly(season_address(&program_id), false),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(ban_list_address(&program_id), false),
+        ],
+    );
+    env.process(update.clone(), &[&session_key]).await.unwrap();
+    assert_eq!(env.score().await.score, 15);
+
+    env.context.warp_to_slot(expiry_slot).unwrap();
+    let result = env.process(update, &[&session_key]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::SessionExpired)
+    );
+    assert_eq!(env.score().await.score, 15);
+}
+
+#[tokio::test]
+async fn submitted_runs_are_kept_in_history() {
+    let mut env = setup(Some(0)).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let history = Pubkey::find_program_address(
+        &[
+            b"history",
+            player.pubkey().as_ref(),
+            &GameMode::Arcade.seed(),
+        ],
+        &program_id,
+    )
+    .0;
+    let initialize = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::InitializeHistory {
+            game_mode: GameMode::Arcade,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(history, false),
+            AccountMeta::new(player.pubkey(), true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    env.process(initialize, &[&player]).await.unwrap();
+
+    for run_score in 1..=HISTORY_CAPACITY as u64 + 2 {
+        let submit = Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::SubmitRun {
+                game_mode: GameMode::Arcade,
+                run_score,
+                run_hash: [run_score as u8; 32],
+            }
+            .pack(),
+            vec![
+                AccountMeta::new(score_address(&program_id, &player.pubkey()), false),
+                AccountMeta::new_readonly(player.pubkey(), true),
+                AccountMeta::new_readonly(season_address(&program_id), false),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(ban_list_address(&program_id), false),
+                AccountMeta::new(history, false),
+            ],
+        );
+        env.process(submit, &[&player]).await.unwrap();
+    }
+
+    let account = env
+        .context
+        .banks_client
+        .get_account(history)
+        .await
+        .unwrap()
+        .unwrap();
+    let state = ScoreHistory::unpack(&account.data).unwrap();
+    let scores: Vec<u64> = state.runs.iter().map(|run| run.score).collect();
+    assert_eq!(
+        scores,
+        (3..=HISTORY_CAPACITY as u64 + 2).collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn replayed_runs_are_rejected() {
+    let mut env = setup(Some(0)).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let submit = |run_score: u64, run_hash: [u8; 32]| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::SubmitRun {
+                game_mode: GameMode::Arcade,
+                run_score,
+                run_hash,
+            }
+            .pack(),
+            vec![
+                AccountMeta::new(score_address(&program_id, &player.pubkey()), false),
+                AccountMeta::new_readonly(player.pubkey(), true),
+                AccountMeta::new_readonly(season_address(&program_id), false),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(ban_list_address(&program_id), false),
+            ],
+        )
+    };
+
+    env.process(submit(40, [9; 32]), &[&player]).await.unwrap();
+    // The same run sent again in a fresh transaction is refused
+    let result = env.process(submit(90, [9; 32]), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::DuplicateRun)
+    );
+    assert_eq!(env.score().await.best_score, 40);
+
+    env.process(submit(90, [10; 32]), &[&player]).await.unwrap();
+    let score = env.score().await;
+    assert_eq!(score.best_score, 90);
+    assert!(score
+        .recent_run_hashes
+        .contains(&Hash::new_from_array([9; 32])));
+    assert!(score
+        .recent_run_hashes
+        .contains(&Hash::new_from_array([10; 32])));
+}
+
+#[tokio::test]
+async fn runs_share_the_update_limits() {
+    let mut env = setup(Some(0)).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    env.set_config(Config {
+        max_increment: 100,
+        update_cooldown_secs: 60,
+        ..env.config()
+    });
+    let submit = |run_score: u64, run_hash: [u8; 32]| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::SubmitRun {
+                game_mode: GameMode::Arcade,
+                run_score,
+                run_hash,
+            }
+            .pack(),
+            vec![
+                AccountMeta::new(score_address(&program_id, &player.pubkey()), false),
+                AccountMeta::new_readonly(player.pubkey(), true),
+                AccountMeta::new_readonly(season_address(&program_id), false),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(ban_list_address(&program_id), false),
+            ],
+        )
+    };
+
+    // A run cannot jump past what one update may add
+    let result = env.process(submit(MAX_SCORE, [1; 32]), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::IncrementTooLarge)
+    );
+    env.process(submit(100, [2; 32]), &[&player]).await.unwrap();
+    let result = env.process(submit(90, [3; 32]), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::UpdateTooSoon)
+    );
+    assert_eq!(env.score().await.best_score, 100);
+}
+
+#[tokio::test]
+async fn realloc_grows_own_score_account() {
+    let mut env = setup(Some(10)).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let admin = env.admin.insecure_clone();
+    let address = score_address(&program_id, &player.pubkey());
+    let new_len = (ScoreAccount::LEN + 64) as u32;
+    let realloc = |signer: Pubkey| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::Realloc { new_len }.pack(),
+            vec![
+                AccountMeta::new(address, false),
+                AccountMeta::new(signer, true),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        )
+    };
+
+    let result = env.process(realloc(admin.pubkey()), &[&admin]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::Unauthorized)
+    );
+    env.process(realloc(player.pubkey()), &[&player])
+        .await
+        .unwrap();
+    let account = env
+        .context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), new_len as usize);
+
+    // The grown account keeps working with the current layout
+    env.process(env.update_score(address, true, 5), &[&player])
+        .await
+        .unwrap();
+    assert_eq!(env.score().await.score, 15);
+}
+
+#[tokio::test]
+async fn banned_player_cannot_update_score() {
+    let mut env = setup(Some(10)).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let admin = env.admin.insecure_clone();
+    let address = score_address(&program_id, &player.pubkey());
+    let ban = |signer: Pubkey, ban: bool| {
+        let data = if ban {
+            GarbageGrabInstruction::Ban {
+                player: player.pubkey(),
+            }
+        } else {
+            GarbageGrabInstruction::Unban {
+                player: player.pubkey(),
+            }
+        };
+        let mut accounts = vec![
+            AccountMeta::new(ban_list_address(&program_id), false),
+            AccountMeta::new(signer, true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+        ];
+        if ban {
+            accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+        }
+        Instruction::new_with_bytes(program_id, &data.pack(), accounts)
+    };
+
+    let result = env.process(ban(player.pubkey(), true), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::Unauthorized)
+    );
+    env.process(ban(admin.pubkey(), true), &[&admin])
+        .await
+        .unwrap();
+    let result = env.process(ban(admin.pubkey(), true), &[&admin]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::PlayerBanned)
+    );
+    let result = env
+        .process(env.update_score(address, true, 5), &[&player])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::PlayerBanned)
+    );
+    let submit_run = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::SubmitRun {
+            game_mode: GameMode::Arcade,
+            run_score: 50,
+            run_hash: [1; 32],
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(address, false),
+            AccountMeta::new_readonly(player.pubkey(), true),
+            AccountMeta::new_readonly(season_address(&program_id), false),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(ban_list_address(&program_id), false),
+        ],
+    );
+    let result = env.process(submit_run, &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::PlayerBanned)
+    );
+
+    env.process(ban(admin.pubkey(), false), &[&admin])
+        .await
+        .unwrap();
+    let list = env
+        .context
+        .banks_client
+        .get_account(ban_list_address(&program_id))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(list.data.len(), BanList::space(0));
+    env.process(env.update_score(address, true, 5), &[&player])
+        .await
+        .unwrap();
+    assert_eq!(env.score().await.score, 15);
+}
+
+#[tokio::test]
+async fn bans_reach_delegate_and_session_updates() {
+    let mut env = setup(Some(10)).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let admin = env.admin.insecure_clone();
+    let delegate = Keypair::new();
+    let session_key = Keypair::new();
+    let address = score_address(&program_id, &player.pubkey());
+    let session = Pubkey::find_program_address(
+        &[
+            b"session",
+            player.pubkey().as_ref(),
+            session_key.pubkey().as_ref(),
+        ],
+        &program_id,
+    )
+    .0;
+
+    // The player hands out a delegate and a session key, then gets banned
+    let approve = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::ApproveDelegate {
+            game_mode: GameMode::Arcade,
+            delegate: delegate.pubkey(),
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(address, false),
+            AccountMeta::new_readonly(player.pubkey(), true),
+        ],
+    );
+    let slot = env.context.banks_client.get_root_slot().await.unwrap();
+    let create_session = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::CreateSession {
+            session_key: session_key.pubkey(),
+            expiry_slot: slot + 100,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(session, false),
+            AccountMeta::new(player.pubkey(), true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    let ban = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::Ban {
+            player: player.pubkey(),
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(ban_list_address(&program_id), false),
+            AccountMeta::new(admin.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    env.process(approve, &[&player]).await.unwrap();
+    env.process(create_session, &[&player]).await.unwrap();
+    env.process(ban, &[&admin]).await.unwrap();
+
+    let delegated_update = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::UpdateScoreAsDelegate {
+            game_mode: GameMode::Arcade,
+            increment: 5,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(address, false),
+            AccountMeta::new_readonly(player.pubkey(), false),
+            AccountMeta::new_readonly(delegate.pubkey(), true),
+            AccountMeta::new_readonly(season_address(&program_id), false),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(ban_list_address(&program_id), false),
+        ],
+    );
+    let result = env.process(delegated_update, &[&delegate]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::PlayerBanned)
+    );
+
+    let session_update = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::UpdateScoreWithSession {
+            game_mode: GameMode::Arcade,
+            increment: 5,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(address, false),
+            AccountMeta::new_readonly(player.pubkey(), false),
+            AccountMeta::new_readonly(session_key.pubkey(), true),
+            AccountMeta::new_readonly(session, false),
+            AccountMeta::new_readonly(season_address(&program_id), false),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(ban_list_address(&program_id), false),
+        ],
+    );
+    let result = env.process(session_update, &[&session_key]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::PlayerBanned)
+    );
+    assert_eq!(env.score().await.score, 10);
+}
+
+#[tokio::test]
+async fn reports_slash_or_forfeit_the_bond() {
+    let mut env = setup(Some(1_000)).await;
+    let program_id = env.program_id;
+    let player = env.player.pubkey();
+    let admin = env.admin.insecure_clone();
+    let reporter = Keypair::new();
+    let funded = Account::new(1_000_000_000, 0, &system_program::id());
+    env.context.set_account(&reporter.pubkey(), &funded.into());
+    let score = score_address(&program_id, &player);
+    let report = Pubkey::find_program_address(
+        &[
+            b"report",
+            player.as_ref(),
+            reporter.pubkey().as_ref(),
+            &GameMode::Arcade.seed(),
+        ],
+        &program_id,
+    )
+    .0;
+    let report_player = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::ReportPlayer {
+            game_mode: GameMode::Arcade,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(report, false),
+            AccountMeta::new(reporter.pubkey(), true),
+            AccountMeta::new_readonly(player, false),
+            AccountMeta::new_readonly(score, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    let resolve = |upheld: bool| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::ResolveReport { upheld, slash: 400 }.pack(),
+            vec![
+                AccountMeta::new(report, false),
+                AccountMeta::new_readonly(admin.pubkey(), true),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new(score, false),
+                AccountMeta::new(reporter.pubkey(), false),
+                AccountMeta::new(prize_pool_address(&program_id), false),
+            ],
+        )
+    };
+
+    // A rejected report sends the bond to the prize pool
+    env.process(report_player.clone(), &[&reporter])
+        .await
+        .unwrap();
+    let result = env.process(report_player.clone(), &[&reporter]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::AlreadyInitialized)
+    );
+    env.process(resolve(false), &[&admin]).await.unwrap();
+    assert_eq!(
+        env.balance(prize_pool_address(&program_id)).await,
+        REPORT_BOND_LAMPORTS
+    );
+    assert_eq!(env.score().await.score, 1_000);
+
+    // An upheld one slashes the score and returns the bond
+    let before = env.balance(reporter.pubkey()).await;
+    env.process(report_player, &[&reporter]).await.unwrap();
+    env.process(resolve(true), &[&admin]).await.unwrap();
+    assert_eq!(env.score().await.score, 600);
+    assert_eq!(env.balance(report).await, 0);
+    assert_eq!(env.balance(reporter.pubkey()).await, before);
+}
+
+// A Switchboard randomness account committed in `seed_slot`, revealed in
+// `reveal_slot` (0 while unrevealed) with a value whose loot draw is `draw`
+fn randomness_account(seed_slot: u64, reveal_slot: u64, draw: u64) -> Account {
+    let mut randomness: RandomnessAccountData = bytemuck::Zeroable::zeroed();
+    randomness.seed_slot = seed_slot;
+    randomness.reveal_slot = reveal_slot;
+    randomness.value[..8].copy_from_slice(&draw.to_le_bytes());
+    let mut data = RandomnessAccountData::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(bytemuck::bytes_of(&randomness));
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: SWITCHBOARD_ON_DEMAND_PROGRAM_ID,
+        ..Account::default()
+    }
+}
+
+#[tokio::test]
+async fn loot_drops_roll_on_revealed_randomness() {
+    let mut env = setup(None).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let randomness = Pubkey::new_unique();
+    let loot_request =
+        Pubkey::find_program_address(&[b"loot_request", player.pubkey().as_ref()], &program_id).0;
+    let inventory =
+        Pubkey::find_program_address(&[b"inventory", player.pubkey().as_ref()], &program_id).0;
+    let request_loot = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::RequestLoot.pack(),
+        vec![
+            AccountMeta::new(loot_request, false),
+            AccountMeta::new(inventory, false),
+            AccountMeta::new(player.pubkey(), true),
+            AccountMeta::new_readonly(randomness, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    let fulfill_loot = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::FulfillLoot.pack(),
+        vec![
+            AccountMeta::new(loot_request, false),
+            AccountMeta::new(inventory, false),
+            AccountMeta::new(player.pubkey(), false),
+            AccountMeta::new_readonly(randomness, false),
+            AccountMeta::new(prize_pool_address(&program_id), false),
+        ],
+    );
+
+    // Randomness committed in the previous slot is accepted once per player
+    env.context.warp_to_slot(100).unwrap();
+    let slot = 100;
+    env.context
+        .set_account(&randomness, &randomness_account(slot - 1, 0, 0).into());
+    env.process(request_loot.clone(), &[&player]).await.unwrap();
+    let result = env.process(request_loot, &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::AlreadyInitialized)
+    );
+
+    // Nothing rolls until the value is revealed in the current slot
+    let result = env.process(fulfill_loot.clone(), &[]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::RandomnessNotReady)
+    );
+
+    env.context.set_account(
+        &randomness,
+        &randomness_account(slot - 1, slot, 9_950).into(),
+    );
+    env.process(fulfill_loot, &[]).await.unwrap();
+    let data = env
+        .context
+        .banks_client
+        .get_account(inventory)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    assert_eq!(Inventory::unpack(&data).unwrap().loot, [0, 0, 0, 1]);
+    assert_eq!(
+        env.balance(prize_pool_address(&program_id)).await,
+        LOOT_PRICE_LAMPORTS
+    );
+    assert_eq!(env.balance(loot_request).await, 0);
+}
+
+#[tokio::test]
+async fn items_apply_their_effect_when_consumed() {
+    let mut env = setup(Some(0)).await;
+    let program_id = env.program_id;
+    let admin = env.admin.insecure_clone();
+    let player = env.player.insecure_clone();
+    let server = Keypair::new();
+    let funded = Account::new(1_000_000_000, 0, &system_program::id());
+    env.context.set_account(&server.pubkey(), &funded.into());
+    env.set_config(Config {
+        game_server: server.pubkey(),
+        ..env.config()
+    });
+    let catalog = Pubkey::find_program_address(&[b"catalog"], &program_id).0;
+    let inventory =
+        Pubkey::find_program_address(&[b"inventory", player.pubkey().as_ref()], &program_id).0;
+    let set_item = |item_id: u32, effect: ItemEffect, effect_bps: u16| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::SetCatalogItem {
+                definition: ItemDefinition {
+                    item_id,
+                    effect,
+                    effect_bps,
+                    ..ItemDefinition::default()
+                },
+            }
+            .pack(),
+            vec![
+                AccountMeta::new(catalog, false),
+                AccountMeta::new(admin.pubkey(), true),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        )
+    };
+    let grant = |authority: Pubkey, item_id: u32, quantity: u32| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::GrantItem { item_id, quantity }.pack(),
+            vec![
+                AccountMeta::new(inventory, false),
+                AccountMeta::new_readonly(player.pubkey(), false),
+                AccountMeta::new(authority, true),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(catalog, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        )
+    };
+    let consume = |item_id: u32| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::ConsumeItem {
+                game_mode: GameMode::Arcade,
+                item_id,
+            }
+            .pack(),
+            vec![
+                AccountMeta::new(inventory, false),
+                AccountMeta::new_readonly(player.pubkey(), true),
+                AccountMeta::new_readonly(catalog, false),
+                AccountMeta::new(score_address(&program_id, &player.pubkey()), false),
+            ],
+        )
+    };
+
+    env.process(set_item(1, ItemEffect::ScoreMultiplier, 20_000), &[&admin])
+        .await
+        .unwrap();
+    env.process(set_item(2, ItemEffect::Continue, 0), &[&admin])
+        .await
+        .unwrap();
+
+    // Only the game server grants, and only catalog items
+    let result = env.process(grant(player.pubkey(), 1, 2), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::Unauthorized)
+    );
+    let result = env.process(grant(server.pubkey(), 9, 1), &[&server]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::UnknownItem)
+    );
+    env.process(grant(server.pubkey(), 1, 2), &[&server])
+        .await
+        .unwrap();
+    env.process(grant(server.pubkey(), 2, 1), &[&server])
+        .await
+        .unwrap();
+
+    // A multiplier doubles the next update only, and only one can wait at a time
+    env.process(consume(1), &[&player]).await.unwrap();
+    let result = env.process(consume(1), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::ItemEffectActive)
+    );
+    let address = score_address(&program_id, &player.pubkey());
+    env.process(env.update_score(address, true, 5), &[&player])
+        .await
+        .unwrap();
+    env.process(env.update_score(address, true, 6), &[&player])
+        .await
+        .unwrap();
+    assert_eq!(env.score().await.score, 16);
+
+    env.process(consume(2), &[&player]).await.unwrap();
+    assert!(env.score().await.continue_pending);
+    let result = env.process(consume(2), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::ItemNotHeld)
+    );
+    let data = env
+        .context
+        .banks_client
+        .get_account(inventory)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    assert_eq!(
+        Inventory::unpack(&data).unwrap().items,
+        vec![ItemStack {
+            item_id: 1,
+            quantity: 1
+        }]
+    );
+}
+
+#[tokio::test]
+async fn shop_sells_items_for_sol_or_tokens() {
+    let mut env = setup(None).await;
+    let program_id = env.program_id;
+    let admin = env.admin.insecure_clone();
+    let player = env.player.insecure_clone();
+    let mint = Pubkey::new_unique();
+    env.context.set_account(&mint, &mint_account(1_000).into());
+    env.set_config(Config {
+        shop_mint: mint,
+        ..env.config()
+    });
+    let catalog = Pubkey::find_program_address(&[b"catalog"], &program_id).0;
+    let inventory =
+        Pubkey::find_program_address(&[b"inventory", player.pubkey().as_ref()], &program_id).0;
+    let treasury = Pubkey::find_program_address(&[b"treasury"], &program_id).0;
+    let player_tokens = get_associated_token_address(&player.pubkey(), &mint);
+    let treasury_tokens = get_associated_token_address(&treasury, &mint);
+    env.context.set_account(
+        &player_tokens,
+        &token_account(&mint, &player.pubkey(), 1_000).into(),
+    );
+    env.context
+        .set_account(&treasury_tokens, &token_account(&mint, &treasury, 0).into());
+
+    let set_item = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::SetCatalogItem {
+            definition: ItemDefinition {
+                item_id: 1,
+                effect: ItemEffect::None,
+                effect_bps: 0,
+                price_lamports: 1_000_000,
+                price_tokens: 50,
+            },
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(catalog, false),
+            AccountMeta::new(admin.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    env.process(set_item, &[&admin]).await.unwrap();
+    let purchase = |item_id: u32, quantity: u32, currency: ShopCurrency| {
+        let mut accounts = vec![
+            AccountMeta::new(inventory, false),
+            AccountMeta::new(player.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(catalog, false),
+            AccountMeta::new(treasury, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(fee_vault_address(&program_id), false),
+        ];
+        if currency == ShopCurrency::Token {
+            accounts.extend([
+                AccountMeta::new(player_tokens, false),
+                AccountMeta::new(treasury_tokens, false),
+                AccountMeta::new_readonly(mint, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+            ]);
+        }
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::PurchaseItem {
+                item_id,
+                quantity,
+                currency,
+            }
+            .pack(),
+            accounts,
+        )
+    };
+
+    env.process(purchase(1, 2, ShopCurrency::Sol), &[&player])
+        .await
+        .unwrap();
+    assert_eq!(env.balance(treasury).await, 2_000_000);
+    env.process(purchase(1, 3, ShopCurrency::Token), &[&player])
+        .await
+        .unwrap();
+    assert_eq!(token_balance(&mut env, player_tokens).await, 850);
+    assert_eq!(token_balance(&mut env, treasury_tokens).await, 150);
+    let data = env
+        .context
+        .banks_client
+        .get_account(inventory)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    assert_eq!(Inventory::unpack(&data).unwrap().quantity(1), 5);
+
+    // Items priced at zero in a currency cannot be bought with it
+    let free = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::SetCatalogItem {
+            definition: ItemDefinition {
+                item_id: 1,
+                price_lamports: 1_000_000,
+                ..ItemDefinition::default()
+            },
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(catalog, false),
+            AccountMeta::new(admin.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    env.process(free, &[&admin]).await.unwrap();
+    let result = env
+        .process(purchase(1, 1, ShopCurrency::Token), &[&player])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::ItemNotForSale)
+    );
+}
+
+#[tokio::test]
+async fn continues_burn_tokens_up_to_the_run_cap() {
+    let mut env = setup(Some(100)).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let mint = Pubkey::new_unique();
+    let mut mint_data = vec![0u8; Mint::LEN];
+    Mint {
+        mint_authority: COption::None,
+        supply: 1_000,
+        decimals: 0,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut mint_data);
+    let mint_account = Account {
+        lamports: 1_000_000_000,
+        data: mint_data,
+        owner: spl_token::id(),
+        ..Account::default()
+    };
+    env.context.set_account(&mint, &mint_account.into());
+    let player_tokens = get_associated_token_address(&player.pubkey(), &mint);
+    env.context.set_account(
+        &player_tokens,
+        &token_account(&mint, &player.pubkey(), 1_000).into(),
+    );
+    env.set_config(Config {
+        reward_mint: mint,
+        continue_burn_amount: 300,
+        max_continues_per_run: 2,
+        ..env.config()
+    });
+    let address = score_address(&program_id, &player.pubkey());
+    let buy_continue = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::Continue {
+            game_mode: GameMode::Arcade,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(address, false),
+            AccountMeta::new_readonly(player.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new(player_tokens, false),
+            AccountMeta::new(mint, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    );
+
+    env.process(buy_continue.clone(), &[&player]).await.unwrap();
+    env.process(buy_continue.clone(), &[&player]).await.unwrap();
+    let score = env.score().await;
+    assert_eq!((score.run_continues, score.continue_pending), (2, true));
+    assert_eq!(token_balance(&mut env, player_tokens).await, 400);
+    let result = env.process(buy_continue.clone(), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::ContinueLimitReached)
+    );
+
+    // Submitting the run ends it and frees up continues for the next one
+    let submit = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::SubmitRun {
+            game_mode: GameMode::Arcade,
+            run_score: 50,
+            run_hash: [1; 32],
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(address, false),
+            AccountMeta::new_readonly(player.pubkey(), true),
+            AccountMeta::new_readonly(season_address(&program_id), false),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(ban_list_address(&program_id), false),
+        ],
+    );
+    env.process(submit, &[&player]).await.unwrap();
+    assert!(!env.score().await.continue_pending);
+    env.process(buy_continue, &[&player]).await.unwrap();
+    assert_eq!(token_balance(&mut env, player_tokens).await, 100);
+}
+
+#[tokio::test]
+async fn admin_set_pauses_only_with_enough_approvals() {
+    let mut env = setup(None).await;
+    let program_id = env.program_id;
+    let admin = env.admin.insecure_clone();
+    let second = Keypair::new();
+    let outsider = Keypair::new();
+    let admin_set = Pubkey::find_program_address(&[b"admin_set"], &program_id).0;
+    let proposal = Pubkey::find_program_address(&[b"proposal", &0u64.to_le_bytes()], &program_id).0;
+    let set_admin_set = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::SetAdminSet {
+            threshold: 2,
+            members: vec![admin.pubkey(), second.pubkey(), Pubkey::new_unique()],
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(admin_set, false),
+            AccountMeta::new(admin.pubkey(), true),
+            AccountMeta::new(config_address(&program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    let pause = |authority: Pubkey, signs: bool| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::Pause.pack(),
+            vec![
+                AccountMeta::new(config_address(&program_id), false),
+                AccountMeta::new_readonly(authority, signs),
+            ],
+        )
+    };
+    let proposed = pause(admin_set, true);
+    let propose = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::Propose {
+            accounts: proposed
+                .accounts
+                .iter()
+                .map(|meta| ProposalAccount {
+                    pubkey: meta.pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: proposed.data.clone(),
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(proposal, false),
+            AccountMeta::new(admin_set, false),
+            AccountMeta::new(admin.pubkey(), true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    let approve = |member: Pubkey| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::Approve.pack(),
+            vec![
+                AccountMeta::new(proposal, false),
+                AccountMeta::new_readonly(admin_set, false),
+                AccountMeta::new_readonly(member, true),
+            ],
+        )
+    };
+    let execute = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::ExecuteProposal.pack(),
+        vec![
+            AccountMeta::new(proposal, false),
+            AccountMeta::new_readonly(admin_set, false),
+            AccountMeta::new(admin.pubkey(), false),
+            AccountMeta::new_readonly(program_id, false),
+            AccountMeta::new(config_address(&program_id), false),
+            AccountMeta::new_readonly(admin_set, false),
+        ],
+    );
+    let paused = |env: &mut TestEnv| {
+        let mut banks_client = env.context.banks_client.clone();
+        async move {
+            let account = banks_client
+                .get_account(config_address(&program_id))
+                .await
+                .unwrap()
+                .unwrap();
+            Config::unpack(&account.data).unwrap().paused
+        }
+    };
+
+    // Once the set is the admin, the old admin key alone can no longer pause
+    env.process(set_admin_set, &[&admin]).await.unwrap();
+    let result = env.process(pause(admin.pubkey(), true), &[&admin]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::Unauthorized)
+    );
+
+    env.process(propose, &[&admin]).await.unwrap();
+    let result = env.process(execute.clone(), &[]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::NotEnoughApprovals)
+    );
+    let result = env.process(approve(outsider.pubkey()), &[&outsider]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::NotAdminSetMember)
+    );
+    env.process(approve(second.pubkey()), &[&second])
+        .await
+        .unwrap();
+    assert!(!paused(&mut env).await);
+    env.process(execute, &[]).await.unwrap();
+    assert!(paused(&mut env).await);
+    assert_eq!(env.balance(proposal).await, 0);
+}
+
+#[tokio::test]
+async fn admin_handover_needs_the_nominee_to_accept() {
+    let mut env = setup(None).await;
+    let program_id = env.program_id;
+    let admin = env.admin.insecure_clone();
+    let nominee = Keypair::new();
+    let admin_ix = |data: GarbageGrabInstruction, signer: Pubkey| {
+        Instruction::new_with_bytes(
+            program_id,
+            &data.pack(),
+            vec![
+                AccountMeta::new(config_address(&program_id), false),
+                AccountMeta::new_readonly(signer, true),
+            ],
+        )
+    };
+    let nominate = GarbageGrabInstruction::NominateAdmin {
+        new_admin: nominee.pubkey(),
+    };
+
+    let result = env
+        .process(admin_ix(nominate.clone(), nominee.pubkey()), &[&nominee])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::Unauthorized)
+    );
+    let result = env
+        .process(
+            admin_ix(GarbageGrabInstruction::AcceptAdmin, nominee.pubkey()),
+            &[&nominee],
+        )
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::Unauthorized)
+    );
+
+    // The old admin stays in charge until the nominee accepts
+    env.process(admin_ix(nominate, admin.pubkey()), &[&admin])
+        .await
+        .unwrap();
+    env.process(
+        admin_ix(GarbageGrabInstruction::Pause, admin.pubkey()),
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    env.process(
+        admin_ix(GarbageGrabInstruction::AcceptAdmin, nominee.pubkey()),
+        &[&nominee],
+    )
+    .await
+    .unwrap();
+    let result = env
+        .process(
+            admin_ix(GarbageGrabInstruction::Unpause, admin.pubkey()),
+            &[&admin],
+        )
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::Unauthorized)
+    );
+    env.process(
+        admin_ix(GarbageGrabInstruction::Unpause, nominee.pubkey()),
+        &[&nominee],
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn top_up_rent_restores_rent_exemption() {
+    let mut env = setup(Some(10)).await;
+    let program_id = env.program_id;
+    let admin = env.admin.insecure_clone();
+    let address = score_address(&program_id, &env.player.pubkey());
+    let mut account = env
+        .context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .unwrap();
+    account.lamports = 1;
+    env.context.set_account(&address, &account.into());
+    let top_up = |account: Pubkey| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::TopUpRent.pack(),
+            vec![
+                AccountMeta::new(account, false),
+                AccountMeta::new(admin.pubkey(), true),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        )
+    };
+
+    let result = env.process(top_up(admin.pubkey()), &[&admin]).await;
+    assert_eq!(
+        instruction_error(result),
+        InstructionError::IncorrectProgramId
+    );
+
+    // Anyone can pay, and only the shortfall is taken
+    let required = env
+        .context
+        .banks_client
+        .get_rent()
+        .await
+        .unwrap()
+        .minimum_balance(ScoreAccount::LEN);
+    env.process(top_up(address), &[&admin]).await.unwrap();
+    assert_eq!(env.balance(address).await, required);
+    env.process(top_up(address), &[&admin]).await.unwrap();
+    assert_eq!(env.balance(address).await, required);
+}
+
+#[tokio::test]
+async fn update_rejects_wrong_pda() {
+    let mut env = setup(Some(10)).await;
+    let player = env.player.insecure_clone();
+    let result = env
+        .process(env.update_score(Pubkey::new_unique(), true, 1), &[&player])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::InvalidPda)
+    );
+}
+
+#[tokio::test]
+async fn game_modes_keep_separate_scores() {
+    let mut env = setup(Some(10)).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let arcade = score_address(&program_id, &player.pubkey());
+    let endless = ScorePda::find(&program_id, &player.pubkey(), GameMode::Endless).0;
+    assert_ne!(arcade, endless);
+
+    let initialize = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::Initialize {
+            game_mode: GameMode::Endless,
+            referrer: None,
+            allowlist_proof: Vec::new(),
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(endless, false),
+            AccountMeta::new(player.pubkey(), true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+        ],
+    );
+    env.process(initialize, &[&player]).await.unwrap();
+    let update = |address: Pubkey, game_mode: GameMode| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::UpdateScore {
+                game_mode,
+                increment: 7,
+            }
+            .pack(),
+            vec![
+                AccountMeta::new(address, false),
+                AccountMeta::new_readonly(player.pubkey(), true),
+                AccountMeta::new_readonly(season_address(&program_id), false),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(ban_list_address(&program_id), false),
+            ],
+        )
+    };
+    env.process(update(endless, GameMode::Endless), &[&player])
+        .await
+        .unwrap();
+
+    // Each mode's account only answers to its own mode byte
+    let result = env
+        .process(update(arcade, GameMode::Endless), &[&player])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::InvalidPda)
+    );
+
+    let account = env.context.banks_client.get_account(endless).await;
+    let account = account.unwrap().expect("endless score exists");
+    let state = ScoreAccount::unpack(&account.data).unwrap();
+    assert_eq!((state.game_mode, state.score), (GameMode::Endless, 7));
+    assert_eq!(env.score().await.score, 10);
+}
+
+#[tokio::test]
+async fn update_requires_player_signature() {
+    let mut env = setup(Some(10)).await;
+    let address = score_address(&env.program_id, &env.player.pubkey());
+    let result = env.process(env.update_score(address, false, 1), &[]).await;
+    assert_eq!(
+        instruction_error(result),
+        InstructionError::MissingRequiredSignature
+    );
+    assert_eq!(env.score().await.score, 10);
+}
+
+#[tokio::test]
+async fn update_rejects_score_account_owned_by_another_program() {
+    let mut env = setup(None).await;
+    let player = env.player.insecure_clone();
+    let address = score_address(&env.program_id, &player.pubkey());
+
+    // Plant a well-formed score account at the right address under a different owner
+    let mut data = vec![0u8; ScoreAccount::LEN];
+    ScoreAccount {
+        is_initialized: true,
+        player: player.pubkey(),
+        score: 900_000,
+        version: ScoreAccount::VERSION,
+        bump: score_bump(&env.program_id, &player.pubkey()),
+        ..ScoreAccount::default()
+    }
+    .pack(&mut data)
+    .unwrap();
+    env.context.set_account(
+        &address,
+        &program_account(&Pubkey::new_unique(), data).into(),
+    );
+
+    let result = env
+        .process(env.update_score(address, true, 1), &[&player])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        InstructionError::IncorrectProgramId
+    );
+}
+
+#[tokio::test]
+async fn initialize_succeeds_on_prefunded_address() {
+    let mut env = setup(None).await;
+    let player = env.player.insecure_clone();
+    let address = score_address(&env.program_id, &player.pubkey());
+
+    // A one-lamport donation would make create_account fail
+    env.context
+        .set_account(&address, &Account::new(1, 0, &system_program::id()).into());
+    env.process(env.initialize(), &[&player]).await.unwrap();
+
+    let account = env
+        .context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .unwrap();
+    let rent = env.context.banks_client.get_rent().await.unwrap();
+    assert_eq!(account.owner, env.program_id);
+    assert_eq!(account.lamports, rent.minimum_balance(ScoreAccount::LEN));
+    assert!(env.score().await.is_initialized);
+}
+
+#[tokio::test]
+async fn initialize_rejects_spoofed_system_program() {
+    let mut env = setup(None).await;
+    let player = env.player.insecure_clone();
+    let mut instruction = env.initialize();
+    instruction.accounts[2].pubkey = Pubkey::new_unique();
+
+    let result = env.process(instruction, &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        InstructionError::IncorrectProgramId
+    );
+}
+
+#[tokio::test]
+async fn closed_beta_initialize_needs_allowlist_proof() {
+    let mut env = setup(None).await;
+    let player = env.player.insecure_clone();
+    let listed = [Pubkey::new_unique(), player.pubkey(), Pubkey::new_unique()];
+    let leaves: Vec<[u8; 32]> = listed.iter().map(merkle::allowlist_leaf).collect();
+    let (root, proofs) = merkle::build_tree(&leaves);
+    env.set_config(Config {
+        closed_beta: true,
+        allowlist_root: root,
+        ..env.config()
+    });
+    let base = env.initialize();
+    let initialize = |allowlist_proof: Vec<[u8; 32]>| {
+        let mut instruction = base.clone();
+        instruction.data = GarbageGrabInstruction::Initialize {
+            game_mode: GameMode::Arcade,
+            referrer: None,
+            allowlist_proof,
+        }
+        .pack();
+        instruction
+    };
+
+    // Another listed player's proof does not admit this one
+    for proof in [Vec::new(), proofs[0].clone()] {
+        let result = env.process(initialize(proof), &[&player]).await;
+        assert_eq!(
+            instruction_error(result),
+            custom(GarbageGrabError::NotAllowlisted)
+        );
+    }
+    env.process(initialize(proofs[1].clone()), &[&player])
+        .await
+        .unwrap();
+    assert!(env.score().await.is_initialized);
+}
+
+#[tokio::test]
+async fn opening_the_beta_drops_the_allowlist_check() {
+    let mut env = setup(None).await;
+    let player = env.player.insecure_clone();
+    // UpdateConfig validates the whole config, which needs a sane increment cap
+    env.set_config(Config {
+        closed_beta: true,
+        allowlist_root: [1; 32],
+        max_increment: MAX_SCORE,
+        ..env.config()
+    });
+    let result = env.process(env.initialize(), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::NotAllowlisted)
+    );
+
+    let admin = env.admin.insecure_clone();
+    let open = Instruction::new_with_bytes(
+        env.program_id,
+        &GarbageGrabInstruction::UpdateConfig(ConfigUpdate {
+            closed_beta: Some(false),
+            ..ConfigUpdate::default()
+        })
+        .pack(),
+        vec![
+            AccountMeta::new(config_address(&env.program_id), false),
+            AccountMeta::new_readonly(admin.pubkey(), true),
+        ],
+    );
+    env.process(open, &[&admin]).await.unwrap();
+    env.process(env.initialize(), &[&player]).await.unwrap();
+}
+
+#[tokio::test]
+async fn rotating_the_allowlist_root_revokes_old_proofs() {
+    let mut env = setup(None).await;
+    let player = env.player.insecure_clone();
+    let admin = env.admin.insecure_clone();
+    let build = |listed: &[Pubkey]| {
+        let leaves: Vec<[u8; 32]> = listed.iter().map(merkle::allowlist_leaf).collect();
+        merkle::build_tree(&leaves)
+    };
+    let (old_root, old_proofs) = build(&[player.pubkey(), Pubkey::new_unique()]);
+    let (new_root, new_proofs) = build(&[
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        player.pubkey(),
+    ]);
+    // UpdateConfig validates the whole config, which needs a sane increment cap
+    env.set_config(Config {
+        closed_beta: true,
+        allowlist_root: old_root,
+        max_increment: MAX_SCORE,
+        ..env.config()
+    });
+    let base = env.initialize();
+    let initialize = |allowlist_proof: Vec<[u8; 32]>| {
+        let mut instruction = base.clone();
+        instruction.data = GarbageGrabInstruction::Initialize {
+            game_mode: GameMode::Arcade,
+            referrer: None,
+            allowlist_proof,
+        }
+        .pack();
+        instruction
+    };
+    let rotate = Instruction::new_with_bytes(
+        env.program_id,
+        &GarbageGrabInstruction::UpdateConfig(ConfigUpdate {
+            allowlist_root: Some(new_root),
+            ..ConfigUpdate::default()
+        })
+        .pack(),
+        vec![
+            AccountMeta::new(config_address(&env.program_id), false),
+            AccountMeta::new_readonly(admin.pubkey(), true),
+        ],
+    );
+    env.process(rotate, &[&admin]).await.unwrap();
+    let config = config_address(&env.program_id);
+    let account = env.context.banks_client.get_account(config).await;
+    let account = account.unwrap().expect("config exists");
+    assert_eq!(Config::unpack(&account.data).unwrap().allowlist_root, new_root);
+
+    let result = env
+        .process(initialize(old_proofs[0].clone()), &[&player])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::NotAllowlisted)
+    );
+    env.process(initialize(new_proofs[2].clone()), &[&player])
+        .await
+        .unwrap();
+    assert!(env.score().await.is_initialized);
+}
+
+#[tokio::test]
+async fn global_stats_count_players_runs_and_score() {
+    let mut env = setup(None).await;
+    let program_id = env.program_id;
+    let admin = env.admin.insecure_clone();
+    let player = env.player.insecure_clone();
+    let stats_address = GlobalStatsPda::find(&program_id).0;
+    let initialize_stats = || {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::InitializeGlobalStats.pack(),
+            vec![
+                AccountMeta::new(stats_address, false),
+                AccountMeta::new(admin.pubkey(), true),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        )
+    };
+    env.process(initialize_stats(), &[&admin]).await.unwrap();
+    let result = env.process(initialize_stats(), &[&admin]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::AlreadyInitialized)
+    );
+
+    let mut initialize = env.initialize();
+    initialize
+        .accounts
+        .push(AccountMeta::new(stats_address, false));
+    env.process(initialize, &[&player]).await.unwrap();
+
+    let score_account = score_address(&program_id, &player.pubkey());
+    let mut update = env.update_score(score_account, true, 7);
+    update.accounts.push(AccountMeta::new(stats_address, false));
+    env.process(update, &[&player]).await.unwrap();
+
+    // Without the stats account the run still lands, it just goes uncounted
+    for (run_score, counted) in [(30, true), (40, false)] {
+        let mut accounts = vec![
+            AccountMeta::new(score_account, false),
+            AccountMeta::new_readonly(player.pubkey(), true),
+            AccountMeta::new_readonly(season_address(&program_id), false),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(ban_list_address(&program_id), false),
+        ];
+        if counted {
+            accounts.push(AccountMeta::new(stats_address, false));
+        }
+        let submit = Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::SubmitRun {
+                game_mode: GameMode::Arcade,
+                run_score,
+                run_hash: [run_score as u8; 32],
+            }
+            .pack(),
+            accounts,
+        );
+        env.process(submit, &[&player]).await.unwrap();
+    }
+
+    let account = env
+        .context
+        .banks_client
+        .get_account(stats_address)
+        .await
+        .unwrap()
+        .unwrap();
+    let stats = GlobalStats::unpack(&account.data).unwrap();
+    assert_eq!(stats.total_players, 1);
+    assert_eq!(stats.total_runs, 1);
+    assert_eq!(stats.total_score, 37);
+    assert_eq!(stats.total_fees, 0);
+
+    // The best score moved 0 -> 7 -> 30; the uncounted 40 never reached the histogram
+    let mut histogram = [0; HISTOGRAM_BUCKETS];
+    histogram[score_bucket(30)] = 1;
+    assert_eq!(stats.histogram, histogram);
+    assert_eq!(stats.top_bps(30), Some(10_000));
+}
+
+#[tokio::test]
+async fn histogram_places_players_by_their_best_score() {
+    let mut env = setup(None).await;
+    let program_id = env.program_id;
+    let admin = env.admin.insecure_clone();
+    let stats_address = GlobalStatsPda::find(&program_id).0;
+    let initialize_stats = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::InitializeGlobalStats.pack(),
+        vec![
+            AccountMeta::new(stats_address, false),
+            AccountMeta::new(admin.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    env.process(initialize_stats, &[&admin]).await.unwrap();
+
+    let rival = Keypair::new();
+    let funded = Account::new(1_000_000_000, 0, &system_program::id());
+    env.context.set_account(&rival.pubkey(), &funded.into());
+    let players = [env.player.insecure_clone(), rival];
+    // Each player scores through the shared helpers by taking the env's player slot
+    let counted_update = |env: &mut TestEnv, player: &Keypair, increment: u64| {
+        env.player = player.insecure_clone();
+        let score_account = score_address(&program_id, &player.pubkey());
+        let mut update = env.update_score(score_account, true, increment);
+        update.accounts.push(AccountMeta::new(stats_address, false));
+        update
+    };
+    for player in &players {
+        env.player = player.insecure_clone();
+        let mut initialize = env.initialize();
+        initialize
+            .accounts
+            .push(AccountMeta::new(stats_address, false));
+        env.process(initialize, &[player]).await.unwrap();
+    }
+    let update = counted_update(&mut env, &players[1], 500);
+    env.process(update, &[&players[1]]).await.unwrap();
+
+    let stats = |account: Option<Account>| GlobalStats::unpack(&account.unwrap().data).unwrap();
+    let account = env.context.banks_client.get_account(stats_address).await;
+    let before = stats(account.unwrap());
+    assert_eq!(before.histogram[0], 1);
+    assert_eq!(before.histogram[score_bucket(500)], 1);
+    assert_eq!(before.top_bps(0), Some(10_000));
+    assert_eq!(before.top_bps(500), Some(5_000));
+
+    // Overtaking the rival moves the player up a bucket and the rival out of the top half
+    let update = counted_update(&mut env, &players[0], 10_000);
+    env.process(update, &[&players[0]]).await.unwrap();
+    let account = env.context.banks_client.get_account(stats_address).await;
+    let after = stats(account.unwrap());
+    assert_eq!(after.histogram[0], 0);
+    assert_eq!(after.histogram[score_bucket(10_000)], 1);
+    assert_eq!(after.top_bps(10_000), Some(5_000));
+    assert_eq!(after.top_bps(500), Some(10_000));
+
+    // A better score in the same bucket leaves the distribution alone
+    let update = counted_update(&mut env, &players[0], 1);
+    env.process(update, &[&players[0]]).await.unwrap();
+    let account = env.context.banks_client.get_account(stats_address).await;
+    assert_eq!(stats(account.unwrap()).histogram, after.histogram);
+}
+
+#[tokio::test]
+async fn set_score_rejects_non_admin() {
+    let mut env = setup(Some(10)).await;
+    let player = env.player.insecure_clone();
+    let set_score = |authority: Pubkey| {
+        Instruction::new_with_bytes(
+            env.program_id,
+            &GarbageGrabInstruction::SetScore {
+                game_mode: GameMode::Arcade,
+                value: 500,
+            }
+            .pack(),
+            vec![
+                AccountMeta::new(score_address(&env.program_id, &player.pubkey()), false),
+                AccountMeta::new_readonly(player.pubkey(), false),
+                AccountMeta::new_readonly(authority, true),
+                AccountMeta::new_readonly(config_address(&env.program_id), false),
+            ],
+        )
+    };
+    let as_player = set_score(player.pubkey());
+    let as_admin = set_score(env.admin.pubkey());
+
+    let result = env.process(as_player, &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::Unauthorized)
+    );
+
+    let admin = env.admin.insecure_clone();
+    env.process(as_admin, &[&admin]).await.unwrap();
+    assert_eq!(env.score().await.score, 500);
+}
+
+#[tokio::test]
+async fn staked_tokens_multiply_score_and_unlock_after_the_cooldown() {
+    let mut env = setup(Some(0)).await;
+    let player = env.player.insecure_clone();
+    let program_id = env.program_id;
+    let mint = Pubkey::new_unique();
+    env.context.set_account(&mint, &mint_account(1_000).into());
+    env.set_config(Config {
+        reward_mint: mint,
+        stake_tier_amounts: [100, 500, 0],
+        stake_tier_bps: [12_000, 15_000, 0],
+        unstake_cooldown_secs: 3_600,
+        stake_lock_bps: [15_000, 0, 0],
+        early_unstake_penalty_bps: 1_000,
+        ..env.config()
+    });
+    let treasury = Pubkey::find_program_address(&[b"treasury"], &program_id).0;
+    let player_tokens = get_associated_token_address(&player.pubkey(), &mint);
+    let treasury_tokens = get_associated_token_address(&treasury, &mint);
+    env.context.set_account(
+        &player_tokens,
+        &token_account(&mint, &player.pubkey(), 1_000).into(),
+    );
+    env.context.set_account(
+        &treasury_tokens,
+        &token_account(&mint, &treasury, 0).into(),
+    );
+
+    let (stake, _) = StakePda::find(&program_id, &player.pubkey());
+    let (stake_vault, _) = StakeVaultPda::find(&program_id, &player.pubkey());
+    let stake_ix = |amount: u64, lock: StakeLock| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::Stake { amount, lock }.pack(),
+            vec![
+                AccountMeta::new(stake, false),
+                AccountMeta::new(stake_vault, false),
+                AccountMeta::new(player.pubkey(), true),
+                AccountMeta::new(player_tokens, false),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(mint, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        )
+    };
+    let unstake_ix = |amount: u64| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::Unstake { amount }.pack(),
+            vec![
+                AccountMeta::new(stake, false),
+                AccountMeta::new(stake_vault, false),
+                AccountMeta::new_readonly(player.pubkey(), true),
+                AccountMeta::new(player_tokens, false),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(mint, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new_readonly(treasury, false),
+                AccountMeta::new(treasury_tokens, false),
+            ],
+        )
+    };
+    let update_with_stake = |env: &TestEnv, increment: u64| {
+        let mut ix = env.update_score(
+            score_address(&program_id, &player.pubkey()),
+            true,
+            increment,
+        );
+        ix.accounts.push(AccountMeta::new_readonly(stake, false));
+        ix
+    };
+
+    env.process(stake_ix(300, StakeLock::None), &[&player]).await.unwrap();
+    assert_eq!(token_balance(&mut env, stake_vault).await, 300);
+    assert_eq!(token_balance(&mut env, player_tokens).await, 700);
+
+    // 300 staked reaches the first tier
+    env.process(update_with_stake(&env, 100), &[&player])
+        .await
+        .unwrap();
+    assert_eq!(env.score().await.score, 120);
+
+    env.process(stake_ix(200, StakeLock::None), &[&player]).await.unwrap();
+    env.process(update_with_stake(&env, 100), &[&player])
+        .await
+        .unwrap();
+    assert_eq!(env.score().await.score, 270);
+
+    // The tokens stay locked for the cooldown after the last stake
+    let result = env.process(unstake_ix(500), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::StakeLocked)
+    );
+    let account = env.context.banks_client.get_account(stake).await.unwrap();
+    let staked = Stake::unpack(&account.expect("stake exists").data).unwrap();
+    assert_eq!(staked.amount, 500);
+    let mut clock: Clock = env.context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp = staked.last_stake_ts + 3_600;
+    env.context.set_sysvar(&clock);
+
+    let result = env.process(unstake_ix(501), &[&player]).await;
+    assert_eq!(instruction_error(result), InstructionError::InsufficientFunds);
+    env.process(unstake_ix(450), &[&player]).await.unwrap();
+    assert_eq!(token_balance(&mut env, stake_vault).await, 50);
+    assert_eq!(token_balance(&mut env, player_tokens).await, 950);
+
+    // Below every tier the increment is not multiplied
+    env.process(update_with_stake(&env, 100), &[&player])
+        .await
+        .unwrap();
+    assert_eq!(env.score().await.score, 370);
+
+    // A 30 day lock boosts the tier multiplier while it runs
+    env.process(stake_ix(50, StakeLock::Days30), &[&player])
+        .await
+        .unwrap();
+    env.process(update_with_stake(&env, 100), &[&player])
+        .await
+        .unwrap();
+    assert_eq!(env.score().await.score, 550);
+
+    // Unstaking before the lock ends sends the penalty to the treasury
+    clock.unix_timestamp += 3_600;
+    env.context.set_sysvar(&clock);
+    env.process(unstake_ix(100), &[&player]).await.unwrap();
+    assert_eq!(token_balance(&mut env, stake_vault).await, 0);
+    assert_eq!(token_balance(&mut env, treasury_tokens).await, 10);
+    assert_eq!(token_balance(&mut env, player_tokens).await, 990);
+}
+
+#[tokio::test]
+async fn quests_track_score_and_pay_their_reward_once() {
+    let mut env = setup(Some(0)).await;
+    let admin = env.admin.insecure_clone();
+    let player = env.player.insecure_clone();
+    let program_id = env.program_id;
+    let mint = Pubkey::new_unique();
+    env.context.set_account(&mint, &mint_account(1_000).into());
+    env.set_config(Config {
+        reward_mint: mint,
+        ..env.config()
+    });
+    let vault = vault_address(&program_id);
+    let player_tokens = get_associated_token_address(&player.pubkey(), &mint);
+    env.context
+        .set_account(&vault, &token_account(&mint, &vault, 500).into());
+    env.context.set_account(
+        &player_tokens,
+        &token_account(&mint, &player.pubkey(), 0).into(),
+    );
+
+    let (quest, _) = QuestPda::find(&program_id, 1);
+    let (progress, _) = QuestProgressPda::find(&program_id, 1, &player.pubkey());
+    let create_quest = |authority: Pubkey| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::CreateQuest {
+                quest_id: 1,
+                metric: QuestMetric::ScoreEarned,
+                target: 150,
+                reward: 100,
+            }
+            .pack(),
+            vec![
+                AccountMeta::new(quest, false),
+                AccountMeta::new(authority, true),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        )
+    };
+    let result = env.process(create_quest(player.pubkey()), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::Unauthorized)
+    );
+    env.process(create_quest(admin.pubkey()), &[&admin])
+        .await
+        .unwrap();
+
+    let accept = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::AcceptQuest.pack(),
+        vec![
+            AccountMeta::new(progress, false),
+            AccountMeta::new_readonly(quest, false),
+            AccountMeta::new(player.pubkey(), true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    env.process(accept, &[&player]).await.unwrap();
+
+    let update_with_quest = |env: &TestEnv, increment: u64| {
+        let mut ix = env.update_score(
+            score_address(&program_id, &player.pubkey()),
+            true,
+            increment,
+        );
+        ix.accounts.push(AccountMeta::new(progress, false));
+        ix
+    };
+    let claim = || {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::ClaimQuestReward.pack(),
+            vec![
+                AccountMeta::new(progress, false),
+                AccountMeta::new_readonly(quest, false),
+                AccountMeta::new_readonly(player.pubkey(), true),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new(vault, false),
+                AccountMeta::new(player_tokens, false),
+                AccountMeta::new_readonly(mint, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+            ],
+        )
+    };
+
+    env.process(update_with_quest(&env, 100), &[&player])
+        .await
+        .unwrap();
+    let result = env.process(claim(), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::QuestIncomplete)
+    );
+
+    env.process(update_with_quest(&env, 100), &[&player])
+        .await
+        .unwrap();
+    let account = env.context.banks_client.get_account(progress).await.unwrap();
+    let state = QuestProgress::unpack(&account.expect("progress exists").data).unwrap();
+    assert_eq!(state.progress, 200);
+
+    env.process(claim(), &[&player]).await.unwrap();
+    assert_eq!(token_balance(&mut env, player_tokens).await, 100);
+    assert_eq!(token_balance(&mut env, vault).await, 400);
+
+    let result = env.process(claim(), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::QuestAlreadyClaimed)
+    );
+}
+
+#[tokio::test]
+async fn vault_funds_reward_claims() {
+    let mut env = setup(Some(5_000)).await;
+    let admin = env.admin.insecure_clone();
+    let player = env.player.insecure_clone();
+    let program_id = env.program_id;
+
+    // Point the config at a fresh mint and give the admin and player token accounts
+    let mint = Pubkey::new_unique();
+    let mut mint_data = vec![0u8; Mint::LEN];
+    Mint {
+        mint_authority: COption::Some(admin.pubkey()),
+        supply: 1_000,
+        decimals: 0,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut mint_data);
+    let mint_account = Account {
+        lamports: 1_000_000_000,
+        data: mint_data,
+        owner: spl_token::id(),
+        ..Account::default()
+    };
+    env.context.set_account(&mint, &mint_account.into());
+
+    env.set_config(Config {
+        reward_mint: mint,
+        reward_per_tier: 100,
+        ..env.config()
+    });
+    let admin_tokens = get_associated_token_address(&admin.pubkey(), &mint);
+    let player_tokens = get_associated_token_address(&player.pubkey(), &mint);
+    env.context.set_account(
+        &admin_tokens,
+        &token_account(&mint, &admin.pubkey(), 1_000).into(),
+    );
+
+    let vault = vault_address(&program_id);
+    let initialize_vault = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::InitializeVault.pack(),
+        vec![
+            AccountMeta::new(vault, false),
+            AccountMeta::new(admin.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    env.process(initialize_vault, &[&admin]).await.unwrap();
+
+    let fund_vault = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::FundVault { amount: 600 }.pack(),
+        vec![
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(admin.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new(admin_tokens, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    );
+    env.process(fund_vault, &[&admin]).await.unwrap();
+    assert_eq!(token_balance(&mut env, vault).await, 600);
+    assert_eq!(token_balance(&mut env, admin_tokens).await, 400);
+
+    // A best score of 5,000 reaches two tiers
+    let claim = |create_token_account: bool| {
+        let mut accounts = vec![
+            AccountMeta::new(score_address(&program_id, &player.pubkey()), false),
+            AccountMeta::new(player.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(player_tokens, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ];
+        if create_token_account {
+            accounts.extend([
+                AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ]);
+        }
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::ClaimRewards {
+                game_mode: GameMode::Arcade,
+            }
+            .pack(),
+            accounts,
+        )
+    };
+
+    // The player has no token account yet, so the claim must be able to create one
+    let result = env.process(claim(false), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        InstructionError::NotEnoughAccountKeys
+    );
+    let player_lamports = env.balance(player.pubkey()).await;
+    env.process(claim(true), &[&player]).await.unwrap();
+    let rent = env.context.banks_client.get_rent().await.unwrap();
+    assert_eq!(
+        env.balance(player.pubkey()).await,
+        player_lamports - rent.minimum_balance(TokenAccount::LEN)
+    );
+    assert_eq!(token_balance(&mut env, player_tokens).await, 200);
+    assert_eq!(token_balance(&mut env, vault).await, 400);
+    assert_eq!(env.score().await.rewards_claimed, 200);
+    let account = env.context.banks_client.get_account(player_tokens).await;
+    let account = account.unwrap().expect("token account exists");
+    assert_eq!(account.owner, spl_token::id());
+    let state = TokenAccount::unpack(&account.data).unwrap();
+    assert_eq!((state.mint, state.owner), (mint, player.pubkey()));
+
+    let result = env.process(claim(false), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::NothingToClaim)
+    );
+
+    // Reaching the 25,000 tier pays into the same account, even with the
+    // creation accounts passed again
+    let address = score_address(&program_id, &player.pubkey());
+    env.process(env.update_score(address, true, 20_000), &[&player])
+        .await
+        .unwrap();
+    env.process(claim(true), &[&player]).await.unwrap();
+    assert_eq!(token_balance(&mut env, player_tokens).await, 300);
+    assert_eq!(token_balance(&mut env, vault).await, 300);
+}
+
+#[tokio::test]
+async fn vault_pays_rewards_in_a_token_2022_mint_with_transfer_fees() {
+    let mut env = setup(Some(5_000)).await;
+    let admin = env.admin.insecure_clone();
+    let player = env.player.insecure_clone();
+    let program_id = env.program_id;
+    let token_program = spl_token_2022::id();
+
+    // A Token-2022 mint that withholds 1% of every transfer
+    let mint_keypair = Keypair::new();
+    let mint = mint_keypair.pubkey();
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[
+        ExtensionType::TransferFeeConfig,
+    ])
+    .unwrap();
+    let rent = env.context.banks_client.get_rent().await.unwrap();
+    let create_mint = solana_sdk::system_instruction::create_account(
+        &admin.pubkey(),
+        &mint,
+        rent.minimum_balance(space),
+        space as u64,
+        &token_program,
+    );
+    env.process(create_mint, &[&admin, &mint_keypair])
+        .await
+        .unwrap();
+    let init_fee =
+        initialize_transfer_fee_config(&token_program, &mint, None, None, 100, 1_000).unwrap();
+    env.process(init_fee, &[]).await.unwrap();
+    let init_mint = spl_token_2022::instruction::initialize_mint2(
+        &token_program,
+        &mint,
+        &admin.pubkey(),
+        None,
+        0,
+    )
+    .unwrap();
+    env.process(init_mint, &[]).await.unwrap();
+    for owner in [admin.pubkey(), player.pubkey()] {
+        let create_ata =
+            create_associated_token_account(&admin.pubkey(), &owner, &mint, &token_program);
+        env.process(create_ata, &[&admin]).await.unwrap();
+    }
+    let admin_tokens =
+        get_associated_token_address_with_program_id(&admin.pubkey(), &mint, &token_program);
+    let player_tokens =
+        get_associated_token_address_with_program_id(&player.pubkey(), &mint, &token_program);
+    let mint_to = spl_token_2022::instruction::mint_to(
+        &token_program,
+        &mint,
+        &admin_tokens,
+        &admin.pubkey(),
+        &[],
+        1_000,
+    )
+    .unwrap();
+    env.process(mint_to, &[&admin]).await.unwrap();
+    env.set_config(Config {
+        reward_mint: mint,
+        reward_per_tier: 100,
+        ..env.config()
+    });
+
+    let vault = vault_address(&program_id);
+    let initialize_vault = |token_program: Pubkey| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::InitializeVault.pack(),
+            vec![
+                AccountMeta::new(vault, false),
+                AccountMeta::new(admin.pubkey(), true),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(mint, false),
+                AccountMeta::new_readonly(token_program, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        )
+    };
+    // The token program passed must be the one owning the mint
+    let result = env
+        .process(initialize_vault(spl_token::id()), &[&admin])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        InstructionError::IncorrectProgramId
+    );
+    env.process(initialize_vault(token_program), &[&admin])
+        .await
+        .unwrap();
+
+    let fund_vault = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::FundVault { amount: 600 }.pack(),
+        vec![
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(admin.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new(admin_tokens, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new_readonly(token_program, false),
+        ],
+    );
+    env.process(fund_vault, &[&admin]).await.unwrap();
+    assert_eq!(token_balance(&mut env, admin_tokens).await, 400);
+    assert_eq!(token_balance(&mut env, vault).await, 594);
+
+    // A best score of 5,000 reaches two tiers; the fee comes out of the payout
+    let claim = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::ClaimRewards {
+            game_mode: GameMode::Arcade,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(score_address(&program_id, &player.pubkey()), false),
+            AccountMeta::new_readonly(player.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(player_tokens, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new_readonly(token_program, false),
+        ],
+    );
+    env.process(claim, &[&player]).await.unwrap();
+    assert_eq!(token_balance(&mut env, player_tokens).await, 198);
+    assert_eq!(token_balance(&mut env, vault).await, 394);
+    assert_eq!(env.score().await.rewards_claimed, 200);
+}
+
+#[tokio::test]
+async fn ranked_run_fee_gates_leaderboard_submission() {
+    const FEE: u64 = 5_000;
+    let mut env = setup(Some(10)).await;
+    let player = env.player.insecure_clone();
+    let program_id = env.program_id;
+    env.set_config(Config {
+        ranked_entry_fee: FEE,
+        ..env.config()
+    });
+
+    let leaderboard = leaderboard_address(&program_id);
+    env.context.set_account(
+        &leaderboard,
+        &leaderboard_account(&program_id, Vec::new()).into(),
+    );
+
+    let score = score_address(&program_id, &player.pubkey());
+    let prize_pool = prize_pool_address(&program_id);
+    let start_ranked_run = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::StartRankedRun {
+            game_mode: GameMode::Arcade,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(score, false),
+            AccountMeta::new(player.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new(prize_pool, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(fee_vault_address(&program_id), false),
+        ],
+    );
+    let submit = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::SubmitToLeaderboard {
+            game_mode: GameMode::Arcade,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(leaderboard, false),
+            AccountMeta::new(score, false),
+            AccountMeta::new_readonly(player.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(ban_list_address(&program_id), false),
+        ],
+    );
+
+    let result = env.process(submit.clone(), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::NoRankedRun)
+    );
+
+    // Only the ranked mode competes on the leaderboard
+    for ix in [&start_ranked_run, &submit] {
+        let mut unranked = ix.clone();
+        unranked.data[1] = GameMode::Endless as u8;
+        let result = env.process(unranked, &[&player]).await;
+        assert_eq!(
+            instruction_error(result),
+            custom(GarbageGrabError::UnrankedGameMode)
+        );
+    }
+
+    env.process(start_ranked_run.clone(), &[&player])
+        .await
+        .unwrap();
+    let pool = env
+        .context
+        .banks_client
+        .get_account(prize_pool)
+        .await
+        .unwrap()
+        .expect("prize pool exists");
+    let rent = env.context.banks_client.get_rent().await.unwrap();
+    assert_eq!(pool.owner, program_id);
+    assert_eq!(pool.lamports, rent.minimum_balance(0) + FEE);
+    assert!(env.score().await.ranked_run_open);
+
+    let result = env.process(start_ranked_run, &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::RankedRunInProgress)
+    );
+
+    // Submitting uses up the run
+    env.process(submit.clone(), &[&player]).await.unwrap();
+    assert!(!env.score().await.ranked_run_open);
+    let result = env.process(submit, &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::NoRankedRun)
+    );
+}
+
+#[tokio::test]
+async fn leaderboard_pages_rank_below_a_full_board_and_rebalance() {
+    let mut env = setup(Some(10)).await;
+    let player = env.player.insecure_clone();
+    let program_id = env.program_id;
+
+    // The global leaderboard is full of better scores
+    let leaderboard = leaderboard_address(&program_id);
+    let better: Vec<LeaderboardEntry> = (0..LEADERBOARD_SIZE as u64)
+        .map(|i| LeaderboardEntry::new(Pubkey::new_unique(), 100 + i))
+        .collect();
+    env.context.set_account(
+        &leaderboard,
+        &leaderboard_account(&program_id, better.clone()).into(),
+    );
+
+    let (page_one, _) = LeaderboardPda::find_page(&program_id, 1);
+    let payer = env.context.payer.pubkey();
+    let initialize_page = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::InitializeLeaderboardPage { page: 1 }.pack(),
+        vec![
+            AccountMeta::new(page_one, false),
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    env.process(initialize_page, &[]).await.unwrap();
+
+    let score = score_address(&program_id, &player.pubkey());
+    let start_ranked_run = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::StartRankedRun {
+            game_mode: GameMode::Arcade,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(score, false),
+            AccountMeta::new(player.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new(prize_pool_address(&program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(fee_vault_address(&program_id), false),
+        ],
+    );
+    let submit_to_page = |page: u8, page_above: Pubkey, page_account: Pubkey| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::SubmitToLeaderboardPage {
+                game_mode: GameMode::Arcade,
+                page,
+            }
+            .pack(),
+            vec![
+                AccountMeta::new(page_account, false),
+                AccountMeta::new_readonly(page_above, false),
+                AccountMeta::new(score, false),
+                AccountMeta::new_readonly(player.pubkey(), true),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(ban_list_address(&program_id), false),
+            ],
+        )
+    };
+
+    // Pages rank the ranked mode alone, like the board above them
+    let mut unranked = submit_to_page(1, leaderboard, page_one);
+    unranked.data[1] = GameMode::Endless as u8;
+    let result = env.process(unranked, &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::UnrankedGameMode)
+    );
+
+    // Page 1 takes the score the global leaderboard has no room for
+    env.process(start_ranked_run.clone(), &[&player])
+        .await
+        .unwrap();
+    env.process(submit_to_page(1, leaderboard, page_one), &[&player])
+        .await
+        .unwrap();
+    let page = env.context.banks_client.get_account(page_one).await;
+    let page = page.unwrap().expect("page 1 exists");
+    let entries = Leaderboard::load(&page.data).unwrap().entries().to_vec();
+    assert_eq!(entries, vec![LeaderboardEntry::new(player.pubkey(), 10)]);
+
+    // Once the global leaderboard has room, a score belongs there instead
+    env.context.set_account(
+        &leaderboard,
+        &leaderboard_account(&program_id, better[1..].to_vec()).into(),
+    );
+    env.process(start_ranked_run, &[&player]).await.unwrap();
+    let result = env
+        .process(submit_to_page(1, leaderboard, page_one), &[&player])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::WrongLeaderboardPage)
+    );
+
+    // and anyone can promote the page's best entry into it
+    let rebalance = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::RebalanceLeaderboardPages { page: 1 }.pack(),
+        vec![
+            AccountMeta::new(leaderboard, false),
+            AccountMeta::new(page_one, false),
+        ],
+    );
+    env.process(rebalance, &[]).await.unwrap();
+    let board = env.context.banks_client.get_account(leaderboard).await;
+    let board = board.unwrap().expect("leaderboard exists");
+    let board = Leaderboard::load(&board.data).unwrap();
+    assert!(board.is_full());
+    assert_eq!(
+        board.last(),
+        Some(&LeaderboardEntry::new(player.pubkey(), 10))
+    );
+    let page = env.context.banks_client.get_account(page_one).await;
+    let page = page.unwrap().expect("page 1 exists");
+    assert!(Leaderboard::load(&page.data).unwrap().entries().is_empty());
+}
+
+#[tokio::test]
+async fn weekly_leaderboard_ranks_this_weeks_scores_only() {
+    let mut env = setup(Some(10)).await;
+    let player = env.player.insecure_clone();
+    let program_id = env.program_id;
+    let (weekly, _) = WeeklyLeaderboardPda::find(&program_id);
+    let payer = env.context.payer.pubkey();
+    let initialize = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::InitializeWeeklyLeaderboard.pack(),
+        vec![
+            AccountMeta::new(weekly, false),
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    env.process(initialize, &[]).await.unwrap();
+
+    // Last week's leader is still on the board
+    let clock: Clock = env.context.banks_client.get_sysvar().await.unwrap();
+    let week = week_of(clock.unix_timestamp);
+    let mut data = env
+        .context
+        .banks_client
+        .get_account(weekly)
+        .await
+        .unwrap()
+        .expect("weekly leaderboard exists")
+        .data;
+    WeeklyLeaderboard::init(&mut data, week - 1)
+        .unwrap()
+        .board
+        .submit(Pubkey::new_unique(), 1_000);
+    env.context
+        .set_account(&weekly, &program_account(&program_id, data).into());
+
+    let score = score_address(&program_id, &player.pubkey());
+    let submit = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::SubmitToWeeklyLeaderboard {
+            game_mode: GameMode::Arcade,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(weekly, false),
+            AccountMeta::new_readonly(score, false),
+            AccountMeta::new_readonly(player.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(ban_list_address(&program_id), false),
+        ],
+    );
+
+    // Only the ranked mode competes on the weekly board either
+    let mut unranked = submit.clone();
+    unranked.data[1] = GameMode::Endless as u8;
+    let result = env.process(unranked, &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::UnrankedGameMode)
+    );
+
+    // The score from the fixture was not recorded this week
+    let result = env.process(submit.clone(), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::NoWeeklyScore)
+    );
+
+    let update = env.update_score(score, true, 5);
+    env.process(update, &[&player]).await.unwrap();
+    let state = env.score().await;
+    assert_eq!((state.week, state.week_best_score), (week, 15));
+
+    // Submitting this week clears last week's entries first
+    env.process(submit, &[&player]).await.unwrap();
+    let account = env.context.banks_client.get_account(weekly).await;
+    let account = account.unwrap().expect("weekly leaderboard exists");
+    let weekly = WeeklyLeaderboard::load(&account.data).unwrap();
+    assert_eq!(weekly.week(), week);
+    assert_eq!(
+        weekly.board.entries(),
+        &[LeaderboardEntry::new(player.pubkey(), 15)]
+    );
+}
+
+#[tokio::test]
+async fn daily_challenge_takes_one_submission_per_player_per_day() {
+    let mut env = setup(None).await;
+    let admin = env.admin.insecure_clone();
+    let player = env.player.insecure_clone();
+    let program_id = env.program_id;
+    let server = Keypair::new();
+    env.set_config(Config {
+        game_server: server.pubkey(),
+        ..env.config()
+    });
+    let clock: Clock = env.context.banks_client.get_sysvar().await.unwrap();
+    let today = clock.unix_timestamp.div_euclid(SECONDS_PER_DAY);
+
+    let publish = |date: i64| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::PublishDailyChallenge {
+                seed: [3; 32],
+                date,
+            }
+            .pack(),
+            vec![
+                AccountMeta::new(DailyChallengePda::find(&program_id, date).0, false),
+                AccountMeta::new(admin.pubkey(), true),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        )
+    };
+    let submit = |date: i64, score: u64| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::SubmitDailyChallenge { date, score }.pack(),
+            vec![
+                AccountMeta::new(DailyChallengePda::find(&program_id, date).0, false),
+                AccountMeta::new(
+                    DailySubmissionPda::find(&program_id, date, &player.pubkey()).0,
+                    false,
+                ),
+                AccountMeta::new(player.pubkey(), true),
+                AccountMeta::new_readonly(server.pubkey(), true),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(ban_list_address(&program_id), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        )
+    };
+
+    let result = env.process(publish(today - 1), &[&admin]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::NotTodaysChallenge)
+    );
+    env.process(publish(today), &[&admin]).await.unwrap();
+    env.process(publish(today + 1), &[&admin]).await.unwrap();
+
+    // Tomorrow's level is published but not open yet
+    let result = env.process(submit(today + 1, 300), &[&player, &server]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::NotTodaysChallenge)
+    );
+
+    env.process(submit(today, 300), &[&player, &server])
+        .await
+        .unwrap();
+    let result = env.process(submit(today, 900), &[&player, &server]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::DailyChallengeAlreadySubmitted)
+    );
+
+    let account = env
+        .context
+        .banks_client
+        .get_account(DailyChallengePda::find(&program_id, today).0)
+        .await
+        .unwrap()
+        .expect("daily challenge exists");
+    let daily = DailyChallenge::load(&account.data).unwrap();
+    assert_eq!((daily.day(), daily.seed), (today, [3; 32]));
+    assert_eq!(
+        daily.board.entries(),
+        &[LeaderboardEntry::new(player.pubkey(), 300)]
+    );
+}
+
+// A Pyth price account whose aggregate is trading at `price * 10^expo`
+fn price_account(price: i64, conf: u64, expo: i32, publish_time: i64) -> Account {
+    let mut feed: SolanaPriceAccount = bytemuck::Zeroable::zeroed();
+    feed.magic = MAGIC;
+    feed.ver = VERSION_2;
+    feed.atype = AccountType::Price as u32;
+    feed.expo = expo;
+    feed.timestamp = publish_time;
+    feed.agg = PriceInfo {
+        price,
+        conf,
+        status: PriceStatus::Trading,
+        ..PriceInfo::default()
+    };
+    Account {
+        lamports: 1_000_000_000,
+        data: bytemuck::bytes_of(&feed).to_vec(),
+        owner: Pubkey::new_unique(),
+        ..Account::default()
+    }
+}
+
+#[tokio::test]
+async fn ranked_run_fee_converts_usd_at_the_pyth_price() {
+    // $5 at $150 per SOL
+    const PRICE: i64 = 15_000_000_000;
+    const FEE: u64 = 33_333_334;
+    let mut env = setup(Some(10)).await;
+    let player = env.player.insecure_clone();
+    let program_id = env.program_id;
+    let feed = Pubkey::new_unique();
+    env.set_config(Config {
+        ranked_entry_fee: 5_000,
+        ranked_entry_fee_usd_cents: 500,
+        sol_usd_price_feed: feed,
+        max_price_age_secs: 60,
+        max_price_conf_bps: 100,
+        ..env.config()
+    });
+    let now = env
+        .context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .unix_timestamp;
+
+    let prize_pool = prize_pool_address(&program_id);
+    let start_ranked_run = |price_feed: Option<Pubkey>| {
+        let mut accounts = vec![
+            AccountMeta::new(score_address(&program_id, &player.pubkey()), false),
+            AccountMeta::new(player.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new(prize_pool, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(fee_vault_address(&program_id), false),
+        ];
+        accounts.extend(price_feed.map(|feed| AccountMeta::new_readonly(feed, false)));
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::StartRankedRun {
+                game_mode: GameMode::Arcade,
+            }
+            .pack(),
+            accounts,
+        )
+    };
+
+    let result = env.process(start_ranked_run(None), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        InstructionError::NotEnoughAccountKeys
+    );
+
+    let other_feed = Pubkey::new_unique();
+    env.context
+        .set_account(&other_feed, &price_account(PRICE, 0, -8, now).into());
+    let result = env
+        .process(start_ranked_run(Some(other_feed)), &[&player])
+        .await;
+    assert_eq!(instruction_error(result), InstructionError::InvalidArgument);
+
+    env.context
+        .set_account(&feed, &price_account(PRICE, 0, -8, now - 61).into());
+    let result = env.process(start_ranked_run(Some(feed)), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::StalePrice)
+    );
+
+    // A confidence interval of 2% of the price is over the 1% limit
+    env.context.set_account(
+        &feed,
+        &price_account(PRICE, PRICE as u64 / 50, -8, now).into(),
+    );
+    let result = env.process(start_ranked_run(Some(feed)), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::PriceTooUncertain)
+    );
+
+    env.context.set_account(
+        &feed,
+        &price_account(PRICE, PRICE as u64 / 200, -8, now).into(),
+    );
+    env.process(start_ranked_run(Some(feed)), &[&player])
+        .await
+        .unwrap();
+    let rent = env.context.banks_client.get_rent().await.unwrap();
+    assert_eq!(env.balance(prize_pool).await, rent.minimum_balance(0) + FEE);
+    assert!(env.score().await.ranked_run_open);
+}
+
+#[tokio::test]
+async fn distribute_prizes_pays_top_places_after_season_end() {
+    let mut env = setup(None).await;
+    let program_id = env.program_id;
+    let first = env.player.pubkey();
+    let second = env.admin.pubkey();
+    env.set_config(Config {
+        prize_split_bps: Config::DEFAULT_PRIZE_SPLIT_BPS,
+        ..env.config()
+    });
+
+    let leaderboard = leaderboard_address(&program_id);
+    let entries = vec![
+        LeaderboardEntry::new(first, 900),
+        LeaderboardEntry::new(second, 400),
+    ];
+    env.context.set_account(
+        &leaderboard,
+        &leaderboard_account(&program_id, entries).into(),
+    );
+    let rent = env.context.banks_client.get_rent().await.unwrap();
+    let prize_pool = prize_pool_address(&program_id);
+    env.context.set_account(
+        &prize_pool,
+        &Account {
+            lamports: rent.minimum_balance(0) + 1_000,
+            owner: program_id,
+            ..Account::default()
+        }
+        .into(),
+    );
+    let set_season = |env: &mut TestEnv, end_ts: i64| {
+        let mut data = vec![0u8; Season::LEN];
+        Season {
+            is_initialized: true,
+            season_id: 1,
+            start_ts: 0,
+            end_ts,
+            prizes_distributed: false,
+        }
+        .pack(&mut data)
+        .unwrap();
+        env.context.set_account(
+            &season_address(&program_id),
+            &program_account(&program_id, data).into(),
+        );
+    };
+
+    let distribute = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::DistributePrizes.pack(),
+        vec![
+            AccountMeta::new(leaderboard, false),
+            AccountMeta::new(season_address(&program_id), false),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new(prize_pool, false),
+            AccountMeta::new(first, false),
+            AccountMeta::new(second, false),
+        ],
+    );
+
+    set_season(&mut env, i64::MAX);
+    let result = env.process(distribute.clone(), &[]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::SeasonNotOver)
+    );
+
+    set_season(&mut env, 1);
+    let first_before = env.balance(first).await;
+    let second_before = env.balance(second).await;
+    env.process(distribute.clone(), &[]).await.unwrap();
+
+    // Two entries take 50% and 30%; the unclaimed third-place share stays in the pool
+    assert_eq!(env.balance(first).await, first_before + 500);
+    assert_eq!(env.balance(second).await, second_before + 300);
+    assert_eq!(env.balance(prize_pool).await, rent.minimum_balance(0) + 200);
+    let board = env
+        .context
+        .banks_client
+        .get_account(leaderboard)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(Leaderboard::load(&board.data).unwrap().entries().is_empty());
+
+    let result = env.process(distribute, &[]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::PrizesAlreadyDistributed)
+    );
+}
+
+#[tokio::test]
+async fn vested_prizes_unlock_over_time() {
+    const PRIZE: u64 = 1_000_000_000;
+    let mut env = setup(None).await;
+    let program_id = env.program_id;
+    let winner = env.player.pubkey();
+    env.set_config(Config {
+        prize_split_bps: [10_000, 0, 0],
+        prize_vesting_cliff_secs: 100,
+        prize_vesting_secs: 1_000,
+        ..env.config()
+    });
+
+    let leaderboard = leaderboard_address(&program_id);
+    env.context.set_account(
+        &leaderboard,
+        &leaderboard_account(&program_id, vec![LeaderboardEntry::new(winner, 900)]).into(),
+    );
+    let rent = env.context.banks_client.get_rent().await.unwrap();
+    let prize_pool = prize_pool_address(&program_id);
+    env.context.set_account(
+        &prize_pool,
+        &Account {
+            lamports: rent.minimum_balance(0) + PRIZE,
+            owner: program_id,
+            ..Account::default()
+        }
+        .into(),
+    );
+    let mut data = vec![0u8; Season::LEN];
+    Season {
+        is_initialized: true,
+        season_id: 4,
+        start_ts: 0,
+        end_ts: 1,
+        prizes_distributed: false,
+    }
+    .pack(&mut data)
+    .unwrap();
+    env.context.set_account(
+        &season_address(&program_id),
+        &program_account(&program_id, data).into(),
+    );
+
+    let (vesting, _) = VestingPda::find(&program_id, 4, &winner);
+    let distribute = |with_system_program: bool| {
+        let mut accounts = vec![
+            AccountMeta::new(leaderboard, false),
+            AccountMeta::new(season_address(&program_id), false),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new(prize_pool, false),
+        ];
+        if with_system_program {
+            accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+        }
+        accounts.push(AccountMeta::new(winner, false));
+        accounts.push(AccountMeta::new(vesting, false));
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::DistributePrizes.pack(),
+            accounts,
+        )
+    };
+    let result = env.process(distribute(false), &[]).await;
+    assert_eq!(
+        instruction_error(result),
+        InstructionError::IncorrectProgramId
+    );
+
+    let winner_before = env.balance(winner).await;
+    env.process(distribute(true), &[]).await.unwrap();
+    assert_eq!(env.balance(winner).await, winner_before);
+    assert_eq!(env.balance(prize_pool).await, rent.minimum_balance(0));
+    // The program allocated and owns the vesting account, which holds the whole
+    // prize and stays rent exempt out of it
+    let account = env.context.banks_client.get_account(vesting).await.unwrap();
+    let account = account.expect("vesting exists");
+    assert_eq!(account.owner, program_id);
+    assert_eq!(account.data.len(), Vesting::LEN);
+    assert_eq!(account.lamports, PRIZE);
+    assert!(rent.is_exempt(account.lamports, account.data.len()));
+    let state = Vesting::unpack(&account.data).unwrap();
+    let total = PRIZE - rent.minimum_balance(Vesting::LEN);
+    assert_eq!(state.beneficiary, winner);
+    assert_eq!(state.total, total);
+    assert_eq!(state.cliff_ts, state.start_ts + 100);
+    assert_eq!(state.end_ts, state.start_ts + 1_000);
+
+    let claim = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::ClaimVested.pack(),
+        vec![
+            AccountMeta::new(vesting, false),
+            AccountMeta::new(winner, true),
+        ],
+    );
+    let player = env.player.insecure_clone();
+    let result = env.process(claim.clone(), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::NothingVested)
+    );
+
+    // Halfway through, half the prize has unlocked
+    let mut clock: Clock = env.context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp = state.start_ts + 500;
+    env.context.set_sysvar(&clock);
+    env.process(claim.clone(), &[&player]).await.unwrap();
+    assert_eq!(env.balance(winner).await, winner_before + total / 2);
+
+    // The last claim pays the rest and closes the account, refunding its rent
+    clock.unix_timestamp = state.end_ts;
+    env.context.set_sysvar(&clock);
+    env.process(claim, &[&player]).await.unwrap();
+    assert_eq!(env.balance(winner).await, winner_before + PRIZE);
+    assert!(env
+        .context
+        .banks_client
+        .get_account(vesting)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn cranks_pay_out_and_roll_the_season_over_for_a_tip() {
+    const TIP: u64 = 5_000;
+    let mut env = setup(None).await;
+    let program_id = env.program_id;
+    let winner = env.player.pubkey();
+    let cranker = Keypair::new();
+    env.set_config(Config {
+        prize_split_bps: Config::DEFAULT_PRIZE_SPLIT_BPS,
+        season_length_secs: 1_000,
+        crank_tip_lamports: TIP,
+        ..env.config()
+    });
+
+    let leaderboard = leaderboard_address(&program_id);
+    env.context.set_account(
+        &leaderboard,
+        &leaderboard_account(&program_id, vec![LeaderboardEntry::new(winner, 900)]).into(),
+    );
+    let rent = env.context.banks_client.get_rent().await.unwrap();
+    let prize_pool = prize_pool_address(&program_id);
+    env.context.set_account(
+        &prize_pool,
+        &Account {
+            lamports: rent.minimum_balance(0) + 1_000,
+            owner: program_id,
+            ..Account::default()
+        }
+        .into(),
+    );
+    let mut data = vec![0u8; Season::LEN];
+    Season {
+        is_initialized: true,
+        season_id: 1,
+        start_ts: 0,
+        end_ts: 1,
+        prizes_distributed: false,
+    }
+    .pack(&mut data)
+    .unwrap();
+    let season = season_address(&program_id);
+    env.context
+        .set_account(&season, &program_account(&program_id, data).into());
+    // Enough for one full tip and part of another
+    let treasury = Pubkey::find_program_address(&[b"treasury"], &program_id).0;
+    env.context.set_account(
+        &treasury,
+        &Account {
+            lamports: rent.minimum_balance(0) + TIP + 2_000,
+            owner: system_program::id(),
+            ..Account::default()
+        }
+        .into(),
+    );
+    env.context.set_account(
+        &cranker.pubkey(),
+        &Account {
+            lamports: 1_000_000_000,
+            owner: system_program::id(),
+            ..Account::default()
+        }
+        .into(),
+    );
+
+    let rollover = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::CrankSeasonRollover.pack(),
+        vec![
+            AccountMeta::new(season, false),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new(cranker.pubkey(), true),
+            AccountMeta::new(treasury, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    let finalize = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::CrankLeaderboardFinalize.pack(),
+        vec![
+            AccountMeta::new(leaderboard, false),
+            AccountMeta::new(season, false),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new(prize_pool, false),
+            AccountMeta::new(cranker.pubkey(), true),
+            AccountMeta::new(treasury, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(winner, false),
+        ],
+    );
+
+    let result = env.process(rollover.clone(), &[&cranker]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::PrizesNotDistributed)
+    );
+
+    let winner_before = env.balance(winner).await;
+    let cranker_before = env.balance(cranker.pubkey()).await;
+    env.process(finalize, &[&cranker]).await.unwrap();
+    assert_eq!(env.balance(winner).await, winner_before + 500);
+    assert_eq!(env.balance(cranker.pubkey()).await, cranker_before + TIP);
+
+    // The treasury only has part of the second tip left
+    let cranker_before = env.balance(cranker.pubkey()).await;
+    env.process(rollover.clone(), &[&cranker]).await.unwrap();
+    assert_eq!(env.balance(cranker.pubkey()).await, cranker_before + 2_000);
+    assert_eq!(env.balance(treasury).await, rent.minimum_balance(0));
+    let clock: Clock = env.context.banks_client.get_sysvar().await.unwrap();
+    let account = env
+        .context
+        .banks_client
+        .get_account(season)
+        .await
+        .unwrap()
+        .unwrap();
+    let next = Season::unpack(&account.data).unwrap();
+    assert_eq!(next.season_id, 2);
+    assert_eq!(next.end_ts, next.start_ts + 1_000);
+    assert!(next.start_ts <= clock.unix_timestamp);
+    assert!(!next.prizes_distributed);
+
+    let result = env.process(rollover, &[&cranker]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::SeasonNotOver)
+    );
+}
+
+#[tokio::test]
+async fn next_season_waits_for_the_prizes() {
+    let mut env = setup(None).await;
+    let program_id = env.program_id;
+    let admin = env.admin.insecure_clone();
+    let rent = env.context.banks_client.get_rent().await.unwrap();
+    let season = season_address(&program_id);
+    let prize_pool = prize_pool_address(&program_id);
+    let set_season = |env: &mut TestEnv, season_id: u64, prizes_distributed: bool| {
+        let mut data = vec![0u8; Season::LEN];
+        Season {
+            is_initialized: true,
+            season_id,
+            start_ts: 0,
+            end_ts: 1,
+            prizes_distributed,
+        }
+        .pack(&mut data)
+        .unwrap();
+        env.context
+            .set_account(&season, &program_account(&program_id, data).into());
+    };
+    let set_pool = |env: &mut TestEnv, prizes: u64| {
+        env.context.set_account(
+            &prize_pool,
+            &Account {
+                lamports: rent.minimum_balance(0) + prizes,
+                owner: program_id,
+                ..Account::default()
+            }
+            .into(),
+        );
+    };
+    let start_season = |end_ts: i64| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::StartSeason { end_ts }.pack(),
+            vec![
+                AccountMeta::new(season, false),
+                AccountMeta::new(admin.pubkey(), true),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(prize_pool, false),
+            ],
+        )
+    };
+    let season_id = |account: Account| Season::unpack(&account.data).unwrap().season_id;
+
+    set_season(&mut env, 1, false);
+    set_pool(&mut env, 1_000);
+    let result = env.process(start_season(i64::MAX), &[&admin]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::PrizesNotDistributed)
+    );
+
+    set_season(&mut env, 1, true);
+    env.process(start_season(i64::MAX), &[&admin])
+        .await
+        .unwrap();
+    let account = env
+        .context
+        .banks_client
+        .get_account(season)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(season_id(account), 2);
+
+    // A season nobody paid into has nothing to distribute
+    set_season(&mut env, 2, false);
+    set_pool(&mut env, 0);
+    env.process(start_season(i64::MAX - 1), &[&admin])
+        .await
+        .unwrap();
+    let account = env
+        .context
+        .banks_client
+        .get_account(season)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(season_id(account), 3);
+}
+
+#[tokio::test]
+async fn treasury_withdrawals_are_admin_only_and_capped_per_epoch() {
+    let mut env = setup(None).await;
+    let program_id = env.program_id;
+    let admin = env.admin.insecure_clone();
+    let player = env.player.insecure_clone();
+    let destination = Pubkey::new_unique();
+    env.set_config(Config {
+        treasury_withdraw_cap_lamports: 3_000,
+        ..env.config()
+    });
+    let rent = env.context.banks_client.get_rent().await.unwrap();
+    let treasury = Pubkey::find_program_address(&[b"treasury"], &program_id).0;
+    env.context.set_account(
+        &treasury,
+        &Account {
+            lamports: rent.minimum_balance(0) + 10_000,
+            owner: system_program::id(),
+            ..Account::default()
+        }
+        .into(),
+    );
+    env.context.set_account(
+        &destination,
+        &Account::new(1_000_000_000, 0, &system_program::id()).into(),
+    );
+    let withdraw = |authority: Pubkey, amount: u64| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::WithdrawTreasury { amount }.pack(),
+            vec![
+                AccountMeta::new(config_address(&program_id), false),
+                AccountMeta::new_readonly(authority, true),
+                AccountMeta::new(treasury, false),
+                AccountMeta::new(destination, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        )
+    };
+
+    let result = env
+        .process(withdraw(player.pubkey(), 1_000), &[&player])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::Unauthorized)
+    );
+
+    env.process(withdraw(admin.pubkey(), 2_000), &[&admin])
+        .await
+        .unwrap();
+    assert_eq!(env.balance(destination).await, 1_000_000_000 + 2_000);
+    let result = env
+        .process(withdraw(admin.pubkey(), 1_001), &[&admin])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::TreasuryCapExceeded)
+    );
+    env.process(withdraw(admin.pubkey(), 1_000), &[&admin])
+        .await
+        .unwrap();
+    assert_eq!(env.balance(destination).await, 1_000_000_000 + 3_000);
+    let account = env
+        .context
+        .banks_client
+        .get_account(config_address(&program_id))
+        .await
+        .unwrap()
+        .unwrap();
+    let config = Config::unpack(&account.data).unwrap();
+    assert_eq!(config.treasury_withdrawn_lamports, 3_000);
+
+    // The rent-exempt minimum stays put whatever the cap
+    env.set_config(Config {
+        treasury_withdraw_cap_lamports: 0,
+        ..env.config()
+    });
+    let result = env
+        .process(withdraw(admin.pubkey(), 7_001), &[&admin])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        InstructionError::InsufficientFunds
+    );
+}
+
+#[tokio::test]
+async fn partner_programs_gate_on_score_by_cpi() {
+    let mut env = setup(Some(500)).await;
+    let program_id = env.program_id;
+    let player = env.player.pubkey();
+    let gate = |threshold: u64| {
+        Instruction::new_with_bytes(
+            GATE_PROGRAM_ID,
+            &threshold.to_le_bytes(),
+            vec![
+                AccountMeta::new_readonly(program_id, false),
+                AccountMeta::new_readonly(score_address(&program_id, &player), false),
+                AccountMeta::new_readonly(player, false),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(season_address(&program_id), false),
+            ],
+        )
+    };
+
+    env.process(gate(500), &[]).await.unwrap();
+    let result = env.process(gate(501), &[]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::ScoreBelowThreshold)
+    );
+
+    // Simulations see the score as return data, even when it falls short
+    let verify = |threshold: u64| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::VerifyScoreAtLeast { threshold }.pack(),
+            vec![
+                AccountMeta::new_readonly(score_address(&program_id, &player), false),
+                AccountMeta::new_readonly(player, false),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(season_address(&program_id), false),
+            ],
+        )
+    };
+    for threshold in [0, 501] {
+        let blockhash = env.context.get_new_latest_blockhash().await.unwrap();
+        let transaction = Transaction::new_signed_with_payer(
+            &[verify(threshold)],
+            Some(&env.context.payer.pubkey()),
+            &[&env.context.payer],
+            blockhash,
+        );
+        let simulation = env
+            .context
+            .banks_client
+            .simulate_transaction(transaction)
+            .await
+            .unwrap();
+        let return_data = simulation.simulation_details.unwrap().return_data.unwrap();
+        assert_eq!(return_data.program_id, program_id);
+        assert_eq!(u64::try_from_slice(&return_data.data).unwrap(), 500);
+    }
+
+    // Another player's wallet does not match the score account
+    let result = env
+        .process(
+            Instruction::new_with_bytes(
+                program_id,
+                &GarbageGrabInstruction::VerifyScoreAtLeast { threshold: 0 }.pack(),
+                vec![
+                    AccountMeta::new_readonly(score_address(&program_id, &player), false),
+                    AccountMeta::new_readonly(env.admin.pubkey(), false),
+                    AccountMeta::new_readonly(config_address(&program_id), false),
+                    AccountMeta::new_readonly(season_address(&program_id), false),
+                ],
+            ),
+            &[],
+        )
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::InvalidPda)
+    );
+
+    // A score from an earlier season no longer counts once a new one starts
+    let mut data = vec![0u8; Season::LEN];
+    Season {
+        is_initialized: true,
+        season_id: 1,
+        start_ts: 0,
+        end_ts: i64::MAX,
+        prizes_distributed: false,
+    }
+    .pack(&mut data)
+    .unwrap();
+    env.context.set_account(
+        &season_address(&program_id),
+        &program_account(&program_id, data).into(),
+    );
+    let result = env.process(gate(1), &[]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::ScoreBelowThreshold)
+    );
+}
+
+#[tokio::test]
+async fn wager_match_pays_pot_to_recorded_winner() {
+    const STAKE: u64 = 50_000;
+    let mut env = setup(None).await;
+    let program_id = env.program_id;
+    let player_a = env.player.insecure_clone();
+    let player_b = env.admin.insecure_clone();
+    let server = Keypair::new();
+    env.set_config(Config {
+        game_server: server.pubkey(),
+        ..env.config()
+    });
+
+    let match_id = 1u64;
+    let match_account = Pubkey::find_program_address(
+        &[
+            b"match",
+            player_a.pubkey().as_ref(),
+            player_b.pubkey().as_ref(),
+            &match_id.to_le_bytes(),
+        ],
+        &program_id,
+    )
+    .0;
+    let create = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::CreateMatch {
+            match_id,
+            stake: STAKE,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(match_account, false),
+            AccountMeta::new(player_a.pubkey(), true),
+            AccountMeta::new(player_b.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    let submit_result = |authority: Pubkey, winner: Pubkey| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::SubmitMatchResult { winner }.pack(),
+            vec![
+                AccountMeta::new(match_account, false),
+                AccountMeta::new_readonly(authority, true),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+            ],
+        )
+    };
+    let settle = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::SettleMatch.pack(),
+        vec![
+            AccountMeta::new(match_account, false),
+            AccountMeta::new(player_b.pubkey(), false),
+            AccountMeta::new(player_a.pubkey(), false),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new(fee_vault_address(&program_id), false),
+            AccountMeta::new_readonly(BetPoolPda::find(&program_id, &match_account).0, false),
+        ],
+    );
+
+    let b_before = env.balance(player_b.pubkey()).await;
+    env.process(create, &[&player_a, &player_b]).await.unwrap();
+    assert_eq!(env.balance(player_b.pubkey()).await, b_before - STAKE);
+
+    // Settling needs a result, and only the game server may record one
+    let result = env.process(settle.clone(), &[]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::InvalidMatchState)
+    );
+    let as_player = submit_result(player_a.pubkey(), player_a.pubkey());
+    let result = env.process(as_player, &[&player_a]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::Unauthorized)
+    );
+    let outsider = submit_result(server.pubkey(), Pubkey::new_unique());
+    let result = env.process(outsider, &[&server]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::InvalidWinner)
+    );
+    env.process(
+        submit_result(server.pubkey(), player_b.pubkey()),
+        &[&server],
+    )
+    .await
+    .unwrap();
+
+    let a_before = env.balance(player_a.pubkey()).await;
+    let match_lamports = env.balance(match_account).await;
+    env.process(settle, &[]).await.unwrap();
+    assert_eq!(env.balance(player_b.pubkey()).await, b_before + STAKE);
+    assert_eq!(
+        env.balance(player_a.pubkey()).await,
+        a_before + match_lamports - 2 * STAKE
+    );
+    assert_eq!(env.balance(match_account).await, 0);
+}
+
+#[tokio::test]
+async fn house_fee_accrues_to_the_fee_vault_and_is_withdrawable() {
+    const STAKE: u64 = 10_000_000;
+    let mut env = setup(None).await;
+    let program_id = env.program_id;
+    let player_a = env.player.insecure_clone();
+    let player_b = env.admin.insecure_clone();
+    let server = Keypair::new();
+    env.set_config(Config {
+        game_server: server.pubkey(),
+        fee_bps: 1_000,
+        ..env.config()
+    });
+
+    let match_id = 1u64;
+    let match_account = Pubkey::find_program_address(
+        &[
+            b"match",
+            player_a.pubkey().as_ref(),
+            player_b.pubkey().as_ref(),
+            &match_id.to_le_bytes(),
+        ],
+        &program_id,
+    )
+    .0;
+    let create = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::CreateMatch {
+            match_id,
+            stake: STAKE,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(match_account, false),
+            AccountMeta::new(player_a.pubkey(), true),
+            AccountMeta::new(player_b.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    let submit_result = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::SubmitMatchResult {
+            winner: player_b.pubkey(),
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(match_account, false),
+            AccountMeta::new_readonly(server.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+        ],
+    );
+    let settle = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::SettleMatch.pack(),
+        vec![
+            AccountMeta::new(match_account, false),
+            AccountMeta::new(player_b.pubkey(), false),
+            AccountMeta::new(player_a.pubkey(), false),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new(fee_vault_address(&program_id), false),
+            AccountMeta::new_readonly(BetPoolPda::find(&program_id, &match_account).0, false),
+        ],
+    );
+    env.process(create, &[&player_a, &player_b]).await.unwrap();
+    env.process(submit_result, &[&server]).await.unwrap();
+    let b_before = env.balance(player_b.pubkey()).await;
+    env.process(settle, &[]).await.unwrap();
+
+    // A tenth of the pot goes to the vault, the rest to the winner
+    let fee = 2 * STAKE / 10;
+    let fee_vault = fee_vault_address(&program_id);
+    assert_eq!(
+        env.balance(player_b.pubkey()).await,
+        b_before + 2 * STAKE - fee
+    );
+    assert_eq!(env.balance(fee_vault).await, fee);
+
+    let rent = env.context.banks_client.get_rent().await.unwrap();
+    let available = fee - rent.minimum_balance(0);
+    let destination = Pubkey::new_unique();
+    let withdraw = |admin: Pubkey, amount: u64| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::WithdrawFees { amount }.pack(),
+            vec![
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(admin, true),
+                AccountMeta::new(fee_vault, false),
+                AccountMeta::new(destination, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        )
+    };
+    let result = env
+        .process(withdraw(server.pubkey(), available), &[&server])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::Unauthorized)
+    );
+    let result = env
+        .process(withdraw(player_b.pubkey(), available + 1), &[&player_b])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        InstructionError::InsufficientFunds
+    );
+    env.process(withdraw(player_b.pubkey(), available), &[&player_b])
+        .await
+        .unwrap();
+    assert_eq!(env.balance(destination).await, available);
+    assert_eq!(env.balance(fee_vault).await, rent.minimum_balance(0));
+}
+
+#[tokio::test]
+async fn bets_pay_out_pro_rata_on_the_match_result() {
+    const STAKE: u64 = 50_000;
+    let mut env = setup(None).await;
+    let program_id = env.program_id;
+    let player_a = env.player.insecure_clone();
+    let player_b = env.admin.insecure_clone();
+    let server = Keypair::new();
+    env.set_config(Config {
+        game_server: server.pubkey(),
+        bet_fee_bps: 1_000,
+        ..env.config()
+    });
+    let rent = env.context.banks_client.get_rent().await.unwrap();
+    let treasury = Pubkey::find_program_address(&[b"treasury"], &program_id).0;
+    env.context.set_account(
+        &treasury,
+        &Account::new(rent.minimum_balance(0), 0, &system_program::id()).into(),
+    );
+    let [x, y, z] = [(); 3].map(|_| Keypair::new());
+    for bettor in [&x, &y, &z] {
+        env.context.set_account(
+            &bettor.pubkey(),
+            &Account::new(1_000_000_000, 0, &system_program::id()).into(),
+        );
+    }
+
+    let match_account = MatchPda::find(&program_id, &player_a.pubkey(), &player_b.pubkey(), 1).0;
+    let create = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::CreateMatch {
+            match_id: 1,
+            stake: STAKE,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(match_account, false),
+            AccountMeta::new(player_a.pubkey(), true),
+            AccountMeta::new(player_b.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    env.process(create, &[&player_a, &player_b]).await.unwrap();
+
+    let pool = BetPoolPda::find(&program_id, &match_account).0;
+    let bet_address = |bettor: Pubkey| BetPda::find(&program_id, &match_account, &bettor).0;
+    let place = |bettor: Pubkey, on: Pubkey, amount: u64| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::PlaceBet { on, amount }.pack(),
+            vec![
+                AccountMeta::new(pool, false),
+                AccountMeta::new(bet_address(bettor), false),
+                AccountMeta::new_readonly(match_account, false),
+                AccountMeta::new(bettor, true),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        )
+    };
+    let settle = |bettors: &[Pubkey]| {
+        let mut accounts = vec![
+            AccountMeta::new(pool, false),
+            AccountMeta::new_readonly(match_account, false),
+            AccountMeta::new(treasury, false),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+        ];
+        for bettor in bettors {
+            accounts.push(AccountMeta::new(bet_address(*bettor), false));
+            accounts.push(AccountMeta::new(*bettor, false));
+        }
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::SettleBets.pack(),
+            accounts,
+        )
+    };
+
+    let (a, b) = (player_a.pubkey(), player_b.pubkey());
+    env.process(place(x.pubkey(), a, 20_000), &[&x])
+        .await
+        .unwrap();
+    env.process(place(x.pubkey(), a, 10_000), &[&x])
+        .await
+        .unwrap();
+    env.process(place(y.pubkey(), a, 10_000), &[&y])
+        .await
+        .unwrap();
+    env.process(place(z.pubkey(), b, 60_000), &[&z])
+        .await
+        .unwrap();
+    // Bettors stay on one side and the players cannot bet on their own match
+    for (bettor, on) in [(&x, b), (&player_a, a)] {
+        let result = env
+            .process(place(bettor.pubkey(), on, 1_000), &[bettor])
+            .await;
+        assert_eq!(
+            instruction_error(result),
+            custom(GarbageGrabError::InvalidBet)
+        );
+    }
+    let result = env.process(settle(&[x.pubkey()]), &[]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::InvalidMatchState)
+    );
+
+    let submit_result = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::SubmitMatchResult { winner: a }.pack(),
+        vec![
+            AccountMeta::new(match_account, false),
+            AccountMeta::new_readonly(server.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+        ],
+    );
+    env.process(submit_result, &[&server]).await.unwrap();
+    let result = env.process(place(z.pubkey(), b, 1_000), &[&z]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::BettingClosed)
+    );
+
+    // Closing the match first would leave the pool without a result to pay out on
+    let settle_match = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::SettleMatch.pack(),
+        vec![
+            AccountMeta::new(match_account, false),
+            AccountMeta::new(a, false),
+            AccountMeta::new(a, false),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new(fee_vault_address(&program_id), false),
+            AccountMeta::new_readonly(pool, false),
+        ],
+    );
+    let (pot, a_before) = (env.balance(match_account).await, env.balance(a).await);
+    let result = env.process(settle_match.clone(), &[]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::InvalidMatchState)
+    );
+    assert_eq!(env.balance(match_account).await, pot);
+    assert_eq!(env.balance(a).await, a_before);
+
+    // 10% of the 100_000 staked goes to the house; backers of a share the rest 3:1
+    let bet_rent = rent.minimum_balance(Bet::LEN);
+    let x_before = env.balance(x.pubkey()).await;
+    env.process(settle(&[x.pubkey()]), &[]).await.unwrap();
+    assert_eq!(env.balance(x.pubkey()).await, x_before + 67_500 + bet_rent);
+    assert_eq!(
+        env.balance(treasury).await,
+        rent.minimum_balance(0) + 10_000
+    );
+    // Once the pool holds the result the match can go
+    env.process(settle_match, &[]).await.unwrap();
+    assert!(env
+        .context
+        .banks_client
+        .get_account(match_account)
+        .await
+        .unwrap()
+        .is_none());
+
+    let y_before = env.balance(y.pubkey()).await;
+    let z_before = env.balance(z.pubkey()).await;
+    env.process(settle(&[y.pubkey(), z.pubkey()]), &[])
+        .await
+        .unwrap();
+    assert_eq!(env.balance(y.pubkey()).await, y_before + 22_500 + bet_rent);
+    assert_eq!(env.balance(z.pubkey()).await, z_before + bet_rent);
+    // The last bet closes the pool, its rent going to the treasury
+    assert_eq!(env.balance(pool).await, 0);
+    assert_eq!(
+        env.balance(treasury).await,
+        rent.minimum_balance(0) + 10_000 + rent.minimum_balance(BetPool::LEN)
+    );
+}
+
+#[tokio::test]
+async fn lobby_pairs_waiting_players_into_a_match() {
+    const STAKE: u64 = 40_000;
+    let mut env = setup(None).await;
+    let program_id = env.program_id;
+    let host = env.admin.insecure_clone();
+    let first = env.player.insecure_clone();
+    let [second, third] = [(); 2].map(|_| Keypair::new());
+    for player in [&second, &third] {
+        env.context.set_account(
+            &player.pubkey(),
+            &Account::new(1_000_000_000, 0, &system_program::id()).into(),
+        );
+    }
+
+    let lobby = LobbyPda::find(&program_id, &host.pubkey()).0;
+    let create = |capacity: u8| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::CreateLobby {
+                capacity,
+                stake: STAKE,
+            }
+            .pack(),
+            vec![
+                AccountMeta::new(lobby, false),
+                AccountMeta::new(host.pubkey(), true),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        )
+    };
+    let join_or_leave = |ix: GarbageGrabInstruction, player: Pubkey| {
+        let mut accounts = vec![
+            AccountMeta::new(lobby, false),
+            AccountMeta::new(player, true),
+        ];
+        if ix == GarbageGrabInstruction::JoinLobby {
+            accounts.push(AccountMeta::new_readonly(
+                config_address(&program_id),
+                false,
+            ));
+            accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+        }
+        Instruction::new_with_bytes(program_id, &ix.pack(), accounts)
+    };
+    let join = |player: Pubkey| join_or_leave(GarbageGrabInstruction::JoinLobby, player);
+    let leave = |player: Pubkey| join_or_leave(GarbageGrabInstruction::LeaveLobby, player);
+    let match_address =
+        |a: Pubkey, b: Pubkey, match_id: u64| MatchPda::find(&program_id, &a, &b, match_id).0;
+    let start = |player_a: Pubkey, match_account: Pubkey| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::StartMatch.pack(),
+            vec![
+                AccountMeta::new(lobby, false),
+                AccountMeta::new(match_account, false),
+                AccountMeta::new(player_a, true),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        )
+    };
+
+    let result = env.process(create(1), &[&host]).await;
+    assert_eq!(instruction_error(result), InstructionError::InvalidArgument);
+    env.process(create(2), &[&host]).await.unwrap();
+
+    // Joining escrows the stake; leaving hands it back
+    let before = env.balance(first.pubkey()).await;
+    env.process(join(first.pubkey()), &[&first]).await.unwrap();
+    assert_eq!(env.balance(first.pubkey()).await, before - STAKE);
+    let result = env.process(join(first.pubkey()), &[&first]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::AlreadyInLobby)
+    );
+    let result = env.process(leave(third.pubkey()), &[&third]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::NotInLobby)
+    );
+    env.process(join(third.pubkey()), &[&third]).await.unwrap();
+    let result = env.process(join(second.pubkey()), &[&second]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::LobbyFull)
+    );
+    let before = env.balance(third.pubkey()).await;
+    env.process(leave(third.pubkey()), &[&third]).await.unwrap();
+    assert_eq!(env.balance(third.pubkey()).await, before + STAKE);
+    env.process(join(second.pubkey()), &[&second])
+        .await
+        .unwrap();
+
+    // Only the player who waited longest can start their match
+    let match_account = match_address(first.pubkey(), second.pubkey(), 0);
+    let result = env
+        .process(start(second.pubkey(), match_account), &[&second])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::Unauthorized)
+    );
+    let lobby_before = env.balance(lobby).await;
+    env.process(start(first.pubkey(), match_account), &[&first])
+        .await
+        .unwrap();
+    assert_eq!(env.balance(lobby).await, lobby_before - 2 * STAKE);
+    let account = env
+        .context
+        .banks_client
+        .get_account(match_account)
+        .await
+        .unwrap()
+        .unwrap();
+    let state = Match::unpack(&account.data).unwrap();
+    assert_eq!(
+        (state.player_a, state.player_b, state.stake, state.match_id),
+        (first.pubkey(), second.pubkey(), STAKE, 0)
+    );
+    let account = env
+        .context
+        .banks_client
+        .get_account(lobby)
+        .await
+        .unwrap()
+        .unwrap();
+    let waiting = Lobby::unpack(&account.data).unwrap();
+    assert!(waiting.players().is_empty());
+    assert_eq!(waiting.matches_started, 1);
+
+    // An emptied lobby has nobody left to pair
+    let result = env
+        .process(
+            start(
+                first.pubkey(),
+                match_address(first.pubkey(), second.pubkey(), 1),
+            ),
+            &[&first],
+        )
+        .await;
+    assert_eq!(instruction_error(result), InstructionError::InvalidArgument);
+}
+
+#[tokio::test]
+async fn tournament_collects_fees_and_pays_standings() {
+    const FEE: u64 = 10_000;
+    let mut env = setup(None).await;
+    let program_id = env.program_id;
+    let admin = env.admin.insecure_clone();
+    let player = env.player.insecure_clone();
+    let latecomer = Keypair::new();
+    env.context.set_account(
+        &latecomer.pubkey(),
+        &Account::new(1_000_000_000, 0, &system_program::id()).into(),
+    );
+    let server = Keypair::new();
+    let config = Config {
+        game_server: server.pubkey(),
+        prize_split_bps: Config::DEFAULT_PRIZE_SPLIT_BPS,
+        ..env.config()
+    };
+    env.set_config(config);
+
+    let tournament_id = 9u64;
+    let tournament =
+        Pubkey::find_program_address(&[b"tournament", &tournament_id.to_le_bytes()], &program_id).0;
+    let create = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::CreateTournament {
+            tournament_id,
+            entry_fee: FEE,
+            max_participants: 2,
+            end_ts: i64::MAX,
+            collection: Pubkey::default(),
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(tournament, false),
+            AccountMeta::new(admin.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    env.process(create, &[&admin]).await.unwrap();
+
+    let register = |player: Pubkey| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::Register.pack(),
+            vec![
+                AccountMeta::new(tournament, false),
+                AccountMeta::new(player, true),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new(fee_vault_address(&program_id), false),
+            ],
+        )
+    };
+    env.process(register(player.pubkey()), &[&player])
+        .await
+        .unwrap();
+    let result = env.process(register(player.pubkey()), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::AlreadyRegistered)
+    );
+    env.process(register(admin.pubkey()), &[&admin])
+        .await
+        .unwrap();
+    let result = env
+        .process(register(latecomer.pubkey()), &[&latecomer])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::TournamentFull)
+    );
+
+    let submit = |player: Pubkey, score: u64| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::SubmitTournamentScore { score }.pack(),
+            vec![
+                AccountMeta::new(tournament, false),
+                AccountMeta::new_readonly(player, false),
+                AccountMeta::new_readonly(server.pubkey(), true),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+            ],
+        )
+    };
+
+    // Nobody registers or scores while the program is paused, and the
+    // tournament is left as it was
+    let account = env.context.banks_client.get_account(tournament).await;
+    let before = account.unwrap().expect("tournament exists");
+    env.set_config(Config {
+        paused: true,
+        ..config
+    });
+    let result = env
+        .process(register(latecomer.pubkey()), &[&latecomer])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::ProgramPaused)
+    );
+    let result = env.process(submit(admin.pubkey(), 300), &[&server]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::ProgramPaused)
+    );
+    let account = env.context.banks_client.get_account(tournament).await;
+    assert_eq!(account.unwrap().expect("tournament exists"), before);
+    env.set_config(config);
+
+    env.process(submit(admin.pubkey(), 300), &[&server])
+        .await
+        .unwrap();
+    env.process(submit(player.pubkey(), 500), &[&server])
+        .await
+        .unwrap();
+    let result = env
+        .process(submit(latecomer.pubkey(), 900), &[&server])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::NotRegistered)
+    );
+
+    let finalize = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::FinalizeTournament.pack(),
+        vec![
+            AccountMeta::new(tournament, false),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new(admin.pubkey(), false),
+            AccountMeta::new(player.pubkey(), false),
+            AccountMeta::new(admin.pubkey(), false),
+        ],
+    );
+    let result = env.process(finalize.clone(), &[]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::TournamentInProgress)
+    );
+
+    // End the tournament by moving its end time into the past
+    let mut account = env
+        .context
+        .banks_client
+        .get_account(tournament)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut state = Tournament::unpack(&account.data).unwrap();
+    state.end_ts = 1;
+    state.pack(&mut account.data).unwrap();
+    env.context.set_account(&tournament, &account.into());
+
+    let player_before = env.balance(player.pubkey()).await;
+    let admin_before = env.balance(admin.pubkey()).await;
+    env.process(finalize, &[]).await.unwrap();
+
+    // First place takes 50% of the fees; second place's 30% and the unfilled
+    // third place's 20% both go to the admin, who is also the creator
+    assert_eq!(env.balance(player.pubkey()).await, player_before + FEE);
+    assert_eq!(env.balance(admin.pubkey()).await, admin_before + FEE);
+    let rent = env.context.banks_client.get_rent().await.unwrap();
+    assert_eq!(
+        env.balance(tournament).await,
+        rent.minimum_balance(Tournament::LEN)
+    );
+}
+
+#[tokio::test]
+async fn gated_tournament_registers_only_collection_holders() {
+    let mut env = setup(None).await;
+    let program_id = env.program_id;
+    let admin = env.admin.insecure_clone();
+    let player = env.player.insecure_clone();
+    let collection = Pubkey::new_unique();
+
+    let tournament_id = 10u64;
+    let tournament =
+        Pubkey::find_program_address(&[b"tournament", &tournament_id.to_le_bytes()], &program_id).0;
+    let create = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::CreateTournament {
+            tournament_id,
+            entry_fee: 0,
+            max_participants: 2,
+            end_ts: i64::MAX,
+            collection,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(tournament, false),
+            AccountMeta::new(admin.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    env.process(create, &[&admin]).await.unwrap();
+
+    let mint = Pubkey::new_unique();
+    let token = get_associated_token_address(&player.pubkey(), &mint);
+    let metadata = Metadata::find_pda(&mint).0;
+    env.context
+        .set_account(&token, &token_account(&mint, &player.pubkey(), 1).into());
+    env.context.set_account(
+        &metadata,
+        &metadata_account(&mint, &Pubkey::new_unique(), true).into(),
+    );
+    let mut register = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::Register.pack(),
+        vec![
+            AccountMeta::new(tournament, false),
+            AccountMeta::new(player.pubkey(), true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new(fee_vault_address(&program_id), false),
+        ],
+    );
+
+    // Without the NFT accounts there is nothing to check the collection against
+    let result = env.process(register.clone(), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        InstructionError::NotEnoughAccountKeys
+    );
+
+    // An NFT from another collection does not count
+    register.accounts.extend([
+        AccountMeta::new_readonly(token, false),
+        AccountMeta::new_readonly(metadata, false),
+    ]);
+    let result = env.process(register.clone(), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::CollectionNftRequired)
+    );
+
+    env.context.set_account(
+        &metadata,
+        &metadata_account(&mint, &collection, true).into(),
+    );
+    env.process(register, &[&player]).await.unwrap();
+    let account = env
+        .context
+        .banks_client
+        .get_account(tournament)
+        .await
+        .unwrap();
+    let state = Tournament::unpack(&account.expect("tournament exists").data).unwrap();
+    assert_eq!(state.participants.len(), 1);
+}
+
+#[tokio::test]
+async fn unlock_achievement_requires_game_server() {
+    let mut env = setup(Some(10)).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let server = Keypair::new();
+    env.set_config(Config {
+        game_server: server.pubkey(),
+        ..env.config()
+    });
+    let unlock = |authority: Pubkey| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::UnlockAchievement {
+                game_mode: GameMode::Arcade,
+                id: 5,
+            }
+            .pack(),
+            vec![
+                AccountMeta::new(score_address(&program_id, &player.pubkey()), false),
+                AccountMeta::new_readonly(player.pubkey(), false),
+                AccountMeta::new_readonly(authority, true),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+            ],
+        )
+    };
+
+    let result = env.process(unlock(player.pubkey()), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::Unauthorized)
+    );
+
+    env.process(unlock(server.pubkey()), &[&server])
+        .await
+        .unwrap();
+    assert!(env.score().await.has_achievement(5));
+
+    let result = env.process(unlock(server.pubkey()), &[&server]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::AchievementAlreadyUnlocked)
+    );
+}
+
+#[tokio::test]
+async fn inactive_scores_decay_before_the_update() {
+    let mut env = setup(None).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let address = score_address(&program_id, &player.pubkey());
+    env.set_config(Config {
+        decay_window_secs: 100,
+        decay_bps: 5_000,
+        ..env.config()
+    });
+
+    // Two and a half windows since the last update halves the score twice
+    let clock: Clock = env.context.banks_client.get_sysvar().await.unwrap();
+    let mut data = vec![0u8; ScoreAccount::LEN];
+    ScoreAccount {
+        is_initialized: true,
+        player: player.pubkey(),
+        score: 1_000,
+        best_score: 1_000,
+        version: ScoreAccount::VERSION,
+        bump: score_bump(&program_id, &player.pubkey()),
+        last_update_ts: clock.unix_timestamp - 250,
+        ..ScoreAccount::default()
+    }
+    .pack(&mut data)
+    .unwrap();
+    env.context
+        .set_account(&address, &program_account(&program_id, data).into());
+
+    env.process(env.update_score(address, true, 5), &[&player])
+        .await
+        .unwrap();
+    let score = env.score().await;
+    assert_eq!((score.score, score.best_score), (255, 1_000));
+}
+
+#[tokio::test]
+async fn score_checks_see_the_decayed_score() {
+    let mut env = setup(None).await;
+    let program_id = env.program_id;
+    let player = env.player.pubkey();
+    let address = score_address(&program_id, &player);
+    env.set_config(Config {
+        decay_window_secs: 100,
+        decay_bps: 5_000,
+        ..env.config()
+    });
+
+    let clock: Clock = env.context.banks_client.get_sysvar().await.unwrap();
+    let mut data = vec![0u8; ScoreAccount::LEN];
+    ScoreAccount {
+        is_initialized: true,
+        player,
+        score: 1_000,
+        best_score: 1_000,
+        version: ScoreAccount::VERSION,
+        bump: score_bump(&program_id, &player),
+        last_update_ts: clock.unix_timestamp - 250,
+        ..ScoreAccount::default()
+    }
+    .pack(&mut data)
+    .unwrap();
+    env.context
+        .set_account(&address, &program_account(&program_id, data).into());
+    let verify = |threshold: u64| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::VerifyScoreAtLeast { threshold }.pack(),
+            vec![
+                AccountMeta::new_readonly(address, false),
+                AccountMeta::new_readonly(player, false),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(season_address(&program_id), false),
+            ],
+        )
+    };
+
+    // The stored 1,000 has halved twice by now, so only 250 counts
+    env.process(verify(250), &[]).await.unwrap();
+    let result = env.process(verify(251), &[]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::ScoreBelowThreshold)
+    );
+    assert_eq!(env.score().await.score, 1_000);
+}
+
+#[tokio::test]
+async fn game_sessions_credit_plausible_scores_and_close() {
+    let mut env = setup(Some(10)).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let server = Keypair::new();
+    env.set_config(Config {
+        game_server: server.pubkey(),
+        max_score_per_slot: 100,
+        session_max_slots: 50,
+        ..env.config()
+    });
+
+    let score = score_address(&program_id, &player.pubkey());
+    let start = |seed: [u8; 32], server: Pubkey| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::StartSession {
+                game_mode: GameMode::Arcade,
+                seed,
+            }
+            .pack(),
+            vec![
+                AccountMeta::new(
+                    GameSessionPda::find(&program_id, &player.pubkey(), &seed).0,
+                    false,
+                ),
+                AccountMeta::new_readonly(score, false),
+                AccountMeta::new(player.pubkey(), true),
+                AccountMeta::new_readonly(server, true),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        )
+    };
+    let end = |seed: [u8; 32], value: u64| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::EndSession { score: value }.pack(),
+            vec![
+                AccountMeta::new(
+                    GameSessionPda::find(&program_id, &player.pubkey(), &seed).0,
+                    false,
+                ),
+                AccountMeta::new(score, false),
+                AccountMeta::new(player.pubkey(), true),
+                AccountMeta::new_readonly(server.pubkey(), true),
+                AccountMeta::new_readonly(season_address(&program_id), false),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(ban_list_address(&program_id), false),
+            ],
+        )
+    };
+
+    // Only the game server can vouch for a session
+    let result = env
+        .process(start([1; 32], player.pubkey()), &[&player])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::Unauthorized)
+    );
+
+    let clock: Clock = env.context.banks_client.get_sysvar().await.unwrap();
+    env.process(start([1; 32], server.pubkey()), &[&player, &server])
+        .await
+        .unwrap();
+    let session = GameSessionPda::find(&program_id, &player.pubkey(), &[1; 32]).0;
+    let account = env.context.banks_client.get_account(session).await.unwrap();
+    let state = GameSession::unpack(&account.expect("session exists").data).unwrap();
+    assert_eq!(state.start_slot, clock.slot);
+
+    // Ten slots in, at most 1000 points are plausible
+    env.context.warp_to_slot(clock.slot + 10).unwrap();
+    let result = env.process(end([1; 32], 1_001), &[&player, &server]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::ImplausibleScore)
+    );
+    env.process(end([1; 32], 1_000), &[&player, &server])
+        .await
+        .unwrap();
+    assert_eq!(env.score().await.best_score, 1_000);
+    assert!(env
+        .context
+        .banks_client
+        .get_account(session)
+        .await
+        .unwrap()
+        .is_none());
+
+    // A session left open too long closes without crediting anything
+    let clock: Clock = env.context.banks_client.get_sysvar().await.unwrap();
+    env.process(start([2; 32], server.pubkey()), &[&player, &server])
+        .await
+        .unwrap();
+    env.context.warp_to_slot(clock.slot + 60).unwrap();
+    env.process(end([2; 32], 2_000), &[&player, &server])
+        .await
+        .unwrap();
+    assert_eq!(env.score().await.best_score, 1_000);
+    let session = GameSessionPda::find(&program_id, &player.pubkey(), &[2; 32]).0;
+    assert!(env
+        .context
+        .banks_client
+        .get_account(session)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn game_sessions_cap_score_per_second() {
+    let mut env = setup(Some(10)).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let server = Keypair::new();
+    env.set_config(Config {
+        game_server: server.pubkey(),
+        max_score_per_second: 10,
+        ..env.config()
+    });
+
+    let seed = [3; 32];
+    let session = GameSessionPda::find(&program_id, &player.pubkey(), &seed).0;
+    let score = score_address(&program_id, &player.pubkey());
+    let start = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::StartSession {
+            game_mode: GameMode::Arcade,
+            seed,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(session, false),
+            AccountMeta::new_readonly(score, false),
+            AccountMeta::new(player.pubkey(), true),
+            AccountMeta::new_readonly(server.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    let end = |value: u64| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::EndSession { score: value }.pack(),
+            vec![
+                AccountMeta::new(session, false),
+                AccountMeta::new(score, false),
+                AccountMeta::new(player.pubkey(), true),
+                AccountMeta::new_readonly(server.pubkey(), true),
+                AccountMeta::new_readonly(season_address(&program_id), false),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(ban_list_address(&program_id), false),
+            ],
+        )
+    };
+
+    env.process(start, &[&player, &server]).await.unwrap();
+    let account = env.context.banks_client.get_account(session).await.unwrap();
+    let started = GameSession::unpack(&account.expect("session exists").data).unwrap();
+
+    // Thirty seconds in, at most 300 points are plausible however many slots passed
+    let mut clock: Clock = env.context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp = started.start_ts + 30;
+    env.context.set_sysvar(&clock);
+    let result = env.process(end(301), &[&player, &server]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::ImplausibleScore)
+    );
+    env.process(end(300), &[&player, &server]).await.unwrap();
+    assert_eq!(env.score().await.best_score, 300);
+}
+
+#[tokio::test]
+async fn updates_are_throttled_per_slot() {
+    let mut env = setup(Some(0)).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let address = score_address(&program_id, &player.pubkey());
+    env.set_config(Config {
+        throttle_window_slots: 1,
+        max_updates_per_window: 1,
+        ..env.config()
+    });
+
+    let clock: Clock = env.context.banks_client.get_sysvar().await.unwrap();
+    env.process(env.update_score(address, true, 5), &[&player])
+        .await
+        .unwrap();
+    let result = env
+        .process(env.update_score(address, true, 6), &[&player])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::TooManyUpdates)
+    );
+    assert_eq!(env.score().await.score, 5);
+
+    env.context.warp_to_slot(clock.slot + 2).unwrap();
+    env.process(env.update_score(address, true, 6), &[&player])
+        .await
+        .unwrap();
+    assert_eq!(env.score().await.score, 11);
+}
+
+#[tokio::test]
+async fn check_in_extends_streak_and_boosts_updates() {
+    let mut env = setup(None).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let address = score_address(&program_id, &player.pubkey());
+
+    // A two-day streak that last checked in yesterday
+    let clock: Clock = env.context.banks_client.get_sysvar().await.unwrap();
+    let today = clock.unix_timestamp.div_euclid(SECONDS_PER_DAY);
+    let mut data = vec![0u8; ScoreAccount::LEN];
+    ScoreAccount {
+        is_initialized: true,
+        player: player.pubkey(),
+        score: 100,
+        version: ScoreAccount::VERSION,
+        bump: score_bump(&program_id, &player.pubkey()),
+        streak: 2,
+        last_play_day: today - 1,
+        ..ScoreAccount::default()
+    }
+    .pack(&mut data)
+    .unwrap();
+    env.context
+        .set_account(&address, &program_account(&program_id, data).into());
+
+    let check_in = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::CheckIn {
+            game_mode: GameMode::Arcade,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(address, false),
+            AccountMeta::new_readonly(player.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+        ],
+    );
+    env.process(check_in.clone(), &[&player]).await.unwrap();
+    let score = env.score().await;
+    assert_eq!((score.streak, score.last_play_day), (3, today));
+
+    let result = env.process(check_in, &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::AlreadyCheckedIn)
+    );
+
+    // Day three of a streak adds 20% to each increment
+    env.process(env.update_score(address, true, 10), &[&player])
+        .await
+        .unwrap();
+    assert_eq!(env.score().await.score, 112);
+}
+
+// Token Metadata account bytes for `mint`, in the collection with the given verified flag
+fn metadata_account(mint: &Pubkey, collection: &Pubkey, verified: bool) -> Account {
+    let mut data = vec![4]; // Key::MetadataV1
+    data.extend_from_slice(Pubkey::new_unique().as_ref()); // update authority
+    data.extend_from_slice(mint.as_ref());
+    for field in ["Trash Panda", "PANDA", "https://example.com/panda.json"] {
+        data.extend_from_slice(&(field.len() as u32).to_le_bytes());
+        data.extend_from_slice(field.as_bytes());
+    }
+    data.extend_from_slice(&500u16.to_le_bytes()); // seller fee
+    data.extend_from_slice(&[0, 0, 1, 0, 0]); // creators, primary sale, mutable, nonce, standard
+    data.extend_from_slice(&[1, verified as u8]);
+    data.extend_from_slice(collection.as_ref());
+    data.extend_from_slice(&[0, 0, 0]); // uses, collection details, programmable config
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: mpl_token_metadata::ID,
+        ..Account::default()
+    }
+}
+
+#[tokio::test]
+async fn apply_boost_requires_verified_collection_nft() {
+    let mut env = setup(Some(100)).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let collection = Pubkey::new_unique();
+    env.set_config(Config {
+        boost_collection: collection,
+        boost_multiplier_bps: 15_000,
+        ..env.config()
+    });
+
+    let mint = Pubkey::new_unique();
+    let token = get_associated_token_address(&player.pubkey(), &mint);
+    let metadata = Metadata::find_pda(&mint).0;
+    env.context
+        .set_account(&token, &token_account(&mint, &player.pubkey(), 1).into());
+    env.context.set_account(
+        &metadata,
+        &metadata_account(&mint, &collection, false).into(),
+    );
+
+    let apply_boost = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::ApplyBoost {
+            game_mode: GameMode::Arcade,
+            increment: 10,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(score_address(&program_id, &player.pubkey()), false),
+            AccountMeta::new_readonly(player.pubkey(), true),
+            AccountMeta::new_readonly(season_address(&program_id), false),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(ban_list_address(&program_id), false),
+            AccountMeta::new_readonly(token, false),
+            AccountMeta::new_readonly(metadata, false),
+        ],
+    );
+
+    // Anyone can claim a collection until its authority verifies the membership
+    let result = env.process(apply_boost.clone(), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::InvalidBoostNft)
+    );
+
+    env.context.set_account(
+        &metadata,
+        &metadata_account(&mint, &collection, true).into(),
+    );
+    env.process(apply_boost, &[&player]).await.unwrap();
+    assert_eq!(env.score().await.score, 115);
+}
+
+#[tokio::test]
+async fn mint_trophy_requires_best_score_margin() {
+    let mut env = setup(Some(100)).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let trophy_mint = Keypair::new();
+    let mint = trophy_mint.pubkey();
+    let mint_trophy = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::MintTrophy {
+            game_mode: GameMode::Arcade,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(score_address(&program_id, &player.pubkey()), false),
+            AccountMeta::new(player.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new(mint, true),
+            AccountMeta::new(get_associated_token_address(&player.pubkey(), &mint), false),
+            AccountMeta::new(Metadata::find_pda(&mint).0, false),
+            AccountMeta::new(MasterEdition::find_pda(&mint).0, false),
+            AccountMeta::new_readonly(
+                Pubkey::find_program_address(&[b"trophy"], &program_id).0,
+                false,
+            ),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+            AccountMeta::new_readonly(mpl_token_metadata::ID, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+
+    // Trophies are off until the admin sets a margin
+    let result = env
+        .process(mint_trophy.clone(), &[&player, &trophy_mint])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::TrophyNotEarned)
+    );
+
+    // A best score of 100 falls short of a 500 point margin
+    env.set_config(Config {
+        trophy_margin: 500,
+        ..env.config()
+    });
+    let result = env.process(mint_trophy, &[&player, &trophy_mint]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::TrophyNotEarned)
+    );
+}
+
+// Invalid names keep their raw bytes so tests can still submit them
+fn name_record_address(program_id: &Pubkey, name: &str) -> Pubkey {
+    let padded = pad_profile_name(name).unwrap();
+    let normalized = normalize_profile_name(&padded).unwrap_or(padded);
+    Pubkey::find_program_address(&[b"name", &normalized], program_id).0
+}
+
+fn create_profile(program_id: &Pubkey, player: &Pubkey, name: &str) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &GarbageGrabInstruction::CreateProfile {
+            name: pad_profile_name(name).unwrap(),
+            avatar_mint: Pubkey::default(),
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(
+                Pubkey::find_program_address(&[b"profile", player.as_ref()], program_id).0,
+                false,
+            ),
+            AccountMeta::new(name_record_address(program_id, name), false),
+            AccountMeta::new(*player, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+#[tokio::test]
+async fn profile_names_are_unique_ignoring_case() {
+    let mut env = setup(None).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let admin = env.admin.insecure_clone();
+    let profile =
+        Pubkey::find_program_address(&[b"profile", player.pubkey().as_ref()], &program_id).0;
+
+    env.process(
+        create_profile(&program_id, &player.pubkey(), "Trash_Panda"),
+        &[&player],
+    )
+    .await
+    .unwrap();
+    let account = env
+        .context
+        .banks_client
+        .get_account(profile)
+        .await
+        .unwrap()
+        .unwrap();
+    let state = PlayerProfile::unpack(&account.data).unwrap();
+    assert_eq!(state.display_name(), "Trash_Panda");
+    assert!(state.created_ts > 0);
+
+    let result = env
+        .process(
+            create_profile(&program_id, &admin.pubkey(), "trash_panda"),
+            &[&admin],
+        )
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::NameTaken)
+    );
+
+    let result = env
+        .process(
+            create_profile(&program_id, &admin.pubkey(), "trash panda"),
+            &[&admin],
+        )
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::InvalidProfileName)
+    );
+
+    // Renaming releases the old name for someone else
+    let rename = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::UpdateProfile {
+            name: pad_profile_name("Raccoon"),
+            avatar_mint: None,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(profile, false),
+            AccountMeta::new(player.pubkey(), true),
+            AccountMeta::new(name_record_address(&program_id, "Trash_Panda"), false),
+            AccountMeta::new(name_record_address(&program_id, "Raccoon"), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    env.process(rename, &[&player]).await.unwrap();
+    env.process(
+        create_profile(&program_id, &admin.pubkey(), "trash_panda"),
+        &[&admin],
+    )
+    .await
+    .unwrap();
+
+    let account = env
+        .context
+        .banks_client
+        .get_account(profile)
+        .await
+        .unwrap()
+        .unwrap();
+    let name = PlayerProfile::unpack(&account.data).unwrap().name;
+    assert_eq!(name, pad_profile_name("Raccoon").unwrap());
+}
+
+#[tokio::test]
+async fn follows_count_towards_the_followed_profile() {
+    let mut env = setup(None).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let followed = env.admin.insecure_clone();
+    let profile =
+        Pubkey::find_program_address(&[b"profile", followed.pubkey().as_ref()], &program_id).0;
+    env.process(
+        create_profile(&program_id, &followed.pubkey(), "Followed"),
+        &[&followed],
+    )
+    .await
+    .unwrap();
+
+    let edge = FollowPda::find(&program_id, &player.pubkey(), &followed.pubkey()).0;
+    let follow = |ix: GarbageGrabInstruction, edge: Pubkey, followed: Pubkey, profile: Pubkey| {
+        let mut accounts = vec![
+            AccountMeta::new(edge, false),
+            AccountMeta::new(player.pubkey(), true),
+            AccountMeta::new_readonly(followed, false),
+            AccountMeta::new(profile, false),
+        ];
+        if ix == GarbageGrabInstruction::Follow {
+            accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+        }
+        Instruction::new_with_bytes(program_id, &ix.pack(), accounts)
+    };
+    let follower_count = |account: Option<Account>| {
+        PlayerProfile::unpack(&account.expect("profile exists").data)
+            .unwrap()
+            .follower_count
+    };
+
+    env.process(
+        follow(
+            GarbageGrabInstruction::Follow,
+            edge,
+            followed.pubkey(),
+            profile,
+        ),
+        &[&player],
+    )
+    .await
+    .unwrap();
+    let account = env.context.banks_client.get_account(edge).await.unwrap();
+    let state = Follow::unpack(&account.expect("edge exists").data).unwrap();
+    assert_eq!(
+        (state.follower, state.followed),
+        (player.pubkey(), followed.pubkey())
+    );
+    let account = env.context.banks_client.get_account(profile).await.unwrap();
+    assert_eq!(follower_count(account), 1);
+
+    let result = env
+        .process(
+            follow(
+                GarbageGrabInstruction::Follow,
+                edge,
+                followed.pubkey(),
+                profile,
+            ),
+            &[&player],
+        )
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::AlreadyInitialized)
+    );
+
+    let self_edge = FollowPda::find(&program_id, &player.pubkey(), &player.pubkey()).0;
+    let own_profile =
+        Pubkey::find_program_address(&[b"profile", player.pubkey().as_ref()], &program_id).0;
+    let result = env
+        .process(
+            follow(
+                GarbageGrabInstruction::Follow,
+                self_edge,
+                player.pubkey(),
+                own_profile,
+            ),
+            &[&player],
+        )
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::CannotFollowSelf)
+    );
+
+    // Unfollowing closes the edge and refunds its rent
+    let balance = env.balance(player.pubkey()).await;
+    env.process(
+        follow(
+            GarbageGrabInstruction::Unfollow,
+            edge,
+            followed.pubkey(),
+            profile,
+        ),
+        &[&player],
+    )
+    .await
+    .unwrap();
+    assert!(env
+        .context
+        .banks_client
+        .get_account(edge)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(env.balance(player.pubkey()).await > balance);
+    let account = env.context.banks_client.get_account(profile).await.unwrap();
+    assert_eq!(follower_count(account), 0);
+}
+
+#[tokio::test]
+async fn regions_are_set_once_and_rank_players_per_region() {
+    let mut env = setup(Some(10)).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let admin = env.admin.insecure_clone();
+    let profile =
+        Pubkey::find_program_address(&[b"profile", player.pubkey().as_ref()], &program_id).0;
+    env.process(
+        create_profile(&program_id, &player.pubkey(), "Regional"),
+        &[&player],
+    )
+    .await
+    .unwrap();
+
+    let set_region = |region: &[u8; 2]| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::SetRegion { region: *region }.pack(),
+            vec![
+                AccountMeta::new(profile, false),
+                AccountMeta::new_readonly(player.pubkey(), true),
+            ],
+        )
+    };
+    let result = env.process(set_region(b"de"), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::InvalidRegion)
+    );
+    env.process(set_region(b"DE"), &[&player]).await.unwrap();
+    let result = env.process(set_region(b"FR"), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::RegionAlreadySet)
+    );
+
+    // The admin can still correct it
+    let correct = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::AdminSetRegion { region: *b"FR" }.pack(),
+        vec![
+            AccountMeta::new(profile, false),
+            AccountMeta::new_readonly(player.pubkey(), false),
+            AccountMeta::new_readonly(admin.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+        ],
+    );
+    env.process(correct, &[&admin]).await.unwrap();
+    let account = env.context.banks_client.get_account(profile).await.unwrap();
+    let state = PlayerProfile::unpack(&account.expect("profile exists").data).unwrap();
+    assert_eq!(state.region, *b"FR");
+
+    let board = |region: &[u8; 2]| RegionalLeaderboardPda::find(&program_id, region).0;
+    for region in [b"DE", b"FR"] {
+        env.context.set_account(
+            &board(region),
+            &leaderboard_account(&program_id, Vec::new()).into(),
+        );
+    }
+    let submit = |region: &[u8; 2]| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::SubmitToRegionalLeaderboard {
+                game_mode: GameMode::Arcade,
+            }
+            .pack(),
+            vec![
+                AccountMeta::new(board(region), false),
+                AccountMeta::new_readonly(score_address(&program_id, &player.pubkey()), false),
+                AccountMeta::new_readonly(player.pubkey(), true),
+                AccountMeta::new_readonly(profile, false),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(ban_list_address(&program_id), false),
+            ],
+        )
+    };
+
+    // Scores only go to the board of the profile's region
+    let result = env.process(submit(b"DE"), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::InvalidPda)
+    );
+    env.process(submit(b"FR"), &[&player]).await.unwrap();
+    let account = env.context.banks_client.get_account(board(b"FR")).await;
+    let account = account.unwrap().expect("regional leaderboard exists");
+    assert_eq!(
+        Leaderboard::load(&account.data).unwrap().entries(),
+        &[LeaderboardEntry::new(player.pubkey(), 10)]
+    );
+}
+
+#[tokio::test]
+async fn high_stakes_lobbies_need_a_level() {
+    const STAKE: u64 = 50_000;
+    let mut env = setup(None).await;
+    let program_id = env.program_id;
+    let host = env.admin.insecure_clone();
+    let player = env.player.insecure_clone();
+    let lobby = LobbyPda::find(&program_id, &host.pubkey()).0;
+    let profile =
+        Pubkey::find_program_address(&[b"profile", player.pubkey().as_ref()], &program_id).0;
+    env.process(
+        create_profile(&program_id, &player.pubkey(), "Rookie"),
+        &[&player],
+    )
+    .await
+    .unwrap();
+    let create = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::CreateLobby {
+            capacity: 2,
+            stake: STAKE,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(lobby, false),
+            AccountMeta::new(host.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    let join = |with_profile: bool| {
+        let mut accounts = vec![
+            AccountMeta::new(lobby, false),
+            AccountMeta::new(player.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ];
+        if with_profile {
+            accounts.push(AccountMeta::new_readonly(profile, false));
+        }
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::JoinLobby.pack(),
+            accounts,
+        )
+    };
+
+    // The lobby opens before the threshold exists, then the config raises it
+    env.process(create.clone(), &[&host]).await.unwrap();
+    env.set_config(Config {
+        high_stakes_wager: STAKE,
+        high_stakes_min_level: 1,
+        ..env.config()
+    });
+
+    // A level 0 player cannot join, with or without their profile
+    let before = env.balance(player.pubkey()).await;
+    for with_profile in [false, true] {
+        let result = env.process(join(with_profile), &[&player]).await;
+        assert_eq!(
+            instruction_error(result),
+            custom(GarbageGrabError::LevelTooLow)
+        );
+    }
+    assert_eq!(env.balance(player.pubkey()).await, before);
+
+    // Nor can a level 0 host open one
+    let result = env.process(create, &[&host]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::LevelTooLow)
+    );
+}
+
+#[tokio::test]
+async fn runs_earn_xp_and_high_stakes_bets_need_a_level() {
+    let mut env = setup(Some(0)).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let (host, guest) = (env.admin.insecure_clone(), Keypair::new());
+    env.context.set_account(
+        &guest.pubkey(),
+        &Account::new(1_000_000_000, 0, &system_program::id()).into(),
+    );
+    env.set_config(Config {
+        high_stakes_wager: 50_000,
+        high_stakes_min_level: 1,
+        ..env.config()
+    });
+    let profile =
+        Pubkey::find_program_address(&[b"profile", player.pubkey().as_ref()], &program_id).0;
+    env.process(
+        create_profile(&program_id, &player.pubkey(), "Leveler"),
+        &[&player],
+    )
+    .await
+    .unwrap();
+    let submit_run = |run_score: u64| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::SubmitRun {
+                game_mode: GameMode::Arcade,
+                run_score,
+                run_hash: [run_score as u8; 32],
+            }
+            .pack(),
+            vec![
+                AccountMeta::new(score_address(&program_id, &player.pubkey()), false),
+                AccountMeta::new_readonly(player.pubkey(), true),
+                AccountMeta::new_readonly(season_address(&program_id), false),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(ban_list_address(&program_id), false),
+                AccountMeta::new(profile, false),
+            ],
+        )
+    };
+
+    // 10 XP per run plus one per 100 points, short of the 100 level 1 needs
+    env.process(submit_run(5_000), &[&player]).await.unwrap();
+    let account = env.context.banks_client.get_account(profile).await.unwrap();
+    let state = PlayerProfile::unpack(&account.expect("profile exists").data).unwrap();
+    assert_eq!((state.xp, state.level), (60, 0));
+
+    let match_account = MatchPda::find(&program_id, &host.pubkey(), &guest.pubkey(), 1).0;
+    let create = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::CreateMatch {
+            match_id: 1,
+            stake: 1_000,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(match_account, false),
+            AccountMeta::new(host.pubkey(), true),
+            AccountMeta::new(guest.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    env.process(create, &[&host, &guest]).await.unwrap();
+    let place = |amount: u64, with_profile: bool| {
+        let mut accounts = vec![
+            AccountMeta::new(BetPoolPda::find(&program_id, &match_account).0, false),
+            AccountMeta::new(
+                BetPda::find(&program_id, &match_account, &player.pubkey()).0,
+                false,
+            ),
+            AccountMeta::new_readonly(match_account, false),
+            AccountMeta::new(player.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ];
+        if with_profile {
+            accounts.push(AccountMeta::new_readonly(profile, false));
+        }
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::PlaceBet {
+                on: host.pubkey(),
+                amount,
+            }
+            .pack(),
+            accounts,
+        )
+    };
+
+    // Small bets need no level, but the bet adding up to the threshold does
+    env.process(place(10_000, false), &[&player]).await.unwrap();
+    for with_profile in [false, true] {
+        let result = env.process(place(40_000, with_profile), &[&player]).await;
+        assert_eq!(
+            instruction_error(result),
+            custom(GarbageGrabError::LevelTooLow)
+        );
+    }
+
+    env.process(submit_run(9_000), &[&player]).await.unwrap();
+    let account = env.context.banks_client.get_account(profile).await.unwrap();
+    let state = PlayerProfile::unpack(&account.expect("profile exists").data).unwrap();
+    assert_eq!((state.xp, state.level), (160, 1));
+    env.process(place(40_000, true), &[&player]).await.unwrap();
+
+    // However big the run, a day earns at most DAILY_XP_CAP
+    env.process(submit_run(900_000), &[&player]).await.unwrap();
+    let account = env.context.banks_client.get_account(profile).await.unwrap();
+    let state = PlayerProfile::unpack(&account.expect("profile exists").data).unwrap();
+    assert_eq!((state.xp, state.day_xp), (DAILY_XP_CAP, DAILY_XP_CAP));
+}
+
+#[tokio::test]
+async fn cosmetics_unlock_once_their_requirements_are_met() {
+    let mut env = setup(Some(0)).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let admin = env.admin.insecure_clone();
+    let server = Keypair::new();
+    env.set_config(Config {
+        game_server: server.pubkey(),
+        ..env.config()
+    });
+    let profile =
+        Pubkey::find_program_address(&[b"profile", player.pubkey().as_ref()], &program_id).0;
+    env.process(
+        create_profile(&program_id, &player.pubkey(), "Collector"),
+        &[&player],
+    )
+    .await
+    .unwrap();
+
+    // Cosmetic 1 needs level 2; cosmetic 2 needs achievement 3 in arcade
+    for (cosmetic_id, min_level, achievement) in [(1, 2, NO_ACHIEVEMENT), (2, 0, 3)] {
+        let set = Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::SetCosmetic {
+                cosmetic_id,
+                min_level,
+                game_mode: GameMode::Arcade,
+                achievement,
+            }
+            .pack(),
+            vec![
+                AccountMeta::new(CosmeticPda::find(&program_id, cosmetic_id).0, false),
+                AccountMeta::new(admin.pubkey(), true),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        );
+        env.process(set, &[&admin]).await.unwrap();
+    }
+    let unlock = |cosmetic_id: u8| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::UnlockCosmetic.pack(),
+            vec![
+                AccountMeta::new_readonly(CosmeticPda::find(&program_id, cosmetic_id).0, false),
+                AccountMeta::new(profile, false),
+                AccountMeta::new_readonly(player.pubkey(), false),
+                AccountMeta::new_readonly(score_address(&program_id, &player.pubkey()), false),
+            ],
+        )
+    };
+    let unlocks = |account: Option<Account>| {
+        PlayerProfile::unpack(&account.expect("profile exists").data)
+            .unwrap()
+            .unlocks
+    };
+
+    // Anyone may record an unlock, but only once the player has earned it
+    for cosmetic_id in [1, 2] {
+        let result = env.process(unlock(cosmetic_id), &[]).await;
+        assert_eq!(
+            instruction_error(result),
+            custom(GarbageGrabError::CosmeticLocked)
+        );
+    }
+
+    let account = env.context.banks_client.get_account(profile).await.unwrap();
+    let mut state = PlayerProfile::unpack(&account.expect("profile exists").data).unwrap();
+    state.level = 2;
+    let mut data = vec![0; PlayerProfile::LEN];
+    state.pack(&mut data).unwrap();
+    env.context
+        .set_account(&profile, &program_account(&program_id, data).into());
+    let achievement = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::UnlockAchievement {
+            game_mode: GameMode::Arcade,
+            id: 3,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(score_address(&program_id, &player.pubkey()), false),
+            AccountMeta::new_readonly(player.pubkey(), false),
+            AccountMeta::new_readonly(server.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+        ],
+    );
+    env.process(achievement, &[&server]).await.unwrap();
+
+    for cosmetic_id in [1, 2] {
+        env.process(unlock(cosmetic_id), &[]).await.unwrap();
+    }
+    let account = env.context.banks_client.get_account(profile).await.unwrap();
+    assert_eq!(unlocks(account), 1 << 1 | 1 << 2);
+    let result = env.process(unlock(1), &[]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::CosmeticAlreadyUnlocked)
+    );
+}
+
+#[tokio::test]
+async fn cosmetic_registry_takes_only_admin_edits() {
+    let mut env = setup(Some(0)).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let admin = env.admin.insecure_clone();
+    let profile =
+        Pubkey::find_program_address(&[b"profile", player.pubkey().as_ref()], &program_id).0;
+    env.process(
+        create_profile(&program_id, &player.pubkey(), "Newcomer"),
+        &[&player],
+    )
+    .await
+    .unwrap();
+    let set = |signer: Pubkey, cosmetic_id: u8, min_level: u16| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::SetCosmetic {
+                cosmetic_id,
+                min_level,
+                game_mode: GameMode::Arcade,
+                achievement: NO_ACHIEVEMENT,
+            }
+            .pack(),
+            vec![
+                AccountMeta::new(CosmeticPda::find(&program_id, cosmetic_id).0, false),
+                AccountMeta::new(signer, true),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        )
+    };
+    let cosmetic = CosmeticPda::find(&program_id, 3).0;
+    let unlock = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::UnlockCosmetic.pack(),
+        vec![
+            AccountMeta::new_readonly(cosmetic, false),
+            AccountMeta::new(profile, false),
+            AccountMeta::new_readonly(player.pubkey(), false),
+        ],
+    );
+
+    // Players cannot grant themselves a cosmetic, and ids stop at the bitmap's width
+    let result = env.process(set(player.pubkey(), 3, 0), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::Unauthorized)
+    );
+    let result = env
+        .process(set(admin.pubkey(), COSMETIC_COUNT, 0), &[&admin])
+        .await;
+    assert_eq!(instruction_error(result), InstructionError::InvalidArgument);
+
+    env.process(set(admin.pubkey(), 3, 5), &[&admin])
+        .await
+        .unwrap();
+    let result = env.process(unlock.clone(), &[]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::CosmeticLocked)
+    );
+
+    // Lowering the requirement later opens it to the level 0 player
+    env.process(set(admin.pubkey(), 3, 0), &[&admin])
+        .await
+        .unwrap();
+    let account = env.context.banks_client.get_account(cosmetic).await;
+    let account = account.unwrap().expect("cosmetic exists");
+    assert_eq!(Cosmetic::unpack(&account.data).unwrap().min_level, 0);
+    env.process(unlock, &[]).await.unwrap();
+    let account = env.context.banks_client.get_account(profile).await;
+    let account = account.unwrap().expect("profile exists");
+    assert_eq!(PlayerProfile::unpack(&account.data).unwrap().unlocks, 1 << 3);
+}
+
+#[tokio::test]
+async fn badges_mint_one_non_transferable_token_per_milestone() {
+    let mut env = setup(Some(50_000)).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let admin = env.admin.insecure_clone();
+    let token_program = spl_token_2022::id();
+    let catalog = BadgeCatalogPda::find(&program_id).0;
+    let authority = BadgeAuthorityPda::find(&program_id).0;
+
+    let mint_keypair = Keypair::new();
+    let mint = mint_keypair.pubkey();
+    let add = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::AddBadge {
+            milestone: BadgeMilestone::BestScore,
+            threshold: 100_000,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(catalog, false),
+            AccountMeta::new(mint, true),
+            AccountMeta::new_readonly(authority, false),
+            AccountMeta::new(admin.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(token_program, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    env.process(add, &[&admin, &mint_keypair]).await.unwrap();
+    let account = env.context.banks_client.get_account(catalog).await.unwrap();
+    let badges = BadgeCatalog::unpack(&account.expect("catalog exists").data)
+        .unwrap()
+        .badges;
+    assert_eq!(badges.len(), 1);
+    assert_eq!(badges[0].mint, mint);
+
+    let player_tokens =
+        get_associated_token_address_with_program_id(&player.pubkey(), &mint, &token_program);
+    let claim = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::ClaimBadge {
+            badge_id: 0,
+            game_mode: GameMode::Arcade,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new_readonly(catalog, false),
+            AccountMeta::new_readonly(score_address(&program_id, &player.pubkey()), false),
+            AccountMeta::new(player.pubkey(), true),
+            AccountMeta::new(mint, false),
+            AccountMeta::new(player_tokens, false),
+            AccountMeta::new_readonly(authority, false),
+            AccountMeta::new_readonly(token_program, false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    let result = env.process(claim.clone(), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::BadgeNotEarned)
+    );
+
+    let address = score_address(&program_id, &player.pubkey());
+    let mut data = vec![0u8; ScoreAccount::LEN];
+    ScoreAccount {
+        is_initialized: true,
+        player: player.pubkey(),
+        score: 100_000,
+        best_score: 100_000,
+        version: ScoreAccount::VERSION,
+        bump: score_bump(&program_id, &player.pubkey()),
+        ..ScoreAccount::default()
+    }
+    .pack(&mut data)
+    .unwrap();
+    env.context
+        .set_account(&address, &program_account(&program_id, data).into());
+    env.process(claim.clone(), &[&player]).await.unwrap();
+    assert_eq!(token_balance(&mut env, player_tokens).await, 1);
+    let result = env.process(claim, &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::BadgeAlreadyClaimed)
+    );
+
+    // The badge cannot be handed to another wallet
+    let create_ata =
+        create_associated_token_account(&admin.pubkey(), &admin.pubkey(), &mint, &token_program);
+    env.process(create_ata, &[&admin]).await.unwrap();
+    let transfer = spl_token_2022::instruction::transfer_checked(
+        &token_program,
+        &player_tokens,
+        &mint,
+        &get_associated_token_address_with_program_id(&admin.pubkey(), &mint, &token_program),
+        &player.pubkey(),
+        &[],
+        1,
+        0,
+    )
+    .unwrap();
+    assert!(env.process(transfer, &[&player]).await.is_err());
+    assert_eq!(token_balance(&mut env, player_tokens).await, 1);
+}
+
+#[tokio::test]
+async fn referrer_is_credited_once_player_reaches_threshold() {
+    let mut env = setup(None).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let referrer = env.admin.insecure_clone();
+    let address = score_address(&program_id, &player.pubkey());
+    let referrer_profile =
+        Pubkey::find_program_address(&[b"profile", referrer.pubkey().as_ref()], &program_id).0;
+    env.set_config(Config {
+        referral_threshold: 50,
+        points_per_referral: 10,
+        ..env.config()
+    });
+
+    env.process(
+        create_profile(&program_id, &referrer.pubkey(), "recruiter"),
+        &[&referrer],
+    )
+    .await
+    .unwrap();
+    let initialize = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::Initialize {
+            game_mode: GameMode::Arcade,
+            referrer: Some(referrer.pubkey()),
+            allowlist_proof: Vec::new(),
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(address, false),
+            AccountMeta::new(player.pubkey(), true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+        ],
+    );
+    env.process(initialize, &[&player]).await.unwrap();
+    assert_eq!(env.score().await.referrer, referrer.pubkey());
+
+    let credit = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::CreditReferral {
+            game_mode: GameMode::Arcade,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(address, false),
+            AccountMeta::new_readonly(player.pubkey(), false),
+            AccountMeta::new(referrer_profile, false),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+        ],
+    );
+    let result = env.process(credit.clone(), &[]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::ReferralNotEarned)
+    );
+
+    env.process(env.update_score(address, true, 60), &[&player])
+        .await
+        .unwrap();
+    env.process(credit.clone(), &[]).await.unwrap();
+    let account = env
+        .context
+        .banks_client
+        .get_account(referrer_profile)
+        .await
+        .unwrap()
+        .unwrap();
+    let profile = PlayerProfile::unpack(&account.data).unwrap();
+    assert_eq!((profile.referral_count, profile.referral_points), (1, 10));
+
+    // Each referred player only counts once
+    let result = env.process(credit, &[]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::ReferralNotEarned)
+    );
+}
+
+#[tokio::test]
+async fn guild_totals_follow_member_contributions() {
+    let mut env = setup(Some(0)).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let leader = env.admin.insecure_clone();
+    let guild = Pubkey::find_program_address(&[b"guild", &7u64.to_le_bytes()], &program_id).0;
+    let member =
+        |key: Pubkey| Pubkey::find_program_address(&[b"guild_member", key.as_ref()], &program_id).0;
+    let guild_ix = |ix: GarbageGrabInstruction, player: Pubkey| {
+        let mut accounts = vec![
+            AccountMeta::new(guild, false),
+            AccountMeta::new(member(player), false),
+            AccountMeta::new(player, true),
+        ];
+        if ix != GarbageGrabInstruction::LeaveGuild {
+            accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+        }
+        Instruction::new_with_bytes(program_id, &ix.pack(), accounts)
+    };
+
+    let create = GarbageGrabInstruction::CreateGuild {
+        guild_id: 7,
+        name: pad_profile_name("Dumpster Divers").unwrap(),
+    };
+    env.process(guild_ix(create, leader.pubkey()), &[&leader])
+        .await
+        .unwrap();
+    env.process(
+        guild_ix(GarbageGrabInstruction::JoinGuild, player.pubkey()),
+        &[&player],
+    )
+    .await
+    .unwrap();
+    let result = env
+        .process(
+            guild_ix(GarbageGrabInstruction::JoinGuild, player.pubkey()),
+            &[&player],
+        )
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::AlreadyInGuild)
+    );
+
+    // Passing the membership and guild with an update credits the guild too
+    let mut update = env.update_score(score_address(&program_id, &player.pubkey()), true, 40);
+    update
+        .accounts
+        .push(AccountMeta::new(member(player.pubkey()), false));
+    update.accounts.push(AccountMeta::new(guild, false));
+    env.process(update, &[&player]).await.unwrap();
+    let load = |account: Account| Guild::unpack(&account.data).unwrap();
+    let state = load(
+        env.context
+            .banks_client
+            .get_account(guild)
+            .await
+            .unwrap()
+            .unwrap(),
+    );
+    assert_eq!((state.member_count, state.total_score), (2, 40));
+
+    let result = env
+        .process(
+            guild_ix(GarbageGrabInstruction::LeaveGuild, leader.pubkey()),
+            &[&leader],
+        )
+        .await;
+    assert_eq!(instruction_error(result), InstructionError::InvalidArgument);
+    env.process(
+        guild_ix(GarbageGrabInstruction::LeaveGuild, player.pubkey()),
+        &[&player],
+    )
+    .await
+    .unwrap();
+    let state = load(
+        env.context
+            .banks_client
+            .get_account(guild)
+            .await
+            .unwrap()
+            .unwrap(),
+    );
+    assert_eq!((state.member_count, state.total_score), (1, 0));
+    assert!(env
+        .context
+        .banks_client
+        .get_account(member(player.pubkey()))
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn team_members_share_one_score() {
+    let mut env = setup(None).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let teammate = env.admin.insecure_clone();
+    let mut members = [player.pubkey(), teammate.pubkey()];
+    members.sort();
+    let team = Pubkey::find_program_address(
+        &[b"team", members[0].as_ref(), members[1].as_ref()],
+        &program_id,
+    )
+    .0;
+    let create = |teammate_signs: bool| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::CreateTeam.pack(),
+            vec![
+                AccountMeta::new(team, false),
+                AccountMeta::new(player.pubkey(), true),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(teammate.pubkey(), teammate_signs),
+                AccountMeta::new_readonly(player.pubkey(), true),
+            ],
+        )
+    };
+    let update = |member: Pubkey, increment: u64| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::UpdateTeamScore { increment }.pack(),
+            vec![
+                AccountMeta::new(team, false),
+                AccountMeta::new_readonly(member, true),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+            ],
+        )
+    };
+
+    let result = env.process(create(false), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::InvalidTeam)
+    );
+    env.process(create(true), &[&player, &teammate])
+        .await
+        .unwrap();
+
+    env.process(update(player.pubkey(), 10), &[&player])
+        .await
+        .unwrap();
+    env.process(update(teammate.pubkey(), 5), &[&teammate])
+        .await
+        .unwrap();
+    let outsider = Keypair::new();
+    let result = env
+        .process(update(outsider.pubkey(), 5), &[&outsider])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::NotTeamMember)
+    );
+
+    let account = env
+        .context
+        .banks_client
+        .get_account(team)
+        .await
+        .unwrap()
+        .unwrap();
+    let state = Team::unpack(&account.data).unwrap();
+    assert_eq!(state.members(), &members);
+    assert_eq!((state.score, state.best_score), (15, 15));
+}
+
+#[tokio::test]
+async fn airdrop_pays_each_proven_claim_once() {
+    let mut env = setup(None).await;
+    let admin = env.admin.insecure_clone();
+    let player = env.player.insecure_clone();
+    let program_id = env.program_id;
+
+    // Stock the reward vault directly; it is its own token authority
+    let mint = Pubkey::new_unique();
+    env.context.set_account(&mint, &mint_account(1_000).into());
+    env.set_config(Config {
+        reward_mint: mint,
+        ..env.config()
+    });
+    let vault = vault_address(&program_id);
+    let player_tokens = get_associated_token_address(&player.pubkey(), &mint);
+    env.context
+        .set_account(&vault, &token_account(&mint, &vault, 1_000).into());
+    env.context.set_account(
+        &player_tokens,
+        &token_account(&mint, &player.pubkey(), 0).into(),
+    );
+
+    let recipients = [(player.pubkey(), 300), (Pubkey::new_unique(), 50)];
+    let leaves: Vec<[u8; 32]> = recipients
+        .iter()
+        .enumerate()
+        .map(|(index, (key, amount))| merkle::airdrop_leaf(index as u32, key, *amount))
+        .collect();
+    let (root, proofs) = merkle::build_tree(&leaves);
+
+    let airdrop_id = 1u64;
+    let id = airdrop_id.to_le_bytes();
+    let airdrop = Pubkey::find_program_address(&[b"airdrop", &id], &program_id).0;
+    let claims = Pubkey::find_program_address(&[b"airdrop_claims", &id], &program_id).0;
+    let set_root = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::SetRewardRoot {
+            airdrop_id,
+            merkle_root: root,
+            max_claims: recipients.len() as u32,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(airdrop, false),
+            AccountMeta::new(claims, false),
+            AccountMeta::new(admin.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    env.process(set_root, &[&admin]).await.unwrap();
+
+    let claim = |amount: u64| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::ClaimAirdrop {
+                airdrop_id,
+                index: 0,
+                amount,
+                proof: proofs[0].clone(),
+            }
+            .pack(),
+            vec![
+                AccountMeta::new(airdrop, false),
+                AccountMeta::new(claims, false),
+                AccountMeta::new_readonly(player.pubkey(), true),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new(vault, false),
+                AccountMeta::new(player_tokens, false),
+                AccountMeta::new_readonly(mint, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+            ],
+        )
+    };
+
+    // Claiming more than the leaf grants fails the proof
+    let result = env.process(claim(301), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::InvalidMerkleProof)
+    );
+
+    env.process(claim(300), &[&player]).await.unwrap();
+    assert_eq!(token_balance(&mut env, player_tokens).await, 300);
+    assert_eq!(token_balance(&mut env, vault).await, 700);
+    let account = env
+        .context
+        .banks_client
+        .get_account(airdrop)
+        .await
+        .unwrap()
+        .unwrap();
+    let state = Airdrop::unpack(&account.data).unwrap();
+    assert_eq!((state.claimed_count, state.total_claimed), (1, 300));
+
+    let result = env.process(claim(300), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::AirdropAlreadyClaimed)
+    );
+}
+
+#[tokio::test]
+async fn compressed_scores_are_appended_and_verified() {
+    let mut env = setup(None).await;
+    let admin = env.admin.insecure_clone();
+    let player = env.player.insecure_clone();
+    let program_id = env.program_id;
+    let server = Keypair::new();
+    env.set_config(Config {
+        game_server: server.pubkey(),
+        ..env.config()
+    });
+
+    // A depth-3 tree allocated for the compression program, as a client would
+    let merkle_tree = Pubkey::new_unique();
+    let space = CONCURRENT_MERKLE_TREE_HEADER_SIZE_V1 + size_of::<ConcurrentMerkleTree<3, 8>>();
+    env.context.set_account(
+        &merkle_tree,
+        &Account::new(1_000_000_000, space, &spl_account_compression::id()).into(),
+    );
+    let score_tree =
+        Pubkey::find_program_address(&[b"score_tree", merkle_tree.as_ref()], &program_id).0;
+
+    let initialize = Instruction::new_with_bytes(
+        program_id,
+        &GarbageGrabInstruction::InitializeScoreTree {
+            max_depth: 3,
+            max_buffer_size: 8,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(score_tree, false),
+            AccountMeta::new(merkle_tree, false),
+            AccountMeta::new(admin.pubkey(), true),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(spl_account_compression::id(), false),
+            AccountMeta::new_readonly(spl_noop::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    );
+    env.process(initialize, &[&admin]).await.unwrap();
+
+    let append = |authority: Pubkey, score: u64| {
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::AppendCompressedScore {
+                game_mode: GameMode::Arcade,
+                score,
+            }
+            .pack(),
+            vec![
+                AccountMeta::new(score_tree, false),
+                AccountMeta::new(merkle_tree, false),
+                AccountMeta::new_readonly(player.pubkey(), false),
+                AccountMeta::new_readonly(authority, true),
+                AccountMeta::new_readonly(config_address(&program_id), false),
+                AccountMeta::new_readonly(spl_account_compression::id(), false),
+                AccountMeta::new_readonly(spl_noop::id(), false),
+            ],
+        )
+    };
+
+    // Players can't vouch for their own scores, and even the admin is not the
+    // game server
+    let result = env.process(append(player.pubkey(), 999), &[&player]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::Unauthorized)
+    );
+    let result = env.process(append(admin.pubkey(), 999), &[&admin]).await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::Unauthorized)
+    );
+    let account = env.context.banks_client.get_account(score_tree).await;
+    let account = account.unwrap().expect("score tree exists");
+    assert_eq!(ScoreTree::unpack(&account.data).unwrap().leaf_count, 0);
+
+    for score in [120, 80] {
+        env.process(append(server.pubkey(), score), &[&server])
+            .await
+            .unwrap();
+    }
+    let account = env
+        .context
+        .banks_client
+        .get_account(score_tree)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(ScoreTree::unpack(&account.data).unwrap().leaf_count, 2);
+
+    // Rebuild both leaves and prove the first one
+    let clock: Clock = env.context.banks_client.get_sysvar().await.unwrap();
+    let leaf = |score: u64, leaf_index: u64| CompressedScore {
+        player: player.pubkey(),
+        game_mode: GameMode::Arcade,
+        score,
+        timestamp: clock.unix_timestamp,
+        leaf_index,
+    };
+    let proof = [leaf(80, 1).leaf_hash(), empty_node(1), empty_node(2)];
+    let root = recompute(leaf(120, 0).leaf_hash(), &proof, 0);
+    let verify = |score: CompressedScore| {
+        let mut accounts = vec![
+            AccountMeta::new_readonly(score_tree, false),
+            AccountMeta::new_readonly(merkle_tree, false),
+            AccountMeta::new_readonly(spl_account_compression::id(), false),
+        ];
+        accounts.extend(
+            proof
+                .iter()
+                .map(|node| AccountMeta::new_readonly(Pubkey::new_from_array(*node), false)),
+        );
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::VerifyCompressedScore { root, score }.pack(),
+            accounts,
+        )
+    };
+    env.process(verify(leaf(120, 0)), &[]).await.unwrap();
+    assert!(env.process(verify(leaf(999, 0)), &[]).await.is_err());
+
+    // A verified leaf hands its score back as return data
+    let blockhash = env.context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[verify(leaf(120, 0))],
+        Some(&env.context.payer.pubkey()),
+        &[&env.context.payer],
+        blockhash,
+    );
+    let simulation = env
+        .context
+        .banks_client
+        .simulate_transaction(transaction)
+        .await
+        .unwrap();
+    let return_data = simulation.simulation_details.unwrap().return_data.unwrap();
+    assert_eq!(return_data.program_id, program_id);
+    assert_eq!(u64::try_from_slice(&return_data.data).unwrap(), 120);
+
+    // Other programs verify the same leaf through the cpi helper
+    let gate = |score: CompressedScore| {
+        let mut accounts = vec![
+            AccountMeta::new_readonly(program_id, false),
+            AccountMeta::new_readonly(score_tree, false),
+            AccountMeta::new_readonly(merkle_tree, false),
+            AccountMeta::new_readonly(spl_account_compression::id(), false),
+        ];
+        accounts.extend(
+            proof
+                .iter()
+                .map(|node| AccountMeta::new_readonly(Pubkey::new_from_array(*node), false)),
+        );
+        let mut data = root.to_vec();
+        data.extend(borsh::to_vec(&score).unwrap());
+        Instruction::new_with_bytes(COMPRESSED_GATE_PROGRAM_ID, &data, accounts)
+    };
+    env.process(gate(leaf(120, 0)), &[]).await.unwrap();
+    assert!(env.process(gate(leaf(999, 0)), &[]).await.is_err());
+}
+
+#[tokio::test]
+async fn batch_update_skips_entries_that_fail_checks() {
+    let mut env = setup(Some(10)).await;
+    let program_id = env.program_id;
+    let player = env.player.insecure_clone();
+    let server = Keypair::new();
+    env.set_config(Config {
+        game_server: server.pubkey(),
+        max_increment: 100,
+        ..env.config()
+    });
+    let batch = |authority: Pubkey, increments: Vec<u64>, score_accounts: &[Pubkey]| {
+        let mut accounts = vec![
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new_readonly(season_address(&program_id), false),
+            AccountMeta::new_readonly(config_address(&program_id), false),
+            AccountMeta::new_readonly(ban_list_address(&program_id), false),
+        ];
+        accounts.extend(
+            score_accounts
+                .iter()
+                .map(|key| AccountMeta::new(*key, false)),
+        );
+        Instruction::new_with_bytes(
+            program_id,
+            &GarbageGrabInstruction::BatchUpdate {
+                game_mode: GameMode::Arcade,
+                increments,
+            }
+            .pack(),
+            accounts,
+        )
+    };
+    let own_score = score_address(&program_id, &player.pubkey());
+    let missing_score = score_address(&program_id, &Pubkey::new_unique());
+
+    let result = env
+        .process(batch(player.pubkey(), vec![5], &[own_score]), &[&player])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        custom(GarbageGrabError::Unauthorized)
+    );
+    let result = env
+        .process(batch(server.pubkey(), vec![5, 5], &[own_score]), &[&server])
+        .await;
+    assert_eq!(
+        instruction_error(result),
+        InstructionError::NotEnoughAccountKeys
+    );
+
+    // The uninitialized account and the oversized increment are skipped
+    let ix = batch(
+        server.pubkey(),
+        vec![5, 5, 500],
+        &[own_score, missing_score, own_score],
+    );
+    env.process(ix, &[&server]).await.unwrap();
+    assert_eq!(env.score().await.score, 15);
+}
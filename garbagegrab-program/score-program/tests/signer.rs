@@ -0,0 +1,230 @@
+use score_program::{
+    instruction::GarbageGrabInstruction,
+    process_instruction,
+    state::{Config, GameMode, ScoreAccount, RECENT_RUN_HASHES, STAKE_TIERS},
+};
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::SUCCESS,
+    hash::Hash,
+    program_error::ProgramError,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    pubkey::Pubkey,
+    system_program,
+};
+
+// Serve a fixed Clock sysvar to the program when running on the host
+struct ClockStubs;
+
+impl SyscallStubs for ClockStubs {
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            unix_timestamp: 1_700_000_000,
+            ..Clock::default()
+        };
+        unsafe { *(var_addr as *mut Clock) = clock };
+        SUCCESS
+    }
+}
+
+// Build an initialized score account buffer for the given player and score
+fn score_data(player: &Pubkey, score: u64, bump: u8) -> Vec<u8> {
+    let mut data = vec![0u8; ScoreAccount::LEN];
+    let state = ScoreAccount {
+        is_initialized: true,
+        player: *player,
+        score,
+        game_mode: GameMode::Arcade,
+        best_score: score,
+        season_id: 0,
+        last_update_ts: 0,
+        nonce: 0,
+        version: ScoreAccount::VERSION,
+        rewards_claimed: 0,
+        ranked_run_open: false,
+        achievements: 0,
+        streak: 0,
+        last_play_day: 0,
+        trophies: 0,
+        trophy_best: 0,
+        referrer: Pubkey::default(),
+        referral_credited: false,
+        delegate: Pubkey::default(),
+        last_update_slot: 0,
+        window_updates: 0,
+        item_boost_bps: 0,
+        continue_pending: false,
+        run_continues: 0,
+        bump,
+        week: 0,
+        week_best_score: 0,
+        recent_run_hashes: [Hash::default(); RECENT_RUN_HASHES],
+        run_hash_cursor: 0,
+    };
+    state.pack(&mut data).unwrap();
+    data
+}
+
+fn config_data(bump: u8) -> Vec<u8> {
+    let mut data = vec![0u8; Config::LEN];
+    let config = Config {
+        is_initialized: true,
+        admin: Pubkey::new_unique(),
+        max_score: 1_000_000,
+        max_increment: 1_000,
+        paused: false,
+        update_cooldown_secs: 0,
+        game_server: Pubkey::default(),
+        reward_mint: Pubkey::default(),
+        reward_per_tier: 0,
+        ranked_entry_fee: 0,
+        prize_split_bps: Config::DEFAULT_PRIZE_SPLIT_BPS,
+        boost_collection: Pubkey::default(),
+        boost_multiplier_bps: 10_000,
+        trophy_margin: 0,
+        referral_threshold: 0,
+        points_per_referral: 0,
+        pending_admin: Pubkey::default(),
+        decay_window_secs: 0,
+        decay_bps: 0,
+        throttle_window_slots: 0,
+        max_updates_per_window: 0,
+        shop_mint: Pubkey::default(),
+        continue_burn_amount: 0,
+        max_continues_per_run: 0,
+        ranked_entry_fee_usd_cents: 0,
+        sol_usd_price_feed: Pubkey::default(),
+        max_price_age_secs: 0,
+        max_price_conf_bps: 0,
+        season_length_secs: 0,
+        crank_tip_lamports: 0,
+        bump,
+        treasury_withdraw_cap_lamports: 0,
+        treasury_withdraw_epoch: 0,
+        treasury_withdrawn_lamports: 0,
+        max_score_per_slot: 0,
+        session_max_slots: 0,
+        max_score_per_second: 0,
+        bet_fee_bps: 0,
+        fee_bps: 0,
+        prize_vesting_cliff_secs: 0,
+        prize_vesting_secs: 0,
+        stake_tier_amounts: [0; STAKE_TIERS],
+        stake_tier_bps: [0; STAKE_TIERS],
+        unstake_cooldown_secs: 0,
+        stake_lock_bps: [0; 3],
+        early_unstake_penalty_bps: 0,
+        high_stakes_wager: 0,
+        high_stakes_min_level: 0,
+        closed_beta: false,
+        allowlist_root: [0; 32],
+        require_attestation: false,
+    };
+    config.pack(&mut data).unwrap();
+    data
+}
+
+fn update_score(player_signed: bool) -> (Result<(), ProgramError>, u64) {
+    set_syscall_stubs(Box::new(ClockStubs));
+    let program_id = Pubkey::new_unique();
+    let player_key = Pubkey::new_unique();
+    let system_key = system_program::id();
+    let (pda, score_bump) = Pubkey::find_program_address(
+        &[b"score", player_key.as_ref(), &GameMode::Arcade.seed()],
+        &program_id,
+    );
+    let (season_pda, _) = Pubkey::find_program_address(&[b"season"], &program_id);
+    let (config_pda, config_bump) = Pubkey::find_program_address(&[b"config"], &program_id);
+    let (ban_list_pda, _) = Pubkey::find_program_address(&[b"ban_list"], &program_id);
+
+    let mut score_lamports = 1_000_000;
+    let mut score_buf = score_data(&player_key, 10, score_bump);
+    let mut player_lamports = 1_000_000;
+    let mut player_buf = [];
+    let mut season_lamports = 0;
+    let mut season_buf = [];
+    let mut config_lamports = 1_000_000;
+    let mut config_buf = config_data(config_bump);
+    let mut ban_list_lamports = 0;
+    let mut ban_list_buf = [];
+
+    let accounts = [
+        AccountInfo::new(
+            &pda,
+            false,
+            true,
+            &mut score_lamports,
+            &mut score_buf,
+            &program_id,
+            false,
+            0,
+        ),
+        AccountInfo::new(
+            &player_key,
+            player_signed,
+            true,
+            &mut player_lamports,
+            &mut player_buf,
+            &system_key,
+            false,
+            0,
+        ),
+        AccountInfo::new(
+            &season_pda,
+            false,
+            false,
+            &mut season_lamports,
+            &mut season_buf,
+            &system_key,
+            false,
+            0,
+        ),
+        AccountInfo::new(
+            &config_pda,
+            false,
+            false,
+            &mut config_lamports,
+            &mut config_buf,
+            &program_id,
+            false,
+            0,
+        ),
+        AccountInfo::new(
+            &ban_list_pda,
+            false,
+            false,
+            &mut ban_list_lamports,
+            &mut ban_list_buf,
+            &system_key,
+            false,
+            0,
+        ),
+    ];
+
+    let instruction_data = GarbageGrabInstruction::UpdateScore {
+        game_mode: GameMode::Arcade,
+        increment: 5,
+    }
+    .pack();
+    let result = process_instruction(&program_id, &accounts, &instruction_data);
+
+    let score = ScoreAccount::unpack(&accounts[0].data.borrow())
+        .unwrap()
+        .score;
+    (result, score)
+}
+
+#[test]
+fn unsigned_update_is_rejected() {
+    let (result, score) = update_score(false);
+    assert_eq!(result, Err(ProgramError::MissingRequiredSignature));
+    assert_eq!(score, 10);
+}
+
+#[test]
+fn signed_update_is_applied() {
+    let (result, score) = update_score(true);
+    assert_eq!(result, Ok(()));
+    assert_eq!(score, 15);
+}